@@ -1,12 +1,22 @@
 // Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use crate::command_factory::CommandFactoryError::{CommandSyntax, UnrecognizedSubcommand};
+use crate::commands::ban_command::BanCommand;
+use crate::commands::ban_list_command::BanListCommand;
+use crate::commands::change_password_command::ChangePasswordCommand;
+use crate::commands::change_rate_pack_command::ChangeRatePackCommand;
 use crate::commands::commands_common::Command;
 use crate::commands::crash_command::CrashCommand;
 use crate::commands::descriptor_command::DescriptorCommand;
+use crate::commands::financials_command::FinancialsCommand;
+use crate::commands::generate_wallets_command::GenerateWalletsCommand;
+use crate::commands::log_filters_command::LogFiltersCommand;
+use crate::commands::neighborhood_command::NeighborhoodCommand;
+use crate::commands::recover_wallets_command::RecoverWalletsCommand;
 use crate::commands::setup_command::SetupCommand;
 use crate::commands::shutdown_command::ShutdownCommand;
 use crate::commands::start_command::StartCommand;
+use crate::commands::unban_command::UnbanCommand;
 
 #[derive(Debug, PartialEq)]
 pub enum CommandFactoryError {
@@ -24,17 +34,57 @@ pub struct CommandFactoryReal {}
 impl CommandFactory for CommandFactoryReal {
     fn make(&self, pieces: Vec<String>) -> Result<Box<dyn Command>, CommandFactoryError> {
         let boxed_command: Box<dyn Command> = match pieces[0].as_str() {
+            "ban" => match BanCommand::new(&pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
+            "ban-list" => match BanListCommand::new(&pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
+            "change-password" => match ChangePasswordCommand::new(&pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
+            "change-rate-pack" => match ChangeRatePackCommand::new(&pieces[..]) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
             "crash" => match CrashCommand::new(&pieces[..]) {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
             },
             "descriptor" => Box::new(DescriptorCommand::new()),
+            "financials" => match FinancialsCommand::new(&pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
+            "generate-wallets" => match GenerateWalletsCommand::new(&pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
+            "log-filters" => match LogFiltersCommand::new(&pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
+            "neighborhood" => match NeighborhoodCommand::new(&pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
+            "recover-wallets" => match RecoverWalletsCommand::new(&pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
             "setup" => match SetupCommand::new(pieces) {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
             },
             "shutdown" => Box::new(ShutdownCommand::new()),
             "start" => Box::new(StartCommand::new()),
+            "unban" => match UnbanCommand::new(&pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
             unrecognized => return Err(UnrecognizedSubcommand(unrecognized.to_string())),
         };
         Ok(boxed_command)
@@ -0,0 +1,221 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, SubCommand};
+use masq_lib::messages::{UiBanRequest, UiBanResponse, NODE_NOT_RUNNING_ERROR};
+use masq_lib::shared_schema::common_validators::validate_ethereum_address;
+use std::fmt::Debug;
+
+const WALLET_HELP: &str =
+    "The Ethereum address of the wallet to ban (e.g. 0x0123456789012345678901234567890123456789)";
+const REASON_HELP: &str =
+    "Why the wallet is being banned; this is shown beside the ban in the output of ban-list.";
+
+#[derive(Debug, PartialEq)]
+pub struct BanCommand {
+    wallet: String,
+    reason: String,
+}
+
+pub fn ban_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("ban")
+        .about("Bans a wallet: the running MASQNode will no longer route or exit data for it. The ban \
+                stands until it is lifted with unban, even if the wallet pays its debts. Only valid if \
+                Node is already running.")
+        .arg(
+            Arg::with_name("wallet")
+                .help(WALLET_HELP)
+                .index(1)
+                .required(true)
+                .validator(validate_ethereum_address),
+        )
+        .arg(
+            Arg::with_name("reason")
+                .help(REASON_HELP)
+                .long("reason")
+                .value_name("REASON")
+                .takes_value(true)
+                .default_value("banned by operator"),
+        )
+}
+
+impl Command for BanCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiBanRequest {
+            wallet: self.wallet.clone(),
+            reason: self.reason.clone(),
+        };
+        let output: Result<UiBanResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(_) => {
+                writeln!(context.stdout(), "Wallet {} banned", self.wallet)
+                    .expect("writeln! failed");
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_NOT_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is not running; therefore it cannot ban wallets."
+                )
+                .expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Ban failed: {:?}", e).expect("writeln! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl BanCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match ban_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        Ok(Self {
+            wallet: matches
+                .value_of("wallet")
+                .expect("wallet parameter is not properly required")
+                .to_string(),
+            reason: matches
+                .value_of("reason")
+                .expect("reason parameter is not properly defaulted")
+                .to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    const WALLET: &str = "0x0123456789012345678901234567890123456789";
+
+    fn pieces(args: &[&str]) -> Vec<String> {
+        vec!["ban"]
+            .into_iter()
+            .chain(args.iter().copied())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context = CommandContextMock::new().transact_result(Ok(UiBanResponse {}.tmb(0)));
+        let subject = factory.make(pieces(&[WALLET])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn ban_command_rejects_malformed_wallet() {
+        let result = BanCommand::new(&pieces(&["0xbooga"]));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("0xbooga"), "{}", msg);
+    }
+
+    #[test]
+    fn ban_command_defaults_reason() {
+        let result = BanCommand::new(&pieces(&[WALLET])).unwrap();
+
+        assert_eq!(
+            result,
+            BanCommand {
+                wallet: WALLET.to_string(),
+                reason: "banned by operator".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn ban_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiBanResponse {}.tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BanCommand::new(&pieces(&[WALLET, "--reason", "spammer"])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            *transact_params_arc.lock().unwrap(),
+            vec![(
+                UiBanRequest {
+                    wallet: WALLET.to_string(),
+                    reason: "spammer".to_string(),
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            format!("Wallet {} banned\n", WALLET)
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn ban_command_doesnt_work_if_node_is_not_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_NOT_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BanCommand::new(&pieces(&[WALLET])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_NOT_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is not running; therefore it cannot ban wallets.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn ban_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BanCommand::new(&pieces(&[WALLET])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Ban failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, SubCommand};
+use masq_lib::messages::{
+    UiBanListRequest, UiBanListResponse, UiBannedWallet, UiPardonedWallet, NODE_NOT_RUNNING_ERROR,
+};
+use std::fmt::Debug;
+use std::io::Write;
+
+const JSON_HELP: &str =
+    "Display the bans and pardons as JSON, suitable for consumption by scripts, instead of as tables.";
+
+#[derive(Debug, PartialEq)]
+pub struct BanListCommand {
+    json: bool,
+}
+
+pub fn ban_list_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("ban-list")
+        .about(
+            "Displays the wallets the running MASQNode has banned, and the wallets that have been \
+                pardoned with unban. Only valid if Node is already running.",
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .takes_value(false)
+                .help(JSON_HELP),
+        )
+}
+
+impl Command for BanListCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let output: Result<UiBanListResponse, CommandError> = transaction(
+            UiBanListRequest {},
+            context,
+            STANDARD_COMMAND_TIMEOUT_MILLIS,
+        );
+        match output {
+            Ok(response) => {
+                if self.json {
+                    Self::dump_json(&response, context.stdout());
+                } else {
+                    Self::dump_bans(&response.bans, context.stdout());
+                    Self::dump_pardons(&response.pardons, context.stdout());
+                }
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_NOT_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is not running; therefore its bans cannot be displayed."
+                )
+                .expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Ban list retrieval failed: {:?}", e)
+                    .expect("writeln! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl BanListCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match ban_list_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        Ok(Self {
+            json: matches.is_present("json"),
+        })
+    }
+
+    fn dump_json(response: &UiBanListResponse, stdout: &mut dyn Write) {
+        let json = serde_json::to_string_pretty(response).expect("Couldn't serialize ban list");
+        writeln!(stdout, "{}", json).expect("writeln! failed");
+    }
+
+    fn dump_bans(bans: &[UiBannedWallet], stdout: &mut dyn Write) {
+        writeln!(stdout, "Bans:").expect("writeln! failed");
+        if bans.is_empty() {
+            writeln!(stdout, "No bans found\n").expect("writeln! failed");
+            return;
+        }
+        writeln!(
+            stdout,
+            "{:44}{:>21}   {:11}REASON",
+            "WALLET", "BANNED AT (time_t)", "BANNED BY"
+        )
+        .expect("writeln! failed");
+        bans.iter().for_each(|ban| {
+            writeln!(
+                stdout,
+                "{:44}{:>21}   {:11}{}",
+                ban.wallet,
+                ban.banned_timestamp,
+                if ban.manual { "operator" } else { "Node" },
+                ban.reason
+            )
+            .expect("writeln! failed")
+        });
+        writeln!(stdout).expect("writeln! failed");
+    }
+
+    fn dump_pardons(pardons: &[UiPardonedWallet], stdout: &mut dyn Write) {
+        writeln!(stdout, "Pardons:").expect("writeln! failed");
+        if pardons.is_empty() {
+            writeln!(stdout, "No pardons found\n").expect("writeln! failed");
+            return;
+        }
+        writeln!(stdout, "{:44}{:>21}", "WALLET", "PARDONED AT (time_t)").expect("writeln! failed");
+        pardons.iter().for_each(|pardon| {
+            writeln!(
+                stdout,
+                "{:44}{:>21}",
+                pardon.wallet, pardon.pardoned_timestamp
+            )
+            .expect("writeln! failed")
+        });
+        writeln!(stdout).expect("writeln! failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    fn make_response() -> UiBanListResponse {
+        UiBanListResponse {
+            bans: vec![
+                UiBannedWallet {
+                    wallet: "0x1111111111111111111111111111111111111111".to_string(),
+                    reason: "delinquency".to_string(),
+                    banned_timestamp: 1_600_000_000,
+                    manual: false,
+                },
+                UiBannedWallet {
+                    wallet: "0x2222222222222222222222222222222222222222".to_string(),
+                    reason: "spammer".to_string(),
+                    banned_timestamp: 1_600_000_001,
+                    manual: true,
+                },
+            ],
+            pardons: vec![UiPardonedWallet {
+                wallet: "0x3333333333333333333333333333333333333333".to_string(),
+                pardoned_timestamp: 1_600_000_002,
+            }],
+        }
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(0)));
+        let subject = factory.make(vec!["ban-list".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn ban_list_command_happy_path_with_tables() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(make_response().tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BanListCommand::new(&["ban-list".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            *transact_params_arc.lock().unwrap(),
+            vec![(UiBanListRequest {}.tmb(0), STANDARD_COMMAND_TIMEOUT_MILLIS)]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Bans:\n\
+             WALLET                                         BANNED AT (time_t)   BANNED BY  REASON\n\
+             0x1111111111111111111111111111111111111111             1600000000   Node       delinquency\n\
+             0x2222222222222222222222222222222222222222             1600000001   operator   spammer\n\
+             \n\
+             Pardons:\n\
+             WALLET                                       PARDONED AT (time_t)\n\
+             0x3333333333333333333333333333333333333333             1600000002\n\
+             \n"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn ban_list_command_handles_empty_lists() {
+        let mut context = CommandContextMock::new().transact_result(Ok(UiBanListResponse {
+            bans: vec![],
+            pardons: vec![],
+        }
+        .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = BanListCommand::new(&["ban-list".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Bans:\nNo bans found\n\nPardons:\nNo pardons found\n\n"
+        );
+    }
+
+    #[test]
+    fn ban_list_command_happy_path_with_json() {
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = BanListCommand::new(&["ban-list".to_string(), "--json".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let stdout = stdout_arc.lock().unwrap().get_string();
+        let response: UiBanListResponse = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(response, make_response());
+    }
+
+    #[test]
+    fn ban_list_command_doesnt_work_if_node_is_not_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_NOT_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BanListCommand::new(&["ban-list".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_NOT_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is not running; therefore its bans cannot be displayed.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn ban_list_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BanListCommand::new(&["ban-list".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Ban list retrieval failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
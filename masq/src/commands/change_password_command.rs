@@ -0,0 +1,266 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, SubCommand};
+use masq_lib::messages::{
+    UiChangePasswordRequest, UiChangePasswordResponse, DB_PASSWORD_ERROR,
+    NODE_ALREADY_RUNNING_ERROR,
+};
+use std::fmt::Debug;
+
+const OLD_PASSWORD_HELP: &str =
+    "The password that currently encrypts the wallet information in the Node's database. Leave it \
+     out only if the database has never had a password.";
+const NEW_PASSWORD_HELP: &str =
+    "The password that will encrypt the wallet information in the Node's database from now on.";
+
+#[derive(Debug, PartialEq)]
+pub struct ChangePasswordCommand {
+    old_password_opt: Option<String>,
+    new_password: String,
+}
+
+pub fn change_password_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("change-password")
+        .about(
+            "Changes the password that encrypts the wallet information in the database the Node \
+             will use, re-encrypting everything under the new one. Either all of it is \
+             re-encrypted or none of it is. Only valid if Node is not already running.",
+        )
+        .arg(
+            Arg::with_name("old-password")
+                .help(OLD_PASSWORD_HELP)
+                .long("old-password")
+                .value_name("OLD-PASSWORD")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("new-password")
+                .help(NEW_PASSWORD_HELP)
+                .long("new-password")
+                .value_name("NEW-PASSWORD")
+                .required(true)
+                .takes_value(true),
+        )
+}
+
+impl Command for ChangePasswordCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiChangePasswordRequest {
+            old_password_opt: self.old_password_opt.clone(),
+            new_password: self.new_password.clone(),
+        };
+        let output: Result<UiChangePasswordResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(_) => {
+                writeln!(context.stdout(), "Database password has been changed.")
+                    .expect("writeln! failed");
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_ALREADY_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is already running; the database password can only be changed before it starts."
+                )
+                .expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(Payload(code, message)) if code == DB_PASSWORD_ERROR => {
+                writeln!(context.stderr(), "{}", message).expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Password change failed: {:?}", e)
+                    .expect("writeln! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl ChangePasswordCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match change_password_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        Ok(Self {
+            old_password_opt: matches.value_of("old-password").map(|s| s.to_string()),
+            new_password: matches
+                .value_of("new-password")
+                .expect("new-password parameter is not properly required")
+                .to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    fn pieces(args: &[&str]) -> Vec<String> {
+        vec!["change-password"]
+            .into_iter()
+            .chain(args.iter().copied())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context =
+            CommandContextMock::new().transact_result(Ok(UiChangePasswordResponse {}.tmb(0)));
+        let subject = factory
+            .make(pieces(&["--new-password", "password"]))
+            .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn change_password_command_requires_new_password() {
+        let result = ChangePasswordCommand::new(&pieces(&["--old-password", "password"]));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("--new-password"), "{}", msg);
+    }
+
+    #[test]
+    fn change_password_command_does_not_require_old_password() {
+        let result = ChangePasswordCommand::new(&pieces(&["--new-password", "password"])).unwrap();
+
+        assert_eq!(
+            result,
+            ChangePasswordCommand {
+                old_password_opt: None,
+                new_password: "password".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn change_password_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiChangePasswordResponse {}.tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = ChangePasswordCommand::new(&pieces(&[
+            "--old-password",
+            "old password",
+            "--new-password",
+            "new password",
+        ]))
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            *transact_params_arc.lock().unwrap(),
+            vec![(
+                UiChangePasswordRequest {
+                    old_password_opt: Some("old password".to_string()),
+                    new_password: "new password".to_string(),
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Database password has been changed.\n"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn change_password_command_doesnt_work_if_node_is_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_ALREADY_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = ChangePasswordCommand::new(&pieces(&["--new-password", "password"])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_ALREADY_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is already running; the database password can only be changed before it starts.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn change_password_command_reports_rejected_change() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ContextError::PayloadError(
+                DB_PASSWORD_ERROR,
+                "Old db password is incorrect".to_string(),
+            )));
+        let stderr_arc = context.stderr_arc();
+        let subject = ChangePasswordCommand::new(&pieces(&[
+            "--old-password",
+            "wrong password",
+            "--new-password",
+            "password",
+        ]))
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                DB_PASSWORD_ERROR,
+                "Old db password is incorrect".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Old db password is incorrect\n"
+        );
+    }
+
+    #[test]
+    fn change_password_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = ChangePasswordCommand::new(&pieces(&["--new-password", "password"])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Password change failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, SubCommand};
+use masq_lib::messages::{
+    UiChangeRatePackRequest, UiChangeRatePackResponse, NODE_NOT_RUNNING_ERROR,
+};
+use masq_lib::shared_schema::common_validators::validate_rate_pack;
+use std::fmt::Debug;
+
+#[derive(Debug, PartialEq)]
+pub struct ChangeRatePackCommand {
+    routing_byte_rate: u64,
+    routing_service_rate: u64,
+    exit_byte_rate: u64,
+    exit_service_rate: u64,
+}
+
+pub fn change_rate_pack_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("change-rate-pack")
+        .about("Changes the rates the running MASQNode advertises to other Nodes and charges them for its services. Only valid if Node is already running.")
+        .arg(Arg::with_name("rate-pack")
+            .help("The routing byte rate, routing service rate, exit byte rate and exit service rate, separated by '|' (e.g. '100|10000|101|10001')")
+            .index(1)
+            .required(true)
+            .validator(validate_rate_pack)
+        )
+}
+
+impl Command for ChangeRatePackCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiChangeRatePackRequest {
+            routing_byte_rate: self.routing_byte_rate,
+            routing_service_rate: self.routing_service_rate,
+            exit_byte_rate: self.exit_byte_rate,
+            exit_service_rate: self.exit_service_rate,
+        };
+        let output: Result<UiChangeRatePackResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(_) => {
+                writeln!(context.stdout(), "Rate pack changed").expect("write! failed");
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_NOT_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is not running; therefore its rate pack cannot be changed."
+                )
+                .expect("write! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Rate pack change failed: {:?}", e)
+                    .expect("write! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl ChangeRatePackCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match change_rate_pack_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        let rates = matches
+            .value_of("rate-pack")
+            .expect("rate-pack parameter is not properly required")
+            .split('|')
+            .map(|rate| {
+                rate.parse::<u64>()
+                    .expect("rate-pack parameter is not properly validated")
+            })
+            .collect::<Vec<u64>>();
+        Ok(Self {
+            routing_byte_rate: rates[0],
+            routing_service_rate: rates[1],
+            exit_byte_rate: rates[2],
+            exit_service_rate: rates[3],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    fn pieces(rate_pack: &str) -> Vec<String> {
+        vec!["change-rate-pack".to_string(), rate_pack.to_string()]
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context =
+            CommandContextMock::new().transact_result(Ok(UiChangeRatePackResponse {}.tmb(0)));
+        let subject = factory.make(pieces("1|2|3|4")).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn command_rejects_malformed_rate_pack() {
+        let result = ChangeRatePackCommand::new(&pieces("1|2|booga"));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("1|2|booga"), "{}", msg);
+    }
+
+    #[test]
+    fn doesnt_work_if_node_is_not_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_NOT_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = ChangeRatePackCommand::new(&pieces("1|2|3|4")).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_NOT_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is not running; therefore its rate pack cannot be changed.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn change_rate_pack_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiChangeRatePackResponse {}.tmb(42)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = ChangeRatePackCommand::new(&pieces("100|10000|101|10001")).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiChangeRatePackRequest {
+                    routing_byte_rate: 100,
+                    routing_service_rate: 10000,
+                    exit_byte_rate: 101,
+                    exit_service_rate: 10001,
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Rate pack changed\n"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn change_rate_pack_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = ChangeRatePackCommand::new(&pieces("1|2|3|4")).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Rate pack change failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
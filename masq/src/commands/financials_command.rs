@@ -0,0 +1,441 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{value_t, App, Arg, SubCommand};
+use lazy_static::lazy_static;
+use masq_lib::messages::{
+    UiFinancialsRequest, UiFinancialsResponse, UiPayableAccount, UiReceivableAccount,
+    NODE_NOT_RUNNING_ERROR,
+};
+use std::fmt::Debug;
+use std::io::Write;
+
+lazy_static! {
+    static ref MAXIMUM_AGE_DEFAULT: String = format!("{}", u64::MAX);
+}
+
+const PAYABLE_MINIMUM_AMOUNT_HELP: &str =
+    "Payable accounts with balances smaller than this many gwei will be left out of the list of \
+     payables, although they will still contribute to the payable total.";
+const PAYABLE_MAXIMUM_AGE_HELP: &str =
+    "Payable accounts whose most recent payment is older than this many seconds will be left out \
+     of the list of payables, although they will still contribute to the payable total.";
+const RECEIVABLE_MINIMUM_AMOUNT_HELP: &str =
+    "Receivable accounts with balances smaller than this many gwei will be left out of the list \
+     of receivables, although they will still contribute to the receivable total.";
+const RECEIVABLE_MAXIMUM_AGE_HELP: &str =
+    "Receivable accounts whose most recent payment is older than this many seconds will be left \
+     out of the list of receivables, although they will still contribute to the receivable total.";
+const JSON_HELP: &str =
+    "Display the financial report as JSON, suitable for consumption by scripts, instead of as tables.";
+
+#[derive(Debug, PartialEq)]
+pub struct FinancialsCommand {
+    payable_minimum_amount: u64,
+    payable_maximum_age: u64,
+    receivable_minimum_amount: u64,
+    receivable_maximum_age: u64,
+    json: bool,
+}
+
+pub fn financials_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("financials")
+        .about("Displays financial statistics of this Node. Only valid if Node is already running.")
+        .arg(u64_arg(
+            "payable-minimum-amount",
+            "0",
+            PAYABLE_MINIMUM_AMOUNT_HELP,
+        ))
+        .arg(u64_arg(
+            "payable-maximum-age",
+            MAXIMUM_AGE_DEFAULT.as_str(),
+            PAYABLE_MAXIMUM_AGE_HELP,
+        ))
+        .arg(u64_arg(
+            "receivable-minimum-amount",
+            "0",
+            RECEIVABLE_MINIMUM_AMOUNT_HELP,
+        ))
+        .arg(u64_arg(
+            "receivable-maximum-age",
+            MAXIMUM_AGE_DEFAULT.as_str(),
+            RECEIVABLE_MAXIMUM_AGE_HELP,
+        ))
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .takes_value(false)
+                .help(JSON_HELP),
+        )
+}
+
+fn u64_arg(
+    name: &'static str,
+    default_value: &'static str,
+    help: &'static str,
+) -> Arg<'static, 'static> {
+    Arg::with_name(name)
+        .long(name)
+        .value_name(name)
+        .takes_value(true)
+        .default_value(default_value)
+        .validator(validate_u64)
+        .help(help)
+}
+
+fn validate_u64(value: String) -> Result<(), String> {
+    match str::parse::<u64>(&value) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(value),
+    }
+}
+
+impl Command for FinancialsCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiFinancialsRequest {
+            payable_minimum_amount: self.payable_minimum_amount,
+            payable_maximum_age: self.payable_maximum_age,
+            receivable_minimum_amount: self.receivable_minimum_amount,
+            receivable_maximum_age: self.receivable_maximum_age,
+        };
+        let output: Result<UiFinancialsResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                if self.json {
+                    Self::dump_json(&response, context.stdout());
+                } else {
+                    Self::dump_tables(&response, context.stdout());
+                }
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_NOT_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is not running; therefore financial statistics cannot be displayed."
+                )
+                .expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Financials retrieval failed: {:?}", e)
+                    .expect("writeln! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl FinancialsCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match financials_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        Ok(Self {
+            payable_minimum_amount: value_t!(matches, "payable-minimum-amount", u64)
+                .expect("payable-minimum-amount is not properly defaulted"),
+            payable_maximum_age: value_t!(matches, "payable-maximum-age", u64)
+                .expect("payable-maximum-age is not properly defaulted"),
+            receivable_minimum_amount: value_t!(matches, "receivable-minimum-amount", u64)
+                .expect("receivable-minimum-amount is not properly defaulted"),
+            receivable_maximum_age: value_t!(matches, "receivable-maximum-age", u64)
+                .expect("receivable-maximum-age is not properly defaulted"),
+            json: matches.is_present("json"),
+        })
+    }
+
+    fn dump_json(response: &UiFinancialsResponse, stdout: &mut dyn Write) {
+        let json = serde_json::to_string_pretty(response).expect("Couldn't serialize financials");
+        writeln!(stdout, "{}", json).expect("writeln! failed");
+    }
+
+    fn dump_tables(response: &UiFinancialsResponse, stdout: &mut dyn Write) {
+        writeln!(stdout, "Financial status totals in gwei\n").expect("writeln! failed");
+        writeln!(stdout, "{:12}{}", "Payable:", response.total_payable).expect("writeln! failed");
+        writeln!(stdout, "{:12}{}", "Receivable:", response.total_receivable)
+            .expect("writeln! failed");
+        writeln!(stdout).expect("writeln! failed");
+        Self::dump_payables(&response.payables, stdout);
+        Self::dump_receivables(&response.receivables, stdout);
+    }
+
+    fn dump_payables(payables: &[UiPayableAccount], stdout: &mut dyn Write) {
+        writeln!(stdout, "Payables:").expect("writeln! failed");
+        if payables.is_empty() {
+            writeln!(stdout, "No payables found\n").expect("writeln! failed");
+            return;
+        }
+        writeln!(
+            stdout,
+            "{:44}{:>21}{:>21}   PENDING TRANSACTION",
+            "WALLET", "AGE (s)", "AMOUNT (gwei)"
+        )
+        .expect("writeln! failed");
+        payables.iter().for_each(|account| {
+            writeln!(
+                stdout,
+                "{:44}{:>21}{:>21}   {}",
+                account.wallet,
+                account.age,
+                account.amount,
+                account.pending_transaction.as_deref().unwrap_or("")
+            )
+            .expect("writeln! failed")
+        });
+        writeln!(stdout).expect("writeln! failed");
+    }
+
+    fn dump_receivables(receivables: &[UiReceivableAccount], stdout: &mut dyn Write) {
+        writeln!(stdout, "Receivables:").expect("writeln! failed");
+        if receivables.is_empty() {
+            writeln!(stdout, "No receivables found\n").expect("writeln! failed");
+            return;
+        }
+        writeln!(
+            stdout,
+            "{:44}{:>21}{:>21}",
+            "WALLET", "AGE (s)", "AMOUNT (gwei)"
+        )
+        .expect("writeln! failed");
+        receivables.iter().for_each(|account| {
+            writeln!(
+                stdout,
+                "{:44}{:>21}{:>21}",
+                account.wallet, account.age, account.amount
+            )
+            .expect("writeln! failed")
+        });
+        writeln!(stdout).expect("writeln! failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    fn make_response() -> UiFinancialsResponse {
+        UiFinancialsResponse {
+            payables: vec![
+                UiPayableAccount {
+                    wallet: "0x1111111111111111111111111111111111111111".to_string(),
+                    age: 1234,
+                    amount: 5678,
+                    pending_transaction: Some("0xABCD".to_string()),
+                },
+                UiPayableAccount {
+                    wallet: "0x2222222222222222222222222222222222222222".to_string(),
+                    age: 2345,
+                    amount: 6789,
+                    pending_transaction: None,
+                },
+            ],
+            total_payable: 12467,
+            receivables: vec![UiReceivableAccount {
+                wallet: "0x3333333333333333333333333333333333333333".to_string(),
+                age: 3456,
+                amount: 7890,
+            }],
+            total_receivable: 7890,
+        }
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(0)));
+        let subject = factory.make(vec!["financials".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn financials_command_defaults_parameters() {
+        let result = FinancialsCommand::new(&["financials".to_string()]).unwrap();
+
+        assert_eq!(
+            result,
+            FinancialsCommand {
+                payable_minimum_amount: 0,
+                payable_maximum_age: u64::MAX,
+                receivable_minimum_amount: 0,
+                receivable_maximum_age: u64::MAX,
+                json: false,
+            }
+        );
+    }
+
+    #[test]
+    fn financials_command_rejects_non_numeric_parameter() {
+        let result = FinancialsCommand::new(&[
+            "financials".to_string(),
+            "--payable-minimum-amount".to_string(),
+            "booga".to_string(),
+        ])
+        .err()
+        .unwrap();
+
+        assert!(result.contains("booga"), "{}", result);
+    }
+
+    #[test]
+    fn financials_command_happy_path_with_tables() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(make_response().tmb(31)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = FinancialsCommand::new(&[
+            "financials".to_string(),
+            "--payable-minimum-amount".to_string(),
+            "1000".to_string(),
+            "--payable-maximum-age".to_string(),
+            "2000".to_string(),
+            "--receivable-minimum-amount".to_string(),
+            "3000".to_string(),
+            "--receivable-maximum-age".to_string(),
+            "4000".to_string(),
+        ])
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiFinancialsRequest {
+                    payable_minimum_amount: 1000,
+                    payable_maximum_age: 2000,
+                    receivable_minimum_amount: 3000,
+                    receivable_maximum_age: 4000,
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq! (stdout_arc.lock().unwrap().get_string(),
+"Financial status totals in gwei\n\
+\n\
+Payable:    12467\n\
+Receivable: 7890\n\
+\n\
+Payables:\n\
+WALLET                                                    AGE (s)        AMOUNT (gwei)   PENDING TRANSACTION\n\
+0x1111111111111111111111111111111111111111                   1234                 5678   0xABCD\n\
+0x2222222222222222222222222222222222222222                   2345                 6789   \n\
+\n\
+Receivables:\n\
+WALLET                                                    AGE (s)        AMOUNT (gwei)\n\
+0x3333333333333333333333333333333333333333                   3456                 7890\n\
+\n");
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn financials_command_handles_empty_account_lists() {
+        let mut context = CommandContextMock::new().transact_result(Ok(UiFinancialsResponse {
+            payables: vec![],
+            total_payable: 1234,
+            receivables: vec![],
+            total_receivable: 2345,
+        }
+        .tmb(31)));
+        let stdout_arc = context.stdout_arc();
+        let subject = FinancialsCommand::new(&["financials".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Financial status totals in gwei\n\
+\n\
+Payable:    1234\n\
+Receivable: 2345\n\
+\n\
+Payables:\n\
+No payables found\n\
+\n\
+Receivables:\n\
+No receivables found\n\
+\n"
+        );
+    }
+
+    #[test]
+    fn financials_command_happy_path_with_json() {
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(31)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject =
+            FinancialsCommand::new(&["financials".to_string(), "--json".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let stdout = stdout_arc.lock().unwrap().get_string();
+        let actual: UiFinancialsResponse = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(actual, make_response());
+        assert!(stdout.contains("\"totalPayable\": 12467"), "{}", stdout);
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn financials_command_doesnt_work_if_node_is_not_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_NOT_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = FinancialsCommand::new(&["financials".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_NOT_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is not running; therefore financial statistics cannot be displayed.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn financials_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = FinancialsCommand::new(&["financials".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Financials retrieval failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
@@ -0,0 +1,345 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{value_t, App, Arg, SubCommand};
+use masq_lib::constants::{DEFAULT_CONSUMING_DERIVATION_PATH, DEFAULT_EARNING_DERIVATION_PATH};
+use masq_lib::messages::{
+    UiGenerateWalletsRequest, UiGenerateWalletsResponse, NODE_ALREADY_RUNNING_ERROR,
+};
+use masq_lib::shared_schema::common_validators::validate_derivation_path;
+use std::fmt::Debug;
+
+const DB_PASSWORD_HELP: &str =
+    "The password that will be used to encrypt the wallet information in the Node's database.";
+const WORD_COUNT_HELP: &str = "The number of words in the mnemonic phrase to generate.";
+const LANGUAGE_HELP: &str = "The language of the words in the mnemonic phrase to generate.";
+const PASSPHRASE_HELP: &str =
+    "An optional additional word (it can be any word) that the wallet-recovery process should \
+     require at the end of the mnemonic phrase. Without it, the wallets cannot be recovered.";
+const CONSUMING_PATH_HELP: &str =
+    "The BIP32 derivation path for the wallet from which your Node will pay for services.";
+const EARNING_PATH_HELP: &str =
+    "The BIP32 derivation path for the wallet into which your Node will be paid for services.";
+
+#[derive(Debug, PartialEq)]
+pub struct GenerateWalletsCommand {
+    db_password: String,
+    word_count: usize,
+    language: String,
+    passphrase_opt: Option<String>,
+    consuming_path: String,
+    earning_path: String,
+}
+
+pub fn generate_wallets_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("generate-wallets")
+        .about("Generates a pair of wallets from a new mnemonic phrase and stores them, encrypted, \
+                in the database the Node will use. Only valid if Node is not already running and has \
+                no wallets yet.")
+        .arg(
+            Arg::with_name("db-password")
+                .help(DB_PASSWORD_HELP)
+                .long("db-password")
+                .value_name("DB-PASSWORD")
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("word-count")
+                .help(WORD_COUNT_HELP)
+                .long("word-count")
+                .value_name("WORD-COUNT")
+                .takes_value(true)
+                .possible_values(&["12", "15", "18", "21", "24"])
+                .default_value("24"),
+        )
+        .arg(
+            Arg::with_name("language")
+                .help(LANGUAGE_HELP)
+                .long("language")
+                .value_name("LANGUAGE")
+                .takes_value(true)
+                .default_value("English"),
+        )
+        .arg(
+            Arg::with_name("passphrase")
+                .help(PASSPHRASE_HELP)
+                .long("passphrase")
+                .value_name("PASSPHRASE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("consuming-path")
+                .help(CONSUMING_PATH_HELP)
+                .long("consuming-path")
+                .value_name("CONSUMING-PATH")
+                .takes_value(true)
+                .default_value(DEFAULT_CONSUMING_DERIVATION_PATH)
+                .validator(validate_derivation_path),
+        )
+        .arg(
+            Arg::with_name("earning-path")
+                .help(EARNING_PATH_HELP)
+                .long("earning-path")
+                .value_name("EARNING-PATH")
+                .takes_value(true)
+                .default_value(DEFAULT_EARNING_DERIVATION_PATH)
+                .validator(validate_derivation_path),
+        )
+}
+
+impl Command for GenerateWalletsCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiGenerateWalletsRequest {
+            db_password: self.db_password.clone(),
+            mnemonic_phrase_size: self.word_count,
+            mnemonic_phrase_language: self.language.clone(),
+            mnemonic_passphrase_opt: self.passphrase_opt.clone(),
+            consuming_derivation_path: self.consuming_path.clone(),
+            earning_derivation_path: self.earning_path.clone(),
+        };
+        let output: Result<UiGenerateWalletsResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                writeln!(
+                    context.stdout(),
+                    "Copy this phrase down and keep it safe; you'll need it to restore your wallets:\n\
+                     '{}'\n\
+                     Address of consuming wallet: {}\n\
+                     Address of   earning wallet: {}",
+                    response.mnemonic_phrase.join(" "),
+                    response.consuming_wallet_address,
+                    response.earning_wallet_address
+                )
+                .expect("writeln! failed");
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_ALREADY_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is already running; wallets can only be generated before it starts."
+                )
+                .expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Wallet generation failed: {:?}", e)
+                    .expect("writeln! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl GenerateWalletsCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match generate_wallets_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        Ok(Self {
+            db_password: matches
+                .value_of("db-password")
+                .expect("db-password parameter is not properly required")
+                .to_string(),
+            word_count: value_t!(matches, "word-count", usize)
+                .expect("word-count parameter is not properly value-restricted"),
+            language: matches
+                .value_of("language")
+                .expect("language parameter is not properly defaulted")
+                .to_string(),
+            passphrase_opt: matches.value_of("passphrase").map(|s| s.to_string()),
+            consuming_path: matches
+                .value_of("consuming-path")
+                .expect("consuming-path parameter is not properly defaulted")
+                .to_string(),
+            earning_path: matches
+                .value_of("earning-path")
+                .expect("earning-path parameter is not properly defaulted")
+                .to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    fn pieces(args: &[&str]) -> Vec<String> {
+        vec!["generate-wallets"]
+            .into_iter()
+            .chain(args.iter().copied())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn make_response() -> UiGenerateWalletsResponse {
+        UiGenerateWalletsResponse {
+            mnemonic_phrase: vec![
+                "taxation".to_string(),
+                "is".to_string(),
+                "theft".to_string(),
+            ],
+            consuming_wallet_address: "0x1111111111111111111111111111111111111111".to_string(),
+            earning_wallet_address: "0x2222222222222222222222222222222222222222".to_string(),
+        }
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(0)));
+        let subject = factory
+            .make(pieces(&["--db-password", "password"]))
+            .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn generate_wallets_command_requires_db_password() {
+        let result = GenerateWalletsCommand::new(&pieces(&[]));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("--db-password"), "{}", msg);
+    }
+
+    #[test]
+    fn generate_wallets_command_rejects_weak_derivation_path() {
+        let result = GenerateWalletsCommand::new(&pieces(&[
+            "--db-password",
+            "password",
+            "--earning-path",
+            "m/44/60/0/0/1",
+        ]));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("m/44/60/0/0/1 may be too weak"), "{}", msg);
+    }
+
+    #[test]
+    fn generate_wallets_command_defaults_parameters() {
+        let result = GenerateWalletsCommand::new(&pieces(&["--db-password", "password"])).unwrap();
+
+        assert_eq!(
+            result,
+            GenerateWalletsCommand {
+                db_password: "password".to_string(),
+                word_count: 24,
+                language: "English".to_string(),
+                passphrase_opt: None,
+                consuming_path: DEFAULT_CONSUMING_DERIVATION_PATH.to_string(),
+                earning_path: DEFAULT_EARNING_DERIVATION_PATH.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn generate_wallets_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(make_response().tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = GenerateWalletsCommand::new(&pieces(&[
+            "--db-password",
+            "password",
+            "--word-count",
+            "12",
+            "--language",
+            "Español",
+            "--passphrase",
+            "booga",
+            "--consuming-path",
+            "m/44'/60'/0'/100/0/200",
+            "--earning-path",
+            "m/44'/60'/0'/100/0/201",
+        ]))
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            *transact_params_arc.lock().unwrap(),
+            vec![(
+                UiGenerateWalletsRequest {
+                    db_password: "password".to_string(),
+                    mnemonic_phrase_size: 12,
+                    mnemonic_phrase_language: "Español".to_string(),
+                    mnemonic_passphrase_opt: Some("booga".to_string()),
+                    consuming_derivation_path: "m/44'/60'/0'/100/0/200".to_string(),
+                    earning_derivation_path: "m/44'/60'/0'/100/0/201".to_string(),
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Copy this phrase down and keep it safe; you'll need it to restore your wallets:\n\
+             'taxation is theft'\n\
+             Address of consuming wallet: 0x1111111111111111111111111111111111111111\n\
+             Address of   earning wallet: 0x2222222222222222222222222222222222222222\n"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn generate_wallets_command_doesnt_work_if_node_is_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_ALREADY_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = GenerateWalletsCommand::new(&pieces(&["--db-password", "password"])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_ALREADY_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is already running; wallets can only be generated before it starts.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn generate_wallets_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = GenerateWalletsCommand::new(&pieces(&["--db-password", "password"])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Wallet generation failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, SubCommand};
+use masq_lib::messages::{
+    UiLogFilter, UiLogFiltersRequest, UiLogFiltersResponse, LOG_FILTERS_ERROR,
+    NODE_NOT_RUNNING_ERROR,
+};
+use masq_lib::shared_schema::common_validators::validate_log_filters;
+use masq_lib::shared_schema::LOG_LEVELS;
+use std::fmt::Debug;
+
+const LOG_LEVEL_HELP: &str =
+    "The new minimum severity of the logs the running Node should write, for every component without a \
+     filter of its own.";
+const FILTERS_HELP: &str =
+    "The new per-component log levels, replacing the old ones: component names followed by '=' and a level, \
+     separated by commas (but no spaces). For example: Neighborhood=debug,Hopper=warn";
+const CLEAR_HELP: &str =
+    "Removes every per-component log level, leaving only --log-level in force.";
+
+#[derive(Debug, PartialEq)]
+pub struct LogFiltersCommand {
+    log_level_opt: Option<String>,
+    filters_opt: Option<Vec<UiLogFilter>>,
+}
+
+pub fn log_filters_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("log-filters")
+        .about("Displays the log level and per-component log filters of the running MASQNode, and \
+                changes them if you specify new ones. Changes take effect immediately, without a \
+                restart, but last only until the Node shuts down. Only valid if Node is already running.")
+        .arg(
+            Arg::with_name("log-level")
+                .help(LOG_LEVEL_HELP)
+                .long("log-level")
+                .value_name("LOG-LEVEL")
+                .takes_value(true)
+                .possible_values(LOG_LEVELS)
+                .case_insensitive(true),
+        )
+        .arg(
+            Arg::with_name("filters")
+                .help(FILTERS_HELP)
+                .long("filters")
+                .value_name("FILTERS")
+                .takes_value(true)
+                .validator(validate_log_filters),
+        )
+        .arg(
+            Arg::with_name("clear")
+                .help(CLEAR_HELP)
+                .long("clear")
+                .takes_value(false)
+                .conflicts_with("filters"),
+        )
+}
+
+impl Command for LogFiltersCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiLogFiltersRequest {
+            log_level_opt: self.log_level_opt.clone(),
+            filters_opt: self.filters_opt.clone(),
+        };
+        let output: Result<UiLogFiltersResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                writeln!(context.stdout(), "Log level: {}", response.log_level)
+                    .expect("writeln! failed");
+                let filters = if response.filters.is_empty() {
+                    "none".to_string()
+                } else {
+                    response
+                        .filters
+                        .iter()
+                        .map(|filter| format!("{}={}", filter.component, filter.level))
+                        .collect::<Vec<String>>()
+                        .join(",")
+                };
+                writeln!(context.stdout(), "Log filters: {}", filters).expect("writeln! failed");
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_NOT_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is not running; therefore it has no log filters."
+                )
+                .expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(Payload(code, message)) if code == LOG_FILTERS_ERROR => {
+                writeln!(context.stderr(), "{}", message).expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Log filters failed: {:?}", e).expect("writeln! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl LogFiltersCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match log_filters_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        let filters_opt = if matches.is_present("clear") {
+            Some(vec![])
+        } else {
+            matches.value_of("filters").map(|filters| {
+                filters
+                    .split(',')
+                    .map(|filter| {
+                        let mut pieces = filter.split('=');
+                        UiLogFilter {
+                            component: pieces.next().expect("Validation failed").to_string(),
+                            level: pieces.next().expect("Validation failed").to_lowercase(),
+                        }
+                    })
+                    .collect()
+            })
+        };
+        Ok(Self {
+            log_level_opt: matches
+                .value_of("log-level")
+                .map(|log_level| log_level.to_lowercase()),
+            filters_opt,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    fn pieces(args: &[&str]) -> Vec<String> {
+        vec!["log-filters"]
+            .into_iter()
+            .chain(args.iter().copied())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn filter(component: &str, level: &str) -> UiLogFilter {
+        UiLogFilter {
+            component: component.to_string(),
+            level: level.to_string(),
+        }
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context = CommandContextMock::new().transact_result(Ok(UiLogFiltersResponse {
+            log_level: "warn".to_string(),
+            filters: vec![],
+        }
+        .tmb(0)));
+        let subject = factory.make(pieces(&[])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn log_filters_command_without_arguments_changes_nothing() {
+        let result = LogFiltersCommand::new(&pieces(&[])).unwrap();
+
+        assert_eq!(
+            result,
+            LogFiltersCommand {
+                log_level_opt: None,
+                filters_opt: None,
+            }
+        );
+    }
+
+    #[test]
+    fn log_filters_command_parses_log_level_and_filters() {
+        let result = LogFiltersCommand::new(&pieces(&[
+            "--log-level",
+            "INFO",
+            "--filters",
+            "Neighborhood=debug,Hopper=Warn",
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            result,
+            LogFiltersCommand {
+                log_level_opt: Some("info".to_string()),
+                filters_opt: Some(vec![
+                    filter("Neighborhood", "debug"),
+                    filter("Hopper", "warn")
+                ]),
+            }
+        );
+    }
+
+    #[test]
+    fn log_filters_command_clears_filters() {
+        let result = LogFiltersCommand::new(&pieces(&["--clear"])).unwrap();
+
+        assert_eq!(
+            result,
+            LogFiltersCommand {
+                log_level_opt: None,
+                filters_opt: Some(vec![]),
+            }
+        );
+    }
+
+    #[test]
+    fn log_filters_command_rejects_bad_filters() {
+        let result = LogFiltersCommand::new(&pieces(&["--filters", "Neighborhood=loud"]));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("Neighborhood=loud"), "{}", msg);
+    }
+
+    #[test]
+    fn log_filters_command_rejects_filters_with_clear() {
+        let result =
+            LogFiltersCommand::new(&pieces(&["--clear", "--filters", "Neighborhood=debug"]));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("cannot be used with"), "{}", msg);
+    }
+
+    #[test]
+    fn log_filters_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiLogFiltersResponse {
+                log_level: "warn".to_string(),
+                filters: vec![filter("Neighborhood", "debug"), filter("Hopper", "off")],
+            }
+            .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject =
+            LogFiltersCommand::new(&pieces(&["--filters", "Neighborhood=debug,Hopper=off"]))
+                .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            *transact_params_arc.lock().unwrap(),
+            vec![(
+                UiLogFiltersRequest {
+                    log_level_opt: None,
+                    filters_opt: Some(vec![
+                        filter("Neighborhood", "debug"),
+                        filter("Hopper", "off")
+                    ]),
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Log level: warn\nLog filters: Neighborhood=debug,Hopper=off\n"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn log_filters_command_reports_absence_of_filters() {
+        let mut context = CommandContextMock::new().transact_result(Ok(UiLogFiltersResponse {
+            log_level: "trace".to_string(),
+            filters: vec![],
+        }
+        .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = LogFiltersCommand::new(&pieces(&["--clear"])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Log level: trace\nLog filters: none\n"
+        );
+    }
+
+    #[test]
+    fn log_filters_command_doesnt_work_if_node_is_not_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_NOT_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = LogFiltersCommand::new(&pieces(&[])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_NOT_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is not running; therefore it has no log filters.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn log_filters_command_reports_rejected_change() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(LOG_FILTERS_ERROR, "Bad log level: \"loud\"".to_string()),
+        ));
+        let stderr_arc = context.stderr_arc();
+        let subject = LogFiltersCommand::new(&pieces(&[])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                LOG_FILTERS_ERROR,
+                "Bad log level: \"loud\"".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Bad log level: \"loud\"\n"
+        );
+    }
+
+    #[test]
+    fn log_filters_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = LogFiltersCommand::new(&pieces(&[])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Log filters failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
@@ -1,8 +1,18 @@
 // Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
+pub mod ban_command;
+pub mod ban_list_command;
+pub mod change_password_command;
+pub mod change_rate_pack_command;
 pub mod commands_common;
 pub mod crash_command;
 pub mod descriptor_command;
+pub mod financials_command;
+pub mod generate_wallets_command;
+pub mod log_filters_command;
+pub mod neighborhood_command;
+pub mod recover_wallets_command;
 pub mod setup_command;
 pub mod shutdown_command;
 pub mod start_command;
+pub mod unban_command;
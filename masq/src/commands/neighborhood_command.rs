@@ -0,0 +1,361 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, SubCommand};
+use masq_lib::messages::{
+    UiNeighborhoodNode, UiNeighborhoodRequest, UiNeighborhoodResponse, NODE_NOT_RUNNING_ERROR,
+};
+use std::fmt::Debug;
+use std::io::Write;
+
+const JSON_HELP: &str =
+    "Display the neighborhood as JSON, suitable for consumption by scripts, instead of as text.";
+const DOT_HELP: &str =
+    "Display the neighborhood as a DOT graph, suitable for rendering with Graphviz, instead of as \
+     text.";
+
+#[derive(Debug, PartialEq)]
+pub struct NeighborhoodCommand {
+    json: bool,
+    dot: bool,
+}
+
+pub fn neighborhood_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("neighborhood")
+        .about("Displays the Nodes this Node knows about and how they are connected. Only valid if Node is already running.")
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .takes_value(false)
+                .conflicts_with("dot")
+                .help(JSON_HELP),
+        )
+        .arg(
+            Arg::with_name("dot")
+                .long("dot")
+                .takes_value(false)
+                .help(DOT_HELP),
+        )
+}
+
+impl Command for NeighborhoodCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let output: Result<UiNeighborhoodResponse, CommandError> = transaction(
+            UiNeighborhoodRequest {},
+            context,
+            STANDARD_COMMAND_TIMEOUT_MILLIS,
+        );
+        match output {
+            Ok(response) => {
+                if self.json {
+                    Self::dump_json(&response, context.stdout());
+                } else if self.dot {
+                    writeln!(context.stdout(), "{}", response.dot_graph).expect("writeln! failed");
+                } else {
+                    Self::dump_text(&response, context.stdout());
+                }
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_NOT_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is not running; therefore its neighborhood cannot be displayed."
+                )
+                .expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Neighborhood retrieval failed: {:?}", e)
+                    .expect("writeln! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl NeighborhoodCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match neighborhood_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        Ok(Self {
+            json: matches.is_present("json"),
+            dot: matches.is_present("dot"),
+        })
+    }
+
+    fn dump_json(response: &UiNeighborhoodResponse, stdout: &mut dyn Write) {
+        let json =
+            serde_json::to_string_pretty(&response.nodes).expect("Couldn't serialize neighborhood");
+        writeln!(stdout, "{}", json).expect("writeln! failed");
+    }
+
+    fn dump_text(response: &UiNeighborhoodResponse, stdout: &mut dyn Write) {
+        writeln!(
+            stdout,
+            "Neighborhood of {} Node{}\n",
+            response.nodes.len(),
+            if response.nodes.len() == 1 { "" } else { "s" }
+        )
+        .expect("writeln! failed");
+        response
+            .nodes
+            .iter()
+            .enumerate()
+            .for_each(|(index, node)| Self::dump_node(node, index == 0, stdout));
+    }
+
+    fn dump_node(node: &UiNeighborhoodNode, is_local: bool, stdout: &mut dyn Write) {
+        let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+        let key_list = |keys: &[String]| {
+            if keys.is_empty() {
+                "none".to_string()
+            } else {
+                keys.join(", ")
+            }
+        };
+        writeln!(
+            stdout,
+            "{}{}",
+            node.public_key,
+            if is_local { " (this Node)" } else { "" }
+        )
+        .expect("writeln! failed");
+        let rate_pack = &node.rate_pack;
+        vec![
+            (
+                "Address:",
+                node.node_addr_opt
+                    .clone()
+                    .unwrap_or_else(|| "unknown".to_string()),
+            ),
+            ("Version:", node.version.to_string()),
+            (
+                "Rate pack:",
+                format!(
+                    "{}|{}|{}|{}",
+                    rate_pack.routing_byte_rate,
+                    rate_pack.routing_service_rate,
+                    rate_pack.exit_byte_rate,
+                    rate_pack.exit_service_rate
+                ),
+            ),
+            ("Desirable:", yes_no(node.desirable).to_string()),
+            (
+                "Accepts connections:",
+                yes_no(node.accepts_connections).to_string(),
+            ),
+            ("Routes data:", yes_no(node.routes_data).to_string()),
+            ("Full neighbors:", key_list(&node.full_neighbors)),
+            ("Half neighbors:", key_list(&node.half_neighbors)),
+        ]
+        .into_iter()
+        .for_each(|(label, value)| {
+            writeln!(stdout, "    {:22}{}", label, value).expect("writeln! failed")
+        });
+        writeln!(stdout).expect("writeln! failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::{ToMessageBody, UiRatePack};
+    use std::sync::{Arc, Mutex};
+
+    fn make_response() -> UiNeighborhoodResponse {
+        UiNeighborhoodResponse {
+            nodes: vec![
+                UiNeighborhoodNode {
+                    public_key: "AQIDBA".to_string(),
+                    node_addr_opt: Some("1.2.3.4:1234".to_string()),
+                    version: 3,
+                    rate_pack: UiRatePack {
+                        routing_byte_rate: 100,
+                        routing_service_rate: 10000,
+                        exit_byte_rate: 101,
+                        exit_service_rate: 10001,
+                    },
+                    desirable: true,
+                    accepts_connections: true,
+                    routes_data: true,
+                    full_neighbors: vec!["AgMEBQ".to_string()],
+                    half_neighbors: vec!["AwQFBg".to_string()],
+                },
+                UiNeighborhoodNode {
+                    public_key: "AgMEBQ".to_string(),
+                    node_addr_opt: None,
+                    version: 0,
+                    rate_pack: UiRatePack {
+                        routing_byte_rate: 1,
+                        routing_service_rate: 2,
+                        exit_byte_rate: 3,
+                        exit_service_rate: 4,
+                    },
+                    desirable: false,
+                    accepts_connections: false,
+                    routes_data: true,
+                    full_neighbors: vec!["AQIDBA".to_string()],
+                    half_neighbors: vec![],
+                },
+            ],
+            dot_graph: "digraph db { \"AQIDBA\" -> \"AgMEBQ\"; }".to_string(),
+        }
+    }
+
+    fn pieces(args: &[&str]) -> Vec<String> {
+        vec!["neighborhood"]
+            .into_iter()
+            .chain(args.iter().copied())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(0)));
+        let subject = factory.make(pieces(&[])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn neighborhood_command_rejects_json_with_dot() {
+        let result = NeighborhoodCommand::new(&pieces(&["--json", "--dot"]));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("cannot be used with"), "{}", msg);
+    }
+
+    #[test]
+    fn neighborhood_command_happy_path_with_text() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(make_response().tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = NeighborhoodCommand::new(&pieces(&[])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            *transact_params_arc.lock().unwrap(),
+            vec![(
+                UiNeighborhoodRequest {}.tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Neighborhood of 2 Nodes\n\n\
+             AQIDBA (this Node)\n\
+             \x20   Address:              1.2.3.4:1234\n\
+             \x20   Version:              3\n\
+             \x20   Rate pack:            100|10000|101|10001\n\
+             \x20   Desirable:            yes\n\
+             \x20   Accepts connections:  yes\n\
+             \x20   Routes data:          yes\n\
+             \x20   Full neighbors:       AgMEBQ\n\
+             \x20   Half neighbors:       AwQFBg\n\
+             \n\
+             AgMEBQ\n\
+             \x20   Address:              unknown\n\
+             \x20   Version:              0\n\
+             \x20   Rate pack:            1|2|3|4\n\
+             \x20   Desirable:            no\n\
+             \x20   Accepts connections:  no\n\
+             \x20   Routes data:          yes\n\
+             \x20   Full neighbors:       AQIDBA\n\
+             \x20   Half neighbors:       none\n\
+             \n"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn neighborhood_command_happy_path_with_json() {
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = NeighborhoodCommand::new(&pieces(&["--json"])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let stdout = stdout_arc.lock().unwrap().get_string();
+        let nodes: Vec<UiNeighborhoodNode> = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(nodes, make_response().nodes);
+    }
+
+    #[test]
+    fn neighborhood_command_happy_path_with_dot() {
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = NeighborhoodCommand::new(&pieces(&["--dot"])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "digraph db { \"AQIDBA\" -> \"AgMEBQ\"; }\n"
+        );
+    }
+
+    #[test]
+    fn neighborhood_command_doesnt_work_if_node_is_not_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_NOT_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = NeighborhoodCommand::new(&pieces(&[])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_NOT_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is not running; therefore its neighborhood cannot be displayed.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn neighborhood_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = NeighborhoodCommand::new(&pieces(&[])).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Neighborhood retrieval failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
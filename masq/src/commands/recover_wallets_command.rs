@@ -0,0 +1,392 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, SubCommand};
+use masq_lib::constants::{DEFAULT_CONSUMING_DERIVATION_PATH, DEFAULT_EARNING_DERIVATION_PATH};
+use masq_lib::messages::{
+    UiRecoverWalletsRequest, UiRecoverWalletsResponse, NODE_ALREADY_RUNNING_ERROR,
+};
+use masq_lib::shared_schema::common_validators::{
+    validate_derivation_path, validate_earning_wallet,
+};
+use std::fmt::Debug;
+
+const DB_PASSWORD_HELP: &str =
+    "The password that will be used to encrypt the wallet information in the Node's database.";
+const MNEMONIC_PHRASE_HELP: &str =
+    "The mnemonic phrase the wallets were generated from: 12, 15, 18, 21, or 24 words surrounded \
+     by double quotes.";
+const LANGUAGE_HELP: &str = "The language of the words in the mnemonic phrase.";
+const PASSPHRASE_HELP: &str =
+    "The additional word, if any, that was specified when the mnemonic phrase was generated.";
+const CONSUMING_PATH_HELP: &str =
+    "The BIP32 derivation path for the wallet from which your Node will pay for services.";
+const EARNING_WALLET_HELP: &str =
+    "Either the BIP32 derivation path for the wallet into which your Node will be paid for services, \
+     or the Ethereum address of a wallet elsewhere (e.g. 0x0123456789012345678901234567890123456789).";
+
+#[derive(Debug, PartialEq)]
+pub struct RecoverWalletsCommand {
+    db_password: String,
+    mnemonic_phrase: Vec<String>,
+    language: String,
+    passphrase_opt: Option<String>,
+    consuming_path: String,
+    earning_wallet: String,
+}
+
+pub fn recover_wallets_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("recover-wallets")
+        .about(
+            "Recovers a pair of wallets from an existing mnemonic phrase and stores them, \
+                encrypted, in the database the Node will use. Only valid if Node is not already \
+                running and has no wallets yet.",
+        )
+        .arg(
+            Arg::with_name("db-password")
+                .help(DB_PASSWORD_HELP)
+                .long("db-password")
+                .value_name("DB-PASSWORD")
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("mnemonic-phrase")
+                .help(MNEMONIC_PHRASE_HELP)
+                .long("mnemonic-phrase")
+                .value_name("MNEMONIC-PHRASE")
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("language")
+                .help(LANGUAGE_HELP)
+                .long("language")
+                .value_name("LANGUAGE")
+                .takes_value(true)
+                .default_value("English"),
+        )
+        .arg(
+            Arg::with_name("passphrase")
+                .help(PASSPHRASE_HELP)
+                .long("passphrase")
+                .value_name("PASSPHRASE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("consuming-path")
+                .help(CONSUMING_PATH_HELP)
+                .long("consuming-path")
+                .value_name("CONSUMING-PATH")
+                .takes_value(true)
+                .default_value(DEFAULT_CONSUMING_DERIVATION_PATH)
+                .validator(validate_derivation_path),
+        )
+        .arg(
+            Arg::with_name("earning-wallet")
+                .help(EARNING_WALLET_HELP)
+                .long("earning-wallet")
+                .value_name("EARNING-WALLET")
+                .takes_value(true)
+                .default_value(DEFAULT_EARNING_DERIVATION_PATH)
+                .validator(validate_earning_wallet),
+        )
+}
+
+impl Command for RecoverWalletsCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiRecoverWalletsRequest {
+            db_password: self.db_password.clone(),
+            mnemonic_phrase: self.mnemonic_phrase.clone(),
+            mnemonic_phrase_language: self.language.clone(),
+            mnemonic_passphrase_opt: self.passphrase_opt.clone(),
+            consuming_derivation_path: self.consuming_path.clone(),
+            earning_wallet: self.earning_wallet.clone(),
+        };
+        let output: Result<UiRecoverWalletsResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                writeln!(
+                    context.stdout(),
+                    "Wallets recovered\n\
+                     Address of consuming wallet: {}\n\
+                     Address of   earning wallet: {}",
+                    response.consuming_wallet_address,
+                    response.earning_wallet_address
+                )
+                .expect("writeln! failed");
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_ALREADY_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is already running; wallets can only be recovered before it starts."
+                )
+                .expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Wallet recovery failed: {:?}", e)
+                    .expect("writeln! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl RecoverWalletsCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match recover_wallets_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        let mnemonic_phrase: Vec<String> = matches
+            .value_of("mnemonic-phrase")
+            .expect("mnemonic-phrase parameter is not properly required")
+            .split_whitespace()
+            .map(|word| word.to_string())
+            .collect();
+        if ![12, 15, 18, 21, 24].contains(&mnemonic_phrase.len()) {
+            return Err(format!(
+                "Mnemonic phrase must be 12, 15, 18, 21, or 24 words long, not {}",
+                mnemonic_phrase.len()
+            ));
+        }
+        Ok(Self {
+            db_password: matches
+                .value_of("db-password")
+                .expect("db-password parameter is not properly required")
+                .to_string(),
+            mnemonic_phrase,
+            language: matches
+                .value_of("language")
+                .expect("language parameter is not properly defaulted")
+                .to_string(),
+            passphrase_opt: matches.value_of("passphrase").map(|s| s.to_string()),
+            consuming_path: matches
+                .value_of("consuming-path")
+                .expect("consuming-path parameter is not properly defaulted")
+                .to_string(),
+            earning_wallet: matches
+                .value_of("earning-wallet")
+                .expect("earning-wallet parameter is not properly defaulted")
+                .to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    const PHRASE: &str =
+        "company replace elder oxygen access into pair squeeze clip occur world crowd";
+
+    fn pieces(args: &[&str]) -> Vec<String> {
+        vec!["recover-wallets"]
+            .into_iter()
+            .chain(args.iter().copied())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn phrase_words() -> Vec<String> {
+        PHRASE.split(' ').map(|word| word.to_string()).collect()
+    }
+
+    fn make_response() -> UiRecoverWalletsResponse {
+        UiRecoverWalletsResponse {
+            consuming_wallet_address: "0x1111111111111111111111111111111111111111".to_string(),
+            earning_wallet_address: "0x2222222222222222222222222222222222222222".to_string(),
+        }
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(0)));
+        let subject = factory
+            .make(pieces(&[
+                "--db-password",
+                "password",
+                "--mnemonic-phrase",
+                PHRASE,
+            ]))
+            .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn recover_wallets_command_rejects_wrong_number_of_words() {
+        let result = RecoverWalletsCommand::new(&pieces(&[
+            "--db-password",
+            "password",
+            "--mnemonic-phrase",
+            "one two three",
+        ]));
+
+        assert_eq!(
+            result,
+            Err("Mnemonic phrase must be 12, 15, 18, 21, or 24 words long, not 3".to_string())
+        );
+    }
+
+    #[test]
+    fn recover_wallets_command_rejects_bad_earning_wallet() {
+        let result = RecoverWalletsCommand::new(&pieces(&[
+            "--db-password",
+            "password",
+            "--mnemonic-phrase",
+            PHRASE,
+            "--earning-wallet",
+            "booga",
+        ]));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("booga"), "{}", msg);
+    }
+
+    #[test]
+    fn recover_wallets_command_defaults_parameters() {
+        let result = RecoverWalletsCommand::new(&pieces(&[
+            "--db-password",
+            "password",
+            "--mnemonic-phrase",
+            &format!("  {}\t", PHRASE.replace(" ", "  ")),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            result,
+            RecoverWalletsCommand {
+                db_password: "password".to_string(),
+                mnemonic_phrase: phrase_words(),
+                language: "English".to_string(),
+                passphrase_opt: None,
+                consuming_path: DEFAULT_CONSUMING_DERIVATION_PATH.to_string(),
+                earning_wallet: DEFAULT_EARNING_DERIVATION_PATH.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn recover_wallets_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(make_response().tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = RecoverWalletsCommand::new(&pieces(&[
+            "--db-password",
+            "password",
+            "--mnemonic-phrase",
+            PHRASE,
+            "--language",
+            "english",
+            "--passphrase",
+            "booga",
+            "--consuming-path",
+            "m/44'/60'/0'/100/0/200",
+            "--earning-wallet",
+            "0x2222222222222222222222222222222222222222",
+        ]))
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            *transact_params_arc.lock().unwrap(),
+            vec![(
+                UiRecoverWalletsRequest {
+                    db_password: "password".to_string(),
+                    mnemonic_phrase: phrase_words(),
+                    mnemonic_phrase_language: "english".to_string(),
+                    mnemonic_passphrase_opt: Some("booga".to_string()),
+                    consuming_derivation_path: "m/44'/60'/0'/100/0/200".to_string(),
+                    earning_wallet: "0x2222222222222222222222222222222222222222".to_string(),
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Wallets recovered\n\
+             Address of consuming wallet: 0x1111111111111111111111111111111111111111\n\
+             Address of   earning wallet: 0x2222222222222222222222222222222222222222\n"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn recover_wallets_command_doesnt_work_if_node_is_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_ALREADY_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = RecoverWalletsCommand::new(&pieces(&[
+            "--db-password",
+            "password",
+            "--mnemonic-phrase",
+            PHRASE,
+        ]))
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_ALREADY_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is already running; wallets can only be recovered before it starts.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn recover_wallets_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = RecoverWalletsCommand::new(&pieces(&[
+            "--db-password",
+            "password",
+            "--mnemonic-phrase",
+            PHRASE,
+        ]))
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Wallet recovery failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
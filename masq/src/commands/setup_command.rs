@@ -164,7 +164,7 @@ mod tests {
             .transact_result(Ok(UiSetupResponse {
                 running: false,
                 values: vec![
-                    UiSetupResponseValue::new("chain", "ropsten", Configured),
+                    UiSetupResponseValue::new("chain", "dev", Configured),
                     UiSetupResponseValue::new("neighborhood-mode", "zero-hop", Set),
                 ],
                 errors: vec![],
@@ -180,7 +180,7 @@ mod tests {
                 "zero-hop".to_string(),
                 "--log-level".to_string(),
                 "--chain".to_string(),
-                "ropsten".to_string(),
+                "dev".to_string(),
             ])
             .unwrap();
 
@@ -204,7 +204,7 @@ mod tests {
         );
         assert_eq! (stdout_arc.lock().unwrap().get_string(),
 "NAME                   VALUE                                                            STATUS\n\
-chain                  ropsten                                                          Configured\n\
+chain                  dev                                                              Configured\n\
 neighborhood-mode      zero-hop                                                         Set\n\
 \n");
         assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
@@ -218,7 +218,7 @@ neighborhood-mode      zero-hop
             .transact_result(Ok(UiSetupResponse {
                 running: true,
                 values: vec![
-                    UiSetupResponseValue::new("chain", "ropsten", Set),
+                    UiSetupResponseValue::new("chain", "dev", Set),
                     UiSetupResponseValue::new("neighborhood-mode", "zero-hop", Configured),
                     UiSetupResponseValue::new("clandestine-port", "8534", Default),
                 ],
@@ -234,7 +234,7 @@ neighborhood-mode      zero-hop
                 "--neighborhood-mode".to_string(),
                 "zero-hop".to_string(),
                 "--chain".to_string(),
-                "ropsten".to_string(),
+                "dev".to_string(),
                 "--clandestine-port".to_string(),
                 "8534".to_string(),
                 "--log-level".to_string(),
@@ -250,7 +250,7 @@ neighborhood-mode      zero-hop
             vec![(
                 UiSetupRequest {
                     values: vec![
-                        UiSetupRequestValue::new("chain", "ropsten"),
+                        UiSetupRequestValue::new("chain", "dev"),
                         UiSetupRequestValue::new("clandestine-port", "8534"),
                         UiSetupRequestValue::clear("log-level"),
                         UiSetupRequestValue::new("neighborhood-mode", "zero-hop"),
@@ -262,7 +262,7 @@ neighborhood-mode      zero-hop
         );
         assert_eq! (stdout_arc.lock().unwrap().get_string(),
 "NAME                   VALUE                                                            STATUS\n\
-chain                  ropsten                                                          Set\n\
+chain                  dev                                                              Set\n\
 clandestine-port       8534                                                             Default\n\
 neighborhood-mode      zero-hop                                                         Configured\n\
 \n\
@@ -279,7 +279,7 @@ NOTE: no changes were made to the setup because the Node is currently running.\n
         let message = UiSetupBroadcast {
             running: false,
             values: vec![
-                UiSetupResponseValue::new("chain", "ropsten", Set),
+                UiSetupResponseValue::new("chain", "dev", Set),
                 UiSetupResponseValue::new("neighborhood-mode", "zero-hop", Configured),
                 UiSetupResponseValue::new("clandestine-port", "8534", Default),
             ],
@@ -296,7 +296,7 @@ NOTE: no changes were made to the setup because the Node is currently running.\n
 Daemon setup has changed:\n\
 \n\
 NAME                   VALUE                                                            STATUS\n\
-chain                  ropsten                                                          Set\n\
+chain                  dev                                                              Set\n\
 clandestine-port       8534                                                             Default\n\
 neighborhood-mode      zero-hop                                                         Configured\n\
 \n\
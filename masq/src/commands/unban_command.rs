@@ -0,0 +1,215 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, SubCommand};
+use masq_lib::messages::{UiUnbanRequest, UiUnbanResponse, NODE_NOT_RUNNING_ERROR};
+use masq_lib::shared_schema::common_validators::validate_ethereum_address;
+use std::fmt::Debug;
+
+const WALLET_HELP: &str = "The Ethereum address of the wallet to pardon (e.g. 0x0123456789012345678901234567890123456789)";
+
+#[derive(Debug, PartialEq)]
+pub struct UnbanCommand {
+    wallet: String,
+}
+
+pub fn unban_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("unban")
+        .about("Pardons a wallet: lifts any ban on it, and keeps the running MASQNode from banning it \
+                for delinquency over what it owes now until it is banned again with ban. Only valid if \
+                Node is already running.")
+        .arg(
+            Arg::with_name("wallet")
+                .help(WALLET_HELP)
+                .index(1)
+                .required(true)
+                .validator(validate_ethereum_address),
+        )
+}
+
+impl Command for UnbanCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiUnbanRequest {
+            wallet: self.wallet.clone(),
+        };
+        let output: Result<UiUnbanResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                if response.was_banned {
+                    writeln!(
+                        context.stdout(),
+                        "Wallet {} unbanned and pardoned",
+                        self.wallet
+                    )
+                } else {
+                    writeln!(
+                        context.stdout(),
+                        "Wallet {} was not banned, but is now pardoned",
+                        self.wallet
+                    )
+                }
+                .expect("writeln! failed");
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == NODE_NOT_RUNNING_ERROR => {
+                writeln!(
+                    context.stderr(),
+                    "MASQNode is not running; therefore it cannot unban wallets."
+                )
+                .expect("writeln! failed");
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                writeln!(context.stderr(), "Unban failed: {:?}", e).expect("writeln! failed");
+                Err(e)
+            }
+        }
+    }
+}
+
+impl UnbanCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match unban_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        Ok(Self {
+            wallet: matches
+                .value_of("wallet")
+                .expect("wallet parameter is not properly required")
+                .to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_context::ContextError::ConnectionDropped;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    const WALLET: &str = "0x0123456789012345678901234567890123456789";
+
+    fn pieces(wallet: &str) -> Vec<String> {
+        vec!["unban".to_string(), wallet.to_string()]
+    }
+
+    #[test]
+    fn testing_command_factory_here() {
+        let factory = CommandFactoryReal::new();
+        let mut context = CommandContextMock::new()
+            .transact_result(Ok(UiUnbanResponse { was_banned: true }.tmb(0)));
+        let subject = factory.make(pieces(WALLET)).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn unban_command_rejects_malformed_wallet() {
+        let result = UnbanCommand::new(&pieces("booga"));
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("booga"), "{}", msg);
+    }
+
+    #[test]
+    fn unban_command_happy_path_for_banned_wallet() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiUnbanResponse { was_banned: true }.tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = UnbanCommand::new(&pieces(WALLET)).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            *transact_params_arc.lock().unwrap(),
+            vec![(
+                UiUnbanRequest {
+                    wallet: WALLET.to_string(),
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            format!("Wallet {} unbanned and pardoned\n", WALLET)
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn unban_command_happy_path_for_unbanned_wallet() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Ok(UiUnbanResponse { was_banned: false }.tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = UnbanCommand::new(&pieces(WALLET)).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            format!("Wallet {} was not banned, but is now pardoned\n", WALLET)
+        );
+    }
+
+    #[test]
+    fn unban_command_doesnt_work_if_node_is_not_running() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(NODE_NOT_RUNNING_ERROR, "irrelevant".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = UnbanCommand::new(&pieces(WALLET)).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::Payload(
+                NODE_NOT_RUNNING_ERROR,
+                "irrelevant".to_string()
+            ))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "MASQNode is not running; therefore it cannot unban wallets.\n"
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn unban_command_sad_path() {
+        let mut context =
+            CommandContextMock::new().transact_result(Err(ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = UnbanCommand::new(&pieces(WALLET)).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Unban failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
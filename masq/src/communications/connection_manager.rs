@@ -9,6 +9,7 @@ use crossbeam_channel::{unbounded, RecvTimeoutError};
 use crossbeam_channel::{Receiver, RecvError, Sender};
 use masq_lib::messages::{CrashReason, FromMessageBody, ToMessageBody, UiNodeCrashedBroadcast};
 use masq_lib::messages::{UiRedirect, NODE_UI_PROTOCOL};
+use masq_lib::ui_auth::with_default_ui_auth_token;
 use masq_lib::ui_gateway::{MessageBody, MessagePath};
 use masq_lib::ui_traffic_converter::UiTrafficConverter;
 use masq_lib::utils::localhost;
@@ -158,7 +159,7 @@ fn make_client_listener(
     timeout_millis: u64,
 ) -> Result<Writer<TcpStream>, ClientListenerError> {
     let url = format!("ws://{}:{}", localhost(), port);
-    let builder = ClientBuilder::new(url.as_str()).expect("Bad URL");
+    let builder = with_default_ui_auth_token(ClientBuilder::new(url.as_str()).expect("Bad URL"));
     let result = builder.add_protocol(NODE_UI_PROTOCOL);
     let result = match connect_insecure_timeout(result, timeout_millis) {
         Err(RecvTimeoutError::Disconnected) => return Err(ClientListenerError::Closed),
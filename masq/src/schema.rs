@@ -1,9 +1,19 @@
 // Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::commands::ban_command::ban_subcommand;
+use crate::commands::ban_list_command::ban_list_subcommand;
+use crate::commands::change_password_command::change_password_subcommand;
+use crate::commands::change_rate_pack_command::change_rate_pack_subcommand;
 use crate::commands::crash_command::crash_subcommand;
 use crate::commands::descriptor_command::descriptor_subcommand;
+use crate::commands::financials_command::financials_subcommand;
+use crate::commands::generate_wallets_command::generate_wallets_subcommand;
+use crate::commands::log_filters_command::log_filters_subcommand;
+use crate::commands::neighborhood_command::neighborhood_subcommand;
+use crate::commands::recover_wallets_command::recover_wallets_subcommand;
 use crate::commands::setup_command::setup_subcommand;
 use crate::commands::shutdown_command::shutdown_subcommand;
 use crate::commands::start_command::start_subcommand;
+use crate::commands::unban_command::unban_subcommand;
 use clap::{App, AppSettings, Arg};
 use lazy_static::lazy_static;
 use masq_lib::constants::{DEFAULT_UI_PORT, HIGHEST_USABLE_PORT, LOWEST_USABLE_INSECURE_PORT};
@@ -42,11 +52,21 @@ pub fn app() -> App<'static, 'static> {
                 .validator(validate_ui_port)
                 .help(UI_PORT_HELP.as_str()),
         )
+        .subcommand(ban_subcommand())
+        .subcommand(ban_list_subcommand())
+        .subcommand(change_password_subcommand())
+        .subcommand(change_rate_pack_subcommand())
         .subcommand(crash_subcommand())
         .subcommand(descriptor_subcommand())
+        .subcommand(financials_subcommand())
+        .subcommand(generate_wallets_subcommand())
+        .subcommand(log_filters_subcommand())
+        .subcommand(neighborhood_subcommand())
+        .subcommand(recover_wallets_subcommand())
         .subcommand(setup_subcommand())
         .subcommand(start_subcommand())
         .subcommand(shutdown_subcommand())
+        .subcommand(unban_subcommand())
 }
 
 fn validate_ui_port(port: String) -> Result<(), String> {
@@ -0,0 +1,655 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::shared_schema::ConfiguratorError;
+use lazy_static::lazy_static;
+use serde_derive::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::RwLock;
+
+pub const BUILT_IN_BLOCKCHAINS: &str = include_str!("blockchains.toml");
+
+// Transactions are signed with v = recovery id + chain ID * 2 + 35, and v must fit in a u64; this is
+// the bound EIP-2294 puts on chain IDs for the same reason
+pub const HIGHEST_USABLE_CHAIN_ID: u64 = u64::MAX / 2 - 36;
+// The Node keeps its books in units of 10^-9 token, so the token must divide at least that finely;
+// and a u64 of those units must still fit in a U256 of the contract's units
+pub const LOWEST_USABLE_TOKEN_DECIMALS: u8 = 9;
+pub const HIGHEST_USABLE_TOKEN_DECIMALS: u8 = 66;
+// A batch contract's method takes the token contract, the recipients, and what each one gets, and
+// moves the tokens from whoever sends it the transaction
+pub const BATCH_METHOD_PARAMETERS: &str = "(address,address[],uint256[])";
+pub const DEFAULT_BATCH_METHOD: &str = "disperseTokenSimple(address,address[],uint256[])";
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct BlockchainRecord {
+    pub name: String,
+    pub chain_id: u64,
+    pub contract: String,
+    pub contract_creation_block: u64,
+    pub token_decimals: u8,
+    #[serde(default, rename = "batch_contract")]
+    pub batch_contract_opt: Option<String>,
+    #[serde(default = "default_batch_method")]
+    pub batch_method: String,
+}
+
+fn default_batch_method() -> String {
+    DEFAULT_BATCH_METHOD.to_string()
+}
+
+impl BlockchainRecord {
+    pub fn contract_bytes(&self) -> [u8; 20] {
+        address_bytes(&self.contract)
+    }
+
+    pub fn batch_contract_bytes(&self) -> Option<[u8; 20]> {
+        self.batch_contract_opt
+            .as_ref()
+            .map(|batch_contract| address_bytes(batch_contract))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("Blockchain name must not be empty".to_string());
+        }
+        if self.chain_id == 0 || self.chain_id > HIGHEST_USABLE_CHAIN_ID {
+            return Err(format!(
+                "Blockchain '{}' has chain_id {}, but it must be between 1 and {}",
+                self.name, self.chain_id, HIGHEST_USABLE_CHAIN_ID
+            ));
+        }
+        if !is_address(&self.contract) {
+            return Err(format!(
+                "Blockchain '{}' has contract '{}', but it must be 0x followed by 40 hexadecimal digits",
+                self.name, self.contract
+            ));
+        }
+        if self.token_decimals < LOWEST_USABLE_TOKEN_DECIMALS
+            || self.token_decimals > HIGHEST_USABLE_TOKEN_DECIMALS
+        {
+            return Err(format!(
+                "Blockchain '{}' has token_decimals {}, but it must be between {} and {}",
+                self.name,
+                self.token_decimals,
+                LOWEST_USABLE_TOKEN_DECIMALS,
+                HIGHEST_USABLE_TOKEN_DECIMALS
+            ));
+        }
+        if let Some(batch_contract) = self.batch_contract_opt.as_ref() {
+            if !is_address(batch_contract) {
+                return Err(format!(
+                    "Blockchain '{}' has batch_contract '{}', but it must be 0x followed by 40 hexadecimal digits",
+                    self.name, batch_contract
+                ));
+            }
+        }
+        let method_name = self
+            .batch_method
+            .strip_suffix(BATCH_METHOD_PARAMETERS)
+            .unwrap_or("");
+        if method_name.is_empty()
+            || !method_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(format!(
+                "Blockchain '{}' has batch_method '{}', but it must be a method name followed by {}",
+                self.name, self.batch_method, BATCH_METHOD_PARAMETERS
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_address(address: &str) -> bool {
+    let hex_digits = address.strip_prefix("0x").unwrap_or("");
+    hex_digits.len() == 40 && hex_digits.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn address_bytes(address: &str) -> [u8; 20] {
+    let mut bytes = [0u8; 20];
+    let hex = &address[2..];
+    bytes.iter_mut().enumerate().for_each(|(idx, byte)| {
+        *byte = u8::from_str_radix(&hex[(idx * 2)..(idx * 2 + 2)], 16)
+            .expect("Contract address was not validated")
+    });
+    bytes
+}
+
+#[derive(Deserialize)]
+struct BlockchainsFile {
+    #[serde(default)]
+    blockchain: Vec<BlockchainRecord>,
+}
+
+lazy_static! {
+    // Only the built-in networks are known until configuration finds a --blockchains-file. Every
+    // set of networks installed here lives as long as the process, so that lookups can hand out
+    // references into it.
+    static ref BLOCKCHAINS: RwLock<&'static [BlockchainRecord]> = RwLock::new(Box::leak(
+        parse_blockchains(BUILT_IN_BLOCKCHAINS)
+            .expect("Built-in blockchain definitions are invalid")
+            .into_boxed_slice()
+    ));
+}
+
+fn blockchains() -> &'static [BlockchainRecord] {
+    *BLOCKCHAINS.read().expect("Blockchains lock is poisoned")
+}
+
+pub fn blockchain_from_name(name: &str) -> Option<&'static BlockchainRecord> {
+    let name = name.to_lowercase();
+    blockchains().iter().find(|b| b.name == name)
+}
+
+pub fn blockchain_from_chain_id(chain_id: u64) -> Option<&'static BlockchainRecord> {
+    blockchains().iter().find(|b| b.chain_id == chain_id)
+}
+
+pub fn blockchain_names() -> Vec<&'static str> {
+    blockchains().iter().map(|b| b.name.as_str()).collect()
+}
+
+pub fn use_blockchains_file(file: &Path) -> Result<(), ConfiguratorError> {
+    let loaded = load_blockchains(Some(file)).map_err(|e| {
+        ConfiguratorError::required(
+            "blockchains-file",
+            &format!("Can't load the blockchains file: {}", e),
+        )
+    })?;
+    let mut installed = BLOCKCHAINS.write().expect("Blockchains lock is poisoned");
+    // The Daemon reconfigures over and over with the same file; don't leak a copy every time
+    if *installed != loaded.as_slice() {
+        *installed = Box::leak(loaded.into_boxed_slice());
+    }
+    Ok(())
+}
+
+pub fn check_chain_name(chain_name: &str) -> Result<(), ConfiguratorError> {
+    let names = blockchain_names();
+    if names.contains(&chain_name) {
+        Ok(())
+    } else {
+        Err(ConfiguratorError::required(
+            "chain",
+            &format!(
+                "Unknown blockchain '{}': use one of {}, or describe it in a --blockchains-file",
+                chain_name,
+                names.join(", ")
+            ),
+        ))
+    }
+}
+
+pub fn load_blockchains(file_opt: Option<&Path>) -> Result<Vec<BlockchainRecord>, String> {
+    let built_in = parse_blockchains(BUILT_IN_BLOCKCHAINS)?;
+    match file_opt {
+        None => Ok(built_in),
+        Some(file) => {
+            let contents = std::fs::read_to_string(file)
+                .map_err(|e| format!("Can't read {:?}: {}", file, e))?;
+            let extras = parse_blockchains(&contents).map_err(|e| format!("{:?}: {}", file, e))?;
+            merge_blockchains(built_in, extras)
+        }
+    }
+}
+
+pub fn parse_blockchains(toml_text: &str) -> Result<Vec<BlockchainRecord>, String> {
+    let file: BlockchainsFile = toml::from_str(toml_text).map_err(|e| format!("{}", e))?;
+    let blockchains = file
+        .blockchain
+        .into_iter()
+        .map(|b| BlockchainRecord {
+            name: b.name.to_lowercase(),
+            contract: b.contract.to_lowercase(),
+            batch_contract_opt: b.batch_contract_opt.map(|c| c.to_lowercase()),
+            ..b
+        })
+        .collect::<Vec<BlockchainRecord>>();
+    blockchains.iter().try_for_each(|b| b.validate())?;
+    check_uniqueness(&blockchains)?;
+    Ok(blockchains)
+}
+
+pub fn merge_blockchains(
+    base: Vec<BlockchainRecord>,
+    overrides: Vec<BlockchainRecord>,
+) -> Result<Vec<BlockchainRecord>, String> {
+    let mut merged = base
+        .into_iter()
+        .filter(|b| !overrides.iter().any(|o| o.name == b.name))
+        .collect::<Vec<BlockchainRecord>>();
+    merged.extend(overrides);
+    check_uniqueness(&merged)?;
+    Ok(merged)
+}
+
+fn check_uniqueness(blockchains: &[BlockchainRecord]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    let mut chain_ids = HashSet::new();
+    for blockchain in blockchains {
+        if !names.insert(blockchain.name.as_str()) {
+            return Err(format!(
+                "Blockchain name '{}' is defined more than once",
+                blockchain.name
+            ));
+        }
+        if !chain_ids.insert(blockchain.chain_id) {
+            return Err(format!(
+                "Blockchain '{}' has chain_id {}, which another blockchain already uses",
+                blockchain.name, blockchain.chain_id
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::DEFAULT_CHAIN_NAME;
+    use crate::test_utils::utils::ensure_node_home_directory_exists;
+    use std::io::Write;
+
+    fn record(name: &str, chain_id: u64) -> BlockchainRecord {
+        BlockchainRecord {
+            name: name.to_string(),
+            chain_id,
+            contract: "0x0123456789abcdef0123456789abcdef01234567".to_string(),
+            contract_creation_block: 1234,
+            token_decimals: 18,
+            batch_contract_opt: None,
+            batch_method: DEFAULT_BATCH_METHOD.to_string(),
+        }
+    }
+
+    #[test]
+    fn built_in_blockchains_are_valid_and_include_the_default() {
+        let result = parse_blockchains(BUILT_IN_BLOCKCHAINS).unwrap();
+
+        let names = result
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["mainnet", "dev"]);
+        assert!(names.contains(&DEFAULT_CHAIN_NAME));
+    }
+
+    #[test]
+    fn lookups_find_built_in_blockchains() {
+        let mainnet = blockchain_from_name("MainNet").unwrap();
+
+        assert_eq!(mainnet.chain_id, 1);
+        assert_eq!(mainnet.contract_creation_block, 9_415_932);
+        assert_eq!(
+            mainnet.batch_contract_opt,
+            Some("0xd152f549545093347a162dce210e7293f1452150".to_string())
+        );
+        assert_eq!(
+            blockchain_from_name("dev").unwrap().batch_contract_opt,
+            None
+        );
+        assert_eq!(blockchain_from_chain_id(2).unwrap().name, "dev");
+        assert_eq!(blockchain_from_name("booga"), None);
+        assert_eq!(blockchain_from_chain_id(99), None);
+    }
+
+    #[test]
+    fn contract_bytes_decodes_the_contract_address() {
+        let subject = record("booga", 42);
+
+        let result = subject.contract_bytes();
+
+        assert_eq!(
+            result,
+            [
+                0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+                0xcd, 0xef, 0x01, 0x23, 0x45, 0x67
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_contract_bytes_decodes_the_batch_contract_address_if_there_is_one() {
+        let subject = BlockchainRecord {
+            batch_contract_opt: Some("0xfedcba9876543210fedcba9876543210fedcba98".to_string()),
+            ..record("booga", 42)
+        };
+
+        let result = subject.batch_contract_bytes();
+
+        assert_eq!(
+            result,
+            Some([
+                0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54,
+                0x32, 0x10, 0xfe, 0xdc, 0xba, 0x98
+            ])
+        );
+        assert_eq!(record("booga", 42).batch_contract_bytes(), None);
+    }
+
+    #[test]
+    fn parse_blockchains_normalizes_case() {
+        let result = parse_blockchains(
+            r#"
+            [[blockchain]]
+            name = "PrivateNet"
+            chain_id = 42
+            contract = "0x0123456789ABCDEF0123456789abcdef01234567"
+            contract_creation_block = 1234
+            token_decimals = 18
+        "#,
+        );
+
+        assert_eq!(result, Ok(vec![record("privatenet", 42)]));
+    }
+
+    #[test]
+    fn parse_blockchains_accepts_chain_ids_too_big_for_a_byte() {
+        let result = parse_blockchains(
+            r#"
+            [[blockchain]]
+            name = "sepolia"
+            chain_id = 11155111
+            contract = "0x0123456789abcdef0123456789abcdef01234567"
+            contract_creation_block = 1234
+            token_decimals = 18
+        "#,
+        );
+
+        assert_eq!(result, Ok(vec![record("sepolia", 11_155_111)]));
+    }
+
+    #[test]
+    fn parse_blockchains_reads_the_batch_contract_and_method() {
+        let result = parse_blockchains(
+            r#"
+            [[blockchain]]
+            name = "privatenet"
+            chain_id = 42
+            contract = "0x0123456789abcdef0123456789abcdef01234567"
+            contract_creation_block = 1234
+            token_decimals = 18
+            batch_contract = "0xFEDCBA9876543210fedcba9876543210fedcba98"
+            batch_method = "disperseToken(address,address[],uint256[])"
+        "#,
+        );
+
+        assert_eq!(
+            result,
+            Ok(vec![BlockchainRecord {
+                batch_contract_opt: Some("0xfedcba9876543210fedcba9876543210fedcba98".to_string()),
+                batch_method: "disperseToken(address,address[],uint256[])".to_string(),
+                ..record("privatenet", 42)
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_blockchains_complains_about_bad_toml() {
+        let result = parse_blockchains("[[blockchain]]\nname = \"booga\"\n");
+
+        let msg = result.err().unwrap();
+        assert!(msg.contains("missing field"), "{}", msg);
+    }
+
+    fn assert_invalid(blockchain: BlockchainRecord, expected: &str) {
+        assert_eq!(blockchain.validate(), Err(expected.to_string()));
+    }
+
+    #[test]
+    fn validate_complains_about_bad_values() {
+        assert_invalid(record("", 42), "Blockchain name must not be empty");
+        assert_invalid(
+            record("booga", 0),
+            "Blockchain 'booga' has chain_id 0, but it must be between 1 and 9223372036854775771",
+        );
+        assert_invalid(
+            record("booga", 9_223_372_036_854_775_772),
+            "Blockchain 'booga' has chain_id 9223372036854775772, but it must be between 1 and 9223372036854775771",
+        );
+        assert_invalid(
+            BlockchainRecord {
+                contract: "0123456789abcdef0123456789abcdef01234567".to_string(),
+                ..record("booga", 42)
+            },
+            "Blockchain 'booga' has contract '0123456789abcdef0123456789abcdef01234567', but it must be 0x followed by 40 hexadecimal digits",
+        );
+        assert_invalid(
+            BlockchainRecord {
+                contract: "0x0123456789abcdef0123456789abcdef0123456g".to_string(),
+                ..record("booga", 42)
+            },
+            "Blockchain 'booga' has contract '0x0123456789abcdef0123456789abcdef0123456g', but it must be 0x followed by 40 hexadecimal digits",
+        );
+        assert_invalid(
+            BlockchainRecord {
+                token_decimals: 8,
+                ..record("booga", 42)
+            },
+            "Blockchain 'booga' has token_decimals 8, but it must be between 9 and 66",
+        );
+        assert_invalid(
+            BlockchainRecord {
+                token_decimals: 67,
+                ..record("booga", 42)
+            },
+            "Blockchain 'booga' has token_decimals 67, but it must be between 9 and 66",
+        );
+        assert_invalid(
+            BlockchainRecord {
+                batch_contract_opt: Some("0x0123".to_string()),
+                ..record("booga", 42)
+            },
+            "Blockchain 'booga' has batch_contract '0x0123', but it must be 0x followed by 40 hexadecimal digits",
+        );
+        assert_invalid(
+            BlockchainRecord {
+                batch_method: "disperseToken(address[],uint256[])".to_string(),
+                ..record("booga", 42)
+            },
+            "Blockchain 'booga' has batch_method 'disperseToken(address[],uint256[])', but it must be a method name followed by (address,address[],uint256[])",
+        );
+        assert_invalid(
+            BlockchainRecord {
+                batch_method: "(address,address[],uint256[])".to_string(),
+                ..record("booga", 42)
+            },
+            "Blockchain 'booga' has batch_method '(address,address[],uint256[])', but it must be a method name followed by (address,address[],uint256[])",
+        );
+    }
+
+    #[test]
+    fn merge_blockchains_replaces_by_name_and_appends_the_rest() {
+        let base = vec![record("one", 1), record("two", 2)];
+        let overrides = vec![
+            BlockchainRecord {
+                contract_creation_block: 5678,
+                ..record("two", 2)
+            },
+            record("three", 3),
+        ];
+
+        let result = merge_blockchains(base, overrides);
+
+        assert_eq!(
+            result,
+            Ok(vec![
+                record("one", 1),
+                BlockchainRecord {
+                    contract_creation_block: 5678,
+                    ..record("two", 2)
+                },
+                record("three", 3),
+            ])
+        );
+    }
+
+    #[test]
+    fn merge_blockchains_complains_about_duplicate_chain_ids() {
+        let result = merge_blockchains(vec![record("one", 1)], vec![record("uno", 1)]);
+
+        assert_eq!(
+            result,
+            Err(
+                "Blockchain 'uno' has chain_id 1, which another blockchain already uses"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn parse_blockchains_complains_about_duplicate_names() {
+        let result = parse_blockchains(
+            r#"
+            [[blockchain]]
+            name = "booga"
+            chain_id = 42
+            contract = "0x0123456789abcdef0123456789abcdef01234567"
+            contract_creation_block = 1234
+            token_decimals = 18
+
+            [[blockchain]]
+            name = "Booga"
+            chain_id = 43
+            contract = "0x0123456789abcdef0123456789abcdef01234567"
+            contract_creation_block = 1234
+            token_decimals = 18
+        "#,
+        );
+
+        assert_eq!(
+            result,
+            Err("Blockchain name 'booga' is defined more than once".to_string())
+        );
+    }
+
+    #[test]
+    fn load_blockchains_without_a_file_gives_the_built_in_ones() {
+        let result = load_blockchains(None);
+
+        assert_eq!(result, parse_blockchains(BUILT_IN_BLOCKCHAINS));
+    }
+
+    #[test]
+    fn load_blockchains_adds_the_ones_in_the_file() {
+        let dir = ensure_node_home_directory_exists(
+            "blockchains",
+            "load_blockchains_adds_the_ones_in_the_file",
+        );
+        let file = dir.join("blockchains.toml");
+        let mut handle = std::fs::File::create(&file).unwrap();
+        handle
+            .write_all(
+                br#"
+                [[blockchain]]
+                name = "privatenet"
+                chain_id = 42
+                contract = "0x0123456789abcdef0123456789abcdef01234567"
+                contract_creation_block = 1234
+                token_decimals = 18
+            "#,
+            )
+            .unwrap();
+
+        let result = load_blockchains(Some(&file)).unwrap();
+
+        let mut expected = parse_blockchains(BUILT_IN_BLOCKCHAINS).unwrap();
+        expected.push(record("privatenet", 42));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn load_blockchains_complains_about_missing_file() {
+        let file = Path::new("/nonexistent/blockchains.toml");
+
+        let result = load_blockchains(Some(file));
+
+        let msg = result.err().unwrap();
+        assert!(
+            msg.starts_with("Can't read \"/nonexistent/blockchains.toml\": "),
+            "{}",
+            msg
+        );
+    }
+
+    #[test]
+    fn use_blockchains_file_makes_the_blockchains_in_the_file_known() {
+        let dir = ensure_node_home_directory_exists(
+            "blockchains",
+            "use_blockchains_file_makes_the_blockchains_in_the_file_known",
+        );
+        let file = dir.join("blockchains.toml");
+        let mut handle = std::fs::File::create(&file).unwrap();
+        handle
+            .write_all(
+                br#"
+                [[blockchain]]
+                name = "usefilenet"
+                chain_id = 4242
+                contract = "0x0123456789abcdef0123456789abcdef01234567"
+                contract_creation_block = 1234
+                token_decimals = 18
+            "#,
+            )
+            .unwrap();
+
+        let result = use_blockchains_file(&file);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            blockchain_from_name("usefilenet"),
+            Some(&record("usefilenet", 4242))
+        );
+        assert_eq!(blockchain_from_chain_id(4242).unwrap().name, "usefilenet");
+        assert_eq!(check_chain_name("usefilenet"), Ok(()));
+        assert_eq!(blockchain_from_name("mainnet").unwrap().chain_id, 1);
+    }
+
+    #[test]
+    fn use_blockchains_file_reports_a_bad_file_and_keeps_the_blockchains_it_had() {
+        let result = use_blockchains_file(Path::new("/nonexistent/blockchains.toml"));
+
+        let param_errors = result.err().unwrap().param_errors;
+        assert_eq!(param_errors.len(), 1);
+        assert_eq!(param_errors[0].parameter, "blockchains-file");
+        assert!(
+            param_errors[0].reason.starts_with(
+                "Can't load the blockchains file: Can't read \"/nonexistent/blockchains.toml\": "
+            ),
+            "{}",
+            param_errors[0].reason
+        );
+        assert_eq!(blockchain_from_name("dev").unwrap().chain_id, 2);
+    }
+
+    #[test]
+    fn check_chain_name_accepts_known_blockchains() {
+        assert_eq!(check_chain_name("mainnet"), Ok(()));
+        assert_eq!(check_chain_name("dev"), Ok(()));
+    }
+
+    #[test]
+    fn check_chain_name_rejects_unknown_blockchains() {
+        let result = check_chain_name("booga");
+
+        let param_errors = result.err().unwrap().param_errors;
+        assert_eq!(param_errors.len(), 1);
+        assert_eq!(param_errors[0].parameter, "chain");
+        assert!(
+            param_errors[0]
+                .reason
+                .starts_with("Unknown blockchain 'booga': use one of mainnet, dev"),
+            "{}",
+            param_errors[0].reason
+        );
+        assert!(
+            param_errors[0]
+                .reason
+                .ends_with(", or describe it in a --blockchains-file"),
+            "{}",
+            param_errors[0].reason
+        );
+    }
+}
@@ -3,8 +3,14 @@
 pub const HIGHEST_RANDOM_CLANDESTINE_PORT: u16 = 9999;
 pub const HTTP_PORT: u16 = 80;
 pub const TLS_PORT: u16 = 443;
+pub const SOCKS_PORT: u16 = 1080;
 pub const DEFAULT_CHAIN_NAME: &str = "mainnet";
 pub const DEFAULT_GAS_PRICE: &str = "1";
+pub const DEFAULT_MAX_GAS_PRICE: u64 = 200;
+pub const DEFAULT_SCAN_BLOCK_RANGE: u64 = 10_000;
+pub const DEFAULT_CONFIRMATION_DEPTH: u64 = 12;
+pub const DEFAULT_CONSUMING_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
+pub const DEFAULT_EARNING_DERIVATION_PATH: &str = "m/44'/60'/0'/0/1";
 pub const LOWEST_USABLE_INSECURE_PORT: u16 = 1025;
 pub const HIGHEST_USABLE_PORT: u16 = 65535;
 pub const DEFAULT_UI_PORT: u16 = 5333;
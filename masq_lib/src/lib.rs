@@ -6,11 +6,13 @@ pub mod multi_config;
 #[macro_use]
 pub mod messages;
 
+pub mod blockchains;
 pub mod command;
 pub mod constants;
 pub mod crash_point;
 pub mod shared_schema;
 pub mod test_utils;
+pub mod ui_auth;
 pub mod ui_gateway;
 pub mod ui_traffic_converter;
 pub mod utils;
@@ -21,6 +21,12 @@ pub const NODE_ALREADY_RUNNING_ERROR: u64 = 0x8000_0000_0000_0003;
 pub const UNMARSHAL_ERROR: u64 = 0x8000_0000_0000_0004;
 pub const SETUP_ERROR: u64 = 0x8000_0000_0000_0005;
 pub const TIMEOUT_ERROR: u64 = 0x8000_0000_0000_0006;
+pub const UNAUTHORIZED_ERROR: u64 = 0x8000_0000_0000_0007;
+pub const RATE_PACK_ERROR: u64 = 0x8000_0000_0000_0008;
+pub const BAN_ERROR: u64 = 0x8000_0000_0000_0009;
+pub const WALLET_CREATION_ERROR: u64 = 0x8000_0000_0000_000A;
+pub const LOG_FILTERS_ERROR: u64 = 0x8000_0000_0000_000B;
+pub const DB_PASSWORD_ERROR: u64 = 0x8000_0000_0000_000C;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum UiMessageError {
@@ -192,6 +198,75 @@ impl UiCrashRequest {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiGenerateWalletsRequest {
+    #[serde(rename = "dbPassword")]
+    pub db_password: String,
+    #[serde(rename = "mnemonicPhraseSize")]
+    pub mnemonic_phrase_size: usize,
+    #[serde(rename = "mnemonicPhraseLanguage")]
+    pub mnemonic_phrase_language: String,
+    #[serde(rename = "mnemonicPassphraseOpt")]
+    pub mnemonic_passphrase_opt: Option<String>,
+    #[serde(rename = "consumingDerivationPath")]
+    pub consuming_derivation_path: String,
+    #[serde(rename = "earningDerivationPath")]
+    pub earning_derivation_path: String,
+}
+conversation_message!(UiGenerateWalletsRequest, "generateWallets");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiGenerateWalletsResponse {
+    #[serde(rename = "mnemonicPhrase")]
+    pub mnemonic_phrase: Vec<String>,
+    #[serde(rename = "consumingWalletAddress")]
+    pub consuming_wallet_address: String,
+    #[serde(rename = "earningWalletAddress")]
+    pub earning_wallet_address: String,
+}
+conversation_message!(UiGenerateWalletsResponse, "generateWallets");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiRecoverWalletsRequest {
+    #[serde(rename = "dbPassword")]
+    pub db_password: String,
+    #[serde(rename = "mnemonicPhrase")]
+    pub mnemonic_phrase: Vec<String>,
+    #[serde(rename = "mnemonicPhraseLanguage")]
+    pub mnemonic_phrase_language: String,
+    #[serde(rename = "mnemonicPassphraseOpt")]
+    pub mnemonic_passphrase_opt: Option<String>,
+    #[serde(rename = "consumingDerivationPath")]
+    pub consuming_derivation_path: String,
+    // Either a derivation path or an Ethereum address
+    #[serde(rename = "earningWallet")]
+    pub earning_wallet: String,
+}
+conversation_message!(UiRecoverWalletsRequest, "recoverWallets");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiRecoverWalletsResponse {
+    #[serde(rename = "consumingWalletAddress")]
+    pub consuming_wallet_address: String,
+    #[serde(rename = "earningWalletAddress")]
+    pub earning_wallet_address: String,
+}
+conversation_message!(UiRecoverWalletsResponse, "recoverWallets");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiChangePasswordRequest {
+    // None only if the database has never had a password
+    #[serde(rename = "oldPasswordOpt")]
+    pub old_password_opt: Option<String>,
+    #[serde(rename = "newPassword")]
+    pub new_password: String,
+}
+conversation_message!(UiChangePasswordRequest, "changePassword");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiChangePasswordResponse {}
+conversation_message!(UiChangePasswordResponse, "changePassword");
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct UiSetupRequestValue {
     pub name: String,
@@ -405,6 +480,37 @@ fire_and_forget_message!(UiRedirect, "redirect");
 // These messages are sent to or by both the Daemon and the Node
 ///////////////////////////////////////////////////////////////////
 
+// A UI that has never subscribed hears every broadcast; once it subscribes, it hears only
+// broadcasts whose opcodes it has subscribed to. A UI that unsubscribes before it has ever
+// subscribed goes on hearing every broadcast except the ones it unsubscribed from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiSubscribeRequest {
+    pub opcodes: Vec<String>,
+}
+conversation_message!(UiSubscribeRequest, "subscribe");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiSubscribeResponse {
+    pub opcodes: Vec<String>,
+    #[serde(rename = "exceptOpcodesOpt")]
+    pub except_opcodes_opt: Option<Vec<String>>,
+}
+conversation_message!(UiSubscribeResponse, "subscribe");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiUnsubscribeRequest {
+    pub opcodes: Vec<String>,
+}
+conversation_message!(UiUnsubscribeRequest, "unsubscribe");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiUnsubscribeResponse {
+    pub opcodes: Vec<String>,
+    #[serde(rename = "exceptOpcodesOpt")]
+    pub except_opcodes_opt: Option<Vec<String>>,
+}
+conversation_message!(UiUnsubscribeResponse, "unsubscribe");
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct UiUnmarshalError {
     pub message: String,
@@ -417,6 +523,61 @@ fire_and_forget_message!(UiUnmarshalError, "unmarshalError");
 // These messages are sent to or by the Node only
 ///////////////////////////////////////////////////////////////////
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiBanRequest {
+    pub wallet: String,
+    pub reason: String,
+}
+conversation_message!(UiBanRequest, "ban");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiBanResponse {}
+conversation_message!(UiBanResponse, "ban");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiBanListRequest {}
+conversation_message!(UiBanListRequest, "banList");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiBannedWallet {
+    pub wallet: String,
+    pub reason: String,
+    #[serde(rename = "bannedTimestamp")]
+    pub banned_timestamp: u64,
+    pub manual: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiPardonedWallet {
+    pub wallet: String,
+    #[serde(rename = "pardonedTimestamp")]
+    pub pardoned_timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiBanListResponse {
+    pub bans: Vec<UiBannedWallet>,
+    pub pardons: Vec<UiPardonedWallet>,
+}
+conversation_message!(UiBanListResponse, "banList");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiChangeRatePackRequest {
+    #[serde(rename = "routingByteRate")]
+    pub routing_byte_rate: u64,
+    #[serde(rename = "routingServiceRate")]
+    pub routing_service_rate: u64,
+    #[serde(rename = "exitByteRate")]
+    pub exit_byte_rate: u64,
+    #[serde(rename = "exitServiceRate")]
+    pub exit_service_rate: u64,
+}
+conversation_message!(UiChangeRatePackRequest, "changeRatePack");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UiChangeRatePackResponse {}
+conversation_message!(UiChangeRatePackResponse, "changeRatePack");
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct UiDescriptorRequest {}
 conversation_message!(UiDescriptorRequest, "descriptor");
@@ -468,6 +629,75 @@ pub struct UiFinancialsResponse {
 }
 conversation_message!(UiFinancialsResponse, "financials");
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiLogFilter {
+    pub component: String,
+    pub level: String,
+}
+
+// Leave out logLevelOpt or filtersOpt to keep what's there; leave out both to just ask
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiLogFiltersRequest {
+    #[serde(rename = "logLevelOpt")]
+    pub log_level_opt: Option<String>,
+    #[serde(rename = "filtersOpt")]
+    pub filters_opt: Option<Vec<UiLogFilter>>,
+}
+conversation_message!(UiLogFiltersRequest, "logFilters");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiLogFiltersResponse {
+    #[serde(rename = "logLevel")]
+    pub log_level: String,
+    pub filters: Vec<UiLogFilter>,
+}
+conversation_message!(UiLogFiltersResponse, "logFilters");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiNeighborhoodRequest {}
+conversation_message!(UiNeighborhoodRequest, "neighborhood");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiRatePack {
+    #[serde(rename = "routingByteRate")]
+    pub routing_byte_rate: u64,
+    #[serde(rename = "routingServiceRate")]
+    pub routing_service_rate: u64,
+    #[serde(rename = "exitByteRate")]
+    pub exit_byte_rate: u64,
+    #[serde(rename = "exitServiceRate")]
+    pub exit_service_rate: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiNeighborhoodNode {
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+    #[serde(rename = "nodeAddrOpt")]
+    pub node_addr_opt: Option<String>,
+    pub version: u32,
+    #[serde(rename = "ratePack")]
+    pub rate_pack: UiRatePack,
+    pub desirable: bool,
+    #[serde(rename = "acceptsConnections")]
+    pub accepts_connections: bool,
+    #[serde(rename = "routesData")]
+    pub routes_data: bool,
+    #[serde(rename = "fullNeighbors")]
+    pub full_neighbors: Vec<String>,
+    #[serde(rename = "halfNeighbors")]
+    pub half_neighbors: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiNeighborhoodResponse {
+    // The local Node comes first; the rest follow in order of public key.
+    pub nodes: Vec<UiNeighborhoodNode>,
+    #[serde(rename = "dotGraph")]
+    pub dot_graph: String,
+}
+conversation_message!(UiNeighborhoodResponse, "neighborhood");
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct UiShutdownRequest {}
 conversation_message!(UiShutdownRequest, "shutdown");
@@ -476,6 +706,19 @@ conversation_message!(UiShutdownRequest, "shutdown");
 pub struct UiShutdownResponse {}
 conversation_message!(UiShutdownResponse, "shutdown");
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiUnbanRequest {
+    pub wallet: String,
+}
+conversation_message!(UiUnbanRequest, "unban");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct UiUnbanResponse {
+    #[serde(rename = "wasBanned")]
+    pub was_banned: bool,
+}
+conversation_message!(UiUnbanResponse, "unban");
+
 #[cfg(test)]
 mod tests {
     use super::*;
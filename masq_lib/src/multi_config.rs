@@ -111,7 +111,7 @@ impl<'a> MultiConfig<'a> {
         })
     }
 
-    pub fn arg_matches(&'a self) -> &ArgMatches<'a> {
+    pub fn arg_matches(&self) -> &ArgMatches<'a> {
         &self.arg_matches
     }
 
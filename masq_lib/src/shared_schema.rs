@@ -1,18 +1,36 @@
 use crate::constants::{
-    DEFAULT_CHAIN_NAME, DEFAULT_GAS_PRICE, DEFAULT_UI_PORT, HIGHEST_USABLE_PORT,
-    LOWEST_USABLE_INSECURE_PORT,
+    DEFAULT_CONFIRMATION_DEPTH, DEFAULT_GAS_PRICE, DEFAULT_MAX_GAS_PRICE, DEFAULT_SCAN_BLOCK_RANGE,
+    DEFAULT_UI_PORT, HIGHEST_USABLE_PORT, LOWEST_USABLE_INSECURE_PORT,
 };
 use crate::crash_point::CrashPoint;
 use clap::{App, Arg};
 use lazy_static::lazy_static;
 
+pub const BATCH_PAYMENTS_HELP: &str =
+    "Whether your MASQ Node should pay its creditors several at a time, in one transaction through \
+     the batch contract of the --chain, rather than in a transaction apiece. That saves most of the \
+     cost of each transaction after the first, but only once your consuming wallet has approved the \
+     batch contract to spend its tokens; until it has, or if the --chain has no batch contract, each \
+     creditor is paid separately.";
+pub const BLOCKCHAINS_FILE_HELP: &str =
+    "A TOML file describing blockchain networks to use besides the built-in ones (mainnet and dev), \
+     or in place of built-in ones with the same names. Each network can then be chosen with --chain. \
+     See the README for the format.";
 pub const BLOCKCHAIN_SERVICE_HELP: &str =
     "The Ethereum client you wish to use to provide Blockchain \
      exit services from your MASQ Node (e.g. http://localhost:8545, \
-     https://ropsten.infura.io/v3/YOUR-PROJECT-ID, https://mainnet.infura.io/v3/YOUR-PROJECT-ID).";
+     https://mainnet.infura.io/v3/YOUR-PROJECT-ID). \
+     To have a fallback when a client stops answering, list several URLs separated by commas; see \
+     --blockchain-service-failover.";
+pub const BLOCKCHAIN_SERVICE_FAILOVER_HELP: &str =
+    "How MASQ Node picks among the Ethereum clients listed in --blockchain-service-url. With \
+     'priority', it uses the first one that's working; with 'round-robin', it takes turns among the \
+     ones that are working. A client that fails several calls in a row is left alone for a minute, and \
+     calls that only read from the blockchain are retried on another client when one fails.";
 pub const CHAIN_HELP: &str =
     "The blockchain network MASQ Node will configure itself to use. You must ensure the \
-    Ethereum client specified by --blockchain-service-url communicates with the same blockchain network.";
+    Ethereum client specified by --blockchain-service-url communicates with the same blockchain network. \
+    Built in are mainnet and dev; to use another network, describe it in a --blockchains-file.";
 pub const CONFIG_FILE_HELP: &str =
     "Optional TOML file containing configuration that doesn't often change. Should contain only \
      scalar items, string or numeric, whose names are exactly the same as the command-line parameters \
@@ -32,8 +50,9 @@ pub const DATA_DIRECTORY_HELP: &str =
      least its database and by default its configuration file as well.";
 pub const DB_PASSWORD_HELP: &str =
     "A password or phrase to decrypt the encrypted material in the database, to include your \
-     mnemonic seed (if applicable) and your list of previous neighbors. If you don't provide this \
-     password, none of the encrypted data in your database will be used.";
+     mnemonic seed (if applicable), your Node's private key, and your list of previous neighbors. \
+     If you don't provide this password, none of the encrypted data in your database will be used, \
+     and your Node will have a different public key every time it starts.";
 pub const DNS_SERVERS_HELP: &str =
     "IP addresses of DNS Servers for host name look-up while providing exit \
      services for other MASQ Nodes (e.g. 1.0.0.1,1.1.1.1,8.8.8.8,9.9.9.9, etc.)";
@@ -42,6 +61,22 @@ pub const EARNING_WALLET_HELP: &str =
      (case-insensitive). If you already have a derivation-path earning wallet, don't supply this. \
      If you have supplied an earning wallet address before, either don't supply it again or be \
      careful to supply exactly the same one you supplied before.";
+pub const EXIT_POLICY_HELP: &str =
+    "Rules deciding where your MASQ Node will connect while providing exit services for other MASQ \
+     Nodes, separated by commas. Each rule is 'allow' or 'deny' followed by a target: '*', a host \
+     name that may contain '*' wildcards, an IP address, or a CIDR network (IPv6 in brackets, e.g. \
+     [fd00::]/8). A target may be followed by a colon and a port or port range (e.g. 'deny *:25, \
+     allow *.example.com:8000-8999, deny 203.0.113.0/24'). The first rule that matches decides; if \
+     none matches, the connection is allowed.";
+pub const EXIT_PORTS_HELP: &str =
+    "Ports to which your MASQ Node will connect while providing exit services for other MASQ Nodes, \
+     separated by commas (e.g. 80,443,993). If you don't specify any, your Node will connect to any \
+     port the originating Node asks for.";
+pub const EXIT_PRIVATE_RANGES_HELP: &str =
+    "Whether your MASQ Node should refuse to connect to private, loopback, link-local and multicast \
+     addresses while providing exit services for other MASQ Nodes. These addresses lead into your own \
+     network rather than out to the Internet, so unless you're testing, you should leave this at the \
+     default, and no --exit-policy rule can override it.";
 pub const IP_ADDRESS_HELP: &str = "The public IP address of your MASQ Node: that is, the IPv4 \
      address at which other Nodes can contact yours. If you're running your Node behind \
      a router, this will be the IP address of the router. If this IP address starts with 192.168 or 10.0, \
@@ -53,6 +88,12 @@ pub const LOG_LEVEL_HELP: &str =
      generates a lot of log traffic. This will both consume your disk space and degrade your Node's performance. \
      You should probably not specify a level higher than the default unless you have security concerns about \
      persistent logs being kept on your computer: if your Node crashes, it's good to know why.";
+pub const LOG_FILTERS_HELP: &str =
+    "Overrides --log-level for particular Node components, so that you can hear more from the one you're \
+     interested in and less from the rest. Give each component the name it logs under, followed by '=' and \
+     a level, and separate them with commas (but no spaces): for example, Neighborhood=debug,Hopper=warn. \
+     A filter applies to every component whose name begins with it, but a longer filter wins over a shorter \
+     one. You can change these filters on a running Node from the UI, without restarting it.";
 pub const NEIGHBORS_HELP: &str = "One or more Node descriptors for running Nodes in the MASQ \
      Network to which you'd like your Node to connect on startup. A Node descriptor looks like \
      this:\n\ngBviQbjOS3e5ReFQCvIhUM3i02d1zPleo1iXg/EN6zQ:86.75.30.9:5542 (initial ':' for testnet) and\n\
@@ -80,12 +121,27 @@ pub const NEIGHBORHOOD_MODE_HELP: &str = "This configures the way the Node relat
      standard means that your Node will operate fully unconstrained, both originating and accepting \
      connections, both consuming and providing services, and when you operate behind a router, it \
      requires that you forward your clandestine port through that router to your Node's machine.";
+pub const RATE_PACK_HELP: &str =
+    "The rates your MASQ Node will advertise to other Nodes, and charge them, for the services it \
+     provides: the routing byte rate, routing service rate, exit byte rate and exit service rate, \
+     separated by '|' (e.g. 100|10000|101|10001). Byte rates are charged for each byte of payload \
+     and service rates once for each CORES package. --rate-pack is meaningless in \
+     --neighborhood-mode zero-hop and consume-only, where your Node provides no services. \
+     [default: 100|10000|101|10001]";
 pub const REAL_USER_HELP: &str =
     "The user whose identity Node will assume when dropping privileges after bootstrapping. Since Node refuses to \
      run with root privilege after bootstrapping, you might want to use this if you start the Node as root, or if \
      you start the Node using pkexec or some other method that doesn't populate the SUDO_xxx variables. Use a value \
      like <uid>:<gid>:<home directory>.";
 
+pub const ROUTE_COST_BUDGET_HELP: &str =
+    "The most your MASQ Node will let a route segment (over or back) cost, summed across every Node \
+     in it that expects to be paid: the maximum byte rate and maximum service rate, separated by '|' \
+     (e.g. 1000|100000). Your Node will not use a route that would cost more than this, and will \
+     refuse requests for which it can find no route within budget. [default: unlimited]";
+
+pub const LOG_LEVELS: &[&str] = &["off", "error", "warn", "info", "debug", "trace"];
+
 lazy_static! {
     pub static ref DEFAULT_UI_PORT_VALUE: String = DEFAULT_UI_PORT.to_string();
     pub static ref UI_PORT_HELP: String = format!(
@@ -102,10 +158,39 @@ lazy_static! {
          Must be between {} and {} [default: last used port]",
         LOWEST_USABLE_INSECURE_PORT, HIGHEST_USABLE_PORT
     );
+    pub static ref METRICS_PORT_HELP: String = format!(
+        "The port on localhost at which the Node will serve Prometheus-style metrics over HTTP, at \
+         /metrics, for monitoring tools to scrape. If you don't specify a metrics port, the Node \
+         serves no metrics. Must be between {} and {}.",
+        LOWEST_USABLE_INSECURE_PORT, HIGHEST_USABLE_PORT
+    );
     pub static ref GAS_PRICE_HELP: String = format!(
-       "The Gas Price is the amount of Gwei you will pay per unit of gas used in a transaction. \
+       "The Gas Price is the amount of Gwei you will pay per unit of gas used in a transaction when the Ethereum \
+       client can't say what recent transactions have paid. \
        If left unspecified, MASQ Node will use the previously stored value (Default {}). Valid range is 1-99 Gwei.",
        DEFAULT_GAS_PRICE);
+    pub static ref MAX_GAS_PRICE_HELP: String = format!(
+        "The most Gwei MASQ Node will pay per unit of gas used in a transaction. It offers what recent \
+         transactions have paid, as EIP-1559 fees on chains that have them, and this keeps a busy blockchain \
+         from running up your costs; a payment that would cost more waits longer to be mined instead. \
+         [default: {}]",
+        DEFAULT_MAX_GAS_PRICE
+    );
+    pub static ref SCAN_BLOCK_RANGE_HELP: String = format!(
+        "The most blocks MASQ Node will ask the Ethereum client about at once when it looks for payments \
+         to your earning wallet. It catches up on a long absence a chunk at a time, remembering its progress \
+         after each chunk. Many hosted clients refuse to search too many blocks at once; if yours does, MASQ \
+         Node searches fewer at a time until it's satisfied, so you only need this to start smaller. \
+         [default: {}]",
+        DEFAULT_SCAN_BLOCK_RANGE
+    );
+    pub static ref CONFIRMATION_DEPTH_HELP: String = format!(
+        "How many blocks must be mined on top of a payment to your earning wallet before MASQ Node credits \
+         it. A payment in a block that's later dropped from the blockchain in a reorganization never \
+         happened; the deeper a block is buried, the less likely that is. If a block MASQ Node has already \
+         credited a payment from disappears anyway, it takes the credit back. [default: {}]",
+        DEFAULT_CONFIRMATION_DEPTH
+    );
 }
 
 // These Args are needed in more than one clap schema. To avoid code duplication, they're defined here and referred
@@ -138,10 +223,19 @@ pub fn chain_arg<'a>() -> Arg<'a, 'a> {
         .value_name("CHAIN")
         .min_values(0)
         .max_values(1)
-        .possible_values(&["dev", DEFAULT_CHAIN_NAME, "ropsten", "rinkeby"])
         .help(CHAIN_HELP)
 }
 
+pub fn blockchains_file_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("blockchains-file")
+        .long("blockchains-file")
+        .value_name("FILE-PATH")
+        .min_values(0)
+        .max_values(1)
+        .empty_values(false)
+        .help(BLOCKCHAINS_FILE_HELP)
+}
+
 pub fn db_password_arg(help: &str) -> Arg {
     Arg::with_name("db-password")
         .long("db-password")
@@ -202,6 +296,26 @@ pub fn ui_port_arg(help: &str) -> Arg {
 
 pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
     head.arg(
+        Arg::with_name("batch-payments")
+            .long("batch-payments")
+            .value_name("BATCH-PAYMENTS")
+            .min_values(0)
+            .max_values(1)
+            .possible_values(&["off", "on"])
+            .case_insensitive(true)
+            .help(BATCH_PAYMENTS_HELP),
+    )
+    .arg(
+        Arg::with_name("blockchain-service-failover")
+            .long("blockchain-service-failover")
+            .value_name("POLICY")
+            .min_values(0)
+            .max_values(1)
+            .possible_values(&["priority", "round-robin"])
+            .case_insensitive(true)
+            .help(BLOCKCHAIN_SERVICE_FAILOVER_HELP),
+    )
+    .arg(
         Arg::with_name("blockchain-service-url")
             .long("blockchain-service-url")
             .empty_values(false)
@@ -210,6 +324,7 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
             .max_values(1)
             .help(BLOCKCHAIN_SERVICE_HELP),
     )
+    .arg(blockchains_file_arg())
     .arg(
         Arg::with_name("clandestine-port")
             .long("clandestine-port")
@@ -220,6 +335,15 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
             .help(&CLANDESTINE_PORT_HELP),
     )
     .arg(config_file_arg())
+    .arg(
+        Arg::with_name("confirmation-depth")
+            .long("confirmation-depth")
+            .value_name("BLOCKS")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_confirmation_depth)
+            .help(&CONFIRMATION_DEPTH_HELP),
+    )
     .arg(
         Arg::with_name("consuming-private-key")
             .long("consuming-private-key")
@@ -255,6 +379,33 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
         common_validators::validate_ethereum_address,
     ))
     .arg(chain_arg())
+    .arg(
+        Arg::with_name("exit-policy")
+            .long("exit-policy")
+            .value_name("EXIT-POLICY")
+            .min_values(0)
+            .max_values(1)
+            .help(EXIT_POLICY_HELP),
+    )
+    .arg(
+        Arg::with_name("exit-ports")
+            .long("exit-ports")
+            .value_name("EXIT-PORTS")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_exit_ports)
+            .help(EXIT_PORTS_HELP),
+    )
+    .arg(
+        Arg::with_name("exit-private-ranges")
+            .long("exit-private-ranges")
+            .value_name("EXIT-PRIVATE-RANGES")
+            .min_values(0)
+            .max_values(1)
+            .possible_values(&["block", "allow"])
+            .case_insensitive(true)
+            .help(EXIT_PRIVATE_RANGES_HELP),
+    )
     .arg(
         Arg::with_name("fake-public-key")
             .long("fake-public-key")
@@ -287,10 +438,37 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
             .value_name("FILTER")
             .min_values(0)
             .max_values(1)
-            .possible_values(&["off", "error", "warn", "info", "debug", "trace"])
+            .possible_values(LOG_LEVELS)
             .case_insensitive(true)
             .help(LOG_LEVEL_HELP),
     )
+    .arg(
+        Arg::with_name("log-filters")
+            .long("log-filters")
+            .value_name("LOG-FILTERS")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_log_filters)
+            .help(LOG_FILTERS_HELP),
+    )
+    .arg(
+        Arg::with_name("max-gas-price")
+            .long("max-gas-price")
+            .value_name("MAX-GAS-PRICE")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_max_gas_price)
+            .help(&MAX_GAS_PRICE_HELP),
+    )
+    .arg(
+        Arg::with_name("metrics-port")
+            .long("metrics-port")
+            .value_name("METRICS-PORT")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_ui_port)
+            .help(&METRICS_PORT_HELP),
+    )
     .arg(
         Arg::with_name("neighborhood-mode")
             .long("neighborhood-mode")
@@ -308,11 +486,39 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
             .min_values(0)
             .help(NEIGHBORS_HELP),
     )
+    .arg(
+        Arg::with_name("rate-pack")
+            .long("rate-pack")
+            .value_name("RATE-PACK")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_rate_pack)
+            .help(RATE_PACK_HELP),
+    )
     .arg(real_user_arg())
+    .arg(
+        Arg::with_name("route-cost-budget")
+            .long("route-cost-budget")
+            .value_name("ROUTE-COST-BUDGET")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_route_cost_budget)
+            .help(ROUTE_COST_BUDGET_HELP),
+    )
+    .arg(
+        Arg::with_name("scan-block-range")
+            .long("scan-block-range")
+            .value_name("BLOCKS")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_scan_block_range)
+            .help(&SCAN_BLOCK_RANGE_HELP),
+    )
 }
 
 pub mod common_validators {
     use crate::constants::LOWEST_USABLE_INSECURE_PORT;
+    use crate::shared_schema::LOG_LEVELS;
     use regex::Regex;
     use std::net::IpAddr;
     use std::str::FromStr;
@@ -342,6 +548,65 @@ pub mod common_validators {
         }
     }
 
+    pub fn validate_exit_ports(ports: String) -> Result<(), String> {
+        let errors = ports
+            .split(',')
+            .flat_map(|port| match port.parse::<u16>() {
+                Ok(port) if port > 0 => None,
+                _ => Some(format!("{:?}", port)),
+            })
+            .collect::<Vec<String>>()
+            .join(";");
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn validate_log_filters(filters: String) -> Result<(), String> {
+        let component_regex =
+            Regex::new("^[A-Za-z0-9_]+$").expect("Failed to compile regular expression");
+        let errors = filters
+            .split(',')
+            .filter(|filter| {
+                let pieces = filter.split('=').collect::<Vec<&str>>();
+                match pieces[..] {
+                    [component, level] => {
+                        !component_regex.is_match(component)
+                            || !LOG_LEVELS.iter().any(|l| l.eq_ignore_ascii_case(level))
+                    }
+                    _ => true,
+                }
+            })
+            .map(|filter| format!("{:?}", filter))
+            .collect::<Vec<String>>()
+            .join(";");
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn validate_rate_pack(rate_pack: String) -> Result<(), String> {
+        let rates = rate_pack.split('|').collect::<Vec<&str>>();
+        if rates.len() == 4 && rates.iter().all(|rate| rate.parse::<u64>().is_ok()) {
+            Ok(())
+        } else {
+            Err(rate_pack)
+        }
+    }
+
+    pub fn validate_route_cost_budget(route_cost_budget: String) -> Result<(), String> {
+        let rates = route_cost_budget.split('|').collect::<Vec<&str>>();
+        if rates.len() == 2 && rates.iter().all(|rate| rate.parse::<u64>().is_ok()) {
+            Ok(())
+        } else {
+            Err(route_cost_budget)
+        }
+    }
+
     pub fn validate_clandestine_port(clandestine_port: String) -> Result<(), String> {
         match clandestine_port.parse::<u16>() {
             Ok(clandestine_port) if clandestine_port >= LOWEST_USABLE_INSECURE_PORT => Ok(()),
@@ -367,6 +632,27 @@ pub mod common_validators {
         }
     }
 
+    pub fn validate_max_gas_price(gas_price: String) -> Result<(), String> {
+        match gas_price.parse::<u64>() {
+            Ok(gp) if gp > 0 => Ok(()),
+            _ => Err(gas_price),
+        }
+    }
+
+    pub fn validate_scan_block_range(blocks: String) -> Result<(), String> {
+        match blocks.parse::<u64>() {
+            Ok(count) if count > 0 => Ok(()),
+            _ => Err(blocks),
+        }
+    }
+
+    pub fn validate_confirmation_depth(blocks: String) -> Result<(), String> {
+        match blocks.parse::<u64>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(blocks),
+        }
+    }
+
     pub fn validate_earning_wallet(value: String) -> Result<(), String> {
         validate_ethereum_address(value.clone()).or_else(|_| validate_derivation_path(value))
     }
@@ -546,6 +832,68 @@ mod tests {
         assert_eq!(Ok(()), result);
     }
 
+    #[test]
+    fn validate_scan_block_range_rejects_zero_and_non_numbers() {
+        vec!["0", "-5", "booga", ""].into_iter().for_each(|blocks| {
+            assert_eq!(
+                common_validators::validate_scan_block_range(blocks.to_string()),
+                Err(blocks.to_string())
+            )
+        });
+    }
+
+    #[test]
+    fn validate_scan_block_range_accepts_positive_counts() {
+        vec!["1", "2000", "10000"].into_iter().for_each(|blocks| {
+            assert_eq!(
+                common_validators::validate_scan_block_range(blocks.to_string()),
+                Ok(())
+            )
+        });
+    }
+
+    #[test]
+    fn validate_confirmation_depth_rejects_non_numbers() {
+        vec!["-5", "booga", ""].into_iter().for_each(|blocks| {
+            assert_eq!(
+                common_validators::validate_confirmation_depth(blocks.to_string()),
+                Err(blocks.to_string())
+            )
+        });
+    }
+
+    #[test]
+    fn validate_confirmation_depth_accepts_counts_including_zero() {
+        vec!["0", "12", "100"].into_iter().for_each(|blocks| {
+            assert_eq!(
+                common_validators::validate_confirmation_depth(blocks.to_string()),
+                Ok(())
+            )
+        });
+    }
+
+    #[test]
+    fn validate_max_gas_price_rejects_zero_and_non_numbers() {
+        vec!["0", "-5", "booga", ""]
+            .into_iter()
+            .for_each(|gas_price| {
+                assert_eq!(
+                    common_validators::validate_max_gas_price(gas_price.to_string()),
+                    Err(gas_price.to_string())
+                )
+            });
+    }
+
+    #[test]
+    fn validate_max_gas_price_accepts_positive_prices_beyond_the_gas_price_range() {
+        vec!["1", "99", "500"].into_iter().for_each(|gas_price| {
+            assert_eq!(
+                common_validators::validate_max_gas_price(gas_price.to_string()),
+                Ok(())
+            )
+        });
+    }
+
     #[test]
     fn validate_clandestine_port_rejects_badly_formatted_port_number() {
         let result = common_validators::validate_clandestine_port(String::from("booga"));
@@ -575,6 +923,85 @@ mod tests {
         assert_eq!(Ok(()), result);
     }
 
+    #[test]
+    fn validate_rate_pack_accepts_four_rates() {
+        let result = common_validators::validate_rate_pack(String::from("100|10000|0|10001"));
+
+        assert_eq!(Ok(()), result);
+    }
+
+    #[test]
+    fn validate_rate_pack_rejects_wrong_number_of_rates() {
+        let result = common_validators::validate_rate_pack(String::from("100|10000|101"));
+
+        assert_eq!(Err(String::from("100|10000|101")), result);
+    }
+
+    #[test]
+    fn validate_rate_pack_rejects_rates_that_are_not_whole_numbers() {
+        let result = common_validators::validate_rate_pack(String::from("100|-1|101|booga"));
+
+        assert_eq!(Err(String::from("100|-1|101|booga")), result);
+    }
+
+    #[test]
+    fn validate_route_cost_budget_accepts_two_rates() {
+        let result = common_validators::validate_route_cost_budget(String::from("1000|100000"));
+
+        assert_eq!(Ok(()), result);
+    }
+
+    #[test]
+    fn validate_route_cost_budget_rejects_wrong_number_of_rates() {
+        let result = common_validators::validate_route_cost_budget(String::from("1000"));
+
+        assert_eq!(Err(String::from("1000")), result);
+    }
+
+    #[test]
+    fn validate_route_cost_budget_rejects_rates_that_are_not_whole_numbers() {
+        let result = common_validators::validate_route_cost_budget(String::from("1000|1.5"));
+
+        assert_eq!(Err(String::from("1000|1.5")), result);
+    }
+
+    #[test]
+    fn validate_exit_ports_accepts_comma_separated_ports() {
+        let result = common_validators::validate_exit_ports(String::from("22,443,8443"));
+
+        assert_eq!(Ok(()), result);
+    }
+
+    #[test]
+    fn validate_exit_ports_reports_every_bad_port() {
+        let result = common_validators::validate_exit_ports(String::from("0,443,booga,65536"));
+
+        assert_eq!(Err(String::from("\"0\";\"booga\";\"65536\"")), result);
+    }
+
+    #[test]
+    fn validate_log_filters_accepts_comma_separated_filters() {
+        let result = common_validators::validate_log_filters(String::from(
+            "Neighborhood=debug,Hopper=WARN,proxy_client=off",
+        ));
+
+        assert_eq!(Ok(()), result);
+    }
+
+    #[test]
+    fn validate_log_filters_reports_every_bad_filter() {
+        let result = common_validators::validate_log_filters(String::from(
+            "Neighborhood=debug,Hopper,Proxy Server=info,Accountant=loud,=trace,A=b=c",
+        ));
+
+        assert_eq!(
+            Err(String::from(
+                "\"Hopper\";\"Proxy Server=info\";\"Accountant=loud\";\"=trace\";\"A=b=c\""
+            )),
+            result
+        );
+    }
+
     #[test]
     fn validate_gas_price_zero() {
         let result = common_validators::validate_gas_price("0".to_string());
@@ -584,7 +1011,7 @@ mod tests {
     }
 
     #[test]
-    fn validate_gas_price_normal_ropsten() {
+    fn validate_gas_price_normal_dev() {
         let result = common_validators::validate_gas_price("2".to_string());
 
         assert!(result.is_ok());
@@ -1,6 +1,7 @@
 // Copyright (c) 2019-2020, MASQ (https://masq.ai). All rights reserved.
 
 use crate::messages::{FromMessageBody, ToMessageBody, UiMessageError};
+use crate::ui_auth::with_ui_auth_token;
 use crate::ui_gateway::MessageTarget::ClientId;
 use crate::ui_traffic_converter::UiTrafficConverter;
 use crate::utils::localhost;
@@ -16,11 +17,27 @@ pub struct UiConnection {
 
 impl UiConnection {
     pub fn make(port: u16, protocol: &str) -> Result<UiConnection, String> {
+        Self::connect(port, protocol, None)
+    }
+
+    pub fn make_authenticated(
+        port: u16,
+        protocol: &str,
+        token: &str,
+    ) -> Result<UiConnection, String> {
+        Self::connect(port, protocol, Some(token))
+    }
+
+    fn connect(port: u16, protocol: &str, token_opt: Option<&str>) -> Result<UiConnection, String> {
         let client_builder =
             match ClientBuilder::new(format!("ws://{}:{}", localhost(), port).as_str()) {
                 Ok(cb) => cb,
                 Err(e) => return Err(format!("{:?}", e)),
             };
+        let client_builder = match token_opt {
+            Some(token) => with_ui_auth_token(client_builder, token),
+            None => client_builder,
+        };
         let client = match client_builder.add_protocol(protocol).connect_insecure() {
             Ok(c) => c,
             Err(e) => return Err(format!("{:?}", e)),
@@ -35,6 +52,10 @@ impl UiConnection {
         Self::make(port, protocol).unwrap()
     }
 
+    pub fn new_authenticated(port: u16, protocol: &str, token: &str) -> UiConnection {
+        Self::make_authenticated(port, protocol, token).unwrap()
+    }
+
     pub fn send<T: ToMessageBody>(&mut self, payload: T) {
         let context_id = self.context_id;
         self.context_id += 1;
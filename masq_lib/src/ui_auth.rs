@@ -0,0 +1,104 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use websocket::header::{Authorization, Bearer, Headers};
+use websocket::ClientBuilder;
+
+pub const UI_AUTH_TOKEN_FILE: &str = "ui-auth-token";
+
+// The token lives in the MASQ directory under the real user's local data directory, beside the
+// Daemon's log, rather than in a chain-specific Node data directory: one token serves the Daemon
+// and every Node it launches.
+pub fn ui_auth_token_path(masq_directory: &Path) -> PathBuf {
+    masq_directory.join(UI_AUTH_TOKEN_FILE)
+}
+
+pub fn default_ui_auth_token_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|data_dir| ui_auth_token_path(&data_dir.join("MASQ")))
+}
+
+pub fn read_ui_auth_token(path: &Path) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) if !contents.trim().is_empty() => Some(contents.trim().to_string()),
+        _ => None,
+    }
+}
+
+pub fn default_ui_auth_token() -> Option<String> {
+    default_ui_auth_token_path().and_then(|path| read_ui_auth_token(&path))
+}
+
+pub fn with_ui_auth_token<'a>(builder: ClientBuilder<'a>, token: &str) -> ClientBuilder<'a> {
+    let mut headers = Headers::new();
+    headers.set(Authorization(Bearer {
+        token: token.to_string(),
+    }));
+    builder.custom_headers(&headers)
+}
+
+// If this user has no token, the connection is made anyway; the server will allow it only the
+// opcodes that don't require authentication.
+pub fn with_default_ui_auth_token(builder: ClientBuilder) -> ClientBuilder {
+    match default_ui_auth_token() {
+        Some(token) => with_ui_auth_token(builder, &token),
+        None => builder,
+    }
+}
+
+pub fn ui_auth_token_from_headers(headers: &Headers) -> Option<String> {
+    headers
+        .get::<Authorization<Bearer>>()
+        .map(|authorization| authorization.0.token.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::utils::ensure_node_home_directory_exists;
+
+    #[test]
+    fn ui_auth_token_is_read_without_surrounding_whitespace() {
+        let home_dir = ensure_node_home_directory_exists(
+            "ui_auth",
+            "ui_auth_token_is_read_without_surrounding_whitespace",
+        );
+        let path = ui_auth_token_path(&home_dir);
+        fs::write(&path, "  booga\n").unwrap();
+
+        let result = read_ui_auth_token(&path);
+
+        assert_eq!(result, Some("booga".to_string()));
+    }
+
+    #[test]
+    fn missing_or_blank_ui_auth_token_is_not_a_token() {
+        let home_dir = ensure_node_home_directory_exists(
+            "ui_auth",
+            "missing_or_blank_ui_auth_token_is_not_a_token",
+        );
+        let path = ui_auth_token_path(&home_dir);
+
+        assert_eq!(read_ui_auth_token(&path), None);
+        fs::write(&path, "\n").unwrap();
+        assert_eq!(read_ui_auth_token(&path), None);
+    }
+
+    #[test]
+    fn ui_auth_token_survives_the_trip_through_handshake_headers() {
+        let builder =
+            with_ui_auth_token(ClientBuilder::new("ws://127.0.0.1:1234").unwrap(), "booga");
+        let mut headers = Headers::new();
+        headers.set(
+            builder
+                .get_header::<Authorization<Bearer>>()
+                .unwrap()
+                .clone(),
+        );
+
+        let result = ui_auth_token_from_headers(&headers);
+
+        assert_eq!(result, Some("booga".to_string()));
+        assert_eq!(ui_auth_token_from_headers(&Headers::new()), None);
+    }
+}
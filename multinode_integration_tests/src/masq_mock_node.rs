@@ -142,7 +142,7 @@ impl MASQMockNode {
         index: usize,
         host_node_parent_dir: Option<String>,
         public_key: &PublicKey,
-        chain_id: u8,
+        chain_id: u64,
     ) -> MASQMockNode {
         let main_cryptde = CryptDENull::from(public_key, chain_id);
         let mut key = public_key.as_slice().to_vec();
@@ -156,7 +156,7 @@ impl MASQMockNode {
         ports: Vec<u16>,
         index: usize,
         host_node_parent_dir: Option<String>,
-        chain_id: u8,
+        chain_id: u64,
     ) -> MASQMockNode {
         let cryptde_enum = CryptDEEnum::Real(CryptDEReal::new(chain_id));
         Self::start_with_cryptde_enum(ports, index, host_node_parent_dir, cryptde_enum)
@@ -181,7 +181,7 @@ pub trait MASQNode: Any {
     fn consuming_wallet(&self) -> Option<Wallet>;
     // The RatePack this Node will use to charge fees.
     fn rate_pack(&self) -> RatePack;
-    // Valid values are "dev, "ropsten", "rinkeby" for now. Add "mainnet" when it's time.
+    // Valid values are "dev" and "mainnet", or any network named in a MASQ_BLOCKCHAINS_FILE.
     fn chain(&self) -> Option<String>;
     fn accepts_connections(&self) -> bool;
     fn routes_data(&self) -> bool;
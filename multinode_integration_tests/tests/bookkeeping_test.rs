@@ -71,7 +71,7 @@ fn provided_and_consumed_services_are_recorded_in_databases() {
     });
 }
 
-fn non_pending_payables(node: &MASQRealNode, chain_id: u8) -> Vec<PayableAccount> {
+fn non_pending_payables(node: &MASQRealNode, chain_id: u64) -> Vec<PayableAccount> {
     let db_initializer = DbInitializerReal::new();
     let payable_dao = PayableDaoReal::new(
         db_initializer
@@ -88,7 +88,7 @@ fn non_pending_payables(node: &MASQRealNode, chain_id: u8) -> Vec<PayableAccount
     payable_dao.non_pending_payables()
 }
 
-fn receivables(node: &MASQRealNode, chain_id: u8) -> Vec<ReceivableAccount> {
+fn receivables(node: &MASQRealNode, chain_id: u64) -> Vec<ReceivableAccount> {
     let db_initializer = DbInitializerReal::new();
     let receivable_dao = ReceivableDaoReal::new(
         db_initializer
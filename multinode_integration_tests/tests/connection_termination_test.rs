@@ -305,7 +305,7 @@ fn create_request_icp(
     stream_key: StreamKey,
     return_route_id: u32,
     server: &MASQNodeServer,
-    chain_id: u8,
+    chain_id: u64,
 ) -> IncipientCoresPackage {
     IncipientCoresPackage::new(
         originating_node.main_cryptde_null().unwrap(),
@@ -10,8 +10,14 @@ use crate::accountant::payable_dao::{PayableAccount, Payment};
 use crate::accountant::receivable_dao::ReceivableAccount;
 use crate::banned_dao::BannedDao;
 use crate::blockchain::blockchain_bridge::RetrieveTransactions;
-use crate::blockchain::blockchain_interface::{BlockchainError, Transaction};
+use crate::blockchain::blockchain_interface::{
+    BlockchainError, BlockchainResult, GasFees, Transaction, TransactionStatus,
+};
 use crate::bootstrapper::BootstrapperConfig;
+use crate::database::dao_utils::to_time_t;
+use crate::metrics::{
+    metrics, BYTES_CONSUMED, BYTES_EXITED, BYTES_ROUTED, PAYABLE_TOTAL, RECEIVABLE_TOTAL,
+};
 use crate::persistent_configuration::PersistentConfiguration;
 use crate::sub_lib::accountant::AccountantConfig;
 use crate::sub_lib::accountant::AccountantSubs;
@@ -20,6 +26,7 @@ use crate::sub_lib::accountant::ReportExitServiceProvidedMessage;
 use crate::sub_lib::accountant::ReportRoutingServiceConsumedMessage;
 use crate::sub_lib::accountant::ReportRoutingServiceProvidedMessage;
 use crate::sub_lib::blockchain_bridge::ReportAccountsPayable;
+use crate::sub_lib::blockchain_bridge::{RequestTransactionReceipts, ResubmitPayments};
 use crate::sub_lib::logger::Logger;
 use crate::sub_lib::peer_actors::{BindMessage, StartMessage};
 use crate::sub_lib::utils::NODE_MAILBOX_CAPACITY;
@@ -36,17 +43,34 @@ use itertools::Itertools;
 use lazy_static::lazy_static;
 use masq_lib::messages::UiMessageError::UnexpectedMessage;
 use masq_lib::messages::{FromMessageBody, ToMessageBody, UiFinancialsRequest, UiMessageError};
+use masq_lib::messages::{
+    UiBanListRequest, UiBanListResponse, UiBanRequest, UiBanResponse, UiBannedWallet,
+    UiPardonedWallet, UiUnbanRequest, UiUnbanResponse, BAN_ERROR,
+};
 use masq_lib::messages::{UiFinancialsResponse, UiPayableAccount, UiReceivableAccount};
+use masq_lib::ui_gateway::MessageBody;
+use masq_lib::ui_gateway::MessagePath::Conversation;
 use masq_lib::ui_gateway::MessageTarget::ClientId;
 use masq_lib::ui_gateway::{NodeFromUiMessage, NodeToUiMessage};
 use payable_dao::PayableDao;
 use receivable_dao::ReceivableDao;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use std::thread;
 use std::time::{Duration, SystemTime};
+use web3::types::H256;
 
 pub const CRASH_KEY: &str = "ACCOUNTANT";
 pub const DEFAULT_PAYABLE_SCAN_INTERVAL: u64 = 3600; // one hour
 pub const DEFAULT_PAYMENT_RECEIVED_SCAN_INTERVAL: u64 = 3600; // one hour
+pub const DEFAULT_PENDING_PAYMENT_SCAN_INTERVAL: u64 = 300; // five minutes
+
+// A payment whose latest transaction has gone this long without a receipt is resubmitted with
+// the same nonce, so that whichever attempt is mined first replaces the others.
+const PENDING_PAYMENT_RESUBMIT_AFTER_SEC: u64 = 1800; // half an hour
+const RESUBMISSION_GAS_PRICE_BUMP_PERCENT: u64 = 20;
+// Clients drop a replacement transaction that doesn't outbid the one it replaces by this much
+const MIN_REPLACEMENT_GAS_PRICE_BUMP_PERCENT: u64 = 10;
 
 const SECONDS_PER_DAY: i64 = 86_400;
 
@@ -90,6 +114,9 @@ pub struct Accountant {
     config: AccountantConfig,
     consuming_wallet: Option<Wallet>,
     earning_wallet: Wallet,
+    max_gas_price: u64,
+    // Stuck transactions the max gas price won't let us replace; the operator has been warned
+    capped_transactions: HashSet<H256>,
     payable_dao: Box<dyn PayableDao>,
     receivable_dao: Box<dyn ReceivableDao>,
     banned_dao: Box<dyn BannedDao>,
@@ -98,6 +125,10 @@ pub struct Accountant {
     retrieve_transactions_sub: Option<Recipient<RetrieveTransactions>>,
     report_new_payments_sub: Option<Recipient<ReceivedPayments>>,
     report_sent_payments_sub: Option<Recipient<SentPayments>>,
+    request_transaction_receipts_sub: Option<Recipient<RequestTransactionReceipts>>,
+    resubmit_payments_sub: Option<Recipient<ResubmitPayments>>,
+    report_transaction_receipts_sub: Option<Recipient<ReportTransactionReceipts>>,
+    report_resent_payments_sub: Option<Recipient<ResentPayments>>,
     ui_message_sub: Option<Recipient<NodeToUiMessage>>,
     logger: Logger,
 }
@@ -109,6 +140,9 @@ impl Actor for Accountant {
 #[derive(Debug, Eq, Message, PartialEq)]
 pub struct ReceivedPayments {
     payments: Vec<Transaction>,
+    new_start_block: u64,
+    caught_up: bool,
+    reorganized: bool,
 }
 
 #[derive(Debug, Eq, Message, PartialEq)]
@@ -116,6 +150,16 @@ pub struct SentPayments {
     pub payments: Vec<Result<Payment, BlockchainError>>,
 }
 
+#[derive(Debug, Eq, Message, PartialEq)]
+pub struct ReportTransactionReceipts {
+    pub payments_with_statuses: Vec<(Payment, BlockchainResult<TransactionStatus>)>,
+}
+
+#[derive(Debug, Eq, Message, PartialEq)]
+pub struct ResentPayments {
+    pub payments: Vec<Result<Payment, BlockchainError>>,
+}
+
 impl Handler<BindMessage> for Accountant {
     type Result = ();
 
@@ -142,6 +186,13 @@ impl Handler<StartMessage> for Accountant {
                 accountant.scan_for_delinquencies();
             },
         );
+
+        ctx.run_interval(
+            self.config.pending_payment_scan_interval,
+            |accountant, _ctx| {
+                accountant.scan_for_pending_payments();
+            },
+        );
     }
 }
 
@@ -161,6 +212,22 @@ impl Handler<SentPayments> for Accountant {
     }
 }
 
+impl Handler<ReportTransactionReceipts> for Accountant {
+    type Result = ();
+
+    fn handle(&mut self, msg: ReportTransactionReceipts, _ctx: &mut Self::Context) -> Self::Result {
+        self.handle_transaction_receipts(msg);
+    }
+}
+
+impl Handler<ResentPayments> for Accountant {
+    type Result = ();
+
+    fn handle(&mut self, msg: ResentPayments, _ctx: &mut Self::Context) -> Self::Result {
+        self.handle_resent_payments(msg);
+    }
+}
+
 impl Handler<ReportRoutingServiceProvidedMessage> for Accountant {
     type Result = ();
 
@@ -229,6 +296,8 @@ impl Accountant {
             config: config.accountant_config.clone(),
             consuming_wallet: config.consuming_wallet.clone(),
             earning_wallet: config.earning_wallet.clone(),
+            max_gas_price: config.blockchain_bridge_config.max_gas_price,
+            capped_transactions: HashSet::new(),
             payable_dao,
             receivable_dao,
             banned_dao,
@@ -237,6 +306,10 @@ impl Accountant {
             retrieve_transactions_sub: None,
             report_new_payments_sub: None,
             report_sent_payments_sub: None,
+            request_transaction_receipts_sub: None,
+            resubmit_payments_sub: None,
+            report_transaction_receipts_sub: None,
+            report_resent_payments_sub: None,
             ui_message_sub: None,
             logger: Logger::new("Accountant"),
         }
@@ -260,12 +333,15 @@ impl Accountant {
                 .recipient::<ReportExitServiceConsumedMessage>(),
             report_new_payments: addr.clone().recipient::<ReceivedPayments>(),
             report_sent_payments: addr.clone().recipient::<SentPayments>(),
+            report_transaction_receipts: addr.clone().recipient::<ReportTransactionReceipts>(),
+            report_resent_payments: addr.clone().recipient::<ResentPayments>(),
             ui_message_sub: addr.clone().recipient::<NodeFromUiMessage>(),
         }
     }
 
     fn scan_for_payables(&mut self) {
         debug!(self.logger, "Scanning for payables");
+        self.report_totals();
         let future_logger = self.logger.clone();
 
         let payables = self
@@ -307,6 +383,47 @@ impl Accountant {
         }
     }
 
+    fn scan_for_pending_payments(&mut self) {
+        debug!(self.logger, "Scanning for pending payments");
+        let future_logger = self.logger.clone();
+
+        let payments = self.payable_dao.pending_payments();
+
+        if !payments.is_empty() {
+            let report_transaction_receipts = self.report_transaction_receipts_sub.clone();
+            let future = self
+                .request_transaction_receipts_sub
+                .as_ref()
+                .expect("BlockchainBridge is unbound")
+                .send(RequestTransactionReceipts {
+                    payments: payments.clone(),
+                })
+                .then(move |results| match results {
+                    Ok(statuses) => {
+                        report_transaction_receipts
+                            .expect("Accountant is unbound")
+                            .try_send(ReportTransactionReceipts {
+                                payments_with_statuses: payments
+                                    .into_iter()
+                                    .zip(statuses)
+                                    .collect(),
+                            })
+                            .expect("Accountant is dead");
+                        Ok(())
+                    }
+                    Err(e) => {
+                        error!(
+                            future_logger,
+                            "Unable to send RequestTransactionReceipts: {:?}", e
+                        );
+                        thread::sleep(Duration::from_secs(1));
+                        panic!("Unable to send RequestTransactionReceipts: {:?}", e);
+                    }
+                });
+            actix::spawn(future);
+        }
+    }
+
     fn scan_for_delinquencies(&mut self) {
         debug!(self.logger, "Scanning for delinquencies");
 
@@ -350,6 +467,7 @@ impl Accountant {
         );
         let future_report_new_payments_sub = self.report_new_payments_sub.clone();
         let start_block = self.persistent_configuration.start_block();
+        let credited_blocks = self.receivable_dao.credited_blocks();
         let future = self
             .retrieve_transactions_sub
             .as_ref()
@@ -357,17 +475,24 @@ impl Accountant {
             .send(RetrieveTransactions {
                 start_block,
                 recipient: self.earning_wallet.clone(),
+                credited_blocks,
             })
             .then(move |transactions_possibly| match transactions_possibly {
-                Ok(Ok(ref vec)) if vec.is_empty() => {
+                Ok(Ok(ref retrieved))
+                    if retrieved.transactions.is_empty()
+                        && retrieved.new_start_block == start_block =>
+                {
                     debug!(future_logger, "No payments detected");
                     Ok(())
                 }
-                Ok(Ok(transactions)) => {
+                Ok(Ok(retrieved)) => {
                     future_report_new_payments_sub
                         .expect("Accountant is unbound")
                         .try_send(ReceivedPayments {
-                            payments: transactions,
+                            payments: retrieved.transactions,
+                            new_start_block: retrieved.new_start_block,
+                            caught_up: retrieved.caught_up,
+                            reorganized: retrieved.reorganized,
                         })
                         .expect("Accountant is dead.");
                     Ok(())
@@ -506,6 +631,15 @@ impl Accountant {
             Some(msg.peer_actors.blockchain_bridge.retrieve_transactions);
         self.report_new_payments_sub = Some(msg.peer_actors.accountant.report_new_payments);
         self.report_sent_payments_sub = Some(msg.peer_actors.accountant.report_sent_payments);
+        self.request_transaction_receipts_sub = Some(
+            msg.peer_actors
+                .blockchain_bridge
+                .request_transaction_receipts,
+        );
+        self.resubmit_payments_sub = Some(msg.peer_actors.blockchain_bridge.resubmit_payments);
+        self.report_transaction_receipts_sub =
+            Some(msg.peer_actors.accountant.report_transaction_receipts);
+        self.report_resent_payments_sub = Some(msg.peer_actors.accountant.report_resent_payments);
         self.ui_message_sub = Some(msg.peer_actors.ui_gateway.node_to_ui_message_sub);
 
         info!(self.logger, "Accountant bound");
@@ -515,13 +649,27 @@ impl Accountant {
         self.scan_for_payables();
         self.scan_for_received_payments();
         self.scan_for_delinquencies();
+        self.scan_for_pending_payments();
     }
 
     fn handle_received_payments(&mut self, received_payments: ReceivedPayments) {
-        self.receivable_dao.as_mut().more_money_received(
-            self.persistent_configuration.as_ref(),
-            received_payments.payments,
-        );
+        let recorded = if received_payments.reorganized {
+            self.receivable_dao.as_mut().reverse_orphaned_payments(
+                self.persistent_configuration.as_ref(),
+                received_payments.new_start_block,
+            )
+        } else {
+            self.receivable_dao.as_mut().more_money_received(
+                self.persistent_configuration.as_ref(),
+                received_payments.payments,
+                received_payments.new_start_block,
+            )
+        };
+        // A Node catching up after a long absence scans one chunk of blocks after another, rather
+        // than waiting a whole scan interval for each; if a chunk can't be recorded, it waits
+        if recorded.is_ok() && !received_payments.caught_up {
+            self.scan_for_received_payments();
+        }
     }
 
     fn handle_sent_payments(&mut self, sent_payments: SentPayments) {
@@ -547,6 +695,280 @@ impl Accountant {
             })
     }
 
+    fn handle_transaction_receipts(&mut self, msg: ReportTransactionReceipts) {
+        let now = SystemTime::now();
+        let batch_sizes = Self::count_by_transaction(
+            msg.payments_with_statuses
+                .iter()
+                .map(|(payment, _)| payment),
+        );
+        // The PayableDao lists attempts grouped by wallet, and a wallet never has more than one
+        // pending payment, so each group is every attempt at a single payment.
+        let due = msg
+            .payments_with_statuses
+            .into_iter()
+            .group_by(|(payment, _)| payment.to.clone())
+            .into_iter()
+            .filter_map(|(_, attempts)| self.reconcile_pending_payment(attempts.collect(), now))
+            .collect::<Vec<Payment>>();
+        // The payments in a batch share every transaction, so a replacement for only some of them
+        // would strand the rest; a batch is resubmitted once all of it is due.
+        let due_sizes = Self::count_by_transaction(due.iter());
+        let to_resubmit = due
+            .into_iter()
+            .filter(|payment| {
+                due_sizes.get(&payment.transaction) == batch_sizes.get(&payment.transaction)
+            })
+            .collect::<Vec<Payment>>();
+
+        if !to_resubmit.is_empty() {
+            let future_logger = self.logger.clone();
+            let report_resent_payments = self.report_resent_payments_sub.clone();
+            let future = self
+                .resubmit_payments_sub
+                .as_ref()
+                .expect("BlockchainBridge is unbound")
+                .send(ResubmitPayments {
+                    payments: to_resubmit,
+                })
+                .then(move |results| match results {
+                    Ok(Ok(results)) => {
+                        report_resent_payments
+                            .expect("Accountant is unbound")
+                            .try_send(ResentPayments { payments: results })
+                            .expect("Accountant is dead");
+                        Ok(())
+                    }
+                    Ok(Err(e)) => {
+                        warning!(future_logger, "{}", e);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        error!(future_logger, "Unable to send ResubmitPayments: {:?}", e);
+                        thread::sleep(Duration::from_secs(1));
+                        panic!("Unable to send ResubmitPayments: {:?}", e);
+                    }
+                });
+            actix::spawn(future);
+        }
+    }
+
+    fn count_by_transaction<'a>(
+        payments: impl Iterator<Item = &'a Payment>,
+    ) -> HashMap<H256, usize> {
+        payments.fold(HashMap::new(), |mut counts, payment| {
+            *counts.entry(payment.transaction).or_insert(0) += 1;
+            counts
+        })
+    }
+
+    // Every attempt shares a nonce, so at most one of them can ever be mined. Returns the
+    // payment to resubmit, if the latest attempt has been waiting too long.
+    fn reconcile_pending_payment(
+        &mut self,
+        attempts: Vec<(Payment, BlockchainResult<TransactionStatus>)>,
+        now: SystemTime,
+    ) -> Option<Payment> {
+        let with_status = |wanted: TransactionStatus| {
+            attempts
+                .iter()
+                .find(|(_, status)| status == &Ok(wanted))
+                .map(|(payment, _)| payment)
+        };
+        if let Some(payment) = with_status(TransactionStatus::Succeeded) {
+            match self.payable_dao.payment_confirmed(
+                &payment.to,
+                payment.amount,
+                now,
+                payment.transaction,
+            ) {
+                Ok(()) => info!(
+                    self.logger,
+                    "Payment of {} to {} confirmed in transaction {:#x}",
+                    payment.amount,
+                    payment.to,
+                    payment.transaction
+                ),
+                Err(PaymentError::SignConversion(_)) => error!(
+                    self.logger,
+                    "Overflow error trying to record confirmation of payment of {} to {} (transaction {:#x}). Skipping",
+                    payment.amount,
+                    payment.to,
+                    payment.transaction
+                ),
+            }
+            self.forget_capped_transactions(&attempts);
+            return None;
+        }
+        if let Some(payment) = with_status(TransactionStatus::Failed) {
+            match self.payable_dao.payment_failed(payment) {
+                Ok(()) => warning!(
+                    self.logger,
+                    "Payment of {} to {} failed in transaction {:#x}; it will be paid again",
+                    payment.amount,
+                    payment.to,
+                    payment.transaction
+                ),
+                Err(PaymentError::SignConversion(_)) => error!(
+                    self.logger,
+                    "Overflow error trying to record failure of payment of {} to {} (transaction {:#x}). Skipping",
+                    payment.amount,
+                    payment.to,
+                    payment.transaction
+                ),
+            }
+            self.forget_capped_transactions(&attempts);
+            return None;
+        }
+        if let Some((payment, Err(e))) = attempts.iter().find(|(_, status)| status.is_err()) {
+            warning!(
+                self.logger,
+                "Unable to check transaction {:#x} paying {} to {}: {} Will try again later",
+                payment.transaction,
+                payment.amount,
+                payment.to,
+                e
+            );
+            return None;
+        }
+        let latest = attempts
+            .iter()
+            .map(|(payment, _)| payment)
+            .max_by_key(|payment| payment.timestamp)?;
+        let waited = now
+            .duration_since(latest.timestamp)
+            .unwrap_or_else(|_| Duration::new(0, 0));
+        if waited < Duration::from_secs(PENDING_PAYMENT_RESUBMIT_AFTER_SEC) {
+            debug!(
+                self.logger,
+                "Transaction {:#x} paying {} to {} is still pending after {} sec",
+                latest.transaction,
+                latest.amount,
+                latest.to,
+                waited.as_secs()
+            );
+            return None;
+        }
+        let fees = Self::bump_gas_fees(latest.fees).capped_at(self.max_gas_price);
+        if !Self::outbids(fees, latest.fees) {
+            if self.capped_transactions.insert(latest.transaction) {
+                warning!(
+                    self.logger,
+                    "Transaction {:#x} paying {} to {} is stuck at {}, and the max gas price of {} Gwei leaves no room to replace it. It will stay pending until it is mined or the max gas price is raised",
+                    latest.transaction,
+                    latest.amount,
+                    latest.to,
+                    latest.fees,
+                    self.max_gas_price
+                );
+            }
+            return None;
+        }
+        Some(Payment {
+            fees,
+            ..latest.clone()
+        })
+    }
+
+    fn forget_capped_transactions(
+        &mut self,
+        attempts: &[(Payment, BlockchainResult<TransactionStatus>)],
+    ) {
+        attempts.iter().for_each(|(payment, _)| {
+            self.capped_transactions.remove(&payment.transaction);
+        });
+    }
+
+    // A replacement is only accepted if it raises the priority fee along with the max fee
+    fn bump_gas_fees(fees: GasFees) -> GasFees {
+        match fees {
+            GasFees::Legacy { gas_price } => GasFees::Legacy {
+                gas_price: Self::bump_gas_price(gas_price),
+            },
+            GasFees::FeeMarket {
+                max_fee,
+                priority_fee,
+            } => GasFees::FeeMarket {
+                max_fee: Self::bump_gas_price(max_fee),
+                priority_fee: Self::bump_gas_price(priority_fee),
+            },
+        }
+    }
+
+    fn bump_gas_price(gas_price: u64) -> u64 {
+        Self::raise_gas_price(gas_price, RESUBMISSION_GAS_PRICE_BUMP_PERCENT)
+    }
+
+    fn raise_gas_price(gas_price: u64, percent: u64) -> u64 {
+        let bump = gas_price.saturating_mul(percent) / 100;
+        gas_price.saturating_add(bump.max(1))
+    }
+
+    fn outbids(fees: GasFees, replaced: GasFees) -> bool {
+        let outbids_price = |price: u64, replaced_price: u64| {
+            price > replaced_price
+                && price
+                    >= Self::raise_gas_price(replaced_price, MIN_REPLACEMENT_GAS_PRICE_BUMP_PERCENT)
+        };
+        match (fees, replaced) {
+            (
+                GasFees::Legacy { gas_price },
+                GasFees::Legacy {
+                    gas_price: replaced,
+                },
+            ) => outbids_price(gas_price, replaced),
+            (
+                GasFees::FeeMarket {
+                    max_fee,
+                    priority_fee,
+                },
+                GasFees::FeeMarket {
+                    max_fee: replaced_max_fee,
+                    priority_fee: replaced_priority_fee,
+                },
+            ) => {
+                outbids_price(max_fee, replaced_max_fee)
+                    && outbids_price(priority_fee, replaced_priority_fee)
+            }
+            _ => true,
+        }
+    }
+
+    fn handle_resent_payments(&mut self, resent_payments: ResentPayments) {
+        resent_payments
+            .payments
+            .iter()
+            .for_each(|payment| match payment {
+                Ok(payment) => match self.payable_dao.payment_resubmitted(payment) {
+                    Ok(()) => info!(
+                        self.logger,
+                        "Resubmitted payment of {} to {} at {} (transaction {:#x})",
+                        payment.amount,
+                        payment.to,
+                        payment.fees,
+                        payment.transaction
+                    ),
+                    Err(PaymentError::SignConversion(_)) => error! (
+                        self.logger,
+                        "Overflow error trying to record resubmitted payment of {} to earning wallet {} (transaction {}). Skipping",
+                        payment.amount,
+                        payment.to,
+                        payment.transaction,
+                    ),
+                },
+                Err(e) => warning!(
+                    self.logger,
+                    "{} Resubmission will be retried on the next scan.",
+                    e
+                ),
+            })
+    }
+
+    fn report_totals(&self) {
+        metrics().set(&PAYABLE_TOTAL, &[], self.payable_dao.total() as f64);
+        metrics().set(&RECEIVABLE_TOTAL, &[], self.receivable_dao.total() as f64);
+    }
+
     fn handle_report_routing_service_provided_message(
         &mut self,
         msg: ReportRoutingServiceProvidedMessage,
@@ -555,6 +977,7 @@ impl Accountant {
             self.logger,
             "Charging routing of {} bytes to wallet {}", msg.payload_size, msg.paying_wallet
         );
+        metrics().increment(&BYTES_ROUTED, &[], msg.payload_size as u64);
         self.record_service_provided(
             msg.service_rate,
             msg.byte_rate,
@@ -575,6 +998,7 @@ impl Accountant {
             msg.service_rate,
             msg.byte_rate
         );
+        metrics().increment(&BYTES_EXITED, &[], msg.payload_size as u64);
         self.record_service_provided(
             msg.service_rate,
             msg.byte_rate,
@@ -593,6 +1017,11 @@ impl Accountant {
             msg.earning_wallet,
             msg.payload_size
         );
+        metrics().increment(
+            &BYTES_CONSUMED,
+            &[("service", "routing")],
+            msg.payload_size as u64,
+        );
         self.record_service_consumed(
             msg.service_rate,
             msg.byte_rate,
@@ -611,6 +1040,11 @@ impl Accountant {
             msg.earning_wallet,
             msg.payload_size
         );
+        metrics().increment(
+            &BYTES_CONSUMED,
+            &[("service", "exit")],
+            msg.payload_size as u64,
+        );
         self.record_service_consumed(
             msg.service_rate,
             msg.byte_rate,
@@ -621,6 +1055,15 @@ impl Accountant {
 
     fn handle_node_from_ui_message(&mut self, msg: NodeFromUiMessage) {
         let client_id = msg.client_id;
+        if let Ok((_, context_id)) = UiBanListRequest::fmb(msg.body.clone()) {
+            return self.handle_ban_list(client_id, context_id);
+        }
+        if let Ok((payload, context_id)) = UiBanRequest::fmb(msg.body.clone()) {
+            return self.handle_ban(client_id, context_id, payload);
+        }
+        if let Ok((payload, context_id)) = UiUnbanRequest::fmb(msg.body.clone()) {
+            return self.handle_unban(client_id, context_id, payload);
+        }
         let result: Result<(UiFinancialsRequest, u64), UiMessageError> =
             UiFinancialsRequest::fmb(msg.body);
         match result {
@@ -675,6 +1118,85 @@ impl Accountant {
             total_receivable,
         }
         .tmb(context_id);
+        self.send_to_ui(client_id, body);
+    }
+
+    fn handle_ban_list(&self, client_id: u64, context_id: u64) {
+        let bans = self
+            .banned_dao
+            .ban_list()
+            .into_iter()
+            .map(|banned| UiBannedWallet {
+                wallet: banned.wallet.to_string(),
+                reason: banned.reason,
+                banned_timestamp: to_time_t(banned.banned_timestamp) as u64,
+                manual: banned.manual,
+            })
+            .collect_vec();
+        let pardons = self
+            .banned_dao
+            .pardon_list()
+            .into_iter()
+            .map(|pardoned| UiPardonedWallet {
+                wallet: pardoned.wallet.to_string(),
+                pardoned_timestamp: to_time_t(pardoned.pardoned_timestamp) as u64,
+            })
+            .collect_vec();
+        self.send_to_ui(
+            client_id,
+            UiBanListResponse { bans, pardons }.tmb(context_id),
+        );
+    }
+
+    fn handle_ban(&mut self, client_id: u64, context_id: u64, request: UiBanRequest) {
+        let body = match Self::parse_ban_wallet("ban", context_id, &request.wallet) {
+            Ok(wallet) => {
+                self.banned_dao.manual_ban(&wallet, &request.reason);
+                info!(
+                    self.logger,
+                    "Wallet {} banned by client {}: {}", wallet, client_id, request.reason
+                );
+                UiBanResponse {}.tmb(context_id)
+            }
+            Err(body) => body,
+        };
+        self.send_to_ui(client_id, body);
+    }
+
+    fn handle_unban(&mut self, client_id: u64, context_id: u64, request: UiUnbanRequest) {
+        let body = match Self::parse_ban_wallet("unban", context_id, &request.wallet) {
+            Ok(wallet) => {
+                let was_banned = self.banned_dao.manual_unban(&wallet);
+                info!(
+                    self.logger,
+                    "Wallet {} pardoned by client {}{}",
+                    wallet,
+                    client_id,
+                    if was_banned { " and unbanned" } else { "" }
+                );
+                UiUnbanResponse { was_banned }.tmb(context_id)
+            }
+            Err(body) => body,
+        };
+        self.send_to_ui(client_id, body);
+    }
+
+    fn parse_ban_wallet(
+        opcode: &str,
+        context_id: u64,
+        wallet: &str,
+    ) -> Result<Wallet, MessageBody> {
+        Wallet::from_str(wallet).map_err(|_| MessageBody {
+            opcode: opcode.to_string(),
+            path: Conversation(context_id),
+            payload: Err((
+                BAN_ERROR,
+                format!("'{}' is not a valid Ethereum address", wallet),
+            )),
+        })
+    }
+
+    fn send_to_ui(&self, client_id: u64, body: MessageBody) {
         self.ui_message_sub
             .as_ref()
             .expect("UiGateway not bound")
@@ -702,7 +1224,10 @@ pub mod tests {
     use super::*;
     use crate::accountant::receivable_dao::ReceivableAccount;
     use crate::accountant::test_utils::make_receivable_account;
+    use crate::banned_dao::{BannedWallet, PardonedWallet};
     use crate::blockchain::blockchain_interface::BlockchainError;
+    use crate::blockchain::blockchain_interface::CreditedBlock;
+    use crate::blockchain::blockchain_interface::RetrievedTransactions;
     use crate::blockchain::blockchain_interface::Transaction;
     use crate::database::dao_utils::from_time_t;
     use crate::database::dao_utils::to_time_t;
@@ -741,6 +1266,13 @@ pub mod tests {
         non_pending_payables_results: RefCell<Vec<Vec<PayableAccount>>>,
         payment_sent_parameters: Arc<Mutex<Vec<Payment>>>,
         payment_sent_results: RefCell<Vec<Result<(), PaymentError>>>,
+        payment_resubmitted_parameters: Arc<Mutex<Vec<Payment>>>,
+        payment_resubmitted_results: RefCell<Vec<Result<(), PaymentError>>>,
+        payment_confirmed_parameters: Arc<Mutex<Vec<(Wallet, u64, SystemTime, H256)>>>,
+        payment_confirmed_results: RefCell<Vec<Result<(), PaymentError>>>,
+        payment_failed_parameters: Arc<Mutex<Vec<Payment>>>,
+        payment_failed_results: RefCell<Vec<Result<(), PaymentError>>>,
+        pending_payments_results: RefCell<Vec<Vec<Payment>>>,
         top_records_parameters: Arc<Mutex<Vec<(u64, u64)>>>,
         top_records_results: RefCell<Vec<Vec<PayableAccount>>>,
         total_results: RefCell<Vec<u64>>,
@@ -755,7 +1287,7 @@ pub mod tests {
             self.more_money_payable_results.borrow_mut().remove(0)
         }
 
-        fn payment_sent(&self, sent_payment: &Payment) -> Result<(), PaymentError> {
+        fn payment_sent(&mut self, sent_payment: &Payment) -> Result<(), PaymentError> {
             self.payment_sent_parameters
                 .lock()
                 .unwrap()
@@ -763,14 +1295,47 @@ pub mod tests {
             self.payment_sent_results.borrow_mut().remove(0)
         }
 
+        fn payment_resubmitted(
+            &mut self,
+            resubmitted_payment: &Payment,
+        ) -> Result<(), PaymentError> {
+            self.payment_resubmitted_parameters
+                .lock()
+                .unwrap()
+                .push(resubmitted_payment.clone());
+            self.payment_resubmitted_results.borrow_mut().remove(0)
+        }
+
         fn payment_confirmed(
-            &self,
-            _wallet: &Wallet,
-            _amount: u64,
-            _confirmation_noticed_timestamp: SystemTime,
-            _transaction_hash: H256,
+            &mut self,
+            wallet: &Wallet,
+            amount: u64,
+            confirmation_noticed_timestamp: SystemTime,
+            transaction_hash: H256,
         ) -> Result<(), PaymentError> {
-            unimplemented!("SC-925: TODO")
+            self.payment_confirmed_parameters.lock().unwrap().push((
+                wallet.clone(),
+                amount,
+                confirmation_noticed_timestamp,
+                transaction_hash,
+            ));
+            self.payment_confirmed_results.borrow_mut().remove(0)
+        }
+
+        fn payment_failed(&mut self, failed_payment: &Payment) -> Result<(), PaymentError> {
+            self.payment_failed_parameters
+                .lock()
+                .unwrap()
+                .push(failed_payment.clone());
+            self.payment_failed_results.borrow_mut().remove(0)
+        }
+
+        fn pending_payments(&self) -> Vec<Payment> {
+            if self.pending_payments_results.borrow().is_empty() {
+                vec![]
+            } else {
+                self.pending_payments_results.borrow_mut().remove(0)
+            }
         }
 
         fn account_status(&self, wallet: &Wallet) -> Option<PayableAccount> {
@@ -798,7 +1363,13 @@ pub mod tests {
         }
 
         fn total(&self) -> u64 {
-            self.total_results.borrow_mut().remove(0)
+            // Every payable scan reads the total for metrics; most tests don't care what it is
+            let mut total_results = self.total_results.borrow_mut();
+            if total_results.is_empty() {
+                0
+            } else {
+                total_results.remove(0)
+            }
         }
     }
 
@@ -835,6 +1406,44 @@ pub mod tests {
             self
         }
 
+        fn payment_resubmitted_parameters(mut self, parameters: &Arc<Mutex<Vec<Payment>>>) -> Self {
+            self.payment_resubmitted_parameters = parameters.clone();
+            self
+        }
+
+        fn payment_resubmitted_result(self, result: Result<(), PaymentError>) -> Self {
+            self.payment_resubmitted_results.borrow_mut().push(result);
+            self
+        }
+
+        fn payment_confirmed_parameters(
+            mut self,
+            parameters: &Arc<Mutex<Vec<(Wallet, u64, SystemTime, H256)>>>,
+        ) -> Self {
+            self.payment_confirmed_parameters = parameters.clone();
+            self
+        }
+
+        fn payment_confirmed_result(self, result: Result<(), PaymentError>) -> Self {
+            self.payment_confirmed_results.borrow_mut().push(result);
+            self
+        }
+
+        fn payment_failed_parameters(mut self, parameters: &Arc<Mutex<Vec<Payment>>>) -> Self {
+            self.payment_failed_parameters = parameters.clone();
+            self
+        }
+
+        fn payment_failed_result(self, result: Result<(), PaymentError>) -> Self {
+            self.payment_failed_results.borrow_mut().push(result);
+            self
+        }
+
+        fn pending_payments_result(self, result: Vec<Payment>) -> Self {
+            self.pending_payments_results.borrow_mut().push(result);
+            self
+        }
+
         fn top_records_parameters(mut self, parameters: &Arc<Mutex<Vec<(u64, u64)>>>) -> Self {
             self.top_records_parameters = parameters.clone();
             self
@@ -857,8 +1466,11 @@ pub mod tests {
         account_status_results: RefCell<Vec<Option<ReceivableAccount>>>,
         more_money_receivable_parameters: Arc<Mutex<Vec<(Wallet, u64)>>>,
         more_money_receivable_results: RefCell<Vec<Result<(), PaymentError>>>,
-        more_money_received_parameters: Arc<Mutex<Vec<Vec<Transaction>>>>,
-        more_money_received_results: RefCell<Vec<Result<(), PaymentError>>>,
+        more_money_received_parameters: Arc<Mutex<Vec<(Vec<Transaction>, u64)>>>,
+        more_money_received_results: RefCell<Vec<Result<(), String>>>,
+        credited_blocks_results: RefCell<Vec<Vec<CreditedBlock>>>,
+        reverse_orphaned_payments_parameters: Arc<Mutex<Vec<u64>>>,
+        reverse_orphaned_payments_results: RefCell<Vec<Result<(), String>>>,
         receivables_results: RefCell<Vec<Vec<ReceivableAccount>>>,
         new_delinquencies_parameters: Arc<Mutex<Vec<(SystemTime, PaymentCurves)>>>,
         new_delinquencies_results: RefCell<Vec<Vec<ReceivableAccount>>>,
@@ -882,11 +1494,35 @@ pub mod tests {
             &mut self,
             _persistent_configuration: &dyn PersistentConfiguration,
             transactions: Vec<Transaction>,
-        ) {
+            new_start_block: u64,
+        ) -> Result<(), String> {
             self.more_money_received_parameters
                 .lock()
                 .unwrap()
-                .push(transactions);
+                .push((transactions, new_start_block));
+            self.more_money_received_results.borrow_mut().remove(0)
+        }
+
+        fn credited_blocks(&self) -> Vec<CreditedBlock> {
+            if self.credited_blocks_results.borrow().is_empty() {
+                vec![]
+            } else {
+                self.credited_blocks_results.borrow_mut().remove(0)
+            }
+        }
+
+        fn reverse_orphaned_payments(
+            &mut self,
+            _persistent_configuration: &dyn PersistentConfiguration,
+            first_orphaned_block: u64,
+        ) -> Result<(), String> {
+            self.reverse_orphaned_payments_parameters
+                .lock()
+                .unwrap()
+                .push(first_orphaned_block);
+            self.reverse_orphaned_payments_results
+                .borrow_mut()
+                .remove(0)
         }
 
         fn account_status(&self, wallet: &Wallet) -> Option<ReceivableAccount> {
@@ -939,7 +1575,13 @@ pub mod tests {
         }
 
         fn total(&self) -> u64 {
-            self.total_results.borrow_mut().remove(0)
+            // Every payable scan reads the total for metrics; most tests don't care what it is
+            let mut total_results = self.total_results.borrow_mut();
+            if total_results.is_empty() {
+                0
+            } else {
+                total_results.remove(0)
+            }
         }
     }
 
@@ -963,17 +1605,37 @@ pub mod tests {
 
         fn more_money_received_parameters(
             mut self,
-            parameters: &Arc<Mutex<Vec<Vec<Transaction>>>>,
+            parameters: &Arc<Mutex<Vec<(Vec<Transaction>, u64)>>>,
         ) -> Self {
             self.more_money_received_parameters = parameters.clone();
             self
         }
 
-        fn more_money_received_result(self, result: Result<(), PaymentError>) -> Self {
+        fn more_money_received_result(self, result: Result<(), String>) -> Self {
             self.more_money_received_results.borrow_mut().push(result);
             self
         }
 
+        fn credited_blocks_result(self, result: Vec<CreditedBlock>) -> Self {
+            self.credited_blocks_results.borrow_mut().push(result);
+            self
+        }
+
+        fn reverse_orphaned_payments_parameters(
+            mut self,
+            parameters: &Arc<Mutex<Vec<u64>>>,
+        ) -> Self {
+            self.reverse_orphaned_payments_parameters = parameters.clone();
+            self
+        }
+
+        fn reverse_orphaned_payments_result(self, result: Result<(), String>) -> Self {
+            self.reverse_orphaned_payments_results
+                .borrow_mut()
+                .push(result);
+            self
+        }
+
         fn new_delinquencies_parameters(
             mut self,
             parameters: &Arc<Mutex<Vec<(SystemTime, PaymentCurves)>>>,
@@ -1019,17 +1681,25 @@ pub mod tests {
     #[derive(Debug, Default)]
     struct BannedDaoMock {
         ban_list_parameters: Arc<Mutex<Vec<()>>>,
-        ban_list_results: RefCell<Vec<Vec<Wallet>>>,
+        ban_list_results: RefCell<Vec<Vec<BannedWallet>>>,
+        pardon_list_results: RefCell<Vec<Vec<PardonedWallet>>>,
         ban_parameters: Arc<Mutex<Vec<Wallet>>>,
         unban_parameters: Arc<Mutex<Vec<Wallet>>>,
+        manual_ban_parameters: Arc<Mutex<Vec<(Wallet, String)>>>,
+        manual_unban_parameters: Arc<Mutex<Vec<Wallet>>>,
+        manual_unban_results: RefCell<Vec<bool>>,
     }
 
     impl BannedDao for BannedDaoMock {
-        fn ban_list(&self) -> Vec<Wallet> {
+        fn ban_list(&self) -> Vec<BannedWallet> {
             self.ban_list_parameters.lock().unwrap().push(());
             self.ban_list_results.borrow_mut().remove(0)
         }
 
+        fn pardon_list(&self) -> Vec<PardonedWallet> {
+            self.pardon_list_results.borrow_mut().remove(0)
+        }
+
         fn ban(&self, wallet: &Wallet) {
             self.ban_parameters.lock().unwrap().push(wallet.clone());
         }
@@ -1037,23 +1707,38 @@ pub mod tests {
         fn unban(&self, wallet: &Wallet) {
             self.unban_parameters.lock().unwrap().push(wallet.clone());
         }
+
+        fn manual_ban(&mut self, wallet: &Wallet, reason: &str) {
+            self.manual_ban_parameters
+                .lock()
+                .unwrap()
+                .push((wallet.clone(), reason.to_string()));
+        }
+
+        fn manual_unban(&mut self, wallet: &Wallet) -> bool {
+            self.manual_unban_parameters
+                .lock()
+                .unwrap()
+                .push(wallet.clone());
+            self.manual_unban_results.borrow_mut().remove(0)
+        }
     }
 
     impl BannedDaoMock {
         pub fn new() -> Self {
-            Self {
-                ban_list_parameters: Arc::new(Mutex::new(vec![])),
-                ban_list_results: RefCell::new(vec![]),
-                ban_parameters: Arc::new(Mutex::new(vec![])),
-                unban_parameters: Arc::new(Mutex::new(vec![])),
-            }
+            Self::default()
         }
 
-        pub fn ban_list_result(self, result: Vec<Wallet>) -> Self {
+        pub fn ban_list_result(self, result: Vec<BannedWallet>) -> Self {
             self.ban_list_results.borrow_mut().push(result);
             self
         }
 
+        pub fn pardon_list_result(self, result: Vec<PardonedWallet>) -> Self {
+            self.pardon_list_results.borrow_mut().push(result);
+            self
+        }
+
         pub fn ban_parameters(mut self, parameters: &Arc<Mutex<Vec<Wallet>>>) -> Self {
             self.ban_parameters = parameters.clone();
             self
@@ -1063,6 +1748,24 @@ pub mod tests {
             self.unban_parameters = parameters.clone();
             self
         }
+
+        pub fn manual_ban_parameters(
+            mut self,
+            parameters: &Arc<Mutex<Vec<(Wallet, String)>>>,
+        ) -> Self {
+            self.manual_ban_parameters = parameters.clone();
+            self
+        }
+
+        pub fn manual_unban_parameters(mut self, parameters: &Arc<Mutex<Vec<Wallet>>>) -> Self {
+            self.manual_unban_parameters = parameters.clone();
+            self
+        }
+
+        pub fn manual_unban_result(self, result: bool) -> Self {
+            self.manual_unban_results.borrow_mut().push(result);
+            self
+        }
     }
 
     #[test]
@@ -1112,6 +1815,7 @@ pub mod tests {
                 AccountantConfig {
                     payable_scan_interval: Duration::from_millis(10_000),
                     payment_received_scan_interval: Duration::from_millis(10_000),
+                    pending_payment_scan_interval: Duration::from_millis(10_000),
                 },
                 make_wallet("some_wallet_address"),
             ),
@@ -1188,6 +1892,211 @@ pub mod tests {
         );
     }
 
+    fn ui_response_from_accountant(
+        banned_dao: BannedDaoMock,
+        body: MessageBody,
+    ) -> NodeToUiMessage {
+        let system = System::new("ui_response_from_accountant");
+        let subject = Accountant::new(
+            &bc_from_ac_plus_earning_wallet(
+                AccountantConfig {
+                    payable_scan_interval: Duration::from_millis(10_000),
+                    payment_received_scan_interval: Duration::from_millis(10_000),
+                    pending_payment_scan_interval: Duration::from_millis(10_000),
+                },
+                make_wallet("some_wallet_address"),
+            ),
+            Box::new(PayableDaoMock::new()),
+            Box::new(ReceivableDaoMock::new()),
+            Box::new(banned_dao),
+            null_config(),
+        );
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let subject_addr = subject.start();
+        let peer_actors = peer_actors_builder().ui_gateway(ui_gateway).build();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body,
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        ui_gateway_recording
+            .get_record::<NodeToUiMessage>(0)
+            .clone()
+    }
+
+    #[test]
+    fn ban_list_request_produces_ban_list_response() {
+        let banned_dao = BannedDaoMock::new()
+            .ban_list_result(vec![
+                BannedWallet {
+                    wallet: make_wallet("delinquent"),
+                    reason: "delinquency".to_string(),
+                    banned_timestamp: from_time_t(1_000_000),
+                    manual: false,
+                },
+                BannedWallet {
+                    wallet: make_wallet("abusive"),
+                    reason: "spam".to_string(),
+                    banned_timestamp: from_time_t(2_000_000),
+                    manual: true,
+                },
+            ])
+            .pardon_list_result(vec![PardonedWallet {
+                wallet: make_wallet("forgiven"),
+                pardoned_timestamp: from_time_t(3_000_000),
+            }]);
+
+        let response = ui_response_from_accountant(banned_dao, UiBanListRequest {}.tmb(2222));
+
+        assert_eq!(response.target, MessageTarget::ClientId(1234));
+        assert_eq!(
+            UiBanListResponse::fmb(response.body).unwrap(),
+            (
+                UiBanListResponse {
+                    bans: vec![
+                        UiBannedWallet {
+                            wallet: make_wallet("delinquent").to_string(),
+                            reason: "delinquency".to_string(),
+                            banned_timestamp: 1_000_000,
+                            manual: false,
+                        },
+                        UiBannedWallet {
+                            wallet: make_wallet("abusive").to_string(),
+                            reason: "spam".to_string(),
+                            banned_timestamp: 2_000_000,
+                            manual: true,
+                        },
+                    ],
+                    pardons: vec![UiPardonedWallet {
+                        wallet: make_wallet("forgiven").to_string(),
+                        pardoned_timestamp: 3_000_000,
+                    }],
+                },
+                2222
+            )
+        );
+    }
+
+    #[test]
+    fn ban_request_bans_wallet_manually() {
+        init_test_logging();
+        let manual_ban_parameters_arc = Arc::new(Mutex::new(vec![]));
+        let banned_dao = BannedDaoMock::new().manual_ban_parameters(&manual_ban_parameters_arc);
+        let wallet = make_wallet("abusive");
+
+        let response = ui_response_from_accountant(
+            banned_dao,
+            UiBanRequest {
+                wallet: wallet.to_string(),
+                reason: "spam".to_string(),
+            }
+            .tmb(2222),
+        );
+
+        assert_eq!(response.body, UiBanResponse {}.tmb(2222));
+        assert_eq!(
+            *manual_ban_parameters_arc.lock().unwrap(),
+            vec![(wallet.clone(), "spam".to_string())]
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "INFO: Accountant: Wallet {} banned by client 1234: spam",
+            wallet
+        ));
+    }
+
+    #[test]
+    fn ban_request_with_bad_wallet_is_refused() {
+        let manual_ban_parameters_arc = Arc::new(Mutex::new(vec![]));
+        let banned_dao = BannedDaoMock::new().manual_ban_parameters(&manual_ban_parameters_arc);
+
+        let response = ui_response_from_accountant(
+            banned_dao,
+            UiBanRequest {
+                wallet: "booga".to_string(),
+                reason: "spam".to_string(),
+            }
+            .tmb(2222),
+        );
+
+        assert_eq!(
+            response.body,
+            MessageBody {
+                opcode: "ban".to_string(),
+                path: Conversation(2222),
+                payload: Err((
+                    BAN_ERROR,
+                    "'booga' is not a valid Ethereum address".to_string()
+                )),
+            }
+        );
+        assert!(manual_ban_parameters_arc.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn unban_request_pardons_wallet() {
+        init_test_logging();
+        let manual_unban_parameters_arc = Arc::new(Mutex::new(vec![]));
+        let banned_dao = BannedDaoMock::new()
+            .manual_unban_parameters(&manual_unban_parameters_arc)
+            .manual_unban_result(true);
+        let wallet = make_wallet("paid_off_chain");
+
+        let response = ui_response_from_accountant(
+            banned_dao,
+            UiUnbanRequest {
+                wallet: wallet.to_string(),
+            }
+            .tmb(2222),
+        );
+
+        assert_eq!(
+            response.body,
+            UiUnbanResponse { was_banned: true }.tmb(2222)
+        );
+        assert_eq!(
+            *manual_unban_parameters_arc.lock().unwrap(),
+            vec![wallet.clone()]
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "INFO: Accountant: Wallet {} pardoned by client 1234 and unbanned",
+            wallet
+        ));
+    }
+
+    #[test]
+    fn unban_request_with_bad_wallet_is_refused() {
+        let manual_unban_parameters_arc = Arc::new(Mutex::new(vec![]));
+        let banned_dao = BannedDaoMock::new().manual_unban_parameters(&manual_unban_parameters_arc);
+
+        let response = ui_response_from_accountant(
+            banned_dao,
+            UiUnbanRequest {
+                wallet: "0xbooga".to_string(),
+            }
+            .tmb(2222),
+        );
+
+        assert_eq!(
+            response.body,
+            MessageBody {
+                opcode: "unban".to_string(),
+                path: Conversation(2222),
+                payload: Err((
+                    BAN_ERROR,
+                    "'0xbooga' is not a valid Ethereum address".to_string()
+                )),
+            }
+        );
+        assert!(manual_unban_parameters_arc.lock().unwrap().is_empty());
+    }
+
     #[test]
     fn unexpected_ui_message_is_ignored() {
         init_test_logging();
@@ -1197,6 +2106,7 @@ pub mod tests {
                 AccountantConfig {
                     payable_scan_interval: Duration::from_millis(10_000),
                     payment_received_scan_interval: Duration::from_millis(10_000),
+                    pending_payment_scan_interval: Duration::from_millis(10_000),
                 },
                 make_wallet("some_wallet_address"),
             ),
@@ -1251,6 +2161,7 @@ pub mod tests {
                 AccountantConfig {
                     payable_scan_interval: Duration::from_millis(100),
                     payment_received_scan_interval: Duration::from_secs(10_000),
+                    pending_payment_scan_interval: Duration::from_secs(10_000),
                 },
                 make_wallet("some_wallet_address"),
             ),
@@ -1267,6 +2178,8 @@ pub mod tests {
             expected_wallet.clone(),
             expected_amount,
             expected_hash.clone(),
+            U256::from(1),
+            GasFees::Legacy { gas_price: 2 },
         );
         let send_payments = SentPayments {
             payments: vec![Ok(expected_payment.clone())],
@@ -1301,6 +2214,7 @@ pub mod tests {
                 AccountantConfig {
                     payable_scan_interval: Duration::from_millis(100),
                     payment_received_scan_interval: Duration::from_secs(10_000),
+                    pending_payment_scan_interval: Duration::from_secs(10_000),
                 },
                 make_wallet("some_wallet_address"),
             ),
@@ -1361,8 +2275,10 @@ pub mod tests {
                 expected_wallet_inner,
                 expected_amount,
                 expected_pending_payment_transaction_inner,
+                U256::from(1),
+                GasFees::Legacy { gas_price: 2 },
             ))]))
-            .retrieve_transactions_response(Ok(vec![]));
+            .retrieve_transactions_response(Ok(nothing_retrieved_since(0)));
 
         let (accountant_mock, accountant_mock_awaiter, accountant_recording_arc) = make_recorder();
 
@@ -1383,6 +2299,7 @@ pub mod tests {
                     AccountantConfig {
                         payable_scan_interval: Duration::from_millis(100),
                         payment_received_scan_interval: Duration::from_secs(10_000),
+                        pending_payment_scan_interval: Duration::from_secs(10_000),
                     },
                     earning_wallet.clone(),
                 ),
@@ -1408,6 +2325,8 @@ pub mod tests {
             expected_wallet,
             expected_amount,
             expected_pending_payment_transaction,
+            U256::from(1),
+            GasFees::Legacy { gas_price: 2 },
         );
         let payments = actual_payments.payments.clone();
         let maybe_payment = payments.get(0).clone();
@@ -1442,7 +2361,7 @@ pub mod tests {
         );
 
         let blockchain_bridge = Recorder::new()
-            .retrieve_transactions_response(Ok(vec![]))
+            .retrieve_transactions_response(Ok(nothing_retrieved_since(0)))
             .report_accounts_payable_response(Err("Failed to send transaction".to_string()));
 
         let (accountant_mock, _, accountant_recording_arc) = make_recorder();
@@ -1464,6 +2383,7 @@ pub mod tests {
                     AccountantConfig {
                         payable_scan_interval: Duration::from_millis(100),
                         payment_received_scan_interval: Duration::from_secs(10_000),
+                        pending_payment_scan_interval: Duration::from_secs(10_000),
                     },
                     earning_wallet.clone(),
                 ),
@@ -1495,11 +2415,17 @@ pub mod tests {
         let amount = 42u64;
         let expected_transactions = vec![Transaction {
             block_number: 7u64,
+            block_hash: H256::from_uint(&U256::from(7)),
             from: paying_wallet.clone(),
             gwei_amount: amount,
         }];
         let blockchain_bridge =
-            Recorder::new().retrieve_transactions_response(Ok(expected_transactions.clone()));
+            Recorder::new().retrieve_transactions_response(Ok(RetrievedTransactions {
+                transactions: expected_transactions.clone(),
+                new_start_block: 8,
+                caught_up: true,
+                reorganized: false,
+            }));
         let blockchain_bridge_awaiter = blockchain_bridge.get_awaiter();
         let blockchain_bridge_recording = blockchain_bridge.get_recording();
         let (accountant_mock, accountant_awaiter, accountant_recording_arc) = make_recorder();
@@ -1507,6 +2433,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(10_000),
                 payment_received_scan_interval: Duration::from_millis(100),
+                pending_payment_scan_interval: Duration::from_millis(100),
             },
             earning_wallet.clone(),
         );
@@ -1519,7 +2446,11 @@ pub mod tests {
             let receivable_dao = Box::new(
                 ReceivableDaoMock::new()
                     .new_delinquencies_result(vec![])
-                    .paid_delinquencies_result(vec![]),
+                    .paid_delinquencies_result(vec![])
+                    .credited_blocks_result(vec![CreditedBlock {
+                        block_number: 4,
+                        block_hash: H256::from_uint(&U256::from(4)),
+                    }]),
             );
             let config_mock = Box::new(PersistentConfigurationMock::new().start_block_result(5));
             let banned_dao = Box::new(BannedDaoMock::new());
@@ -1551,6 +2482,10 @@ pub mod tests {
             &RetrieveTransactions {
                 start_block: 5u64,
                 recipient: earning_wallet,
+                credited_blocks: vec![CreditedBlock {
+                    block_number: 4,
+                    block_hash: H256::from_uint(&U256::from(4)),
+                }],
             },
             retrieve_transactions_message
         );
@@ -1561,7 +2496,10 @@ pub mod tests {
             received_payments_recording.get_record::<ReceivedPayments>(0);
         assert_eq!(
             &ReceivedPayments {
-                payments: expected_transactions
+                payments: expected_transactions,
+                new_start_block: 8,
+                caught_up: true,
+                reorganized: false,
             },
             received_payments_message
         );
@@ -1571,7 +2509,8 @@ pub mod tests {
     fn accountant_logs_if_no_transactions_were_detected() {
         init_test_logging();
         let earning_wallet = make_wallet("earner3000");
-        let blockchain_bridge = Recorder::new().retrieve_transactions_response(Ok(vec![]));
+        let blockchain_bridge =
+            Recorder::new().retrieve_transactions_response(Ok(nothing_retrieved_since(5)));
         let blockchain_bridge_awaiter = blockchain_bridge.get_awaiter();
         let blockchain_bridge_recording = blockchain_bridge.get_recording();
         let (accountant_mock, _, accountant_recording_arc) = make_recorder();
@@ -1579,6 +2518,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(10_000),
                 payment_received_scan_interval: Duration::from_millis(100),
+                pending_payment_scan_interval: Duration::from_millis(100),
             },
             earning_wallet.clone(),
         );
@@ -1622,6 +2562,7 @@ pub mod tests {
             &RetrieveTransactions {
                 start_block: 5u64,
                 recipient: earning_wallet,
+                credited_blocks: vec![],
             },
             retrieve_transactions_message
         );
@@ -1643,6 +2584,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(10_000),
                 payment_received_scan_interval: Duration::from_millis(100),
+                pending_payment_scan_interval: Duration::from_millis(100),
             },
             earning_wallet.clone(),
         );
@@ -1695,6 +2637,7 @@ pub mod tests {
         let gwei_amount = 42u64;
         let expected_payment = Transaction {
             block_number: 7u64,
+            block_hash: H256::from_uint(&U256::from(7)),
             from: wallet.clone(),
             gwei_amount,
         };
@@ -1709,6 +2652,7 @@ pub mod tests {
                 AccountantConfig {
                     payable_scan_interval: Duration::from_secs(10_000),
                     payment_received_scan_interval: Duration::from_secs(10_000),
+                    pending_payment_scan_interval: Duration::from_secs(10_000),
                 },
                 earning_wallet.clone(),
             ),
@@ -1724,6 +2668,9 @@ pub mod tests {
         subject
             .try_send(ReceivedPayments {
                 payments: vec![expected_payment.clone(), expected_payment.clone()],
+                new_start_block: 8,
+                caught_up: true,
+                reorganized: false,
             })
             .expect("unexpected actix error");
         System::current().stop();
@@ -1731,15 +2678,241 @@ pub mod tests {
         let more_money_received_params = more_money_received_params_arc.lock().unwrap();
         assert_eq!(1, more_money_received_params.len());
 
-        let more_money_received_params = more_money_received_params.get(0).unwrap();
-        assert_eq!(2, more_money_received_params.len());
+        let (payments, new_start_block) = more_money_received_params.get(0).unwrap();
+        assert_eq!(2, payments.len());
+        assert_eq!(8, *new_start_block);
+
+        let first_payment = payments.get(0).unwrap();
+        assert_eq!(expected_payment.from, first_payment.from);
+        assert_eq!(gwei_amount, first_payment.gwei_amount);
+        let second_payment = payments.get(1).unwrap();
+        assert_eq!(expected_payment.from, second_payment.from);
+        assert_eq!(gwei_amount, second_payment.gwei_amount);
+    }
+
+    #[test]
+    fn accountant_reports_an_advanced_start_block_even_without_payments() {
+        let earning_wallet = make_wallet("earner3000");
+        let blockchain_bridge =
+            Recorder::new().retrieve_transactions_response(Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: 10_005,
+                caught_up: false,
+                reorganized: false,
+            }));
+        let (accountant_mock, accountant_awaiter, accountant_recording_arc) = make_recorder();
+        let config = bc_from_ac_plus_earning_wallet(
+            AccountantConfig {
+                payable_scan_interval: Duration::from_secs(10_000),
+                payment_received_scan_interval: Duration::from_secs(10_000),
+                pending_payment_scan_interval: Duration::from_secs(10_000),
+            },
+            earning_wallet,
+        );
+
+        thread::spawn(move || {
+            let system =
+                System::new("accountant_reports_an_advanced_start_block_even_without_payments");
+            let mut subject = Accountant::new(
+                &config,
+                Box::new(PayableDaoMock::new()),
+                Box::new(ReceivableDaoMock::new()),
+                Box::new(BannedDaoMock::new()),
+                Box::new(PersistentConfigurationMock::new().start_block_result(5)),
+            );
+            let peer_actors = peer_actors_builder()
+                .blockchain_bridge(blockchain_bridge)
+                .accountant(accountant_mock)
+                .build();
+            subject.handle_bind_message(BindMessage { peer_actors });
+            subject.scan_for_received_payments();
+
+            system.run();
+        });
+
+        accountant_awaiter.await_message_count(1);
+        let accountant_recording = accountant_recording_arc.lock().unwrap();
+        assert_eq!(
+            accountant_recording.get_record::<ReceivedPayments>(0),
+            &ReceivedPayments {
+                payments: vec![],
+                new_start_block: 10_005,
+                caught_up: false,
+                reorganized: false,
+            }
+        );
+    }
+
+    #[test]
+    fn accountant_scans_the_next_chunk_of_blocks_at_once_when_not_caught_up() {
+        let earning_wallet = make_wallet("earner3000");
+        let blockchain_bridge =
+            Recorder::new().retrieve_transactions_response(Ok(nothing_retrieved_since(1042)));
+        let blockchain_bridge_awaiter = blockchain_bridge.get_awaiter();
+        let blockchain_bridge_recording = blockchain_bridge.get_recording();
+        let more_money_received_params_arc = Arc::new(Mutex::new(vec![]));
+        let more_money_received_params_arc_inner = more_money_received_params_arc.clone();
+        let config = bc_from_ac_plus_earning_wallet(
+            AccountantConfig {
+                payable_scan_interval: Duration::from_secs(10_000),
+                payment_received_scan_interval: Duration::from_secs(10_000),
+                pending_payment_scan_interval: Duration::from_secs(10_000),
+            },
+            earning_wallet.clone(),
+        );
+
+        thread::spawn(move || {
+            let system =
+                System::new("accountant_scans_the_next_chunk_of_blocks_at_once_when_not_caught_up");
+            let receivable_dao = ReceivableDaoMock::new()
+                .more_money_received_parameters(&more_money_received_params_arc_inner)
+                .more_money_received_result(Ok(()));
+            let subject = Accountant::new(
+                &config,
+                Box::new(PayableDaoMock::new()),
+                Box::new(receivable_dao),
+                Box::new(BannedDaoMock::new()),
+                Box::new(PersistentConfigurationMock::new().start_block_result(1042)),
+            );
+            let peer_actors = peer_actors_builder()
+                .blockchain_bridge(blockchain_bridge)
+                .build();
+            let subject_addr: Addr<Accountant> = subject.start();
+            let subject_subs = Accountant::make_subs_from(&subject_addr);
+            send_bind_message!(subject_subs, peer_actors);
+
+            subject_addr
+                .try_send(ReceivedPayments {
+                    payments: vec![],
+                    new_start_block: 1042,
+                    caught_up: false,
+                    reorganized: false,
+                })
+                .unwrap();
+
+            system.run();
+        });
+
+        blockchain_bridge_awaiter.await_message_count(1);
+        let retrieve_transactions_recording = blockchain_bridge_recording.lock().unwrap();
+        assert_eq!(
+            retrieve_transactions_recording.get_record::<RetrieveTransactions>(0),
+            &RetrieveTransactions {
+                start_block: 1042,
+                recipient: earning_wallet,
+                credited_blocks: vec![],
+            }
+        );
+        let more_money_received_params = more_money_received_params_arc.lock().unwrap();
+        assert_eq!(*more_money_received_params, vec![(vec![], 1042)]);
+    }
+
+    #[test]
+    fn accountant_does_not_scan_the_next_chunk_of_blocks_if_this_one_was_not_recorded() {
+        // Unbound, the Accountant would panic if it tried to scan again
+        let receivable_dao =
+            ReceivableDaoMock::new().more_money_received_result(Err("BOOM".to_string()));
+        let accountant = Accountant::new(
+            &bc_from_ac_plus_earning_wallet(
+                AccountantConfig {
+                    payable_scan_interval: Duration::from_secs(10_000),
+                    payment_received_scan_interval: Duration::from_secs(10_000),
+                    pending_payment_scan_interval: Duration::from_secs(10_000),
+                },
+                make_wallet("earner3000"),
+            ),
+            Box::new(PayableDaoMock::new()),
+            Box::new(receivable_dao),
+            Box::new(BannedDaoMock::new()),
+            null_config(),
+        );
+        let system = System::new(
+            "accountant_does_not_scan_the_next_chunk_of_blocks_if_this_one_was_not_recorded",
+        );
+        let subject = accountant.start();
+
+        subject
+            .try_send(ReceivedPayments {
+                payments: vec![],
+                new_start_block: 1042,
+                caught_up: false,
+                reorganized: false,
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+    }
+
+    #[test]
+    fn accountant_takes_back_orphaned_payments_and_scans_again_after_a_reorganization() {
+        let earning_wallet = make_wallet("earner3000");
+        let blockchain_bridge =
+            Recorder::new().retrieve_transactions_response(Ok(nothing_retrieved_since(1000)));
+        let blockchain_bridge_awaiter = blockchain_bridge.get_awaiter();
+        let blockchain_bridge_recording = blockchain_bridge.get_recording();
+        let more_money_received_params_arc = Arc::new(Mutex::new(vec![]));
+        let more_money_received_params_arc_inner = more_money_received_params_arc.clone();
+        let reverse_orphaned_payments_params_arc = Arc::new(Mutex::new(vec![]));
+        let reverse_orphaned_payments_params_arc_inner =
+            reverse_orphaned_payments_params_arc.clone();
+        let config = bc_from_ac_plus_earning_wallet(
+            AccountantConfig {
+                payable_scan_interval: Duration::from_secs(10_000),
+                payment_received_scan_interval: Duration::from_secs(10_000),
+                pending_payment_scan_interval: Duration::from_secs(10_000),
+            },
+            earning_wallet.clone(),
+        );
+
+        thread::spawn(move || {
+            let system = System::new(
+                "accountant_takes_back_orphaned_payments_and_scans_again_after_a_reorganization",
+            );
+            let receivable_dao = ReceivableDaoMock::new()
+                .more_money_received_parameters(&more_money_received_params_arc_inner)
+                .reverse_orphaned_payments_parameters(&reverse_orphaned_payments_params_arc_inner)
+                .reverse_orphaned_payments_result(Ok(()));
+            let subject = Accountant::new(
+                &config,
+                Box::new(PayableDaoMock::new()),
+                Box::new(receivable_dao),
+                Box::new(BannedDaoMock::new()),
+                Box::new(PersistentConfigurationMock::new().start_block_result(1000)),
+            );
+            let peer_actors = peer_actors_builder()
+                .blockchain_bridge(blockchain_bridge)
+                .build();
+            let subject_addr: Addr<Accountant> = subject.start();
+            let subject_subs = Accountant::make_subs_from(&subject_addr);
+            send_bind_message!(subject_subs, peer_actors);
+
+            subject_addr
+                .try_send(ReceivedPayments {
+                    payments: vec![],
+                    new_start_block: 1000,
+                    caught_up: false,
+                    reorganized: true,
+                })
+                .unwrap();
 
-        let first_payment = more_money_received_params.get(0).unwrap();
-        assert_eq!(expected_payment.from, first_payment.from);
-        assert_eq!(gwei_amount, first_payment.gwei_amount);
-        let second_payment = more_money_received_params.get(1).unwrap();
-        assert_eq!(expected_payment.from, second_payment.from);
-        assert_eq!(gwei_amount, second_payment.gwei_amount);
+            system.run();
+        });
+
+        blockchain_bridge_awaiter.await_message_count(1);
+        let retrieve_transactions_recording = blockchain_bridge_recording.lock().unwrap();
+        assert_eq!(
+            retrieve_transactions_recording.get_record::<RetrieveTransactions>(0),
+            &RetrieveTransactions {
+                start_block: 1000,
+                recipient: earning_wallet,
+                credited_blocks: vec![],
+            }
+        );
+        let reverse_orphaned_payments_params = reverse_orphaned_payments_params_arc.lock().unwrap();
+        assert_eq!(*reverse_orphaned_payments_params, vec![1000]);
+        let more_money_received_params = more_money_received_params_arc.lock().unwrap();
+        assert!(more_money_received_params.is_empty());
     }
 
     #[test]
@@ -1747,7 +2920,7 @@ pub mod tests {
         init_test_logging();
         let (blockchain_bridge, blockchain_bridge_awaiter, _) = make_recorder();
         let blockchain_bridge = blockchain_bridge
-            .retrieve_transactions_response(Ok(vec![]))
+            .retrieve_transactions_response(Ok(nothing_retrieved_since(0)))
             .report_accounts_payable_response(Ok(vec![]));
 
         thread::spawn(move || {
@@ -1757,6 +2930,7 @@ pub mod tests {
                 AccountantConfig {
                     payable_scan_interval: Duration::from_millis(100),
                     payment_received_scan_interval: Duration::from_secs(100),
+                    pending_payment_scan_interval: Duration::from_secs(100),
                 },
                 make_wallet("hi"),
             );
@@ -1818,6 +2992,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(1000),
                 payment_received_scan_interval: Duration::from_secs(1000),
+                pending_payment_scan_interval: Duration::from_secs(1000),
             },
             make_wallet("buy"),
             make_wallet("hi"),
@@ -1860,6 +3035,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(1000),
+                pending_payment_scan_interval: Duration::from_secs(1000),
             },
             make_wallet("mine"),
         );
@@ -1923,6 +3099,34 @@ pub mod tests {
         assert_eq!(blockchain_bridge_recordings.len(), 0);
     }
 
+    #[test]
+    fn scan_for_payables_reports_payable_and_receivable_totals_to_metrics() {
+        let config = bc_from_ac_plus_earning_wallet(
+            AccountantConfig {
+                payable_scan_interval: Duration::from_secs(100),
+                payment_received_scan_interval: Duration::from_secs(1000),
+                pending_payment_scan_interval: Duration::from_secs(1000),
+            },
+            make_wallet("mine"),
+        );
+        let payable_dao = PayableDaoMock::new()
+            .non_pending_payables_result(vec![])
+            .total_result(12345);
+        let receivable_dao = ReceivableDaoMock::new().total_result(67890);
+        let mut subject = Accountant::new(
+            &config,
+            Box::new(payable_dao),
+            Box::new(receivable_dao),
+            Box::new(BannedDaoMock::new()),
+            null_config(),
+        );
+
+        subject.scan_for_payables();
+
+        assert_eq!(metrics().value(&PAYABLE_TOTAL, &[]), Some(12345.0));
+        assert_eq!(metrics().value(&RECEIVABLE_TOTAL, &[]), Some(67890.0));
+    }
+
     #[test]
     fn scan_for_payables_message_triggers_payment_for_balances_over_the_curve() {
         init_test_logging();
@@ -1930,6 +3134,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_millis(100),
                 payment_received_scan_interval: Duration::from_millis(1_000),
+                pending_payment_scan_interval: Duration::from_millis(1_000),
             },
             make_wallet("mine"),
         );
@@ -1962,7 +3167,7 @@ pub mod tests {
         let (mut blockchain_bridge, blockchain_bridge_awaiter, blockchain_bridge_recordings_arc) =
             make_recorder();
         blockchain_bridge = blockchain_bridge
-            .retrieve_transactions_response(Ok(vec![]))
+            .retrieve_transactions_response(Ok(nothing_retrieved_since(0)))
             .report_accounts_payable_response(Ok(vec![]));
 
         thread::spawn(move || {
@@ -2001,13 +3206,15 @@ pub mod tests {
     fn payment_received_scan_triggers_scan_for_delinquencies() {
         let ban_parameters_arc = Arc::new(Mutex::new(vec![]));
         let ban_parameters_arc_inner = ban_parameters_arc.clone();
-        let blockchain_bridge = Recorder::new().retrieve_transactions_response(Ok(vec![]));
+        let blockchain_bridge =
+            Recorder::new().retrieve_transactions_response(Ok(nothing_retrieved_since(0)));
         thread::spawn(move || {
             let system = System::new("payment_received_scan_triggers_scan_for_delinquencies");
             let config = bc_from_ac_plus_earning_wallet(
                 AccountantConfig {
                     payable_scan_interval: Duration::from_secs(10_000),
                     payment_received_scan_interval: Duration::from_millis(100),
+                    pending_payment_scan_interval: Duration::from_millis(100),
                 },
                 make_wallet("hi"),
             );
@@ -2058,6 +3265,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(1000),
+                pending_payment_scan_interval: Duration::from_secs(1000),
             },
             make_wallet("mine"),
         );
@@ -2117,6 +3325,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             make_wallet("hi"),
         );
@@ -2155,6 +3364,7 @@ pub mod tests {
 
         System::current().stop_with_code(0);
         system.run();
+        assert_eq!(metrics().value(&BYTES_ROUTED, &[]), Some(1234.0));
         let more_money_receivable_parameters = more_money_receivable_parameters_arc.lock().unwrap();
         assert_eq!(
             more_money_receivable_parameters[0],
@@ -2174,6 +3384,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             consuming_wallet.clone(),
             make_wallet("our earning wallet"),
@@ -2230,6 +3441,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             earning_wallet.clone(),
         );
@@ -2284,6 +3496,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             make_wallet("hi"),
         );
@@ -2323,6 +3536,10 @@ pub mod tests {
 
         System::current().stop_with_code(0);
         system.run();
+        assert_eq!(
+            metrics().value(&BYTES_CONSUMED, &[("service", "routing")]),
+            Some(1234.0)
+        );
         let more_money_payable_parameters = more_money_payable_parameters_arc.lock().unwrap();
         assert_eq!(
             more_money_payable_parameters[0],
@@ -2341,6 +3558,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             consuming_wallet.clone(),
             make_wallet("the earning wallet"),
@@ -2395,6 +3613,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             earning_wallet.clone(),
         );
@@ -2447,6 +3666,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             make_wallet("hi"),
         );
@@ -2485,6 +3705,7 @@ pub mod tests {
 
         System::current().stop();
         system.run();
+        assert_eq!(metrics().value(&BYTES_EXITED, &[]), Some(1234.0));
         let more_money_receivable_parameters = more_money_receivable_parameters_arc.lock().unwrap();
         assert_eq!(
             more_money_receivable_parameters[0],
@@ -2504,6 +3725,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             consuming_wallet.clone(),
             make_wallet("my earning wallet"),
@@ -2560,6 +3782,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             earning_wallet.clone(),
         );
@@ -2614,6 +3837,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             make_wallet("hi"),
         );
@@ -2653,6 +3877,10 @@ pub mod tests {
 
         System::current().stop_with_code(0);
         system.run();
+        assert_eq!(
+            metrics().value(&BYTES_CONSUMED, &[("service", "exit")]),
+            Some(1234.0)
+        );
         let more_money_payable_parameters = more_money_payable_parameters_arc.lock().unwrap();
         assert_eq!(
             more_money_payable_parameters[0],
@@ -2672,6 +3900,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             consuming_wallet.clone(),
             make_wallet("own earning wallet"),
@@ -2726,6 +3955,7 @@ pub mod tests {
             AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             earning_wallet.clone(),
         );
@@ -2828,6 +4058,8 @@ pub mod tests {
                 wallet.clone(),
                 std::u64::MAX,
                 H256::from_uint(&U256::from(1)),
+                U256::from(2),
+                GasFees::Legacy { gas_price: 3 },
             ))],
         };
         let mut subject = Accountant::new(
@@ -2850,6 +4082,430 @@ pub mod tests {
         ));
     }
 
+    fn make_pending_payment(wallet: &Wallet, hash: u64, seconds_ago: u64) -> Payment {
+        Payment {
+            to: wallet.clone(),
+            amount: 1234,
+            timestamp: SystemTime::now().sub(Duration::from_secs(seconds_ago)),
+            transaction: H256::from_uint(&U256::from(hash)),
+            nonce: U256::from(7),
+            fees: GasFees::Legacy { gas_price: 10 },
+        }
+    }
+
+    fn make_subject_with_payable_dao(payable_dao: PayableDaoMock) -> Accountant {
+        Accountant::new(
+            &BootstrapperConfig::new(),
+            Box::new(payable_dao),
+            Box::new(ReceivableDaoMock::new()),
+            Box::new(BannedDaoMock::new()),
+            null_config(),
+        )
+    }
+
+    #[test]
+    fn accountant_pending_payment_scan_timer_triggers_requests_for_transaction_receipts() {
+        let first_payment = make_pending_payment(&make_wallet("first"), 1, 10);
+        let second_payment = make_pending_payment(&make_wallet("second"), 2, 10);
+        let blockchain_bridge = Recorder::new()
+            .retrieve_transactions_response(Ok(nothing_retrieved_since(0)))
+            .request_transaction_receipts_response(vec![
+                Ok(TransactionStatus::Pending),
+                Err(BlockchainError::QueryFailed),
+            ]);
+        let blockchain_bridge_awaiter = blockchain_bridge.get_awaiter();
+        let blockchain_bridge_recording = blockchain_bridge.get_recording();
+        let (accountant_mock, accountant_awaiter, accountant_recording_arc) = make_recorder();
+        let config = bc_from_ac_plus_earning_wallet(
+            AccountantConfig {
+                payable_scan_interval: Duration::from_secs(10_000),
+                payment_received_scan_interval: Duration::from_secs(10_000),
+                pending_payment_scan_interval: Duration::from_millis(100),
+            },
+            make_wallet("earner"),
+        );
+        let payable_dao = PayableDaoMock::new()
+            .pending_payments_result(vec![])
+            .pending_payments_result(vec![first_payment.clone(), second_payment.clone()]);
+
+        thread::spawn(move || {
+            let system = System::new(
+                "accountant_pending_payment_scan_timer_triggers_requests_for_transaction_receipts",
+            );
+            let subject = Accountant::new(
+                &config,
+                Box::new(payable_dao),
+                Box::new(ReceivableDaoMock::new()),
+                Box::new(BannedDaoMock::new()),
+                null_config(),
+            );
+            let peer_actors = peer_actors_builder()
+                .blockchain_bridge(blockchain_bridge)
+                .accountant(accountant_mock)
+                .build();
+            let subject_addr: Addr<Accountant> = subject.start();
+            let subject_subs = Accountant::make_subs_from(&subject_addr);
+
+            send_bind_message!(subject_subs, peer_actors);
+            subject_addr.try_send(StartMessage {}).unwrap();
+
+            system.run();
+        });
+
+        blockchain_bridge_awaiter.await_message_count(2);
+        let blockchain_bridge_recording = blockchain_bridge_recording.lock().unwrap();
+        assert_eq!(
+            blockchain_bridge_recording.get_record::<RequestTransactionReceipts>(1),
+            &RequestTransactionReceipts {
+                payments: vec![first_payment.clone(), second_payment.clone()]
+            }
+        );
+        accountant_awaiter.await_message_count(1);
+        let accountant_recording = accountant_recording_arc.lock().unwrap();
+        assert_eq!(
+            accountant_recording.get_record::<ReportTransactionReceipts>(0),
+            &ReportTransactionReceipts {
+                payments_with_statuses: vec![
+                    (first_payment, Ok(TransactionStatus::Pending)),
+                    (second_payment, Err(BlockchainError::QueryFailed)),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn transaction_receipts_confirm_a_payment_when_any_of_its_attempts_succeeded() {
+        init_test_logging();
+        let wallet = make_wallet("booga");
+        let payment_confirmed_parameters_arc = Arc::new(Mutex::new(vec![]));
+        let mut subject = make_subject_with_payable_dao(
+            PayableDaoMock::new()
+                .payment_confirmed_parameters(&payment_confirmed_parameters_arc)
+                .payment_confirmed_result(Ok(())),
+        );
+        let before = SystemTime::now();
+
+        subject.handle_transaction_receipts(ReportTransactionReceipts {
+            payments_with_statuses: vec![
+                (
+                    make_pending_payment(&wallet, 1, 4000),
+                    Ok(TransactionStatus::Succeeded),
+                ),
+                (
+                    make_pending_payment(&wallet, 2, 2000),
+                    Err(BlockchainError::QueryFailed),
+                ),
+            ],
+        });
+
+        let after = SystemTime::now();
+        let payment_confirmed_parameters = payment_confirmed_parameters_arc.lock().unwrap();
+        let (actual_wallet, amount, timestamp, hash) = payment_confirmed_parameters[0].clone();
+        assert_eq!(actual_wallet, wallet);
+        assert_eq!(amount, 1234);
+        assert!(before <= timestamp && timestamp <= after);
+        assert_eq!(hash, H256::from_uint(&U256::from(1)));
+        assert_eq!(payment_confirmed_parameters.len(), 1);
+        TestLogHandler::new().exists_log_containing(&format!(
+            "INFO: Accountant: Payment of 1234 to {} confirmed in transaction {:#x}",
+            wallet,
+            H256::from_uint(&U256::from(1))
+        ));
+    }
+
+    #[test]
+    fn transaction_receipts_return_a_reverted_payment_to_the_payable_balance() {
+        init_test_logging();
+        let wallet = make_wallet("booga");
+        let failed_payment = make_pending_payment(&wallet, 2, 2000);
+        let payment_failed_parameters_arc = Arc::new(Mutex::new(vec![]));
+        let mut subject = make_subject_with_payable_dao(
+            PayableDaoMock::new()
+                .payment_failed_parameters(&payment_failed_parameters_arc)
+                .payment_failed_result(Ok(())),
+        );
+
+        subject.handle_transaction_receipts(ReportTransactionReceipts {
+            payments_with_statuses: vec![
+                (
+                    make_pending_payment(&wallet, 1, 4000),
+                    Ok(TransactionStatus::Pending),
+                ),
+                (failed_payment.clone(), Ok(TransactionStatus::Failed)),
+            ],
+        });
+
+        assert_eq!(
+            *payment_failed_parameters_arc.lock().unwrap(),
+            vec![failed_payment]
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "WARN: Accountant: Payment of 1234 to {} failed in transaction {:#x}; it will be paid again",
+            wallet,
+            H256::from_uint(&U256::from(2))
+        ));
+    }
+
+    #[test]
+    fn transaction_receipts_leave_payments_alone_while_unknown_or_recently_sent() {
+        init_test_logging();
+        let unknown_wallet = make_wallet("unknown");
+        let recent_wallet = make_wallet("recent");
+        let mut subject = make_subject_with_payable_dao(PayableDaoMock::new());
+
+        subject.handle_transaction_receipts(ReportTransactionReceipts {
+            payments_with_statuses: vec![
+                (
+                    make_pending_payment(&unknown_wallet, 1, 4000),
+                    Err(BlockchainError::QueryFailed),
+                ),
+                (
+                    make_pending_payment(&recent_wallet, 2, 4000),
+                    Ok(TransactionStatus::Pending),
+                ),
+                (
+                    make_pending_payment(&recent_wallet, 3, 10),
+                    Ok(TransactionStatus::Pending),
+                ),
+            ],
+        });
+
+        // The mock would panic if it were asked to record anything, and the bridge is unbound
+        TestLogHandler::new().exists_log_containing(&format!(
+            "WARN: Accountant: Unable to check transaction {:#x} paying 1234 to {}: Blockchain QueryFailed. Will try again later",
+            H256::from_uint(&U256::from(1)),
+            unknown_wallet
+        ));
+    }
+
+    #[test]
+    fn transaction_receipts_hold_back_a_stuck_batch_until_all_of_it_is_due() {
+        init_test_logging();
+        let checked_wallet = make_wallet("checked");
+        let unchecked_wallet = make_wallet("unchecked");
+        let mut subject = make_subject_with_payable_dao(PayableDaoMock::new());
+
+        subject.handle_transaction_receipts(ReportTransactionReceipts {
+            payments_with_statuses: vec![
+                (
+                    make_pending_payment(&checked_wallet, 2, 2000),
+                    Ok(TransactionStatus::Pending),
+                ),
+                (
+                    make_pending_payment(&unchecked_wallet, 2, 2000),
+                    Err(BlockchainError::QueryFailed),
+                ),
+            ],
+        });
+
+        // The bridge is unbound, so resubmitting the checked payment by itself would panic
+        TestLogHandler::new().exists_log_containing(&format!(
+            "WARN: Accountant: Unable to check transaction {:#x} paying 1234 to {}: Blockchain QueryFailed. Will try again later",
+            H256::from_uint(&U256::from(2)),
+            unchecked_wallet
+        ));
+    }
+
+    #[test]
+    fn transaction_receipts_resubmit_a_stuck_payment_at_a_higher_gas_price() {
+        let wallet = make_wallet("booga");
+        let stuck_payment = make_pending_payment(&wallet, 2, 2000);
+        let resubmitted_payment = Payment {
+            fees: GasFees::Legacy { gas_price: 12 },
+            ..make_pending_payment(&wallet, 3, 0)
+        };
+        let blockchain_bridge =
+            Recorder::new().resubmit_payments_response(Ok(vec![Ok(resubmitted_payment.clone())]));
+        let blockchain_bridge_awaiter = blockchain_bridge.get_awaiter();
+        let blockchain_bridge_recording = blockchain_bridge.get_recording();
+        let (accountant_mock, accountant_awaiter, accountant_recording_arc) = make_recorder();
+        let report = ReportTransactionReceipts {
+            payments_with_statuses: vec![
+                (
+                    make_pending_payment(&wallet, 1, 4000),
+                    Ok(TransactionStatus::Pending),
+                ),
+                (stuck_payment.clone(), Ok(TransactionStatus::Pending)),
+            ],
+        };
+
+        thread::spawn(move || {
+            let system =
+                System::new("transaction_receipts_resubmit_a_stuck_payment_at_a_higher_gas_price");
+            let subject = make_subject_with_payable_dao(PayableDaoMock::new());
+            let peer_actors = peer_actors_builder()
+                .blockchain_bridge(blockchain_bridge)
+                .accountant(accountant_mock)
+                .build();
+            let subject_addr: Addr<Accountant> = subject.start();
+            let subject_subs = Accountant::make_subs_from(&subject_addr);
+
+            send_bind_message!(subject_subs, peer_actors);
+            subject_addr.try_send(report).unwrap();
+
+            system.run();
+        });
+
+        blockchain_bridge_awaiter.await_message_count(1);
+        let blockchain_bridge_recording = blockchain_bridge_recording.lock().unwrap();
+        assert_eq!(
+            blockchain_bridge_recording.get_record::<ResubmitPayments>(0),
+            &ResubmitPayments {
+                payments: vec![Payment {
+                    fees: GasFees::Legacy { gas_price: 12 },
+                    ..stuck_payment
+                }]
+            }
+        );
+        accountant_awaiter.await_message_count(1);
+        let accountant_recording = accountant_recording_arc.lock().unwrap();
+        assert_eq!(
+            accountant_recording.get_record::<ResentPayments>(0),
+            &ResentPayments {
+                payments: vec![Ok(resubmitted_payment)]
+            }
+        );
+    }
+
+    #[test]
+    fn transaction_receipts_leave_a_stuck_payment_pending_once_the_max_gas_price_blocks_a_replacement(
+    ) {
+        init_test_logging();
+        let wallet = make_wallet("booga");
+        let stuck_payment = make_pending_payment(&wallet, 2, 2000);
+        let mut config = BootstrapperConfig::new();
+        config.blockchain_bridge_config.max_gas_price = 10;
+        let mut subject = Accountant::new(
+            &config,
+            Box::new(PayableDaoMock::new()),
+            Box::new(ReceivableDaoMock::new()),
+            Box::new(BannedDaoMock::new()),
+            null_config(),
+        );
+        let report = || ReportTransactionReceipts {
+            payments_with_statuses: vec![(stuck_payment.clone(), Ok(TransactionStatus::Pending))],
+        };
+
+        // The bridge is unbound, so a resubmission would panic
+        subject.handle_transaction_receipts(report());
+        subject.handle_transaction_receipts(report());
+
+        let warning = format!(
+            "WARN: Accountant: Transaction {:#x} paying 1234 to {} is stuck at gas price 10 Gwei, and the max gas price of 10 Gwei leaves no room to replace it. It will stay pending until it is mined or the max gas price is raised",
+            stuck_payment.transaction, wallet
+        );
+        assert_eq!(TestLogHandler::new().count_logs_containing(&warning), 1);
+    }
+
+    #[test]
+    fn transaction_receipts_leave_a_stuck_payment_pending_when_the_max_gas_price_trims_the_bump_too_far(
+    ) {
+        init_test_logging();
+        let wallet = make_wallet("trimmed");
+        let stuck_payment = Payment {
+            fees: GasFees::Legacy { gas_price: 100 },
+            ..make_pending_payment(&wallet, 4, 2000)
+        };
+        let mut config = BootstrapperConfig::new();
+        config.blockchain_bridge_config.max_gas_price = 109;
+        let mut subject = Accountant::new(
+            &config,
+            Box::new(PayableDaoMock::new()),
+            Box::new(ReceivableDaoMock::new()),
+            Box::new(BannedDaoMock::new()),
+            null_config(),
+        );
+
+        // The bridge is unbound, so a resubmission would panic
+        subject.handle_transaction_receipts(ReportTransactionReceipts {
+            payments_with_statuses: vec![(stuck_payment.clone(), Ok(TransactionStatus::Pending))],
+        });
+
+        TestLogHandler::new().exists_log_containing(&format!(
+            "WARN: Accountant: Transaction {:#x} paying 1234 to {} is stuck at gas price 100 Gwei, and the max gas price of 109 Gwei leaves no room to replace it.",
+            stuck_payment.transaction, wallet
+        ));
+    }
+
+    #[test]
+    fn resent_payments_are_recorded_as_new_attempts() {
+        init_test_logging();
+        let wallet = make_wallet("booga");
+        let resubmitted_payment = make_pending_payment(&wallet, 3, 0);
+        let payment_resubmitted_parameters_arc = Arc::new(Mutex::new(vec![]));
+        let mut subject = make_subject_with_payable_dao(
+            PayableDaoMock::new()
+                .payment_resubmitted_parameters(&payment_resubmitted_parameters_arc)
+                .payment_resubmitted_result(Ok(())),
+        );
+
+        subject.handle_resent_payments(ResentPayments {
+            payments: vec![
+                Ok(resubmitted_payment.clone()),
+                Err(BlockchainError::TransactionFailed("booga".to_string())),
+            ],
+        });
+
+        assert_eq!(
+            *payment_resubmitted_parameters_arc.lock().unwrap(),
+            vec![resubmitted_payment]
+        );
+        let log_handler = TestLogHandler::new();
+        log_handler.exists_log_containing(&format!(
+            "INFO: Accountant: Resubmitted payment of 1234 to {} at gas price 10 Gwei (transaction {:#x})",
+            wallet,
+            H256::from_uint(&U256::from(3))
+        ));
+        log_handler.exists_log_containing(
+            r#"WARN: Accountant: Blockchain TransactionFailed("booga"). Resubmission will be retried on the next scan."#,
+        );
+    }
+
+    #[test]
+    fn bump_gas_price_raises_the_price_by_a_fifth_and_at_least_by_one() {
+        assert_eq!(Accountant::bump_gas_price(0), 1);
+        assert_eq!(Accountant::bump_gas_price(1), 2);
+        assert_eq!(Accountant::bump_gas_price(10), 12);
+        assert_eq!(Accountant::bump_gas_price(99), 118);
+        assert_eq!(Accountant::bump_gas_price(std::u64::MAX), std::u64::MAX);
+    }
+
+    #[test]
+    fn bump_gas_fees_raises_both_the_max_fee_and_the_priority_fee_of_a_fee_market_payment() {
+        assert_eq!(
+            Accountant::bump_gas_fees(GasFees::Legacy { gas_price: 10 }),
+            GasFees::Legacy { gas_price: 12 }
+        );
+        assert_eq!(
+            Accountant::bump_gas_fees(GasFees::FeeMarket {
+                max_fee: 40,
+                priority_fee: 2
+            }),
+            GasFees::FeeMarket {
+                max_fee: 48,
+                priority_fee: 3
+            }
+        );
+    }
+
+    #[test]
+    fn outbids_requires_every_price_to_rise_by_at_least_a_tenth() {
+        let legacy = |gas_price| GasFees::Legacy { gas_price };
+        let fee_market = |max_fee, priority_fee| GasFees::FeeMarket {
+            max_fee,
+            priority_fee,
+        };
+
+        assert!(Accountant::outbids(legacy(110), legacy(100)));
+        assert!(!Accountant::outbids(legacy(109), legacy(100)));
+        assert!(!Accountant::outbids(legacy(100), legacy(100)));
+        assert!(Accountant::outbids(legacy(1), legacy(0)));
+        assert!(!Accountant::outbids(legacy(0), legacy(0)));
+        assert!(Accountant::outbids(fee_market(44, 3), fee_market(40, 2)));
+        assert!(!Accountant::outbids(fee_market(44, 2), fee_market(40, 2)));
+        assert!(!Accountant::outbids(fee_market(43, 3), fee_market(40, 2)));
+    }
+
     #[test]
     fn jackass_unsigned_to_signed_handles_zero() {
         let result = jackass_unsigned_to_signed(0u64);
@@ -2897,4 +4553,13 @@ pub mod tests {
     fn null_config() -> Box<dyn PersistentConfiguration> {
         Box::new(PersistentConfigurationMock::new().start_block_result(0))
     }
+
+    fn nothing_retrieved_since(start_block: u64) -> RetrievedTransactions {
+        RetrievedTransactions {
+            transactions: vec![],
+            new_start_block: start_block,
+            caught_up: true,
+            reorganized: false,
+        }
+    }
 }
@@ -1,14 +1,15 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 use crate::accountant::{jackass_unsigned_to_signed, PaymentError};
+use crate::blockchain::blockchain_interface::GasFees;
 use crate::database::dao_utils;
 use crate::database::db_initializer::ConnectionWrapper;
 use crate::sub_lib::wallet::Wallet;
 use rusqlite::types::{ToSql, Type};
-use rusqlite::{Error, OptionalExtension, NO_PARAMS};
+use rusqlite::{Connection, Error, OptionalExtension, Transaction, NO_PARAMS};
 use serde_json::{self, json};
 use std::fmt::Debug;
 use std::time::SystemTime;
-use web3::types::H256;
+use web3::types::{H256, U256};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct PayableAccount {
@@ -24,15 +25,19 @@ pub struct Payment {
     pub amount: u64,
     pub timestamp: SystemTime,
     pub transaction: H256,
+    pub nonce: U256,
+    pub fees: GasFees,
 }
 
 impl Payment {
-    pub fn new(to: Wallet, amount: u64, transaction: H256) -> Self {
+    pub fn new(to: Wallet, amount: u64, transaction: H256, nonce: U256, fees: GasFees) -> Self {
         Self {
             to,
             amount,
             timestamp: SystemTime::now(),
             transaction,
+            nonce,
+            fees,
         }
     }
 }
@@ -40,16 +45,22 @@ impl Payment {
 pub trait PayableDao: Debug + Send {
     fn more_money_payable(&self, wallet: &Wallet, amount: u64) -> Result<(), PaymentError>;
 
-    fn payment_sent(&self, sent_payment: &Payment) -> Result<(), PaymentError>;
+    fn payment_sent(&mut self, sent_payment: &Payment) -> Result<(), PaymentError>;
+
+    fn payment_resubmitted(&mut self, resubmitted_payment: &Payment) -> Result<(), PaymentError>;
 
     fn payment_confirmed(
-        &self,
+        &mut self,
         wallet: &Wallet,
         amount: u64,
         confirmation_noticed_timestamp: SystemTime,
         transaction_hash: H256,
     ) -> Result<(), PaymentError>;
 
+    fn payment_failed(&mut self, failed_payment: &Payment) -> Result<(), PaymentError>;
+
+    fn pending_payments(&self) -> Vec<Payment>;
+
     fn account_status(&self, wallet: &Wallet) -> Option<PayableAccount>;
 
     fn non_pending_payables(&self) -> Vec<PayableAccount>;
@@ -59,6 +70,8 @@ pub trait PayableDao: Debug + Send {
     fn total(&self) -> u64;
 }
 
+const INCREASE_BALANCE_SQL: &str = "insert into payable (wallet_address, balance, last_paid_timestamp, pending_payment_transaction) values (:address, :balance, strftime('%s','now'), null) on conflict (wallet_address) do update set balance = balance + :balance where wallet_address = :address";
+
 #[derive(Debug)]
 pub struct PayableDaoReal {
     conn: Box<dyn ConnectionWrapper>,
@@ -67,34 +80,158 @@ pub struct PayableDaoReal {
 impl PayableDao for PayableDaoReal {
     fn more_money_payable(&self, wallet: &Wallet, amount: u64) -> Result<(), PaymentError> {
         let signed_amount = jackass_unsigned_to_signed(amount)?;
-        match self.try_increase_balance(wallet, signed_amount) {
+        let mut stmt = self
+            .conn
+            .prepare(INCREASE_BALANCE_SQL)
+            .expect("Internal error");
+        let params: &[(&str, &dyn ToSql)] = &[(":address", &wallet), (":balance", &signed_amount)];
+        match stmt.execute_named(params) {
             Ok(_) => Ok(()),
             Err(e) => panic!("Database is corrupt: {}", e),
         }
     }
 
-    fn payment_sent(&self, payment: &Payment) -> Result<(), PaymentError> {
+    fn payment_sent(&mut self, payment: &Payment) -> Result<(), PaymentError> {
         let signed_amount = jackass_unsigned_to_signed(payment.amount)?;
-        match self.try_decrease_balance(
-            &payment.to,
-            signed_amount,
-            payment.timestamp,
-            payment.transaction,
-        ) {
+        match self.in_transaction(|tx| {
+            Self::try_decrease_balance(
+                tx,
+                &payment.to,
+                signed_amount,
+                payment.timestamp,
+                payment.transaction,
+            )?;
+            Self::insert_pending_payment(tx, payment, signed_amount)
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => panic!("Database is corrupt: {}", e),
+        }
+    }
+
+    fn payment_resubmitted(&mut self, payment: &Payment) -> Result<(), PaymentError> {
+        let signed_amount = jackass_unsigned_to_signed(payment.amount)?;
+        match self.in_transaction(|tx| {
+            Self::insert_pending_payment(tx, payment, signed_amount)?;
+            Self::set_pending_payment_transaction(tx, &payment.to, Some(payment.transaction))
+        }) {
             Ok(_) => Ok(()),
             Err(e) => panic!("Database is corrupt: {}", e),
         }
     }
 
     fn payment_confirmed(
-        &self,
-        _wallet: &Wallet,
+        &mut self,
+        wallet: &Wallet,
         amount: u64,
-        _confirmation_noticed_timestamp: SystemTime,
+        confirmation_noticed_timestamp: SystemTime,
         _transaction_hash: H256,
     ) -> Result<(), PaymentError> {
-        let _signed_amount = jackass_unsigned_to_signed(amount)?;
-        unimplemented!("SC-925: TODO")
+        jackass_unsigned_to_signed(amount)?;
+        match self.in_transaction(|tx| {
+            let mut stmt = tx
+                .prepare("update payable set last_paid_timestamp = :last_paid, pending_payment_transaction = null where wallet_address = :address")
+                .expect("Internal error");
+            let params: &[(&str, &dyn ToSql)] = &[
+                (
+                    ":last_paid",
+                    &dao_utils::to_time_t(confirmation_noticed_timestamp),
+                ),
+                (":address", &wallet),
+            ];
+            stmt.execute_named(params).map_err(|e| format!("{}", e))?;
+            Self::delete_pending_payments(tx, wallet)
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => panic!("Database is corrupt: {}", e),
+        }
+    }
+
+    fn payment_failed(&mut self, payment: &Payment) -> Result<(), PaymentError> {
+        let signed_amount = jackass_unsigned_to_signed(payment.amount)?;
+        match self.in_transaction(|tx| {
+            Self::try_increase_balance(tx, &payment.to, signed_amount)?;
+            Self::set_pending_payment_transaction(tx, &payment.to, None)?;
+            Self::delete_pending_payments(tx, &payment.to)
+        }) {
+            Ok(_) => Ok(()),
+            Err(e) => panic!("Database is corrupt: {}", e),
+        }
+    }
+
+    fn pending_payments(&self) -> Vec<Payment> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                r#"
+                select
+                    wallet_address,
+                    amount,
+                    attempted_timestamp,
+                    transaction_hash,
+                    nonce,
+                    gas_price,
+                    priority_fee
+                from
+                    pending_payments
+                order by
+                    wallet_address,
+                    attempted_timestamp
+            "#,
+            )
+            .expect("Internal error");
+        stmt.query_map(NO_PARAMS, |row| {
+            let wallet_result: Result<Wallet, rusqlite::Error> = row.get(0);
+            let amount_result: Result<i64, rusqlite::Error> = row.get(1);
+            let attempted_timestamp_result = row.get(2);
+            let transaction_result: Result<String, rusqlite::Error> = row.get(3);
+            let nonce_result: Result<String, rusqlite::Error> = row.get(4);
+            let gas_price_result: Result<i64, rusqlite::Error> = row.get(5);
+            let priority_fee_result: Result<Option<i64>, rusqlite::Error> = row.get(6);
+            match (
+                wallet_result,
+                amount_result,
+                attempted_timestamp_result,
+                transaction_result,
+                nonce_result,
+                gas_price_result,
+                priority_fee_result,
+            ) {
+                (
+                    Ok(wallet),
+                    Ok(amount),
+                    Ok(attempted_timestamp),
+                    Ok(transaction),
+                    Ok(nonce),
+                    Ok(gas_price),
+                    Ok(priority_fee_opt),
+                ) => Ok(Payment {
+                    to: wallet,
+                    amount: amount as u64,
+                    timestamp: dao_utils::from_time_t(attempted_timestamp),
+                    transaction: match serde_json::from_value(json!(transaction)) {
+                        Ok(transaction) => transaction,
+                        Err(e) => panic!("{:?}", e),
+                    },
+                    nonce: match serde_json::from_value(json!(nonce)) {
+                        Ok(nonce) => nonce,
+                        Err(e) => panic!("{:?}", e),
+                    },
+                    fees: match priority_fee_opt {
+                        None => GasFees::Legacy {
+                            gas_price: gas_price as u64,
+                        },
+                        Some(priority_fee) => GasFees::FeeMarket {
+                            max_fee: gas_price as u64,
+                            priority_fee: priority_fee as u64,
+                        },
+                    },
+                }),
+                _ => panic!("Database is corrupt: PENDING_PAYMENTS table columns and/or types"),
+            }
+        })
+        .expect("Database is corrupt")
+        .flatten()
+        .collect()
     }
 
     fn account_status(&self, wallet: &Wallet) -> Option<PayableAccount> {
@@ -261,11 +398,23 @@ impl PayableDaoReal {
         PayableDaoReal { conn }
     }
 
-    fn try_increase_balance(&self, wallet: &Wallet, amount: i64) -> Result<bool, String> {
-        let mut stmt = self
-            .conn
-            .prepare("insert into payable (wallet_address, balance, last_paid_timestamp, pending_payment_transaction) values (:address, :balance, strftime('%s','now'), null) on conflict (wallet_address) do update set balance = balance + :balance where wallet_address = :address")
-            .expect("Internal error");
+    // A payment's balance change and its pending payments are recorded together or not at all
+    fn in_transaction<F>(&mut self, change: F) -> Result<bool, String>
+    where
+        F: FnOnce(&Transaction) -> Result<bool, String>,
+    {
+        let tx = self.conn.transaction().map_err(|e| format!("{}", e))?;
+        let result = change(&tx)?;
+        tx.commit().map_err(|e| format!("{}", e))?;
+        Ok(result)
+    }
+
+    fn try_increase_balance(
+        conn: &Connection,
+        wallet: &Wallet,
+        amount: i64,
+    ) -> Result<bool, String> {
+        let mut stmt = conn.prepare(INCREASE_BALANCE_SQL).expect("Internal error");
         let params: &[(&str, &dyn ToSql)] = &[(":address", &wallet), (":balance", &amount)];
         match stmt.execute_named(params) {
             Ok(0) => Ok(false),
@@ -275,14 +424,13 @@ impl PayableDaoReal {
     }
 
     fn try_decrease_balance(
-        &self,
+        conn: &Connection,
         wallet: &Wallet,
         amount: i64,
         last_paid_timestamp: SystemTime,
         transaction_hash: H256,
     ) -> Result<bool, String> {
-        let mut stmt = self
-            .conn
+        let mut stmt = conn
             .prepare("insert into payable (balance, last_paid_timestamp, pending_payment_transaction, wallet_address) values (0 - :balance, :last_paid, :transaction, :address) on conflict (wallet_address) do update set balance = balance - :balance, last_paid_timestamp = :last_paid, pending_payment_transaction = :transaction where wallet_address = :address")
             .expect("Internal error");
         let params: &[(&str, &dyn ToSql)] = &[
@@ -297,6 +445,75 @@ impl PayableDaoReal {
             Err(e) => Err(format!("{}", e)),
         }
     }
+
+    fn insert_pending_payment(
+        conn: &Connection,
+        payment: &Payment,
+        amount: i64,
+    ) -> Result<bool, String> {
+        // A fee market transaction's max fee goes where a legacy transaction's gas price does
+        let (gas_price, priority_fee_opt) = match payment.fees {
+            GasFees::Legacy { gas_price } => (gas_price, None),
+            GasFees::FeeMarket {
+                max_fee,
+                priority_fee,
+            } => (max_fee, Some(priority_fee)),
+        };
+        let gas_price = jackass_unsigned_to_signed(gas_price)
+            .map_err(|_| format!("Gas price out of range: {}", gas_price))?;
+        let priority_fee_opt = priority_fee_opt
+            .map(|priority_fee| {
+                jackass_unsigned_to_signed(priority_fee)
+                    .map_err(|_| format!("Priority fee out of range: {}", priority_fee))
+            })
+            .transpose()?;
+        let mut stmt = conn
+            .prepare("insert into pending_payments (transaction_hash, wallet_address, amount, nonce, gas_price, priority_fee, attempted_timestamp) values (:transaction, :address, :amount, :nonce, :gas_price, :priority_fee, :attempted)")
+            .expect("Internal error");
+        let params: &[(&str, &dyn ToSql)] = &[
+            (":transaction", &format!("{:#x}", &payment.transaction)),
+            (":address", &payment.to),
+            (":amount", &amount),
+            (":nonce", &format!("{:#x}", &payment.nonce)),
+            (":gas_price", &gas_price),
+            (":priority_fee", &priority_fee_opt),
+            (":attempted", &dao_utils::to_time_t(payment.timestamp)),
+        ];
+        match stmt.execute_named(params) {
+            Ok(0) => Ok(false),
+            Ok(_) => Ok(true),
+            Err(e) => Err(format!("{}", e)),
+        }
+    }
+
+    fn set_pending_payment_transaction(
+        conn: &Connection,
+        wallet: &Wallet,
+        transaction_hash: Option<H256>,
+    ) -> Result<bool, String> {
+        let mut stmt = conn
+            .prepare("update payable set pending_payment_transaction = :transaction where wallet_address = :address")
+            .expect("Internal error");
+        let transaction = transaction_hash.map(|hash| format!("{:#x}", hash));
+        let params: &[(&str, &dyn ToSql)] =
+            &[(":transaction", &transaction), (":address", &wallet)];
+        match stmt.execute_named(params) {
+            Ok(0) => Ok(false),
+            Ok(_) => Ok(true),
+            Err(e) => Err(format!("{}", e)),
+        }
+    }
+
+    fn delete_pending_payments(conn: &Connection, wallet: &Wallet) -> Result<bool, String> {
+        let mut stmt = conn
+            .prepare("delete from pending_payments where wallet_address = ?")
+            .expect("Internal error");
+        match stmt.execute(&[&wallet]) {
+            Ok(0) => Ok(false),
+            Ok(_) => Ok(true),
+            Err(e) => Err(format!("{}", e)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -309,6 +526,7 @@ mod tests {
     use ethereum_types::BigEndianHash;
     use masq_lib::test_utils::utils::{ensure_node_home_directory_exists, DEFAULT_CHAIN_ID};
     use rusqlite::{Connection, OpenFlags, NO_PARAMS};
+    use std::panic::{self, AssertUnwindSafe};
     use std::str::FromStr;
     use web3::types::U256;
 
@@ -411,12 +629,18 @@ mod tests {
             "payment_sent_records_a_pending_transaction_for_a_new_address",
         );
         let wallet = make_wallet("booga");
-        let subject = PayableDaoReal::new(
+        let mut subject = PayableDaoReal::new(
             DbInitializerReal::new()
                 .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
                 .unwrap(),
         );
-        let payment = Payment::new(wallet.clone(), 1, H256::from_uint(&U256::from(1)));
+        let payment = Payment::new(
+            wallet.clone(),
+            1,
+            H256::from_uint(&U256::from(1)),
+            U256::from(2),
+            GasFees::Legacy { gas_price: 3 },
+        );
 
         let before_account_status = subject.account_status(&payment.to);
         assert!(before_account_status.is_none());
@@ -443,12 +667,18 @@ mod tests {
             "payment_sent_records_a_pending_transaction_for_an_existing_address",
         );
         let wallet = make_wallet("booga");
-        let subject = PayableDaoReal::new(
+        let mut subject = PayableDaoReal::new(
             DbInitializerReal::new()
                 .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
                 .unwrap(),
         );
-        let payment = Payment::new(wallet.clone(), 1, H256::from_uint(&U256::from(1)));
+        let payment = Payment::new(
+            wallet.clone(),
+            1,
+            H256::from_uint(&U256::from(1)),
+            U256::from(2),
+            GasFees::Legacy { gas_price: 3 },
+        );
 
         let before_account_status = subject.account_status(&payment.to);
         assert!(before_account_status.is_none());
@@ -473,12 +703,18 @@ mod tests {
         let home_dir =
             ensure_node_home_directory_exists("payable_dao", "payment_sent_works_for_overflow");
         let wallet = make_wallet("booga");
-        let subject = PayableDaoReal::new(
+        let mut subject = PayableDaoReal::new(
             DbInitializerReal::new()
                 .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
                 .unwrap(),
         );
-        let payment = Payment::new(wallet, std::u64::MAX, H256::from_uint(&U256::from(1)));
+        let payment = Payment::new(
+            wallet,
+            std::u64::MAX,
+            H256::from_uint(&U256::from(1)),
+            U256::from(2),
+            GasFees::Legacy { gas_price: 3 },
+        );
 
         let result = subject.payment_sent(&payment);
 
@@ -492,7 +728,7 @@ mod tests {
             "payment_confirmed_works_for_overflow",
         );
         let wallet = make_wallet("booga");
-        let subject = PayableDaoReal::new(
+        let mut subject = PayableDaoReal::new(
             DbInitializerReal::new()
                 .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
                 .unwrap(),
@@ -508,6 +744,209 @@ mod tests {
         assert_eq!(result, Err(PaymentError::SignConversion(std::u64::MAX)))
     }
 
+    fn make_payment(wallet: &Wallet, amount: u64, hash: u64, timestamp: i64) -> Payment {
+        Payment {
+            to: wallet.clone(),
+            amount,
+            timestamp: from_time_t(timestamp),
+            transaction: H256::from_uint(&U256::from(hash)),
+            nonce: U256::from(7),
+            fees: GasFees::Legacy { gas_price: 10 },
+        }
+    }
+
+    #[test]
+    fn payment_sent_records_a_pending_payment_attempt() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "payment_sent_records_a_pending_payment_attempt",
+        );
+        let wallet = make_wallet("booga");
+        let mut subject = PayableDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let payment = make_payment(&wallet, 1234, 1, 1_000);
+        subject.more_money_payable(&wallet, 2345).unwrap();
+
+        subject.payment_sent(&payment).unwrap();
+
+        assert_eq!(subject.pending_payments(), vec![payment]);
+    }
+
+    #[test]
+    fn payment_sent_changes_nothing_if_the_pending_payment_cannot_be_recorded() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "payment_sent_changes_nothing_if_the_pending_payment_cannot_be_recorded",
+        );
+        let wallet = make_wallet("booga");
+        let mut subject = PayableDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let payment = make_payment(&wallet, 1234, 1, 1_000);
+        subject.more_money_payable(&wallet, 2345).unwrap();
+        subject.payment_sent(&payment).unwrap();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| subject.payment_sent(&payment)));
+
+        assert!(result.is_err());
+        assert_eq!(subject.account_status(&wallet).unwrap().balance, 1111);
+        assert_eq!(subject.pending_payments(), vec![payment]);
+    }
+
+    #[test]
+    fn payment_resubmitted_adds_an_attempt_and_updates_the_pending_transaction() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "payment_resubmitted_adds_an_attempt_and_updates_the_pending_transaction",
+        );
+        let wallet = make_wallet("booga");
+        let mut subject = PayableDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let first_attempt = make_payment(&wallet, 1234, 1, 1_000);
+        let second_attempt = Payment {
+            fees: GasFees::FeeMarket {
+                max_fee: 12,
+                priority_fee: 2,
+            },
+            ..make_payment(&wallet, 1234, 2, 2_000)
+        };
+        subject.more_money_payable(&wallet, 2345).unwrap();
+        subject.payment_sent(&first_attempt).unwrap();
+
+        subject.payment_resubmitted(&second_attempt).unwrap();
+
+        assert_eq!(
+            subject.pending_payments(),
+            vec![first_attempt, second_attempt]
+        );
+        let status = subject.account_status(&wallet).unwrap();
+        assert_eq!(status.balance, 1111);
+        assert_eq!(
+            status.pending_payment_transaction,
+            Some(H256::from_uint(&U256::from(2)))
+        );
+    }
+
+    #[test]
+    fn payment_resubmitted_works_for_overflow() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "payment_resubmitted_works_for_overflow",
+        );
+        let mut subject = PayableDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+
+        let result =
+            subject.payment_resubmitted(&make_payment(&make_wallet("booga"), std::u64::MAX, 1, 0));
+
+        assert_eq!(result, Err(PaymentError::SignConversion(std::u64::MAX)))
+    }
+
+    #[test]
+    fn payment_confirmed_clears_the_pending_transaction_and_its_attempts() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "payment_confirmed_clears_the_pending_transaction_and_its_attempts",
+        );
+        let wallet = make_wallet("booga");
+        let other_wallet = make_wallet("other");
+        let mut subject = PayableDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let other_payment = make_payment(&other_wallet, 100, 3, 1_000);
+        subject.more_money_payable(&wallet, 2345).unwrap();
+        subject.more_money_payable(&other_wallet, 200).unwrap();
+        subject
+            .payment_sent(&make_payment(&wallet, 1234, 1, 1_000))
+            .unwrap();
+        subject
+            .payment_resubmitted(&make_payment(&wallet, 1234, 2, 2_000))
+            .unwrap();
+        subject.payment_sent(&other_payment).unwrap();
+
+        subject
+            .payment_confirmed(
+                &wallet,
+                1234,
+                from_time_t(3_000),
+                H256::from_uint(&U256::from(1)),
+            )
+            .unwrap();
+
+        assert_eq!(
+            subject.account_status(&wallet).unwrap(),
+            PayableAccount {
+                wallet,
+                balance: 1111,
+                last_paid_timestamp: from_time_t(3_000),
+                pending_payment_transaction: None,
+            }
+        );
+        assert_eq!(subject.pending_payments(), vec![other_payment]);
+    }
+
+    #[test]
+    fn payment_failed_restores_the_balance_and_clears_the_pending_transaction() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "payment_failed_restores_the_balance_and_clears_the_pending_transaction",
+        );
+        let wallet = make_wallet("booga");
+        let mut subject = PayableDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let payment = make_payment(&wallet, 1234, 1, 1_000);
+        subject.more_money_payable(&wallet, 2345).unwrap();
+        subject.payment_sent(&payment).unwrap();
+
+        subject.payment_failed(&payment).unwrap();
+
+        let status = subject.account_status(&wallet).unwrap();
+        assert_eq!(status.balance, 2345);
+        assert_eq!(status.pending_payment_transaction, None);
+        assert_eq!(subject.pending_payments(), vec![]);
+        assert_eq!(
+            subject.non_pending_payables(),
+            vec![PayableAccount {
+                wallet,
+                balance: 2345,
+                last_paid_timestamp: from_time_t(1_000),
+                pending_payment_transaction: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn payment_failed_works_for_overflow() {
+        let home_dir =
+            ensure_node_home_directory_exists("payable_dao", "payment_failed_works_for_overflow");
+        let mut subject = PayableDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+
+        let result =
+            subject.payment_failed(&make_payment(&make_wallet("booga"), std::u64::MAX, 1, 0));
+
+        assert_eq!(result, Err(PaymentError::SignConversion(std::u64::MAX)))
+    }
+
     #[test]
     fn payable_account_status_works_when_account_doesnt_exist() {
         let home_dir = ensure_node_home_directory_exists(
@@ -627,7 +1066,7 @@ mod tests {
             "payable_dao",
             "payable_amount_precision_loss_panics_on_update_balance",
         );
-        let subject = PayableDaoReal::new(
+        let mut subject = PayableDaoReal::new(
             DbInitializerReal::new()
                 .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
                 .unwrap(),
@@ -637,6 +1076,8 @@ mod tests {
             make_wallet("foobar"),
             std::u64::MAX,
             H256::from_uint(&U256::from(123)),
+            U256::from(2),
+            GasFees::Legacy { gas_price: 3 },
         ));
 
         assert_eq!(result, Err(PaymentError::SignConversion(std::u64::MAX)))
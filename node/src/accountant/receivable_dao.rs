@@ -1,6 +1,6 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 use crate::accountant::{jackass_unsigned_to_signed, PaymentCurves, PaymentError};
-use crate::blockchain::blockchain_interface::Transaction;
+use crate::blockchain::blockchain_interface::{CreditedBlock, Transaction};
 use crate::database::dao_utils;
 use crate::database::dao_utils::to_time_t;
 use crate::database::db_initializer::ConnectionWrapper;
@@ -11,8 +11,16 @@ use indoc::indoc;
 use rusqlite::named_params;
 use rusqlite::types::{ToSql, Type};
 use rusqlite::{OptionalExtension, Row, NO_PARAMS};
+use serde_json::json;
 use std::time::SystemTime;
 
+// Credited payments are remembered for this many blocks, in case a reorganization orphans them
+pub const CREDITED_BLOCK_RETENTION: u64 = 1_000;
+
+// Block number, wallet, amount, and the wallet's last payment time and pardoned balance from
+// before the payment
+type OrphanedPayment = (i64, Wallet, i64, Option<i64>, Option<i64>);
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ReceivableAccount {
     pub wallet: Wallet,
@@ -23,11 +31,24 @@ pub struct ReceivableAccount {
 pub trait ReceivableDao: Send {
     fn more_money_receivable(&self, wallet: &Wallet, amount: u64) -> Result<(), PaymentError>;
 
+    // Records the payments and the block where the next scan should start, together or not at all
     fn more_money_received(
         &mut self,
         persistent_configuration: &dyn PersistentConfiguration,
         transactions: Vec<Transaction>,
-    );
+        new_start_block: u64,
+    ) -> Result<(), String>;
+
+    // The blocks that recently credited payments came from, newest first
+    fn credited_blocks(&self) -> Vec<CreditedBlock>;
+
+    // Takes back the credit for every payment from the given block on, and starts the next scan
+    // there, together or not at all
+    fn reverse_orphaned_payments(
+        &mut self,
+        persistent_configuration: &dyn PersistentConfiguration,
+        first_orphaned_block: u64,
+    ) -> Result<(), String>;
 
     fn account_status(&self, wallet: &Wallet) -> Option<ReceivableAccount>;
 
@@ -72,10 +93,48 @@ impl ReceivableDao for ReceivableDaoReal {
         &mut self,
         persistent_configuration: &dyn PersistentConfiguration,
         payments: Vec<Transaction>,
-    ) {
-        self.try_multi_insert_payment(persistent_configuration, payments)
-            .unwrap_or_else(|e| {
+        new_start_block: u64,
+    ) -> Result<(), String> {
+        self.try_multi_insert_payment(persistent_configuration, payments, new_start_block)
+            .map_err(|e| {
+                error!(self.logger, "Transaction failed, rolling back: {}", e);
+                e
+            })
+    }
+
+    fn credited_blocks(&self) -> Vec<CreditedBlock> {
+        let mut stmt = self
+            .conn
+            .prepare("select distinct block_number, block_hash from received_payments order by block_number desc")
+            .expect("Internal error");
+        stmt.query_map(NO_PARAMS, |row| {
+            let block_number_result: Result<i64, rusqlite::Error> = row.get(0);
+            let block_hash_result: Result<String, rusqlite::Error> = row.get(1);
+            match (block_number_result, block_hash_result) {
+                (Ok(block_number), Ok(block_hash)) => Ok(CreditedBlock {
+                    block_number: block_number as u64,
+                    block_hash: match serde_json::from_value(json!(block_hash)) {
+                        Ok(hash) => hash,
+                        Err(e) => panic!("{:?}", e),
+                    },
+                }),
+                _ => panic!("Database is corrupt: RECEIVED_PAYMENTS table columns and/or types"),
+            }
+        })
+        .expect("Database is corrupt")
+        .flatten()
+        .collect()
+    }
+
+    fn reverse_orphaned_payments(
+        &mut self,
+        persistent_configuration: &dyn PersistentConfiguration,
+        first_orphaned_block: u64,
+    ) -> Result<(), String> {
+        self.try_reverse_payments(persistent_configuration, first_orphaned_block)
+            .map_err(|e| {
                 error!(self.logger, "Transaction failed, rolling back: {}", e);
+                e
             })
     }
 
@@ -128,12 +187,15 @@ impl ReceivableDao for ReceivableDaoReal {
         let sql = indoc!(
             r"
             select r.wallet_address, r.balance, r.last_received_timestamp
-            from receivable r left outer join banned b on r.wallet_address = b.wallet_address
+            from receivable r
+                left outer join banned b on r.wallet_address = b.wallet_address
+                left outer join pardoned p on r.wallet_address = p.wallet_address
             where
                 r.last_received_timestamp < :sugg_and_grace
                 and r.balance > :balance_to_decrease_from + :slope * (:sugg_and_grace - r.last_received_timestamp)
                 and r.balance > :permanent_debt
                 and b.wallet_address is null
+                and (p.wallet_address is null or r.balance > p.pardoned_balance)
         "
         );
         let mut stmt = self.conn.prepare(sql).expect("Couldn't prepare statement");
@@ -158,6 +220,7 @@ impl ReceivableDao for ReceivableDaoReal {
             from receivable r inner join banned b on r.wallet_address = b.wallet_address
             where
                 r.balance <= :unban_balance
+                and b.manual = 0
         "
         );
         let mut stmt = self.conn.prepare(sql).expect("Couldn't prepare statement");
@@ -285,32 +348,131 @@ impl ReceivableDaoReal {
         &mut self,
         persistent_configuration: &dyn PersistentConfiguration,
         payments: Vec<Transaction>,
+        new_start_block: u64,
     ) -> Result<(), String> {
         let tx = match self.conn.transaction() {
             Ok(t) => t,
             Err(e) => return Err(e.to_string()),
         };
 
-        let block_number = payments
-            .iter()
-            .map(|t| t.block_number)
-            .max()
-            .ok_or("no payments given")?;
-
-        persistent_configuration.set_start_block_transactionally(&tx, block_number)?;
+        persistent_configuration.set_start_block_transactionally(&tx, new_start_block)?;
 
         {
+            // What the payment changes besides the balance is recorded with it, so that it can be put back if the payment is orphaned
+            let mut prior_stmt = tx.prepare("select r.last_received_timestamp, p.pardoned_balance from receivable r left outer join pardoned p on r.wallet_address = p.wallet_address where r.wallet_address = ?").expect("Internal error");
             let mut stmt = tx.prepare("update receivable set balance = balance - ?, last_received_timestamp = ? where wallet_address = ?").expect("Internal error");
+            let mut record_stmt = tx.prepare("insert into received_payments (block_number, block_hash, wallet_address, amount, prior_last_received_timestamp, prior_pardoned_balance) values (?, ?, ?, ?, ?, ?)").expect("Internal error");
+            // A pardon covers only what's still owed of the debt it was granted for
+            let mut pardon_stmt = tx.prepare("update pardoned set pardoned_balance = min(pardoned_balance, (select balance from receivable where wallet_address = ?)) where wallet_address = ?").expect("Internal error");
             for transaction in payments {
                 let timestamp = dao_utils::now_time_t();
                 let gwei_amount = match jackass_unsigned_to_signed(transaction.gwei_amount) {
                     Ok(amount) => amount,
                     Err(e) => return Err(format!("Amount too large: {:?}", e)),
                 };
+                let prior_state: Option<(i64, Option<i64>)> = prior_stmt
+                    .query_row(&[&transaction.from], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .optional()
+                    .map_err(|e| e.to_string())?;
+                let (prior_last_received_timestamp, prior_pardoned_balance) = match prior_state {
+                    Some(prior_state) => prior_state,
+                    None => continue,
+                };
                 let params: &[&dyn ToSql] = &[&gwei_amount, &timestamp, &transaction.from];
                 stmt.execute(params).map_err(|e| e.to_string())?;
+                let block_number = transaction.block_number as i64;
+                let block_hash = format!("{:#x}", transaction.block_hash);
+                let record_params: &[&dyn ToSql] = &[
+                    &block_number,
+                    &block_hash,
+                    &transaction.from,
+                    &gwei_amount,
+                    &prior_last_received_timestamp,
+                    &prior_pardoned_balance,
+                ];
+                record_stmt
+                    .execute(record_params)
+                    .map_err(|e| e.to_string())?;
+                pardon_stmt
+                    .execute(&[&transaction.from, &transaction.from])
+                    .map_err(|e| e.to_string())?;
             }
         }
+        let retained_from_block = new_start_block.saturating_sub(CREDITED_BLOCK_RETENTION) as i64;
+        tx.execute(
+            "delete from received_payments where block_number < ?",
+            &[&retained_from_block],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    fn try_reverse_payments(
+        &mut self,
+        persistent_configuration: &dyn PersistentConfiguration,
+        first_orphaned_block: u64,
+    ) -> Result<(), String> {
+        let tx = match self.conn.transaction() {
+            Ok(t) => t,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        persistent_configuration.set_start_block_transactionally(&tx, first_orphaned_block)?;
+
+        let first_orphaned_block = first_orphaned_block as i64;
+        // Latest first, so that a wallet with several orphaned payments ends up as it was before the earliest
+        let orphaned_payments: Vec<OrphanedPayment> = {
+            let mut stmt = tx
+                .prepare("select block_number, wallet_address, amount, prior_last_received_timestamp, prior_pardoned_balance from received_payments where block_number >= ? order by block_number desc, rowid desc")
+                .expect("Internal error");
+            let rows = stmt
+                .query_map(&[&first_orphaned_block], |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                })
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+        };
+        {
+            let mut stmt = tx
+                .prepare("update receivable set balance = balance + ?, last_received_timestamp = coalesce(?, last_received_timestamp) where wallet_address = ?")
+                .expect("Internal error");
+            let mut pardon_stmt = tx
+                .prepare("update pardoned set pardoned_balance = coalesce(?, pardoned_balance) where wallet_address = ?")
+                .expect("Internal error");
+            for (
+                block_number,
+                wallet,
+                amount,
+                prior_last_received_timestamp,
+                prior_pardoned_balance,
+            ) in orphaned_payments
+            {
+                warning!(
+                    self.logger,
+                    "Block {} was dropped from the blockchain; taking back credit for {}gw from {}",
+                    block_number,
+                    amount,
+                    wallet
+                );
+                let params: &[&dyn ToSql] = &[&amount, &prior_last_received_timestamp, &wallet];
+                stmt.execute(params).map_err(|e| e.to_string())?;
+                let pardon_params: &[&dyn ToSql] = &[&prior_pardoned_balance, &wallet];
+                pardon_stmt
+                    .execute(pardon_params)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        tx.execute(
+            "delete from received_payments where block_number >= ?",
+            &[&first_orphaned_block],
+        )
+        .map_err(|e| e.to_string())?;
         tx.commit().map_err(|e| e.to_string())
     }
 
@@ -344,9 +506,12 @@ mod tests {
     use crate::test_utils::logging::TestLogHandler;
     use crate::test_utils::persistent_configuration_mock::PersistentConfigurationMock;
     use crate::test_utils::{assert_contains, make_wallet};
+    use ethereum_types::BigEndianHash;
     use masq_lib::test_utils::utils::{ensure_node_home_directory_exists, DEFAULT_CHAIN_ID};
     use rusqlite::NO_PARAMS;
     use rusqlite::{Connection, Error, OpenFlags};
+    use std::path::PathBuf;
+    use web3::types::{H256, U256};
 
     #[test]
     fn more_money_receivable_works_for_new_address() {
@@ -475,15 +640,19 @@ mod tests {
                     from: debtor1.clone(),
                     gwei_amount: 1200u64,
                     block_number: 35u64,
+                    block_hash: H256::from_uint(&U256::from(35)),
                 },
                 Transaction {
                     from: debtor2.clone(),
                     gwei_amount: 2300u64,
                     block_number: 57u64,
+                    block_hash: H256::from_uint(&U256::from(57)),
                 },
             ];
 
-            subject.more_money_received(persistent_config.as_ref(), transactions);
+            subject
+                .more_money_received(persistent_config.as_ref(), transactions, 58)
+                .unwrap();
             (
                 subject.account_status(&debtor1).unwrap(),
                 subject.account_status(&debtor2).unwrap(),
@@ -503,7 +672,7 @@ mod tests {
         assert!(timestamp2 <= dao_utils::to_time_t(SystemTime::now()));
 
         let start_block = persistent_config.start_block();
-        assert_eq!(57u64, start_block);
+        assert_eq!(58u64, start_block);
     }
 
     #[test]
@@ -532,8 +701,11 @@ mod tests {
                 from: debtor.clone(),
                 gwei_amount: 2300u64,
                 block_number: 33u64,
+                block_hash: H256::from_uint(&U256::from(33)),
             }];
-            subject.more_money_received(persistent_config.as_ref(), transactions);
+            subject
+                .more_money_received(persistent_config.as_ref(), transactions, 34)
+                .unwrap();
             subject.account_status(&debtor)
         };
 
@@ -551,8 +723,10 @@ mod tests {
         let persistent_configuration: Box<dyn PersistentConfiguration> =
             Box::new(PersistentConfigurationMock::new());
 
-        receivable_dao.more_money_received(persistent_configuration.as_ref(), vec![]);
+        let result =
+            receivable_dao.more_money_received(persistent_configuration.as_ref(), vec![], 100);
 
+        assert_eq!(result, Err(Error::InvalidQuery.to_string()));
         TestLogHandler::new().exists_log_containing(&format!(
             "ERROR: ReceivableDaoReal: Transaction failed, rolling back: {}",
             Error::InvalidQuery
@@ -560,28 +734,28 @@ mod tests {
     }
 
     #[test]
-    fn more_money_received_logs_when_no_payment_are_given() {
-        logging::init_test_logging();
-
+    fn more_money_received_advances_start_block_even_without_payments() {
         let home_dir = ensure_node_home_directory_exists(
             "receivable_dao",
-            "more_money_received_logs_when_no_payment_are_given",
+            "more_money_received_advances_start_block_even_without_payments",
         );
-
-        let mut receivable_dao = ReceivableDaoReal::new(
+        let mut subject = ReceivableDaoReal::new(
             DbInitializerReal::new()
                 .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
                 .unwrap(),
         );
+        let config_dao = ConfigDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let persistent_config: Box<dyn PersistentConfiguration> =
+            Box::new(PersistentConfigurationReal::new(Box::new(config_dao)));
 
-        let persistent_configuration: Box<dyn PersistentConfiguration> =
-            Box::new(PersistentConfigurationMock::new());
-
-        receivable_dao.more_money_received(persistent_configuration.as_ref(), vec![]);
+        let result = subject.more_money_received(persistent_config.as_ref(), vec![], 123_456);
 
-        TestLogHandler::new().exists_log_containing(
-            "ERROR: ReceivableDaoReal: Transaction failed, rolling back: no payments given",
-        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(persistent_config.start_block(), 123_456);
     }
 
     #[test]
@@ -606,18 +780,378 @@ mod tests {
             from: make_wallet("foobar"),
             gwei_amount: 2300u64,
             block_number: 33u64,
+            block_hash: H256::from_uint(&U256::from(33)),
         }];
 
         let persistent_configuration: Box<dyn PersistentConfiguration> =
             Box::new(persistent_configuration_mock);
 
-        receivable_dao.more_money_received(persistent_configuration.as_ref(), payments);
+        let result =
+            receivable_dao.more_money_received(persistent_configuration.as_ref(), payments, 34);
+
+        assert_eq!(result, Err("BOOM".to_string()));
 
         TestLogHandler::new().exists_log_containing(
             r#"ERROR: ReceivableDaoReal: Transaction failed, rolling back: BOOM"#,
         );
     }
 
+    fn subject_and_persistent_config(
+        home_dir: &PathBuf,
+    ) -> (ReceivableDaoReal, Box<dyn PersistentConfiguration>) {
+        let subject = ReceivableDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let config_dao = ConfigDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        (
+            subject,
+            Box::new(PersistentConfigurationReal::new(Box::new(config_dao))),
+        )
+    }
+
+    fn payment(from: &Wallet, gwei_amount: u64, block_number: u64) -> Transaction {
+        Transaction {
+            block_number,
+            block_hash: H256::from_uint(&U256::from(block_number)),
+            from: from.clone(),
+            gwei_amount,
+        }
+    }
+
+    fn credited_block(block_number: u64) -> CreditedBlock {
+        CreditedBlock {
+            block_number,
+            block_hash: H256::from_uint(&U256::from(block_number)),
+        }
+    }
+
+    #[test]
+    fn more_money_received_remembers_which_blocks_credited_payments_came_from() {
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "more_money_received_remembers_which_blocks_credited_payments_came_from",
+        );
+        let (mut subject, persistent_config) = subject_and_persistent_config(&home_dir);
+        let debtor1 = make_wallet("debtor1");
+        let debtor2 = make_wallet("debtor2");
+        subject.more_money_receivable(&debtor1, 1234).unwrap();
+        subject.more_money_receivable(&debtor2, 2345).unwrap();
+
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![
+                    payment(&debtor1, 100, 35),
+                    payment(&debtor2, 200, 57),
+                    payment(&debtor1, 300, 57),
+                    payment(&make_wallet("unknown"), 400, 60),
+                ],
+                61,
+            )
+            .unwrap();
+
+        assert_eq!(
+            subject.credited_blocks(),
+            vec![credited_block(57), credited_block(35)]
+        );
+    }
+
+    #[test]
+    fn more_money_received_holds_pardons_to_what_is_still_owed() {
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "more_money_received_holds_pardons_to_what_is_still_owed",
+        );
+        let (mut subject, persistent_config) = subject_and_persistent_config(&home_dir);
+        let debtor1 = make_wallet("debtor1");
+        let debtor2 = make_wallet("debtor2");
+        subject.more_money_receivable(&debtor1, 1234).unwrap();
+        subject.more_money_receivable(&debtor2, 2345).unwrap();
+        let conn = DbInitializerReal::new()
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        let mut pardoned1 = subject.account_status(&debtor1).unwrap();
+        pardoned1.balance = 1234;
+        let mut pardoned2 = subject.account_status(&debtor2).unwrap();
+        pardoned2.balance = 100;
+        add_pardoned_account(conn.as_ref(), &pardoned1);
+        add_pardoned_account(conn.as_ref(), &pardoned2);
+
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![payment(&debtor1, 1000, 35), payment(&debtor2, 200, 57)],
+                58,
+            )
+            .unwrap();
+
+        let pardoned_balance = |wallet: &Wallet| -> i64 {
+            conn.prepare("select pardoned_balance from pardoned where wallet_address = ?")
+                .unwrap()
+                .query_row(&[wallet], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(pardoned_balance(&debtor1), 234);
+        assert_eq!(pardoned_balance(&debtor2), 100);
+    }
+
+    #[test]
+    fn more_money_received_forgets_credited_blocks_too_old_to_be_reorganized() {
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "more_money_received_forgets_credited_blocks_too_old_to_be_reorganized",
+        );
+        let (mut subject, persistent_config) = subject_and_persistent_config(&home_dir);
+        let debtor = make_wallet("debtor");
+        subject.more_money_receivable(&debtor, 1234).unwrap();
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![payment(&debtor, 100, 100)],
+                101,
+            )
+            .unwrap();
+
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![],
+                100 + CREDITED_BLOCK_RETENTION,
+            )
+            .unwrap();
+        let credited_blocks_at_the_limit = subject.credited_blocks();
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![],
+                101 + CREDITED_BLOCK_RETENTION,
+            )
+            .unwrap();
+        let credited_blocks_past_the_limit = subject.credited_blocks();
+
+        assert_eq!(credited_blocks_at_the_limit, vec![credited_block(100)]);
+        assert_eq!(credited_blocks_past_the_limit, vec![]);
+    }
+
+    #[test]
+    fn reverse_orphaned_payments_takes_back_their_credit_and_scans_their_blocks_again() {
+        logging::init_test_logging();
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "reverse_orphaned_payments_takes_back_their_credit_and_scans_their_blocks_again",
+        );
+        let (mut subject, persistent_config) = subject_and_persistent_config(&home_dir);
+        let debtor1 = make_wallet("debtor1");
+        let debtor2 = make_wallet("debtor2");
+        subject.more_money_receivable(&debtor1, 1234).unwrap();
+        subject.more_money_receivable(&debtor2, 2345).unwrap();
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![
+                    payment(&debtor1, 200, 35),
+                    payment(&debtor2, 300, 57),
+                    payment(&debtor1, 400, 60),
+                ],
+                61,
+            )
+            .unwrap();
+
+        let result = subject.reverse_orphaned_payments(persistent_config.as_ref(), 57);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(subject.account_status(&debtor1).unwrap().balance, 1034);
+        assert_eq!(subject.account_status(&debtor2).unwrap().balance, 2345);
+        assert_eq!(subject.credited_blocks(), vec![credited_block(35)]);
+        assert_eq!(persistent_config.start_block(), 57);
+        let log_handler = TestLogHandler::new();
+        log_handler.exists_log_containing(&format!(
+            "WARN: ReceivableDaoReal: Block 57 was dropped from the blockchain; taking back credit for 300gw from {}",
+            debtor2
+        ));
+        log_handler.exists_log_containing(&format!(
+            "WARN: ReceivableDaoReal: Block 60 was dropped from the blockchain; taking back credit for 400gw from {}",
+            debtor1
+        ));
+    }
+
+    #[test]
+    fn reverse_orphaned_payments_puts_back_the_last_received_timestamp() {
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "reverse_orphaned_payments_puts_back_the_last_received_timestamp",
+        );
+        let (mut subject, persistent_config) = subject_and_persistent_config(&home_dir);
+        let conn = DbInitializerReal::new()
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        let debtor = make_wallet("debtor");
+        add_receivable_account(
+            &conn,
+            &ReceivableAccount {
+                wallet: debtor.clone(),
+                balance: 2000,
+                last_received_timestamp: from_time_t(100_000),
+            },
+        );
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![
+                    payment(&debtor, 200, 35),
+                    payment(&debtor, 300, 57),
+                    payment(&debtor, 400, 60),
+                ],
+                61,
+            )
+            .unwrap();
+        let timestamp_after_payments = subject
+            .account_status(&debtor)
+            .unwrap()
+            .last_received_timestamp;
+
+        subject
+            .reverse_orphaned_payments(persistent_config.as_ref(), 35)
+            .unwrap();
+
+        let reversed = subject.account_status(&debtor).unwrap();
+        assert_ne!(timestamp_after_payments, from_time_t(100_000));
+        assert_eq!(reversed.balance, 2000);
+        assert_eq!(reversed.last_received_timestamp, from_time_t(100_000));
+    }
+
+    #[test]
+    fn reverse_orphaned_payments_leaves_the_last_received_timestamp_of_payments_from_an_older_node()
+    {
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "reverse_orphaned_payments_leaves_the_last_received_timestamp_of_payments_from_an_older_node",
+        );
+        let (mut subject, persistent_config) = subject_and_persistent_config(&home_dir);
+        let conn = DbInitializerReal::new()
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        let debtor = make_wallet("debtor");
+        subject.more_money_receivable(&debtor, 1234).unwrap();
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![payment(&debtor, 200, 35)],
+                36,
+            )
+            .unwrap();
+        let timestamp_after_payment = subject
+            .account_status(&debtor)
+            .unwrap()
+            .last_received_timestamp;
+        conn.prepare(
+            "update received_payments set prior_last_received_timestamp = null, prior_pardoned_balance = null",
+        )
+        .unwrap()
+        .execute(NO_PARAMS)
+        .unwrap();
+
+        subject
+            .reverse_orphaned_payments(persistent_config.as_ref(), 35)
+            .unwrap();
+
+        let reversed = subject.account_status(&debtor).unwrap();
+        assert_eq!(reversed.balance, 1234);
+        assert_eq!(reversed.last_received_timestamp, timestamp_after_payment);
+    }
+
+    #[test]
+    fn reverse_orphaned_payments_puts_back_the_pardoned_balance() {
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "reverse_orphaned_payments_puts_back_the_pardoned_balance",
+        );
+        let (mut subject, persistent_config) = subject_and_persistent_config(&home_dir);
+        let conn = DbInitializerReal::new()
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        let pardoned_debtor = make_wallet("pardoned_debtor");
+        let unpardoned_debtor = make_wallet("unpardoned_debtor");
+        subject
+            .more_money_receivable(&pardoned_debtor, 1234)
+            .unwrap();
+        subject
+            .more_money_receivable(&unpardoned_debtor, 2345)
+            .unwrap();
+        add_pardoned_account(
+            conn.as_ref(),
+            &subject.account_status(&pardoned_debtor).unwrap(),
+        );
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![
+                    payment(&pardoned_debtor, 1000, 57),
+                    payment(&pardoned_debtor, 100, 60),
+                    payment(&unpardoned_debtor, 300, 60),
+                ],
+                61,
+            )
+            .unwrap();
+        let pardoned_balance = |wallet: &Wallet| -> Option<i64> {
+            conn.prepare("select pardoned_balance from pardoned where wallet_address = ?")
+                .unwrap()
+                .query_row(&[wallet], |row| row.get(0))
+                .optional()
+                .unwrap()
+        };
+        let pardoned_balance_after_payments = pardoned_balance(&pardoned_debtor);
+
+        subject
+            .reverse_orphaned_payments(persistent_config.as_ref(), 57)
+            .unwrap();
+
+        assert_eq!(pardoned_balance_after_payments, Some(134));
+        assert_eq!(pardoned_balance(&pardoned_debtor), Some(1234));
+        assert_eq!(pardoned_balance(&unpardoned_debtor), None);
+        assert_eq!(
+            subject.account_status(&unpardoned_debtor).unwrap().balance,
+            2345
+        );
+    }
+
+    #[test]
+    fn reverse_orphaned_payments_changes_nothing_when_start_block_cannot_be_updated() {
+        logging::init_test_logging();
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "reverse_orphaned_payments_changes_nothing_when_start_block_cannot_be_updated",
+        );
+        let (mut subject, persistent_config) = subject_and_persistent_config(&home_dir);
+        let debtor = make_wallet("debtor");
+        subject.more_money_receivable(&debtor, 1234).unwrap();
+        subject
+            .more_money_received(
+                persistent_config.as_ref(),
+                vec![payment(&debtor, 200, 35)],
+                36,
+            )
+            .unwrap();
+        let failing_config = PersistentConfigurationMock::new()
+            .set_start_block_transactionally_result(Err("BOOM".to_string()));
+
+        let result = subject.reverse_orphaned_payments(&failing_config, 35);
+
+        assert_eq!(result, Err("BOOM".to_string()));
+        assert_eq!(subject.account_status(&debtor).unwrap().balance, 1034);
+        assert_eq!(subject.credited_blocks(), vec![credited_block(35)]);
+        assert_eq!(persistent_config.start_block(), 36);
+        TestLogHandler::new().exists_log_containing(
+            "ERROR: ReceivableDaoReal: Transaction failed, rolling back: BOOM",
+        );
+    }
+
     #[test]
     fn receivable_account_status_works_when_account_doesnt_exist() {
         let home_dir = ensure_node_home_directory_exists(
@@ -839,6 +1373,106 @@ mod tests {
         assert_eq!(1, result.len());
     }
 
+    #[test]
+    fn new_delinquencies_does_not_find_pardoned_delinquencies() {
+        let pcs = PaymentCurves {
+            payment_suggested_after_sec: 25,
+            payment_grace_before_ban_sec: 50,
+            permanent_debt_allowed_gwub: 100,
+            balance_to_decrease_from_gwub: 200,
+            balance_decreases_for_sec: 100,
+            unban_when_balance_below_gwub: 0, // doesn't matter for this test
+        };
+        let now = now_time_t();
+        let mut pardoned_delinquency = make_receivable_account(1234, true);
+        pardoned_delinquency.balance = 250;
+        pardoned_delinquency.last_received_timestamp = from_time_t(pcs.sugg_and_grace(now) - 1);
+        let mut new_delinquency = make_receivable_account(2345, true);
+        new_delinquency.balance = 250;
+        new_delinquency.last_received_timestamp = from_time_t(pcs.sugg_and_grace(now) - 1);
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "new_delinquencies_does_not_find_pardoned_delinquencies",
+        );
+        let conn = DbInitializerReal::new()
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        add_receivable_account(&conn, &pardoned_delinquency);
+        add_receivable_account(&conn, &new_delinquency);
+        add_pardoned_account(conn.as_ref(), &pardoned_delinquency);
+        let subject = ReceivableDaoReal::new(conn);
+
+        let result = subject.new_delinquencies(from_time_t(now), &pcs);
+
+        assert_contains(&result, &new_delinquency);
+        assert_eq!(1, result.len());
+    }
+
+    #[test]
+    fn new_delinquencies_finds_pardoned_wallets_that_owe_more_than_they_were_pardoned_for() {
+        let pcs = PaymentCurves {
+            payment_suggested_after_sec: 25,
+            payment_grace_before_ban_sec: 50,
+            permanent_debt_allowed_gwub: 100,
+            balance_to_decrease_from_gwub: 200,
+            balance_decreases_for_sec: 100,
+            unban_when_balance_below_gwub: 0, // doesn't matter for this test
+        };
+        let now = now_time_t();
+        let mut relapsed_delinquency = make_receivable_account(1234, true);
+        relapsed_delinquency.balance = 250;
+        relapsed_delinquency.last_received_timestamp = from_time_t(pcs.sugg_and_grace(now) - 1);
+        let mut pardon = relapsed_delinquency.clone();
+        pardon.balance = 249;
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "new_delinquencies_finds_pardoned_wallets_that_owe_more_than_they_were_pardoned_for",
+        );
+        let conn = DbInitializerReal::new()
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        add_receivable_account(&conn, &relapsed_delinquency);
+        add_pardoned_account(conn.as_ref(), &pardon);
+        let subject = ReceivableDaoReal::new(conn);
+
+        let result = subject.new_delinquencies(from_time_t(now), &pcs);
+
+        assert_eq!(result, vec![relapsed_delinquency]);
+    }
+
+    #[test]
+    fn paid_delinquencies_does_not_find_manual_bans() {
+        let pcs = PaymentCurves {
+            payment_suggested_after_sec: 0,   // doesn't matter for this test
+            payment_grace_before_ban_sec: 0,  // doesn't matter for this test
+            permanent_debt_allowed_gwub: 0,   // doesn't matter for this test
+            balance_to_decrease_from_gwub: 0, // doesn't matter for this test
+            balance_decreases_for_sec: 0,     // doesn't matter for this test
+            unban_when_balance_below_gwub: 50,
+        };
+        let mut paid_delinquent = make_receivable_account(1234, true);
+        paid_delinquent.balance = 50;
+        let mut manually_banned = make_receivable_account(2345, true);
+        manually_banned.balance = 50;
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "paid_delinquencies_does_not_find_manual_bans",
+        );
+        let conn = DbInitializerReal::new()
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        add_receivable_account(&conn, &paid_delinquent);
+        add_receivable_account(&conn, &manually_banned);
+        add_banned_account(&conn, &paid_delinquent);
+        add_banned_account_with_manual(conn.as_ref(), &manually_banned, true);
+        let subject = ReceivableDaoReal::new(conn);
+
+        let result = subject.paid_delinquencies(&pcs);
+
+        assert_contains(&result, &paid_delinquent);
+        assert_eq!(1, result.len());
+    }
+
     #[test]
     fn paid_delinquencies() {
         let pcs = PaymentCurves {
@@ -991,9 +1625,26 @@ mod tests {
     }
 
     fn add_banned_account(conn: &Box<dyn ConnectionWrapper>, account: &ReceivableAccount) {
+        add_banned_account_with_manual(conn.as_ref(), account, false)
+    }
+
+    fn add_banned_account_with_manual(
+        conn: &dyn ConnectionWrapper,
+        account: &ReceivableAccount,
+        manual: bool,
+    ) {
         let mut stmt = conn
-            .prepare("insert into banned (wallet_address) values (?)")
+            .prepare("insert into banned (wallet_address, reason, banned_timestamp, manual) values (?, 'delinquency', 0, ?)")
             .unwrap();
-        stmt.execute(&[&account.wallet]).unwrap();
+        let params: &[&dyn ToSql] = &[&account.wallet, &manual];
+        stmt.execute(params).unwrap();
+    }
+
+    fn add_pardoned_account(conn: &dyn ConnectionWrapper, account: &ReceivableAccount) {
+        let mut stmt = conn
+            .prepare("insert into pardoned (wallet_address, pardoned_timestamp, pardoned_balance) values (?, 0, ?)")
+            .unwrap();
+        let params: &[&dyn ToSql] = &[&account.wallet, &account.balance];
+        stmt.execute(params).unwrap();
     }
 }
@@ -19,6 +19,7 @@ use crate::blockchain::blockchain_bridge::BlockchainBridge;
 use crate::blockchain::blockchain_interface::{
     BlockchainInterface, BlockchainInterfaceClandestine, BlockchainInterfaceNonClandestine,
 };
+use crate::blockchain::failover_transport::{parse_service_urls, FailoverTransport};
 use crate::config_dao::ConfigDaoReal;
 use crate::database::db_initializer::{DbInitializer, DbInitializerReal, DATABASE_FILE};
 use crate::persistent_configuration::PersistentConfigurationReal;
@@ -42,7 +43,6 @@ use actix::{Actor, Arbiter};
 use std::path::PathBuf;
 use std::sync::mpsc;
 use std::sync::mpsc::Sender;
-use web3::transports::Http;
 
 pub trait ActorSystemFactory: Send {
     fn make_and_start_actors(
@@ -107,6 +107,7 @@ impl ActorSystemFactoryReal {
                 .clone()
                 .exit_service_rate,
             exit_byte_rate: config.neighborhood_config.mode.rate_pack().exit_byte_rate,
+            exit_policy: config.exit_policy.clone(),
         });
         let hopper_subs = actor_factory.make_and_start_hopper(HopperConfig {
             main_cryptde,
@@ -392,14 +393,19 @@ impl ActorFactory for ActorFactoryReal {
             .clone();
         let blockchain_interface: Box<dyn BlockchainInterface> = {
             match blockchain_service_url {
-                Some(url) => match Http::new(&url) {
-                    Ok((event_loop_handle, transport)) => {
-                        Box::new(BlockchainInterfaceNonClandestine::new(
+                Some(urls) => match FailoverTransport::new(
+                    &parse_service_urls(&urls),
+                    config.blockchain_bridge_config.blockchain_service_failover,
+                ) {
+                    Ok((event_loop_handle, transport)) => Box::new(
+                        BlockchainInterfaceNonClandestine::new(
                             transport,
                             event_loop_handle,
                             config.blockchain_bridge_config.chain_id,
-                        ))
-                    }
+                        )
+                        .scan_block_range(config.blockchain_bridge_config.scan_block_range)
+                        .confirmation_depth(config.blockchain_bridge_config.confirmation_depth),
+                    ),
                     Err(e) => panic!("Invalid blockchain node URL: {:?}", e),
                 },
                 None => Box::new(BlockchainInterfaceClandestine::new(
@@ -432,8 +438,11 @@ impl ActorFactory for ActorFactoryReal {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::accountant::{ReceivedPayments, SentPayments};
+    use crate::accountant::{
+        ReceivedPayments, ReportTransactionReceipts, ResentPayments, SentPayments,
+    };
     use crate::blockchain::blockchain_bridge::RetrieveTransactions;
+    use crate::blockchain::failover_transport::FailoverPolicy;
     use crate::bootstrapper::{Bootstrapper, RealUser};
     use crate::database::db_initializer::test_utils::{ConnectionWrapperMock, DbInitializerMock};
     use crate::database::db_initializer::{ConnectionWrapper, InitializationError};
@@ -447,27 +456,37 @@ mod tests {
         ReportExitServiceConsumedMessage, ReportExitServiceProvidedMessage,
     };
     use crate::sub_lib::blockchain_bridge::{BlockchainBridgeConfig, ReportAccountsPayable};
+    use crate::sub_lib::blockchain_bridge::{RequestTransactionReceipts, ResubmitPayments};
     use crate::sub_lib::cryptde::PlainData;
     use crate::sub_lib::dispatcher::{InboundClientData, StreamShutdownMsg};
+    use crate::sub_lib::exit_policy::{parse_exit_rules, ExitPolicy};
     use crate::sub_lib::hopper::IncipientCoresPackage;
     use crate::sub_lib::hopper::{ExpiredCoresPackage, NoLookupIncipientCoresPackage};
+    use crate::sub_lib::logger::LogFilters;
     use crate::sub_lib::neighborhood::RouteQueryMessage;
     use crate::sub_lib::neighborhood::{
         DispatcherNodeQueryMessage, GossipFailure_0v1, NodeRecordMetadataMessage,
     };
-    use crate::sub_lib::neighborhood::{NeighborhoodConfig, NodeQueryMessage};
-    use crate::sub_lib::neighborhood::{NeighborhoodMode, RemoveNeighborMessage};
+    use crate::sub_lib::neighborhood::{
+        NeighborhoodConfig, NodeQueryMessage, UNLIMITED_ROUTE_COST_BUDGET,
+    };
+    use crate::sub_lib::neighborhood::{
+        NeighborhoodMode, NodeRatePackChangeMessage, RatePackChangeMessage, RemoveNeighborMessage,
+    };
     use crate::sub_lib::node_addr::NodeAddr;
     use crate::sub_lib::peer_actors::StartMessage;
     use crate::sub_lib::proxy_client::{
-        ClientResponsePayload_0v1, DnsResolveFailure_0v1, InboundServerData,
+        ClientResponsePayload_0v1, DatagramResponsePayload_0v1, DnsResolveFailure_0v1,
+        ExitPolicyViolation_0v1, InboundServerData,
     };
     use crate::sub_lib::proxy_server::{
         AddReturnRouteMessage, AddRouteMessage, ClientRequestPayload_0v1,
+        DatagramRequestPayload_0v1,
     };
     use crate::sub_lib::set_consuming_wallet_message::SetConsumingWalletMessage;
     use crate::sub_lib::stream_handler_pool::DispatcherNodeQueryResponse;
     use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
+    use crate::sub_lib::udp_relay::InboundDatagram;
     use crate::sub_lib::ui_gateway::UiGatewayConfig;
     use crate::test_utils::recorder::Recorder;
     use crate::test_utils::recorder::Recording;
@@ -475,6 +494,9 @@ mod tests {
     use crate::test_utils::{main_cryptde, make_wallet};
     use actix::System;
     use log::LevelFilter;
+    use masq_lib::constants::{
+        DEFAULT_CONFIRMATION_DEPTH, DEFAULT_MAX_GAS_PRICE, DEFAULT_SCAN_BLOCK_RANGE,
+    };
     use masq_lib::crash_point::CrashPoint;
     use masq_lib::test_utils::utils::DEFAULT_CHAIN_ID;
     use masq_lib::ui_gateway::NodeFromUiMessage;
@@ -558,10 +580,18 @@ mod tests {
                 dns_failure_from_hopper: addr
                     .clone()
                     .recipient::<ExpiredCoresPackage<DnsResolveFailure_0v1>>(),
+                exit_policy_violation_from_hopper: addr
+                    .clone()
+                    .recipient::<ExpiredCoresPackage<ExitPolicyViolation_0v1>>(),
+                datagram_from_hopper: addr
+                    .clone()
+                    .recipient::<ExpiredCoresPackage<DatagramResponsePayload_0v1>>(),
+                inbound_datagram: recipient!(addr, InboundDatagram),
                 add_return_route: recipient!(addr, AddReturnRouteMessage),
                 add_route: recipient!(addr, AddRouteMessage),
                 stream_shutdown_sub: recipient!(addr, StreamShutdownMsg),
                 set_consuming_wallet_sub: recipient!(addr, SetConsumingWalletMessage),
+                node_rate_pack_change: recipient!(addr, NodeRatePackChangeMessage),
             }
         }
 
@@ -579,6 +609,7 @@ mod tests {
                     .clone()
                     .recipient::<NoLookupIncipientCoresPackage>(),
                 from_dispatcher: recipient!(addr, InboundClientData),
+                rate_pack_change: recipient!(addr, RatePackChangeMessage),
             }
         }
 
@@ -641,6 +672,8 @@ mod tests {
                     .recipient::<ReportExitServiceConsumedMessage>(),
                 report_new_payments: recipient!(addr, ReceivedPayments),
                 report_sent_payments: recipient!(addr, SentPayments),
+                report_transaction_receipts: recipient!(addr, ReportTransactionReceipts),
+                report_resent_payments: recipient!(addr, ResentPayments),
                 ui_message_sub: addr.clone().recipient::<NodeFromUiMessage>(),
             }
         }
@@ -687,6 +720,12 @@ mod tests {
                     .recipient::<ExpiredCoresPackage<ClientRequestPayload_0v1>>(),
                 inbound_server_data: recipient!(addr, InboundServerData),
                 dns_resolve_failed: recipient!(addr, DnsResolveFailure_0v1),
+                exit_policy_violated: recipient!(addr, ExitPolicyViolation_0v1),
+                datagram_from_hopper: addr
+                    .clone()
+                    .recipient::<ExpiredCoresPackage<DatagramRequestPayload_0v1>>(),
+                inbound_datagram: recipient!(addr, InboundDatagram),
+                rate_pack_change: recipient!(addr, RatePackChangeMessage),
             }
         }
 
@@ -705,6 +744,10 @@ mod tests {
                 bind: recipient!(addr, BindMessage),
                 report_accounts_payable: addr.clone().recipient::<ReportAccountsPayable>(),
                 retrieve_transactions: addr.clone().recipient::<RetrieveTransactions>(),
+                request_transaction_receipts: addr
+                    .clone()
+                    .recipient::<RequestTransactionReceipts>(),
+                resubmit_payments: addr.clone().recipient::<ResubmitPayments>(),
                 ui_sub: addr.clone().recipient::<NodeFromUiMessage>(),
             }
         }
@@ -819,6 +862,7 @@ mod tests {
         let aconfig = AccountantConfig {
             payable_scan_interval: Duration::from_secs(9),
             payment_received_scan_interval: Duration::from_secs(100),
+            pending_payment_scan_interval: Duration::from_secs(100),
         };
         let mut config = BootstrapperConfig::new();
         config.accountant_config = aconfig;
@@ -866,6 +910,7 @@ mod tests {
         let aconfig = AccountantConfig {
             payable_scan_interval: Duration::from_secs(6),
             payment_received_scan_interval: Duration::from_secs(100),
+            pending_payment_scan_interval: Duration::from_secs(100),
         };
         let mut config = BootstrapperConfig::new();
         config.accountant_config = aconfig;
@@ -889,6 +934,7 @@ mod tests {
         let aconfig = AccountantConfig {
             payable_scan_interval: Duration::from_secs(6),
             payment_received_scan_interval: Duration::from_secs(100),
+            pending_payment_scan_interval: Duration::from_secs(100),
         };
         let mut config = BootstrapperConfig::new();
         config.accountant_config = aconfig;
@@ -914,6 +960,7 @@ mod tests {
         let aconfig = AccountantConfig {
             payable_scan_interval: Duration::from_secs(6),
             payment_received_scan_interval: Duration::from_secs(1000),
+            pending_payment_scan_interval: Duration::from_secs(1000),
         };
         let mut config = BootstrapperConfig::new();
         config.accountant_config = aconfig;
@@ -939,6 +986,7 @@ mod tests {
         let aconfig = AccountantConfig {
             payable_scan_interval: Duration::from_secs(6),
             payment_received_scan_interval: Duration::from_secs(1000),
+            pending_payment_scan_interval: Duration::from_secs(1000),
         };
         let mut config = BootstrapperConfig::new();
         config.accountant_config = aconfig;
@@ -964,8 +1012,13 @@ mod tests {
     fn invalid_blockchain_url_produces_panic() {
         let bbconfig = BlockchainBridgeConfig {
             blockchain_service_url: Some("http://λ:8545".to_string()),
+            blockchain_service_failover: FailoverPolicy::Priority,
             chain_id: DEFAULT_CHAIN_ID,
             gas_price: 1,
+            max_gas_price: DEFAULT_MAX_GAS_PRICE,
+            batch_payments: false,
+            scan_block_range: DEFAULT_SCAN_BLOCK_RANGE,
+            confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
         };
         let mut config = BootstrapperConfig::new();
         config.blockchain_bridge_config = bbconfig;
@@ -980,21 +1033,31 @@ mod tests {
         let recordings = actor_factory.get_recordings();
         let config = BootstrapperConfig {
             log_level: LevelFilter::Off,
+            log_filters: LogFilters::default(),
             crash_point: CrashPoint::None,
             dns_servers: vec![],
+            exit_policy: ExitPolicy::default(),
             accountant_config: AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             clandestine_discriminator_factories: Vec::new(),
             ui_gateway_config: UiGatewayConfig {
                 ui_port: 5335,
                 node_descriptor: String::from("uninitialized"),
+                ui_auth_token: String::new(),
             },
+            metrics_port_opt: None,
             blockchain_bridge_config: BlockchainBridgeConfig {
                 blockchain_service_url: None,
+                blockchain_service_failover: FailoverPolicy::Priority,
                 chain_id: DEFAULT_CHAIN_ID,
                 gas_price: 1,
+                max_gas_price: DEFAULT_MAX_GAS_PRICE,
+                batch_payments: false,
+                scan_block_range: DEFAULT_SCAN_BLOCK_RANGE,
+                confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
             },
             port_configurations: HashMap::new(),
             db_password_opt: None,
@@ -1011,6 +1074,7 @@ mod tests {
                     vec![],
                     rate_pack(100),
                 ),
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             },
         };
         Bootstrapper::pub_initialize_cryptdes_for_testing(
@@ -1044,21 +1108,35 @@ mod tests {
         let parameters = actor_factory.make_parameters();
         let config = BootstrapperConfig {
             log_level: LevelFilter::Off,
+            log_filters: LogFilters::default(),
             crash_point: CrashPoint::None,
             dns_servers: vec![],
+            exit_policy: ExitPolicy {
+                rules: parse_exit_rules("deny *:25").unwrap(),
+                exit_ports_opt: Some(vec![443, 993]),
+                block_private_ranges: false,
+            },
             accountant_config: AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             clandestine_discriminator_factories: Vec::new(),
             ui_gateway_config: UiGatewayConfig {
                 ui_port: 5335,
                 node_descriptor: String::from("NODE-DESCRIPTOR"),
+                ui_auth_token: String::new(),
             },
+            metrics_port_opt: None,
             blockchain_bridge_config: BlockchainBridgeConfig {
                 blockchain_service_url: None,
+                blockchain_service_failover: FailoverPolicy::Priority,
                 chain_id: DEFAULT_CHAIN_ID,
                 gas_price: 1,
+                max_gas_price: DEFAULT_MAX_GAS_PRICE,
+                batch_payments: false,
+                scan_block_range: DEFAULT_SCAN_BLOCK_RANGE,
+                confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
             },
             port_configurations: HashMap::new(),
             db_password_opt: None,
@@ -1071,6 +1149,7 @@ mod tests {
             real_user: RealUser::null(),
             neighborhood_config: NeighborhoodConfig {
                 mode: NeighborhoodMode::ZeroHop,
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             },
         };
         let (tx, rx) = mpsc::channel();
@@ -1103,6 +1182,7 @@ mod tests {
         assert_eq!(proxy_client_config.exit_service_rate, 0);
         assert_eq!(proxy_client_config.exit_byte_rate, 0);
         assert_eq!(proxy_client_config.dns_servers, config.dns_servers);
+        assert_eq!(proxy_client_config.exit_policy, config.exit_policy);
         let (
             actual_main_cryptde,
             actual_alias_cryptde,
@@ -1135,8 +1215,13 @@ mod tests {
             bootstrapper_config.blockchain_bridge_config,
             BlockchainBridgeConfig {
                 blockchain_service_url: None,
+                blockchain_service_failover: FailoverPolicy::Priority,
                 chain_id: DEFAULT_CHAIN_ID,
                 gas_price: 1,
+                max_gas_price: DEFAULT_MAX_GAS_PRICE,
+                batch_payments: false,
+                scan_block_range: DEFAULT_SCAN_BLOCK_RANGE,
+                confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
             }
         );
         assert_eq!(
@@ -1154,21 +1239,31 @@ mod tests {
         let parameters = actor_factory.make_parameters();
         let config = BootstrapperConfig {
             log_level: LevelFilter::Off,
+            log_filters: LogFilters::default(),
             crash_point: CrashPoint::None,
             dns_servers: vec![],
+            exit_policy: ExitPolicy::default(),
             accountant_config: AccountantConfig {
                 payable_scan_interval: Duration::from_secs(100),
                 payment_received_scan_interval: Duration::from_secs(100),
+                pending_payment_scan_interval: Duration::from_secs(100),
             },
             clandestine_discriminator_factories: Vec::new(),
             ui_gateway_config: UiGatewayConfig {
                 ui_port: 5335,
                 node_descriptor: String::from("NODE-DESCRIPTOR"),
+                ui_auth_token: String::new(),
             },
+            metrics_port_opt: None,
             blockchain_bridge_config: BlockchainBridgeConfig {
                 blockchain_service_url: None,
+                blockchain_service_failover: FailoverPolicy::Priority,
                 chain_id: DEFAULT_CHAIN_ID,
                 gas_price: 1,
+                max_gas_price: DEFAULT_MAX_GAS_PRICE,
+                batch_payments: false,
+                scan_block_range: DEFAULT_SCAN_BLOCK_RANGE,
+                confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
             },
             port_configurations: HashMap::new(),
             db_password_opt: None,
@@ -1185,6 +1280,7 @@ mod tests {
                     vec![],
                     rate_pack(100),
                 ),
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             },
         };
         let (tx, _) = mpsc::channel();
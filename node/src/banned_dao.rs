@@ -1,10 +1,12 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
+use crate::database::dao_utils::{from_time_t, now_time_t};
 use crate::database::db_initializer::ConnectionWrapper;
 use crate::sub_lib::wallet::Wallet;
 use lazy_static::lazy_static;
-use rusqlite::{Error, ErrorCode, ToSql, NO_PARAMS};
+use rusqlite::{Error, ErrorCode, ToSql, Transaction, NO_PARAMS};
 use std::collections::HashSet;
 use std::sync::RwLock;
+use std::time::SystemTime;
 
 lazy_static! {
     pub static ref BAN_CACHE: BannedCache = BannedCache::default();
@@ -61,10 +63,33 @@ impl BannedCacheLoader for BannedCacheLoaderReal {
     }
 }
 
+pub const DELINQUENCY_BAN_REASON: &str = "delinquency";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BannedWallet {
+    pub wallet: Wallet,
+    pub reason: String,
+    pub banned_timestamp: SystemTime,
+    pub manual: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PardonedWallet {
+    pub wallet: Wallet,
+    pub pardoned_timestamp: SystemTime,
+}
+
+// ban() and unban() are for the Accountant's delinquency scans; manual_ban() and manual_unban()
+// are for the operator. A manual ban can't be lifted by unban(), and a manual unban leaves a
+// pardon behind that keeps the scans from banning the wallet again over the debt it owed when it
+// was pardoned. The pardon shrinks as the wallet pays, and ends when the operator bans it.
 pub trait BannedDao: Send {
-    fn ban_list(&self) -> Vec<Wallet>;
+    fn ban_list(&self) -> Vec<BannedWallet>;
+    fn pardon_list(&self) -> Vec<PardonedWallet>;
     fn ban(&self, wallet: &Wallet);
     fn unban(&self, wallet: &Wallet);
+    fn manual_ban(&mut self, wallet: &Wallet, reason: &str);
+    fn manual_unban(&mut self, wallet: &Wallet) -> bool;
 }
 
 pub struct BannedDaoReal {
@@ -75,18 +100,68 @@ impl BannedDaoReal {
     pub fn new(conn: Box<dyn ConnectionWrapper>) -> Self {
         Self { conn }
     }
+
+    fn execute_or_panic(
+        tx: &Transaction,
+        sql: &str,
+        params: &[&dyn ToSql],
+        failure: &str,
+    ) -> usize {
+        tx.execute(sql, params)
+            .unwrap_or_else(|e| panic!("{} because of database corruption: {}", failure, e))
+    }
+
+    fn transaction_or_panic(&mut self, failure: &str) -> Transaction<'_> {
+        self.conn
+            .transaction()
+            .unwrap_or_else(|e| panic!("{} because of database corruption: {}", failure, e))
+    }
+
+    fn commit_or_panic(tx: Transaction, failure: &str) {
+        tx.commit()
+            .unwrap_or_else(|e| panic!("{} because of database corruption: {}", failure, e))
+    }
 }
 
 impl BannedDao for BannedDaoReal {
-    fn ban_list(&self) -> Vec<Wallet> {
+    fn ban_list(&self) -> Vec<BannedWallet> {
         let mut stmt = self
             .conn
-            .prepare("select wallet_address from banned")
+            .prepare("select wallet_address, reason, banned_timestamp, manual from banned order by banned_timestamp, wallet_address")
+            .expect("Failed to prepare a statement");
+        stmt.query_map(NO_PARAMS, |row| {
+            let wallet: Wallet = row.get(0)?;
+            let reason: String = row.get(1)?;
+            let banned_timestamp: i64 = row.get(2)?;
+            let manual: i64 = row.get(3)?;
+            Ok(BannedWallet {
+                wallet,
+                reason,
+                banned_timestamp: from_time_t(banned_timestamp),
+                manual: manual != 0,
+            })
+        })
+        .expect("Couldn't retrieve delinquency-ban list: database corrupt")
+        .flatten()
+        .collect()
+    }
+
+    fn pardon_list(&self) -> Vec<PardonedWallet> {
+        let mut stmt = self
+            .conn
+            .prepare("select wallet_address, pardoned_timestamp from pardoned order by pardoned_timestamp, wallet_address")
             .expect("Failed to prepare a statement");
-        stmt.query_map(NO_PARAMS, |row| row.get(0))
-            .expect("Couldn't retrieve delinquency-ban list: database corrupt")
-            .flatten()
-            .collect()
+        stmt.query_map(NO_PARAMS, |row| {
+            let wallet: Wallet = row.get(0)?;
+            let pardoned_timestamp: i64 = row.get(1)?;
+            Ok(PardonedWallet {
+                wallet,
+                pardoned_timestamp: from_time_t(pardoned_timestamp),
+            })
+        })
+        .expect("Couldn't retrieve pardon list: database corrupt")
+        .flatten()
+        .collect()
     }
 
     fn ban(&self, wallet: &Wallet) {
@@ -96,9 +171,9 @@ impl BannedDao for BannedDaoReal {
 
         let mut stmt = self
             .conn
-            .prepare("insert into banned (wallet_address) values (?)")
+            .prepare("insert into banned (wallet_address, reason, banned_timestamp, manual) values (?, ?, ?, 0)")
             .expect("Failed to prepare a statement");
-        let params: &[&dyn ToSql] = &[&wallet];
+        let params: &[&dyn ToSql] = &[&wallet, &DELINQUENCY_BAN_REASON, &now_time_t()];
         match stmt.execute(params) {
             Ok(_) => BAN_CACHE.insert(wallet.clone()),
             Err(e) => match e {
@@ -120,10 +195,11 @@ impl BannedDao for BannedDaoReal {
 
         let mut stmt = self
             .conn
-            .prepare("delete from banned where wallet_address = ?")
+            .prepare("delete from banned where wallet_address = ? and manual = 0")
             .expect("Failed to prepare a statement");
         let params: &[&dyn ToSql] = &[&wallet];
         match stmt.execute(params) {
+            Ok(0) => (),
             Ok(_) => BAN_CACHE.remove(&wallet),
             Err(e) => panic!(format!(
                 "Could not terminate delinquency ban for {} because of database corruption: {}",
@@ -131,6 +207,46 @@ impl BannedDao for BannedDaoReal {
             )),
         }
     }
+
+    fn manual_ban(&mut self, wallet: &Wallet, reason: &str) {
+        let failure = format!("Could not ban {}", wallet);
+        let tx = self.transaction_or_panic(&failure);
+        Self::execute_or_panic(
+            &tx,
+            "delete from pardoned where wallet_address = ?",
+            &[&wallet],
+            &format!("Could not revoke pardon for {}", wallet),
+        );
+        Self::execute_or_panic(
+            &tx,
+            "insert or replace into banned (wallet_address, reason, banned_timestamp, manual) values (?, ?, ?, 1)",
+            &[&wallet, &reason, &now_time_t()],
+            &failure,
+        );
+        Self::commit_or_panic(tx, &failure);
+        BAN_CACHE.insert(wallet.clone());
+    }
+
+    fn manual_unban(&mut self, wallet: &Wallet) -> bool {
+        let failure = format!("Could not pardon {}", wallet);
+        let tx = self.transaction_or_panic(&failure);
+        let was_banned = Self::execute_or_panic(
+            &tx,
+            "delete from banned where wallet_address = ?",
+            &[&wallet],
+            &format!("Could not unban {}", wallet),
+        ) > 0;
+        Self::execute_or_panic(
+            &tx,
+            "insert or replace into pardoned (wallet_address, pardoned_timestamp, pardoned_balance) \
+             values (?, ?, coalesce((select balance from receivable where wallet_address = ?), 0))",
+            &[&wallet, &now_time_t(), &wallet],
+            &failure,
+        );
+        Self::commit_or_panic(tx, &failure);
+        BAN_CACHE.remove(wallet);
+        was_banned
+    }
 }
 
 #[cfg(test)]
@@ -189,10 +305,203 @@ mod tests {
         subject.ban(&make_wallet("no_duplicate_wallets"));
         subject.ban(&make_wallet("no_duplicate_wallets"));
 
-        let ban_list = subject.ban_list();
+        let ban_list = subject
+            .ban_list()
+            .into_iter()
+            .map(|banned| banned.wallet)
+            .collect::<Vec<Wallet>>();
         assert_eq!(vec![make_wallet("no_duplicate_wallets")], ban_list);
     }
 
+    #[test]
+    fn ban_list_reports_reasons_timestamps_and_who_imposed_the_bans() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "banned_dao",
+            "ban_list_reports_reasons_timestamps_and_who_imposed_the_bans",
+        );
+        let mut subject = BannedDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let before = from_time_t(now_time_t());
+
+        subject.ban(&make_wallet("automatically_banned"));
+        subject.manual_ban(&make_wallet("manually_banned"), "spammer");
+
+        let after = from_time_t(now_time_t());
+        let ban_list = subject.ban_list();
+        let summary = ban_list
+            .iter()
+            .map(|banned| (banned.wallet.clone(), banned.reason.as_str(), banned.manual))
+            .collect::<HashSet<(Wallet, &str, bool)>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    make_wallet("automatically_banned"),
+                    DELINQUENCY_BAN_REASON,
+                    false
+                ),
+                (make_wallet("manually_banned"), "spammer", true),
+            ]
+            .into_iter()
+            .collect::<HashSet<(Wallet, &str, bool)>>()
+        );
+        ban_list.iter().for_each(|banned| {
+            assert!(banned.banned_timestamp >= before && banned.banned_timestamp <= after)
+        });
+    }
+
+    #[test]
+    fn unban_does_not_lift_a_manual_ban() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "banned_dao",
+            "unban_does_not_lift_a_manual_ban",
+        );
+        let mut subject = BannedDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let wallet = make_wallet("operator_says_no");
+        subject.manual_ban(&wallet, "operator says no");
+
+        subject.unban(&wallet);
+
+        assert_eq!(subject.ban_list().len(), 1);
+        assert!(BAN_CACHE.is_banned(&wallet));
+    }
+
+    #[test]
+    fn manual_unban_lifts_an_automatic_ban_and_leaves_a_pardon() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "banned_dao",
+            "manual_unban_lifts_an_automatic_ban_and_leaves_a_pardon",
+        );
+        let mut subject = BannedDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let wallet = make_wallet("paid_off_chain");
+        subject.ban(&wallet);
+        let before = from_time_t(now_time_t());
+
+        let result = subject.manual_unban(&wallet);
+
+        let after = from_time_t(now_time_t());
+        assert!(result);
+        assert!(subject.ban_list().is_empty());
+        assert!(!BAN_CACHE.is_banned(&wallet));
+        let pardon_list = subject.pardon_list();
+        assert_eq!(pardon_list.len(), 1);
+        assert_eq!(pardon_list[0].wallet, wallet);
+        assert!(
+            pardon_list[0].pardoned_timestamp >= before
+                && pardon_list[0].pardoned_timestamp <= after
+        );
+    }
+
+    #[test]
+    fn manual_unban_pardons_only_what_the_wallet_owes_now() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "banned_dao",
+            "manual_unban_pardons_only_what_the_wallet_owes_now",
+        );
+        let db_initializer = DbInitializerReal::new();
+        let mut subject = BannedDaoReal::new(
+            db_initializer
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let conn = db_initializer
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        let debtor = make_wallet("debtor");
+        let stranger = make_wallet("stranger");
+        conn.prepare("insert into receivable (wallet_address, balance, last_received_timestamp) values (?, 1234, 0)")
+            .unwrap()
+            .execute(&[&debtor])
+            .unwrap();
+
+        subject.manual_unban(&debtor);
+        subject.manual_unban(&stranger);
+
+        let pardoned_balance = |wallet: &Wallet| -> i64 {
+            conn.prepare("select pardoned_balance from pardoned where wallet_address = ?")
+                .unwrap()
+                .query_row(&[wallet], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(pardoned_balance(&debtor), 1234);
+        assert_eq!(pardoned_balance(&stranger), 0);
+    }
+
+    #[test]
+    fn manual_unban_of_an_unbanned_wallet_still_leaves_a_pardon() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "banned_dao",
+            "manual_unban_of_an_unbanned_wallet_still_leaves_a_pardon",
+        );
+        let mut subject = BannedDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let wallet = make_wallet("never_banned");
+
+        let result = subject.manual_unban(&wallet);
+
+        assert!(!result);
+        assert_eq!(
+            subject
+                .pardon_list()
+                .into_iter()
+                .map(|pardoned| pardoned.wallet)
+                .collect::<Vec<Wallet>>(),
+            vec![wallet]
+        );
+    }
+
+    #[test]
+    fn manual_ban_revokes_a_pardon_and_takes_over_an_automatic_ban() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "banned_dao",
+            "manual_ban_revokes_a_pardon_and_takes_over_an_automatic_ban",
+        );
+        let mut subject = BannedDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let pardoned_wallet = make_wallet("pardoned_then_banned");
+        let delinquent_wallet = make_wallet("delinquent_then_banned");
+        subject.manual_unban(&pardoned_wallet);
+        subject.ban(&delinquent_wallet);
+
+        subject.manual_ban(&pardoned_wallet, "relapsed");
+        subject.manual_ban(&delinquent_wallet, "abusive");
+
+        assert!(subject.pardon_list().is_empty());
+        let summary = subject
+            .ban_list()
+            .into_iter()
+            .map(|banned| (banned.wallet, banned.reason, banned.manual))
+            .collect::<HashSet<(Wallet, String, bool)>>();
+        assert_eq!(
+            summary,
+            vec![
+                (pardoned_wallet.clone(), "relapsed".to_string(), true),
+                (delinquent_wallet.clone(), "abusive".to_string(), true),
+            ]
+            .into_iter()
+            .collect::<HashSet<(Wallet, String, bool)>>()
+        );
+        assert!(BAN_CACHE.is_banned(&pardoned_wallet));
+        assert!(BAN_CACHE.is_banned(&delinquent_wallet));
+    }
+
     #[test]
     fn ban_error_when_table_doesnt_exist() {
         let home_dir =
@@ -220,7 +529,7 @@ mod tests {
             .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
             .unwrap();
         let wallet = &make_wallet("booga");
-        conn.prepare("insert into banned (wallet_address) values (?)")
+        conn.prepare("insert into banned (wallet_address, reason, banned_timestamp, manual) values (?, 'delinquency', 0, 0)")
             .unwrap()
             .execute(&[&wallet])
             .unwrap();
@@ -267,7 +576,7 @@ mod tests {
         let conn = db_initializer
             .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
             .unwrap();
-        conn.prepare("insert into banned (wallet_address) values ('0x000000000000000000495f414d5f42414e4e4544')")
+        conn.prepare("insert into banned (wallet_address, reason, banned_timestamp, manual) values ('0x000000000000000000495f414d5f42414e4e4544', 'delinquency', 0, 0)")
             .unwrap()
             .execute(NO_PARAMS)
             .unwrap();
@@ -311,9 +620,9 @@ mod tests {
             .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
             .unwrap();
         let unban_me_baby = make_wallet("UNBAN_ME_BABY");
-        conn.prepare("insert into banned (wallet_address) values ('UNBAN_ME_BABY')")
+        conn.prepare("insert into banned (wallet_address, reason, banned_timestamp, manual) values (?, 'delinquency', 0, 0)")
             .unwrap()
-            .execute(NO_PARAMS)
+            .execute(&[&unban_me_baby])
             .unwrap();
         BAN_CACHE.insert(unban_me_baby.clone());
 
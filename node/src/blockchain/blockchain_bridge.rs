@@ -1,13 +1,16 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 
-use crate::accountant::payable_dao::Payment;
+use crate::accountant::payable_dao::{PayableAccount, Payment};
 use crate::blockchain::blockchain_interface::{
-    BlockchainError, BlockchainInterface, BlockchainResult, Transaction,
+    BlockchainError, BlockchainInterface, BlockchainResult, CreditedBlock, GasFees,
+    RetrievedTransactions, TransactionStatus,
 };
+use crate::blockchain::nonce_manager::NonceManager;
 use crate::bootstrapper::BootstrapperConfig;
 use crate::persistent_configuration::PersistentConfiguration;
 use crate::sub_lib::blockchain_bridge::BlockchainBridgeSubs;
 use crate::sub_lib::blockchain_bridge::ReportAccountsPayable;
+use crate::sub_lib::blockchain_bridge::{RequestTransactionReceipts, ResubmitPayments};
 use crate::sub_lib::logger::Logger;
 use crate::sub_lib::peer_actors::BindMessage;
 use crate::sub_lib::set_consuming_wallet_message::SetConsumingWalletMessage;
@@ -18,16 +21,24 @@ use actix::Handler;
 use actix::Message;
 use actix::{Actor, MessageResult};
 use actix::{Addr, Recipient};
+use itertools::Itertools;
 use masq_lib::crash_point::CrashPoint;
 use masq_lib::messages::{FromMessageBody, UiCrashRequest};
 use masq_lib::ui_gateway::NodeFromUiMessage;
 use std::convert::TryFrom;
+use web3::types::{H256, U256};
 
 pub const CRASH_KEY: &str = "BLOCKCHAINBRIDGE";
 
+// Keeps a batch transaction well inside a block's gas limit
+const MAX_BATCH_PAYMENTS: usize = 50;
+
 pub struct BlockchainBridge {
     consuming_wallet: Option<Wallet>,
     blockchain_interface: Box<dyn BlockchainInterface>,
+    nonce_manager: NonceManager,
+    max_gas_price: u64,
+    batch_payments: bool,
     logger: Logger,
     persistent_config: Box<dyn PersistentConfiguration>,
     set_consuming_wallet_subs: Option<Vec<Recipient<SetConsumingWalletMessage>>>,
@@ -66,10 +77,12 @@ impl Handler<BindMessage> for BlockchainBridge {
 pub struct RetrieveTransactions {
     pub start_block: u64,
     pub recipient: Wallet,
+    // Newest first
+    pub credited_blocks: Vec<CreditedBlock>,
 }
 
 impl Message for RetrieveTransactions {
-    type Result = Result<Vec<Transaction>, BlockchainError>;
+    type Result = Result<RetrievedTransactions, BlockchainError>;
 }
 
 impl Handler<RetrieveTransactions> for BlockchainBridge {
@@ -80,10 +93,7 @@ impl Handler<RetrieveTransactions> for BlockchainBridge {
         msg: RetrieveTransactions,
         _ctx: &mut Self::Context,
     ) -> <Self as Handler<RetrieveTransactions>>::Result {
-        MessageResult(
-            self.blockchain_interface
-                .retrieve_transactions(msg.start_block, &msg.recipient),
-        )
+        MessageResult(self.retrieve_transactions(&msg))
     }
 }
 
@@ -95,37 +105,63 @@ impl Handler<ReportAccountsPayable> for BlockchainBridge {
         msg: ReportAccountsPayable,
         _ctx: &mut Self::Context,
     ) -> <Self as Handler<ReportAccountsPayable>>::Result {
-        MessageResult(match self.consuming_wallet.as_ref() {
+        MessageResult(match self.consuming_wallet.clone() {
+            Some(consuming_wallet) => {
+                let fees = self.gas_fees();
+                let payments = msg
+                    .accounts
+                    .iter()
+                    .map(Self::amount_due)
+                    .collect::<Vec<(Wallet, u64)>>();
+                Ok(self
+                    .batches(&consuming_wallet, &payments)
+                    .into_iter()
+                    .flat_map(|batch| self.pay(&consuming_wallet, batch, fees))
+                    .collect::<Vec<BlockchainResult<Payment>>>())
+            }
+            None => Err(String::from("No consuming wallet specified")),
+        })
+    }
+}
+
+impl Handler<RequestTransactionReceipts> for BlockchainBridge {
+    type Result = MessageResult<RequestTransactionReceipts>;
+
+    fn handle(
+        &mut self,
+        msg: RequestTransactionReceipts,
+        _ctx: &mut Self::Context,
+    ) -> <Self as Handler<RequestTransactionReceipts>>::Result {
+        MessageResult(
+            msg.payments
+                .iter()
+                .map(|payment| {
+                    self.blockchain_interface
+                        .get_transaction_receipt(payment.transaction)
+                })
+                .collect::<Vec<BlockchainResult<TransactionStatus>>>(),
+        )
+    }
+}
+
+impl Handler<ResubmitPayments> for BlockchainBridge {
+    type Result = MessageResult<ResubmitPayments>;
+
+    fn handle(
+        &mut self,
+        msg: ResubmitPayments,
+        _ctx: &mut Self::Context,
+    ) -> <Self as Handler<ResubmitPayments>>::Result {
+        MessageResult(match self.consuming_wallet.clone() {
+            // The payments in a batch share a nonce, and have to be resubmitted together
             Some(consuming_wallet) => Ok(msg
-                .accounts
+                .payments
                 .iter()
-                .map(|payable| {
-                    match self
-                        .blockchain_interface
-                        .get_transaction_count(&consuming_wallet)
-                    {
-                        Ok(nonce) => {
-                            match self.blockchain_interface.send_transaction(
-                                &consuming_wallet,
-                                &payable.wallet,
-                                u64::try_from(payable.balance).unwrap_or_else(|_| {
-                                    panic!("Lost payable amount precision: {}", payable.balance)
-                                }),
-                                nonce,
-                                self.persistent_config.gas_price(),
-                            ) {
-                                Ok(hash) => Ok(Payment::new(
-                                    payable.wallet.clone(),
-                                    u64::try_from(payable.balance).unwrap_or_else(|_| {
-                                        panic!("Lost payable amount precision: {}", payable.balance)
-                                    }),
-                                    hash,
-                                )),
-                                Err(e) => Err(e),
-                            }
-                        }
-                        Err(e) => Err(e),
-                    }
+                .sorted_by_key(|payment| payment.nonce)
+                .group_by(|payment| payment.nonce)
+                .into_iter()
+                .flat_map(|(_, batch)| {
+                    self.resubmit(&consuming_wallet, &batch.collect::<Vec<&Payment>>())
                 })
                 .collect::<Vec<BlockchainResult<Payment>>>()),
             None => Err(String::from("No consuming wallet specified")),
@@ -152,6 +188,9 @@ impl BlockchainBridge {
         BlockchainBridge {
             consuming_wallet: config.consuming_wallet.clone(),
             blockchain_interface,
+            nonce_manager: NonceManager::new(),
+            max_gas_price: config.blockchain_bridge_config.max_gas_price,
+            batch_payments: config.blockchain_bridge_config.batch_payments,
             logger: Logger::new("BlockchainBridge"),
             persistent_config,
             set_consuming_wallet_subs: None,
@@ -159,11 +198,218 @@ impl BlockchainBridge {
         }
     }
 
+    fn retrieve_transactions(
+        &self,
+        msg: &RetrieveTransactions,
+    ) -> BlockchainResult<RetrievedTransactions> {
+        match self.find_reorganization(&msg.credited_blocks)? {
+            Some(first_orphaned_block) => {
+                warning!(
+                    self.logger,
+                    "Blockchain reorganization dropped blocks that payments were credited from; scanning again from block {}",
+                    first_orphaned_block
+                );
+                Ok(RetrievedTransactions {
+                    transactions: vec![],
+                    new_start_block: first_orphaned_block,
+                    caught_up: false,
+                    reorganized: true,
+                })
+            }
+            None => self
+                .blockchain_interface
+                .retrieve_transactions(msg.start_block, &msg.recipient),
+        }
+    }
+
+    // A block is on the canonical chain only if its parent is, so if the newest block a payment was
+    // credited from is still there, they all are. Otherwise, everything after the newest credited
+    // block that's still there has to be scanned again.
+    fn find_reorganization(
+        &self,
+        credited_blocks: &[CreditedBlock],
+    ) -> BlockchainResult<Option<u64>> {
+        let mut first_orphaned_block_opt = None;
+        for credited_block in credited_blocks {
+            let canonical_hash_opt = self
+                .blockchain_interface
+                .get_block_hash(credited_block.block_number)?;
+            if canonical_hash_opt == Some(credited_block.block_hash) {
+                return Ok(first_orphaned_block_opt.map(|_| credited_block.block_number + 1));
+            }
+            first_orphaned_block_opt = Some(credited_block.block_number);
+        }
+        Ok(first_orphaned_block_opt)
+    }
+
+    // The configured gas price is only a fallback, for when the blockchain service can't say what
+    // the going rate is
+    fn gas_fees(&self) -> GasFees {
+        let fees = match self.blockchain_interface.estimate_gas_fees() {
+            Ok(fees) => fees,
+            Err(e) => {
+                let gas_price = self.persistent_config.gas_price();
+                warning!(
+                    self.logger,
+                    "Couldn't estimate gas fees: {} Paying at the configured gas price of {} Gwei",
+                    e,
+                    gas_price
+                );
+                GasFees::Legacy { gas_price }
+            }
+        };
+        let capped_fees = fees.capped_at(self.max_gas_price);
+        if capped_fees != fees {
+            warning!(
+                self.logger,
+                "Estimated {} is more than the maximum gas price of {} Gwei; paying at {} instead",
+                fees,
+                self.max_gas_price,
+                capped_fees
+            );
+        }
+        capped_fees
+    }
+
+    fn amount_due(payable: &PayableAccount) -> (Wallet, u64) {
+        let amount = u64::try_from(payable.balance)
+            .unwrap_or_else(|_| panic!("Lost payable amount precision: {}", payable.balance));
+        (payable.wallet.clone(), amount)
+    }
+
+    // Creditors are only paid several at a time if the batch contract may spend enough of the
+    // consuming wallet's tokens to pay them all; a batch it can't pay for would just fail
+    fn batches<'a>(
+        &self,
+        consuming_wallet: &Wallet,
+        payments: &'a [(Wallet, u64)],
+    ) -> Vec<&'a [(Wallet, u64)]> {
+        let batch_size = if self.batch_payments && payments.len() > 1 {
+            self.batch_size(consuming_wallet, payments)
+        } else {
+            1
+        };
+        payments.chunks(batch_size).collect()
+    }
+
+    fn batch_size(&self, consuming_wallet: &Wallet, payments: &[(Wallet, u64)]) -> usize {
+        let total = payments
+            .iter()
+            .fold(0u64, |total, (_, amount)| total.saturating_add(*amount));
+        match self
+            .blockchain_interface
+            .get_batch_allowance(consuming_wallet)
+        {
+            Ok(Some(allowance)) if allowance >= total => MAX_BATCH_PAYMENTS,
+            Ok(Some(allowance)) => {
+                warning!(
+                    self.logger,
+                    "The batch contract may spend only {} of the consuming wallet's tokens, less than the {} due; paying each creditor separately",
+                    allowance,
+                    total
+                );
+                1
+            }
+            Ok(None) => {
+                warning!(
+                    self.logger,
+                    "This chain has no batch contract; paying each creditor separately"
+                );
+                1
+            }
+            Err(e) => {
+                warning!(
+                    self.logger,
+                    "Couldn't check what the batch contract may spend: {} Paying each creditor separately",
+                    e
+                );
+                1
+            }
+        }
+    }
+
+    fn pay(
+        &mut self,
+        consuming_wallet: &Wallet,
+        payments: &[(Wallet, u64)],
+        fees: GasFees,
+    ) -> Vec<BlockchainResult<Payment>> {
+        let result = self
+            .nonce_manager
+            .next_nonce(self.blockchain_interface.as_ref(), consuming_wallet)
+            .and_then(|nonce| {
+                let hash = self.send(consuming_wallet, payments, nonce, fees)?;
+                self.nonce_manager.nonce_used(nonce);
+                Ok((hash, nonce))
+            });
+        payments
+            .iter()
+            .map(|(to, amount)| {
+                result
+                    .clone()
+                    .map(|(hash, nonce)| Payment::new(to.clone(), *amount, hash, nonce, fees))
+            })
+            .collect()
+    }
+
+    // Every payment in a batch was last sent in the same transaction, at the same fees
+    fn resubmit(
+        &mut self,
+        consuming_wallet: &Wallet,
+        batch: &[&Payment],
+    ) -> Vec<BlockchainResult<Payment>> {
+        let nonce = batch[0].nonce;
+        let fees = batch[0].fees.capped_at(self.max_gas_price);
+        let payments = batch
+            .iter()
+            .map(|payment| (payment.to.clone(), payment.amount))
+            .collect::<Vec<(Wallet, u64)>>();
+        let result = self.send(consuming_wallet, &payments, nonce, fees);
+        if result.is_ok() {
+            // The manager may have been created after this nonce was first handed out
+            self.nonce_manager.nonce_used(nonce);
+        }
+        payments
+            .into_iter()
+            .map(|(to, amount)| {
+                result
+                    .clone()
+                    .map(|hash| Payment::new(to, amount, hash, nonce, fees))
+            })
+            .collect()
+    }
+
+    fn send(
+        &self,
+        consuming_wallet: &Wallet,
+        payments: &[(Wallet, u64)],
+        nonce: U256,
+        fees: GasFees,
+    ) -> BlockchainResult<H256> {
+        match payments {
+            [(recipient, amount)] => self.blockchain_interface.send_transaction(
+                consuming_wallet,
+                recipient,
+                *amount,
+                nonce,
+                fees,
+            ),
+            _ => self.blockchain_interface.send_batch_transaction(
+                consuming_wallet,
+                payments,
+                nonce,
+                fees,
+            ),
+        }
+    }
+
     pub fn make_subs_from(addr: &Addr<BlockchainBridge>) -> BlockchainBridgeSubs {
         BlockchainBridgeSubs {
             bind: recipient!(addr, BindMessage),
             report_accounts_payable: recipient!(addr, ReportAccountsPayable),
             retrieve_transactions: recipient!(addr, RetrieveTransactions),
+            request_transaction_receipts: recipient!(addr, RequestTransactionReceipts),
+            resubmit_payments: recipient!(addr, ResubmitPayments),
             ui_sub: recipient!(addr, NodeFromUiMessage),
         }
     }
@@ -175,8 +421,8 @@ mod tests {
     use crate::accountant::payable_dao::PayableAccount;
     use crate::blockchain::bip32::Bip32ECKeyPair;
     use crate::blockchain::blockchain_interface::{
-        contract_address, Balance, BlockchainError, BlockchainResult, Nonce, Transaction,
-        Transactions,
+        contract_address, Balance, BlockchainError, BlockchainResult, CreditedBlock, Nonce,
+        RetrievedTransactions, Transaction, Transactions,
     };
     use crate::test_utils::logging::init_test_logging;
     use crate::test_utils::logging::TestLogHandler;
@@ -187,6 +433,7 @@ mod tests {
     };
     use actix::Addr;
     use actix::System;
+    use ethereum_types::BigEndianHash;
     use ethsign::SecretKey;
     use ethsign_crypto::Keccak256;
     use futures::future::Future;
@@ -261,19 +508,26 @@ mod tests {
     #[derive(Debug, Default)]
     struct BlockchainInterfaceMock {
         pub retrieve_transactions_parameters: Arc<Mutex<Vec<(u64, Wallet)>>>,
-        pub retrieve_transactions_results: RefCell<Vec<BlockchainResult<Vec<Transaction>>>>,
-        pub send_transaction_parameters: Arc<Mutex<Vec<(Wallet, Wallet, u64, U256, u64)>>>,
+        pub retrieve_transactions_results: RefCell<Vec<Transactions>>,
+        pub send_transaction_parameters: Arc<Mutex<Vec<(Wallet, Wallet, u64, U256, GasFees)>>>,
         pub send_transaction_results: RefCell<Vec<BlockchainResult<H256>>>,
+        pub send_batch_transaction_parameters:
+            Arc<Mutex<Vec<(Wallet, Vec<(Wallet, u64)>, U256, GasFees)>>>,
+        pub send_batch_transaction_results: RefCell<Vec<BlockchainResult<H256>>>,
+        pub get_batch_allowance_parameters: Arc<Mutex<Vec<Wallet>>>,
+        pub get_batch_allowance_results: RefCell<Vec<BlockchainResult<Option<u64>>>>,
+        pub estimate_gas_fees_results: RefCell<Vec<BlockchainResult<GasFees>>>,
         pub contract_address_results: RefCell<Vec<Address>>,
         pub get_transaction_count_parameters: Arc<Mutex<Vec<Wallet>>>,
         pub get_transaction_count_results: RefCell<Vec<BlockchainResult<U256>>>,
+        pub get_transaction_receipt_parameters: Arc<Mutex<Vec<H256>>>,
+        pub get_transaction_receipt_results: RefCell<Vec<BlockchainResult<TransactionStatus>>>,
+        pub get_block_hash_parameters: Arc<Mutex<Vec<u64>>>,
+        pub get_block_hash_results: RefCell<Vec<BlockchainResult<Option<H256>>>>,
     }
 
     impl BlockchainInterfaceMock {
-        fn retrieve_transactions_result(
-            self,
-            result: Result<Vec<Transaction>, BlockchainError>,
-        ) -> Self {
+        fn retrieve_transactions_result(self, result: Transactions) -> Self {
             self.retrieve_transactions_results.borrow_mut().push(result);
             self
         }
@@ -283,6 +537,23 @@ mod tests {
             self
         }
 
+        fn send_batch_transaction_result(self, result: BlockchainResult<H256>) -> Self {
+            self.send_batch_transaction_results
+                .borrow_mut()
+                .push(result);
+            self
+        }
+
+        fn get_batch_allowance_result(self, result: BlockchainResult<Option<u64>>) -> Self {
+            self.get_batch_allowance_results.borrow_mut().push(result);
+            self
+        }
+
+        fn estimate_gas_fees_result(self, result: BlockchainResult<GasFees>) -> Self {
+            self.estimate_gas_fees_results.borrow_mut().push(result);
+            self
+        }
+
         fn contract_address_result(self, address: Address) -> Self {
             self.contract_address_results.borrow_mut().push(address);
             self
@@ -292,6 +563,21 @@ mod tests {
             self.get_transaction_count_results.borrow_mut().push(result);
             self
         }
+
+        fn get_transaction_receipt_result(
+            self,
+            result: BlockchainResult<TransactionStatus>,
+        ) -> Self {
+            self.get_transaction_receipt_results
+                .borrow_mut()
+                .push(result);
+            self
+        }
+
+        fn get_block_hash_result(self, result: BlockchainResult<Option<H256>>) -> Self {
+            self.get_block_hash_results.borrow_mut().push(result);
+            self
+        }
     }
 
     impl BlockchainInterface for BlockchainInterfaceMock {
@@ -313,18 +599,44 @@ mod tests {
             recipient: &Wallet,
             amount: u64,
             nonce: U256,
-            gas_price: u64,
+            fees: GasFees,
         ) -> BlockchainResult<H256> {
             self.send_transaction_parameters.lock().unwrap().push((
                 consuming_wallet.clone(),
                 recipient.clone(),
                 amount,
                 nonce,
-                gas_price,
+                fees,
             ));
             self.send_transaction_results.borrow_mut().remove(0)
         }
 
+        fn send_batch_transaction(
+            &self,
+            consuming_wallet: &Wallet,
+            payments: &[(Wallet, u64)],
+            nonce: U256,
+            fees: GasFees,
+        ) -> BlockchainResult<H256> {
+            self.send_batch_transaction_parameters
+                .lock()
+                .unwrap()
+                .push((consuming_wallet.clone(), payments.to_vec(), nonce, fees));
+            self.send_batch_transaction_results.borrow_mut().remove(0)
+        }
+
+        fn get_batch_allowance(&self, wallet: &Wallet) -> BlockchainResult<Option<u64>> {
+            self.get_batch_allowance_parameters
+                .lock()
+                .unwrap()
+                .push(wallet.clone());
+            self.get_batch_allowance_results.borrow_mut().remove(0)
+        }
+
+        fn estimate_gas_fees(&self) -> BlockchainResult<GasFees> {
+            self.estimate_gas_fees_results.borrow_mut().remove(0)
+        }
+
         fn get_eth_balance(&self, _address: &Wallet) -> Balance {
             unimplemented!()
         }
@@ -340,17 +652,39 @@ mod tests {
                 .push(wallet.clone());
             self.get_transaction_count_results.borrow_mut().remove(0)
         }
+
+        fn get_transaction_receipt(&self, hash: H256) -> BlockchainResult<TransactionStatus> {
+            self.get_transaction_receipt_parameters
+                .lock()
+                .unwrap()
+                .push(hash);
+            self.get_transaction_receipt_results.borrow_mut().remove(0)
+        }
+
+        fn get_block_hash(&self, block_number: u64) -> BlockchainResult<Option<H256>> {
+            self.get_block_hash_parameters
+                .lock()
+                .unwrap()
+                .push(block_number);
+            self.get_block_hash_results.borrow_mut().remove(0)
+        }
     }
 
     #[test]
     fn ask_me_about_my_transactions() {
         let system = System::new("ask_me_about_my_transactions");
         let block_no = 37;
-        let expected_results = vec![Transaction {
-            block_number: 42u64,
-            from: make_wallet("some_address"),
-            gwei_amount: 21,
-        }];
+        let expected_results = RetrievedTransactions {
+            transactions: vec![Transaction {
+                block_number: 42u64,
+                block_hash: H256::from_uint(&U256::from(42)),
+                from: make_wallet("some_address"),
+                gwei_amount: 21,
+            }],
+            new_start_block: 43,
+            caught_up: true,
+            reorganized: false,
+        };
         let result = Ok(expected_results.clone());
         let wallet = make_wallet("smelly");
         let blockchain_interface_mock = BlockchainInterfaceMock::default()
@@ -369,6 +703,7 @@ mod tests {
         let request = addr.send(RetrieveTransactions {
             start_block: block_no,
             recipient: wallet.clone(),
+            credited_blocks: vec![],
         });
         System::current().stop();
         system.run();
@@ -380,12 +715,148 @@ mod tests {
         assert_eq!(expected_results, result);
     }
 
+    fn credited_block(block_number: u64) -> CreditedBlock {
+        CreditedBlock {
+            block_number,
+            block_hash: H256::from_uint(&U256::from(block_number)),
+        }
+    }
+
+    fn retrieve_transactions_after_checking(
+        blockchain_interface_mock: BlockchainInterfaceMock,
+        credited_blocks: Vec<CreditedBlock>,
+    ) -> (BlockchainResult<RetrievedTransactions>, Vec<u64>, usize) {
+        let get_block_hash_parameters = blockchain_interface_mock.get_block_hash_parameters.clone();
+        let retrieve_transactions_parameters = blockchain_interface_mock
+            .retrieve_transactions_parameters
+            .clone();
+        let subject = BlockchainBridge::new(
+            &bc_from_wallet(None),
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+
+        let result = subject.retrieve_transactions(&RetrieveTransactions {
+            start_block: 400,
+            recipient: make_wallet("earning"),
+            credited_blocks,
+        });
+
+        let get_block_hash_parameters = get_block_hash_parameters.lock().unwrap().clone();
+        let retrieve_count = retrieve_transactions_parameters.lock().unwrap().len();
+        (result, get_block_hash_parameters, retrieve_count)
+    }
+
+    #[test]
+    fn retrieve_transactions_checks_only_the_newest_credited_block_when_it_is_still_there() {
+        let retrieved = RetrievedTransactions {
+            transactions: vec![],
+            new_start_block: 500,
+            caught_up: true,
+            reorganized: false,
+        };
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .get_block_hash_result(Ok(Some(H256::from_uint(&U256::from(300)))))
+            .retrieve_transactions_result(Ok(retrieved.clone()));
+
+        let (result, get_block_hash_parameters, retrieve_count) =
+            retrieve_transactions_after_checking(
+                blockchain_interface_mock,
+                vec![credited_block(300), credited_block(200)],
+            );
+
+        assert_eq!(result, Ok(retrieved));
+        assert_eq!(get_block_hash_parameters, vec![300]);
+        assert_eq!(retrieve_count, 1);
+    }
+
+    #[test]
+    fn retrieve_transactions_reports_a_reorganization_after_the_newest_credited_block_still_there()
+    {
+        init_test_logging();
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .get_block_hash_result(Ok(Some(H256::from_uint(&U256::from(0xbad)))))
+            .get_block_hash_result(Ok(None))
+            .get_block_hash_result(Ok(Some(H256::from_uint(&U256::from(100)))));
+
+        let (result, get_block_hash_parameters, retrieve_count) =
+            retrieve_transactions_after_checking(
+                blockchain_interface_mock,
+                vec![
+                    credited_block(300),
+                    credited_block(200),
+                    credited_block(100),
+                ],
+            );
+
+        assert_eq!(
+            result,
+            Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: 101,
+                caught_up: false,
+                reorganized: true,
+            })
+        );
+        assert_eq!(get_block_hash_parameters, vec![300, 200, 100]);
+        assert_eq!(retrieve_count, 0);
+        TestLogHandler::new().exists_log_containing(
+            "WARN: BlockchainBridge: Blockchain reorganization dropped blocks that payments were credited from; scanning again from block 101",
+        );
+    }
+
+    #[test]
+    fn retrieve_transactions_scans_again_from_the_oldest_credited_block_when_none_is_still_there() {
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .get_block_hash_result(Ok(Some(H256::from_uint(&U256::from(0xbad)))))
+            .get_block_hash_result(Ok(Some(H256::from_uint(&U256::from(0xbad)))));
+
+        let (result, get_block_hash_parameters, retrieve_count) =
+            retrieve_transactions_after_checking(
+                blockchain_interface_mock,
+                vec![credited_block(300), credited_block(200)],
+            );
+
+        assert_eq!(
+            result,
+            Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: 200,
+                caught_up: false,
+                reorganized: true,
+            })
+        );
+        assert_eq!(get_block_hash_parameters, vec![300, 200]);
+        assert_eq!(retrieve_count, 0);
+    }
+
+    #[test]
+    fn retrieve_transactions_fails_without_retrieving_if_a_credited_block_cannot_be_checked() {
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .get_block_hash_result(Err(BlockchainError::QueryFailed));
+
+        let (result, get_block_hash_parameters, retrieve_count) =
+            retrieve_transactions_after_checking(
+                blockchain_interface_mock,
+                vec![credited_block(300)],
+            );
+
+        assert_eq!(result, Err(BlockchainError::QueryFailed));
+        assert_eq!(get_block_hash_parameters, vec![300]);
+        assert_eq!(retrieve_count, 0);
+    }
+
     #[test]
     fn report_accounts_payable_sends_transactions_to_blockchain_interface() {
         let system =
             System::new("report_accounts_payable_sends_transactions_to_blockchain_interface");
 
+        let expected_fees = GasFees::FeeMarket {
+            max_fee: 40,
+            priority_fee: 2,
+        };
         let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .estimate_gas_fees_result(Ok(expected_fees))
             .get_transaction_count_result(Ok(U256::from(1)))
             .get_transaction_count_result(Ok(U256::from(2)))
             .send_transaction_result(Ok(H256::from("sometransactionhash".keccak256())))
@@ -397,9 +868,7 @@ mod tests {
         let transaction_count_parameters = blockchain_interface_mock
             .get_transaction_count_parameters
             .clone();
-        let expected_gas_price = 5u64;
-        let persistent_configuration_mock =
-            PersistentConfigurationMock::default().gas_price_result(expected_gas_price);
+        let persistent_configuration_mock = PersistentConfigurationMock::default();
 
         let consuming_wallet = make_paying_wallet(b"somewallet");
         let subject = BlockchainBridge::new(
@@ -435,7 +904,7 @@ mod tests {
                 make_wallet("blah"),
                 42,
                 U256::from(1),
-                expected_gas_price
+                expected_fees
             )
         );
         assert_eq!(
@@ -445,7 +914,7 @@ mod tests {
                 make_wallet("foo"),
                 21,
                 U256::from(2),
-                expected_gas_price
+                expected_fees
             )
         );
 
@@ -454,6 +923,8 @@ mod tests {
             make_wallet("blah"),
             42,
             H256::from("sometransactionhash".keccak256()),
+            U256::from(1),
+            expected_fees,
         );
 
         if let Ok(zero) = result.clone().get(0).unwrap().clone() {
@@ -478,6 +949,8 @@ mod tests {
             make_wallet("foo"),
             21,
             H256::from("someothertransactionhash".keccak256()),
+            U256::from(2),
+            expected_fees,
         );
 
         if let Ok(one) = result.clone().get(1).unwrap().clone() {
@@ -515,6 +988,7 @@ mod tests {
         let system = System::new("report_accounts_payable_returns_error_for_blockchain_error");
 
         let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .estimate_gas_fees_result(Ok(GasFees::Legacy { gas_price: 3 }))
             .get_transaction_count_result(Ok(web3::types::U256::from(1)))
             .send_transaction_result(Err(BlockchainError::TransactionFailed(String::from(
                 "mock payment failure",
@@ -526,8 +1000,7 @@ mod tests {
 
         let consuming_wallet = make_wallet("somewallet");
 
-        let persistent_configuration_mock =
-            PersistentConfigurationMock::new().gas_price_result(3u64);
+        let persistent_configuration_mock = PersistentConfigurationMock::new();
         let subject = BlockchainBridge::new(
             &bc_from_wallet(Some(consuming_wallet.clone())),
             Box::new(blockchain_interface_mock),
@@ -560,6 +1033,330 @@ mod tests {
         assert_eq!(actual_wallet, consuming_wallet);
     }
 
+    #[test]
+    fn report_accounts_payable_assigns_sequential_nonces_without_gaps_when_the_transaction_count_lags(
+    ) {
+        let system = System::new(
+            "report_accounts_payable_assigns_sequential_nonces_without_gaps_when_the_transaction_count_lags",
+        );
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .estimate_gas_fees_result(Ok(GasFees::Legacy { gas_price: 5 }))
+            .get_transaction_count_result(Ok(U256::from(7)))
+            .get_transaction_count_result(Ok(U256::from(7)))
+            .get_transaction_count_result(Ok(U256::from(7)))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(1))))
+            .send_transaction_result(Err(BlockchainError::TransactionFailed(String::from(
+                "mock payment failure",
+            ))))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(3))));
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let persistent_configuration_mock = PersistentConfigurationMock::default();
+        let subject = BlockchainBridge::new(
+            &bc_from_wallet(Some(make_paying_wallet(b"somewallet"))),
+            Box::new(blockchain_interface_mock),
+            Box::new(persistent_configuration_mock),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(ReportAccountsPayable {
+            accounts: vec![
+                make_payable("first"),
+                make_payable("second"),
+                make_payable("third"),
+            ],
+        });
+        System::current().stop();
+        system.run();
+
+        let result = request.wait().unwrap().unwrap();
+        let nonces = send_parameters
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, _, _, nonce, _)| *nonce)
+            .collect::<Vec<U256>>();
+        assert_eq!(nonces, vec![U256::from(7), U256::from(8), U256::from(8)]);
+        assert_eq!(result[0].as_ref().unwrap().nonce, U256::from(7));
+        assert_eq!(
+            result[1],
+            Err(BlockchainError::TransactionFailed(String::from(
+                "mock payment failure"
+            )))
+        );
+        assert_eq!(result[2].as_ref().unwrap().nonce, U256::from(8));
+    }
+
+    fn make_payable(name: &str) -> PayableAccount {
+        PayableAccount {
+            wallet: make_wallet(name),
+            balance: 42,
+            last_paid_timestamp: SystemTime::now(),
+            pending_payment_transaction: None,
+        }
+    }
+
+    #[test]
+    fn report_accounts_payable_pays_at_the_configured_gas_price_when_fees_cannot_be_estimated() {
+        init_test_logging();
+        let system = System::new(
+            "report_accounts_payable_pays_at_the_configured_gas_price_when_fees_cannot_be_estimated",
+        );
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .estimate_gas_fees_result(Err(BlockchainError::QueryFailed))
+            .get_transaction_count_result(Ok(U256::from(1)))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(1))));
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let subject = BlockchainBridge::new(
+            &bc_from_wallet(Some(make_paying_wallet(b"somewallet"))),
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default().gas_price_result(5u64)),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(ReportAccountsPayable {
+            accounts: vec![make_payable("blah")],
+        });
+        System::current().stop();
+        system.run();
+
+        let result = request.wait().unwrap().unwrap();
+        assert_eq!(
+            result[0].as_ref().unwrap().fees,
+            GasFees::Legacy { gas_price: 5 }
+        );
+        assert_eq!(
+            send_parameters.lock().unwrap()[0].4,
+            GasFees::Legacy { gas_price: 5 }
+        );
+        TestLogHandler::new().exists_log_containing(
+            "WARN: BlockchainBridge: Couldn't estimate gas fees: Blockchain QueryFailed. Paying at the configured gas price of 5 Gwei",
+        );
+    }
+
+    #[test]
+    fn report_accounts_payable_pays_no_more_than_the_max_gas_price() {
+        init_test_logging();
+        let system = System::new("report_accounts_payable_pays_no_more_than_the_max_gas_price");
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .estimate_gas_fees_result(Ok(GasFees::FeeMarket {
+                max_fee: 80,
+                priority_fee: 2,
+            }))
+            .get_transaction_count_result(Ok(U256::from(1)))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(1))));
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let mut config = bc_from_wallet(Some(make_paying_wallet(b"somewallet")));
+        config.blockchain_bridge_config.max_gas_price = 50;
+        let subject = BlockchainBridge::new(
+            &config,
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(ReportAccountsPayable {
+            accounts: vec![make_payable("blah")],
+        });
+        System::current().stop();
+        system.run();
+
+        let expected_fees = GasFees::FeeMarket {
+            max_fee: 50,
+            priority_fee: 2,
+        };
+        let result = request.wait().unwrap().unwrap();
+        assert_eq!(result[0].as_ref().unwrap().fees, expected_fees);
+        assert_eq!(send_parameters.lock().unwrap()[0].4, expected_fees);
+        TestLogHandler::new().exists_log_containing(
+            "WARN: BlockchainBridge: Estimated max fee 80 Gwei and priority fee 2 Gwei is more than the maximum gas price of 50 Gwei; paying at max fee 50 Gwei and priority fee 2 Gwei instead",
+        );
+    }
+
+    #[test]
+    fn report_accounts_payable_pays_creditors_in_one_batch_transaction_when_the_batch_contract_may_spend_enough(
+    ) {
+        let system = System::new("report_accounts_payable_pays_creditors_in_one_batch_transaction_when_the_batch_contract_may_spend_enough");
+        let fees = GasFees::Legacy { gas_price: 3 };
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .estimate_gas_fees_result(Ok(fees))
+            .get_batch_allowance_result(Ok(Some(126)))
+            .get_transaction_count_result(Ok(U256::from(5)))
+            .send_batch_transaction_result(Ok(H256::from_uint(&U256::from(9))));
+        let allowance_parameters = blockchain_interface_mock
+            .get_batch_allowance_parameters
+            .clone();
+        let batch_parameters = blockchain_interface_mock
+            .send_batch_transaction_parameters
+            .clone();
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let consuming_wallet = make_paying_wallet(b"somewallet");
+        let mut config = bc_from_wallet(Some(consuming_wallet.clone()));
+        config.blockchain_bridge_config.batch_payments = true;
+        let subject = BlockchainBridge::new(
+            &config,
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(ReportAccountsPayable {
+            accounts: vec![
+                make_payable("blah"),
+                make_payable("foo"),
+                make_payable("bar"),
+            ],
+        });
+        System::current().stop();
+        system.run();
+
+        assert_eq!(
+            *allowance_parameters.lock().unwrap(),
+            vec![consuming_wallet.clone()]
+        );
+        assert_eq!(
+            *batch_parameters.lock().unwrap(),
+            vec![(
+                consuming_wallet,
+                vec![
+                    (make_wallet("blah"), 42),
+                    (make_wallet("foo"), 42),
+                    (make_wallet("bar"), 42)
+                ],
+                U256::from(5),
+                fees
+            )]
+        );
+        assert!(send_parameters.lock().unwrap().is_empty());
+        let result = request
+            .wait()
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|payment| {
+                let payment = payment.unwrap();
+                (payment.to, payment.transaction, payment.nonce)
+            })
+            .collect::<Vec<(Wallet, H256, U256)>>();
+        assert_eq!(
+            result,
+            vec![
+                (
+                    make_wallet("blah"),
+                    H256::from_uint(&U256::from(9)),
+                    U256::from(5)
+                ),
+                (
+                    make_wallet("foo"),
+                    H256::from_uint(&U256::from(9)),
+                    U256::from(5)
+                ),
+                (
+                    make_wallet("bar"),
+                    H256::from_uint(&U256::from(9)),
+                    U256::from(5)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_accounts_payable_pays_creditors_separately_when_the_batch_contract_may_not_spend_enough(
+    ) {
+        init_test_logging();
+        let system = System::new("report_accounts_payable_pays_creditors_separately_when_the_batch_contract_may_not_spend_enough");
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .estimate_gas_fees_result(Ok(GasFees::Legacy { gas_price: 3 }))
+            .get_batch_allowance_result(Ok(Some(83)))
+            .get_transaction_count_result(Ok(U256::from(5)))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(9))))
+            .get_transaction_count_result(Ok(U256::from(5)))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(10))));
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let batch_parameters = blockchain_interface_mock
+            .send_batch_transaction_parameters
+            .clone();
+        let mut config = bc_from_wallet(Some(make_paying_wallet(b"somewallet")));
+        config.blockchain_bridge_config.batch_payments = true;
+        let subject = BlockchainBridge::new(
+            &config,
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(ReportAccountsPayable {
+            accounts: vec![make_payable("blah"), make_payable("foo")],
+        });
+        System::current().stop();
+        system.run();
+
+        let result = request.wait().unwrap().unwrap();
+        assert_eq!(result.len(), 2);
+        assert!(batch_parameters.lock().unwrap().is_empty());
+        let send_parameters = send_parameters.lock().unwrap();
+        assert_eq!(
+            send_parameters
+                .iter()
+                .map(|(_, recipient, _, nonce, _)| (recipient.clone(), *nonce))
+                .collect::<Vec<(Wallet, U256)>>(),
+            vec![
+                (make_wallet("blah"), U256::from(5)),
+                (make_wallet("foo"), U256::from(6))
+            ]
+        );
+        TestLogHandler::new().exists_log_containing(
+            "WARN: BlockchainBridge: The batch contract may spend only 83 of the consuming wallet's tokens, less than the 84 due; paying each creditor separately",
+        );
+    }
+
+    #[test]
+    fn report_accounts_payable_pays_creditors_separately_when_the_chain_has_no_batch_contract() {
+        init_test_logging();
+        let system = System::new(
+            "report_accounts_payable_pays_creditors_separately_when_the_chain_has_no_batch_contract",
+        );
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .estimate_gas_fees_result(Ok(GasFees::Legacy { gas_price: 3 }))
+            .get_batch_allowance_result(Ok(None))
+            .get_transaction_count_result(Ok(U256::from(5)))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(9))))
+            .get_transaction_count_result(Ok(U256::from(6)))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(10))));
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let mut config = bc_from_wallet(Some(make_paying_wallet(b"somewallet")));
+        config.blockchain_bridge_config.batch_payments = true;
+        let subject = BlockchainBridge::new(
+            &config,
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(ReportAccountsPayable {
+            accounts: vec![make_payable("blah"), make_payable("foo")],
+        });
+        System::current().stop();
+        system.run();
+
+        assert_eq!(request.wait().unwrap().unwrap().len(), 2);
+        assert_eq!(send_parameters.lock().unwrap().len(), 2);
+        TestLogHandler::new().exists_log_containing(
+            "WARN: BlockchainBridge: This chain has no batch contract; paying each creditor separately",
+        );
+    }
+
     #[test]
     fn report_accounts_payable_returns_error_when_there_is_no_consuming_wallet_configured() {
         let system = System::new("report_accounts_payable_returns_error_for_blockchain_error");
@@ -591,6 +1388,366 @@ mod tests {
         assert_eq!(result, &Err("No consuming wallet specified".to_string()));
     }
 
+    #[test]
+    fn request_transaction_receipts_asks_about_each_payment() {
+        let system = System::new("request_transaction_receipts_asks_about_each_payment");
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .get_transaction_receipt_result(Ok(TransactionStatus::Succeeded))
+            .get_transaction_receipt_result(Err(BlockchainError::QueryFailed));
+        let get_transaction_receipt_parameters = blockchain_interface_mock
+            .get_transaction_receipt_parameters
+            .clone();
+        let subject = BlockchainBridge::new(
+            &bc_from_wallet(None),
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(RequestTransactionReceipts {
+            payments: vec![
+                Payment::new(
+                    make_wallet("blah"),
+                    42,
+                    H256::from_uint(&U256::from(1)),
+                    U256::from(1),
+                    GasFees::Legacy { gas_price: 5 },
+                ),
+                Payment::new(
+                    make_wallet("foo"),
+                    21,
+                    H256::from_uint(&U256::from(2)),
+                    U256::from(2),
+                    GasFees::Legacy { gas_price: 5 },
+                ),
+            ],
+        });
+        System::current().stop();
+        system.run();
+
+        let result = request.wait().unwrap();
+        assert_eq!(
+            result,
+            vec![
+                Ok(TransactionStatus::Succeeded),
+                Err(BlockchainError::QueryFailed)
+            ]
+        );
+        assert_eq!(
+            *get_transaction_receipt_parameters.lock().unwrap(),
+            vec![
+                H256::from_uint(&U256::from(1)),
+                H256::from_uint(&U256::from(2))
+            ]
+        );
+    }
+
+    #[test]
+    fn resubmit_payments_reuses_the_nonce_with_the_requested_gas_price() {
+        let system = System::new("resubmit_payments_reuses_the_nonce_with_the_requested_gas_price");
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(3))))
+            .send_transaction_result(Err(BlockchainError::TransactionFailed(
+                "nonce too low".to_string(),
+            )));
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let consuming_wallet = make_paying_wallet(b"somewallet");
+        let subject = BlockchainBridge::new(
+            &bc_from_wallet(Some(consuming_wallet.clone())),
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(ResubmitPayments {
+            payments: vec![
+                Payment::new(
+                    make_wallet("blah"),
+                    42,
+                    H256::from_uint(&U256::from(1)),
+                    U256::from(7),
+                    GasFees::Legacy { gas_price: 6 },
+                ),
+                Payment::new(
+                    make_wallet("foo"),
+                    21,
+                    H256::from_uint(&U256::from(2)),
+                    U256::from(8),
+                    GasFees::FeeMarket {
+                        max_fee: 12,
+                        priority_fee: 2,
+                    },
+                ),
+            ],
+        });
+        System::current().stop();
+        system.run();
+
+        assert_eq!(
+            *send_parameters.lock().unwrap(),
+            vec![
+                (
+                    consuming_wallet.clone(),
+                    make_wallet("blah"),
+                    42,
+                    U256::from(7),
+                    GasFees::Legacy { gas_price: 6 }
+                ),
+                (
+                    consuming_wallet,
+                    make_wallet("foo"),
+                    21,
+                    U256::from(8),
+                    GasFees::FeeMarket {
+                        max_fee: 12,
+                        priority_fee: 2,
+                    }
+                ),
+            ]
+        );
+        let mut result = request.wait().unwrap().unwrap();
+        assert_eq!(
+            result.remove(1),
+            Err(BlockchainError::TransactionFailed(
+                "nonce too low".to_string()
+            ))
+        );
+        let resubmitted = result.remove(0).unwrap();
+        assert_eq!(
+            resubmitted,
+            Payment {
+                timestamp: resubmitted.timestamp,
+                ..Payment::new(
+                    make_wallet("blah"),
+                    42,
+                    H256::from_uint(&U256::from(3)),
+                    U256::from(7),
+                    GasFees::Legacy { gas_price: 6 }
+                )
+            }
+        );
+    }
+
+    #[test]
+    fn resubmit_payments_pays_no_more_than_the_max_gas_price() {
+        let system = System::new("resubmit_payments_pays_no_more_than_the_max_gas_price");
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(3))));
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let mut config = bc_from_wallet(Some(make_paying_wallet(b"somewallet")));
+        config.blockchain_bridge_config.max_gas_price = 50;
+        let subject = BlockchainBridge::new(
+            &config,
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(ResubmitPayments {
+            payments: vec![Payment::new(
+                make_wallet("blah"),
+                42,
+                H256::from_uint(&U256::from(1)),
+                U256::from(7),
+                GasFees::Legacy { gas_price: 60 },
+            )],
+        });
+        System::current().stop();
+        system.run();
+
+        assert_eq!(
+            send_parameters.lock().unwrap()[0].4,
+            GasFees::Legacy { gas_price: 50 }
+        );
+        let result = request.wait().unwrap().unwrap();
+        assert_eq!(
+            result[0].as_ref().unwrap().fees,
+            GasFees::Legacy { gas_price: 50 }
+        );
+    }
+
+    #[test]
+    fn resubmit_payments_resubmits_the_payments_in_a_batch_together() {
+        let system = System::new("resubmit_payments_resubmits_the_payments_in_a_batch_together");
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .send_batch_transaction_result(Ok(H256::from_uint(&U256::from(3))))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(4))));
+        let batch_parameters = blockchain_interface_mock
+            .send_batch_transaction_parameters
+            .clone();
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let consuming_wallet = make_paying_wallet(b"somewallet");
+        let subject = BlockchainBridge::new(
+            &bc_from_wallet(Some(consuming_wallet.clone())),
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+        let fees = GasFees::Legacy { gas_price: 6 };
+
+        let request = addr.send(ResubmitPayments {
+            payments: vec![
+                Payment::new(
+                    make_wallet("bar"),
+                    42,
+                    H256::from_uint(&U256::from(1)),
+                    U256::from(7),
+                    fees,
+                ),
+                Payment::new(
+                    make_wallet("blah"),
+                    10,
+                    H256::from_uint(&U256::from(2)),
+                    U256::from(8),
+                    fees,
+                ),
+                Payment::new(
+                    make_wallet("foo"),
+                    21,
+                    H256::from_uint(&U256::from(1)),
+                    U256::from(7),
+                    fees,
+                ),
+            ],
+        });
+        System::current().stop();
+        system.run();
+
+        assert_eq!(
+            *batch_parameters.lock().unwrap(),
+            vec![(
+                consuming_wallet.clone(),
+                vec![(make_wallet("bar"), 42), (make_wallet("foo"), 21)],
+                U256::from(7),
+                fees
+            )]
+        );
+        assert_eq!(
+            *send_parameters.lock().unwrap(),
+            vec![(
+                consuming_wallet,
+                make_wallet("blah"),
+                10,
+                U256::from(8),
+                fees
+            )]
+        );
+        let result = request
+            .wait()
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|payment| {
+                let payment = payment.unwrap();
+                (payment.to, payment.transaction, payment.nonce)
+            })
+            .collect::<Vec<(Wallet, H256, U256)>>();
+        assert_eq!(
+            result,
+            vec![
+                (
+                    make_wallet("bar"),
+                    H256::from_uint(&U256::from(3)),
+                    U256::from(7)
+                ),
+                (
+                    make_wallet("foo"),
+                    H256::from_uint(&U256::from(3)),
+                    U256::from(7)
+                ),
+                (
+                    make_wallet("blah"),
+                    H256::from_uint(&U256::from(4)),
+                    U256::from(8)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn payments_after_a_resubmission_get_nonces_beyond_the_resubmitted_one() {
+        let system =
+            System::new("payments_after_a_resubmission_get_nonces_beyond_the_resubmitted_one");
+        let blockchain_interface_mock = BlockchainInterfaceMock::default()
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(3))))
+            .estimate_gas_fees_result(Ok(GasFees::Legacy { gas_price: 5 }))
+            .get_transaction_count_result(Ok(U256::from(3)))
+            .send_transaction_result(Ok(H256::from_uint(&U256::from(4))));
+        let send_parameters = blockchain_interface_mock
+            .send_transaction_parameters
+            .clone();
+        let subject = BlockchainBridge::new(
+            &bc_from_wallet(Some(make_paying_wallet(b"somewallet"))),
+            Box::new(blockchain_interface_mock),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        addr.try_send(ResubmitPayments {
+            payments: vec![Payment::new(
+                make_wallet("blah"),
+                42,
+                H256::from_uint(&U256::from(1)),
+                U256::from(9),
+                GasFees::Legacy { gas_price: 6 },
+            )],
+        })
+        .unwrap();
+        addr.try_send(ReportAccountsPayable {
+            accounts: vec![PayableAccount {
+                wallet: make_wallet("foo"),
+                balance: 21,
+                last_paid_timestamp: SystemTime::now(),
+                pending_payment_transaction: None,
+            }],
+        })
+        .unwrap();
+        System::current().stop();
+        system.run();
+
+        let nonces = send_parameters
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, _, _, nonce, _)| *nonce)
+            .collect::<Vec<U256>>();
+        assert_eq!(nonces, vec![U256::from(9), U256::from(10)]);
+    }
+
+    #[test]
+    fn resubmit_payments_returns_error_when_there_is_no_consuming_wallet_configured() {
+        let system = System::new(
+            "resubmit_payments_returns_error_when_there_is_no_consuming_wallet_configured",
+        );
+        let subject = BlockchainBridge::new(
+            &BootstrapperConfig::new(),
+            Box::new(BlockchainInterfaceMock::default()),
+            Box::new(PersistentConfigurationMock::default()),
+        );
+        let addr: Addr<BlockchainBridge> = subject.start();
+
+        let request = addr.send(ResubmitPayments {
+            payments: vec![Payment::new(
+                make_wallet("blah"),
+                42,
+                H256::from_uint(&U256::from(1)),
+                U256::from(7),
+                GasFees::Legacy { gas_price: 6 },
+            )],
+        });
+        System::current().stop();
+        system.run();
+
+        let result = request.wait().unwrap();
+        assert_eq!(result, Err("No consuming wallet specified".to_string()));
+    }
+
     #[test]
     fn cant_be_crashed_if_key_doesnt_match() {
         let system = System::new("test");
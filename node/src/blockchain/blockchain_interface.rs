@@ -1,110 +1,80 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 
-use crate::blockchain::raw_transaction::RawTransaction;
+use crate::blockchain::raw_transaction::{FeeMarketTransaction, RawTransaction};
+use crate::metrics::{metrics, BLOCKCHAIN_RPC_DURATION, BLOCKCHAIN_RPC_ERRORS};
 use crate::sub_lib::logger::Logger;
 use crate::sub_lib::wallet::Wallet;
 use actix::Message;
-use futures::{future, Future};
-use masq_lib::constants::DEFAULT_CHAIN_NAME;
+use ethsign_crypto::Keccak256;
+use futures::Future;
+use masq_lib::blockchains::{
+    blockchain_from_chain_id, blockchain_from_name, BlockchainRecord, LOWEST_USABLE_TOKEN_DECIMALS,
+};
+use masq_lib::constants::{
+    DEFAULT_CHAIN_NAME, DEFAULT_CONFIRMATION_DEPTH, DEFAULT_SCAN_BLOCK_RANGE,
+};
+use serde_derive::Deserialize;
+use serde_json::json;
+use std::cell::Cell;
 use std::convert::{From, TryFrom, TryInto};
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
+use std::time::Instant;
 use web3::contract::{Contract, Options};
 use web3::transports::EventLoopHandle;
-use web3::types::{Address, BlockNumber, Bytes, FilterBuilder, Log, H256, U256};
-use web3::{Transport, Web3};
-
-// SHRD (Ropsten)
-pub const ROPSTEN_TESTNET_CONTRACT_ADDRESS: Address = Address {
-    0: [
-        0x38, 0x4d, 0xec, 0x25, 0xe0, 0x3f, 0x94, 0x93, 0x17, 0x67, 0xce, 0x4c, 0x35, 0x56, 0x16,
-        0x84, 0x68, 0xba, 0x24, 0xc3,
-    ],
-};
-
-// SHRD (Rinkeby)
-pub const RINKEBY_TESTNET_CONTRACT_ADDRESS: Address = Address {
-    0: [
-        0x02, 0xba, 0x9b, 0x52, 0x84, 0x25, 0xf9, 0xde, 0x08, 0xf9, 0x61, 0xb8, 0x8a, 0x10, 0xb0,
-        0x3b, 0xe8, 0xb8, 0xb9, 0x98,
-    ],
-};
-
-pub const MULTINODE_TESTNET_CONTRACT_ADDRESS: Address = Address {
-    0: [
-        0x59, 0x88, 0x2e, 0x4a, 0x8f, 0x5d, 0x24, 0x64, 0x3d, 0x4d, 0xda, 0x42, 0x29, 0x22, 0xa8,
-        0x70, 0xf1, 0xb3, 0xe6, 0x64,
-    ],
-};
-
-pub const MAINNET_CONTRACT_ADDRESS: Address = Address {
-    0: [
-        0x02, 0xba, 0x9b, 0x52, 0x84, 0x25, 0xf9, 0xde, 0x08, 0xf9, 0x61, 0xb8, 0x8a, 0x10, 0xb0,
-        0x3b, 0xe8, 0xb8, 0xb9, 0x98,
-    ],
+use web3::types::{
+    Address, BlockId, BlockNumber, Bytes, FilterBuilder, Log, TransactionId, H256, U256,
 };
+use web3::{Transport, Web3};
 
-const CONTRACTS: [Address; 5] = [
-    Address { 0: [0u8; 20] },
-    MAINNET_CONTRACT_ADDRESS,
-    MULTINODE_TESTNET_CONTRACT_ADDRESS,
-    ROPSTEN_TESTNET_CONTRACT_ADDRESS,
-    RINKEBY_TESTNET_CONTRACT_ADDRESS,
-];
+// Chain definitions come from masq_lib::blockchains; a chain ID it doesn't know is a bug upstream
+fn blockchain(chain_id: u64) -> &'static BlockchainRecord {
+    blockchain_from_chain_id(chain_id)
+        .unwrap_or_else(|| panic!("No blockchain is defined with chain ID {}", chain_id))
+}
 
-pub const MAINNET_CONTRACT_CREATION_BLOCK: u64 = 9_415_932;
-pub const ROPSTEN_TESTNET_CONTRACT_CREATION_BLOCK: u64 = 8_688_171;
-pub const RINKEBY_TESTNET_CONTRACT_CREATION_BLOCK: u64 = 5_893_771;
+pub fn contract_address(chain_id: u64) -> Address {
+    Address {
+        0: blockchain(chain_id).contract_bytes(),
+    }
+}
 
-pub const CONTRACT_CREATION_BLOCK: [u64; 5] = [
-    0,
-    MAINNET_CONTRACT_CREATION_BLOCK,
-    0,
-    ROPSTEN_TESTNET_CONTRACT_CREATION_BLOCK,
-    RINKEBY_TESTNET_CONTRACT_CREATION_BLOCK,
-];
+pub fn batch_contract_address(chain_id: u64) -> Option<Address> {
+    blockchain(chain_id)
+        .batch_contract_bytes()
+        .map(|bytes| Address { 0: bytes })
+}
 
-pub const CHAIN_NAMES: [&str; 5] = ["", "mainnet", "dev", "ropsten", "rinkeby"];
+fn batch_method_id(chain_id: u64) -> [u8; 4] {
+    let mut method_id = [0u8; 4];
+    method_id.copy_from_slice(&blockchain(chain_id).batch_method.as_bytes().keccak256()[0..4]);
+    method_id
+}
 
-pub fn contract_address(chain_id: u8) -> Address {
-    match chain_id {
-        1u8 | 2u8 | 3u8 | 4u8 => CONTRACTS[usize::from(chain_id)], // IDEA/CLion is wrong: This is copy
-        _ => CONTRACTS[0], // IDEA/CLion is wrong: This is copy
-    }
+pub fn chain_name(chain_id: u64) -> &'static str {
+    chain_name_from_id(chain_id)
 }
 
-pub fn chain_name(chain_id: u8) -> &'static str {
-    match chain_id {
-        1u8 | 2u8 | 3u8 | 4u8 => CHAIN_NAMES[usize::from(chain_id)],
-        _ => CHAIN_NAMES[3],
+pub fn chain_id_from_name(name: &str) -> u64 {
+    match blockchain_from_name(name) {
+        Some(blockchain) => blockchain.chain_id,
+        None => panic!("No blockchain is defined with the name '{}'", name),
     }
 }
 
-pub fn chain_id_from_name(name: &str) -> u8 {
-    match name.to_lowercase().as_str() {
-        "mainnet" => 1u8,
-        "dev" => 2u8,
-        "ropsten" => 3u8,
-        "rinkeby" => 4u8,
-        _ => 3u8,
-    }
+pub fn chain_name_from_id(chain_id: u64) -> &'static str {
+    blockchain(chain_id).name.as_str()
 }
 
-pub fn chain_name_from_id(chain_id: u8) -> &'static str {
-    match chain_id {
-        1u8 | 2u8 | 3u8 | 4u8 => CHAIN_NAMES[usize::from(chain_id)],
-        _ => CHAIN_NAMES[3],
-    }
+pub fn contract_creation_block_from_chain_id(chain_id: u64) -> u64 {
+    blockchain(chain_id).contract_creation_block
 }
 
-pub fn contract_creation_block_from_chain_id(chain_id: u8) -> u64 {
-    match chain_id {
-        1u8 | 2u8 | 3u8 | 4u8 => CONTRACT_CREATION_BLOCK[usize::from(chain_id)],
-        _ => CONTRACT_CREATION_BLOCK[3],
-    }
+pub fn token_decimals_from_chain_id(chain_id: u64) -> u8 {
+    blockchain(chain_id).token_decimals
 }
 
-pub const CONTRACT_ABI: &str = r#"[{"constant":true,"inputs":[{"name":"owner","type":"address"}],"name":"balanceOf","outputs":[{"name":"","type":"uint256"}],"payable":false,"stateMutability":"view","type":"function"},{"constant":false,"inputs":[{"name":"to","type":"address"},{"name":"value","type":"uint256"}],"name":"transfer","outputs":[{"name":"","type":"bool"}],"payable":false,"stateMutability":"nonpayable","type":"function"}]"#;
+pub const CONTRACT_ABI: &str = r#"[{"constant":true,"inputs":[{"name":"owner","type":"address"}],"name":"balanceOf","outputs":[{"name":"","type":"uint256"}],"payable":false,"stateMutability":"view","type":"function"},{"constant":true,"inputs":[{"name":"owner","type":"address"},{"name":"spender","type":"address"}],"name":"allowance","outputs":[{"name":"","type":"uint256"}],"payable":false,"stateMutability":"view","type":"function"},{"constant":false,"inputs":[{"name":"to","type":"address"},{"name":"value","type":"uint256"}],"name":"transfer","outputs":[{"name":"","type":"bool"}],"payable":false,"stateMutability":"nonpayable","type":"function"}]"#;
 
 const TRANSACTION_LITERAL: H256 = H256 {
     0: [
@@ -116,9 +86,30 @@ const TRANSACTION_LITERAL: H256 = H256 {
 
 const TRANSFER_METHOD_ID: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
 
+// Covers a token transfer, and the base cost of the transaction when it's the only one in it
+const GAS_PER_PAYMENT: u64 = 55_000;
+
+const FEE_HISTORY_BLOCK_COUNT: u64 = 10;
+const FEE_HISTORY_REWARD_PERCENTILE: u64 = 50;
+const MIN_PRIORITY_FEE: u64 = 1;
+
+const BLOCK_RANGE_TOO_LARGE_HINTS: &[&str] = &[
+    "block range",
+    "range limit",
+    "range too",
+    "range is too",
+    "returned more than",
+    "too many results",
+    "response size exceeded",
+];
+
+// After this many full-size chunks in a row go through, a range that was shrunk is doubled again
+const CHUNKS_BEFORE_SCAN_BLOCK_RANGE_GROWS: u64 = 10;
+
 #[derive(Clone, Debug, Eq, Message, PartialEq)]
 pub struct Transaction {
     pub block_number: u64,
+    pub block_hash: H256,
     pub from: Wallet,
     pub gwei_amount: u64,
 }
@@ -133,6 +124,23 @@ impl fmt::Display for Transaction {
     }
 }
 
+// A block that a credited payment came from, as it looked when the payment was credited
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CreditedBlock {
+    pub block_number: u64,
+    pub block_hash: H256,
+}
+
+// What one scan of a chunk of blocks found, and where the next scan should start
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetrievedTransactions {
+    pub transactions: Vec<Transaction>,
+    pub new_start_block: u64,
+    pub caught_up: bool,
+    // Payments credited from new_start_block on came from blocks that are no longer on the chain
+    pub reorganized: bool,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum BlockchainError {
     InvalidUrl,
@@ -148,10 +156,68 @@ impl Display for BlockchainError {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+// What a transaction offers to pay per unit of gas, in Gwei. Chains from before EIP-1559 take a
+// single gas price; the others take a ceiling on the whole fee, and a tip for the miner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GasFees {
+    Legacy { gas_price: u64 },
+    FeeMarket { max_fee: u64, priority_fee: u64 },
+}
+
+impl GasFees {
+    pub fn capped_at(self, ceiling: u64) -> Self {
+        match self {
+            GasFees::Legacy { gas_price } => GasFees::Legacy {
+                gas_price: gas_price.min(ceiling),
+            },
+            GasFees::FeeMarket {
+                max_fee,
+                priority_fee,
+            } => {
+                let max_fee = max_fee.min(ceiling);
+                GasFees::FeeMarket {
+                    max_fee,
+                    priority_fee: priority_fee.min(max_fee),
+                }
+            }
+        }
+    }
+}
+
+impl Display for GasFees {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GasFees::Legacy { gas_price } => write!(f, "gas price {} Gwei", gas_price),
+            GasFees::FeeMarket {
+                max_fee,
+                priority_fee,
+            } => write!(
+                f,
+                "max fee {} Gwei and priority fee {} Gwei",
+                max_fee, priority_fee
+            ),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeeHistory {
+    base_fee_per_gas: Option<Vec<U256>>,
+    reward: Option<Vec<Vec<U256>>>,
+}
+
 pub type BlockchainResult<T> = Result<T, BlockchainError>;
 pub type Balance = BlockchainResult<web3::types::U256>;
 pub type Nonce = BlockchainResult<web3::types::U256>;
-pub type Transactions = BlockchainResult<Vec<Transaction>>;
+pub type Transactions = BlockchainResult<RetrievedTransactions>;
 
 pub trait BlockchainInterface {
     fn contract_address(&self) -> Address;
@@ -164,9 +230,27 @@ pub trait BlockchainInterface {
         recipient: &Wallet,
         amount: u64,
         nonce: U256,
-        gas_price: u64,
+        fees: GasFees,
+    ) -> BlockchainResult<H256>;
+
+    // Pays every recipient in one transaction through the chain's batch contract, which has to be
+    // allowed to spend enough of the consuming wallet's tokens first
+    fn send_batch_transaction(
+        &self,
+        consuming_wallet: &Wallet,
+        payments: &[(Wallet, u64)],
+        nonce: U256,
+        fees: GasFees,
     ) -> BlockchainResult<H256>;
 
+    // How much of the wallet's tokens the chain's batch contract may spend, or None if the chain
+    // has no batch contract
+    fn get_batch_allowance(&self, wallet: &Wallet) -> BlockchainResult<Option<u64>>;
+
+    // What to offer for gas to get a transaction mined promptly: from the recent fee history on
+    // chains with a fee market, from the blockchain service's gas price on chains without one
+    fn estimate_gas_fees(&self) -> BlockchainResult<GasFees>;
+
     fn get_eth_balance(&self, address: &Wallet) -> Balance;
 
     fn get_token_balance(&self, address: &Wallet) -> Balance;
@@ -179,16 +263,21 @@ pub trait BlockchainInterface {
     }
 
     fn get_transaction_count(&self, address: &Wallet) -> Nonce;
+
+    fn get_transaction_receipt(&self, hash: H256) -> BlockchainResult<TransactionStatus>;
+
+    // The hash of the block with this number on the canonical chain, if the chain is that long yet
+    fn get_block_hash(&self, block_number: u64) -> BlockchainResult<Option<H256>>;
 }
 
 // TODO: This probably should go away
 pub struct BlockchainInterfaceClandestine {
     logger: Logger,
-    chain_id: u8,
+    chain_id: u64,
 }
 
 impl BlockchainInterfaceClandestine {
-    pub fn new(chain_id: u8) -> Self {
+    pub fn new(chain_id: u64) -> Self {
         BlockchainInterfaceClandestine {
             logger: Logger::new("BlockchainInterface"),
             chain_id,
@@ -219,13 +308,37 @@ impl BlockchainInterface for BlockchainInterfaceClandestine {
         _recipient: &Wallet,
         _amount: u64,
         _nonce: U256,
-        _gas_price: u64,
+        _fees: GasFees,
     ) -> BlockchainResult<H256> {
         let msg = "Can't send transactions clandestinely yet".to_string();
         error!(self.logger, "{}", &msg);
         Err(BlockchainError::TransactionFailed(msg))
     }
 
+    fn send_batch_transaction(
+        &self,
+        _consuming_wallet: &Wallet,
+        _payments: &[(Wallet, u64)],
+        _nonce: U256,
+        _fees: GasFees,
+    ) -> BlockchainResult<H256> {
+        let msg = "Can't send transactions clandestinely yet".to_string();
+        error!(self.logger, "{}", &msg);
+        Err(BlockchainError::TransactionFailed(msg))
+    }
+
+    fn get_batch_allowance(&self, _wallet: &Wallet) -> BlockchainResult<Option<u64>> {
+        let msg = "Can't get batch allowance clandestinely yet".to_string();
+        error!(self.logger, "{}", &msg);
+        Err(BlockchainError::QueryFailed)
+    }
+
+    fn estimate_gas_fees(&self) -> BlockchainResult<GasFees> {
+        let msg = "Can't estimate gas fees clandestinely yet".to_string();
+        error!(self.logger, "{}", &msg);
+        Err(BlockchainError::QueryFailed)
+    }
+
     fn get_eth_balance(&self, _address: &Wallet) -> Balance {
         error!(self.logger, "Can't get eth balance clandestinely yet",);
         Ok(0.into())
@@ -239,11 +352,30 @@ impl BlockchainInterface for BlockchainInterfaceClandestine {
     fn get_transaction_count(&self, _address: &Wallet) -> Nonce {
         unimplemented!()
     }
+
+    fn get_transaction_receipt(&self, _hash: H256) -> BlockchainResult<TransactionStatus> {
+        let msg = "Can't get transaction receipts clandestinely yet".to_string();
+        error!(self.logger, "{}", &msg);
+        Err(BlockchainError::QueryFailed)
+    }
+
+    fn get_block_hash(&self, _block_number: u64) -> BlockchainResult<Option<H256>> {
+        let msg = "Can't get block hashes clandestinely yet".to_string();
+        error!(self.logger, "{}", &msg);
+        Err(BlockchainError::QueryFailed)
+    }
 }
 
 pub struct BlockchainInterfaceNonClandestine<T: Transport + Debug> {
     logger: Logger,
-    chain_id: u8,
+    chain_id: u64,
+    token_decimals: u8,
+    batch_contract_opt: Option<Address>,
+    batch_method_id: [u8; 4],
+    scan_block_range: Cell<u64>,
+    max_scan_block_range: u64,
+    full_chunks_scanned: Cell<u64>,
+    confirmation_depth: u64,
     // This must not be dropped for Web3 requests to be completed
     _event_loop_handle: EventLoopHandle,
     web3: Web3<T>,
@@ -256,11 +388,32 @@ pub fn to_gwei(wei: U256) -> Option<u64> {
     u64::try_from(wei / GWEI).ok()
 }
 
+// A transaction that offers a fraction of a Gwei less than the going rate gets left behind
+fn to_gwei_rounded_up(wei: U256) -> u64 {
+    to_gwei(wei.saturating_add(GWEI - U256::one())).unwrap_or(u64::MAX)
+}
+
 pub fn to_wei(gwub: u64) -> U256 {
     let subgwei = U256::from(gwub);
     subgwei.full_mul(GWEI).try_into().expect("Internal Error")
 }
 
+// The Node counts tokens in units of 10^-9 token; the contract counts them in units of 10^-decimals
+fn token_unit(token_decimals: u8) -> U256 {
+    U256::exp10(usize::from(token_decimals - LOWEST_USABLE_TOKEN_DECIMALS))
+}
+
+pub fn to_token_units(gwub: u64, token_decimals: u8) -> U256 {
+    U256::from(gwub)
+        .full_mul(token_unit(token_decimals))
+        .try_into()
+        .expect("Internal Error")
+}
+
+pub fn from_token_units(amount: U256, token_decimals: u8) -> Option<u64> {
+    u64::try_from(amount / token_unit(token_decimals)).ok()
+}
+
 impl<T> BlockchainInterface for BlockchainInterfaceNonClandestine<T>
 where
     T: Transport + Debug,
@@ -278,54 +431,78 @@ where
             self.chain_id,
             self.contract_address()
         );
-        let filter = FilterBuilder::default()
-            .address(vec![self.contract_address()])
-            .from_block(BlockNumber::Number(start_block))
-            .to_block(BlockNumber::Latest)
-            .topics(
-                Some(vec![TRANSACTION_LITERAL]),
-                None,
-                Some(vec![recipient.address().into()]),
-                None,
-            )
-            .build();
-
-        let log_request = self.web3.eth().logs(filter);
-        let logger = self.logger.clone();
-        log_request
-            .then(|logs| {
-                future::result::<Vec<Transaction>, BlockchainError>(match logs {
-                    Ok(logs) => {
-                        if logs
-                            .iter()
-                            .any(|log| log.topics.len() < 2 || log.data.0.len() > 32)
-                        {
-                            Err(BlockchainError::InvalidResponse)
-                        } else {
-                            let transactions = logs
-                                .iter()
-                                .filter_map(|log: &Log| match log.block_number {
-                                    Some(block_number) => {
-                                        let amount: U256 = U256::from(log.data.0.as_slice());
-                                        let gwei_amount = to_gwei(amount);
-                                        gwei_amount.map(|gwei_amount| Transaction {
-                                            block_number: u64::try_from(block_number)
-                                                .expect("Internal Error"), // TODO: back to testing for overflow
-                                            from: Wallet::from(log.topics[1]),
-                                            gwei_amount,
-                                        })
-                                    }
-                                    None => None,
-                                })
-                                .collect();
-                            debug!(logger, "Retrieved transactions: {:?}", transactions);
-                            Ok(transactions)
-                        }
+        let recipient_topic: H256 = recipient.address().into();
+        // Payments in the newest blocks aren't credited until enough blocks are mined on top of them
+        let latest_block = timed_rpc("eth_blockNumber", || {
+            self.web3
+                .eth()
+                .block_number()
+                .map_err(|_| BlockchainError::QueryFailed)
+                .wait()
+        })?
+        .low_u64()
+        .saturating_sub(self.confirmation_depth);
+        if start_block > latest_block {
+            return Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: start_block,
+                caught_up: true,
+                reorganized: false,
+            });
+        }
+        loop {
+            let block_count = self
+                .scan_block_range
+                .get()
+                .min(latest_block - start_block + 1);
+            let end_block = start_block + block_count - 1;
+            let filter = FilterBuilder::default()
+                .address(vec![self.contract_address()])
+                .from_block(BlockNumber::Number(start_block))
+                .to_block(BlockNumber::Number(end_block))
+                .topics(
+                    Some(vec![TRANSACTION_LITERAL]),
+                    None,
+                    Some(vec![recipient_topic]),
+                    None,
+                )
+                .build();
+            match timed_rpc("eth_getLogs", || self.web3.eth().logs(filter).wait()) {
+                Ok(logs) => {
+                    let logs = self.with_batch_payers(logs)?;
+                    if block_count == self.scan_block_range.get() {
+                        self.count_full_chunk();
                     }
-                    Err(_) => Err(BlockchainError::QueryFailed),
-                })
-            })
-            .wait()
+                    let transactions = transactions_from_logs(&logs, self.token_decimals)?;
+                    debug!(
+                        self.logger,
+                        "Retrieved transactions from blocks {} to {}: {:?}",
+                        start_block,
+                        end_block,
+                        transactions
+                    );
+                    return Ok(RetrievedTransactions {
+                        transactions,
+                        new_start_block: end_block + 1,
+                        caught_up: end_block == latest_block,
+                        reorganized: false,
+                    });
+                }
+                Err(ref e) if block_count > 1 && is_block_range_too_large(e) => {
+                    let smaller_count = block_count / 2;
+                    warning!(
+                        self.logger,
+                        "Blockchain service wouldn't search {} blocks at once for payments ({}); searching {} at a time from now on",
+                        block_count,
+                        e,
+                        smaller_count
+                    );
+                    self.scan_block_range.set(smaller_count);
+                    self.full_chunks_scanned.set(0);
+                }
+                Err(_) => return Err(BlockchainError::QueryFailed),
+            }
+        }
     }
 
     fn send_transaction(
@@ -334,84 +511,232 @@ where
         recipient: &Wallet,
         amount: u64,
         nonce: U256,
-        gas_price: u64,
+        fees: GasFees,
     ) -> BlockchainResult<H256> {
         debug!(
             self.logger,
-            "Sending transaction for {} Gwei to {} from {}: (chain_id: {} contract: {:#x})",
+            "Sending transaction for {} Gwei to {} from {} at {}: (chain_id: {} contract: {:#x})",
             amount,
             recipient,
             consuming_wallet,
+            fees,
             self.chain_id,
             self.contract_address()
         );
         let mut data = [0u8; 4 + 32 + 32];
         data[0..4].copy_from_slice(&TRANSFER_METHOD_ID);
         data[16..36].copy_from_slice(&recipient.address().0[..]);
-        to_wei(amount).to_big_endian(&mut data[36..68]);
-        let gas_limit = ethereum_types::U256::try_from(
-            data.iter()
-                .fold(55_000u64, |acc, v| acc + if v == &0u8 { 4 } else { 68 }),
+        to_token_units(amount, self.token_decimals).to_big_endian(&mut data[36..68]);
+        self.sign_and_send(
+            consuming_wallet,
+            self.contract_address(),
+            &data,
+            1,
+            nonce,
+            fees,
         )
-        .expect("Internal error");
+    }
 
-        let converted_nonce = serde_json::from_value::<ethereum_types::U256>(
-            serde_json::to_value(nonce).expect("Internal error"),
-        )
-        .expect("Internal error");
-        let gas_price = serde_json::from_value::<ethereum_types::U256>(
-            serde_json::to_value(to_wei(gas_price)).expect("Internal error"),
+    fn send_batch_transaction(
+        &self,
+        consuming_wallet: &Wallet,
+        payments: &[(Wallet, u64)],
+        nonce: U256,
+        fees: GasFees,
+    ) -> BlockchainResult<H256> {
+        let batch_contract = match self.batch_contract_opt {
+            Some(batch_contract) => batch_contract,
+            None => {
+                return Err(BlockchainError::TransactionFailed(format!(
+                    "Blockchain '{}' has no batch contract",
+                    chain_name(self.chain_id)
+                )))
+            }
+        };
+        debug!(
+            self.logger,
+            "Sending batch transaction for {} payments totaling {} Gwei from {} at {}: (chain_id: {} batch contract: {:#x})",
+            payments.len(),
+            payments.iter().map(|(_, amount)| amount).sum::<u64>(),
+            consuming_wallet,
+            fees,
+            self.chain_id,
+            batch_contract
+        );
+        let data = self.batch_call_data(payments);
+        self.sign_and_send(
+            consuming_wallet,
+            batch_contract,
+            &data,
+            payments.len() as u64,
+            nonce,
+            fees,
         )
-        .expect("Internal error");
+    }
 
-        let tx = RawTransaction {
-            nonce: converted_nonce,
-            to: Some(ethereum_types::Address {
-                0: self.contract_address().0,
-            }),
-            value: ethereum_types::U256::zero(),
-            gas_price,
-            gas_limit,
-            data: data.to_vec(),
+    fn get_batch_allowance(&self, wallet: &Wallet) -> BlockchainResult<Option<u64>> {
+        let batch_contract = match self.batch_contract_opt {
+            Some(batch_contract) => batch_contract,
+            None => return Ok(None),
         };
-        match self
-            .web3
-            .eth()
-            .send_raw_transaction(Bytes(tx.sign(&consuming_wallet, self.chain_id)))
-            .wait()
-        {
-            Ok(result) => Ok(result),
-            Err(e) => Err(BlockchainError::TransactionFailed(format!("{:?}", e))),
+        timed_rpc("eth_call", || {
+            self.contract
+                .query(
+                    "allowance",
+                    (wallet.address(), batch_contract),
+                    None,
+                    Options::with(|_| {}),
+                    None,
+                )
+                .map_err(|_| BlockchainError::QueryFailed)
+                .wait()
+        })
+        .map(|allowance: U256| {
+            Some(from_token_units(allowance, self.token_decimals).unwrap_or(u64::MAX))
+        })
+    }
+
+    fn estimate_gas_fees(&self) -> BlockchainResult<GasFees> {
+        match self.fee_market_fees() {
+            Some(fees) => Ok(fees),
+            None => timed_rpc("eth_gasPrice", || {
+                self.web3
+                    .eth()
+                    .gas_price()
+                    .map_err(|_| BlockchainError::QueryFailed)
+                    .wait()
+            })
+            .map(|gas_price| GasFees::Legacy {
+                gas_price: to_gwei_rounded_up(gas_price),
+            }),
         }
     }
 
     fn get_eth_balance(&self, wallet: &Wallet) -> Balance {
-        self.web3
-            .eth()
-            .balance(wallet.address(), None)
-            .map_err(|_| BlockchainError::QueryFailed)
-            .wait()
+        timed_rpc("eth_getBalance", || {
+            self.web3
+                .eth()
+                .balance(wallet.address(), None)
+                .map_err(|_| BlockchainError::QueryFailed)
+                .wait()
+        })
     }
 
     fn get_token_balance(&self, wallet: &Wallet) -> Balance {
-        self.contract
-            .query(
-                "balanceOf",
-                wallet.address(),
-                None,
-                Options::with(|_| {}),
-                None,
-            )
-            .map_err(|_| BlockchainError::QueryFailed)
-            .wait()
+        timed_rpc("eth_call", || {
+            self.contract
+                .query(
+                    "balanceOf",
+                    wallet.address(),
+                    None,
+                    Options::with(|_| {}),
+                    None,
+                )
+                .map_err(|_| BlockchainError::QueryFailed)
+                .wait()
+        })
     }
 
     fn get_transaction_count(&self, wallet: &Wallet) -> Nonce {
-        self.web3
-            .eth()
-            .transaction_count(wallet.address(), Some(BlockNumber::Pending))
-            .map_err(|_| BlockchainError::QueryFailed)
-            .wait()
+        timed_rpc("eth_getTransactionCount", || {
+            self.web3
+                .eth()
+                .transaction_count(wallet.address(), Some(BlockNumber::Pending))
+                .map_err(|_| BlockchainError::QueryFailed)
+                .wait()
+        })
+    }
+
+    fn get_transaction_receipt(&self, hash: H256) -> BlockchainResult<TransactionStatus> {
+        timed_rpc("eth_getTransactionReceipt", || {
+            self.web3
+                .eth()
+                .transaction_receipt(hash)
+                .map_err(|_| BlockchainError::QueryFailed)
+                .wait()
+        })
+        .map(|receipt_opt| match receipt_opt {
+            Some(ref receipt) if receipt.block_number.is_none() => TransactionStatus::Pending,
+            // Receipts from before the Byzantium fork have no status; being mined was success
+            Some(ref receipt) => match receipt.status {
+                Some(status) if status.is_zero() => TransactionStatus::Failed,
+                _ => TransactionStatus::Succeeded,
+            },
+            None => TransactionStatus::Pending,
+        })
+    }
+
+    fn get_block_hash(&self, block_number: u64) -> BlockchainResult<Option<H256>> {
+        timed_rpc("eth_getBlockByNumber", || {
+            self.web3
+                .eth()
+                .block(BlockId::Number(BlockNumber::Number(block_number)))
+                .map_err(|_| BlockchainError::QueryFailed)
+                .wait()
+        })
+        .map(|block_opt| block_opt.and_then(|block| block.hash))
+    }
+}
+
+// Records how long a blocking call to the blockchain service took, and whether it failed
+fn timed_rpc<R, E, F>(method: &'static str, call: F) -> Result<R, E>
+where
+    F: FnOnce() -> Result<R, E>,
+{
+    let labels = [("method", method)];
+    let start = Instant::now();
+    let result = call();
+    metrics().observe(&BLOCKCHAIN_RPC_DURATION, &labels, start.elapsed());
+    if result.is_err() {
+        metrics().increment(&BLOCKCHAIN_RPC_ERRORS, &labels, 1);
+    }
+    result
+}
+
+// web3 and ethsign depend on different versions of ethereum-types
+fn to_ethereum_u256(value: U256) -> ethereum_types::U256 {
+    serde_json::from_value::<ethereum_types::U256>(
+        serde_json::to_value(value).expect("Internal error"),
+    )
+    .expect("Internal error")
+}
+
+fn transactions_from_logs(logs: &[Log], token_decimals: u8) -> BlockchainResult<Vec<Transaction>> {
+    if logs
+        .iter()
+        .any(|log| log.topics.len() < 2 || log.data.0.len() > 32)
+    {
+        return Err(BlockchainError::InvalidResponse);
+    }
+    Ok(logs
+        .iter()
+        .filter_map(|log: &Log| match (log.block_number, log.block_hash) {
+            (Some(block_number), Some(block_hash)) => {
+                let amount: U256 = U256::from(log.data.0.as_slice());
+                let gwei_amount = from_token_units(amount, token_decimals);
+                gwei_amount.map(|gwei_amount| Transaction {
+                    block_number: u64::try_from(block_number).expect("Internal Error"), // TODO: back to testing for overflow
+                    block_hash,
+                    from: Wallet::from(log.topics[1]),
+                    gwei_amount,
+                })
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+// Providers word it differently, but when they won't search a block range that large, they say so
+// in the message; rate limits and outages don't talk about ranges or results
+fn is_block_range_too_large(error: &web3::Error) -> bool {
+    match error {
+        web3::Error::Rpc(e) => {
+            let message = e.message.to_lowercase();
+            BLOCK_RANGE_TOO_LARGE_HINTS
+                .iter()
+                .any(|hint| message.contains(hint))
+        }
+        _ => false,
     }
 }
 
@@ -419,7 +744,31 @@ impl<T> BlockchainInterfaceNonClandestine<T>
 where
     T: Transport + Debug,
 {
-    pub fn new(transport: T, event_loop_handle: EventLoopHandle, chain_id: u8) -> Self {
+    // One refusal may have been about a few unusually busy blocks, so a shrunken range doesn't
+    // have to stay small forever
+    fn count_full_chunk(&self) {
+        let block_count = self.scan_block_range.get();
+        if block_count >= self.max_scan_block_range {
+            return;
+        }
+        let full_chunks_scanned = self.full_chunks_scanned.get() + 1;
+        if full_chunks_scanned < CHUNKS_BEFORE_SCAN_BLOCK_RANGE_GROWS {
+            self.full_chunks_scanned.set(full_chunks_scanned);
+            return;
+        }
+        let larger_count = (block_count * 2).min(self.max_scan_block_range);
+        debug!(
+            self.logger,
+            "Blockchain service searched {} blocks at once {} times; searching {} at a time from now on",
+            block_count,
+            full_chunks_scanned,
+            larger_count
+        );
+        self.scan_block_range.set(larger_count);
+        self.full_chunks_scanned.set(0);
+    }
+
+    pub fn new(transport: T, event_loop_handle: EventLoopHandle, chain_id: u64) -> Self {
         let web3 = Web3::new(transport);
         let contract = Contract::from_json(
             web3.eth(),
@@ -430,142 +779,339 @@ where
         Self {
             logger: Logger::new("BlockchainInterface"),
             chain_id,
+            token_decimals: token_decimals_from_chain_id(chain_id),
+            batch_contract_opt: batch_contract_address(chain_id),
+            batch_method_id: batch_method_id(chain_id),
+            scan_block_range: Cell::new(DEFAULT_SCAN_BLOCK_RANGE),
+            max_scan_block_range: DEFAULT_SCAN_BLOCK_RANGE,
+            full_chunks_scanned: Cell::new(0),
+            confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
             _event_loop_handle: event_loop_handle,
             web3,
             contract,
         }
     }
+
+    // Start with chunks this size; they shrink if the blockchain service objects, and grow back
+    // once it stops objecting, but never past this size
+    pub fn scan_block_range(mut self, blocks: u64) -> Self {
+        self.scan_block_range.set(blocks.max(1));
+        self.max_scan_block_range = blocks.max(1);
+        self
+    }
+
+    pub fn confirmation_depth(mut self, blocks: u64) -> Self {
+        self.confirmation_depth = blocks;
+        self
+    }
+
+    fn sign_and_send(
+        &self,
+        consuming_wallet: &Wallet,
+        to: Address,
+        data: &[u8],
+        payment_count: u64,
+        nonce: U256,
+        fees: GasFees,
+    ) -> BlockchainResult<H256> {
+        let gas_limit = ethereum_types::U256::from(
+            data.iter().fold(GAS_PER_PAYMENT * payment_count, |acc, v| {
+                acc + if v == &0u8 { 4 } else { 68 }
+            }),
+        );
+
+        let converted_nonce = to_ethereum_u256(nonce);
+        let to = Some(ethereum_types::Address { 0: to.0 });
+        let signed_tx = match fees {
+            GasFees::Legacy { gas_price } => RawTransaction {
+                nonce: converted_nonce,
+                to,
+                value: ethereum_types::U256::zero(),
+                gas_price: to_ethereum_u256(to_wei(gas_price)),
+                gas_limit,
+                data: data.to_vec(),
+            }
+            .sign(consuming_wallet, self.chain_id),
+            GasFees::FeeMarket {
+                max_fee,
+                priority_fee,
+            } => FeeMarketTransaction {
+                nonce: converted_nonce,
+                to,
+                value: ethereum_types::U256::zero(),
+                max_priority_fee_per_gas: to_ethereum_u256(to_wei(priority_fee)),
+                max_fee_per_gas: to_ethereum_u256(to_wei(max_fee)),
+                gas_limit,
+                data: data.to_vec(),
+            }
+            .sign(consuming_wallet, self.chain_id),
+        };
+        match timed_rpc("eth_sendRawTransaction", || {
+            self.web3
+                .eth()
+                .send_raw_transaction(Bytes(signed_tx))
+                .wait()
+        }) {
+            Ok(result) => Ok(result),
+            Err(e) => Err(BlockchainError::TransactionFailed(format!("{:?}", e))),
+        }
+    }
+
+    // ABI encoding: the token contract and where each array starts, then each array as its length
+    // followed by its elements
+    fn batch_call_data(&self, payments: &[(Wallet, u64)]) -> Vec<u8> {
+        let number_word = |number: U256| {
+            let mut word = [0u8; 32];
+            number.to_big_endian(&mut word);
+            word
+        };
+        let address_word = |address: Address| {
+            let mut word = [0u8; 32];
+            word[12..].copy_from_slice(&address.0[..]);
+            word
+        };
+        let count = payments.len();
+        let mut words = vec![
+            address_word(self.contract_address()),
+            number_word(U256::from(3 * 32)),
+            number_word(U256::from((4 + count) * 32)),
+            number_word(U256::from(count)),
+        ];
+        words.extend(
+            payments
+                .iter()
+                .map(|(recipient, _)| address_word(recipient.address())),
+        );
+        words.push(number_word(U256::from(count)));
+        words.extend(
+            payments
+                .iter()
+                .map(|(_, amount)| number_word(to_token_units(*amount, self.token_decimals))),
+        );
+        words
+            .iter()
+            .fold(self.batch_method_id.to_vec(), |mut data, word| {
+                data.extend_from_slice(word);
+                data
+            })
+    }
+
+    // A batch contract that collects the tokens before passing them on is what its Transfer logs
+    // name as the payer; the real payer is whoever sent it the transaction
+    fn with_batch_payers(&self, logs: Vec<Log>) -> BlockchainResult<Vec<Log>> {
+        let batch_contract_topic: H256 = match self.batch_contract_opt {
+            Some(batch_contract) => batch_contract.into(),
+            None => return Ok(logs),
+        };
+        logs.into_iter()
+            .map(|mut log| {
+                if log.topics.get(1) == Some(&batch_contract_topic) {
+                    let transaction_hash = log
+                        .transaction_hash
+                        .ok_or(BlockchainError::InvalidResponse)?;
+                    let transaction = timed_rpc("eth_getTransactionByHash", || {
+                        self.web3
+                            .eth()
+                            .transaction(TransactionId::Hash(transaction_hash))
+                            .map_err(|_| BlockchainError::QueryFailed)
+                            .wait()
+                    })?
+                    .ok_or(BlockchainError::QueryFailed)?;
+                    log.topics[1] = transaction.from.into();
+                }
+                Ok(log)
+            })
+            .collect()
+    }
+
+    // None if the chain has no fee market, or the blockchain service can't say what's going on in
+    // it. The next block's base fee can rise by an eighth in each full block after it, so allowing
+    // for twice that keeps a transaction eligible through several busy blocks in a row.
+    fn fee_market_fees(&self) -> Option<GasFees> {
+        let params = vec![
+            json!(format!("{:#x}", FEE_HISTORY_BLOCK_COUNT)),
+            json!("latest"),
+            json!([FEE_HISTORY_REWARD_PERCENTILE]),
+        ];
+        let fee_history = match timed_rpc("eth_feeHistory", || {
+            self.web3
+                .transport()
+                .execute("eth_feeHistory", params)
+                .wait()
+        }) {
+            Ok(response) => serde_json::from_value::<FeeHistory>(response).ok()?,
+            Err(e) => {
+                debug!(
+                    self.logger,
+                    "No fee history from the blockchain service ({:?}); pricing gas the legacy way",
+                    e
+                );
+                return None;
+            }
+        };
+        let base_fee = *fee_history.base_fee_per_gas?.last()?;
+        if base_fee.is_zero() {
+            return None;
+        }
+        let mut tips = fee_history
+            .reward
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|rewards| rewards.first().cloned())
+            .collect::<Vec<U256>>();
+        tips.sort();
+        let priority_fee = tips
+            .get(tips.len() / 2)
+            .map(|tip| to_gwei_rounded_up(*tip))
+            .unwrap_or(MIN_PRIORITY_FEE)
+            .max(MIN_PRIORITY_FEE);
+        let max_fee = to_gwei_rounded_up(base_fee)
+            .saturating_mul(2)
+            .saturating_add(priority_fee);
+        Some(GasFees::FeeMarket {
+            max_fee,
+            priority_fee,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blockchain::test_utils::{make_fake_event_loop_handle, TestTransport};
     use crate::sub_lib::wallet::Wallet;
+    use crate::test_utils::logging::{init_test_logging, TestLogHandler};
     use crate::test_utils::{make_paying_wallet, make_wallet};
     use ethereum_types::BigEndianHash;
     use ethsign_crypto::Keccak256;
     use jsonrpc_core as rpc;
     use masq_lib::test_utils::utils::DEFAULT_CHAIN_ID;
     use masq_lib::utils::find_free_port;
+    use rustc_hex::FromHex;
     use serde_json::json;
     use serde_json::Value;
     use simple_server::Server;
-    use std::cell::RefCell;
-    use std::collections::VecDeque;
     use std::net::Ipv4Addr;
-    use std::rc::Rc;
     use std::str::FromStr;
-    use std::sync::mpsc;
     use std::thread;
-    use web3::{transports::Http, Error, RequestId, Transport};
-
-    #[derive(Debug, Default, Clone)]
-    pub struct TestTransport {
-        asserted: usize,
-        requests: Rc<RefCell<Vec<(String, Vec<rpc::Value>)>>>,
-        responses: Rc<RefCell<VecDeque<rpc::Value>>>,
+    use web3::transports::Http;
+    use web3::types::{Block, TransactionReceipt, U128, U64};
+
+    fn make_log_retrieving_subject(
+        latest_block: &str,
+        logs_response: &str,
+    ) -> (
+        TestTransport,
+        BlockchainInterfaceNonClandestine<TestTransport>,
+    ) {
+        let mut transport = TestTransport::default();
+        transport.add_response(json!(latest_block));
+        let logs_response: Value = serde_json::from_str(logs_response).unwrap();
+        transport.add_response(logs_response["result"].clone());
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        );
+        (transport, subject)
     }
 
-    impl Transport for TestTransport {
-        type Out = web3::Result<rpc::Value>;
-
-        fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
-            let request = web3::helpers::build_request(1, method, params.clone());
-            self.requests.borrow_mut().push((method.into(), params));
-            (self.requests.borrow().len(), request)
-        }
-
-        fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
-            match self.responses.borrow_mut().pop_front() {
-                Some(response) => Box::new(futures::finished(response)),
-                None => {
-                    println!("Unexpected request (id: {:?}): {:?}", id, request);
-                    Box::new(futures::failed(Error::Unreachable))
-                }
-            }
-        }
+    fn assert_logs_request(transport: &mut TestTransport, from_block: &str, to_block: &str) {
+        transport.assert_request(
+            "eth_getLogs",
+            &[format!(
+                r#"{{"address":"0x59882e4a8f5d24643d4dda422922a870f1b3e664","fromBlock":"{}","toBlock":"{}","topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",null,"0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc"]}}"#,
+                from_block, to_block
+            )],
+        );
     }
 
-    impl TestTransport {
-        pub fn add_response(&mut self, value: rpc::Value) {
-            self.responses.borrow_mut().push_back(value);
-        }
-
-        pub fn assert_request(&mut self, method: &str, params: &[String]) {
-            let idx = self.asserted;
-            self.asserted += 1;
-
-            let (m, p) = self
-                .requests
-                .borrow()
-                .get(idx)
-                .expect("Expected result.")
-                .clone();
-            assert_eq!(&m, method);
-            let p: Vec<String> = p
-                .into_iter()
-                .map(|p| serde_json::to_string(&p).unwrap())
-                .collect();
-            assert_eq!(p, params);
-        }
+    #[test]
+    fn blockchain_interface_non_clandestine_retrieves_transactions() {
+        let (mut transport, subject) = make_log_retrieving_subject(
+            "0x4be6ff",
+            r#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","blockNumber":"0x4be663","data":"0x0000000000000000000000000000000000000000000000000010000000000000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#,
+        );
 
-        pub fn assert_no_more_requests(&mut self) {
-            let requests = self.requests.borrow();
-            assert_eq!(
-                self.asserted,
-                requests.len(),
-                "Expected no more requests, got: {:?}",
-                &requests[self.asserted..]
-            );
-        }
-    }
+        let result = subject
+            .retrieve_transactions(
+                4_974_000,
+                &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+            )
+            .unwrap();
 
-    fn make_fake_event_loop_handle() -> EventLoopHandle {
-        Http::new("http://86.75.30.9").unwrap().0
+        transport.assert_request("eth_blockNumber", &[]);
+        assert_logs_request(&mut transport, "0x4be5b0", "0x4be6f3");
+        transport.assert_no_more_requests();
+        assert_eq!(
+            RetrievedTransactions {
+                transactions: vec![Transaction {
+                    block_number: 4_974_179u64,
+                    block_hash: H256::from_str(
+                        "1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a"
+                    )
+                    .unwrap(),
+                    from: Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+                    gwei_amount: 4_503_599u64,
+                }],
+                new_start_block: 0x4be6f4,
+                caught_up: true,
+                reorganized: false,
+            },
+            result,
+        )
     }
 
     #[test]
-    fn blockchain_interface_non_clandestine_retrieves_transactions() {
-        let to = "0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc";
-        let port = find_free_port();
-
-        let (tx, rx) = mpsc::sync_channel(1337);
-        thread::spawn(move || {
-            Server::new(move |req, mut rsp| {
-                tx.send(req.body().clone()).unwrap();
-                Ok(rsp.body(br#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","blockNumber":"0x4be663","data":"0x0000000000000000000000000000000000000000000000000010000000000000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#.to_vec())?)
-            }).listen(&Ipv4Addr::LOCALHOST.to_string(), &format!("{}", port));
-        });
-
-        let (event_loop_handle, transport) = Http::new(&format!(
-            "http://{}:{}",
-            &Ipv4Addr::LOCALHOST.to_string(),
-            port
-        ))
-        .unwrap();
-        let subject =
-            BlockchainInterfaceNonClandestine::new(transport, event_loop_handle, DEFAULT_CHAIN_ID);
+    fn blockchain_interface_non_clandestine_credits_the_sender_of_a_batch_transaction_whose_contract_passed_the_tokens_on(
+    ) {
+        let (mut transport, mut subject) = make_log_retrieving_subject(
+            "0x4be6ff",
+            r#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","blockNumber":"0x4be663","data":"0x0000000000000000000000000000000000000000000000000010000000000000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x000000000000000000000000d152f549545093347a162dce210e7293f1452150","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#,
+        );
+        transport.add_response(json!({
+            "hash": "0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681",
+            "nonce": "0x7",
+            "blockHash": "0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a",
+            "blockNumber": "0x4be663",
+            "transactionIndex": "0x0",
+            "from": "0x5c361ba8d82fcf0e5538b2a823e9d457a2296725",
+            "to": "0xd152f549545093347a162dce210e7293f1452150",
+            "value": "0x0",
+            "gasPrice": "0x3b9aca00",
+            "gas": "0x1d4c0",
+            "input": "0x"
+        }));
+        subject.batch_contract_opt = batch_contract_address(chain_id_from_name("mainnet"));
 
         let result = subject
             .retrieve_transactions(
-                42,
+                4_974_000,
                 &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
             )
             .unwrap();
 
-        let body: Value = serde_json::from_slice(&rx.recv().unwrap()).unwrap();
-        assert_eq!(
-            format!("\"0x000000000000000000000000{}\"", &to[2..]),
-            body["params"][0]["topics"][2].to_string(),
+        transport.assert_request("eth_blockNumber", &[]);
+        assert_logs_request(&mut transport, "0x4be5b0", "0x4be6f3");
+        transport.assert_request(
+            "eth_getTransactionByHash",
+            &[String::from(
+                r#""0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681""#,
+            )],
         );
+        transport.assert_no_more_requests();
         assert_eq!(
+            result.transactions,
             vec![Transaction {
                 block_number: 4_974_179u64,
-                from: Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+                block_hash: H256::from_str(
+                    "1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a"
+                )
+                .unwrap(),
+                from: Wallet::from_str("0x5c361ba8d82fcf0e5538b2a823e9d457a2296725").unwrap(),
                 gwei_amount: 4_503_599u64,
-            }],
-            result,
-        )
+            }]
+        );
     }
 
     #[test]
@@ -590,91 +1136,391 @@ mod tests {
     #[test]
     fn blockchain_interface_non_clandestine_retrieve_transactions_returns_an_error_if_a_response_with_too_few_topics_is_returned(
     ) {
-        let port = find_free_port();
+        let (_, subject) = make_log_retrieving_subject(
+            "0x4be6ff",
+            r#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","blockNumber":"0x4be663","data":"0x0000000000000000000000000000000000000000000000056bc75e2d63100000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#,
+        );
 
-        thread::spawn(move || {
-            Server::new(|_req, mut rsp| {
-                Ok(rsp.body(br#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","blockNumber":"0x4be663","data":"0x0000000000000000000000000000000000000000000000056bc75e2d63100000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#.to_vec())?)
-            }).listen(&Ipv4Addr::LOCALHOST.to_string(), &format!("{}", port));
-        });
+        let result = subject.retrieve_transactions(
+            4_974_000,
+            &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+        );
 
-        let (event_loop_handle, transport) = Http::new(&format!(
-            "http://{}:{}",
-            &Ipv4Addr::LOCALHOST.to_string(),
-            port
-        ))
-        .unwrap();
-        let subject =
-            BlockchainInterfaceNonClandestine::new(transport, event_loop_handle, DEFAULT_CHAIN_ID);
+        assert_eq!(
+            BlockchainError::InvalidResponse,
+            result.expect_err("Expected an Err, got Ok")
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_retrieve_transactions_returns_an_error_if_a_response_with_data_that_is_too_long_is_returned(
+    ) {
+        let (_, subject) = make_log_retrieving_subject(
+            "0x4be6ff",
+            r#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","blockNumber":"0x4be663","data":"0x0000000000000000000000000000000000000000000000056bc75e2d6310000001","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#,
+        );
 
         let result = subject.retrieve_transactions(
-            42,
+            4_974_000,
+            &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+        );
+
+        assert_eq!(Err(BlockchainError::InvalidResponse), result);
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_retrieve_transactions_ignores_transaction_logs_that_have_no_block_number(
+    ) {
+        let (_, subject) = make_log_retrieving_subject(
+            "0x4be6ff",
+            r#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","data":"0x0000000000000000000000000000000000000000000000000010000000000000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#,
+        );
+
+        let result = subject.retrieve_transactions(
+            4_974_000,
             &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
         );
 
         assert_eq!(
-            BlockchainError::InvalidResponse,
-            result.expect_err("Expected an Err, got Ok")
+            Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: 0x4be6f4,
+                caught_up: true,
+                reorganized: false,
+            }),
+            result
         );
     }
 
     #[test]
-    fn blockchain_interface_non_clandestine_retrieve_transactions_returns_an_error_if_a_response_with_data_that_is_too_long_is_returned(
+    fn blockchain_interface_non_clandestine_retrieve_transactions_ignores_transaction_logs_that_have_no_block_hash(
     ) {
-        let port = find_free_port();
+        let (_, subject) = make_log_retrieving_subject(
+            "0x4be6ff",
+            r#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockNumber":"0x4be663","data":"0x0000000000000000000000000000000000000000000000000010000000000000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#,
+        );
 
-        thread::spawn(move || {
-            Server::new(move |_req, mut rsp| {
-                Ok(rsp.body(br#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","blockNumber":"0x4be663","data":"0x0000000000000000000000000000000000000000000000056bc75e2d6310000001","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#.to_vec())?)
-            }).listen(&Ipv4Addr::LOCALHOST.to_string(), &format!("{}", port));
-        });
+        let result = subject.retrieve_transactions(
+            4_974_000,
+            &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+        );
 
-        let (event_loop_handle, transport) = Http::new(&format!(
-            "http://{}:{}",
-            &Ipv4Addr::LOCALHOST.to_string(),
-            port
-        ))
-        .unwrap();
+        assert_eq!(
+            Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: 0x4be6f4,
+                caught_up: true,
+                reorganized: false,
+            }),
+            result
+        );
+    }
 
-        let subject =
-            BlockchainInterfaceNonClandestine::new(transport, event_loop_handle, DEFAULT_CHAIN_ID);
+    #[test]
+    fn blockchain_interface_non_clandestine_searches_only_blocks_buried_under_the_confirmation_depth(
+    ) {
+        let (mut transport, subject) =
+            make_log_retrieving_subject("0x4e20", r#"{"jsonrpc":"2.0","id":3,"result":[]}"#);
+        let subject = subject.confirmation_depth(100);
+
+        let result = subject.retrieve_transactions(
+            19_000,
+            &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+        );
+
+        transport.assert_request("eth_blockNumber", &[]);
+        assert_logs_request(&mut transport, "0x4a38", "0x4dbc");
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: 19_901,
+                caught_up: true,
+                reorganized: false,
+            })
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_does_not_search_blocks_within_the_confirmation_depth() {
+        let mut transport = TestTransport::default();
+        transport.add_response(json!("0x4e20"));
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        )
+        .confirmation_depth(100);
+
+        let result = subject.retrieve_transactions(
+            19_950,
+            &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+        );
+
+        transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: 19_950,
+                caught_up: true,
+                reorganized: false,
+            })
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_retrieves_transactions_a_chunk_at_a_time() {
+        let (mut transport, subject) =
+            make_log_retrieving_subject("0x4e20", r#"{"jsonrpc":"2.0","id":3,"result":[]}"#);
+        let subject = subject.scan_block_range(1000);
 
         let result = subject.retrieve_transactions(
             42,
             &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
         );
 
-        assert_eq!(Err(BlockchainError::InvalidResponse), result);
+        transport.assert_request("eth_blockNumber", &[]);
+        assert_logs_request(&mut transport, "0x2a", "0x411");
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: 1042,
+                caught_up: false,
+                reorganized: false,
+            })
+        );
     }
 
     #[test]
-    fn blockchain_interface_non_clandestine_retrieve_transactions_ignores_transaction_logs_that_have_no_block_number(
-    ) {
-        let port = find_free_port();
+    fn blockchain_interface_non_clandestine_does_not_look_for_logs_past_the_latest_block() {
+        let mut transport = TestTransport::default();
+        transport.add_response(json!("0x29"));
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        );
 
-        thread::spawn(move || {
-            Server::new(|_req, mut rsp| {
-                Ok(rsp.body(br#"{"jsonrpc":"2.0","id":3,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","data":"0x0000000000000000000000000000000000000000000000000010000000000000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#.to_vec())?)
+        let result = subject.retrieve_transactions(
+            42,
+            &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+        );
+
+        transport.assert_request("eth_blockNumber", &[]);
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            Ok(RetrievedTransactions {
+                transactions: vec![],
+                new_start_block: 42,
+                caught_up: true,
+                reorganized: false,
             })
-                .listen(&Ipv4Addr::LOCALHOST.to_string(), &format!("{}", port));
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_searches_fewer_blocks_when_the_service_objects() {
+        init_test_logging();
+        let mut transport = TestTransport::default();
+        transport.add_response(json!("0x4e20"));
+        transport.add_error_response(web3::Error::Rpc(rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32005),
+            message: "query returned more than 10000 results".to_string(),
+            data: None,
+        }));
+        transport.add_response(json!([]));
+        transport.add_response(json!("0x4e20"));
+        transport.add_response(json!([]));
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        )
+        .scan_block_range(1000);
+        let recipient = Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap();
+
+        let first_result = subject.retrieve_transactions(42, &recipient);
+        let second_result = subject.retrieve_transactions(542, &recipient);
+
+        transport.assert_request("eth_blockNumber", &[]);
+        assert_logs_request(&mut transport, "0x2a", "0x411");
+        assert_logs_request(&mut transport, "0x2a", "0x21d");
+        transport.assert_request("eth_blockNumber", &[]);
+        assert_logs_request(&mut transport, "0x21e", "0x411");
+        transport.assert_no_more_requests();
+        assert_eq!(first_result.unwrap().new_start_block, 542);
+        assert_eq!(second_result.unwrap().new_start_block, 1042);
+        TestLogHandler::new().exists_log_containing(
+            "WARN: BlockchainInterface: Blockchain service wouldn't search 1000 blocks at once for payments (RPC error: Error { code: ServerError(-32005), message: \"query returned more than 10000 results\", data: None }); searching 500 at a time from now on",
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_searches_more_blocks_again_after_enough_chunks_go_through(
+    ) {
+        init_test_logging();
+        let mut transport = TestTransport::default();
+        (0..11).for_each(|_| {
+            transport.add_response(json!("0x4e20"));
+            transport.add_response(json!([]));
+        });
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        )
+        .scan_block_range(1000);
+        subject.scan_block_range.set(250);
+        let recipient = Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap();
+
+        let start_block = (0..11).fold(42, |start_block, _| {
+            subject
+                .retrieve_transactions(start_block, &recipient)
+                .unwrap()
+                .new_start_block
         });
 
-        let (event_loop_handle, transport) = Http::new(&format!(
-            "http://{}:{}",
-            &Ipv4Addr::LOCALHOST.to_string(),
-            port
-        ))
-        .unwrap();
+        (0..10).for_each(|chunk| {
+            transport.assert_request("eth_blockNumber", &[]);
+            assert_logs_request(
+                &mut transport,
+                &format!("{:#x}", 42 + chunk * 250),
+                &format!("{:#x}", 42 + chunk * 250 + 249),
+            );
+        });
+        transport.assert_request("eth_blockNumber", &[]);
+        assert_logs_request(&mut transport, "0x9ee", "0xbe1");
+        transport.assert_no_more_requests();
+        assert_eq!(start_block, 3042);
+        assert_eq!(subject.scan_block_range.get(), 500);
+        TestLogHandler::new().exists_log_containing(
+            "DEBUG: BlockchainInterface: Blockchain service searched 250 blocks at once 10 times; searching 500 at a time from now on",
+        );
+    }
 
-        let subject =
-            BlockchainInterfaceNonClandestine::new(transport, event_loop_handle, DEFAULT_CHAIN_ID);
+    #[test]
+    fn blockchain_interface_non_clandestine_never_searches_more_blocks_than_it_started_with() {
+        let mut transport = TestTransport::default();
+        (0..11).for_each(|_| {
+            transport.add_response(json!("0x4e20"));
+            transport.add_response(json!([]));
+        });
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        )
+        .scan_block_range(100);
+        let recipient = Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap();
+
+        (0..11).fold(42, |start_block, _| {
+            subject
+                .retrieve_transactions(start_block, &recipient)
+                .unwrap()
+                .new_start_block
+        });
+
+        assert_eq!(subject.scan_block_range.get(), 100);
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_gives_up_when_the_service_will_not_search_even_one_block(
+    ) {
+        let mut transport = TestTransport::default();
+        transport.add_response(json!("0x4e20"));
+        transport.add_error_response(web3::Error::Rpc(rpc::Error {
+            code: rpc::ErrorCode::InvalidParams,
+            message: "block range is too wide".to_string(),
+            data: None,
+        }));
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        )
+        .scan_block_range(1);
 
         let result = subject.retrieve_transactions(
             42,
             &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
         );
 
-        assert_eq!(Ok(vec![]), result);
+        assert_eq!(result, Err(BlockchainError::QueryFailed));
+        transport.assert_request("eth_blockNumber", &[]);
+        assert_logs_request(&mut transport, "0x2a", "0x2a");
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_does_not_search_fewer_blocks_for_other_errors() {
+        let mut transport = TestTransport::default();
+        transport.add_response(json!("0x4e20"));
+        transport.add_error_response(web3::Error::Rpc(rpc::Error {
+            code: rpc::ErrorCode::ServerError(-32005),
+            message: "project ID request rate exceeded".to_string(),
+            data: None,
+        }));
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        )
+        .scan_block_range(1000);
+
+        let result = subject.retrieve_transactions(
+            42,
+            &Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc").unwrap(),
+        );
+
+        assert_eq!(result, Err(BlockchainError::QueryFailed));
+        transport.assert_request("eth_blockNumber", &[]);
+        assert_logs_request(&mut transport, "0x2a", "0x411");
+        transport.assert_no_more_requests();
+        assert_eq!(subject.scan_block_range.get(), 1000);
+    }
+
+    #[test]
+    fn block_range_too_large_is_recognized_in_the_ways_providers_say_it() {
+        let rpc_error = |message: &str| {
+            web3::Error::Rpc(rpc::Error {
+                code: rpc::ErrorCode::ServerError(-32000),
+                message: message.to_string(),
+                data: None,
+            })
+        };
+
+        vec![
+            "query returned more than 10000 results",
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range",
+            "exceed maximum block range: 5000",
+            "block range is too wide",
+            "Block range limit exceeded",
+        ]
+        .into_iter()
+        .for_each(|message| assert!(is_block_range_too_large(&rpc_error(message)), "{}", message));
+        vec![
+            "daily request count exceeded, request rate limited",
+            "header not found",
+            "gas required exceeds allowance or always failing transaction",
+            "more than 10 requests per second",
+        ]
+        .into_iter()
+        .for_each(|message| {
+            assert!(
+                !is_block_range_too_large(&rpc_error(message)),
+                "{}",
+                message
+            )
+        });
+        assert!(!is_block_range_too_large(&web3::Error::Transport(
+            "block range".to_string()
+        )));
     }
 
     #[test]
@@ -703,6 +1549,12 @@ mod tests {
         );
 
         assert_eq!(U256::from(65_535), result.unwrap());
+        let labels = [("method", "eth_getBalance")];
+        assert_eq!(
+            metrics().observation_count(&BLOCKCHAIN_RPC_DURATION, &labels),
+            Some(1)
+        );
+        assert_eq!(metrics().value(&BLOCKCHAIN_RPC_ERRORS, &labels), None);
     }
 
     #[test]
@@ -753,6 +1605,12 @@ mod tests {
         );
 
         assert_eq!(Err(BlockchainError::QueryFailed), result);
+        let labels = [("method", "eth_getBalance")];
+        assert_eq!(
+            metrics().observation_count(&BLOCKCHAIN_RPC_DURATION, &labels),
+            Some(1)
+        );
+        assert_eq!(metrics().value(&BLOCKCHAIN_RPC_ERRORS, &labels), Some(1.0));
     }
 
     #[test]
@@ -887,14 +1745,308 @@ mod tests {
             &make_wallet("blah123"),
             9000,
             U256::from(1),
-            2u64,
+            GasFees::Legacy { gas_price: 2 },
         );
 
-        transport.assert_request("eth_sendRawTransaction", &[String::from(r#""0xf8a801847735940082dbe894384dec25e03f94931767ce4c3556168468ba24c380b844a9059cbb00000000000000000000000000000000000000000000000000626c61683132330000000000000000000000000000000000000000000000000000082f79cd900029a0b8e83e714af8bf1685b496912ee4aeff7007ba0f4c29ae50f513bc71ce6a18f4a06a923088306b4ee9cbfcdc62c9b396385f9b1c380134bf046d6c9ae47dea6578""#)]);
+        transport.assert_request("eth_sendRawTransaction", &[String::from(r#""0xf8a801847735940082dbe89459882e4a8f5d24643d4dda422922a870f1b3e66480b844a9059cbb00000000000000000000000000000000000000000000000000626c61683132330000000000000000000000000000000000000000000000000000082f79cd900027a0156a7e59506cb4a27714ced2b988c38b24f451c4530f6a75f52c861027c45d15a03493478028c2b1d8e98da4cefb08a28b4a605dd23844373621cbaec9b990fbb2""#)]);
         transport.assert_no_more_requests();
         assert_eq!(result, Ok(H256::from_uint(&U256::from(1))));
     }
 
+    #[test]
+    fn blockchain_interface_non_clandestine_can_transfer_tokens_in_a_fee_market_transaction() {
+        let mut transport = TestTransport::default();
+        transport.add_response(json!(
+            "0x0000000000000000000000000000000000000000000000000000000000000001"
+        ));
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        );
+
+        let result = subject.send_transaction(
+            &make_paying_wallet(b"gdasgsa"),
+            &make_wallet("blah123"),
+            9000,
+            U256::from(1),
+            GasFees::FeeMarket {
+                max_fee: 42,
+                priority_fee: 2,
+            },
+        );
+
+        assert_eq!(result, Ok(H256::from_uint(&U256::from(1))));
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        let (method, params) = &requests[0];
+        assert_eq!(method, "eth_sendRawTransaction");
+        let signed_tx: Vec<u8> = params[0].as_str().unwrap()[2..].from_hex().unwrap();
+        assert_eq!(signed_tx[0], 0x02);
+        let rlp = rlp::Rlp::new(&signed_tx[1..]);
+        assert_eq!(rlp.item_count().unwrap(), 12);
+        assert_eq!(rlp.val_at::<u64>(0).unwrap(), DEFAULT_CHAIN_ID);
+        assert_eq!(
+            rlp.val_at::<ethereum_types::U256>(1).unwrap(),
+            ethereum_types::U256::from(1)
+        );
+        assert_eq!(
+            rlp.val_at::<ethereum_types::U256>(2).unwrap(),
+            ethereum_types::U256::from(2_000_000_000u64)
+        );
+        assert_eq!(
+            rlp.val_at::<ethereum_types::U256>(3).unwrap(),
+            ethereum_types::U256::from(42_000_000_000u64)
+        );
+        assert_eq!(
+            rlp.val_at::<ethereum_types::Address>(5).unwrap().0,
+            contract_address(DEFAULT_CHAIN_ID).0
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_pays_several_recipients_through_the_batch_contract() {
+        let mut transport = TestTransport::default();
+        transport.add_response(json!(
+            "0x0000000000000000000000000000000000000000000000000000000000000001"
+        ));
+        let chain_id = chain_id_from_name("mainnet");
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            chain_id,
+        );
+
+        let result = subject.send_batch_transaction(
+            &make_paying_wallet(b"gdasgsa"),
+            &[(make_wallet("blah123"), 9000), (make_wallet("foo"), 10)],
+            U256::from(1),
+            GasFees::Legacy { gas_price: 2 },
+        );
+
+        assert_eq!(result, Ok(H256::from_uint(&U256::from(1))));
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        let (method, params) = &requests[0];
+        assert_eq!(method, "eth_sendRawTransaction");
+        let signed_tx: Vec<u8> = params[0].as_str().unwrap()[2..].from_hex().unwrap();
+        let rlp = rlp::Rlp::new(&signed_tx);
+        assert_eq!(
+            rlp.val_at::<ethereum_types::Address>(3).unwrap().0,
+            batch_contract_address(chain_id).unwrap().0
+        );
+        let word = |value: &str| format!("{:0>64}", value);
+        let expected_data = [
+            "51ba162c".to_string(),
+            word("02ba9b528425f9de08f961b88a10b03be8b8b998"),
+            word("60"),
+            word("c0"),
+            word("2"),
+            word(&format!("{:x}", make_wallet("blah123").address())),
+            word(&format!("{:x}", make_wallet("foo").address())),
+            word("2"),
+            word(&format!("{:x}", 9_000_000_000_000u64)),
+            word(&format!("{:x}", 10_000_000_000u64)),
+        ]
+        .concat()
+        .from_hex::<Vec<u8>>()
+        .unwrap();
+        let data = rlp.val_at::<Vec<u8>>(5).unwrap();
+        assert_eq!(data, expected_data);
+        let data_gas = data
+            .iter()
+            .map(|byte| if byte == &0u8 { 4 } else { 68 })
+            .sum::<u64>();
+        assert_eq!(
+            rlp.val_at::<ethereum_types::U256>(2).unwrap(),
+            ethereum_types::U256::from(2 * 55_000 + data_gas)
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_gets_the_batch_allowance_in_gwub() {
+        let mut transport = TestTransport::default();
+        transport.add_response(json!(
+            "0x0000000000000000000000000000000000000000000000004563918244f40000"
+        ));
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            chain_id_from_name("mainnet"),
+        );
+
+        let result = subject.get_batch_allowance(&make_paying_wallet(b"gdasgsa"));
+
+        assert_eq!(result, Ok(Some(5_000_000_000)));
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        let (method, params) = &requests[0];
+        assert_eq!(method, "eth_call");
+        assert_eq!(
+            params[0]["data"],
+            json!(format!(
+                "0xdd62ed3e{}{}",
+                "0000000000000000000000005c361ba8d82fcf0e5538b2a823e9d457a2296725",
+                "000000000000000000000000d152f549545093347a162dce210e7293f1452150"
+            ))
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_has_no_batch_allowance_on_a_chain_without_a_batch_contract(
+    ) {
+        let transport = TestTransport::default();
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            chain_id_from_name("dev"),
+        );
+
+        let result = subject.get_batch_allowance(&make_paying_wallet(b"gdasgsa"));
+
+        assert_eq!(result, Ok(None));
+        assert!(transport.requests().is_empty());
+    }
+
+    fn gas_fees_from_responses(
+        responses: Vec<Result<Value, web3::Error>>,
+    ) -> (TestTransport, BlockchainResult<GasFees>) {
+        let mut transport = TestTransport::default();
+        responses.into_iter().for_each(|response| match response {
+            Ok(value) => transport.add_response(value),
+            Err(error) => transport.add_error_response(error),
+        });
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        );
+
+        let result = subject.estimate_gas_fees();
+
+        transport.assert_request(
+            "eth_feeHistory",
+            &[
+                String::from(r#""0xa""#),
+                String::from(r#""latest""#),
+                String::from("[50]"),
+            ],
+        );
+        (transport, result)
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_estimates_fee_market_fees_from_the_fee_history() {
+        let (mut transport, result) = gas_fees_from_responses(vec![Ok(json!({
+            "oldestBlock": "0x4be6fd",
+            "baseFeePerGas": ["0x2540be400", "0x2a05f2000", "0x2e90edd00"],
+            "gasUsedRatio": [1.0, 1.0],
+            "reward": [["0xb2d05e00"], ["0x3b9aca00"], ["0x77359401"]]
+        }))]);
+
+        transport.assert_no_more_requests();
+        // The median tip is rounded up to 3 Gwei, and the next base fee of 12.5 Gwei up to 13
+        assert_eq!(
+            result,
+            Ok(GasFees::FeeMarket {
+                max_fee: 29,
+                priority_fee: 3
+            })
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_offers_at_least_the_minimum_tip() {
+        let (mut transport, result) = gas_fees_from_responses(vec![Ok(json!({
+            "oldestBlock": "0x4be6fd",
+            "baseFeePerGas": ["0x2540be400", "0x2540be400"],
+            "gasUsedRatio": [0.5],
+            "reward": [["0x0"]]
+        }))]);
+
+        transport.assert_no_more_requests();
+        assert_eq!(
+            result,
+            Ok(GasFees::FeeMarket {
+                max_fee: 21,
+                priority_fee: 1
+            })
+        );
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_prices_gas_the_legacy_way_without_a_fee_history() {
+        let (mut transport, result) = gas_fees_from_responses(vec![
+            Err(web3::Error::Rpc(rpc::Error::method_not_found())),
+            Ok(json!("0x4a817c801")),
+        ]);
+
+        transport.assert_request("eth_gasPrice", &[]);
+        transport.assert_no_more_requests();
+        assert_eq!(result, Ok(GasFees::Legacy { gas_price: 21 }));
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_prices_gas_the_legacy_way_without_a_base_fee() {
+        let (mut transport, result) = gas_fees_from_responses(vec![
+            Ok(json!({
+                "oldestBlock": "0x4be6fd",
+                "baseFeePerGas": ["0x0", "0x0"],
+                "gasUsedRatio": [0.5],
+                "reward": [["0x0"]]
+            })),
+            Ok(json!("0x4a817c800")),
+        ]);
+
+        transport.assert_request("eth_gasPrice", &[]);
+        transport.assert_no_more_requests();
+        assert_eq!(result, Ok(GasFees::Legacy { gas_price: 20 }));
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_reports_failure_when_no_gas_price_is_available() {
+        let (mut transport, result) =
+            gas_fees_from_responses(vec![Ok(json!("booga")), Err(web3::Error::Unreachable)]);
+
+        transport.assert_request("eth_gasPrice", &[]);
+        transport.assert_no_more_requests();
+        assert_eq!(result, Err(BlockchainError::QueryFailed));
+    }
+
+    #[test]
+    fn gas_fees_are_capped_at_the_ceiling() {
+        assert_eq!(
+            GasFees::Legacy { gas_price: 150 }.capped_at(100),
+            GasFees::Legacy { gas_price: 100 }
+        );
+        assert_eq!(
+            GasFees::Legacy { gas_price: 50 }.capped_at(100),
+            GasFees::Legacy { gas_price: 50 }
+        );
+        assert_eq!(
+            GasFees::FeeMarket {
+                max_fee: 150,
+                priority_fee: 2
+            }
+            .capped_at(100),
+            GasFees::FeeMarket {
+                max_fee: 100,
+                priority_fee: 2
+            }
+        );
+        assert_eq!(
+            GasFees::FeeMarket {
+                max_fee: 150,
+                priority_fee: 120
+            }
+            .capped_at(100),
+            GasFees::FeeMarket {
+                max_fee: 100,
+                priority_fee: 100
+            }
+        );
+    }
+
     #[test]
     fn blockchain_interface_non_clandestine_can_fetch_nonce() {
         let mut transport = TestTransport::default();
@@ -922,11 +2074,146 @@ mod tests {
         assert_eq!(result, Ok(U256::from(1)));
     }
 
+    fn receipt_status_from_response(response: Value) -> BlockchainResult<TransactionStatus> {
+        let mut transport = TestTransport::default();
+        transport.add_response(response);
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        );
+
+        let result = subject.get_transaction_receipt(H256::from_uint(&U256::from(0x1234)));
+
+        transport.assert_request(
+            "eth_getTransactionReceipt",
+            &[String::from(
+                r#""0x0000000000000000000000000000000000000000000000000000000000001234""#,
+            )],
+        );
+        transport.assert_no_more_requests();
+        result
+    }
+
+    fn mined_receipt(status: Option<u64>) -> Value {
+        serde_json::to_value(TransactionReceipt {
+            transaction_hash: H256::from_uint(&U256::from(0x1234)),
+            block_hash: Some(H256::from_uint(&U256::from(0x5678))),
+            block_number: Some(U256::from(1000)),
+            status: status.map(U64::from),
+            ..TransactionReceipt::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_reports_transaction_without_receipt_as_pending() {
+        let result = receipt_status_from_response(Value::Null);
+
+        assert_eq!(result, Ok(TransactionStatus::Pending));
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_reports_receipt_without_block_as_pending() {
+        let result = receipt_status_from_response(
+            serde_json::to_value(TransactionReceipt {
+                transaction_hash: H256::from_uint(&U256::from(0x1234)),
+                ..TransactionReceipt::default()
+            })
+            .unwrap(),
+        );
+
+        assert_eq!(result, Ok(TransactionStatus::Pending));
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_reports_successful_receipt_as_succeeded() {
+        let result = receipt_status_from_response(mined_receipt(Some(1)));
+
+        assert_eq!(result, Ok(TransactionStatus::Succeeded));
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_reports_pre_byzantium_receipt_as_succeeded() {
+        let result = receipt_status_from_response(mined_receipt(None));
+
+        assert_eq!(result, Ok(TransactionStatus::Succeeded));
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_reports_reverted_receipt_as_failed() {
+        let result = receipt_status_from_response(mined_receipt(Some(0)));
+
+        assert_eq!(result, Ok(TransactionStatus::Failed));
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_reports_unintelligible_receipt_as_query_failure() {
+        let result = receipt_status_from_response(json!("booga"));
+
+        assert_eq!(result, Err(BlockchainError::QueryFailed));
+    }
+
+    fn block_hash_from_response(response: Value) -> BlockchainResult<Option<H256>> {
+        let mut transport = TestTransport::default();
+        transport.add_response(response);
+        let subject = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        );
+
+        let result = subject.get_block_hash(1000);
+
+        transport.assert_request(
+            "eth_getBlockByNumber",
+            &[String::from(r#""0x3e8""#), String::from("false")],
+        );
+        transport.assert_no_more_requests();
+        result
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_gets_the_hash_of_a_canonical_block() {
+        let result = block_hash_from_response(
+            serde_json::to_value(Block::<H256> {
+                hash: Some(H256::from_uint(&U256::from(0x5678))),
+                number: Some(U128::from(1000)),
+                ..Block::default()
+            })
+            .unwrap(),
+        );
+
+        assert_eq!(result, Ok(Some(H256::from_uint(&U256::from(0x5678)))));
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_reports_no_hash_for_a_block_that_does_not_exist_yet() {
+        let result = block_hash_from_response(Value::Null);
+
+        assert_eq!(result, Ok(None));
+    }
+
+    #[test]
+    fn blockchain_interface_non_clandestine_reports_unintelligible_block_as_query_failure() {
+        let result = block_hash_from_response(json!("booga"));
+
+        assert_eq!(result, Err(BlockchainError::QueryFailed));
+    }
+
     #[test]
     fn to_gwei_truncates_units_smaller_than_gwei() {
         assert_eq!(Some(1), to_gwei(U256::from(1_999_999_999)));
     }
 
+    #[test]
+    fn to_gwei_rounded_up_rounds_up_units_smaller_than_gwei() {
+        assert_eq!(2, to_gwei_rounded_up(U256::from(1_000_000_001)));
+        assert_eq!(1, to_gwei_rounded_up(U256::from(1_000_000_000)));
+        assert_eq!(0, to_gwei_rounded_up(U256::zero()));
+        assert_eq!(u64::MAX, to_gwei_rounded_up(U256::MAX));
+    }
+
     #[test]
     fn to_wei_converts_units_properly_for_max_value() {
         let converted_wei = to_wei(std::u64::MAX);
@@ -944,6 +2231,44 @@ mod tests {
         assert_eq!(converted_wei, U256::from_dec_str("1000000000").unwrap());
     }
 
+    #[test]
+    fn to_token_units_scales_by_token_decimals() {
+        assert_eq!(to_token_units(1, 18), to_wei(1));
+        assert_eq!(to_token_units(1234, 9), U256::from(1234));
+        assert_eq!(to_token_units(5, 12), U256::from(5000));
+    }
+
+    #[test]
+    fn from_token_units_truncates_units_smaller_than_a_billionth_of_a_token() {
+        assert_eq!(from_token_units(U256::from(1_999_999_999), 18), Some(1));
+        assert_eq!(from_token_units(U256::from(1234), 9), Some(1234));
+        assert_eq!(from_token_units(U256::from(5999), 12), Some(5));
+    }
+
+    #[test]
+    fn chain_functions_resolve_through_the_blockchain_definitions() {
+        assert_eq!(chain_id_from_name("Mainnet"), 1);
+        assert_eq!(chain_name_from_id(2), "dev");
+        assert_eq!(contract_creation_block_from_chain_id(1), 9_415_932);
+        assert_eq!(token_decimals_from_chain_id(1), 18);
+        assert_eq!(
+            format!("{:#x}", contract_address(2)),
+            "0x59882e4a8f5d24643d4dda422922a870f1b3e664"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "No blockchain is defined with chain ID 99")]
+    fn contract_address_panics_for_an_undefined_chain_id() {
+        contract_address(99);
+    }
+
+    #[test]
+    #[should_panic(expected = "No blockchain is defined with the name 'booga'")]
+    fn chain_id_from_name_panics_for_an_undefined_name() {
+        chain_id_from_name("booga");
+    }
+
     #[test]
     fn constant_gwei_matches_calculated_value() {
         let value = U256::from(1_000_000_000);
@@ -3,6 +3,8 @@ pub mod bip32;
 pub mod bip39;
 pub mod blockchain_bridge;
 pub mod blockchain_interface;
+pub mod failover_transport;
+pub mod nonce_manager;
 pub mod payer;
 pub mod raw_transaction;
 pub mod signature;
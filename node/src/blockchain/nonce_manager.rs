@@ -0,0 +1,165 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::blockchain::blockchain_interface::{BlockchainInterface, BlockchainResult};
+use crate::sub_lib::wallet::Wallet;
+use web3::types::U256;
+
+// The blockchain service's count of the consuming wallet's transactions, pending ones included,
+// often lags behind the transactions we've just sent it; so besides asking, we remember the nonce
+// after the last one we know was used, and hand out whichever is higher. A nonce whose transaction
+// couldn't be sent is never recorded, so the next transaction gets it and no gap opens up.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    next_nonce_opt: Option<U256>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn next_nonce(
+        &self,
+        blockchain_interface: &dyn BlockchainInterface,
+        wallet: &Wallet,
+    ) -> BlockchainResult<U256> {
+        let transaction_count = blockchain_interface.get_transaction_count(wallet)?;
+        Ok(match self.next_nonce_opt {
+            Some(next_nonce) if next_nonce > transaction_count => next_nonce,
+            _ => transaction_count,
+        })
+    }
+
+    pub fn nonce_used(&mut self, nonce: U256) {
+        let following_nonce = nonce + U256::one();
+        match self.next_nonce_opt {
+            Some(next_nonce) if next_nonce >= following_nonce => (),
+            _ => self.next_nonce_opt = Some(following_nonce),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::blockchain_interface::{
+        BlockchainError, BlockchainInterfaceNonClandestine,
+    };
+    use crate::blockchain::test_utils::{make_fake_event_loop_handle, TestTransport};
+    use crate::test_utils::make_paying_wallet;
+    use masq_lib::test_utils::utils::DEFAULT_CHAIN_ID;
+    use serde_json::json;
+
+    fn make_interface(
+        transaction_counts: &[&str],
+    ) -> (
+        TestTransport,
+        BlockchainInterfaceNonClandestine<TestTransport>,
+    ) {
+        let mut transport = TestTransport::default();
+        transaction_counts
+            .iter()
+            .for_each(|count| transport.add_response(json!(count)));
+        let interface = BlockchainInterfaceNonClandestine::new(
+            transport.clone(),
+            make_fake_event_loop_handle(),
+            DEFAULT_CHAIN_ID,
+        );
+        (transport, interface)
+    }
+
+    fn assert_transaction_count_requests(transport: &mut TestTransport, count: usize) {
+        (0..count).for_each(|_| {
+            transport.assert_request(
+                "eth_getTransactionCount",
+                &[
+                    String::from(r#""0x5c361ba8d82fcf0e5538b2a823e9d457a2296725""#),
+                    String::from(r#""pending""#),
+                ],
+            )
+        });
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn first_nonce_is_the_pending_transaction_count() {
+        let (mut transport, interface) = make_interface(&["0x5"]);
+        let subject = NonceManager::new();
+
+        let result = subject.next_nonce(&interface, &make_paying_wallet(b"gdasgsa"));
+
+        assert_eq!(result, Ok(U256::from(5)));
+        assert_transaction_count_requests(&mut transport, 1);
+    }
+
+    #[test]
+    fn nonces_are_sequential_when_the_pending_transaction_count_lags() {
+        let (mut transport, interface) = make_interface(&["0x5", "0x5", "0x6"]);
+        let wallet = make_paying_wallet(b"gdasgsa");
+        let mut subject = NonceManager::new();
+
+        let first = subject.next_nonce(&interface, &wallet).unwrap();
+        subject.nonce_used(first);
+        let second = subject.next_nonce(&interface, &wallet).unwrap();
+        subject.nonce_used(second);
+        let third = subject.next_nonce(&interface, &wallet).unwrap();
+
+        assert_eq!(
+            vec![first, second, third],
+            vec![U256::from(5), U256::from(6), U256::from(7)]
+        );
+        assert_transaction_count_requests(&mut transport, 3);
+    }
+
+    #[test]
+    fn nonce_of_a_failed_send_is_handed_out_again() {
+        let (mut transport, interface) = make_interface(&["0x5", "0x5", "0x5"]);
+        let wallet = make_paying_wallet(b"gdasgsa");
+        let mut subject = NonceManager::new();
+
+        let first = subject.next_nonce(&interface, &wallet).unwrap();
+        subject.nonce_used(first);
+        let failed = subject.next_nonce(&interface, &wallet).unwrap();
+        let retried = subject.next_nonce(&interface, &wallet).unwrap();
+
+        assert_eq!(failed, U256::from(6));
+        assert_eq!(retried, U256::from(6));
+        assert_transaction_count_requests(&mut transport, 3);
+    }
+
+    #[test]
+    fn pending_transaction_count_wins_when_it_is_ahead() {
+        let (mut transport, interface) = make_interface(&["0x9"]);
+        let mut subject = NonceManager::new();
+        subject.nonce_used(U256::from(5));
+
+        let result = subject.next_nonce(&interface, &make_paying_wallet(b"gdasgsa"));
+
+        assert_eq!(result, Ok(U256::from(9)));
+        assert_transaction_count_requests(&mut transport, 1);
+    }
+
+    #[test]
+    fn recording_an_older_nonce_does_not_move_the_next_one_back() {
+        let (mut transport, interface) = make_interface(&["0x0"]);
+        let mut subject = NonceManager::new();
+        subject.nonce_used(U256::from(7));
+        subject.nonce_used(U256::from(3));
+
+        let result = subject.next_nonce(&interface, &make_paying_wallet(b"gdasgsa"));
+
+        assert_eq!(result, Ok(U256::from(8)));
+        assert_transaction_count_requests(&mut transport, 1);
+    }
+
+    #[test]
+    fn failure_to_get_the_transaction_count_is_reported() {
+        let (mut transport, interface) = make_interface(&[]);
+        let subject = NonceManager::new();
+
+        let result = subject.next_nonce(&interface, &make_paying_wallet(b"gdasgsa"));
+
+        assert_eq!(result, Err(BlockchainError::QueryFailed));
+        assert_transaction_count_requests(&mut transport, 1);
+    }
+}
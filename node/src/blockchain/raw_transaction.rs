@@ -28,24 +28,24 @@ pub struct RawTransaction {
 
 impl RawTransaction {
     /// Signs and returns the RLP-encoded transaction
-    pub fn sign(&self, wallet: &Wallet, chain_id: u8) -> Vec<u8> {
+    pub fn sign(&self, wallet: &Wallet, chain_id: u64) -> Vec<u8> {
         let hash = self.tx_hash(chain_id);
-        let sig = ecdsa_sign(&hash, &wallet, chain_id);
+        let (v, sig) = ecdsa_sign(&hash, wallet, chain_id);
         let mut tx = RlpStream::new();
         tx.begin_unbounded_list();
         self.encode(&mut tx);
-        tx.append(&sig.v);
+        tx.append(&v);
         tx.append(&sig.r.to_vec());
         tx.append(&sig.s.to_vec());
         tx.finalize_unbounded_list();
         tx.out()
     }
 
-    fn tx_hash(&self, chain_id: u8) -> Vec<u8> {
+    fn tx_hash(&self, chain_id: u64) -> Vec<u8> {
         let mut hash = RlpStream::new();
         hash.begin_unbounded_list();
         self.encode(&mut hash);
-        hash.append(&vec![chain_id]);
+        hash.append(&chain_id_bytes(chain_id));
         hash.append(&U256::zero());
         hash.append(&U256::zero());
         hash.finalize_unbounded_list();
@@ -66,22 +66,106 @@ impl RawTransaction {
     }
 }
 
-fn ecdsa_sign(hash: &dyn AsRef<[u8]>, wallet: &Wallet, chain_id: u8) -> Signature {
+const FEE_MARKET_TRANSACTION_TYPE: u8 = 0x02;
+
+/// Description of an EIP-1559 Transaction, which offers a fee on top of the block's base fee
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FeeMarketTransaction {
+    /// Nonce
+    pub nonce: U256,
+    /// Recipient (None when contract creation)
+    pub to: Option<Address>,
+    /// Transfered value
+    pub value: U256,
+    /// Most that will go to the miner per unit of gas
+    #[serde(rename = "maxPriorityFeePerGas")]
+    pub max_priority_fee_per_gas: U256,
+    /// Most that will be paid per unit of gas, base fee included
+    #[serde(rename = "maxFeePerGas")]
+    pub max_fee_per_gas: U256,
+    /// Gas limit
+    #[serde(rename = "gasLimit")]
+    pub gas_limit: U256,
+    /// Input data
+    pub data: Vec<u8>,
+}
+
+impl FeeMarketTransaction {
+    /// Signs and returns the EIP-2718 envelope: the transaction type, then the RLP-encoded transaction
+    pub fn sign(&self, wallet: &Wallet, chain_id: u64) -> Vec<u8> {
+        let hash = self.tx_hash(chain_id);
+        let sig = match wallet.sign(&hash) {
+            Ok(s) => s,
+            Err(e) => panic!("{:?}", e),
+        };
+        let mut tx = RlpStream::new();
+        tx.begin_unbounded_list();
+        self.encode(&mut tx, chain_id);
+        tx.append(&sig.v);
+        tx.append(&U256::from_big_endian(&sig.r));
+        tx.append(&U256::from_big_endian(&sig.s));
+        tx.finalize_unbounded_list();
+        Self::envelope(tx.out())
+    }
+
+    fn tx_hash(&self, chain_id: u64) -> Vec<u8> {
+        let mut hash = RlpStream::new();
+        hash.begin_unbounded_list();
+        self.encode(&mut hash, chain_id);
+        hash.finalize_unbounded_list();
+        Self::envelope(hash.out()).keccak256().to_vec()
+    }
+
+    fn encode(&self, s: &mut RlpStream, chain_id: u64) {
+        s.append(&chain_id);
+        s.append(&self.nonce);
+        s.append(&self.max_priority_fee_per_gas);
+        s.append(&self.max_fee_per_gas);
+        s.append(&self.gas_limit);
+        if let Some(ref t) = self.to {
+            s.append(t);
+        } else {
+            s.append(&vec![]);
+        }
+        s.append(&self.value);
+        s.append(&self.data);
+        // No access list
+        s.begin_list(0);
+    }
+
+    fn envelope(payload: Vec<u8>) -> Vec<u8> {
+        let mut envelope = vec![FEE_MARKET_TRANSACTION_TYPE];
+        envelope.extend(payload);
+        envelope
+    }
+}
+
+// Signature's v is a byte, and an EIP-155 v outgrows a byte for every chain ID above 109
+fn ecdsa_sign(hash: &dyn AsRef<[u8]>, wallet: &Wallet, chain_id: u64) -> (u64, Signature) {
     match wallet.sign(&hash) {
-        Ok(s) => Signature {
-            v: s.v + chain_id * 2 + 35,
-            r: s.r,
-            s: s.s,
-        },
+        Ok(s) => (u64::from(s.v) + chain_id * 2 + 35, s),
         Err(e) => panic!("{:?}", e),
     }
 }
 
+// The chain ID's big-endian bytes without leading zeros, as RLP encodes an integer; a chain ID of
+// 0 stays a single zero byte
+fn chain_id_bytes(chain_id: u64) -> Vec<u8> {
+    let bytes = chain_id.to_be_bytes();
+    let first = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len() - 1);
+    bytes[first..].to_vec()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::blockchain::bip32::Bip32ECKeyPair;
+    use crate::test_utils::make_paying_wallet;
     use ethereum_types::H256;
+    use rlp::Rlp;
 
     #[derive(Deserialize)]
     struct Signing {
@@ -100,7 +184,7 @@ mod tests {
         );
 
         let txs: Vec<(RawTransaction, Signing)> = serde_json::from_str(&text_txs_json).unwrap();
-        let chain_id = 0u8;
+        let chain_id = 0u64;
         for (tx, signed) in txs.into_iter() {
             assert_eq!(
                 signed.signed,
@@ -125,7 +209,7 @@ mod tests {
         );
 
         let txs: Vec<(RawTransaction, Signing)> = serde_json::from_str(&text_txs_json).unwrap();
-        let chain_id = 3u8;
+        let chain_id = 3u64;
         for (tx, signed) in txs.into_iter() {
             assert_eq!(
                 signed.signed,
@@ -149,7 +233,7 @@ mod tests {
         );
 
         let txs: Vec<(RawTransaction, Signing)> = serde_json::from_str(&txt_txs_json).unwrap();
-        let chain_id = 3u8;
+        let chain_id = 3u64;
         for (tx, signed) in txs.into_iter() {
             assert_eq!(
                 signed.signed,
@@ -173,7 +257,7 @@ mod tests {
         );
 
         let txs: Vec<(RawTransaction, Signing)> = serde_json::from_str(&txt_txs_json).unwrap();
-        let chain_id = 1u8;
+        let chain_id = 1u64;
         for (tx, signed) in txs.into_iter() {
             assert_eq!(
                 signed.signed,
@@ -186,4 +270,99 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn transaction_for_a_chain_id_past_a_byte_carries_a_wide_v_and_recovers_to_the_signer() {
+        let wallet = make_paying_wallet(b"wide chain id");
+        let chain_id = 11_155_111u64;
+        let subject = RawTransaction {
+            nonce: U256::from(9),
+            to: Some(Address::from_low_u64_be(0x3535)),
+            value: U256::zero(),
+            gas_price: U256::from(1_000_000_000u64),
+            gas_limit: U256::from(0xdbe8),
+            data: vec![169, 5, 156, 187],
+        };
+
+        let result = subject.sign(&wallet, chain_id);
+
+        let rlp = Rlp::new(&result);
+        assert_eq!(rlp.item_count().unwrap(), 9);
+        let v = rlp.val_at::<u64>(6).unwrap();
+        let recovery_id = v - (chain_id * 2 + 35);
+        assert!(recovery_id < 2, "{}", v);
+        let mut unsigned = RlpStream::new_list(9);
+        (0..6).for_each(|index| {
+            unsigned.append_raw(rlp.at(index).unwrap().as_raw(), 1);
+        });
+        unsigned.append(&chain_id);
+        unsigned.append(&U256::zero());
+        unsigned.append(&U256::zero());
+        let mut r = [0u8; 32];
+        rlp.val_at::<U256>(7).unwrap().to_big_endian(&mut r);
+        let mut s = [0u8; 32];
+        rlp.val_at::<U256>(8).unwrap().to_big_endian(&mut s);
+        let signature = Signature {
+            v: recovery_id as u8,
+            r,
+            s,
+        };
+        let signer = *signature
+            .recover(&unsigned.out().keccak256())
+            .unwrap()
+            .address();
+        assert_eq!(signer, wallet.address().0);
+    }
+
+    #[test]
+    fn fee_market_transaction_is_signed_in_a_typed_envelope_that_recovers_to_the_signer() {
+        let wallet = make_paying_wallet(b"fee market");
+        let subject = FeeMarketTransaction {
+            nonce: U256::from(9),
+            to: Some(Address::from_low_u64_be(0x3535)),
+            value: U256::zero(),
+            max_priority_fee_per_gas: U256::from(2_000_000_000u64),
+            max_fee_per_gas: U256::from(42_000_000_000u64),
+            gas_limit: U256::from(0xdbe8),
+            data: vec![169, 5, 156, 187],
+        };
+
+        let result = subject.sign(&wallet, 3);
+
+        assert_eq!(result[0], 0x02);
+        let rlp = Rlp::new(&result[1..]);
+        assert_eq!(rlp.item_count().unwrap(), 12);
+        assert_eq!(rlp.val_at::<u8>(0).unwrap(), 3);
+        assert_eq!(rlp.val_at::<U256>(1).unwrap(), subject.nonce);
+        assert_eq!(
+            rlp.val_at::<U256>(2).unwrap(),
+            subject.max_priority_fee_per_gas
+        );
+        assert_eq!(rlp.val_at::<U256>(3).unwrap(), subject.max_fee_per_gas);
+        assert_eq!(rlp.val_at::<U256>(4).unwrap(), subject.gas_limit);
+        assert_eq!(rlp.val_at::<Address>(5).unwrap(), subject.to.unwrap());
+        assert_eq!(rlp.val_at::<U256>(6).unwrap(), subject.value);
+        assert_eq!(rlp.val_at::<Vec<u8>>(7).unwrap(), subject.data);
+        assert_eq!(rlp.at(8).unwrap().item_count().unwrap(), 0);
+        let mut unsigned = RlpStream::new_list(9);
+        (0..9).for_each(|index| {
+            unsigned.append_raw(rlp.at(index).unwrap().as_raw(), 1);
+        });
+        let mut signed_payload = vec![0x02];
+        signed_payload.extend(unsigned.out());
+        let mut r = [0u8; 32];
+        rlp.val_at::<U256>(10).unwrap().to_big_endian(&mut r);
+        let mut s = [0u8; 32];
+        rlp.val_at::<U256>(11).unwrap().to_big_endian(&mut s);
+        let signature = Signature {
+            v: rlp.val_at::<u8>(9).unwrap(),
+            r,
+            s,
+        };
+        let signer = *signature
+            .recover(&signed_payload.keccak256())
+            .unwrap()
+            .address();
+        assert_eq!(signer, wallet.address().0);
+    }
 }
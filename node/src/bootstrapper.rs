@@ -1,9 +1,13 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
-use crate::accountant::{DEFAULT_PAYABLE_SCAN_INTERVAL, DEFAULT_PAYMENT_RECEIVED_SCAN_INTERVAL};
+use crate::accountant::{
+    DEFAULT_PAYABLE_SCAN_INTERVAL, DEFAULT_PAYMENT_RECEIVED_SCAN_INTERVAL,
+    DEFAULT_PENDING_PAYMENT_SCAN_INTERVAL,
+};
 use crate::actor_system_factory::ActorFactoryReal;
 use crate::actor_system_factory::ActorSystemFactory;
 use crate::actor_system_factory::ActorSystemFactoryReal;
 use crate::blockchain::blockchain_interface::chain_id_from_name;
+use crate::blockchain::failover_transport::FailoverPolicy;
 use crate::config_dao::ConfigDaoReal;
 use crate::crash_test_dummy::CrashTestDummy;
 use crate::database::db_initializer::{DbInitializer, DbInitializerReal};
@@ -12,31 +16,40 @@ use crate::json_discriminator_factory::JsonDiscriminatorFactory;
 use crate::listener_handler::ListenerHandler;
 use crate::listener_handler::ListenerHandlerFactory;
 use crate::listener_handler::ListenerHandlerFactoryReal;
+use crate::metrics::metrics;
+use crate::metrics::metrics_listener::start_metrics_listener;
 use crate::node_configurator::node_configurator_standard::{
     NodeConfiguratorStandardPrivileged, NodeConfiguratorStandardUnprivileged,
 };
-use crate::node_configurator::{DirsWrapper, NodeConfigurator};
+use crate::node_configurator::{DirsWrapper, NodeConfigurator, RealDirsWrapper};
 use crate::persistent_configuration::{PersistentConfiguration, PersistentConfigurationReal};
 use crate::privilege_drop::{IdWrapper, IdWrapperReal};
 use crate::server_initializer::LoggerInitializerWrapper;
 use crate::sub_lib::accountant;
 use crate::sub_lib::accountant::AccountantConfig;
 use crate::sub_lib::blockchain_bridge::BlockchainBridgeConfig;
-use crate::sub_lib::cryptde::CryptDE;
+use crate::sub_lib::cryptde::{CryptDE, PrivateKey};
 use crate::sub_lib::cryptde_null::CryptDENull;
 use crate::sub_lib::cryptde_real::CryptDEReal;
-use crate::sub_lib::logger::Logger;
+use crate::sub_lib::exit_policy::ExitPolicy;
+use crate::sub_lib::logger::{LogFilters, Logger};
 use crate::sub_lib::neighborhood::NodeDescriptor;
-use crate::sub_lib::neighborhood::{NeighborhoodConfig, NeighborhoodMode};
+use crate::sub_lib::neighborhood::{
+    NeighborhoodConfig, NeighborhoodMode, UNLIMITED_ROUTE_COST_BUDGET,
+};
 use crate::sub_lib::node_addr::NodeAddr;
 use crate::sub_lib::socket_server::SocketServer;
 use crate::sub_lib::ui_gateway::UiGatewayConfig;
 use crate::sub_lib::wallet::Wallet;
+use crate::ui_gateway::ui_auth_token::{UiAuthTokenEstablisher, UiAuthTokenEstablisherReal};
 use futures::try_ready;
 use itertools::Itertools;
 use log::LevelFilter;
 use masq_lib::command::StdStreams;
-use masq_lib::constants::{DEFAULT_CHAIN_NAME, DEFAULT_UI_PORT};
+use masq_lib::constants::{
+    DEFAULT_CHAIN_NAME, DEFAULT_CONFIRMATION_DEPTH, DEFAULT_MAX_GAS_PRICE,
+    DEFAULT_SCAN_BLOCK_RANGE, DEFAULT_UI_PORT,
+};
 use masq_lib::crash_point::CrashPoint;
 use masq_lib::shared_schema::ConfiguratorError;
 use std::collections::HashMap;
@@ -270,11 +283,14 @@ impl Display for RealUser {
 pub struct BootstrapperConfig {
     // These fields can be set while privileged without penalty
     pub log_level: LevelFilter,
+    pub log_filters: LogFilters,
     pub dns_servers: Vec<SocketAddr>,
+    pub exit_policy: ExitPolicy,
     pub accountant_config: AccountantConfig,
     pub crash_point: CrashPoint,
     pub clandestine_discriminator_factories: Vec<Box<dyn DiscriminatorFactory>>,
     pub ui_gateway_config: UiGatewayConfig,
+    pub metrics_port_opt: Option<u16>,
     pub blockchain_bridge_config: BlockchainBridgeConfig,
     pub port_configurations: HashMap<u16, PortConfiguration>,
     pub data_directory: PathBuf,
@@ -301,23 +317,35 @@ impl BootstrapperConfig {
         BootstrapperConfig {
             // These fields can be set while privileged without penalty
             log_level: LevelFilter::Off,
+            log_filters: LogFilters::default(),
             dns_servers: vec![],
+            exit_policy: ExitPolicy::default(),
             accountant_config: AccountantConfig {
                 payable_scan_interval: Duration::from_secs(DEFAULT_PAYABLE_SCAN_INTERVAL),
                 payment_received_scan_interval: Duration::from_secs(
                     DEFAULT_PAYMENT_RECEIVED_SCAN_INTERVAL,
                 ),
+                pending_payment_scan_interval: Duration::from_secs(
+                    DEFAULT_PENDING_PAYMENT_SCAN_INTERVAL,
+                ),
             },
             crash_point: CrashPoint::None,
             clandestine_discriminator_factories: vec![],
             ui_gateway_config: UiGatewayConfig {
                 ui_port: DEFAULT_UI_PORT,
                 node_descriptor: String::from(""),
+                ui_auth_token: String::new(),
             },
+            metrics_port_opt: None,
             blockchain_bridge_config: BlockchainBridgeConfig {
                 blockchain_service_url: None,
-                chain_id: 3u8, /*DEFAULT_CHAIN_ID*/
+                blockchain_service_failover: FailoverPolicy::Priority,
+                chain_id: 2u64, /*DEFAULT_CHAIN_ID*/
                 gas_price: 1,
+                max_gas_price: DEFAULT_MAX_GAS_PRICE,
+                batch_payments: false,
+                scan_block_range: DEFAULT_SCAN_BLOCK_RANGE,
+                confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
             },
             port_configurations: HashMap::new(),
             data_directory: PathBuf::new(),
@@ -332,6 +360,7 @@ impl BootstrapperConfig {
             consuming_wallet: None,
             neighborhood_config: NeighborhoodConfig {
                 mode: NeighborhoodMode::ZeroHop,
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             },
         }
     }
@@ -351,6 +380,7 @@ pub struct Bootstrapper {
     listener_handlers: FuturesUnordered<Box<dyn ListenerHandler<Item = (), Error = ()>>>,
     actor_system_factory: Box<dyn ActorSystemFactory>,
     logger_initializer: Box<dyn LoggerInitializerWrapper>,
+    ui_auth_token_establisher: Box<dyn UiAuthTokenEstablisher>,
     config: BootstrapperConfig,
 }
 
@@ -386,6 +416,7 @@ impl SocketServer<BootstrapperConfig> for Bootstrapper {
             self.config.data_directory.clone(),
             &self.config.real_user,
             self.config.log_level,
+            &self.config.log_filters,
             None,
         );
         self.listener_handlers =
@@ -418,9 +449,14 @@ impl SocketServer<BootstrapperConfig> for Bootstrapper {
             .configure(&args.to_vec(), streams)?;
         self.config.merge_unprivileged(unprivileged_config);
         self.establish_clandestine_port();
+        self.config.ui_gateway_config.ui_auth_token = self
+            .ui_auth_token_establisher
+            .establish(&self.config.real_user);
+        let main_private_key_opt = self.establish_main_private_key();
         let (cryptde_ref, _) = Bootstrapper::initialize_cryptdes(
             &self.config.main_cryptde_null_opt,
             &self.config.alias_cryptde_null_opt,
+            &main_private_key_opt,
             self.config.blockchain_bridge_config.chain_id,
         );
         self.config.ui_gateway_config.node_descriptor = Bootstrapper::report_local_descriptor(
@@ -432,6 +468,11 @@ impl SocketServer<BootstrapperConfig> for Bootstrapper {
         let stream_handler_pool_subs = self
             .actor_system_factory
             .make_and_start_actors(self.config.clone(), Box::new(ActorFactoryReal {}));
+        if let Some(metrics_port) = self.config.metrics_port_opt {
+            if let Err(e) = start_metrics_listener(metrics_port, metrics()) {
+                panic!("Could not serve metrics on port {}: {}", metrics_port, e)
+            }
+        }
 
         for f in self.listener_handlers.iter_mut() {
             f.bind_subs(stream_handler_pool_subs.add_sub.clone());
@@ -448,6 +489,9 @@ impl Bootstrapper {
                 FuturesUnordered::<Box<dyn ListenerHandler<Item = (), Error = ()>>>::new(),
             actor_system_factory: Box::new(ActorSystemFactoryReal {}),
             logger_initializer,
+            ui_auth_token_establisher: Box::new(UiAuthTokenEstablisherReal::new(Box::new(
+                RealDirsWrapper {},
+            ))),
             config: BootstrapperConfig::new(),
         }
     }
@@ -460,6 +504,7 @@ impl Bootstrapper {
         Self::initialize_cryptdes(
             main_cryptde_null_opt,
             alias_cryptde_null_opt,
+            &None,
             masq_lib::test_utils::utils::DEFAULT_CHAIN_ID,
         )
     }
@@ -467,13 +512,23 @@ impl Bootstrapper {
     fn initialize_cryptdes(
         main_cryptde_null_opt: &Option<CryptDENull>,
         alias_cryptde_null_opt: &Option<CryptDENull>,
-        chain_id: u8,
+        main_private_key_opt: &Option<PrivateKey>,
+        chain_id: u64,
     ) -> (&'static dyn CryptDE, &'static dyn CryptDE) {
         match main_cryptde_null_opt {
             Some(cryptde_null) => unsafe {
                 MAIN_CRYPTDE_BOX_OPT = Some(Box::new(cryptde_null.clone()))
             },
-            None => unsafe { MAIN_CRYPTDE_BOX_OPT = Some(Box::new(CryptDEReal::new(chain_id))) },
+            None => {
+                let cryptde_real = match main_private_key_opt {
+                    Some(private_key) => CryptDEReal::from_private_key(private_key, chain_id)
+                        .unwrap_or_else(|e| {
+                            panic!("Can't continue; the Node's private key is corrupt: {}", e)
+                        }),
+                    None => CryptDEReal::new(chain_id),
+                };
+                unsafe { MAIN_CRYPTDE_BOX_OPT = Some(Box::new(cryptde_real)) }
+            }
         }
         match alias_cryptde_null_opt {
             Some(cryptde_null) => unsafe {
@@ -488,7 +543,7 @@ impl Bootstrapper {
         cryptde: &dyn CryptDE,
         node_addr_opt: Option<NodeAddr>,
         streams: &mut StdStreams<'_>,
-        chain_id: u8,
+        chain_id: u64,
     ) -> String {
         let descriptor = match node_addr_opt {
             Some(node_addr) => {
@@ -511,6 +566,51 @@ impl Bootstrapper {
         descriptor
     }
 
+    // The main key pair is kept in the database so that the Node's public key, and therefore its
+    // descriptor, survives a restart. Without a database password there's nowhere to keep it.
+    fn establish_main_private_key(&self) -> Option<PrivateKey> {
+        if self.config.main_cryptde_null_opt.is_some() {
+            return None;
+        }
+        let db_password = self.config.db_password_opt.as_ref()?;
+        let chain_id = self.config.blockchain_bridge_config.chain_id;
+        let conn = DbInitializerReal::new()
+            .initialize(&self.config.data_directory, chain_id, true)
+            .expect("Cannot initialize database");
+        let persistent_config = PersistentConfigurationReal::from(conn);
+        Some(Self::load_or_create_main_private_key(
+            &persistent_config,
+            db_password,
+            chain_id,
+        ))
+    }
+
+    fn load_or_create_main_private_key(
+        persistent_config: &dyn PersistentConfiguration,
+        db_password: &str,
+        chain_id: u64,
+    ) -> PrivateKey {
+        match persistent_config.node_private_key(db_password) {
+            Ok(Some(private_key)) => private_key,
+            Ok(None) => {
+                let private_key = CryptDEReal::new(chain_id).private_key().clone();
+                persistent_config
+                    .set_node_private_key(&private_key, db_password)
+                    .unwrap_or_else(|e| {
+                        panic!(
+                            "Can't continue; couldn't save the Node's private key: {:?}",
+                            e
+                        )
+                    });
+                private_key
+            }
+            Err(e) => panic!(
+                "Can't continue; couldn't load the Node's private key: {:?}",
+                e
+            ),
+        }
+    }
+
     fn establish_clandestine_port(&mut self) {
         if let NeighborhoodMode::Standard(node_addr, neighbor_configs, rate_pack) =
             &self.config.neighborhood_config.mode
@@ -545,6 +645,7 @@ impl Bootstrapper {
                     neighbor_configs.clone(),
                     rate_pack.clone(),
                 ),
+                route_cost_budget: self.config.neighborhood_config.route_cost_budget,
             };
         }
         self.config
@@ -565,7 +666,9 @@ mod tests {
     use crate::node_test_utils::make_stream_handler_pool_subs_from;
     use crate::node_test_utils::TestLogOwner;
     use crate::node_test_utils::{extract_log, IdWrapperMock, MockDirsWrapper};
-    use crate::persistent_configuration::{PersistentConfiguration, PersistentConfigurationReal};
+    use crate::persistent_configuration::{
+        PersistentConfigError, PersistentConfiguration, PersistentConfigurationReal,
+    };
     use crate::server_initializer::test_utils::LoggerInitializerWrapperMock;
     use crate::stream_handler_pool::StreamHandlerPoolSubs;
     use crate::stream_messages::AddStreamMsg;
@@ -578,12 +681,14 @@ mod tests {
     use crate::test_utils::logging::TestLog;
     use crate::test_utils::logging::TestLogHandler;
     use crate::test_utils::main_cryptde;
+    use crate::test_utils::persistent_configuration_mock::PersistentConfigurationMock;
     use crate::test_utils::recorder::make_recorder;
     use crate::test_utils::recorder::RecordAwaiter;
     use crate::test_utils::recorder::Recording;
     use crate::test_utils::tokio_wrapper_mocks::ReadHalfWrapperMock;
     use crate::test_utils::tokio_wrapper_mocks::WriteHalfWrapperMock;
     use crate::test_utils::{assert_contains, rate_pack, ArgsBuilder};
+    use crate::ui_gateway::ui_auth_token::test_utils::UiAuthTokenEstablisherMock;
     use actix::Recipient;
     use actix::System;
     use lazy_static::lazy_static;
@@ -592,12 +697,14 @@ mod tests {
     use masq_lib::test_utils::environment_guard::ClapGuard;
     use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
     use masq_lib::test_utils::utils::{ensure_node_home_directory_exists, DEFAULT_CHAIN_ID};
+    use masq_lib::utils::{find_free_port, localhost};
     use regex::Regex;
     use std::cell::RefCell;
     use std::io;
     use std::io::ErrorKind;
+    use std::io::{Read, Write};
     use std::marker::Sync;
-    use std::net::{IpAddr, SocketAddr};
+    use std::net::{IpAddr, SocketAddr, TcpStream};
     use std::ops::DerefMut;
     use std::str::FromStr;
     use std::sync::mpsc;
@@ -844,7 +951,7 @@ mod tests {
     }
 
     #[test]
-    fn initialize_as_privileged_with_no_args_binds_http_and_tls_ports() {
+    fn initialize_as_privileged_with_no_args_binds_http_tls_and_socks_ports() {
         let _lock = INITIALIZATION.lock();
         let (first_handler, first_handler_log) =
             extract_log(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
@@ -852,10 +959,13 @@ mod tests {
             extract_log(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
         let (third_handler, third_handler_log) =
             extract_log(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
+        let (fourth_handler, fourth_handler_log) =
+            extract_log(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
         let mut subject = BootstrapperBuilder::new()
             .add_listener_handler(Box::new(first_handler))
             .add_listener_handler(Box::new(second_handler))
             .add_listener_handler(Box::new(third_handler))
+            .add_listener_handler(Box::new(fourth_handler))
             .build();
 
         subject
@@ -869,6 +979,7 @@ mod tests {
         all_calls.extend(first_handler_log.lock().unwrap().dump());
         all_calls.extend(second_handler_log.lock().unwrap().dump());
         all_calls.extend(third_handler_log.lock().unwrap().dump());
+        all_calls.extend(fourth_handler_log.lock().unwrap().dump());
         assert!(
             all_calls.contains(&String::from(
                 "bind_port_and_configuration (80, PortConfiguration {is_clandestine: false, ...})"
@@ -883,7 +994,14 @@ mod tests {
             "{:?}",
             all_calls
         );
-        assert_eq!(all_calls.len(), 2, "{:?}", all_calls);
+        assert!(
+            all_calls.contains(&String::from(
+                "bind_port_and_configuration (1080, PortConfiguration {is_clandestine: false, ...})"
+            )),
+            "{:?}",
+            all_calls
+        );
+        assert_eq!(all_calls.len(), 3, "{:?}", all_calls);
     }
 
     #[test]
@@ -892,9 +1010,11 @@ mod tests {
         let _lock = INITIALIZATION.lock();
         let first_handler = Box::new(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
         let second_handler = Box::new(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
+        let third_handler = Box::new(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
         let mut subject = BootstrapperBuilder::new()
             .add_listener_handler(first_handler)
             .add_listener_handler(second_handler)
+            .add_listener_handler(third_handler)
             .build();
 
         subject
@@ -933,6 +1053,9 @@ mod tests {
         listener_handler_factory.add(Box::new(
             ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())),
         ));
+        listener_handler_factory.add(Box::new(
+            ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())),
+        ));
         let mut subject = Bootstrapper::new(Box::new(logger_initializer));
         subject.listener_handler_factory = Box::new(listener_handler_factory);
         let args: Vec<String> = ArgsBuilder::new()
@@ -953,6 +1076,7 @@ mod tests {
                 data_dir,
                 RealUser::new(Some(123), Some(456), Some("/home/booga".into())),
                 LevelFilter::Warn,
+                LogFilters::default(),
                 None,
             )]
         )
@@ -1013,6 +1137,89 @@ For more information try --help".to_string()
         assert!(!config.ui_gateway_config.node_descriptor.is_empty());
     }
 
+    #[test]
+    fn initialize_as_unprivileged_serves_metrics_if_metrics_port_is_specified() {
+        let _lock = INITIALIZATION.lock();
+        let data_dir = ensure_node_home_directory_exists(
+            "bootstrapper",
+            "initialize_as_unprivileged_serves_metrics_if_metrics_port_is_specified",
+        );
+        let metrics_port = find_free_port();
+        let mut config = BootstrapperConfig::new();
+        config.clandestine_port_opt = Some(1234);
+        config.data_directory = data_dir.clone();
+        config.metrics_port_opt = Some(metrics_port);
+        let mut subject = BootstrapperBuilder::new()
+            .add_listener_handler(Box::new(
+                ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())),
+            ))
+            .config(config)
+            .build();
+
+        subject
+            .initialize_as_unprivileged(
+                &[
+                    "MASQNode".to_string(),
+                    String::from("--ip"),
+                    String::from("1.2.3.4"),
+                    String::from("--data-directory"),
+                    data_dir.to_str().unwrap().to_string(),
+                ],
+                &mut FakeStreamHolder::new().streams(),
+            )
+            .unwrap();
+
+        let mut stream = TcpStream::connect(SocketAddr::new(localhost(), metrics_port)).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+    }
+
+    #[test]
+    fn initialize_as_unprivileged_establishes_ui_auth_token_for_real_user() {
+        let _lock = INITIALIZATION.lock();
+        let data_dir = ensure_node_home_directory_exists(
+            "bootstrapper",
+            "initialize_as_unprivileged_establishes_ui_auth_token_for_real_user",
+        );
+        let real_user = RealUser::new(Some(123), Some(456), Some(data_dir.clone()));
+        let mut config = BootstrapperConfig::new();
+        config.clandestine_port_opt = Some(1234);
+        config.data_directory = data_dir.clone();
+        config.real_user = real_user.clone();
+        let establish_params_arc = Arc::new(Mutex::new(vec![]));
+        let ui_auth_token_establisher = UiAuthTokenEstablisherMock::new()
+            .establish_params(&establish_params_arc)
+            .establish_result("secret token");
+        let mut subject = BootstrapperBuilder::new()
+            .add_listener_handler(Box::new(
+                ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())),
+            ))
+            .ui_auth_token_establisher(Box::new(ui_auth_token_establisher))
+            .config(config)
+            .build();
+
+        subject
+            .initialize_as_unprivileged(
+                &[
+                    "MASQNode".to_string(),
+                    String::from("--ip"),
+                    String::from("1.2.3.4"),
+                    String::from("--data-directory"),
+                    data_dir.to_str().unwrap().to_string(),
+                ],
+                &mut FakeStreamHolder::new().streams(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            subject.config.ui_gateway_config.ui_auth_token,
+            "secret token".to_string()
+        );
+        assert_eq!(*establish_params_arc.lock().unwrap(), vec![real_user]);
+    }
+
     #[test]
     fn initialize_as_unprivileged_sets_gas_price_on_blockchain_config() {
         let _lock = INITIALIZATION.lock();
@@ -1121,6 +1328,9 @@ For more information try --help".to_string()
             .add_listener_handler(Box::new(
                 ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())),
             ))
+            .add_listener_handler(Box::new(
+                ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())),
+            ))
             .build();
 
         subject
@@ -1169,7 +1379,8 @@ For more information try --help".to_string()
     #[test]
     fn initialize_cryptde_without_cryptde_null_uses_cryptde_real() {
         let _lock = INITIALIZATION.lock();
-        let (cryptde_init, _) = Bootstrapper::initialize_cryptdes(&None, &None, DEFAULT_CHAIN_ID);
+        let (cryptde_init, _) =
+            Bootstrapper::initialize_cryptdes(&None, &None, &None, DEFAULT_CHAIN_ID);
 
         assert_eq!(main_cryptde_ref().public_key(), cryptde_init.public_key());
         // Brittle assertion: this may not be true forever
@@ -1184,12 +1395,144 @@ For more information try --help".to_string()
         let cryptde_null_public_key = cryptde_null.public_key().clone();
 
         let (cryptde, _) =
-            Bootstrapper::initialize_cryptdes(&Some(cryptde_null), &None, DEFAULT_CHAIN_ID);
+            Bootstrapper::initialize_cryptdes(&Some(cryptde_null), &None, &None, DEFAULT_CHAIN_ID);
 
         assert_eq!(cryptde.public_key(), &cryptde_null_public_key);
         assert_eq!(main_cryptde_ref().public_key(), cryptde.public_key());
     }
 
+    #[test]
+    fn initialize_cryptde_with_private_key_uses_cryptde_real_built_from_it() {
+        let _lock = INITIALIZATION.lock();
+        let original = CryptDEReal::new(DEFAULT_CHAIN_ID);
+
+        let (cryptde, _) = Bootstrapper::initialize_cryptdes(
+            &None,
+            &None,
+            &Some(original.private_key().clone()),
+            DEFAULT_CHAIN_ID,
+        );
+
+        assert_eq!(cryptde.public_key(), original.public_key());
+        assert_eq!(main_cryptde_ref().public_key(), original.public_key());
+    }
+
+    #[test]
+    fn load_or_create_main_private_key_uses_stored_key_if_present() {
+        let node_private_key_params_arc = Arc::new(Mutex::new(vec![]));
+        let persistent_config = PersistentConfigurationMock::new()
+            .node_private_key_params(&node_private_key_params_arc)
+            .node_private_key_result(Ok(Some(PrivateKey::new(b"stored key"))));
+
+        let result = Bootstrapper::load_or_create_main_private_key(
+            &persistent_config,
+            "password",
+            DEFAULT_CHAIN_ID,
+        );
+
+        assert_eq!(result, PrivateKey::new(b"stored key"));
+        let node_private_key_params = node_private_key_params_arc.lock().unwrap();
+        assert_eq!(*node_private_key_params, vec!["password".to_string()]);
+    }
+
+    #[test]
+    fn load_or_create_main_private_key_stores_new_key_if_none_present() {
+        let set_node_private_key_params_arc = Arc::new(Mutex::new(vec![]));
+        let persistent_config = PersistentConfigurationMock::new()
+            .node_private_key_result(Ok(None))
+            .set_node_private_key_params(&set_node_private_key_params_arc)
+            .set_node_private_key_result(Ok(()));
+
+        let result = Bootstrapper::load_or_create_main_private_key(
+            &persistent_config,
+            "password",
+            DEFAULT_CHAIN_ID,
+        );
+
+        let set_node_private_key_params = set_node_private_key_params_arc.lock().unwrap();
+        assert_eq!(
+            *set_node_private_key_params,
+            vec![(result.clone(), "password".to_string())]
+        );
+        assert!(CryptDEReal::from_private_key(&result, DEFAULT_CHAIN_ID).is_ok());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Can't continue; couldn't load the Node's private key: PasswordError"
+    )]
+    fn load_or_create_main_private_key_panics_if_password_is_wrong() {
+        let persistent_config = PersistentConfigurationMock::new()
+            .node_private_key_result(Err(PersistentConfigError::PasswordError));
+
+        Bootstrapper::load_or_create_main_private_key(
+            &persistent_config,
+            "password",
+            DEFAULT_CHAIN_ID,
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Can't continue; couldn't save the Node's private key: DatabaseError(\"booga\")"
+    )]
+    fn load_or_create_main_private_key_panics_if_new_key_cant_be_saved() {
+        let persistent_config = PersistentConfigurationMock::new()
+            .node_private_key_result(Ok(None))
+            .set_node_private_key_result(Err(PersistentConfigError::DatabaseError(
+                "booga".to_string(),
+            )));
+
+        Bootstrapper::load_or_create_main_private_key(
+            &persistent_config,
+            "password",
+            DEFAULT_CHAIN_ID,
+        );
+    }
+
+    #[test]
+    fn establish_main_private_key_keeps_the_same_key_from_run_to_run() {
+        let data_dir = ensure_node_home_directory_exists(
+            "bootstrapper",
+            "establish_main_private_key_keeps_the_same_key_from_run_to_run",
+        );
+        let mut config = BootstrapperConfig::new();
+        config.data_directory = data_dir;
+        config.blockchain_bridge_config.chain_id = DEFAULT_CHAIN_ID;
+        config.db_password_opt = Some("password".to_string());
+        let first_run = BootstrapperBuilder::new().config(config.clone()).build();
+        let second_run = BootstrapperBuilder::new().config(config).build();
+
+        let first_key = first_run.establish_main_private_key();
+        let second_key = second_run.establish_main_private_key();
+
+        assert!(first_key.is_some());
+        assert_eq!(first_key, second_key);
+    }
+
+    #[test]
+    fn establish_main_private_key_does_nothing_without_a_database_password() {
+        let mut config = BootstrapperConfig::new();
+        config.db_password_opt = None;
+        let subject = BootstrapperBuilder::new().config(config).build();
+
+        let result = subject.establish_main_private_key();
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn establish_main_private_key_does_nothing_when_main_cryptde_is_null() {
+        let mut config = BootstrapperConfig::new();
+        config.db_password_opt = Some("password".to_string());
+        config.main_cryptde_null_opt = Some(main_cryptde().clone());
+        let subject = BootstrapperBuilder::new().config(config).build();
+
+        let result = subject.establish_main_private_key();
+
+        assert_eq!(result, None);
+    }
+
     #[test]
     fn initialize_cryptde_and_report_local_descriptor_with_ip_address() {
         let _lock = INITIALIZATION.lock();
@@ -1203,7 +1546,7 @@ For more information try --help".to_string()
             let mut streams = holder.streams();
 
             let (cryptde_ref, _) =
-                Bootstrapper::initialize_cryptdes(&None, &None, DEFAULT_CHAIN_ID);
+                Bootstrapper::initialize_cryptdes(&None, &None, &None, DEFAULT_CHAIN_ID);
             Bootstrapper::report_local_descriptor(
                 cryptde_ref,
                 Some(node_addr),
@@ -1260,7 +1603,7 @@ For more information try --help".to_string()
             let mut streams = holder.streams();
 
             let (main_cryptde_ref, alias_cryptde_ref) =
-                Bootstrapper::initialize_cryptdes(&None, &None, DEFAULT_CHAIN_ID);
+                Bootstrapper::initialize_cryptdes(&None, &None, &None, DEFAULT_CHAIN_ID);
             Bootstrapper::report_local_descriptor(
                 main_cryptde_ref,
                 None,
@@ -1323,11 +1666,14 @@ For more information try --help".to_string()
             extract_log(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
         let (another_listener_handler, _) =
             extract_log(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
+        let (yet_another_listener_handler, _) =
+            extract_log(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
         let (clandestine_listener_handler, clandestine_listener_handler_log_arc) =
             extract_log(ListenerHandlerNull::new(vec![]).bind_port_result(Ok(())));
         let mut subject = BootstrapperBuilder::new()
             .add_listener_handler(Box::new(one_listener_handler))
             .add_listener_handler(Box::new(another_listener_handler))
+            .add_listener_handler(Box::new(yet_another_listener_handler))
             .add_listener_handler(Box::new(clandestine_listener_handler))
             .build();
         let mut holder = FakeStreamHolder::new();
@@ -1384,6 +1730,8 @@ For more information try --help".to_string()
         let another_listener_handler = ListenerHandlerNull::new(vec![]).bind_port_result(Ok(()));
         let yet_another_listener_handler =
             ListenerHandlerNull::new(vec![]).bind_port_result(Ok(()));
+        let still_another_listener_handler =
+            ListenerHandlerNull::new(vec![]).bind_port_result(Ok(()));
         let actor_system_factory = ActorSystemFactoryMock::new();
         let mut config = BootstrapperConfig::new();
         config.data_directory = data_dir;
@@ -1392,6 +1740,7 @@ For more information try --help".to_string()
             .add_listener_handler(Box::new(one_listener_handler))
             .add_listener_handler(Box::new(another_listener_handler))
             .add_listener_handler(Box::new(yet_another_listener_handler))
+            .add_listener_handler(Box::new(still_another_listener_handler))
             .config(config)
             .build();
         subject
@@ -1455,6 +1804,8 @@ For more information try --help".to_string()
             ListenerHandlerNull::new(vec![first_message, second_message]).bind_port_result(Ok(()));
         let another_listener_handler =
             ListenerHandlerNull::new(vec![third_message]).bind_port_result(Ok(()));
+        let yet_another_listener_handler =
+            ListenerHandlerNull::new(vec![]).bind_port_result(Ok(()));
         let mut actor_system_factory = ActorSystemFactoryMock::new();
         let awaiter = actor_system_factory
             .stream_handler_pool_cluster
@@ -1470,6 +1821,7 @@ For more information try --help".to_string()
             .actor_system_factory(Box::new(actor_system_factory))
             .add_listener_handler(Box::new(one_listener_handler))
             .add_listener_handler(Box::new(another_listener_handler))
+            .add_listener_handler(Box::new(yet_another_listener_handler))
             .build();
         let args = vec![
             String::from("MASQNode"),
@@ -1525,6 +1877,7 @@ For more information try --help".to_string()
                 ))],
                 rate_pack(100),
             ),
+            route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
         };
         config.data_directory = data_dir.clone();
         config.clandestine_port_opt = Some(1234);
@@ -1594,6 +1947,7 @@ For more information try --help".to_string()
                 ))],
                 rate_pack(100),
             ),
+            route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
         };
         config.data_directory = data_dir.clone();
         config.clandestine_port_opt = None;
@@ -1645,6 +1999,7 @@ For more information try --help".to_string()
                 ))],
                 rate_pack(100),
             ),
+            route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
         };
         let listener_handler = ListenerHandlerNull::new(vec![]);
         let mut subject = BootstrapperBuilder::new()
@@ -1680,6 +2035,7 @@ For more information try --help".to_string()
                 DEFAULT_CHAIN_ID == chain_id_from_name(DEFAULT_CHAIN_NAME),
                 cryptde,
             ))]),
+            route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
         };
         let listener_handler = ListenerHandlerNull::new(vec![]);
         let mut subject = BootstrapperBuilder::new()
@@ -1708,6 +2064,7 @@ For more information try --help".to_string()
         config.clandestine_port_opt = None;
         config.neighborhood_config = NeighborhoodConfig {
             mode: NeighborhoodMode::ZeroHop,
+            route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
         };
         let listener_handler = ListenerHandlerNull::new(vec![]);
         let mut subject = BootstrapperBuilder::new()
@@ -1859,6 +2216,7 @@ For more information try --help".to_string()
         actor_system_factory: Box<dyn ActorSystemFactory>,
         log_initializer_wrapper: Box<dyn LoggerInitializerWrapper>,
         listener_handler_factory: ListenerHandlerFactoryMock,
+        ui_auth_token_establisher: Box<dyn UiAuthTokenEstablisher>,
         config: BootstrapperConfig,
     }
 
@@ -1869,10 +2227,21 @@ For more information try --help".to_string()
                 log_initializer_wrapper: Box::new(LoggerInitializerWrapperMock::new()),
                 // Don't modify this line unless you've already looked at DispatcherBuilder::add_listener_handler().
                 listener_handler_factory: ListenerHandlerFactoryMock::new(),
+                ui_auth_token_establisher: Box::new(
+                    UiAuthTokenEstablisherMock::new().establish_result("booga"),
+                ),
                 config: BootstrapperConfig::new(),
             }
         }
 
+        fn ui_auth_token_establisher(
+            mut self,
+            ui_auth_token_establisher: Box<dyn UiAuthTokenEstablisher>,
+        ) -> BootstrapperBuilder {
+            self.ui_auth_token_establisher = ui_auth_token_establisher;
+            self
+        }
+
         fn actor_system_factory(
             mut self,
             actor_system_factory: Box<dyn ActorSystemFactory>,
@@ -1902,6 +2271,7 @@ For more information try --help".to_string()
                     Box<dyn ListenerHandler<Item = (), Error = ()>>,
                 >::new(),
                 logger_initializer: self.log_initializer_wrapper,
+                ui_auth_token_establisher: self.ui_auth_token_establisher,
                 config: self.config,
             }
         }
@@ -87,81 +87,21 @@ impl ConfigDao for ConfigDaoReal {
         }
     }
 
+    // Either every encrypted value ends up under the new password, or none of them does.
     fn change_password(
         &self,
         old_password_opt: Option<&str>,
         new_password: &str,
     ) -> Result<(), ConfigDaoError> {
-        if let Some(old_password) = old_password_opt {
-            match self.check_password(old_password) {
-                Ok(true) => (),
-                Ok(false) => return Err(ConfigDaoError::PasswordError),
-                Err(ConfigDaoError::NotPresent) => return Err(ConfigDaoError::PasswordError),
-                Err(e) => return Err(ConfigDaoError::DatabaseError(format!("{:?}", e))),
-            }
-        } else if self.check_password("bad password") != Err(ConfigDaoError::NotPresent) {
-            return Err(ConfigDaoError::PasswordError);
-        }
-        let example_data: Vec<u8> = [0..32]
-            .iter()
-            .map(|_| rand::thread_rng().gen::<u8>())
-            .collect();
-        let example_encrypted = match Bip39::encrypt_bytes(&example_data, new_password) {
-            Ok(bytes) => bytes,
-            Err(e) => return Err(ConfigDaoError::CryptoError(format!("{:?}", e))),
-        };
-        self.set_string("example_encrypted", &example_encrypted)?;
-        if old_password_opt == None {
-            return Ok(());
-        }
-        let encrypted_column_names: Vec<String> = {
-            let mut stmt = self
-                .conn
-                .prepare("select name from config where encrypted = 1")
-                .expect("Couldn't create statement to select names");
-            stmt.query_map(NO_PARAMS, |row| {
-                let column_name: String = row.get(0).expect("Row has no name");
-                Ok(column_name)
-            })
-            .optional()
-            .expect("Config table is corrupt 1")
-            .expect("Config table is corrupt 2")
-            .flatten()
-            .collect()
-        };
-        for name in encrypted_column_names {
-            match self.get_string(&name) {
-                Err(ConfigDaoError::NotPresent) => (),
-                Err(e) => return Err(e),
-                Ok(encrypted_value) => {
-                    match Bip39::decrypt_bytes(
-                        &encrypted_value,
-                        old_password_opt.expect("Old password disappeared"),
-                    ) {
-                        Err(e) => {
-                            return Err(ConfigDaoError::DatabaseError(format!(
-                                "Corrupt encrypted value for {}: {:?}",
-                                name, e
-                            )))
-                        }
-                        Ok(plain_data) => {
-                            let reencrypted =
-                                match Bip39::encrypt_bytes(&plain_data.as_slice(), new_password) {
-                                    Err(e) => {
-                                        return Err(ConfigDaoError::DatabaseError(format!(
-                                            "Error reencrypting {}: {:?}",
-                                            name, e
-                                        )))
-                                    }
-                                    Ok(s) => s,
-                                };
-                            self.set_string(&name, &reencrypted)?;
-                        }
-                    }
-                }
+        self.execute("savepoint change_password")?;
+        match self.reencrypt_everything(old_password_opt, new_password) {
+            Ok(()) => self.execute("release change_password"),
+            Err(e) => {
+                self.execute("rollback to change_password")?;
+                self.execute("release change_password")?;
+                Err(e)
             }
         }
-        Ok(())
     }
 
     fn get_string(&self, name: &str) -> Result<String, ConfigDaoError> {
@@ -270,6 +210,90 @@ impl ConfigDaoReal {
         ConfigDaoReal { conn }
     }
 
+    fn reencrypt_everything(
+        &self,
+        old_password_opt: Option<&str>,
+        new_password: &str,
+    ) -> Result<(), ConfigDaoError> {
+        if let Some(old_password) = old_password_opt {
+            match self.check_password(old_password) {
+                Ok(true) => (),
+                Ok(false) => return Err(ConfigDaoError::PasswordError),
+                Err(ConfigDaoError::NotPresent) => return Err(ConfigDaoError::PasswordError),
+                Err(e) => return Err(ConfigDaoError::DatabaseError(format!("{:?}", e))),
+            }
+        } else if self.check_password("bad password") != Err(ConfigDaoError::NotPresent) {
+            return Err(ConfigDaoError::PasswordError);
+        }
+        let example_data: Vec<u8> = (0..32).map(|_| rand::thread_rng().gen::<u8>()).collect();
+        let example_encrypted = match Bip39::encrypt_bytes(&example_data, new_password) {
+            Ok(bytes) => bytes,
+            Err(e) => return Err(ConfigDaoError::CryptoError(format!("{:?}", e))),
+        };
+        self.set_string("example_encrypted", &example_encrypted)?;
+        if old_password_opt.is_none() {
+            return Ok(());
+        }
+        let encrypted_column_names: Vec<String> = {
+            let mut stmt = self
+                .conn
+                .prepare("select name from config where encrypted = 1")
+                .expect("Couldn't create statement to select names");
+            stmt.query_map(NO_PARAMS, |row| {
+                let column_name: String = row.get(0).expect("Row has no name");
+                Ok(column_name)
+            })
+            .optional()
+            .expect("Config table is corrupt 1")
+            .expect("Config table is corrupt 2")
+            .flatten()
+            .collect()
+        };
+        for name in encrypted_column_names {
+            match self.get_string(&name) {
+                Err(ConfigDaoError::NotPresent) => (),
+                Err(e) => return Err(e),
+                Ok(encrypted_value) => {
+                    match Bip39::decrypt_bytes(
+                        &encrypted_value,
+                        old_password_opt.expect("Old password disappeared"),
+                    ) {
+                        Err(e) => {
+                            return Err(ConfigDaoError::DatabaseError(format!(
+                                "Corrupt encrypted value for {}: {:?}",
+                                name, e
+                            )))
+                        }
+                        Ok(plain_data) => {
+                            let reencrypted =
+                                match Bip39::encrypt_bytes(&plain_data.as_slice(), new_password) {
+                                    Err(e) => {
+                                        return Err(ConfigDaoError::DatabaseError(format!(
+                                            "Error reencrypting {}: {:?}",
+                                            name, e
+                                        )))
+                                    }
+                                    Ok(s) => s,
+                                };
+                            self.set_string(&name, &reencrypted)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn execute(&self, sql: &str) -> Result<(), ConfigDaoError> {
+        match self.conn.prepare(sql) {
+            Ok(mut stmt) => match stmt.execute(NO_PARAMS) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ConfigDaoError::DatabaseError(format!("{}", e))),
+            },
+            Err(e) => Err(ConfigDaoError::DatabaseError(format!("{}", e))),
+        }
+    }
+
     fn try_get(&self, name: &str) -> Result<String, ConfigDaoError> {
         let mut stmt = match self.conn.prepare("select value from config where name = ?") {
             Ok(stmt) => stmt,
@@ -322,7 +346,7 @@ fn handle_update_execution(result: rusqlite::Result<usize>) -> Result<(), Config
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::blockchain::blockchain_interface::ROPSTEN_TESTNET_CONTRACT_CREATION_BLOCK;
+    use crate::blockchain::blockchain_interface::contract_creation_block_from_chain_id;
     use crate::database::db_initializer::{
         DbInitializer, DbInitializerReal, CURRENT_SCHEMA_VERSION,
     };
@@ -353,7 +377,7 @@ mod tests {
             &result,
             &(
                 "start_block".to_string(),
-                Some(ROPSTEN_TESTNET_CONTRACT_CREATION_BLOCK.to_string()),
+                Some(contract_creation_block_from_chain_id(DEFAULT_CHAIN_ID).to_string()),
             ),
         );
         assert_contains(&result, &("seed".to_string(), None));
@@ -445,6 +469,25 @@ mod tests {
         assert!(subject.check_password("password").unwrap());
     }
 
+    #[test]
+    fn setting_the_password_encrypts_a_full_size_example() {
+        let home_dir = ensure_node_home_directory_exists(
+            "node",
+            "setting_the_password_encrypts_a_full_size_example",
+        );
+        let subject = ConfigDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+
+        subject.change_password(None, "password").unwrap();
+
+        let example_encrypted = subject.get_string("example_encrypted").unwrap();
+        let example = Bip39::decrypt_bytes(&example_encrypted, "password").unwrap();
+        assert_eq!(example.as_slice().len(), 32);
+    }
+
     #[test]
     fn change_password_complains_if_given_no_old_password_when_an_old_password_exists() {
         let home_dir = ensure_node_home_directory_exists(
@@ -565,6 +608,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn change_password_changes_nothing_if_any_value_cannot_be_reencrypted() {
+        let home_dir = ensure_node_home_directory_exists(
+            "node",
+            "change_password_changes_nothing_if_any_value_cannot_be_reencrypted",
+        );
+        let subject = ConfigDaoReal::new(
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap(),
+        );
+        let old_password = "old password";
+        let new_password = "new password";
+        let ciphertext = PlainData::new(&[1, 2, 3, 4]);
+        subject.add_string_rows(vec![("encrypted_one", true), ("encrypted_two", true)]);
+        subject.change_password(None, old_password).unwrap();
+        subject
+            .set_bytes_e("encrypted_one", &ciphertext, old_password)
+            .unwrap();
+        let stray_ciphertext = Bip39::encrypt_bytes(&[4, 3, 2, 1], "other password").unwrap();
+        subject
+            .set_string("encrypted_two", &stray_ciphertext)
+            .unwrap();
+
+        let result = subject.change_password(Some(old_password), new_password);
+
+        match result {
+            Err(ConfigDaoError::DatabaseError(msg)) => {
+                assert!(
+                    msg.starts_with("Corrupt encrypted value for encrypted_two"),
+                    "{}",
+                    msg
+                )
+            }
+            x => panic!("Expected DatabaseError, got {:?}", x),
+        }
+        assert!(subject.check_password(old_password).unwrap());
+        assert!(!subject.check_password(new_password).unwrap());
+        assert_eq!(
+            subject.get_bytes_e("encrypted_one", old_password),
+            Ok(ciphertext)
+        );
+        assert_eq!(subject.get_string("encrypted_two"), Ok(stray_ciphertext));
+    }
+
     #[test]
     fn get_string_complains_about_nonexistent_row() {
         let home_dir =
@@ -8,8 +8,10 @@ use crate::daemon::{
 use crate::node_configurator::node_configurator_initialization::InitializationConfig;
 use crate::node_configurator::{port_is_busy, DirsWrapper};
 use crate::server_initializer::LoggerInitializerWrapper;
+use crate::sub_lib::logger::LogFilters;
 use crate::sub_lib::main_tools::main_with_args;
 use crate::sub_lib::ui_gateway::UiGatewayConfig;
+use crate::ui_gateway::ui_auth_token::{UiAuthTokenEstablisher, UiAuthTokenEstablisherReal};
 use crate::ui_gateway::UiGateway;
 use actix::{Actor, System, SystemRunner};
 use crossbeam_channel::{unbounded, Receiver, Sender};
@@ -19,17 +21,18 @@ use masq_lib::command::{Command, StdStreams};
 use std::collections::HashMap;
 
 pub trait RecipientsFactory {
-    fn make(&self, launcher: Box<dyn Launcher>, ui_port: u16) -> Recipients;
+    fn make(&self, launcher: Box<dyn Launcher>, ui_port: u16, ui_auth_token: &str) -> Recipients;
 }
 
 #[derive(Default)]
 pub struct RecipientsFactoryReal {}
 
 impl RecipientsFactory for RecipientsFactoryReal {
-    fn make(&self, launcher: Box<dyn Launcher>, ui_port: u16) -> Recipients {
+    fn make(&self, launcher: Box<dyn Launcher>, ui_port: u16, ui_auth_token: &str) -> Recipients {
         let ui_gateway_addr = UiGateway::new(&UiGatewayConfig {
             ui_port,
             node_descriptor: "".to_string(), // irrelevant; field should be removed
+            ui_auth_token: ui_auth_token.to_string(),
         })
         .start();
         let daemon_addr = Daemon::new(launcher).start();
@@ -63,6 +66,7 @@ impl ChannelFactory for ChannelFactoryReal {
 
 pub struct DaemonInitializer {
     config: InitializationConfig,
+    ui_auth_token: String,
     channel_factory: Box<dyn ChannelFactory>,
     recipients_factory: Box<dyn RecipientsFactory>,
     rerunner: Box<dyn Rerunner>,
@@ -115,17 +119,22 @@ impl DaemonInitializer {
         recipients_factory: Box<dyn RecipientsFactory>,
         rerunner: Box<dyn Rerunner>,
     ) -> DaemonInitializer {
+        let real_user = RealUser::null().populate(dirs_wrapper);
         logger_initializer_wrapper.init(
             dirs_wrapper
                 .data_dir()
                 .expect("No data directory")
                 .join("MASQ"),
-            &RealUser::null().populate(dirs_wrapper),
+            &real_user,
             LevelFilter::Trace,
+            &LogFilters::default(),
             Some("daemon"),
         );
+        let ui_auth_token =
+            UiAuthTokenEstablisherReal::new(dirs_wrapper.dup()).establish(&real_user);
         DaemonInitializer {
             config,
+            ui_auth_token,
             channel_factory,
             recipients_factory,
             rerunner,
@@ -134,9 +143,11 @@ impl DaemonInitializer {
 
     fn bind(&mut self, sender: Sender<HashMap<String, String>>) -> u8 {
         let launcher = LauncherReal::new(sender);
-        let recipients = self
-            .recipients_factory
-            .make(Box::new(launcher), self.config.ui_port);
+        let recipients = self.recipients_factory.make(
+            Box::new(launcher),
+            self.config.ui_port,
+            &self.ui_auth_token,
+        );
         let bind_message = DaemonBindMessage {
             to_ui_message_recipient: recipients.ui_gateway_to_sub,
             from_ui_message_recipient: recipients.ui_gateway_from_sub,
@@ -174,6 +185,7 @@ mod tests {
     use crossbeam_channel::unbounded;
     use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
     use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+    use masq_lib::ui_auth::{read_ui_auth_token, ui_auth_token_path};
     use masq_lib::utils::{find_free_port, localhost};
     use std::cell::RefCell;
     use std::iter::FromIterator;
@@ -181,13 +193,21 @@ mod tests {
     use std::sync::{Arc, Mutex};
 
     struct RecipientsFactoryMock {
-        make_params: Arc<Mutex<Vec<(Box<dyn Launcher>, u16)>>>,
+        make_params: Arc<Mutex<Vec<(u16, String)>>>,
         make_results: RefCell<Vec<Recipients>>,
     }
 
     impl RecipientsFactory for RecipientsFactoryMock {
-        fn make(&self, launcher: Box<dyn Launcher>, ui_port: u16) -> Recipients {
-            self.make_params.lock().unwrap().push((launcher, ui_port));
+        fn make(
+            &self,
+            _launcher: Box<dyn Launcher>,
+            ui_port: u16,
+            ui_auth_token: &str,
+        ) -> Recipients {
+            self.make_params
+                .lock()
+                .unwrap()
+                .push((ui_port, ui_auth_token.to_string()));
             self.make_results.borrow_mut().remove(0)
         }
     }
@@ -200,6 +220,11 @@ mod tests {
             }
         }
 
+        fn make_params(mut self, params: &Arc<Mutex<Vec<(u16, String)>>>) -> Self {
+            self.make_params = params.clone();
+            self
+        }
+
         fn make_result(self, result: Recipients) -> Self {
             self.make_results.borrow_mut().push(result);
             self
@@ -278,12 +303,15 @@ mod tests {
         let recipients = make_recipients(ui_gateway, daemon);
         let dirs_wrapper = MockDirsWrapper::new()
             .home_dir_result(Some(data_dir.clone()))
-            .data_dir_result(Some(data_dir));
+            .data_dir_result(Some(data_dir.clone()));
         let logger_initializer_wrapper = LoggerInitializerWrapperMock::new();
         let port = find_free_port();
         let config = InitializationConfig { ui_port: port };
         let channel_factory = ChannelFactoryMock::new();
-        let addr_factory = RecipientsFactoryMock::new().make_result(recipients);
+        let make_params_arc = Arc::new(Mutex::new(vec![]));
+        let addr_factory = RecipientsFactoryMock::new()
+            .make_params(&make_params_arc)
+            .make_result(recipients);
         let rerunner = RerunnerMock::new();
         let mut subject = DaemonInitializer::new(
             &dirs_wrapper,
@@ -304,6 +332,12 @@ mod tests {
         let _ = daemon_recording.get_record::<DaemonBindMessage>(0);
         assert_eq!(ui_gateway_recording.len(), 1);
         assert_eq!(daemon_recording.len(), 1);
+        let make_params = make_params_arc.lock().unwrap();
+        assert_eq!(make_params[0].0, port);
+        assert_eq!(
+            Some(make_params[0].1.clone()),
+            read_ui_auth_token(&ui_auth_token_path(&data_dir.join("MASQ")))
+        );
     }
 
     #[test]
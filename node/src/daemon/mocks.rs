@@ -1,7 +1,14 @@
 // Copyright (c) 2019-2020, MASQ (https://masq.ai). All rights reserved.
 
 use crate::daemon::launch_verifier::{LaunchVerification, LaunchVerifier, VerifierTools};
+use crate::daemon::password_changer::{PasswordChanger, PasswordChangerError};
+use crate::daemon::wallet_maker::{WalletMaker, WalletMakerError};
+use masq_lib::messages::{
+    UiChangePasswordRequest, UiChangePasswordResponse, UiGenerateWalletsRequest,
+    UiGenerateWalletsResponse, UiRecoverWalletsRequest, UiRecoverWalletsResponse,
+};
 use std::cell::RefCell;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 pub struct LaunchVerifierMock {
@@ -119,3 +126,118 @@ impl VerifierToolsMock {
         self
     }
 }
+
+type GenerateWalletsParams = Arc<Mutex<Vec<(PathBuf, u64, UiGenerateWalletsRequest)>>>;
+type RecoverWalletsParams = Arc<Mutex<Vec<(PathBuf, u64, UiRecoverWalletsRequest)>>>;
+type ChangePasswordParams = Arc<Mutex<Vec<(PathBuf, u64, UiChangePasswordRequest)>>>;
+
+#[derive(Default)]
+pub struct WalletMakerMock {
+    generate_wallets_params: GenerateWalletsParams,
+    generate_wallets_results: RefCell<Vec<Result<UiGenerateWalletsResponse, WalletMakerError>>>,
+    recover_wallets_params: RecoverWalletsParams,
+    recover_wallets_results: RefCell<Vec<Result<UiRecoverWalletsResponse, WalletMakerError>>>,
+}
+
+impl WalletMaker for WalletMakerMock {
+    fn generate_wallets(
+        &self,
+        data_directory: &Path,
+        chain_id: u64,
+        request: UiGenerateWalletsRequest,
+    ) -> Result<UiGenerateWalletsResponse, WalletMakerError> {
+        self.generate_wallets_params.lock().unwrap().push((
+            data_directory.to_path_buf(),
+            chain_id,
+            request,
+        ));
+        self.generate_wallets_results.borrow_mut().remove(0)
+    }
+
+    fn recover_wallets(
+        &self,
+        data_directory: &Path,
+        chain_id: u64,
+        request: UiRecoverWalletsRequest,
+    ) -> Result<UiRecoverWalletsResponse, WalletMakerError> {
+        self.recover_wallets_params.lock().unwrap().push((
+            data_directory.to_path_buf(),
+            chain_id,
+            request,
+        ));
+        self.recover_wallets_results.borrow_mut().remove(0)
+    }
+}
+
+impl WalletMakerMock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn generate_wallets_params(mut self, params: &GenerateWalletsParams) -> Self {
+        self.generate_wallets_params = params.clone();
+        self
+    }
+
+    pub fn generate_wallets_result(
+        self,
+        result: Result<UiGenerateWalletsResponse, WalletMakerError>,
+    ) -> Self {
+        self.generate_wallets_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn recover_wallets_params(mut self, params: &RecoverWalletsParams) -> Self {
+        self.recover_wallets_params = params.clone();
+        self
+    }
+
+    pub fn recover_wallets_result(
+        self,
+        result: Result<UiRecoverWalletsResponse, WalletMakerError>,
+    ) -> Self {
+        self.recover_wallets_results.borrow_mut().push(result);
+        self
+    }
+}
+
+#[derive(Default)]
+pub struct PasswordChangerMock {
+    change_password_params: ChangePasswordParams,
+    change_password_results: RefCell<Vec<Result<UiChangePasswordResponse, PasswordChangerError>>>,
+}
+
+impl PasswordChanger for PasswordChangerMock {
+    fn change_password(
+        &self,
+        data_directory: &Path,
+        chain_id: u64,
+        request: UiChangePasswordRequest,
+    ) -> Result<UiChangePasswordResponse, PasswordChangerError> {
+        self.change_password_params.lock().unwrap().push((
+            data_directory.to_path_buf(),
+            chain_id,
+            request,
+        ));
+        self.change_password_results.borrow_mut().remove(0)
+    }
+}
+
+impl PasswordChangerMock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn change_password_params(mut self, params: &ChangePasswordParams) -> Self {
+        self.change_password_params = params.clone();
+        self
+    }
+
+    pub fn change_password_result(
+        self,
+        result: Result<UiChangePasswordResponse, PasswordChangerError>,
+    ) -> Self {
+        self.change_password_results.borrow_mut().push(result);
+        self
+    }
+}
@@ -4,14 +4,23 @@ pub mod crash_notification;
 pub mod daemon_initializer;
 pub mod launch_verifier;
 mod launcher;
+pub mod password_changer;
 mod setup_reporter;
+pub mod wallet_maker;
 
 #[cfg(test)]
 mod mocks;
 
+use crate::blockchain::blockchain_interface::chain_id_from_name;
+use crate::bootstrapper::RealUser;
 use crate::daemon::crash_notification::CrashNotification;
 use crate::daemon::launch_verifier::{VerifierTools, VerifierToolsReal};
+use crate::daemon::password_changer::{PasswordChanger, PasswordChangerReal};
 use crate::daemon::setup_reporter::{SetupCluster, SetupReporter, SetupReporterReal};
+use crate::daemon::wallet_maker::{WalletMaker, WalletMakerReal};
+use crate::database::db_initializer::DATABASE_FILE;
+use crate::node_configurator::RealDirsWrapper;
+use crate::privilege_drop::{PrivilegeDropper, PrivilegeDropperReal};
 use crate::sub_lib::logger::Logger;
 use crate::sub_lib::utils::NODE_MAILBOX_CAPACITY;
 use actix::Recipient;
@@ -19,10 +28,13 @@ use actix::{Actor, Context, Handler, Message};
 use crossbeam_channel::{Receiver, Sender};
 use itertools::Itertools;
 use lazy_static::lazy_static;
+use masq_lib::blockchains::blockchain_from_name;
+use masq_lib::constants::DEFAULT_CHAIN_NAME;
 use masq_lib::messages::UiSetupResponseValueStatus::{Configured, Set};
 use masq_lib::messages::{
-    FromMessageBody, ToMessageBody, UiNodeCrashedBroadcast, UiRedirect, UiSetupBroadcast,
-    UiSetupRequest, UiSetupResponse, UiSetupResponseValue, UiStartOrder, UiStartResponse,
+    FromMessageBody, ToMessageBody, UiChangePasswordRequest, UiGenerateWalletsRequest,
+    UiNodeCrashedBroadcast, UiRecoverWalletsRequest, UiRedirect, UiSetupBroadcast, UiSetupRequest,
+    UiSetupResponse, UiSetupResponseValue, UiStartOrder, UiStartResponse,
     NODE_ALREADY_RUNNING_ERROR, NODE_LAUNCH_ERROR, NODE_NOT_RUNNING_ERROR,
 };
 use masq_lib::shared_schema::ConfiguratorError;
@@ -31,6 +43,8 @@ use masq_lib::ui_gateway::MessageTarget::ClientId;
 use masq_lib::ui_gateway::{MessageBody, MessageTarget, NodeFromUiMessage, NodeToUiMessage};
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 pub struct Recipients {
     ui_gateway_from_sub: Recipient<NodeFromUiMessage>,
@@ -101,6 +115,9 @@ pub struct Daemon {
     node_ui_port: Option<u16>,
     verifier_tools: Box<dyn VerifierTools>,
     setup_reporter: Box<dyn SetupReporter>,
+    wallet_maker: Box<dyn WalletMaker>,
+    password_changer: Box<dyn PasswordChanger>,
+    privilege_dropper: Box<dyn PrivilegeDropper>,
     logger: Logger,
 }
 
@@ -133,6 +150,46 @@ impl Handler<NodeFromUiMessage> for Daemon {
             self.handle_setup(client_id, context_id, setup_request);
         } else if let Ok((_, context_id)) = UiStartOrder::fmb(msg.body.clone()) {
             self.handle_start_order(client_id, context_id);
+        } else if let Ok((request, context_id)) = UiGenerateWalletsRequest::fmb(msg.body.clone()) {
+            self.handle_database_request(
+                client_id,
+                context_id,
+                "generateWallets",
+                |daemon, data_directory, chain_id| {
+                    daemon
+                        .wallet_maker
+                        .generate_wallets(data_directory, chain_id, request)
+                        .map(|response| response.tmb(context_id))
+                },
+            );
+        } else if let Ok((request, context_id)) = UiRecoverWalletsRequest::fmb(msg.body.clone()) {
+            self.handle_database_request(
+                client_id,
+                context_id,
+                "recoverWallets",
+                |daemon, data_directory, chain_id| {
+                    daemon
+                        .wallet_maker
+                        .recover_wallets(data_directory, chain_id, request)
+                        .map(|response| response.tmb(context_id))
+                },
+            );
+        } else if let Ok((request, context_id)) = UiChangePasswordRequest::fmb(msg.body.clone()) {
+            let new_password = request.new_password.clone();
+            let changed = self.handle_database_request(
+                client_id,
+                context_id,
+                "changePassword",
+                |daemon, data_directory, chain_id| {
+                    daemon
+                        .password_changer
+                        .change_password(data_directory, chain_id, request)
+                        .map(|response| response.tmb(context_id))
+                },
+            );
+            if changed {
+                self.update_db_password_and_notify(&new_password);
+            }
         } else {
             self.handle_unexpected_message(client_id, msg.body);
         }
@@ -161,6 +218,9 @@ impl Daemon {
             node_ui_port: None,
             verifier_tools: Box::new(VerifierToolsReal::new()),
             setup_reporter: Box::new(SetupReporterReal::new()),
+            wallet_maker: Box::new(WalletMakerReal::new()),
+            password_changer: Box::new(PasswordChangerReal::new()),
+            privilege_dropper: Box::new(PrivilegeDropperReal::new()),
             logger: Logger::new("Daemon"),
         }
     }
@@ -238,6 +298,114 @@ impl Daemon {
         }
     }
 
+    // Returns whether the request succeeded
+    fn handle_database_request<F>(
+        &mut self,
+        client_id: u64,
+        context_id: u64,
+        opcode: &str,
+        handle_request: F,
+    ) -> bool
+    where
+        F: FnOnce(&Daemon, &Path, u64) -> Result<MessageBody, (u64, String)>,
+    {
+        let result = match self.port_if_node_is_running() {
+            Some(_) => Err((
+                NODE_ALREADY_RUNNING_ERROR,
+                format!("Cannot handle {} request: Node is already running", opcode),
+            )),
+            None => {
+                let (data_directory, chain_id, real_user) = self.wallet_location();
+                let result = handle_request(self, &data_directory, chain_id);
+                self.give_database_to_real_user(&data_directory, &real_user);
+                result
+            }
+        };
+        let succeeded = result.is_ok();
+        let body = match result {
+            Ok(body) => body,
+            Err(error) => MessageBody {
+                opcode: opcode.to_string(),
+                path: Conversation(context_id),
+                payload: Err(error),
+            },
+        };
+        self.respond_to_ui(client_id, body);
+        succeeded
+    }
+
+    // A Node started with the old password would be locked out of the database, so a setup that
+    // carries the password gets the new one.
+    fn update_db_password_and_notify(&mut self, new_password: &str) {
+        let stale = match self.params.get("db-password") {
+            Some(uisrv) => {
+                (uisrv.status == Set || uisrv.status == Configured) && uisrv.value != new_password
+            }
+            None => false,
+        };
+        if !stale {
+            return;
+        }
+        self.params.insert(
+            "db-password".to_string(),
+            UiSetupResponseValue::new("db-password", new_password, Set),
+        );
+        self.send_ui_message(
+            UiSetupBroadcast::new(
+                false,
+                self.censored_params(),
+                ConfiguratorError::new(vec![]),
+            )
+            .tmb(0),
+            MessageTarget::AllClients,
+        );
+    }
+
+    // The Daemon runs as root, so whatever it just created in the data directory belongs to root
+    // until it's handed over to the user the Node will drop privileges to.
+    fn give_database_to_real_user(&self, data_directory: &Path, real_user: &RealUser) {
+        vec![
+            data_directory.to_path_buf(),
+            data_directory.join(DATABASE_FILE),
+        ]
+        .into_iter()
+        .filter(|path| path.exists())
+        .for_each(|path| self.privilege_dropper.chown(&path, real_user));
+    }
+
+    // Wallets go into the database the Node would use if it were started with the current setup.
+    fn wallet_location(&self) -> (PathBuf, u64, RealUser) {
+        let setup = match (self.params.get("data-directory"), self.params.get("chain")) {
+            (Some(_), Some(_)) => self.params.clone(),
+            _ => match self
+                .setup_reporter
+                .get_modified_setup(self.params.clone(), vec![])
+            {
+                Ok(setup) => setup,
+                Err((lame_setup, _)) => lame_setup,
+            },
+        };
+        let data_directory = setup
+            .get("data-directory")
+            .map(|uisrv| PathBuf::from(&uisrv.value))
+            .expect("Setup contains no data-directory");
+        let chain_name = setup
+            .get("chain")
+            .map(|uisrv| uisrv.value.clone())
+            .unwrap_or_else(|| DEFAULT_CHAIN_NAME.to_string());
+        // A rejected setup may still carry a chain name nobody has defined
+        let chain_id = match blockchain_from_name(&chain_name) {
+            Some(blockchain) => blockchain.chain_id,
+            None => chain_id_from_name(DEFAULT_CHAIN_NAME),
+        };
+        let real_user = setup
+            .get("real-user")
+            .and_then(|uisrv| RealUser::from_str(&uisrv.value).ok())
+            .unwrap_or_else(RealUser::null)
+            .populate(&RealDirsWrapper);
+        (data_directory, chain_id, real_user)
+    }
+
     fn handle_unexpected_message(&mut self, client_id: u64, body: MessageBody) {
         match self.port_if_node_is_running() {
             Some(port) => {
@@ -425,17 +593,19 @@ impl Daemon {
 mod tests {
     use super::*;
     use crate::daemon::crash_notification::CrashNotification;
-    use crate::daemon::mocks::VerifierToolsMock;
+    use crate::daemon::mocks::{PasswordChangerMock, VerifierToolsMock, WalletMakerMock};
     use crate::daemon::setup_reporter::{setup_cluster_from, SetupCluster};
     use crate::daemon::LaunchSuccess;
+    use crate::server_initializer::test_utils::PrivilegeDropperMock;
     use crate::test_utils::recorder::{make_recorder, Recorder};
     use actix::System;
-    use masq_lib::messages::UiSetupResponseValueStatus::{Blank, Required, Set};
+    use masq_lib::messages::UiSetupResponseValueStatus::{Blank, Default, Required, Set};
     use masq_lib::messages::{
-        CrashReason, UiFinancialsRequest, UiNodeCrashedBroadcast, UiRedirect, UiSetupBroadcast,
-        UiSetupRequest, UiSetupRequestValue, UiSetupResponse, UiSetupResponseValue,
-        UiSetupResponseValueStatus, UiShutdownRequest, UiStartOrder, UiStartResponse,
-        NODE_ALREADY_RUNNING_ERROR, NODE_LAUNCH_ERROR, NODE_NOT_RUNNING_ERROR,
+        CrashReason, UiChangePasswordResponse, UiFinancialsRequest, UiGenerateWalletsResponse,
+        UiNodeCrashedBroadcast, UiRedirect, UiSetupBroadcast, UiSetupRequest, UiSetupRequestValue,
+        UiSetupResponse, UiSetupResponseValue, UiSetupResponseValueStatus, UiShutdownRequest,
+        UiStartOrder, UiStartResponse, DB_PASSWORD_ERROR, NODE_ALREADY_RUNNING_ERROR,
+        NODE_LAUNCH_ERROR, NODE_NOT_RUNNING_ERROR, WALLET_CREATION_ERROR,
     };
     use masq_lib::shared_schema::ConfiguratorError;
     use masq_lib::test_utils::environment_guard::{ClapGuard, EnvironmentGuard};
@@ -1485,6 +1655,446 @@ mod tests {
         );
     }
 
+    fn make_generate_wallets_request() -> UiGenerateWalletsRequest {
+        UiGenerateWalletsRequest {
+            db_password: "password".to_string(),
+            mnemonic_phrase_size: 12,
+            mnemonic_phrase_language: "English".to_string(),
+            mnemonic_passphrase_opt: None,
+            consuming_derivation_path: "m/44'/60'/0'/0/0".to_string(),
+            earning_derivation_path: "m/44'/60'/0'/0/1".to_string(),
+        }
+    }
+
+    fn make_recover_wallets_request() -> UiRecoverWalletsRequest {
+        UiRecoverWalletsRequest {
+            db_password: "password".to_string(),
+            mnemonic_phrase: vec!["word".to_string(); 12],
+            mnemonic_phrase_language: "English".to_string(),
+            mnemonic_passphrase_opt: Some("booga".to_string()),
+            consuming_derivation_path: "m/44'/60'/0'/0/0".to_string(),
+            earning_wallet: "0x0123456789012345678901234567890123456789".to_string(),
+        }
+    }
+
+    #[test]
+    fn generate_wallets_request_makes_wallets_where_setup_says_and_responds() {
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let generate_wallets_params_arc = Arc::new(Mutex::new(vec![]));
+        let response = UiGenerateWalletsResponse {
+            mnemonic_phrase: vec!["one".to_string(), "two".to_string()],
+            consuming_wallet_address: "0x1111111111111111111111111111111111111111".to_string(),
+            earning_wallet_address: "0x2222222222222222222222222222222222222222".to_string(),
+        };
+        let wallet_maker = WalletMakerMock::new()
+            .generate_wallets_params(&generate_wallets_params_arc)
+            .generate_wallets_result(Ok(response.clone()));
+        let system = System::new("test");
+        let mut subject = Daemon::new(Box::new(LauncherMock::new()));
+        subject.setup_reporter = Box::new(SetupReporterMock::new()); // will panic if called
+        subject.wallet_maker = Box::new(wallet_maker);
+        subject.params = make_setup_cluster(vec![
+            ("chain", TEST_DEFAULT_CHAIN_NAME, Set),
+            ("data-directory", "/home/booga/data", Set),
+        ]);
+        let subject_addr = subject.start();
+        subject_addr
+            .try_send(make_bind_message(ui_gateway))
+            .unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: make_generate_wallets_request().tmb(4321),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        assert_eq!(
+            *generate_wallets_params_arc.lock().unwrap(),
+            vec![(
+                PathBuf::from("/home/booga/data"),
+                chain_id_from_name(TEST_DEFAULT_CHAIN_NAME),
+                make_generate_wallets_request()
+            )]
+        );
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        let record = ui_gateway_recording
+            .get_record::<NodeToUiMessage>(0)
+            .clone();
+        assert_eq!(record.target, ClientId(1234));
+        assert_eq!(
+            UiGenerateWalletsResponse::fmb(record.body).unwrap(),
+            (response, 4321)
+        );
+    }
+
+    #[test]
+    fn wallet_request_gives_the_database_it_made_to_the_real_user() {
+        let data_directory = ensure_node_home_directory_exists(
+            "daemon",
+            "wallet_request_gives_the_database_it_made_to_the_real_user",
+        );
+        std::fs::File::create(data_directory.join(DATABASE_FILE)).unwrap();
+        let (ui_gateway, _, _) = make_recorder();
+        let chown_params_arc = Arc::new(Mutex::new(vec![]));
+        let wallet_maker = WalletMakerMock::new().generate_wallets_result(Err((
+            WALLET_CREATION_ERROR,
+            "Can't create wallets: mnemonic seed has already been created".to_string(),
+        )));
+        let system = System::new("test");
+        let mut subject = Daemon::new(Box::new(LauncherMock::new()));
+        subject.setup_reporter = Box::new(SetupReporterMock::new()); // will panic if called
+        subject.wallet_maker = Box::new(wallet_maker);
+        subject.privilege_dropper =
+            Box::new(PrivilegeDropperMock::new().chown_params(&chown_params_arc));
+        subject.params = make_setup_cluster(vec![
+            ("chain", TEST_DEFAULT_CHAIN_NAME, Set),
+            ("data-directory", data_directory.to_str().unwrap(), Set),
+            ("real-user", "123:456:/home/booga", Set),
+        ]);
+        let subject_addr = subject.start();
+        subject_addr
+            .try_send(make_bind_message(ui_gateway))
+            .unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: make_generate_wallets_request().tmb(4321),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let real_user = RealUser::new(Some(123), Some(456), Some(PathBuf::from("/home/booga")));
+        assert_eq!(
+            *chown_params_arc.lock().unwrap(),
+            vec![
+                (data_directory.clone(), real_user.clone()),
+                (data_directory.join(DATABASE_FILE), real_user),
+            ]
+        );
+    }
+
+    #[test]
+    fn recover_wallets_request_uses_default_setup_if_none_is_established_and_reports_failure() {
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let recover_wallets_params_arc = Arc::new(Mutex::new(vec![]));
+        let wallet_maker = WalletMakerMock::new()
+            .recover_wallets_params(&recover_wallets_params_arc)
+            .recover_wallets_result(Err((WALLET_CREATION_ERROR, "booga".to_string())));
+        let setup_reporter =
+            SetupReporterMock::new().get_modified_setup_result(Ok(make_setup_cluster(vec![
+                ("chain", DEFAULT_CHAIN_NAME, Default),
+                ("data-directory", "/home/booga/default", Default),
+            ])));
+        let system = System::new("test");
+        let mut subject = Daemon::new(Box::new(LauncherMock::new()));
+        subject.setup_reporter = Box::new(setup_reporter);
+        subject.wallet_maker = Box::new(wallet_maker);
+        let subject_addr = subject.start();
+        subject_addr
+            .try_send(make_bind_message(ui_gateway))
+            .unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: make_recover_wallets_request().tmb(4321),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        assert_eq!(
+            *recover_wallets_params_arc.lock().unwrap(),
+            vec![(
+                PathBuf::from("/home/booga/default"),
+                chain_id_from_name(DEFAULT_CHAIN_NAME),
+                make_recover_wallets_request()
+            )]
+        );
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        let record = ui_gateway_recording
+            .get_record::<NodeToUiMessage>(0)
+            .clone();
+        assert_eq!(record.target, ClientId(1234));
+        assert_eq!(
+            record.body,
+            MessageBody {
+                opcode: "recoverWallets".to_string(),
+                path: MessagePath::Conversation(4321),
+                payload: Err((WALLET_CREATION_ERROR, "booga".to_string())),
+            }
+        );
+    }
+
+    fn make_change_password_request() -> UiChangePasswordRequest {
+        UiChangePasswordRequest {
+            old_password_opt: Some("old password".to_string()),
+            new_password: "new password".to_string(),
+        }
+    }
+
+    #[test]
+    fn change_password_request_changes_password_where_setup_says_and_responds() {
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let change_password_params_arc = Arc::new(Mutex::new(vec![]));
+        let password_changer = PasswordChangerMock::new()
+            .change_password_params(&change_password_params_arc)
+            .change_password_result(Ok(UiChangePasswordResponse {}));
+        let system = System::new("test");
+        let mut subject = Daemon::new(Box::new(LauncherMock::new()));
+        subject.setup_reporter = Box::new(SetupReporterMock::new()); // will panic if called
+        subject.password_changer = Box::new(password_changer);
+        subject.params = make_setup_cluster(vec![
+            ("chain", TEST_DEFAULT_CHAIN_NAME, Set),
+            ("data-directory", "/home/booga/data", Set),
+        ]);
+        let subject_addr = subject.start();
+        subject_addr
+            .try_send(make_bind_message(ui_gateway))
+            .unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: make_change_password_request().tmb(4321),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        assert_eq!(
+            *change_password_params_arc.lock().unwrap(),
+            vec![(
+                PathBuf::from("/home/booga/data"),
+                chain_id_from_name(TEST_DEFAULT_CHAIN_NAME),
+                make_change_password_request()
+            )]
+        );
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        let record = ui_gateway_recording
+            .get_record::<NodeToUiMessage>(0)
+            .clone();
+        assert_eq!(record.target, ClientId(1234));
+        assert_eq!(
+            UiChangePasswordResponse::fmb(record.body).unwrap(),
+            (UiChangePasswordResponse {}, 4321)
+        );
+    }
+
+    #[test]
+    fn change_password_request_gives_the_setup_the_new_password_and_broadcasts_the_change() {
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let launch_params_arc = Arc::new(Mutex::new(vec![]));
+        let launcher = LauncherMock::new()
+            .launch_params(&launch_params_arc)
+            .launch_result(Ok(None));
+        let password_changer =
+            PasswordChangerMock::new().change_password_result(Ok(UiChangePasswordResponse {}));
+        let system = System::new("test");
+        let mut subject = Daemon::new(Box::new(launcher));
+        subject.setup_reporter = Box::new(SetupReporterMock::new()); // will panic if called
+        subject.password_changer = Box::new(password_changer);
+        subject.params = make_setup_cluster(vec![
+            ("chain", TEST_DEFAULT_CHAIN_NAME, Set),
+            ("data-directory", "/home/booga/data", Set),
+            ("db-password", "old password", Configured),
+        ]);
+        let subject_addr = subject.start();
+        subject_addr
+            .try_send(make_bind_message(ui_gateway))
+            .unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: make_change_password_request().tmb(4321),
+            })
+            .unwrap();
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: UiStartOrder {}.tmb(4322),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let launch_params = launch_params_arc.lock().unwrap();
+        assert_eq!(
+            launch_params[0].0.get("db-password"),
+            Some(&"new password".to_string())
+        );
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        let record = ui_gateway_recording
+            .get_record::<NodeToUiMessage>(0)
+            .clone();
+        assert_eq!(record.target, ClientId(1234));
+        assert_eq!(
+            UiChangePasswordResponse::fmb(record.body).unwrap(),
+            (UiChangePasswordResponse {}, 4321)
+        );
+        let record = ui_gateway_recording
+            .get_record::<NodeToUiMessage>(1)
+            .clone();
+        assert_eq!(record.target, MessageTarget::AllClients);
+        let (payload, context_id) = UiSetupBroadcast::fmb(record.body).unwrap();
+        assert_eq!(context_id, 0);
+        assert!(!payload.running);
+        assert_eq!(
+            payload
+                .values
+                .into_iter()
+                .find(|uisrv| uisrv.name == "db-password"),
+            Some(UiSetupResponseValue::new(
+                "db-password",
+                "****************",
+                Set
+            ))
+        );
+        assert_eq!(ui_gateway_recording.len(), 2);
+    }
+
+    #[test]
+    fn change_password_request_that_fails_leaves_the_setup_alone() {
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let launch_params_arc = Arc::new(Mutex::new(vec![]));
+        let launcher = LauncherMock::new()
+            .launch_params(&launch_params_arc)
+            .launch_result(Ok(None));
+        let password_changer = PasswordChangerMock::new().change_password_result(Err((
+            DB_PASSWORD_ERROR,
+            "Old db password is incorrect".to_string(),
+        )));
+        let system = System::new("test");
+        let mut subject = Daemon::new(Box::new(launcher));
+        subject.setup_reporter = Box::new(SetupReporterMock::new()); // will panic if called
+        subject.password_changer = Box::new(password_changer);
+        subject.params = make_setup_cluster(vec![
+            ("chain", TEST_DEFAULT_CHAIN_NAME, Set),
+            ("data-directory", "/home/booga/data", Set),
+            ("db-password", "old password", Set),
+        ]);
+        let subject_addr = subject.start();
+        subject_addr
+            .try_send(make_bind_message(ui_gateway))
+            .unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: make_change_password_request().tmb(4321),
+            })
+            .unwrap();
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: UiStartOrder {}.tmb(4322),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let launch_params = launch_params_arc.lock().unwrap();
+        assert_eq!(
+            launch_params[0].0.get("db-password"),
+            Some(&"old password".to_string())
+        );
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        assert_eq!(
+            ui_gateway_recording.get_record::<NodeToUiMessage>(0).body,
+            MessageBody {
+                opcode: "changePassword".to_string(),
+                path: MessagePath::Conversation(4321),
+                payload: Err((
+                    DB_PASSWORD_ERROR,
+                    "Old db password is incorrect".to_string()
+                )),
+            }
+        );
+        assert_eq!(ui_gateway_recording.len(), 1);
+    }
+
+    #[test]
+    fn rejects_wallet_requests_when_node_is_already_running() {
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let verifier_tools = VerifierToolsMock::new()
+            .process_is_running_result(true)
+            .process_is_running_result(true)
+            .process_is_running_result(true);
+        let system = System::new("test");
+        let mut subject = Daemon::new(Box::new(LauncherMock::new()));
+        subject.wallet_maker = Box::new(WalletMakerMock::new()); // will panic if called
+        subject.password_changer = Box::new(PasswordChangerMock::new()); // will panic if called
+        subject.node_ui_port = Some(1234);
+        subject.node_process_id = Some(3421);
+        subject.verifier_tools = Box::new(verifier_tools);
+        let subject_addr = subject.start();
+        subject_addr
+            .try_send(make_bind_message(ui_gateway))
+            .unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: make_generate_wallets_request().tmb(4321),
+            })
+            .unwrap();
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: make_recover_wallets_request().tmb(4322),
+            })
+            .unwrap();
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: make_change_password_request().tmb(4323),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        assert_eq!(
+            ui_gateway_recording.get_record::<NodeToUiMessage>(0).body,
+            MessageBody {
+                opcode: "generateWallets".to_string(),
+                path: MessagePath::Conversation(4321),
+                payload: Err((
+                    NODE_ALREADY_RUNNING_ERROR,
+                    "Cannot handle generateWallets request: Node is already running".to_string()
+                )),
+            }
+        );
+        assert_eq!(
+            ui_gateway_recording.get_record::<NodeToUiMessage>(1).body,
+            MessageBody {
+                opcode: "recoverWallets".to_string(),
+                path: MessagePath::Conversation(4322),
+                payload: Err((
+                    NODE_ALREADY_RUNNING_ERROR,
+                    "Cannot handle recoverWallets request: Node is already running".to_string()
+                )),
+            }
+        );
+        assert_eq!(
+            ui_gateway_recording.get_record::<NodeToUiMessage>(2).body,
+            MessageBody {
+                opcode: "changePassword".to_string(),
+                path: MessagePath::Conversation(4323),
+                payload: Err((
+                    NODE_ALREADY_RUNNING_ERROR,
+                    "Cannot handle changePassword request: Node is already running".to_string()
+                )),
+            }
+        );
+    }
+
     #[test]
     fn accepts_crash_notification_when_not_in_setup_mode_and_sends_ui_notification() {
         let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
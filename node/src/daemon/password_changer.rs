@@ -0,0 +1,226 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai). All rights reserved.
+
+use crate::database::db_initializer::{DbInitializer, DbInitializerReal};
+use crate::node_configurator::cannot_be_blank;
+use crate::persistent_configuration::{
+    PersistentConfigError, PersistentConfiguration, PersistentConfigurationReal,
+};
+use masq_lib::messages::{UiChangePasswordRequest, UiChangePasswordResponse, DB_PASSWORD_ERROR};
+use std::path::Path;
+
+pub type PasswordChangerError = (u64, String);
+
+pub trait PasswordChanger {
+    fn change_password(
+        &self,
+        data_directory: &Path,
+        chain_id: u64,
+        request: UiChangePasswordRequest,
+    ) -> Result<UiChangePasswordResponse, PasswordChangerError>;
+}
+
+#[derive(Default)]
+pub struct PasswordChangerReal {}
+
+impl PasswordChanger for PasswordChangerReal {
+    fn change_password(
+        &self,
+        data_directory: &Path,
+        chain_id: u64,
+        request: UiChangePasswordRequest,
+    ) -> Result<UiChangePasswordResponse, PasswordChangerError> {
+        cannot_be_blank(&request.new_password)
+            .map_err(|e| (DB_PASSWORD_ERROR, format!("Bad new db password: {}", e)))?;
+        let conn = DbInitializerReal::new()
+            .initialize(&data_directory.to_path_buf(), chain_id, false)
+            .map_err(|e| {
+                (
+                    DB_PASSWORD_ERROR,
+                    format!("Can't open database in {:?}: {:?}", data_directory, e),
+                )
+            })?;
+        let persistent_config = PersistentConfigurationReal::from(conn);
+        match persistent_config
+            .change_password(request.old_password_opt.as_deref(), &request.new_password)
+        {
+            Ok(()) => Ok(UiChangePasswordResponse {}),
+            Err(PersistentConfigError::PasswordError) => Err((
+                DB_PASSWORD_ERROR,
+                match request.old_password_opt {
+                    Some(_) => "Old db password is incorrect".to_string(),
+                    None => {
+                        "Database already has a password; old db password is required".to_string()
+                    }
+                },
+            )),
+            Err(e) => Err((
+                DB_PASSWORD_ERROR,
+                format!("Db password was not changed: {:?}", e),
+            )),
+        }
+    }
+}
+
+impl PasswordChangerReal {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sub_lib::cryptde::PlainData;
+    use masq_lib::test_utils::utils::{ensure_node_home_directory_exists, DEFAULT_CHAIN_ID};
+    use std::path::PathBuf;
+
+    fn persistent_config_at(data_directory: &PathBuf) -> PersistentConfigurationReal {
+        let conn = DbInitializerReal::new()
+            .initialize(data_directory, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        PersistentConfigurationReal::from(conn)
+    }
+
+    fn protect_seed_with_password(data_directory: &PathBuf) -> PlainData {
+        let seed = PlainData::new(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let persistent_config = persistent_config_at(data_directory);
+        persistent_config.set_password("password");
+        persistent_config
+            .set_mnemonic_seed(&seed, "password")
+            .unwrap();
+        seed
+    }
+
+    fn make_change_password_request(
+        old_password_opt: Option<&str>,
+        new_password: &str,
+    ) -> UiChangePasswordRequest {
+        UiChangePasswordRequest {
+            old_password_opt: old_password_opt.map(|password| password.to_string()),
+            new_password: new_password.to_string(),
+        }
+    }
+
+    #[test]
+    fn change_password_reencrypts_wallet_secrets() {
+        let home_dir = ensure_node_home_directory_exists(
+            "password_changer",
+            "change_password_reencrypts_wallet_secrets",
+        );
+        let seed = protect_seed_with_password(&home_dir);
+        let subject = PasswordChangerReal::new();
+
+        let result = subject.change_password(
+            &home_dir,
+            DEFAULT_CHAIN_ID,
+            make_change_password_request(Some("password"), "new password"),
+        );
+
+        assert_eq!(result, Ok(UiChangePasswordResponse {}));
+        let persistent_config = persistent_config_at(&home_dir);
+        assert_eq!(
+            persistent_config.mnemonic_seed("new password").unwrap(),
+            Some(seed)
+        );
+        assert_eq!(
+            persistent_config.mnemonic_seed("password"),
+            Err(PersistentConfigError::PasswordError)
+        );
+        assert_eq!(persistent_config.check_password("new password"), Some(true));
+    }
+
+    #[test]
+    fn change_password_rejects_incorrect_old_password() {
+        let home_dir = ensure_node_home_directory_exists(
+            "password_changer",
+            "change_password_rejects_incorrect_old_password",
+        );
+        protect_seed_with_password(&home_dir);
+        let subject = PasswordChangerReal::new();
+
+        let result = subject.change_password(
+            &home_dir,
+            DEFAULT_CHAIN_ID,
+            make_change_password_request(Some("wrong password"), "new password"),
+        );
+
+        assert_eq!(
+            result,
+            Err((
+                DB_PASSWORD_ERROR,
+                "Old db password is incorrect".to_string()
+            ))
+        );
+        assert_eq!(
+            persistent_config_at(&home_dir).check_password("password"),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn change_password_requires_old_password_if_there_is_one() {
+        let home_dir = ensure_node_home_directory_exists(
+            "password_changer",
+            "change_password_requires_old_password_if_there_is_one",
+        );
+        protect_seed_with_password(&home_dir);
+        let subject = PasswordChangerReal::new();
+
+        let result = subject.change_password(
+            &home_dir,
+            DEFAULT_CHAIN_ID,
+            make_change_password_request(None, "new password"),
+        );
+
+        assert_eq!(
+            result,
+            Err((
+                DB_PASSWORD_ERROR,
+                "Database already has a password; old db password is required".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn change_password_rejects_blank_new_password() {
+        let home_dir = ensure_node_home_directory_exists(
+            "password_changer",
+            "change_password_rejects_blank_new_password",
+        );
+        let subject = PasswordChangerReal::new();
+
+        let result = subject.change_password(
+            &home_dir,
+            DEFAULT_CHAIN_ID,
+            make_change_password_request(Some("password"), ""),
+        );
+
+        let (code, message) = result.err().unwrap();
+        assert_eq!(code, DB_PASSWORD_ERROR);
+        assert!(message.starts_with("Bad new db password: "), "{}", message);
+    }
+
+    #[test]
+    fn change_password_does_not_create_database() {
+        let home_dir = ensure_node_home_directory_exists(
+            "password_changer",
+            "change_password_does_not_create_database",
+        );
+        let subject = PasswordChangerReal::new();
+
+        let result = subject.change_password(
+            &home_dir,
+            DEFAULT_CHAIN_ID,
+            make_change_password_request(None, "new password"),
+        );
+
+        let (code, message) = result.err().unwrap();
+        assert_eq!(code, DB_PASSWORD_ERROR);
+        assert!(
+            message.starts_with("Can't open database in "),
+            "{}",
+            message
+        );
+        assert!(message.ends_with(": Nonexistent"), "{}", message);
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2019-2020, MASQ (https://masq.ai). All rights reserved.
 
 use crate::blockchain::blockchain_interface::{chain_id_from_name, chain_name_from_id};
+use crate::blockchain::failover_transport::FailoverPolicy;
 use crate::bootstrapper::BootstrapperConfig;
 use crate::database::db_initializer::{DbInitializer, DbInitializerReal};
 use crate::node_configurator::node_configurator_standard::standard::{
@@ -11,7 +12,9 @@ use crate::node_configurator::{
 };
 use crate::persistent_configuration::{PersistentConfiguration, PersistentConfigurationReal};
 use crate::sub_lib::accountant::DEFAULT_EARNING_WALLET;
-use crate::sub_lib::neighborhood::NodeDescriptor;
+use crate::sub_lib::neighborhood::{
+    NodeDescriptor, DEFAULT_RATE_PACK, UNLIMITED_ROUTE_COST_BUDGET,
+};
 use crate::sub_lib::utils::make_new_multi_config;
 use crate::test_utils::main_cryptde;
 use clap::value_t;
@@ -390,7 +393,7 @@ impl SetupReporterReal {
         dirs_wrapper: &dyn DirsWrapper,
         multi_config: &MultiConfig,
         data_directory: &PathBuf,
-        chain_id: u8,
+        chain_id: u64,
     ) -> (
         (BootstrapperConfig, Option<Box<dyn PersistentConfiguration>>),
         Option<ConfiguratorError>,
@@ -492,6 +495,42 @@ fn is_required_for_blockchain(params: &SetupCluster) -> bool {
     !matches! (params.get("neighborhood-mode"), Some(nhm) if &nhm.value == "zero-hop")
 }
 
+struct BatchPayments {}
+impl ValueRetriever for BatchPayments {
+    fn value_name(&self) -> &'static str {
+        "batch-payments"
+    }
+
+    fn computed_default(
+        &self,
+        bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        if bootstrapper_config.blockchain_bridge_config.batch_payments {
+            Some(("on".to_string(), Configured))
+        } else {
+            Some(("off".to_string(), Default))
+        }
+    }
+}
+
+struct BlockchainServiceFailover {}
+impl ValueRetriever for BlockchainServiceFailover {
+    fn value_name(&self) -> &'static str {
+        "blockchain-service-failover"
+    }
+
+    fn computed_default(
+        &self,
+        _bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        Some((FailoverPolicy::default().name().to_string(), Default))
+    }
+}
+
 struct BlockchainServiceUrl {}
 impl ValueRetriever for BlockchainServiceUrl {
     fn value_name(&self) -> &'static str {
@@ -503,6 +542,13 @@ impl ValueRetriever for BlockchainServiceUrl {
     }
 }
 
+struct BlockchainsFile {}
+impl ValueRetriever for BlockchainsFile {
+    fn value_name(&self) -> &'static str {
+        "blockchains-file"
+    }
+}
+
 struct Chain {}
 impl ValueRetriever for Chain {
     fn value_name(&self) -> &'static str {
@@ -552,6 +598,28 @@ impl ValueRetriever for ConfigFile {
     }
 }
 
+struct ConfirmationDepth {}
+impl ValueRetriever for ConfirmationDepth {
+    fn value_name(&self) -> &'static str {
+        "confirmation-depth"
+    }
+
+    fn computed_default(
+        &self,
+        bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        Some((
+            bootstrapper_config
+                .blockchain_bridge_config
+                .confirmation_depth
+                .to_string(),
+            Default,
+        ))
+    }
+}
+
 struct ConsumingPrivateKey {}
 impl ValueRetriever for ConsumingPrivateKey {
     fn value_name(&self) -> &'static str {
@@ -669,6 +737,40 @@ impl ValueRetriever for EarningWallet {
     }
 }
 
+struct ExitPolicy {}
+impl ValueRetriever for ExitPolicy {
+    fn value_name(&self) -> &'static str {
+        "exit-policy"
+    }
+}
+
+struct ExitPorts {}
+impl ValueRetriever for ExitPorts {
+    fn value_name(&self) -> &'static str {
+        "exit-ports"
+    }
+}
+
+struct ExitPrivateRanges {}
+impl ValueRetriever for ExitPrivateRanges {
+    fn value_name(&self) -> &'static str {
+        "exit-private-ranges"
+    }
+
+    fn computed_default(
+        &self,
+        bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        if bootstrapper_config.exit_policy.block_private_ranges {
+            Some(("block".to_string(), Default))
+        } else {
+            Some(("allow".to_string(), Configured))
+        }
+    }
+}
+
 struct GasPrice {}
 impl ValueRetriever for GasPrice {
     fn value_name(&self) -> &'static str {
@@ -710,6 +812,26 @@ impl ValueRetriever for Ip {
     }
 }
 
+struct LogFilters {}
+impl ValueRetriever for LogFilters {
+    fn value_name(&self) -> &'static str {
+        "log-filters"
+    }
+
+    fn computed_default(
+        &self,
+        bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        if bootstrapper_config.log_filters.filters.is_empty() {
+            None
+        } else {
+            Some((bootstrapper_config.log_filters.to_string(), Configured))
+        }
+    }
+}
+
 struct LogLevel {}
 impl ValueRetriever for LogLevel {
     fn value_name(&self) -> &'static str {
@@ -730,6 +852,46 @@ impl ValueRetriever for LogLevel {
     }
 }
 
+struct MaxGasPrice {}
+impl ValueRetriever for MaxGasPrice {
+    fn value_name(&self) -> &'static str {
+        "max-gas-price"
+    }
+
+    fn computed_default(
+        &self,
+        bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        Some((
+            bootstrapper_config
+                .blockchain_bridge_config
+                .max_gas_price
+                .to_string(),
+            Default,
+        ))
+    }
+}
+
+struct MetricsPort {}
+impl ValueRetriever for MetricsPort {
+    fn value_name(&self) -> &'static str {
+        "metrics-port"
+    }
+
+    fn computed_default(
+        &self,
+        bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        bootstrapper_config
+            .metrics_port_opt
+            .map(|metrics_port| (metrics_port.to_string(), Configured))
+    }
+}
+
 struct NeighborhoodMode {}
 impl ValueRetriever for NeighborhoodMode {
     fn value_name(&self) -> &'static str {
@@ -788,6 +950,31 @@ impl ValueRetriever for Neighbors {
     }
 }
 
+struct RatePack {}
+impl ValueRetriever for RatePack {
+    fn value_name(&self) -> &'static str {
+        "rate-pack"
+    }
+
+    fn computed_default(
+        &self,
+        bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        let neighborhood_mode = &bootstrapper_config.neighborhood_config.mode;
+        if !neighborhood_mode.routes_data() {
+            return None;
+        }
+        let rate_pack = neighborhood_mode.rate_pack();
+        if rate_pack == &DEFAULT_RATE_PACK {
+            Some((rate_pack.to_config_string(), Default))
+        } else {
+            Some((rate_pack.to_config_string(), Configured))
+        }
+    }
+}
+
 struct RealUser {
     #[allow(dead_code)]
     dirs_wrapper: Box<dyn DirsWrapper>,
@@ -831,25 +1018,81 @@ impl RealUser {
     }
 }
 
+struct RouteCostBudget {}
+impl ValueRetriever for RouteCostBudget {
+    fn value_name(&self) -> &'static str {
+        "route-cost-budget"
+    }
+
+    fn computed_default(
+        &self,
+        bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        let route_cost_budget = bootstrapper_config.neighborhood_config.route_cost_budget;
+        if route_cost_budget == UNLIMITED_ROUTE_COST_BUDGET {
+            None
+        } else {
+            Some((route_cost_budget.to_config_string(), Configured))
+        }
+    }
+}
+
+struct ScanBlockRange {}
+impl ValueRetriever for ScanBlockRange {
+    fn value_name(&self) -> &'static str {
+        "scan-block-range"
+    }
+
+    fn computed_default(
+        &self,
+        bootstrapper_config: &BootstrapperConfig,
+        _persistent_config_opt: &Option<Box<dyn PersistentConfiguration>>,
+        _db_password_opt: &Option<String>,
+    ) -> Option<(String, UiSetupResponseValueStatus)> {
+        Some((
+            bootstrapper_config
+                .blockchain_bridge_config
+                .scan_block_range
+                .to_string(),
+            Default,
+        ))
+    }
+}
+
 fn value_retrievers(dirs_wrapper: &dyn DirsWrapper) -> Vec<Box<dyn ValueRetriever>> {
     vec![
+        Box::new(BatchPayments {}),
+        Box::new(BlockchainServiceFailover {}),
         Box::new(BlockchainServiceUrl {}),
+        Box::new(BlockchainsFile {}),
         Box::new(Chain {}),
         Box::new(ClandestinePort {}),
         Box::new(ConfigFile {}),
+        Box::new(ConfirmationDepth {}),
         Box::new(ConsumingPrivateKey {}),
         Box::new(CrashPoint {}),
         Box::new(DataDirectory::new(dirs_wrapper)),
         Box::new(DbPassword {}),
         Box::new(DnsServers {}),
         Box::new(EarningWallet {}),
+        Box::new(ExitPolicy {}),
+        Box::new(ExitPorts {}),
+        Box::new(ExitPrivateRanges {}),
         Box::new(GasPrice {}),
         Box::new(Ip {}),
+        Box::new(LogFilters {}),
         Box::new(LogLevel {}),
+        Box::new(MaxGasPrice {}),
+        Box::new(MetricsPort {}),
         Box::new(NeighborhoodMode {}),
         Box::new(Neighbors {}),
+        Box::new(RatePack {}),
         #[cfg(not(target_os = "windows"))]
         Box::new(RealUser::new(dirs_wrapper)),
+        Box::new(RouteCostBudget {}),
+        Box::new(ScanBlockRange {}),
     ]
 }
 
@@ -876,6 +1119,7 @@ mod tests {
     use std::default::Default;
     use std::fs::File;
     use std::io::Write;
+    use std::path::Path;
     use std::net::IpAddr;
     use std::str::FromStr;
     use std::sync::{Arc, Mutex};
@@ -955,10 +1199,14 @@ mod tests {
             .unwrap();
 
         let expected_result = vec![
+            ("batch-payments", "off", Default),
+            ("blockchain-service-failover", "priority", Default),
             ("blockchain-service-url", "", Required),
+            ("blockchains-file", "", Blank),
             ("chain", DEFAULT_CHAIN_NAME, Default),
             ("clandestine-port", "1234", Default),
             ("config-file", "config.toml", Default),
+            ("confirmation-depth", "12", Default),
             ("consuming-private-key", "", Blank),
             ("crash-point", "", Blank),
             ("data-directory", home_dir.to_str().unwrap(), Set),
@@ -969,15 +1217,22 @@ mod tests {
                 "0x0000000000000000000000000000000000000000",
                 Configured,
             ),
+            ("exit-policy", "", Blank),
+            ("exit-ports", "", Blank),
+            ("exit-private-ranges", "block", Default),
             ("gas-price", "1234567890", Default),
             ("ip", "4.3.2.1", Set),
+            ("log-filters", "", Blank),
             ("log-level", "warn", Default),
+            ("max-gas-price", "200", Default),
+            ("metrics-port", "", Blank),
             ("neighborhood-mode", "standard", Default),
             (
                 "neighbors",
                 "QUJDRA@1.2.3.4:1234,RUZHSA@5.6.7.8:5678",
                 Configured,
             ),
+            ("rate-pack", "100|10000|101|10001", Default),
             #[cfg(not(target_os = "windows"))]
             (
                 "real-user",
@@ -986,6 +1241,8 @@ mod tests {
                     .to_string(),
                 Default,
             ),
+            ("route-cost-budget", "", Blank),
+            ("scan-block-range", "10000", Default),
         ]
         .into_iter()
         .map(|(name, value, status)| {
@@ -1010,45 +1267,70 @@ mod tests {
             "get_modified_setup_database_nonexistent_everything_preexistent",
         );
         let existing_setup = setup_cluster_from(vec![
+            ("batch-payments", "on", Set),
+            ("blockchain-service-failover", "round-robin", Set),
             ("blockchain-service-url", "https://example.com", Set),
             ("chain", TEST_DEFAULT_CHAIN_NAME, Set),
             ("clandestine-port", "1234", Set),
+            ("confirmation-depth", "20", Set),
             ("consuming-private-key", "0011223344556677001122334455667700112233445566770011223344556677", Set),
             ("crash-point", "Message", Set),
             ("data-directory", home_dir.to_str().unwrap(), Set),
             ("db-password", "password", Set),
             ("dns-servers", "8.8.8.8", Set),
             ("earning-wallet", "0x0123456789012345678901234567890123456789", Set),
+            ("exit-policy", "deny *:25", Set),
+            ("exit-ports", "443,993", Set),
+            ("exit-private-ranges", "allow", Set),
             ("gas-price", "50", Set),
             ("ip", "4.3.2.1", Set),
+            ("log-filters", "Neighborhood=debug", Set),
             ("log-level", "error", Set),
+            ("max-gas-price", "150", Set),
+            ("metrics-port", "5333", Set),
             ("neighborhood-mode", "originate-only", Set),
             ("neighbors", "MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:1.2.3.4:1234,MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:5.6.7.8:5678", Set),
+            ("rate-pack", "1|2|3|4", Set),
             #[cfg(not(target_os = "windows"))]
             ("real-user", "9999:9999:booga", Set),
+            ("route-cost-budget", "1000|100000", Set),
+            ("scan-block-range", "2000", Set),
         ]);
         let subject = SetupReporterReal::new();
 
         let result = subject.get_modified_setup(existing_setup, vec![]).unwrap();
 
         let expected_result = vec![
+            ("batch-payments", "on", Set),
+            ("blockchain-service-failover", "round-robin", Set),
             ("blockchain-service-url", "https://example.com", Set),
+            ("blockchains-file", "", Blank),
             ("chain", TEST_DEFAULT_CHAIN_NAME, Set),
             ("clandestine-port", "1234", Set),
             ("config-file", "config.toml", Default),
+            ("confirmation-depth", "20", Set),
             ("consuming-private-key", "0011223344556677001122334455667700112233445566770011223344556677", Set),
             ("crash-point", "Message", Set),
             ("data-directory", home_dir.to_str().unwrap(), Set),
             ("db-password", "password", Set),
             ("dns-servers", "8.8.8.8", Set),
             ("earning-wallet", "0x0123456789012345678901234567890123456789", Set),
+            ("exit-policy", "deny *:25", Set),
+            ("exit-ports", "443,993", Set),
+            ("exit-private-ranges", "allow", Set),
             ("gas-price", "50", Set),
             ("ip", "4.3.2.1", Set),
+            ("log-filters", "Neighborhood=debug", Set),
             ("log-level", "error", Set),
+            ("max-gas-price", "150", Set),
+            ("metrics-port", "5333", Set),
             ("neighborhood-mode", "originate-only", Set),
             ("neighbors", "MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:1.2.3.4:1234,MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:5.6.7.8:5678", Set),
+            ("rate-pack", "1|2|3|4", Set),
             #[cfg(not(target_os = "windows"))]
             ("real-user", "9999:9999:booga", Set),
+            ("route-cost-budget", "1000|100000", Set),
+            ("scan-block-range", "2000", Set),
         ].into_iter()
             .map (|(name, value, status)| (name.to_string(), UiSetupResponseValue::new(name, value, status)))
             .collect_vec();
@@ -1067,22 +1349,34 @@ mod tests {
             "get_modified_setup_database_nonexistent_everything_set",
         );
         let incoming_setup = vec![
+            ("batch-payments", "on"),
+            ("blockchain-service-failover", "round-robin"),
             ("blockchain-service-url", "https://example.com"),
             ("chain", TEST_DEFAULT_CHAIN_NAME),
             ("clandestine-port", "1234"),
+            ("confirmation-depth", "20"),
             ("consuming-private-key", "0011223344556677001122334455667700112233445566770011223344556677"),
             ("crash-point", "Message"),
             ("data-directory", home_dir.to_str().unwrap()),
             ("db-password", "password"),
             ("dns-servers", "8.8.8.8"),
             ("earning-wallet", "0x0123456789012345678901234567890123456789"),
+            ("exit-policy", "deny *:25"),
+            ("exit-ports", "443,993"),
+            ("exit-private-ranges", "allow"),
             ("gas-price", "50"),
             ("ip", "4.3.2.1"),
+            ("log-filters", "Neighborhood=debug"),
             ("log-level", "error"),
+            ("max-gas-price", "150"),
+            ("metrics-port", "5333"),
             ("neighborhood-mode", "originate-only"),
             ("neighbors", "MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:1.2.3.4:1234,MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:5.6.7.8:5678"),
+            ("rate-pack", "1|2|3|4"),
             #[cfg(not(target_os = "windows"))]
             ("real-user", "9999:9999:booga"),
+            ("route-cost-budget", "1000|100000"),
+            ("scan-block-range", "2000"),
         ].into_iter()
             .map (|(name, value)| UiSetupRequestValue::new(name, value))
             .collect_vec();
@@ -1093,23 +1387,36 @@ mod tests {
             .unwrap();
 
         let expected_result = vec![
+            ("batch-payments", "on", Set),
+            ("blockchain-service-failover", "round-robin", Set),
             ("blockchain-service-url", "https://example.com", Set),
+            ("blockchains-file", "", Blank),
             ("chain", TEST_DEFAULT_CHAIN_NAME, Set),
             ("clandestine-port", "1234", Set),
             ("config-file", "config.toml", Default),
+            ("confirmation-depth", "20", Set),
             ("consuming-private-key", "0011223344556677001122334455667700112233445566770011223344556677", Set),
             ("crash-point", "Message", Set),
             ("data-directory", home_dir.to_str().unwrap(), Set),
             ("db-password", "password", Set),
             ("dns-servers", "8.8.8.8", Set),
             ("earning-wallet", "0x0123456789012345678901234567890123456789", Set),
+            ("exit-policy", "deny *:25", Set),
+            ("exit-ports", "443,993", Set),
+            ("exit-private-ranges", "allow", Set),
             ("gas-price", "50", Set),
             ("ip", "4.3.2.1", Set),
+            ("log-filters", "Neighborhood=debug", Set),
             ("log-level", "error", Set),
+            ("max-gas-price", "150", Set),
+            ("metrics-port", "5333", Set),
             ("neighborhood-mode", "originate-only", Set),
             ("neighbors", "MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:1.2.3.4:1234,MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:5.6.7.8:5678", Set),
+            ("rate-pack", "1|2|3|4", Set),
             #[cfg(not(target_os = "windows"))]
             ("real-user", "9999:9999:booga", Set),
+            ("route-cost-budget", "1000|100000", Set),
+            ("scan-block-range", "2000", Set),
         ].into_iter()
             .map (|(name, value, status)| (name.to_string(), UiSetupResponseValue::new(name, value, status)))
             .collect_vec();
@@ -1129,22 +1436,34 @@ mod tests {
             "get_modified_setup_database_nonexistent_nothing_set_everything_in_environment",
         );
         vec![
+            ("MASQ_BATCH_PAYMENTS", "on"),
+            ("MASQ_BLOCKCHAIN_SERVICE_FAILOVER", "round-robin"),
             ("MASQ_BLOCKCHAIN_SERVICE_URL", "https://example.com"),
             ("MASQ_CHAIN", TEST_DEFAULT_CHAIN_NAME),
             ("MASQ_CLANDESTINE_PORT", "1234"),
+            ("MASQ_CONFIRMATION_DEPTH", "20"),
             ("MASQ_CONSUMING_PRIVATE_KEY", "0011223344556677001122334455667700112233445566770011223344556677"),
             ("MASQ_CRASH_POINT", "Error"),
             ("MASQ_DATA_DIRECTORY", home_dir.to_str().unwrap()),
             ("MASQ_DB_PASSWORD", "password"),
             ("MASQ_DNS_SERVERS", "8.8.8.8"),
             ("MASQ_EARNING_WALLET", "0x0123456789012345678901234567890123456789"),
+            ("MASQ_EXIT_POLICY", "deny *:25"),
+            ("MASQ_EXIT_PORTS", "443,993"),
+            ("MASQ_EXIT_PRIVATE_RANGES", "allow"),
             ("MASQ_GAS_PRICE", "50"),
             ("MASQ_IP", "4.3.2.1"),
+            ("MASQ_LOG_FILTERS", "Neighborhood=debug"),
             ("MASQ_LOG_LEVEL", "error"),
+            ("MASQ_MAX_GAS_PRICE", "150"),
+            ("MASQ_METRICS_PORT", "5333"),
             ("MASQ_NEIGHBORHOOD_MODE", "originate-only"),
             ("MASQ_NEIGHBORS", "MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:1.2.3.4:1234,MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:5.6.7.8:5678"),
+            ("MASQ_RATE_PACK", "1|2|3|4"),
             #[cfg(not(target_os = "windows"))]
             ("MASQ_REAL_USER", "9999:9999:booga"),
+            ("MASQ_ROUTE_COST_BUDGET", "1000|100000"),
+            ("MASQ_SCAN_BLOCK_RANGE", "2000"),
         ].into_iter()
             .for_each (|(name, value)| std::env::set_var (name, value));
         let params = vec![];
@@ -1153,23 +1472,36 @@ mod tests {
         let result = subject.get_modified_setup(HashMap::new(), params).unwrap();
 
         let expected_result = vec![
+            ("batch-payments", "on", Configured),
+            ("blockchain-service-failover", "round-robin", Configured),
             ("blockchain-service-url", "https://example.com", Configured),
+            ("blockchains-file", "", Blank),
             ("chain", TEST_DEFAULT_CHAIN_NAME, Configured),
             ("clandestine-port", "1234", Configured),
             ("config-file", "config.toml", Default),
+            ("confirmation-depth", "20", Configured),
             ("consuming-private-key", "0011223344556677001122334455667700112233445566770011223344556677", Configured),
             ("crash-point", "Error", Configured),
             ("data-directory", home_dir.to_str().unwrap(), Configured),
             ("db-password", "password", Configured),
             ("dns-servers", "8.8.8.8", Configured),
             ("earning-wallet", "0x0123456789012345678901234567890123456789", Configured),
+            ("exit-policy", "deny *:25", Configured),
+            ("exit-ports", "443,993", Configured),
+            ("exit-private-ranges", "allow", Configured),
             ("gas-price", "50", Configured),
             ("ip", "4.3.2.1", Configured),
+            ("log-filters", "Neighborhood=debug", Configured),
             ("log-level", "error", Configured),
+            ("max-gas-price", "150", Configured),
+            ("metrics-port", "5333", Configured),
             ("neighborhood-mode", "originate-only", Configured),
             ("neighbors", "MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:1.2.3.4:1234,MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:5.6.7.8:5678", Configured),
+            ("rate-pack", "1|2|3|4", Configured),
             #[cfg(not(target_os = "windows"))]
             ("real-user", "9999:9999:booga", Configured),
+            ("route-cost-budget", "1000|100000", Configured),
+            ("scan-block-range", "2000", Configured),
         ].into_iter()
             .map (|(name, value, status)| (name.to_string(), UiSetupResponseValue::new(name, value, status)))
             .collect_vec();
@@ -1215,12 +1547,12 @@ mod tests {
                 .write_all(b"neighborhood-mode = \"zero-hop\"\n")
                 .unwrap();
         }
-        let ropsten_dir = data_root.join("MASQ").join(TEST_DEFAULT_CHAIN_NAME);
+        let test_chain_dir = data_root.join("MASQ").join(TEST_DEFAULT_CHAIN_NAME);
         {
-            std::fs::create_dir_all(ropsten_dir.clone()).unwrap();
-            let mut config_file = File::create(ropsten_dir.join("config.toml")).unwrap();
+            std::fs::create_dir_all(test_chain_dir.clone()).unwrap();
+            let mut config_file = File::create(test_chain_dir.join("config.toml")).unwrap();
             config_file
-                .write_all(b"blockchain-service-url = \"https://www.ropsten.com\"\n")
+                .write_all(b"blockchain-service-url = \"https://www.testnet.com\"\n")
                 .unwrap();
             config_file
                 .write_all(b"clandestine-port = \"8877\"\n")
@@ -1228,7 +1560,7 @@ mod tests {
             config_file.write_all(b"consuming-private-key = \"FFEEDDCCBBAA99887766554433221100FFEEDDCCBBAA99887766554433221100\"\n").unwrap();
             config_file.write_all(b"crash-point = \"None\"\n").unwrap();
             config_file
-                .write_all(b"db-password = \"ropsten\"\n")
+                .write_all(format!("db-password = \"{}\"\n", TEST_DEFAULT_CHAIN_NAME).as_bytes())
                 .unwrap();
             config_file
                 .write_all(b"dns-servers = \"8.7.6.5\"\n")
@@ -1255,14 +1587,18 @@ mod tests {
         let result = subject.get_modified_setup(existing_setup, params).unwrap();
 
         let expected_result = vec![
+            ("batch-payments", "off", Default),
+            ("blockchain-service-failover", "priority", Default),
             (
                 "blockchain-service-url",
-                "https://www.ropsten.com",
+                "https://www.testnet.com",
                 Configured,
             ),
+            ("blockchains-file", "", Blank),
             ("chain", TEST_DEFAULT_CHAIN_NAME, Set),
             ("clandestine-port", "8877", Configured),
             ("config-file", "config.toml", Default),
+            ("confirmation-depth", "12", Default),
             (
                 "consuming-private-key",
                 "FFEEDDCCBBAA99887766554433221100FFEEDDCCBBAA99887766554433221100",
@@ -1271,7 +1607,7 @@ mod tests {
             ("crash-point", "None", Configured),
             (
                 "data-directory",
-                &ropsten_dir.to_string_lossy().to_string(),
+                &test_chain_dir.to_string_lossy(),
                 Default,
             ),
             ("db-password", TEST_DEFAULT_CHAIN_NAME, Configured),
@@ -1281,11 +1617,18 @@ mod tests {
                 "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
                 Configured,
             ),
+            ("exit-policy", "", Blank),
+            ("exit-ports", "", Blank),
+            ("exit-private-ranges", "block", Default),
             ("gas-price", "88", Configured),
             ("ip", "", Blank),
+            ("log-filters", "", Blank),
             ("log-level", "debug", Configured),
+            ("max-gas-price", "200", Default),
+            ("metrics-port", "", Blank),
             ("neighborhood-mode", "zero-hop", Configured),
             ("neighbors", "", Blank),
+            ("rate-pack", "", Blank),
             #[cfg(not(target_os = "windows"))]
             (
                 "real-user",
@@ -1294,6 +1637,8 @@ mod tests {
                     .to_string(),
                 Default,
             ),
+            ("route-cost-budget", "", Blank),
+            ("scan-block-range", "10000", Default),
         ]
         .into_iter()
         .map(|(name, value, status)| {
@@ -1318,47 +1663,74 @@ mod tests {
             "get_modified_setup_database_nonexistent_all_but_requireds_cleared",
         );
         vec![
+            ("MASQ_BATCH_PAYMENTS", "on"),
+            ("MASQ_BLOCKCHAIN_SERVICE_FAILOVER", "round-robin"),
             ("MASQ_BLOCKCHAIN_SERVICE_URL", "https://example.com"),
             ("MASQ_CHAIN", TEST_DEFAULT_CHAIN_NAME),
             ("MASQ_CLANDESTINE_PORT", "1234"),
+            ("MASQ_CONFIRMATION_DEPTH", "20"),
             ("MASQ_CONSUMING_PRIVATE_KEY", "0011223344556677001122334455667700112233445566770011223344556677"),
             ("MASQ_CRASH_POINT", "Panic"),
             ("MASQ_DATA_DIRECTORY", home_dir.to_str().unwrap()),
             ("MASQ_DB_PASSWORD", "password"),
             ("MASQ_DNS_SERVERS", "8.8.8.8"),
             ("MASQ_EARNING_WALLET", "0x0123456789012345678901234567890123456789"),
+            ("MASQ_EXIT_POLICY", "deny *:25"),
+            ("MASQ_EXIT_PORTS", "443,993"),
+            ("MASQ_EXIT_PRIVATE_RANGES", "allow"),
             ("MASQ_GAS_PRICE", "50"),
             ("MASQ_IP", "4.3.2.1"),
+            ("MASQ_LOG_FILTERS", "Neighborhood=debug"),
             ("MASQ_LOG_LEVEL", "error"),
+            ("MASQ_MAX_GAS_PRICE", "150"),
+            ("MASQ_METRICS_PORT", "5333"),
             ("MASQ_NEIGHBORHOOD_MODE", "originate-only"),
             ("MASQ_NEIGHBORS", "MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:1.2.3.4:1234,MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:5.6.7.8:5678"),
+            ("MASQ_RATE_PACK", "1|2|3|4"),
             #[cfg(not(target_os = "windows"))]
             ("MASQ_REAL_USER", "9999:9999:booga"),
+            ("MASQ_ROUTE_COST_BUDGET", "1000|100000"),
+            ("MASQ_SCAN_BLOCK_RANGE", "2000"),
         ].into_iter()
             .for_each (|(name, value)| std::env::set_var (name, value));
         let params = vec![
+            "batch-payments",
+            "blockchain-service-failover",
             "blockchain-service-url",
             "clandestine-port",
             "config-file",
+            "confirmation-depth",
             "consuming-private-key",
             "crash-point",
             "data-directory",
             "db-password",
             "dns-servers",
             "earning-wallet",
+            "exit-policy",
+            "exit-ports",
+            "exit-private-ranges",
             "gas-price",
             "ip",
+            "log-filters",
+            "max-gas-price",
+            "metrics-port",
             "neighborhood-mode",
             "neighbors",
+            "rate-pack",
             #[cfg(not(target_os = "windows"))]
             "real-user",
+            "route-cost-budget",
+            "scan-block-range",
         ]
         .into_iter()
         .map(|name| UiSetupRequestValue::clear(name))
         .collect_vec();
         let existing_setup = setup_cluster_from(vec![
+            ("batch-payments", "off", Set),
+            ("blockchain-service-failover", "priority", Set),
             ("blockchain-service-url", "https://booga.com", Set),
             ("clandestine-port", "4321", Set),
+            ("confirmation-depth", "30", Set),
             (
                 "consuming-private-key",
                 "7766554433221100776655443322110077665544332211007766554433221100",
@@ -1373,26 +1745,39 @@ mod tests {
                 "0x9876543210987654321098765432109876543210",
                 Set,
             ),
+            ("exit-policy", "deny *:22", Set),
+            ("exit-ports", "22", Set),
+            ("exit-private-ranges", "block", Set),
             ("gas-price", "5", Set),
             ("ip", "1.2.3.4", Set),
+            ("log-filters", "Hopper=warn", Set),
+            ("max-gas-price", "175", Set),
+            ("metrics-port", "6666", Set),
             ("neighborhood-mode", "consume-only", Set),
             (
                 "neighbors",
                 "MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:9.10.11.12:9101",
                 Set,
             ),
+            ("rate-pack", "5|6|7|8", Set),
             #[cfg(not(target_os = "windows"))]
             ("real-user", "6666:6666:agoob", Set),
+            ("route-cost-budget", "500|50000", Set),
+            ("scan-block-range", "5000", Set),
         ]);
         let subject = SetupReporterReal::new();
 
         let result = subject.get_modified_setup(existing_setup, params).unwrap();
 
         let expected_result = vec![
+            ("batch-payments", "on", Configured),
+            ("blockchain-service-failover", "round-robin", Configured),
             ("blockchain-service-url", "https://example.com", Configured),
+            ("blockchains-file", "", Blank),
             ("chain", TEST_DEFAULT_CHAIN_NAME, Configured),
             ("clandestine-port", "1234", Configured),
             ("config-file", "config.toml", Default),
+            ("confirmation-depth", "20", Configured),
             ("consuming-private-key", "0011223344556677001122334455667700112233445566770011223344556677", Configured),
             ("crash-point", "Panic", Configured),
             ("data-directory", home_dir.to_str().unwrap(), Configured),
@@ -1403,13 +1788,22 @@ mod tests {
                 "0x0123456789012345678901234567890123456789",
                 Configured,
             ),
+            ("exit-policy", "deny *:25", Configured),
+            ("exit-ports", "443,993", Configured),
+            ("exit-private-ranges", "allow", Configured),
             ("gas-price", "50", Configured),
             ("ip", "4.3.2.1", Configured),
+            ("log-filters", "Neighborhood=debug", Configured),
             ("log-level", "error", Configured),
+            ("max-gas-price", "150", Configured),
+            ("metrics-port", "5333", Configured),
             ("neighborhood-mode", "originate-only", Configured),
             ("neighbors", "MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:1.2.3.4:1234,MTIzNDU2Nzg5MTEyMzQ1Njc4OTIxMjM0NTY3ODkzMTI:5.6.7.8:5678", Configured),
+            ("rate-pack", "1|2|3|4", Configured),
             #[cfg(not(target_os = "windows"))]
             ("real-user", "9999:9999:booga", Configured),
+            ("route-cost-budget", "1000|100000", Configured),
+            ("scan-block-range", "2000", Configured),
         ]
         .into_iter()
         .map(|(name, value, status)| {
@@ -1480,10 +1874,14 @@ mod tests {
             .join("MASQ")
             .join(DEFAULT_CHAIN_NAME);
         let existing_setup = setup_cluster_from(vec![
+            ("batch-payments", "off", Default),
+            ("blockchain-service-failover", "priority", Default),
             ("blockchain-service-url", "", Required),
+            ("blockchains-file", "", Blank),
             ("chain", DEFAULT_CHAIN_NAME, Default),
             ("clandestine-port", "7788", Default),
             ("config-file", "config.toml", Default),
+            ("confirmation-depth", "12", Default),
             ("consuming-private-key", "", Blank),
             (
                 "data-directory",
@@ -1499,7 +1897,10 @@ mod tests {
             ),
             ("gas-price", "1", Default),
             ("ip", "1.2.3.4", Set),
+            ("log-filters", "", Blank),
             ("log-level", "warn", Default),
+            ("max-gas-price", "200", Default),
+            ("metrics-port", "", Blank),
             ("neighborhood-mode", "originate-only", Set),
             ("neighbors", "", Blank),
             (
@@ -1509,6 +1910,8 @@ mod tests {
                     .to_string(),
                 Default,
             ),
+            ("route-cost-budget", "", Blank),
+            ("scan-block-range", "10000", Default),
         ]);
         let incoming_setup = vec![("chain", TEST_DEFAULT_CHAIN_NAME)]
             .into_iter()
@@ -1531,6 +1934,117 @@ mod tests {
         assert_eq!(actual_data_directory, expected_data_directory);
     }
 
+    fn make_privatenet_blockchains_file(dir: &Path) -> String {
+        let path = dir.join("blockchains.toml");
+        let mut file = File::create(&path).unwrap();
+        file.write_all(
+            br#"
+            [[blockchain]]
+            name = "privatenet"
+            chain_id = 4242
+            contract = "0x0123456789abcdef0123456789abcdef01234567"
+            contract_creation_block = 1234
+            token_decimals = 18
+        "#,
+        )
+        .unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn get_modified_setup_accepts_a_chain_from_a_blockchains_file_that_is_set() {
+        let _guard = EnvironmentGuard::new();
+        let home_dir = ensure_node_home_directory_exists(
+            "setup_reporter",
+            "get_modified_setup_accepts_a_chain_from_a_blockchains_file_that_is_set",
+        );
+        let blockchains_file = make_privatenet_blockchains_file(&home_dir);
+        let existing_setup = setup_cluster_from(vec![
+            ("neighborhood-mode", "zero-hop", Set),
+            ("data-directory", home_dir.to_str().unwrap(), Set),
+        ]);
+        let incoming_setup = vec![
+            ("blockchains-file", blockchains_file.as_str()),
+            ("chain", "privatenet"),
+        ]
+        .into_iter()
+        .map(|(name, value)| UiSetupRequestValue::new(name, value))
+        .collect_vec();
+        let subject = SetupReporterReal::new();
+
+        let result = subject
+            .get_modified_setup(existing_setup, incoming_setup)
+            .unwrap();
+
+        assert_eq!(
+            result.get("blockchains-file").unwrap().clone(),
+            UiSetupResponseValue::new("blockchains-file", &blockchains_file, Set)
+        );
+        assert_eq!(
+            result.get("chain").unwrap().clone(),
+            UiSetupResponseValue::new("chain", "privatenet", Set)
+        );
+    }
+
+    #[test]
+    fn get_modified_setup_accepts_a_chain_from_a_blockchains_file_in_the_config_file() {
+        let _guard = EnvironmentGuard::new();
+        let home_dir = ensure_node_home_directory_exists(
+            "setup_reporter",
+            "get_modified_setup_accepts_a_chain_from_a_blockchains_file_in_the_config_file",
+        );
+        let blockchains_file = make_privatenet_blockchains_file(&home_dir);
+        {
+            let mut config_file = File::create(home_dir.join("config.toml")).unwrap();
+            config_file
+                .write_all(format!("blockchains-file = \"{}\"\n", blockchains_file).as_bytes())
+                .unwrap();
+        }
+        let existing_setup = setup_cluster_from(vec![
+            ("neighborhood-mode", "zero-hop", Set),
+            ("data-directory", home_dir.to_str().unwrap(), Set),
+        ]);
+        let incoming_setup = vec![UiSetupRequestValue::new("chain", "privatenet")];
+        let subject = SetupReporterReal::new();
+
+        let result = subject
+            .get_modified_setup(existing_setup, incoming_setup)
+            .unwrap();
+
+        assert_eq!(
+            result.get("blockchains-file").unwrap().clone(),
+            UiSetupResponseValue::new("blockchains-file", &blockchains_file, Configured)
+        );
+        assert_eq!(
+            result.get("chain").unwrap().clone(),
+            UiSetupResponseValue::new("chain", "privatenet", Set)
+        );
+    }
+
+    #[test]
+    fn get_modified_setup_rejects_a_chain_no_blockchains_file_describes() {
+        let _guard = EnvironmentGuard::new();
+        let existing_setup = setup_cluster_from(vec![("neighborhood-mode", "zero-hop", Set)]);
+        let incoming_setup = vec![UiSetupRequestValue::new("chain", "nosuchnet")];
+        let subject = SetupReporterReal::new();
+
+        let result = subject
+            .get_modified_setup(existing_setup, incoming_setup)
+            .err()
+            .unwrap()
+            .1;
+
+        assert_eq!(result.param_errors.len(), 1);
+        assert_eq!(result.param_errors[0].parameter, "chain");
+        assert!(
+            result.param_errors[0]
+                .reason
+                .starts_with("Unknown blockchain 'nosuchnet'"),
+            "{}",
+            result.param_errors[0].reason
+        );
+    }
+
     #[test]
     fn get_modified_blanking_something_that_shouldnt_be_blanked_fails_properly() {
         let _guard = EnvironmentGuard::new();
@@ -1770,7 +2284,7 @@ mod tests {
             &RealDirsWrapper {},
             &setup,
             &data_directory,
-            "irrelevant",
+            TEST_DEFAULT_CHAIN_NAME,
         )
         .0;
 
@@ -1815,7 +2329,7 @@ mod tests {
             &RealDirsWrapper {},
             &setup,
             &data_directory,
-            "irrelevant",
+            TEST_DEFAULT_CHAIN_NAME,
         )
         .0;
 
@@ -1853,7 +2367,7 @@ mod tests {
             &RealDirsWrapper {},
             &setup,
             &data_directory,
-            "irrelevant",
+            TEST_DEFAULT_CHAIN_NAME,
         )
         .0;
 
@@ -1884,7 +2398,7 @@ mod tests {
             &RealDirsWrapper,
             &setup,
             &data_directory,
-            "irrelevant",
+            TEST_DEFAULT_CHAIN_NAME,
         )
         .1
         .unwrap();
@@ -1929,7 +2443,7 @@ mod tests {
             &RealDirsWrapper {},
             &setup,
             &data_directory,
-            "irrelevant",
+            TEST_DEFAULT_CHAIN_NAME,
         )
         .0;
 
@@ -1967,7 +2481,7 @@ mod tests {
             &RealDirsWrapper {},
             &setup,
             &data_directory,
-            "irrelevant",
+            TEST_DEFAULT_CHAIN_NAME,
         )
         .1
         .unwrap();
@@ -2082,6 +2596,118 @@ mod tests {
         assert_eq!(result, Some(("1".to_string(), Default)))
     }
 
+    #[test]
+    fn rate_pack_computed_default_default() {
+        let mut bootstrapper_config = BootstrapperConfig::new();
+        bootstrapper_config.neighborhood_config.mode =
+            crate::sub_lib::neighborhood::NeighborhoodMode::OriginateOnly(
+                vec![],
+                DEFAULT_RATE_PACK,
+            );
+        let subject = RatePack {};
+
+        let result = subject.computed_default(&bootstrapper_config, &None, &None);
+
+        assert_eq!(result, Some(("100|10000|101|10001".to_string(), Default)))
+    }
+
+    #[test]
+    fn rate_pack_computed_default_configured() {
+        let mut bootstrapper_config = BootstrapperConfig::new();
+        bootstrapper_config.neighborhood_config.mode =
+            crate::sub_lib::neighborhood::NeighborhoodMode::OriginateOnly(
+                vec![],
+                crate::test_utils::rate_pack(100),
+            );
+        let subject = RatePack {};
+
+        let result = subject.computed_default(&bootstrapper_config, &None, &None);
+
+        assert_eq!(result, Some(("101|102|103|104".to_string(), Configured)))
+    }
+
+    #[test]
+    fn rate_pack_computed_default_absent_when_node_provides_no_services() {
+        let mut bootstrapper_config = BootstrapperConfig::new();
+        bootstrapper_config.neighborhood_config.mode =
+            crate::sub_lib::neighborhood::NeighborhoodMode::ConsumeOnly(vec![]);
+        let subject = RatePack {};
+
+        let result = subject.computed_default(&bootstrapper_config, &None, &None);
+
+        assert_eq!(result, None)
+    }
+
+    #[test]
+    fn route_cost_budget_computed_default_configured() {
+        let mut bootstrapper_config = BootstrapperConfig::new();
+        bootstrapper_config.neighborhood_config.route_cost_budget =
+            crate::sub_lib::neighborhood::RouteCostBudget {
+                max_byte_rate: 1000,
+                max_service_rate: 100000,
+            };
+        let subject = RouteCostBudget {};
+
+        let result = subject.computed_default(&bootstrapper_config, &None, &None);
+
+        assert_eq!(result, Some(("1000|100000".to_string(), Configured)))
+    }
+
+    #[test]
+    fn route_cost_budget_computed_default_absent_when_unlimited() {
+        let mut bootstrapper_config = BootstrapperConfig::new();
+        bootstrapper_config.neighborhood_config.route_cost_budget = UNLIMITED_ROUTE_COST_BUDGET;
+        let subject = RouteCostBudget {};
+
+        let result = subject.computed_default(&bootstrapper_config, &None, &None);
+
+        assert_eq!(result, None)
+    }
+
+    #[test]
+    fn metrics_port_computed_default_configured() {
+        let mut bootstrapper_config = BootstrapperConfig::new();
+        bootstrapper_config.metrics_port_opt = Some(5333);
+        let subject = MetricsPort {};
+
+        let result = subject.computed_default(&bootstrapper_config, &None, &None);
+
+        assert_eq!(result, Some(("5333".to_string(), Configured)))
+    }
+
+    #[test]
+    fn metrics_port_computed_default_absent() {
+        let subject = MetricsPort {};
+
+        let result = subject.computed_default(&BootstrapperConfig::new(), &None, &None);
+
+        assert_eq!(result, None)
+    }
+
+    #[test]
+    fn log_filters_computed_default_configured() {
+        let mut bootstrapper_config = BootstrapperConfig::new();
+        bootstrapper_config.log_filters =
+            crate::sub_lib::logger::LogFilters::from_str("Neighborhood=debug,Hopper=warn").unwrap();
+        let subject = LogFilters {};
+
+        let result = subject.computed_default(&bootstrapper_config, &None, &None);
+
+        assert_eq!(
+            result,
+            Some(("Neighborhood=debug,Hopper=warn".to_string(), Configured))
+        )
+    }
+
+    #[test]
+    fn log_filters_computed_default_absent() {
+        let subject = LogFilters {};
+
+        let result = subject.computed_default(&BootstrapperConfig::new(), &None, &None);
+
+        assert_eq!(result, None)
+    }
+
     #[test]
     fn log_level_computed_default() {
         let subject = LogLevel {};
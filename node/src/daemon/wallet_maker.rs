@@ -0,0 +1,533 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai). All rights reserved.
+
+use crate::blockchain::bip32::Bip32ECKeyPair;
+use crate::blockchain::bip39::Bip39;
+use crate::database::db_initializer::{DbInitializer, DbInitializerReal};
+use crate::node_configurator::node_configurator_generate_wallet::{
+    MnemonicFactory, MnemonicFactoryReal,
+};
+use crate::node_configurator::{
+    cannot_be_blank, common_validators, create_wallet, mnemonic_seed_exists, update_db_password,
+    DerivationPathWalletInfo, WalletCreationConfig,
+};
+use crate::persistent_configuration::{PersistentConfiguration, PersistentConfigurationReal};
+use crate::sub_lib::cryptde::PlainData;
+use crate::sub_lib::wallet::Wallet;
+use bip39::{Language, Mnemonic, MnemonicType};
+use masq_lib::messages::{
+    UiGenerateWalletsRequest, UiGenerateWalletsResponse, UiRecoverWalletsRequest,
+    UiRecoverWalletsResponse, WALLET_CREATION_ERROR,
+};
+use std::path::Path;
+
+pub type WalletMakerError = (u64, String);
+
+pub trait WalletMaker {
+    fn generate_wallets(
+        &self,
+        data_directory: &Path,
+        chain_id: u64,
+        request: UiGenerateWalletsRequest,
+    ) -> Result<UiGenerateWalletsResponse, WalletMakerError>;
+    fn recover_wallets(
+        &self,
+        data_directory: &Path,
+        chain_id: u64,
+        request: UiRecoverWalletsRequest,
+    ) -> Result<UiRecoverWalletsResponse, WalletMakerError>;
+}
+
+pub struct WalletMakerReal {
+    mnemonic_factory: Box<dyn MnemonicFactory>,
+}
+
+impl WalletMaker for WalletMakerReal {
+    fn generate_wallets(
+        &self,
+        data_directory: &Path,
+        chain_id: u64,
+        request: UiGenerateWalletsRequest,
+    ) -> Result<UiGenerateWalletsResponse, WalletMakerError> {
+        let language = Self::language(&request.mnemonic_phrase_language)?;
+        let mnemonic_type =
+            MnemonicType::for_word_count(request.mnemonic_phrase_size).map_err(|_| {
+                Self::error(format!(
+                    "Mnemonic phrase must be 12, 15, 18, 21, or 24 words long, not {}",
+                    request.mnemonic_phrase_size
+                ))
+            })?;
+        Self::validate_derivation_path(&request.consuming_derivation_path)?;
+        Self::validate_derivation_path(&request.earning_derivation_path)?;
+        Self::validate_db_password(&request.db_password)?;
+        let persistent_config = Self::uninitialized_persistent_config(data_directory, chain_id)?;
+        let mnemonic = self.mnemonic_factory.make(mnemonic_type, language);
+        let seed = Self::seed(&mnemonic, &request.mnemonic_passphrase_opt);
+        let consuming_wallet_address =
+            Self::address_from_path(&seed, &request.consuming_derivation_path);
+        let earning_wallet_address =
+            Self::address_from_path(&seed, &request.earning_derivation_path);
+        Self::store(
+            persistent_config.as_ref(),
+            seed,
+            request.db_password,
+            request.consuming_derivation_path,
+            earning_wallet_address.clone(),
+        );
+        Ok(UiGenerateWalletsResponse {
+            mnemonic_phrase: mnemonic
+                .phrase()
+                .split(' ')
+                .map(|word| word.to_string())
+                .collect(),
+            consuming_wallet_address,
+            earning_wallet_address,
+        })
+    }
+
+    fn recover_wallets(
+        &self,
+        data_directory: &Path,
+        chain_id: u64,
+        request: UiRecoverWalletsRequest,
+    ) -> Result<UiRecoverWalletsResponse, WalletMakerError> {
+        let language = Self::language(&request.mnemonic_phrase_language)?;
+        let phrase = request.mnemonic_phrase.join(" ");
+        let mnemonic = Mnemonic::from_phrase(phrase, language).map_err(|e| {
+            Self::error(format!(
+                "Mnemonic phrase is not valid for {}: {}",
+                Bip39::name_from_language(language),
+                e
+            ))
+        })?;
+        Self::validate_derivation_path(&request.consuming_derivation_path)?;
+        let earning_wallet_is_address =
+            common_validators::validate_ethereum_address(request.earning_wallet.clone()).is_ok();
+        if !earning_wallet_is_address {
+            Self::validate_derivation_path(&request.earning_wallet)?;
+        }
+        Self::validate_db_password(&request.db_password)?;
+        let persistent_config = Self::uninitialized_persistent_config(data_directory, chain_id)?;
+        let seed = Self::seed(&mnemonic, &request.mnemonic_passphrase_opt);
+        let consuming_wallet_address =
+            Self::address_from_path(&seed, &request.consuming_derivation_path);
+        let earning_wallet_address = if earning_wallet_is_address {
+            request.earning_wallet
+        } else {
+            Self::address_from_path(&seed, &request.earning_wallet)
+        };
+        Self::store(
+            persistent_config.as_ref(),
+            seed,
+            request.db_password,
+            request.consuming_derivation_path,
+            earning_wallet_address.clone(),
+        );
+        Ok(UiRecoverWalletsResponse {
+            consuming_wallet_address,
+            earning_wallet_address,
+        })
+    }
+}
+
+impl Default for WalletMakerReal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WalletMakerReal {
+    pub fn new() -> Self {
+        Self {
+            mnemonic_factory: Box::new(MnemonicFactoryReal {}),
+        }
+    }
+
+    fn error(message: String) -> WalletMakerError {
+        (WALLET_CREATION_ERROR, message)
+    }
+
+    fn language(name: &str) -> Result<Language, WalletMakerError> {
+        let lowercase_name = name.to_lowercase();
+        if Bip39::possible_language_values()
+            .into_iter()
+            .any(|possible| possible.to_lowercase() == lowercase_name)
+        {
+            Ok(Bip39::language_from_name(name))
+        } else {
+            Err(Self::error(format!(
+                "Mnemonic phrase language '{}' is not supported; choose from {}",
+                name,
+                Bip39::possible_language_values().join(", ")
+            )))
+        }
+    }
+
+    fn validate_derivation_path(path: &str) -> Result<(), WalletMakerError> {
+        common_validators::validate_derivation_path(path.to_string())
+            .map_err(|e| Self::error(format!("Bad derivation path: {}", e)))
+    }
+
+    fn validate_db_password(db_password: &str) -> Result<(), WalletMakerError> {
+        cannot_be_blank(db_password).map_err(|e| Self::error(format!("Bad db password: {}", e)))
+    }
+
+    fn uninitialized_persistent_config(
+        data_directory: &Path,
+        chain_id: u64,
+    ) -> Result<Box<dyn PersistentConfiguration>, WalletMakerError> {
+        let conn = DbInitializerReal::new()
+            .initialize(&data_directory.to_path_buf(), chain_id, true)
+            .map_err(|e| {
+                Self::error(format!(
+                    "Can't initialize database in {:?}: {:?}",
+                    data_directory, e
+                ))
+            })?;
+        let persistent_config = PersistentConfigurationReal::from(conn);
+        if mnemonic_seed_exists(&persistent_config) {
+            return Err(Self::error(
+                "Can't create wallets: mnemonic seed has already been created".to_string(),
+            ));
+        }
+        Ok(Box::new(persistent_config))
+    }
+
+    fn seed(mnemonic: &Mnemonic, mnemonic_passphrase_opt: &Option<String>) -> PlainData {
+        let passphrase = match mnemonic_passphrase_opt {
+            Some(passphrase) => passphrase.as_str(),
+            None => "",
+        };
+        PlainData::new(Bip39::seed(mnemonic, passphrase).as_ref())
+    }
+
+    fn address_from_path(seed: &PlainData, derivation_path: &str) -> String {
+        let keypair =
+            Bip32ECKeyPair::from_raw(seed.as_ref(), derivation_path).unwrap_or_else(|_| {
+                panic!(
+                    "Couldn't make key pair from validated derivation path '{}'",
+                    derivation_path
+                )
+            });
+        Wallet::from(keypair.address()).to_string()
+    }
+
+    fn store(
+        persistent_config: &dyn PersistentConfiguration,
+        mnemonic_seed: PlainData,
+        db_password: String,
+        consuming_derivation_path: String,
+        earning_wallet_address: String,
+    ) {
+        let config = WalletCreationConfig {
+            earning_wallet_address_opt: Some(earning_wallet_address),
+            derivation_path_info_opt: Some(DerivationPathWalletInfo {
+                mnemonic_seed,
+                db_password,
+                consuming_derivation_path_opt: Some(consuming_derivation_path),
+            }),
+            real_user: Default::default(),
+        };
+        create_wallet(&config, persistent_config);
+        update_db_password(&config, persistent_config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sub_lib::wallet::{
+        DEFAULT_CONSUMING_DERIVATION_PATH, DEFAULT_EARNING_DERIVATION_PATH,
+    };
+    use masq_lib::test_utils::utils::{ensure_node_home_directory_exists, DEFAULT_CHAIN_ID};
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    struct MnemonicFactoryMock {
+        make_params: Arc<Mutex<Vec<(MnemonicType, Language)>>>,
+        make_results: RefCell<Vec<Mnemonic>>,
+    }
+
+    impl MnemonicFactory for MnemonicFactoryMock {
+        fn make(&self, mnemonic_type: MnemonicType, language: Language) -> Mnemonic {
+            self.make_params
+                .lock()
+                .unwrap()
+                .push((mnemonic_type, language));
+            self.make_results.borrow_mut().remove(0)
+        }
+    }
+
+    impl MnemonicFactoryMock {
+        fn new() -> Self {
+            Self {
+                make_params: Arc::new(Mutex::new(vec![])),
+                make_results: RefCell::new(vec![]),
+            }
+        }
+
+        fn make_params(mut self, params: &Arc<Mutex<Vec<(MnemonicType, Language)>>>) -> Self {
+            self.make_params = params.clone();
+            self
+        }
+
+        fn make_result(self, result: Mnemonic) -> Self {
+            self.make_results.borrow_mut().push(result);
+            self
+        }
+    }
+
+    const PHRASE: &str =
+        "timber cage wide hawk phone shaft pattern movie army dizzy hen tackle lamp absent write kind term toddler sphere ripple idle dragon curious hold";
+
+    fn make_generate_request() -> UiGenerateWalletsRequest {
+        UiGenerateWalletsRequest {
+            db_password: "password".to_string(),
+            mnemonic_phrase_size: 24,
+            mnemonic_phrase_language: "English".to_string(),
+            mnemonic_passphrase_opt: Some("booga".to_string()),
+            consuming_derivation_path: DEFAULT_CONSUMING_DERIVATION_PATH.to_string(),
+            earning_derivation_path: DEFAULT_EARNING_DERIVATION_PATH.to_string(),
+        }
+    }
+
+    fn make_recover_request() -> UiRecoverWalletsRequest {
+        UiRecoverWalletsRequest {
+            db_password: "password".to_string(),
+            mnemonic_phrase: PHRASE.split(' ').map(|word| word.to_string()).collect(),
+            mnemonic_phrase_language: "english".to_string(),
+            mnemonic_passphrase_opt: Some("booga".to_string()),
+            consuming_derivation_path: DEFAULT_CONSUMING_DERIVATION_PATH.to_string(),
+            earning_wallet: DEFAULT_EARNING_DERIVATION_PATH.to_string(),
+        }
+    }
+
+    fn address_for(path: &str) -> String {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        let seed = Bip39::seed(&mnemonic, "booga");
+        let keypair = Bip32ECKeyPair::from_raw(seed.as_ref(), path).unwrap();
+        Wallet::from(keypair.address()).to_string()
+    }
+
+    fn persistent_config_at(data_directory: &PathBuf) -> PersistentConfigurationReal {
+        let conn = DbInitializerReal::new()
+            .initialize(data_directory, DEFAULT_CHAIN_ID, false)
+            .unwrap();
+        PersistentConfigurationReal::from(conn)
+    }
+
+    #[test]
+    fn generate_wallets_stores_seed_and_derivation_paths() {
+        let home_dir = ensure_node_home_directory_exists(
+            "wallet_maker",
+            "generate_wallets_stores_seed_and_derivation_paths",
+        );
+        let make_params_arc = Arc::new(Mutex::new(vec![]));
+        let subject = WalletMakerReal {
+            mnemonic_factory: Box::new(
+                MnemonicFactoryMock::new()
+                    .make_params(&make_params_arc)
+                    .make_result(Mnemonic::from_phrase(PHRASE, Language::English).unwrap()),
+            ),
+        };
+
+        let result = subject
+            .generate_wallets(&home_dir, DEFAULT_CHAIN_ID, make_generate_request())
+            .unwrap();
+
+        assert_eq!(
+            result,
+            UiGenerateWalletsResponse {
+                mnemonic_phrase: PHRASE.split(' ').map(|word| word.to_string()).collect(),
+                consuming_wallet_address: address_for(DEFAULT_CONSUMING_DERIVATION_PATH),
+                earning_wallet_address: address_for(DEFAULT_EARNING_DERIVATION_PATH),
+            }
+        );
+        let make_params = make_params_arc.lock().unwrap();
+        assert_eq!(make_params.len(), 1);
+        assert_eq!(make_params[0].0.word_count(), 24);
+        assert_eq!(Bip39::name_from_language(make_params[0].1), "English");
+        let persistent_config = persistent_config_at(&home_dir);
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        assert_eq!(
+            persistent_config.mnemonic_seed("password").unwrap(),
+            Some(PlainData::new(Bip39::seed(&mnemonic, "booga").as_ref()))
+        );
+        assert_eq!(
+            persistent_config.consuming_wallet_derivation_path(),
+            Some(DEFAULT_CONSUMING_DERIVATION_PATH.to_string())
+        );
+        assert_eq!(
+            persistent_config.earning_wallet_address(),
+            Some(address_for(DEFAULT_EARNING_DERIVATION_PATH))
+        );
+        assert_eq!(persistent_config.check_password("password"), Some(true));
+    }
+
+    #[test]
+    fn generate_wallets_rejects_bad_word_count() {
+        let home_dir = ensure_node_home_directory_exists(
+            "wallet_maker",
+            "generate_wallets_rejects_bad_word_count",
+        );
+        let subject = WalletMakerReal::new();
+        let mut request = make_generate_request();
+        request.mnemonic_phrase_size = 13;
+
+        let result = subject.generate_wallets(&home_dir, DEFAULT_CHAIN_ID, request);
+
+        assert_eq!(
+            result,
+            Err((
+                WALLET_CREATION_ERROR,
+                "Mnemonic phrase must be 12, 15, 18, 21, or 24 words long, not 13".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn generate_wallets_rejects_unsupported_language() {
+        let home_dir = ensure_node_home_directory_exists(
+            "wallet_maker",
+            "generate_wallets_rejects_unsupported_language",
+        );
+        let subject = WalletMakerReal::new();
+        let mut request = make_generate_request();
+        request.mnemonic_phrase_language = "Klingon".to_string();
+
+        let result = subject.generate_wallets(&home_dir, DEFAULT_CHAIN_ID, request);
+
+        let (code, message) = result.err().unwrap();
+        assert_eq!(code, WALLET_CREATION_ERROR);
+        assert!(
+            message.starts_with("Mnemonic phrase language 'Klingon' is not supported"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn generate_wallets_rejects_weak_derivation_path() {
+        let home_dir = ensure_node_home_directory_exists(
+            "wallet_maker",
+            "generate_wallets_rejects_weak_derivation_path",
+        );
+        let subject = WalletMakerReal::new();
+        let mut request = make_generate_request();
+        request.earning_derivation_path = "m/44/60/0/0/1".to_string();
+
+        let result = subject.generate_wallets(&home_dir, DEFAULT_CHAIN_ID, request);
+
+        assert_eq!(
+            result,
+            Err((
+                WALLET_CREATION_ERROR,
+                "Bad derivation path: m/44/60/0/0/1 may be too weak".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn recover_wallets_stores_seed_and_derivation_paths() {
+        let home_dir = ensure_node_home_directory_exists(
+            "wallet_maker",
+            "recover_wallets_stores_seed_and_derivation_paths",
+        );
+        let subject = WalletMakerReal::new();
+
+        let result = subject
+            .recover_wallets(&home_dir, DEFAULT_CHAIN_ID, make_recover_request())
+            .unwrap();
+
+        assert_eq!(
+            result,
+            UiRecoverWalletsResponse {
+                consuming_wallet_address: address_for(DEFAULT_CONSUMING_DERIVATION_PATH),
+                earning_wallet_address: address_for(DEFAULT_EARNING_DERIVATION_PATH),
+            }
+        );
+        let persistent_config = persistent_config_at(&home_dir);
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        assert_eq!(
+            persistent_config.mnemonic_seed("password").unwrap(),
+            Some(PlainData::new(Bip39::seed(&mnemonic, "booga").as_ref()))
+        );
+        assert_eq!(
+            persistent_config.consuming_wallet_derivation_path(),
+            Some(DEFAULT_CONSUMING_DERIVATION_PATH.to_string())
+        );
+        assert_eq!(
+            persistent_config.earning_wallet_address(),
+            Some(address_for(DEFAULT_EARNING_DERIVATION_PATH))
+        );
+    }
+
+    #[test]
+    fn recover_wallets_accepts_earning_wallet_address() {
+        let home_dir = ensure_node_home_directory_exists(
+            "wallet_maker",
+            "recover_wallets_accepts_earning_wallet_address",
+        );
+        let subject = WalletMakerReal::new();
+        let mut request = make_recover_request();
+        request.earning_wallet = "0x0123456789012345678901234567890123456789".to_string();
+
+        let result = subject
+            .recover_wallets(&home_dir, DEFAULT_CHAIN_ID, request)
+            .unwrap();
+
+        assert_eq!(
+            result.earning_wallet_address,
+            "0x0123456789012345678901234567890123456789".to_string()
+        );
+        assert_eq!(
+            persistent_config_at(&home_dir).earning_wallet_address(),
+            Some("0x0123456789012345678901234567890123456789".to_string())
+        );
+    }
+
+    #[test]
+    fn recover_wallets_rejects_invalid_phrase() {
+        let home_dir = ensure_node_home_directory_exists(
+            "wallet_maker",
+            "recover_wallets_rejects_invalid_phrase",
+        );
+        let subject = WalletMakerReal::new();
+        let mut request = make_recover_request();
+        request.mnemonic_phrase[0] = "booga".to_string();
+
+        let result = subject.recover_wallets(&home_dir, DEFAULT_CHAIN_ID, request);
+
+        let (code, message) = result.err().unwrap();
+        assert_eq!(code, WALLET_CREATION_ERROR);
+        assert!(
+            message.starts_with("Mnemonic phrase is not valid for English: "),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn wallets_cannot_be_created_twice() {
+        let home_dir =
+            ensure_node_home_directory_exists("wallet_maker", "wallets_cannot_be_created_twice");
+        let subject = WalletMakerReal::new();
+        subject
+            .recover_wallets(&home_dir, DEFAULT_CHAIN_ID, make_recover_request())
+            .unwrap();
+
+        let result = subject.generate_wallets(&home_dir, DEFAULT_CHAIN_ID, make_generate_request());
+
+        assert_eq!(
+            result,
+            Err((
+                WALLET_CREATION_ERROR,
+                "Can't create wallets: mnemonic seed has already been created".to_string()
+            ))
+        );
+        assert_eq!(
+            persistent_config_at(&home_dir).earning_wallet_address(),
+            Some(address_for(DEFAULT_EARNING_DERIVATION_PATH))
+        );
+    }
+}
@@ -14,7 +14,9 @@ use clap::Arg;
 use heck::MixedCase;
 use masq_lib::command::StdStreams;
 use masq_lib::multi_config::{CommandLineVcl, EnvironmentVcl, VirtualCommandLine};
-use masq_lib::shared_schema::{chain_arg, data_directory_arg, real_user_arg, ConfiguratorError};
+use masq_lib::shared_schema::{
+    blockchains_file_arg, chain_arg, data_directory_arg, real_user_arg, ConfiguratorError,
+};
 use serde_json::json;
 use serde_json::{Map, Value};
 use std::path::PathBuf;
@@ -58,7 +60,7 @@ fn configuration_to_json(configuration: Vec<(String, Option<String>)>) -> String
     serde_json::to_string_pretty(&value).expect("Couldn't serialize configuration to JSON")
 }
 
-fn make_config_dao(data_directory: &PathBuf, chain_id: u8) -> ConfigDaoReal {
+fn make_config_dao(data_directory: &PathBuf, chain_id: u64) -> ConfigDaoReal {
     let conn = DbInitializerReal::new()
         .initialize(&data_directory, chain_id, true) // TODO: Probably should be false
         .unwrap_or_else(|e| {
@@ -75,7 +77,7 @@ fn distill_args(
     dirs_wrapper: &dyn DirsWrapper,
     args: &[String],
     streams: &mut StdStreams,
-) -> Result<(RealUser, PathBuf, u8), ConfiguratorError> {
+) -> Result<(RealUser, PathBuf, u64), ConfiguratorError> {
     let app = app_head()
         .arg(
             Arg::with_name("dump-config")
@@ -84,6 +86,7 @@ fn distill_args(
                 .takes_value(false)
                 .help(DUMP_CONFIG_HELP),
         )
+        .arg(blockchains_file_arg())
         .arg(chain_arg())
         .arg(data_directory_arg())
         .arg(real_user_arg());
@@ -147,6 +150,7 @@ mod tests {
            "earningWalletAddress": null,
            "exampleEncrypted": null,
            "gasPrice": "1",
+           "nodePrivateKey": null,
            "pastNeighbors": null,
            "schemaVersion": CURRENT_SCHEMA_VERSION,
            "seed": null,
@@ -194,6 +198,7 @@ mod tests {
            "earningWalletAddress": "0x0123456789012345678901234567890123456789",
            "exampleEncrypted": null,
            "gasPrice": "1",
+           "nodePrivateKey": null,
            "pastNeighbors": null,
            "schemaVersion": CURRENT_SCHEMA_VERSION,
            "seed": null,
@@ -2,6 +2,7 @@
 use crate::blockchain::blockchain_interface::{
     chain_name_from_id, contract_creation_block_from_chain_id,
 };
+use crate::database::db_migrations::{DbMigrator, DbMigratorReal};
 use masq_lib::constants::{
     DEFAULT_GAS_PRICE, HIGHEST_RANDOM_CLANDESTINE_PORT, LOWEST_USABLE_INSECURE_PORT,
 };
@@ -17,7 +18,7 @@ use std::path::PathBuf;
 use tokio::net::TcpListener;
 
 pub const DATABASE_FILE: &str = "node-data.db";
-pub const CURRENT_SCHEMA_VERSION: &str = "0.0.10";
+pub const CURRENT_SCHEMA_VERSION: &str = "0.0.18";
 
 pub trait ConnectionWrapper: Debug + Send {
     fn prepare(&self, query: &str) -> Result<Statement, rusqlite::Error>;
@@ -48,6 +49,7 @@ impl ConnectionWrapperReal {
 pub enum InitializationError {
     Nonexistent,
     IncompatibleVersion(String),
+    MigrationFailed(String, String, String),
     SqliteError(rusqlite::Error),
 }
 
@@ -55,19 +57,20 @@ pub trait DbInitializer {
     fn initialize(
         &self,
         path: &PathBuf,
-        chain_id: u8,
+        chain_id: u64,
         create_if_necessary: bool,
     ) -> Result<Box<dyn ConnectionWrapper>, InitializationError>;
 }
 
-#[derive(Default)]
-pub struct DbInitializerReal {}
+pub struct DbInitializerReal {
+    migrator: Box<dyn DbMigrator>,
+}
 
 impl DbInitializer for DbInitializerReal {
     fn initialize(
         &self,
         path: &PathBuf,
-        chain_id: u8,
+        chain_id: u64,
         create_if_necessary: bool,
     ) -> Result<Box<dyn ConnectionWrapper>, InitializationError> {
         let is_creation_necessary = Self::is_creation_necessary(path);
@@ -79,13 +82,18 @@ impl DbInitializer for DbInitializerReal {
         flags.insert(OpenFlags::SQLITE_OPEN_READ_WRITE);
         let database_file_path = &path.join(DATABASE_FILE);
         match Connection::open_with_flags(database_file_path, flags) {
-            Ok(conn) => {
+            Ok(mut conn) => {
                 eprintln!("Opened existing database at {:?}", database_file_path);
                 let config = self.extract_configurations(&conn);
-                match self.check_version(config.get("schema_version")) {
-                    Ok(_) => Ok(Box::new(ConnectionWrapperReal::new(conn))),
-                    Err(e) => Err(e),
+                let found_version = self.check_version(config.get("schema_version"))?;
+                if found_version != CURRENT_SCHEMA_VERSION {
+                    self.migrator.migrate_database(
+                        &mut conn,
+                        &found_version,
+                        CURRENT_SCHEMA_VERSION,
+                    )?;
                 }
+                Ok(Box::new(ConnectionWrapperReal::new(conn)))
             }
             Err(_) => {
                 let mut flags = OpenFlags::empty();
@@ -106,11 +114,21 @@ impl DbInitializer for DbInitializerReal {
     }
 }
 
+impl Default for DbInitializerReal {
+    fn default() -> Self {
+        Self::new_with_migrator(Box::new(DbMigratorReal::default()))
+    }
+}
+
 impl DbInitializerReal {
     pub fn new() -> Self {
         Self::default()
     }
 
+    pub fn new_with_migrator(migrator: Box<dyn DbMigrator>) -> Self {
+        Self { migrator }
+    }
+
     fn is_creation_necessary(data_directory: &PathBuf) -> bool {
         match fs::read_dir(data_directory) {
             Ok(_) => !data_directory.join(DATABASE_FILE).exists(),
@@ -138,13 +156,16 @@ impl DbInitializerReal {
     fn create_database_tables(
         &self,
         conn: &Connection,
-        chain_id: u8,
+        chain_id: u64,
     ) -> Result<(), InitializationError> {
         self.create_config_table(conn)?;
         self.initialize_config(conn, chain_id)?;
         self.create_payable_table(conn)?;
         self.create_receivable_table(conn)?;
-        self.create_banned_table(conn)
+        self.create_banned_table(conn)?;
+        self.create_pardoned_table(conn)?;
+        self.create_pending_payments_table(conn)?;
+        self.create_received_payments_table(conn)
     }
 
     fn create_config_table(&self, conn: &Connection) -> Result<(), InitializationError> {
@@ -168,7 +189,7 @@ impl DbInitializerReal {
     fn initialize_config(
         &self,
         conn: &Connection,
-        chain_id: u8,
+        chain_id: u64,
     ) -> Result<(), InitializationError> {
         Self::set_config_value(conn, "example_encrypted", None, false, "example_encrypted");
         Self::set_config_value(
@@ -222,6 +243,7 @@ impl DbInitializerReal {
             "gas price",
         );
         Self::set_config_value(conn, "past_neighbors", None, true, "past neighbors");
+        Self::set_config_value(conn, "node_private_key", None, true, "node private key");
         Ok(())
     }
 
@@ -264,7 +286,12 @@ impl DbInitializerReal {
 
     fn create_banned_table(&self, conn: &Connection) -> Result<(), InitializationError> {
         conn.execute(
-            "create table banned ( wallet_address text primary key )",
+            "create table banned (
+                wallet_address text primary key,
+                reason text not null,
+                banned_timestamp integer not null,
+                manual integer not null
+            )",
             NO_PARAMS,
         )
         .expect("Can't create banned table");
@@ -276,6 +303,63 @@ impl DbInitializerReal {
         Ok(())
     }
 
+    fn create_pardoned_table(&self, conn: &Connection) -> Result<(), InitializationError> {
+        conn.execute(
+            "create table pardoned (
+                wallet_address text primary key,
+                pardoned_timestamp integer not null,
+                pardoned_balance integer not null
+            )",
+            NO_PARAMS,
+        )
+        .expect("Can't create pardoned table");
+        Ok(())
+    }
+
+    fn create_pending_payments_table(&self, conn: &Connection) -> Result<(), InitializationError> {
+        conn.execute(
+            "create table if not exists pending_payments (
+                transaction_hash text not null,
+                wallet_address text not null,
+                amount integer not null,
+                nonce text not null,
+                gas_price integer not null,
+                priority_fee integer,
+                attempted_timestamp integer not null,
+                primary key (transaction_hash, wallet_address)
+            )",
+            NO_PARAMS,
+        )
+        .expect("Can't create pending_payments table");
+        conn.execute(
+            "create index if not exists idx_pending_payments_wallet_address on pending_payments (wallet_address)",
+            NO_PARAMS,
+        )
+        .expect("Can't create pending_payments wallet_address index");
+        Ok(())
+    }
+
+    fn create_received_payments_table(&self, conn: &Connection) -> Result<(), InitializationError> {
+        conn.execute(
+            "create table if not exists received_payments (
+                block_number integer not null,
+                block_hash text not null,
+                wallet_address text not null,
+                amount integer not null,
+                prior_last_received_timestamp integer,
+                prior_pardoned_balance integer
+            )",
+            NO_PARAMS,
+        )
+        .expect("Can't create received_payments table");
+        conn.execute(
+            "create index if not exists idx_received_payments_block_number on received_payments (block_number)",
+            NO_PARAMS,
+        )
+        .expect("Can't create received_payments block_number index");
+        Ok(())
+    }
+
     fn extract_configurations(&self, conn: &Connection) -> HashMap<String, Option<String>> {
         let mut stmt = conn.prepare("select name, value from config").unwrap();
         let query_result = stmt.query_map(NO_PARAMS, |row| Ok((row.get(0), row.get(1))));
@@ -291,26 +375,16 @@ impl DbInitializerReal {
         .collect::<HashMap<String, Option<String>>>()
     }
 
-    fn check_version(&self, version: Option<&Option<String>>) -> Result<(), InitializationError> {
+    fn check_version(
+        &self,
+        version: Option<&Option<String>>,
+    ) -> Result<String, InitializationError> {
         match version {
-            None => Err(InitializationError::IncompatibleVersion(format!(
+            Some(Some(v_ref)) => Ok(v_ref.clone()),
+            _ => Err(InitializationError::IncompatibleVersion(format!(
                 "Need {}, found nothing",
                 CURRENT_SCHEMA_VERSION
             ))),
-            Some(None) => Err(InitializationError::IncompatibleVersion(format!(
-                "Need {}, found nothing",
-                CURRENT_SCHEMA_VERSION
-            ))),
-            Some(Some(v_ref)) => {
-                if *v_ref == CURRENT_SCHEMA_VERSION {
-                    Ok(())
-                } else {
-                    Err(InitializationError::IncompatibleVersion(format!(
-                        "Need {}, found {}",
-                        CURRENT_SCHEMA_VERSION, v_ref
-                    )))
-                }
-            }
         }
     }
 
@@ -398,7 +472,7 @@ pub mod test_utils {
 
     #[derive(Default)]
     pub struct DbInitializerMock {
-        pub initialize_parameters: Arc<Mutex<Vec<(PathBuf, u8, bool)>>>,
+        pub initialize_parameters: Arc<Mutex<Vec<(PathBuf, u64, bool)>>>,
         pub initialize_results:
             RefCell<Vec<Result<Box<dyn ConnectionWrapper>, InitializationError>>>,
     }
@@ -407,7 +481,7 @@ pub mod test_utils {
         fn initialize(
             &self,
             path: &PathBuf,
-            chain_id: u8,
+            chain_id: u64,
             create_if_necessary: bool,
         ) -> Result<Box<dyn ConnectionWrapper>, InitializationError> {
             self.initialize_parameters.lock().unwrap().push((
@@ -426,7 +500,7 @@ pub mod test_utils {
 
         pub fn initialize_parameters(
             mut self,
-            parameters: Arc<Mutex<Vec<(PathBuf, u8, bool)>>>,
+            parameters: Arc<Mutex<Vec<(PathBuf, u64, bool)>>>,
         ) -> DbInitializerMock {
             self.initialize_parameters = parameters;
             self
@@ -446,6 +520,7 @@ pub mod test_utils {
 mod tests {
     use super::*;
     use crate::blockchain::blockchain_interface::chain_id_from_name;
+    use crate::database::db_migrations::test_utils::DbMigratorMock;
     use masq_lib::constants::{
         DEFAULT_GAS_PRICE, HIGHEST_RANDOM_CLANDESTINE_PORT, LOWEST_USABLE_INSECURE_PORT,
     };
@@ -458,6 +533,7 @@ mod tests {
     use std::fs::File;
     use std::io::{Read, Write};
     use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+    use std::sync::{Arc, Mutex};
     use tokio::net::TcpListener;
 
     #[test]
@@ -558,11 +634,83 @@ mod tests {
         flags.insert(OpenFlags::SQLITE_OPEN_READ_ONLY);
         let conn = Connection::open_with_flags(&home_dir.join(DATABASE_FILE), flags).unwrap();
 
-        let mut stmt = conn.prepare("select wallet_address from banned").unwrap();
+        let mut stmt = conn
+            .prepare("select wallet_address, reason, banned_timestamp, manual from banned")
+            .unwrap();
         let mut banned_contents = stmt.query_map(NO_PARAMS, |_| Ok(42)).unwrap();
         assert!(banned_contents.next().is_none());
     }
 
+    #[test]
+    fn db_initialize_creates_pardoned_table() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "db_initializer",
+            "db_initialize_creates_pardoned_table",
+        );
+        let subject = DbInitializerReal::new();
+
+        subject
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+
+        let mut flags = OpenFlags::empty();
+        flags.insert(OpenFlags::SQLITE_OPEN_READ_ONLY);
+        let conn = Connection::open_with_flags(home_dir.join(DATABASE_FILE), flags).unwrap();
+
+        let mut stmt = conn
+            .prepare("select wallet_address, pardoned_timestamp, pardoned_balance from pardoned")
+            .unwrap();
+        let mut pardoned_contents = stmt.query_map(NO_PARAMS, |_| Ok(42)).unwrap();
+        assert!(pardoned_contents.next().is_none());
+    }
+
+    #[test]
+    fn db_initialize_creates_pending_payments_table() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "db_initializer",
+            "db_initialize_creates_pending_payments_table",
+        );
+        let subject = DbInitializerReal::new();
+
+        subject
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+
+        let mut flags = OpenFlags::empty();
+        flags.insert(OpenFlags::SQLITE_OPEN_READ_ONLY);
+        let conn = Connection::open_with_flags(&home_dir.join(DATABASE_FILE), flags).unwrap();
+        let mut stmt = conn
+            .prepare("select transaction_hash, wallet_address, amount, nonce, gas_price, priority_fee, attempted_timestamp from pending_payments")
+            .unwrap();
+        let mut pending_payments_contents = stmt.query_map(NO_PARAMS, |_| Ok(42)).unwrap();
+        assert!(pending_payments_contents.next().is_none());
+    }
+
+    #[test]
+    fn db_initialize_creates_received_payments_table() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "db_initializer",
+            "db_initialize_creates_received_payments_table",
+        );
+        let subject = DbInitializerReal::new();
+
+        subject
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+
+        let mut flags = OpenFlags::empty();
+        flags.insert(OpenFlags::SQLITE_OPEN_READ_ONLY);
+        let conn = Connection::open_with_flags(home_dir.join(DATABASE_FILE), flags).unwrap();
+        let mut stmt = conn
+            .prepare(
+                "select block_number, block_hash, wallet_address, amount, \
+                 prior_last_received_timestamp, prior_pardoned_balance from received_payments",
+            )
+            .unwrap();
+        let mut received_payments_contents = stmt.query_map(NO_PARAMS, |_| Ok(42)).unwrap();
+        assert!(received_payments_contents.next().is_none());
+    }
+
     #[test]
     fn existing_database_with_correct_version_is_accepted_without_changes() {
         let home_dir = ensure_node_home_directory_exists(
@@ -615,6 +763,7 @@ mod tests {
         verify(&mut config_vec, "earning_wallet_address", None);
         verify(&mut config_vec, "example_encrypted", None);
         verify(&mut config_vec, "gas_price", Some(DEFAULT_GAS_PRICE));
+        verify(&mut config_vec, "node_private_key", None);
         verify(&mut config_vec, "past_neighbors", None);
         verify(&mut config_vec, "preexisting", Some("yes")); // makes sure we just created this database
         verify(
@@ -698,6 +847,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn existing_database_with_an_older_version_is_migrated_to_the_current_version() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_initializer",
+            "existing_database_with_an_older_version_is_migrated_to_the_current_version",
+        );
+        {
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap();
+            let mut flags = OpenFlags::empty();
+            flags.insert(OpenFlags::SQLITE_OPEN_READ_WRITE);
+            let conn = Connection::open_with_flags(&home_dir.join(DATABASE_FILE), flags).unwrap();
+            conn.execute(
+                "update config set value = '0.0.0' where name = 'schema_version'",
+                NO_PARAMS,
+            )
+            .unwrap();
+        }
+        let migrate_database_params_arc = Arc::new(Mutex::new(vec![]));
+        let migrator = DbMigratorMock::default()
+            .migrate_database_params(&migrate_database_params_arc)
+            .migrate_database_result(Ok(()));
+        let subject = DbInitializerReal::new_with_migrator(Box::new(migrator));
+
+        let result = subject.initialize(&home_dir, DEFAULT_CHAIN_ID, true);
+
+        assert!(result.is_ok());
+        let migrate_database_params = migrate_database_params_arc.lock().unwrap();
+        assert_eq!(
+            *migrate_database_params,
+            vec![("0.0.0".to_string(), CURRENT_SCHEMA_VERSION.to_string())]
+        );
+    }
+
+    #[test]
+    fn existing_database_whose_migration_fails_is_rejected() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_initializer",
+            "existing_database_whose_migration_fails_is_rejected",
+        );
+        {
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+                .unwrap();
+            let mut flags = OpenFlags::empty();
+            flags.insert(OpenFlags::SQLITE_OPEN_READ_WRITE);
+            let conn = Connection::open_with_flags(&home_dir.join(DATABASE_FILE), flags).unwrap();
+            conn.execute(
+                "update config set value = '0.0.0' where name = 'schema_version'",
+                NO_PARAMS,
+            )
+            .unwrap();
+        }
+        let migrator = DbMigratorMock::default().migrate_database_result(Err(
+            InitializationError::MigrationFailed(
+                "0.0.0".to_string(),
+                "0.0.1".to_string(),
+                "booga".to_string(),
+            ),
+        ));
+        let subject = DbInitializerReal::new_with_migrator(Box::new(migrator));
+
+        let result = subject.initialize(&home_dir, DEFAULT_CHAIN_ID, true);
+
+        assert_eq!(
+            result.err().unwrap(),
+            InitializationError::MigrationFailed(
+                "0.0.0".to_string(),
+                "0.0.1".to_string(),
+                "booga".to_string()
+            )
+        );
+    }
+
     #[test]
     fn choose_clandestine_port_chooses_different_unused_ports_each_time() {
         let _listeners = (0..10)
@@ -0,0 +1,1305 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::banned_dao::DELINQUENCY_BAN_REASON;
+use crate::database::dao_utils::now_time_t;
+use crate::database::db_initializer::InitializationError;
+use rusqlite::{Connection, Transaction, NO_PARAMS};
+use std::fmt::Debug;
+
+// Each step upgrades the database from exactly one schema version to the next. When you change
+// the schema, bump CURRENT_SCHEMA_VERSION in db_initializer.rs, change the table-creation code
+// there so that new databases come out right, and add a step to the end of this chain so that
+// old databases are brought up to date without losing their payables, receivables, bans, or
+// configuration.
+pub fn production_migrations() -> Vec<Box<dyn DatabaseMigration>> {
+    vec![
+        Box::new(AddPendingPaymentsTable {}),
+        Box::new(AddNodePrivateKeyConfig {}),
+        Box::new(AddBanDetailsAndPardons {}),
+        Box::new(AddReceivedPaymentsTable {}),
+        Box::new(AddPriorityFeeToPendingPayments {}),
+        Box::new(KeyPendingPaymentsByTransactionAndWallet {}),
+        Box::new(AddPardonedBalance {}),
+        Box::new(AddPriorStateToReceivedPayments {}),
+    ]
+}
+
+pub trait DatabaseMigration: Debug + Send {
+    fn old_version(&self) -> &'static str;
+    fn new_version(&self) -> &'static str;
+    fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()>;
+}
+
+// An older Node took what it sent off the balance and marked the payable with the transaction,
+// but never recorded the nonce or the amount, so the payment can't be reconciled against the
+// blockchain. Left marked, the payable would never be paid again; unmarked, the payment counts as
+// made, the way the older Node assumed it would be, and whatever is owed from now on gets paid.
+#[derive(Debug)]
+struct AddPendingPaymentsTable {}
+
+impl DatabaseMigration for AddPendingPaymentsTable {
+    fn old_version(&self) -> &'static str {
+        "0.0.10"
+    }
+
+    fn new_version(&self) -> &'static str {
+        "0.0.11"
+    }
+
+    fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()> {
+        transaction.execute(
+            "create table pending_payments (
+                transaction_hash text primary key,
+                wallet_address text not null,
+                amount integer not null,
+                nonce text not null,
+                gas_price integer not null,
+                attempted_timestamp integer not null
+            )",
+            NO_PARAMS,
+        )?;
+        transaction.execute(
+            "create index idx_pending_payments_wallet_address on pending_payments (wallet_address)",
+            NO_PARAMS,
+        )?;
+        transaction.execute(
+            "update payable set pending_payment_transaction = null",
+            NO_PARAMS,
+        )?;
+        Ok(())
+    }
+}
+
+// Older Nodes never saved their key pair, so they'll get a new one the first time they start
+// after this migration and keep it from then on.
+#[derive(Debug)]
+struct AddNodePrivateKeyConfig {}
+
+impl DatabaseMigration for AddNodePrivateKeyConfig {
+    fn old_version(&self) -> &'static str {
+        "0.0.11"
+    }
+
+    fn new_version(&self) -> &'static str {
+        "0.0.12"
+    }
+
+    fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()> {
+        transaction.execute(
+            "insert into config (name, value, encrypted) values ('node_private_key', null, 1)",
+            NO_PARAMS,
+        )?;
+        Ok(())
+    }
+}
+
+// Bans that an older Node imposed were all imposed for delinquency, but nobody recorded when; they
+// are dated to the moment of migration.
+#[derive(Debug)]
+struct AddBanDetailsAndPardons {}
+
+impl DatabaseMigration for AddBanDetailsAndPardons {
+    fn old_version(&self) -> &'static str {
+        "0.0.12"
+    }
+
+    fn new_version(&self) -> &'static str {
+        "0.0.13"
+    }
+
+    fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()> {
+        transaction.execute(
+            &format!(
+                "alter table banned add column reason text not null default '{}'",
+                DELINQUENCY_BAN_REASON
+            ),
+            NO_PARAMS,
+        )?;
+        transaction.execute(
+            "alter table banned add column banned_timestamp integer not null default 0",
+            NO_PARAMS,
+        )?;
+        transaction.execute(
+            "alter table banned add column manual integer not null default 0",
+            NO_PARAMS,
+        )?;
+        transaction.execute("update banned set banned_timestamp = ?", [now_time_t()])?;
+        transaction.execute(
+            "create table pardoned (
+                wallet_address text primary key,
+                pardoned_timestamp integer not null
+            )",
+            NO_PARAMS,
+        )?;
+        Ok(())
+    }
+}
+
+// Payments that an older Node credited weren't recorded with the blocks they came from, so if a
+// reorganization orphans one of those blocks, its credit stays.
+#[derive(Debug)]
+struct AddReceivedPaymentsTable {}
+
+impl DatabaseMigration for AddReceivedPaymentsTable {
+    fn old_version(&self) -> &'static str {
+        "0.0.13"
+    }
+
+    fn new_version(&self) -> &'static str {
+        "0.0.14"
+    }
+
+    fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()> {
+        transaction.execute(
+            "create table received_payments (
+                block_number integer not null,
+                block_hash text not null,
+                wallet_address text not null,
+                amount integer not null
+            )",
+            NO_PARAMS,
+        )?;
+        transaction.execute(
+            "create index idx_received_payments_block_number on received_payments (block_number)",
+            NO_PARAMS,
+        )?;
+        Ok(())
+    }
+}
+
+// Payments that an older Node sent were all legacy transactions, so they have no priority fee.
+#[derive(Debug)]
+struct AddPriorityFeeToPendingPayments {}
+
+impl DatabaseMigration for AddPriorityFeeToPendingPayments {
+    fn old_version(&self) -> &'static str {
+        "0.0.14"
+    }
+
+    fn new_version(&self) -> &'static str {
+        "0.0.15"
+    }
+
+    fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()> {
+        transaction.execute(
+            "alter table pending_payments add column priority_fee integer",
+            NO_PARAMS,
+        )?;
+        Ok(())
+    }
+}
+
+// A batch transaction pays several wallets, so the transaction hash alone no longer identifies a
+// pending payment. SQLite can't change a table's primary key, so the table is rebuilt.
+#[derive(Debug)]
+struct KeyPendingPaymentsByTransactionAndWallet {}
+
+impl DatabaseMigration for KeyPendingPaymentsByTransactionAndWallet {
+    fn old_version(&self) -> &'static str {
+        "0.0.15"
+    }
+
+    fn new_version(&self) -> &'static str {
+        "0.0.16"
+    }
+
+    fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()> {
+        transaction.execute(
+            "create table pending_payments_0_0_15 as select * from pending_payments",
+            NO_PARAMS,
+        )?;
+        transaction.execute("drop table pending_payments", NO_PARAMS)?;
+        transaction.execute(
+            "create table pending_payments (
+                transaction_hash text not null,
+                wallet_address text not null,
+                amount integer not null,
+                nonce text not null,
+                gas_price integer not null,
+                priority_fee integer,
+                attempted_timestamp integer not null,
+                primary key (transaction_hash, wallet_address)
+            )",
+            NO_PARAMS,
+        )?;
+        transaction.execute(
+            "create index idx_pending_payments_wallet_address on pending_payments (wallet_address)",
+            NO_PARAMS,
+        )?;
+        transaction.execute(
+            "insert into pending_payments select transaction_hash, wallet_address, amount, nonce, \
+             gas_price, priority_fee, attempted_timestamp from pending_payments_0_0_15",
+            NO_PARAMS,
+        )?;
+        transaction.execute("drop table pending_payments_0_0_15", NO_PARAMS)?;
+        Ok(())
+    }
+}
+
+// A pardon used to cover whatever the wallet went on to owe. Existing pardons are held to what the
+// wallet owes at the time of the migration, which is at least as much as it owed when pardoned
+// unless it has paid since.
+#[derive(Debug)]
+struct AddPardonedBalance {}
+
+impl DatabaseMigration for AddPardonedBalance {
+    fn old_version(&self) -> &'static str {
+        "0.0.16"
+    }
+
+    fn new_version(&self) -> &'static str {
+        "0.0.17"
+    }
+
+    fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()> {
+        transaction.execute(
+            "alter table pardoned add column pardoned_balance integer not null default 0",
+            NO_PARAMS,
+        )?;
+        transaction.execute(
+            "update pardoned set pardoned_balance = coalesce(\
+             (select balance from receivable r where r.wallet_address = pardoned.wallet_address), 0)",
+            NO_PARAMS,
+        )?;
+        Ok(())
+    }
+}
+
+// Payments that an older Node credited weren't recorded with the wallet's last payment time and
+// pardoned balance from before they came in, so if a reorganization orphans one of those payments,
+// its wallet keeps both as they were after the payment.
+#[derive(Debug)]
+struct AddPriorStateToReceivedPayments {}
+
+impl DatabaseMigration for AddPriorStateToReceivedPayments {
+    fn old_version(&self) -> &'static str {
+        "0.0.17"
+    }
+
+    fn new_version(&self) -> &'static str {
+        "0.0.18"
+    }
+
+    fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()> {
+        transaction.execute(
+            "alter table received_payments add column prior_last_received_timestamp integer",
+            NO_PARAMS,
+        )?;
+        transaction.execute(
+            "alter table received_payments add column prior_pardoned_balance integer",
+            NO_PARAMS,
+        )?;
+        Ok(())
+    }
+}
+
+pub trait DbMigrator: Send {
+    fn migrate_database(
+        &self,
+        conn: &mut Connection,
+        found_version: &str,
+        target_version: &str,
+    ) -> Result<(), InitializationError>;
+}
+
+pub struct DbMigratorReal {
+    migrations: Vec<Box<dyn DatabaseMigration>>,
+}
+
+impl DbMigrator for DbMigratorReal {
+    fn migrate_database(
+        &self,
+        conn: &mut Connection,
+        found_version: &str,
+        target_version: &str,
+    ) -> Result<(), InitializationError> {
+        let steps = self.plan(found_version, target_version)?;
+        for step in steps {
+            Self::apply(conn, step)?;
+            eprintln!(
+                "Migrated database from schema version {} to {}",
+                step.old_version(),
+                step.new_version()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for DbMigratorReal {
+    fn default() -> Self {
+        Self::new(production_migrations())
+    }
+}
+
+impl DbMigratorReal {
+    pub fn new(migrations: Vec<Box<dyn DatabaseMigration>>) -> Self {
+        Self { migrations }
+    }
+
+    fn plan(
+        &self,
+        found_version: &str,
+        target_version: &str,
+    ) -> Result<Vec<&dyn DatabaseMigration>, InitializationError> {
+        let mut steps: Vec<&dyn DatabaseMigration> = vec![];
+        let mut version = found_version;
+        while version != target_version {
+            match self
+                .migrations
+                .iter()
+                .find(|step| step.old_version() == version)
+            {
+                Some(step) if steps.len() < self.migrations.len() => {
+                    steps.push(step.as_ref());
+                    version = step.new_version();
+                }
+                _ => {
+                    return Err(InitializationError::IncompatibleVersion(format!(
+                        "Need {}, found {}",
+                        target_version, found_version
+                    )))
+                }
+            }
+        }
+        Ok(steps)
+    }
+
+    fn apply(
+        conn: &mut Connection,
+        step: &dyn DatabaseMigration,
+    ) -> Result<(), InitializationError> {
+        let transaction = conn
+            .transaction()
+            .map_err(InitializationError::SqliteError)?;
+        let result = step.migrate(&transaction).and_then(|_| {
+            transaction.execute(
+                "update config set value = ? where name = 'schema_version'",
+                &[step.new_version()],
+            )
+        });
+        match result {
+            Ok(_) => transaction
+                .commit()
+                .map_err(InitializationError::SqliteError),
+            Err(e) => {
+                let _ = transaction.rollback();
+                Err(InitializationError::MigrationFailed(
+                    step.old_version().to_string(),
+                    step.new_version().to_string(),
+                    format!("{:?}", e),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use crate::database::db_initializer::{InitializationError, DATABASE_FILE};
+    use crate::database::db_migrations::DbMigrator;
+    use rusqlite::{Connection, OpenFlags, NO_PARAMS};
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    pub struct DbMigratorMock {
+        migrate_database_params: Arc<Mutex<Vec<(String, String)>>>,
+        migrate_database_results: RefCell<Vec<Result<(), InitializationError>>>,
+    }
+
+    unsafe impl Send for DbMigratorMock {}
+
+    impl DbMigrator for DbMigratorMock {
+        fn migrate_database(
+            &self,
+            _conn: &mut Connection,
+            found_version: &str,
+            target_version: &str,
+        ) -> Result<(), InitializationError> {
+            self.migrate_database_params
+                .lock()
+                .unwrap()
+                .push((found_version.to_string(), target_version.to_string()));
+            self.migrate_database_results.borrow_mut().remove(0)
+        }
+    }
+
+    impl DbMigratorMock {
+        pub fn migrate_database_params(
+            mut self,
+            params: &Arc<Mutex<Vec<(String, String)>>>,
+        ) -> Self {
+            self.migrate_database_params = params.clone();
+            self
+        }
+
+        pub fn migrate_database_result(self, result: Result<(), InitializationError>) -> Self {
+            self.migrate_database_results.borrow_mut().push(result);
+            self
+        }
+    }
+
+    // Builds a database the way a Node of the given schema version would have built it, so that
+    // migration tests have something realistic to chew on. When you add a migration, add a case
+    // here that reproduces the schema as it was before your change.
+    pub fn make_fixture_database(data_dir: &PathBuf, schema_version: &str) -> Connection {
+        let mut flags = OpenFlags::empty();
+        flags.insert(OpenFlags::SQLITE_OPEN_READ_WRITE);
+        flags.insert(OpenFlags::SQLITE_OPEN_CREATE);
+        let conn = Connection::open_with_flags(&data_dir.join(DATABASE_FILE), flags).unwrap();
+        match schema_version {
+            "0.0.10" => make_0_0_10_tables(&conn),
+            "0.0.11" => make_0_0_11_tables(&conn),
+            "0.0.12" => make_0_0_12_tables(&conn),
+            "0.0.13" => make_0_0_13_tables(&conn),
+            "0.0.14" => make_0_0_14_tables(&conn),
+            "0.0.15" => make_0_0_15_tables(&conn),
+            "0.0.16" => make_0_0_16_tables(&conn),
+            "0.0.17" => make_0_0_17_tables(&conn),
+            unknown => panic!("No fixture for schema version {}", unknown),
+        }
+        conn
+    }
+
+    pub fn populate_fixture_database(conn: &Connection) {
+        conn.execute(
+            "insert into payable (wallet_address, balance, last_paid_timestamp, pending_payment_transaction) \
+             values ('0x1111111111111111111111111111111111111111', 1234, 100000, null)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "insert into receivable (wallet_address, balance, last_received_timestamp) \
+             values ('0x2222222222222222222222222222222222222222', 2345, 200000)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "insert into banned (wallet_address) values ('0x3333333333333333333333333333333333333333')",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "update config set value = '0x4444444444444444444444444444444444444444' \
+             where name = 'earning_wallet_address'",
+            NO_PARAMS,
+        )
+        .unwrap();
+    }
+
+    fn make_0_0_10_tables(conn: &Connection) {
+        conn.execute(
+            "create table config (name text not null, value text, encrypted integer)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create unique index idx_config_name on config (name)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        [
+            ("example_encrypted", None, 0),
+            ("clandestine_port", Some("2897"), 0),
+            ("consuming_wallet_derivation_path", None, 0),
+            ("consuming_wallet_public_key", None, 0),
+            ("earning_wallet_address", None, 0),
+            ("schema_version", Some("0.0.10"), 0),
+            ("seed", None, 1),
+            ("start_block", Some("8688171"), 0),
+            ("gas_price", Some("1"), 0),
+            ("past_neighbors", None, 1),
+        ]
+        .iter()
+        .for_each(|(name, value, encrypted)| {
+            conn.execute(
+                "insert into config (name, value, encrypted) values (?, ?, ?)",
+                &[
+                    name as &dyn rusqlite::ToSql,
+                    value as &dyn rusqlite::ToSql,
+                    encrypted as &dyn rusqlite::ToSql,
+                ],
+            )
+            .unwrap();
+        });
+        conn.execute(
+            "create table payable (
+                wallet_address text primary key,
+                balance integer not null,
+                last_paid_timestamp integer not null,
+                pending_payment_transaction text null
+            )",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create unique index idx_payable_wallet_address on payable (wallet_address)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create table receivable (
+                wallet_address text primary key,
+                balance integer not null,
+                last_received_timestamp integer not null
+            )",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create unique index idx_receivable_wallet_address on receivable (wallet_address)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create table banned ( wallet_address text primary key )",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create unique index idx_banned_wallet_address on banned (wallet_address)",
+            NO_PARAMS,
+        )
+        .unwrap();
+    }
+
+    fn make_0_0_11_tables(conn: &Connection) {
+        make_0_0_10_tables(conn);
+        conn.execute(
+            "update config set value = '0.0.11' where name = 'schema_version'",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create table pending_payments (
+                transaction_hash text primary key,
+                wallet_address text not null,
+                amount integer not null,
+                nonce text not null,
+                gas_price integer not null,
+                attempted_timestamp integer not null
+            )",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create index idx_pending_payments_wallet_address on pending_payments (wallet_address)",
+            NO_PARAMS,
+        )
+        .unwrap();
+    }
+
+    fn make_0_0_12_tables(conn: &Connection) {
+        make_0_0_11_tables(conn);
+        conn.execute(
+            "update config set value = '0.0.12' where name = 'schema_version'",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "insert into config (name, value, encrypted) values ('node_private_key', null, 1)",
+            NO_PARAMS,
+        )
+        .unwrap();
+    }
+
+    fn make_0_0_13_tables(conn: &Connection) {
+        make_0_0_12_tables(conn);
+        conn.execute(
+            "update config set value = '0.0.13' where name = 'schema_version'",
+            NO_PARAMS,
+        )
+        .unwrap();
+        [
+            "alter table banned add column reason text not null default 'delinquency'",
+            "alter table banned add column banned_timestamp integer not null default 0",
+            "alter table banned add column manual integer not null default 0",
+        ]
+        .iter()
+        .for_each(|statement| {
+            conn.execute(statement, NO_PARAMS).unwrap();
+        });
+        conn.execute(
+            "create table pardoned (
+                wallet_address text primary key,
+                pardoned_timestamp integer not null
+            )",
+            NO_PARAMS,
+        )
+        .unwrap();
+    }
+
+    fn make_0_0_14_tables(conn: &Connection) {
+        make_0_0_13_tables(conn);
+        conn.execute(
+            "update config set value = '0.0.14' where name = 'schema_version'",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create table received_payments (
+                block_number integer not null,
+                block_hash text not null,
+                wallet_address text not null,
+                amount integer not null
+            )",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create index idx_received_payments_block_number on received_payments (block_number)",
+            NO_PARAMS,
+        )
+        .unwrap();
+    }
+
+    fn make_0_0_15_tables(conn: &Connection) {
+        make_0_0_14_tables(conn);
+        conn.execute(
+            "update config set value = '0.0.15' where name = 'schema_version'",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "alter table pending_payments add column priority_fee integer",
+            NO_PARAMS,
+        )
+        .unwrap();
+    }
+
+    fn make_0_0_16_tables(conn: &Connection) {
+        make_0_0_15_tables(conn);
+        conn.execute(
+            "update config set value = '0.0.16' where name = 'schema_version'",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute("drop table pending_payments", NO_PARAMS)
+            .unwrap();
+        conn.execute(
+            "create table pending_payments (
+                transaction_hash text not null,
+                wallet_address text not null,
+                amount integer not null,
+                nonce text not null,
+                gas_price integer not null,
+                priority_fee integer,
+                attempted_timestamp integer not null,
+                primary key (transaction_hash, wallet_address)
+            )",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "create index idx_pending_payments_wallet_address on pending_payments (wallet_address)",
+            NO_PARAMS,
+        )
+        .unwrap();
+    }
+
+    fn make_0_0_17_tables(conn: &Connection) {
+        make_0_0_16_tables(conn);
+        conn.execute(
+            "update config set value = '0.0.17' where name = 'schema_version'",
+            NO_PARAMS,
+        )
+        .unwrap();
+        conn.execute(
+            "alter table pardoned add column pardoned_balance integer not null default 0",
+            NO_PARAMS,
+        )
+        .unwrap();
+    }
+
+    pub fn schema_version(conn: &Connection) -> String {
+        conn.query_row(
+            "select value from config where name = 'schema_version'",
+            NO_PARAMS,
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::db_initializer::{
+        DbInitializer, DbInitializerReal, CURRENT_SCHEMA_VERSION, DATABASE_FILE,
+    };
+    use crate::database::db_migrations::test_utils::{
+        make_fixture_database, populate_fixture_database, schema_version,
+    };
+    use masq_lib::test_utils::utils::{ensure_node_home_directory_exists, DEFAULT_CHAIN_ID};
+
+    #[derive(Debug)]
+    struct TestMigration {
+        old_version: &'static str,
+        new_version: &'static str,
+        statements: Vec<&'static str>,
+    }
+
+    impl DatabaseMigration for TestMigration {
+        fn old_version(&self) -> &'static str {
+            self.old_version
+        }
+
+        fn new_version(&self) -> &'static str {
+            self.new_version
+        }
+
+        fn migrate(&self, transaction: &Transaction) -> rusqlite::Result<()> {
+            self.statements
+                .iter()
+                .try_for_each(|statement| transaction.execute(statement, NO_PARAMS).map(|_| ()))
+        }
+    }
+
+    fn test_migration(
+        old_version: &'static str,
+        new_version: &'static str,
+        statements: Vec<&'static str>,
+    ) -> Box<dyn DatabaseMigration> {
+        Box::new(TestMigration {
+            old_version,
+            new_version,
+            statements,
+        })
+    }
+
+    fn config_value(conn: &Connection, name: &str) -> Option<String> {
+        conn.query_row("select value from config where name = ?", &[name], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    fn count_rows(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(
+            format!("select count(*) from {}", table).as_str(),
+            NO_PARAMS,
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn migrator_applies_steps_in_order_regardless_of_their_order_in_the_chain() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migrator_applies_steps_in_order_regardless_of_their_order_in_the_chain",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.10");
+        populate_fixture_database(&conn);
+        let subject = DbMigratorReal::new(vec![
+            test_migration(
+                "0.0.11",
+                "0.0.12",
+                vec!["update config set value = value || '-second' where name = 'step_trail'"],
+            ),
+            test_migration(
+                "0.0.10",
+                "0.0.11",
+                vec![
+                    "insert into config (name, value, encrypted) values ('step_trail', 'first', 0)",
+                    "alter table banned add column reason text null",
+                ],
+            ),
+        ]);
+
+        let result = subject.migrate_database(&mut conn, "0.0.10", "0.0.12");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.12".to_string());
+        assert_eq!(
+            config_value(&conn, "step_trail"),
+            Some("first-second".to_string())
+        );
+        let reason: Option<String> = conn
+            .query_row("select reason from banned", NO_PARAMS, |row| row.get(0))
+            .unwrap();
+        assert_eq!(reason, None);
+        assert_eq!(count_rows(&conn, "payable"), 1);
+        assert_eq!(count_rows(&conn, "receivable"), 1);
+        assert_eq!(count_rows(&conn, "banned"), 1);
+        assert_eq!(
+            config_value(&conn, "earning_wallet_address"),
+            Some("0x4444444444444444444444444444444444444444".to_string())
+        );
+    }
+
+    #[test]
+    fn migrator_leaves_database_alone_when_there_is_no_path_to_the_target_version() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migrator_leaves_database_alone_when_there_is_no_path_to_the_target_version",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.10");
+        let subject = DbMigratorReal::new(vec![
+            test_migration(
+                "0.0.10",
+                "0.0.11",
+                vec!["insert into config (name, value, encrypted) values ('booga', 'yes', 0)"],
+            ),
+            test_migration("0.0.12", "0.0.13", vec![]),
+        ]);
+
+        let result = subject.migrate_database(&mut conn, "0.0.10", "0.0.13");
+
+        assert_eq!(
+            result,
+            Err(InitializationError::IncompatibleVersion(
+                "Need 0.0.13, found 0.0.10".to_string()
+            ))
+        );
+        assert_eq!(schema_version(&conn), "0.0.10".to_string());
+        assert_eq!(count_rows(&conn, "config"), 10);
+    }
+
+    #[test]
+    fn migrator_refuses_to_chase_a_circular_chain() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migrator_refuses_to_chase_a_circular_chain",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.10");
+        let subject = DbMigratorReal::new(vec![
+            test_migration("0.0.10", "0.0.11", vec![]),
+            test_migration("0.0.11", "0.0.10", vec![]),
+        ]);
+
+        let result = subject.migrate_database(&mut conn, "0.0.10", "0.0.12");
+
+        assert_eq!(
+            result,
+            Err(InitializationError::IncompatibleVersion(
+                "Need 0.0.12, found 0.0.10".to_string()
+            ))
+        );
+        assert_eq!(schema_version(&conn), "0.0.10".to_string());
+    }
+
+    #[test]
+    fn failed_step_is_rolled_back_and_earlier_steps_are_kept() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "failed_step_is_rolled_back_and_earlier_steps_are_kept",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.10");
+        populate_fixture_database(&conn);
+        let subject = DbMigratorReal::new(vec![
+            test_migration(
+                "0.0.10",
+                "0.0.11",
+                vec!["insert into config (name, value, encrypted) values ('first', 'yes', 0)"],
+            ),
+            test_migration(
+                "0.0.11",
+                "0.0.12",
+                vec![
+                    "delete from payable",
+                    "insert into config (name, value, encrypted) values ('second', 'yes', 0)",
+                    "insert into nonexistent_table (booga) values (1)",
+                ],
+            ),
+        ]);
+
+        let result = subject.migrate_database(&mut conn, "0.0.10", "0.0.12");
+
+        match result {
+            Err(InitializationError::MigrationFailed(old, new, msg)) => {
+                assert_eq!(old, "0.0.11".to_string());
+                assert_eq!(new, "0.0.12".to_string());
+                assert!(msg.contains("nonexistent_table"), "{}", msg);
+            }
+            x => panic!("Expected MigrationFailed, got {:?}", x),
+        }
+        assert_eq!(schema_version(&conn), "0.0.11".to_string());
+        assert_eq!(config_value(&conn, "first"), Some("yes".to_string()));
+        let second_count: i64 = conn
+            .query_row(
+                "select count(*) from config where name = 'second'",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(second_count, 0);
+        assert_eq!(count_rows(&conn, "payable"), 1);
+    }
+
+    #[test]
+    fn migrator_does_nothing_to_a_database_that_is_already_at_the_target_version() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migrator_does_nothing_to_a_database_that_is_already_at_the_target_version",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.10");
+        let subject = DbMigratorReal::new(vec![test_migration(
+            "0.0.10",
+            "0.0.11",
+            vec!["insert into nonexistent_table (booga) values (1)"],
+        )]);
+
+        let result = subject.migrate_database(&mut conn, "0.0.10", "0.0.10");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.10".to_string());
+    }
+
+    #[test]
+    fn production_migrations_form_an_unbroken_chain_ending_at_the_current_version() {
+        let migrations = production_migrations();
+
+        migrations.iter().enumerate().for_each(|(idx, step)| {
+            let expected_new_version = match migrations.get(idx + 1) {
+                Some(next_step) => next_step.old_version(),
+                None => CURRENT_SCHEMA_VERSION,
+            };
+            assert_eq!(step.new_version(), expected_new_version);
+        });
+    }
+
+    #[test]
+    fn migration_from_0_0_10_adds_an_empty_pending_payments_table() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_0_0_10_adds_an_empty_pending_payments_table",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.10");
+        populate_fixture_database(&conn);
+        let subject = DbMigratorReal::default();
+
+        let result = subject.migrate_database(&mut conn, "0.0.10", "0.0.11");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.11".to_string());
+        assert_eq!(count_rows(&conn, "pending_payments"), 0);
+        conn.execute(
+            "insert into pending_payments (transaction_hash, wallet_address, amount, nonce, gas_price, attempted_timestamp) \
+             values ('0x5555', '0x1111111111111111111111111111111111111111', 1234, '0x1', 1, 300000)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        assert_eq!(count_rows(&conn, "pending_payments"), 1);
+        assert_eq!(count_rows(&conn, "payable"), 1);
+    }
+
+    #[test]
+    fn migration_from_0_0_10_lets_payables_marked_as_sent_be_paid_again() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_0_0_10_lets_payables_marked_as_sent_be_paid_again",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.10");
+        populate_fixture_database(&conn);
+        conn.execute(
+            "insert into payable (wallet_address, balance, last_paid_timestamp, pending_payment_transaction) \
+             values ('0x5555555555555555555555555555555555555555', 3456, 100000, '0x7777')",
+            NO_PARAMS,
+        )
+        .unwrap();
+        let subject = DbMigratorReal::default();
+
+        let result = subject.migrate_database(&mut conn, "0.0.10", "0.0.11");
+
+        assert_eq!(result, Ok(()));
+        let stranded: (i64, Option<String>) = conn
+            .query_row(
+                "select balance, pending_payment_transaction from payable \
+                 where wallet_address = '0x5555555555555555555555555555555555555555'",
+                NO_PARAMS,
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(stranded, (3456, None));
+    }
+
+    #[test]
+    fn migration_from_0_0_11_adds_an_empty_encrypted_node_private_key_row() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_0_0_11_adds_an_empty_encrypted_node_private_key_row",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.11");
+        populate_fixture_database(&conn);
+        let subject = DbMigratorReal::default();
+
+        let result = subject.migrate_database(&mut conn, "0.0.11", "0.0.12");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.12".to_string());
+        assert_eq!(config_value(&conn, "node_private_key"), None);
+        let encrypted: i64 = conn
+            .query_row(
+                "select encrypted from config where name = 'node_private_key'",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(encrypted, 1);
+        assert_eq!(count_rows(&conn, "config"), 11);
+    }
+
+    #[test]
+    fn migration_from_0_0_12_dates_existing_bans_and_adds_an_empty_pardoned_table() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_0_0_12_dates_existing_bans_and_adds_an_empty_pardoned_table",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.12");
+        populate_fixture_database(&conn);
+        let subject = DbMigratorReal::default();
+        let before = now_time_t();
+
+        let result = subject.migrate_database(&mut conn, "0.0.12", "0.0.13");
+
+        let after = now_time_t();
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.13".to_string());
+        let (wallet_address, reason, banned_timestamp, manual): (String, String, i64, i64) = conn
+            .query_row(
+                "select wallet_address, reason, banned_timestamp, manual from banned",
+                NO_PARAMS,
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            wallet_address,
+            "0x3333333333333333333333333333333333333333".to_string()
+        );
+        assert_eq!(reason, DELINQUENCY_BAN_REASON.to_string());
+        assert!(
+            banned_timestamp >= before && banned_timestamp <= after,
+            "{} should be between {} and {}",
+            banned_timestamp,
+            before,
+            after
+        );
+        assert_eq!(manual, 0);
+        assert_eq!(count_rows(&conn, "pardoned"), 0);
+    }
+
+    #[test]
+    fn migration_from_0_0_13_adds_an_empty_received_payments_table() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_0_0_13_adds_an_empty_received_payments_table",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.13");
+        populate_fixture_database(&conn);
+        let subject = DbMigratorReal::default();
+
+        let result = subject.migrate_database(&mut conn, "0.0.13", "0.0.14");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.14".to_string());
+        assert_eq!(count_rows(&conn, "received_payments"), 0);
+        conn.execute(
+            "insert into received_payments (block_number, block_hash, wallet_address, amount) \
+             values (1234, '0x5555', '0x2222222222222222222222222222222222222222', 2345)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        assert_eq!(count_rows(&conn, "received_payments"), 1);
+        assert_eq!(count_rows(&conn, "receivable"), 1);
+    }
+
+    #[test]
+    fn migration_from_0_0_14_leaves_pending_payments_without_a_priority_fee() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_0_0_14_leaves_pending_payments_without_a_priority_fee",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.14");
+        populate_fixture_database(&conn);
+        conn.execute(
+            "insert into pending_payments (transaction_hash, wallet_address, amount, nonce, gas_price, attempted_timestamp) \
+             values ('0x5555', '0x1111111111111111111111111111111111111111', 1234, '0x1', 1, 300000)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        let subject = DbMigratorReal::default();
+
+        let result = subject.migrate_database(&mut conn, "0.0.14", "0.0.15");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.15".to_string());
+        let (gas_price, priority_fee): (i64, Option<i64>) = conn
+            .query_row(
+                "select gas_price, priority_fee from pending_payments",
+                NO_PARAMS,
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((gas_price, priority_fee), (1, None));
+        assert_eq!(count_rows(&conn, "payable"), 1);
+    }
+
+    #[test]
+    fn migration_from_0_0_15_keeps_pending_payments_and_lets_a_transaction_pay_several_wallets() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_0_0_15_keeps_pending_payments_and_lets_a_transaction_pay_several_wallets",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.15");
+        populate_fixture_database(&conn);
+        conn.execute(
+            "insert into pending_payments (transaction_hash, wallet_address, amount, nonce, gas_price, priority_fee, attempted_timestamp) \
+             values ('0x5555', '0x1111111111111111111111111111111111111111', 1234, '0x1', 12, 2, 300000)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        let subject = DbMigratorReal::default();
+
+        let result = subject.migrate_database(&mut conn, "0.0.15", "0.0.16");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.16".to_string());
+        let pending_payment: (String, String, i64, String, i64, Option<i64>, i64) = conn
+            .query_row(
+                "select transaction_hash, wallet_address, amount, nonce, gas_price, priority_fee, attempted_timestamp from pending_payments",
+                NO_PARAMS,
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(6)?,
+                    ))
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            pending_payment,
+            (
+                "0x5555".to_string(),
+                "0x1111111111111111111111111111111111111111".to_string(),
+                1234,
+                "0x1".to_string(),
+                12,
+                Some(2),
+                300000
+            )
+        );
+        conn.execute(
+            "insert into pending_payments (transaction_hash, wallet_address, amount, nonce, gas_price, priority_fee, attempted_timestamp) \
+             values ('0x5555', '0x6666666666666666666666666666666666666666', 2345, '0x1', 12, 2, 300000)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        assert_eq!(count_rows(&conn, "pending_payments"), 2);
+        assert_eq!(count_rows(&conn, "payable"), 1);
+    }
+
+    #[test]
+    fn migration_from_0_0_16_holds_existing_pardons_to_what_their_wallets_owe_now() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_0_0_16_holds_existing_pardons_to_what_their_wallets_owe_now",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.16");
+        populate_fixture_database(&conn);
+        conn.execute(
+            "insert into pardoned (wallet_address, pardoned_timestamp) values \
+             ('0x2222222222222222222222222222222222222222', 300000), \
+             ('0x5555555555555555555555555555555555555555', 400000)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        let subject = DbMigratorReal::default();
+
+        let result = subject.migrate_database(&mut conn, "0.0.16", "0.0.17");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.17".to_string());
+        let mut stmt = conn
+            .prepare("select wallet_address, pardoned_timestamp, pardoned_balance from pardoned order by wallet_address")
+            .unwrap();
+        let pardons: Vec<(String, i64, i64)> = stmt
+            .query_map(NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .flatten()
+            .collect();
+        assert_eq!(
+            pardons,
+            vec![
+                (
+                    "0x2222222222222222222222222222222222222222".to_string(),
+                    300000,
+                    2345
+                ),
+                (
+                    "0x5555555555555555555555555555555555555555".to_string(),
+                    400000,
+                    0
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn migration_from_0_0_17_keeps_received_payments_without_prior_state() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_0_0_17_keeps_received_payments_without_prior_state",
+        );
+        let mut conn = make_fixture_database(&home_dir, "0.0.17");
+        populate_fixture_database(&conn);
+        conn.execute(
+            "insert into received_payments (block_number, block_hash, wallet_address, amount) \
+             values (1000, '0xabc', '0x2222222222222222222222222222222222222222', 1234)",
+            NO_PARAMS,
+        )
+        .unwrap();
+        let subject = DbMigratorReal::default();
+
+        let result = subject.migrate_database(&mut conn, "0.0.17", "0.0.18");
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(schema_version(&conn), "0.0.18".to_string());
+        let received_payment: (i64, i64, Option<i64>, Option<i64>) = conn
+            .query_row(
+                "select block_number, amount, prior_last_received_timestamp, prior_pardoned_balance \
+                 from received_payments",
+                NO_PARAMS,
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(received_payment, (1000, 1234, None, None));
+    }
+
+    #[test]
+    fn fixture_databases_from_every_older_version_open_at_the_current_version() {
+        production_migrations().iter().for_each(|step| {
+            let home_dir = ensure_node_home_directory_exists(
+                "db_migrations",
+                &format!(
+                    "fixture_databases_from_every_older_version_open_at_the_current_version_{}",
+                    step.old_version()
+                ),
+            );
+            {
+                let conn = make_fixture_database(&home_dir, step.old_version());
+                populate_fixture_database(&conn);
+            }
+
+            DbInitializerReal::new()
+                .initialize(&home_dir, DEFAULT_CHAIN_ID, false)
+                .unwrap();
+
+            let conn = Connection::open(&home_dir.join(DATABASE_FILE)).unwrap();
+            assert_eq!(schema_version(&conn), CURRENT_SCHEMA_VERSION.to_string());
+            assert_eq!(count_rows(&conn, "payable"), 1);
+            assert_eq!(count_rows(&conn, "receivable"), 1);
+            assert_eq!(count_rows(&conn, "banned"), 1);
+            assert_eq!(
+                config_value(&conn, "earning_wallet_address"),
+                Some("0x4444444444444444444444444444444444444444".to_string())
+            );
+        });
+    }
+}
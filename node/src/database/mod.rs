@@ -2,3 +2,5 @@
 pub mod config_dumper;
 pub mod dao_utils;
 pub mod db_initializer;
+pub mod db_migrations;
+pub mod node_key_rotator;
@@ -0,0 +1,227 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::blockchain::blockchain_interface::chain_id_from_name;
+use crate::bootstrapper::RealUser;
+use crate::database::db_initializer::{DbInitializer, DbInitializerReal, DATABASE_FILE};
+use crate::node_configurator::RealDirsWrapper;
+use crate::node_configurator::{
+    app_head, data_directory_from_context, real_user_data_directory_opt_and_chain_name, DirsWrapper,
+};
+use crate::persistent_configuration::{
+    PersistentConfigError, PersistentConfiguration, PersistentConfigurationReal,
+};
+use crate::privilege_drop::{PrivilegeDropper, PrivilegeDropperReal};
+use crate::sub_lib::cryptde::CryptDE;
+use crate::sub_lib::cryptde_real::CryptDEReal;
+use crate::sub_lib::utils::make_new_multi_config;
+use clap::{value_t, Arg};
+use masq_lib::command::StdStreams;
+use masq_lib::multi_config::{CommandLineVcl, EnvironmentVcl, VirtualCommandLine};
+use masq_lib::shared_schema::{
+    blockchains_file_arg, chain_arg, data_directory_arg, db_password_arg, real_user_arg,
+    ConfiguratorError,
+};
+use std::path::PathBuf;
+
+const ROTATE_NODE_KEY_HELP: &str =
+    "Replace the key pair MASQ Node uses to identify itself with a freshly generated one. The Node \
+     will debut under the new public key the next time it starts; neighbors that knew the old one \
+     will treat it as a stranger.";
+const ROTATE_NODE_KEY_DB_PASSWORD_HELP: &str =
+    "The password the database was created with. The new private key is encrypted with it.";
+
+pub fn rotate_node_key(
+    args: &[String],
+    streams: &mut StdStreams,
+) -> Result<i32, ConfiguratorError> {
+    let (real_user, data_directory, chain_id, db_password) =
+        distill_args(&RealDirsWrapper {}, args, streams)?;
+    PrivilegeDropperReal::new().drop_privileges(&real_user);
+    let persistent_config = make_persistent_config(&data_directory, chain_id);
+    let cryptde = CryptDEReal::new(chain_id);
+    match persistent_config.set_node_private_key(cryptde.private_key(), &db_password) {
+        Ok(()) => (),
+        Err(PersistentConfigError::PasswordError) => {
+            return Err(ConfiguratorError::required(
+                "db-password",
+                "Incorrect password for this database",
+            ))
+        }
+        Err(e) => panic!("Couldn't save new Node private key: {:?}", e),
+    }
+    writeln!(
+        streams.stdout,
+        "MASQ Node will debut with public key {} the next time it starts",
+        cryptde.public_key_to_descriptor_fragment(cryptde.public_key())
+    )
+    .expect("writeln! failed");
+    Ok(0)
+}
+
+fn make_persistent_config(data_directory: &PathBuf, chain_id: u64) -> PersistentConfigurationReal {
+    let conn = DbInitializerReal::new()
+        .initialize(data_directory, chain_id, true)
+        .unwrap_or_else(|e| {
+            panic!(
+                "Can't initialize database at {:?}: {:?}",
+                data_directory.join(DATABASE_FILE),
+                e
+            )
+        });
+    PersistentConfigurationReal::from(conn)
+}
+
+fn distill_args(
+    dirs_wrapper: &dyn DirsWrapper,
+    args: &[String],
+    streams: &mut StdStreams,
+) -> Result<(RealUser, PathBuf, u64, String), ConfiguratorError> {
+    let app = app_head()
+        .arg(
+            Arg::with_name("rotate-node-key")
+                .long("rotate-node-key")
+                .required(true)
+                .takes_value(false)
+                .help(ROTATE_NODE_KEY_HELP),
+        )
+        .arg(blockchains_file_arg())
+        .arg(chain_arg())
+        .arg(data_directory_arg())
+        .arg(db_password_arg(ROTATE_NODE_KEY_DB_PASSWORD_HELP))
+        .arg(real_user_arg());
+    let vcls: Vec<Box<dyn VirtualCommandLine>> = vec![
+        Box::new(CommandLineVcl::new(args.to_vec())),
+        Box::new(EnvironmentVcl::new(&app)),
+    ];
+    let multi_config = make_new_multi_config(&app, vcls, streams)?;
+    let db_password = match value_m!(multi_config, "db-password", String) {
+        Some(db_password) => db_password,
+        None => {
+            return Err(ConfiguratorError::required(
+                "db-password",
+                "The Node's private key can't be stored without a database password",
+            ))
+        }
+    };
+    let (real_user, data_directory_opt, chain_name) =
+        real_user_data_directory_opt_and_chain_name(dirs_wrapper, &multi_config);
+    let directory =
+        data_directory_from_context(dirs_wrapper, &real_user, &data_directory_opt, &chain_name);
+    Ok((
+        real_user,
+        directory,
+        chain_id_from_name(&chain_name),
+        db_password,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistent_configuration::PersistentConfiguration;
+    use crate::test_utils::ArgsBuilder;
+    use masq_lib::shared_schema::ParamError;
+    use masq_lib::test_utils::environment_guard::ClapGuard;
+    use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
+    use masq_lib::test_utils::utils::{
+        ensure_node_home_directory_exists, DEFAULT_CHAIN_ID, TEST_DEFAULT_CHAIN_NAME,
+    };
+
+    fn make_args(data_dir: &PathBuf, db_password_opt: Option<&str>) -> Vec<String> {
+        let builder = ArgsBuilder::new()
+            .param("--data-directory", data_dir.to_str().unwrap())
+            .param("--real-user", "123::")
+            .param("--chain", TEST_DEFAULT_CHAIN_NAME)
+            .opt("--rotate-node-key");
+        match db_password_opt {
+            Some(db_password) => builder.param("--db-password", db_password).into(),
+            None => builder.into(),
+        }
+    }
+
+    #[test]
+    fn rotate_node_key_replaces_stored_key_and_reports_new_public_key() {
+        let _clap_guard = ClapGuard::new();
+        let data_dir = ensure_node_home_directory_exists(
+            "node_key_rotator",
+            "rotate_node_key_replaces_stored_key_and_reports_new_public_key",
+        );
+        let old_cryptde = CryptDEReal::new(DEFAULT_CHAIN_ID);
+        make_persistent_config(&data_dir, DEFAULT_CHAIN_ID)
+            .set_node_private_key(old_cryptde.private_key(), "password")
+            .unwrap();
+        let mut holder = FakeStreamHolder::new();
+
+        let result = rotate_node_key(
+            make_args(&data_dir, Some("password")).as_slice(),
+            &mut holder.streams(),
+        );
+
+        assert_eq!(result, Ok(0));
+        let new_private_key = make_persistent_config(&data_dir, DEFAULT_CHAIN_ID)
+            .node_private_key("password")
+            .unwrap()
+            .unwrap();
+        assert_ne!(&new_private_key, old_cryptde.private_key());
+        let new_cryptde =
+            CryptDEReal::from_private_key(&new_private_key, DEFAULT_CHAIN_ID).unwrap();
+        assert_eq!(
+            holder.stdout.get_string(),
+            format!(
+                "MASQ Node will debut with public key {} the next time it starts\n",
+                new_cryptde.public_key_to_descriptor_fragment(new_cryptde.public_key())
+            )
+        );
+    }
+
+    #[test]
+    fn rotate_node_key_complains_about_wrong_password() {
+        let _clap_guard = ClapGuard::new();
+        let data_dir = ensure_node_home_directory_exists(
+            "node_key_rotator",
+            "rotate_node_key_complains_about_wrong_password",
+        );
+        let old_cryptde = CryptDEReal::new(DEFAULT_CHAIN_ID);
+        make_persistent_config(&data_dir, DEFAULT_CHAIN_ID)
+            .set_node_private_key(old_cryptde.private_key(), "password")
+            .unwrap();
+        let mut holder = FakeStreamHolder::new();
+
+        let result = rotate_node_key(
+            make_args(&data_dir, Some("bad password")).as_slice(),
+            &mut holder.streams(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ConfiguratorError::new(vec![ParamError::new(
+                "db-password",
+                "Incorrect password for this database"
+            )]))
+        );
+        let stored_private_key = make_persistent_config(&data_dir, DEFAULT_CHAIN_ID)
+            .node_private_key("password")
+            .unwrap();
+        assert_eq!(stored_private_key, Some(old_cryptde.private_key().clone()));
+    }
+
+    #[test]
+    fn rotate_node_key_requires_db_password() {
+        let _clap_guard = ClapGuard::new();
+        let data_dir = ensure_node_home_directory_exists(
+            "node_key_rotator",
+            "rotate_node_key_requires_db_password",
+        );
+        let mut holder = FakeStreamHolder::new();
+
+        let result = rotate_node_key(make_args(&data_dir, None).as_slice(), &mut holder.streams());
+
+        assert_eq!(
+            result,
+            Err(ConfiguratorError::required(
+                "db-password",
+                "The Node's private key can't be stored without a database password"
+            ))
+        );
+    }
+}
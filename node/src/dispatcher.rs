@@ -1,8 +1,9 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
+use crate::server_initializer::{LogReconfigurer, LogReconfigurerReal};
 use crate::stream_messages::{PoolBindMessage, RemovedStreamType};
 use crate::sub_lib::dispatcher::InboundClientData;
 use crate::sub_lib::dispatcher::{DispatcherSubs, StreamShutdownMsg};
-use crate::sub_lib::logger::Logger;
+use crate::sub_lib::logger::{level_name, LogFilters, Logger};
 use crate::sub_lib::peer_actors::BindMessage;
 use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
 use crate::sub_lib::utils::{handle_ui_crash_request, NODE_MAILBOX_CAPACITY};
@@ -11,11 +12,15 @@ use actix::Addr;
 use actix::Context;
 use actix::Handler;
 use actix::Recipient;
+use log::LevelFilter;
 use masq_lib::crash_point::CrashPoint;
 use masq_lib::messages::{
     FromMessageBody, ToMessageBody, UiCrashRequest, UiDescriptorRequest, UiDescriptorResponse,
+    UiLogFilter, UiLogFiltersRequest, UiLogFiltersResponse, LOG_FILTERS_ERROR,
 };
-use masq_lib::ui_gateway::{MessageTarget, NodeFromUiMessage, NodeToUiMessage};
+use masq_lib::ui_gateway::MessagePath::Conversation;
+use masq_lib::ui_gateway::{MessageBody, MessageTarget, NodeFromUiMessage, NodeToUiMessage};
+use std::str::FromStr;
 
 pub const CRASH_KEY: &str = "DISPATCHER";
 
@@ -32,6 +37,7 @@ pub struct Dispatcher {
     crashable: bool,
     node_descriptor: String,
     to_stream: Option<Recipient<TransmitDataMsg>>,
+    log_reconfigurer: Box<dyn LogReconfigurer>,
     logger: Logger,
 }
 
@@ -117,8 +123,10 @@ impl Handler<NodeFromUiMessage> for Dispatcher {
     fn handle(&mut self, msg: NodeFromUiMessage, _ctx: &mut Self::Context) -> Self::Result {
         if let Ok((crash_request, _)) = UiCrashRequest::fmb(msg.body.clone()) {
             handle_ui_crash_request(crash_request, &self.logger, self.crashable, CRASH_KEY);
-        } else if let Ok((_, context_id)) = UiDescriptorRequest::fmb(msg.body) {
+        } else if let Ok((_, context_id)) = UiDescriptorRequest::fmb(msg.body.clone()) {
             self.handle_descriptor_request(msg.client_id, context_id);
+        } else if let Ok((request, context_id)) = UiLogFiltersRequest::fmb(msg.body) {
+            self.handle_log_filters_request(msg.client_id, context_id, request);
         }
     }
 }
@@ -130,6 +138,7 @@ impl Dispatcher {
             crashable: crash_point == CrashPoint::Message,
             node_descriptor,
             to_stream: None,
+            log_reconfigurer: Box::new(LogReconfigurerReal {}),
             logger: Logger::new("Dispatcher"),
         }
     }
@@ -171,14 +180,84 @@ impl Dispatcher {
             .try_send(response_msg)
             .expect("UiGateway is dead");
     }
+
+    fn handle_log_filters_request(
+        &mut self,
+        client_id: u64,
+        context_id: u64,
+        request: UiLogFiltersRequest,
+    ) {
+        let body = match self.change_log_filters(request) {
+            Ok((log_level, log_filters)) => UiLogFiltersResponse {
+                log_level: level_name(log_level),
+                filters: log_filters
+                    .filters
+                    .into_iter()
+                    .map(|(component, level)| UiLogFilter {
+                        component,
+                        level: level_name(level),
+                    })
+                    .collect(),
+            }
+            .tmb(context_id),
+            Err(e) => MessageBody {
+                opcode: "logFilters".to_string(),
+                path: Conversation(context_id),
+                payload: Err((LOG_FILTERS_ERROR, e)),
+            },
+        };
+        let subs = self.subs.as_ref().expect("Dispatcher is unbound");
+        subs.ui_gateway_sub
+            .try_send(NodeToUiMessage {
+                target: MessageTarget::ClientId(client_id),
+                body,
+            })
+            .expect("UiGateway is dead");
+    }
+
+    fn change_log_filters(
+        &mut self,
+        request: UiLogFiltersRequest,
+    ) -> Result<(LevelFilter, LogFilters), String> {
+        let (current_log_level, current_log_filters) = self.log_reconfigurer.current();
+        if request.log_level_opt.is_none() && request.filters_opt.is_none() {
+            return Ok((current_log_level, current_log_filters));
+        }
+        let log_level = match request.log_level_opt {
+            Some(log_level) => LevelFilter::from_str(&log_level)
+                .map_err(|_| format!("Bad log level: {:?}", log_level))?,
+            None => current_log_level,
+        };
+        let log_filters = match request.filters_opt {
+            Some(filters) => LogFilters::from_str(
+                &filters
+                    .iter()
+                    .map(|filter| format!("{}={}", filter.component, filter.level))
+                    .collect::<Vec<String>>()
+                    .join(","),
+            )?,
+            None => current_log_filters,
+        };
+        info!(
+            self.logger,
+            "Changing log level to {} and log filters to '{}'",
+            level_name(log_level),
+            log_filters
+        );
+        self.log_reconfigurer
+            .reconfigure(log_level, log_filters.clone());
+        Ok((log_level, log_filters))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::node_test_utils::make_stream_handler_pool_subs_from;
+    use crate::server_initializer::test_utils::LogReconfigurerMock;
     use crate::stream_messages::NonClandestineAttributes;
     use crate::sub_lib::dispatcher::Endpoint;
+    use crate::test_utils::logging::{init_test_logging, TestLogHandler};
     use crate::test_utils::recorder::Recorder;
     use crate::test_utils::recorder::{make_recorder, peer_actors_builder};
     use actix::Addr;
@@ -188,6 +267,7 @@ mod tests {
     use masq_lib::ui_gateway::MessageTarget;
     use std::net::SocketAddr;
     use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn sends_inbound_data_for_proxy_server_to_proxy_server() {
@@ -483,4 +563,209 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn log_filters_request_reconfigures_logging_and_reports_the_result() {
+        init_test_logging();
+        let reconfigure_params_arc = Arc::new(Mutex::new(vec![]));
+        let log_reconfigurer = LogReconfigurerMock::new(LevelFilter::Warn, LogFilters::default())
+            .reconfigure_params(&reconfigure_params_arc);
+
+        let result = send_log_filters_request(
+            log_reconfigurer,
+            UiLogFiltersRequest {
+                log_level_opt: Some("info".to_string()),
+                filters_opt: Some(vec![
+                    UiLogFilter {
+                        component: "Neighborhood".to_string(),
+                        level: "debug".to_string(),
+                    },
+                    UiLogFilter {
+                        component: "Hopper".to_string(),
+                        level: "OFF".to_string(),
+                    },
+                ]),
+            },
+        );
+
+        let log_filters = LogFilters {
+            filters: vec![
+                ("Neighborhood".to_string(), LevelFilter::Debug),
+                ("Hopper".to_string(), LevelFilter::Off),
+            ],
+        };
+        assert_eq!(
+            *reconfigure_params_arc.lock().unwrap(),
+            vec![(LevelFilter::Info, log_filters)]
+        );
+        assert_eq!(
+            result,
+            UiLogFiltersResponse {
+                log_level: "info".to_string(),
+                filters: vec![
+                    UiLogFilter {
+                        component: "Neighborhood".to_string(),
+                        level: "debug".to_string(),
+                    },
+                    UiLogFilter {
+                        component: "Hopper".to_string(),
+                        level: "off".to_string(),
+                    },
+                ],
+            }
+            .tmb(4321)
+        );
+        TestLogHandler::new().exists_log_containing(
+            "INFO: Dispatcher: Changing log level to info and log filters to 'Neighborhood=debug,Hopper=off'",
+        );
+    }
+
+    #[test]
+    fn log_filters_request_keeps_whatever_it_does_not_mention() {
+        let reconfigure_params_arc = Arc::new(Mutex::new(vec![]));
+        let log_filters = LogFilters {
+            filters: vec![("Hopper".to_string(), LevelFilter::Error)],
+        };
+        let log_reconfigurer = LogReconfigurerMock::new(LevelFilter::Warn, log_filters.clone())
+            .reconfigure_params(&reconfigure_params_arc);
+
+        let result = send_log_filters_request(
+            log_reconfigurer,
+            UiLogFiltersRequest {
+                log_level_opt: Some("trace".to_string()),
+                filters_opt: None,
+            },
+        );
+
+        assert_eq!(
+            *reconfigure_params_arc.lock().unwrap(),
+            vec![(LevelFilter::Trace, log_filters)]
+        );
+        assert_eq!(
+            result,
+            UiLogFiltersResponse {
+                log_level: "trace".to_string(),
+                filters: vec![UiLogFilter {
+                    component: "Hopper".to_string(),
+                    level: "error".to_string(),
+                }],
+            }
+            .tmb(4321)
+        );
+    }
+
+    #[test]
+    fn log_filters_request_without_changes_just_reports_the_current_configuration() {
+        let reconfigure_params_arc = Arc::new(Mutex::new(vec![]));
+        let log_filters = LogFilters {
+            filters: vec![("Neighborhood".to_string(), LevelFilter::Trace)],
+        };
+        let log_reconfigurer = LogReconfigurerMock::new(LevelFilter::Error, log_filters)
+            .reconfigure_params(&reconfigure_params_arc);
+
+        let result = send_log_filters_request(
+            log_reconfigurer,
+            UiLogFiltersRequest {
+                log_level_opt: None,
+                filters_opt: None,
+            },
+        );
+
+        assert!(reconfigure_params_arc.lock().unwrap().is_empty());
+        assert_eq!(
+            result,
+            UiLogFiltersResponse {
+                log_level: "error".to_string(),
+                filters: vec![UiLogFilter {
+                    component: "Neighborhood".to_string(),
+                    level: "trace".to_string(),
+                }],
+            }
+            .tmb(4321)
+        );
+    }
+
+    #[test]
+    fn log_filters_request_with_bad_log_level_changes_nothing() {
+        let reconfigure_params_arc = Arc::new(Mutex::new(vec![]));
+        let log_reconfigurer = LogReconfigurerMock::new(LevelFilter::Warn, LogFilters::default())
+            .reconfigure_params(&reconfigure_params_arc);
+
+        let result = send_log_filters_request(
+            log_reconfigurer,
+            UiLogFiltersRequest {
+                log_level_opt: Some("loud".to_string()),
+                filters_opt: Some(vec![]),
+            },
+        );
+
+        assert!(reconfigure_params_arc.lock().unwrap().is_empty());
+        assert_eq!(
+            result,
+            MessageBody {
+                opcode: "logFilters".to_string(),
+                path: Conversation(4321),
+                payload: Err((LOG_FILTERS_ERROR, "Bad log level: \"loud\"".to_string())),
+            }
+        );
+    }
+
+    #[test]
+    fn log_filters_request_with_bad_filter_changes_nothing() {
+        let reconfigure_params_arc = Arc::new(Mutex::new(vec![]));
+        let log_reconfigurer = LogReconfigurerMock::new(LevelFilter::Warn, LogFilters::default())
+            .reconfigure_params(&reconfigure_params_arc);
+
+        let result = send_log_filters_request(
+            log_reconfigurer,
+            UiLogFiltersRequest {
+                log_level_opt: None,
+                filters_opt: Some(vec![UiLogFilter {
+                    component: "Proxy Server".to_string(),
+                    level: "debug".to_string(),
+                }]),
+            },
+        );
+
+        assert!(reconfigure_params_arc.lock().unwrap().is_empty());
+        assert_eq!(
+            result,
+            MessageBody {
+                opcode: "logFilters".to_string(),
+                path: Conversation(4321),
+                payload: Err((
+                    LOG_FILTERS_ERROR,
+                    "Bad log filters: \"Proxy Server=debug\"".to_string()
+                )),
+            }
+        );
+    }
+
+    fn send_log_filters_request(
+        log_reconfigurer: LogReconfigurerMock,
+        request: UiLogFiltersRequest,
+    ) -> MessageBody {
+        let system = System::new("test");
+        let mut subject = Dispatcher::new(CrashPoint::None, "Node descriptor".to_string());
+        subject.log_reconfigurer = Box::new(log_reconfigurer);
+        let addr = subject.start();
+        let (ui_gateway_recorder, _, ui_gateway_recording_arc) = make_recorder();
+        let peer_actors = peer_actors_builder()
+            .ui_gateway(ui_gateway_recorder)
+            .build();
+        addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        addr.try_send(NodeFromUiMessage {
+            client_id: 1234,
+            body: request.tmb(4321),
+        })
+        .unwrap();
+
+        System::current().stop_with_code(0);
+        system.run();
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        let response = ui_gateway_recording.get_record::<NodeToUiMessage>(0);
+        assert_eq!(response.target, MessageTarget::ClientId(1234));
+        response.body.clone()
+    }
 }
@@ -158,6 +158,10 @@ mod tests {
             UdpSocketWrapperMock::copy(&mut guts.buf, buf);
             self.send_to_results.lock().unwrap().borrow_mut().remove(0)
         }
+
+        fn local_addr(&self) -> io::Result<SocketAddr> {
+            unimplemented!()
+        }
     }
 
     impl UdpSocketWrapperMock {
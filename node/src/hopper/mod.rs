@@ -10,6 +10,7 @@ use crate::sub_lib::dispatcher::InboundClientData;
 use crate::sub_lib::hopper::HopperSubs;
 use crate::sub_lib::hopper::IncipientCoresPackage;
 use crate::sub_lib::hopper::{HopperConfig, NoLookupIncipientCoresPackage};
+use crate::sub_lib::neighborhood::RatePackChangeMessage;
 use crate::sub_lib::peer_actors::BindMessage;
 use crate::sub_lib::utils::NODE_MAILBOX_CAPACITY;
 use actix::Actor;
@@ -104,6 +105,14 @@ impl Handler<InboundClientData> for Hopper {
     }
 }
 
+impl Handler<RatePackChangeMessage> for Hopper {
+    type Result = ();
+
+    fn handle(&mut self, msg: RatePackChangeMessage, _ctx: &mut Self::Context) -> Self::Result {
+        self.handle_rate_pack_change(msg)
+    }
+}
+
 impl Hopper {
     pub fn new(config: HopperConfig) -> Hopper {
         Hopper {
@@ -123,6 +132,15 @@ impl Hopper {
             from_hopper_client: recipient!(addr, IncipientCoresPackage),
             from_hopper_client_no_lookup: recipient!(addr, NoLookupIncipientCoresPackage),
             from_dispatcher: recipient!(addr, InboundClientData),
+            rate_pack_change: recipient!(addr, RatePackChangeMessage),
+        }
+    }
+
+    fn handle_rate_pack_change(&mut self, msg: RatePackChangeMessage) {
+        self.per_routing_service = msg.rate_pack.routing_service_rate;
+        self.per_routing_byte = msg.rate_pack.routing_byte_rate;
+        if let Some(routing_service) = self.routing_service.as_mut() {
+            routing_service.set_rates(self.per_routing_service, self.per_routing_byte);
         }
     }
 }
@@ -139,8 +157,8 @@ mod tests {
     use crate::sub_lib::route::Route;
     use crate::sub_lib::route::RouteSegment;
     use crate::test_utils::{
-        alias_cryptde, main_cryptde, make_meaningless_message_type, make_paying_wallet,
-        route_to_proxy_client,
+        alias_cryptde, main_cryptde, make_meaningless_message_type, make_paying_wallet, rate_pack,
+        rate_pack_routing, rate_pack_routing_byte, route_to_proxy_client,
     };
     use actix::Actor;
     use actix::System;
@@ -232,4 +250,22 @@ mod tests {
         System::current().stop_with_code(0);
         system.run();
     }
+
+    #[test]
+    fn rate_pack_change_replaces_routing_rates() {
+        let mut subject = Hopper::new(HopperConfig {
+            main_cryptde: main_cryptde(),
+            alias_cryptde: alias_cryptde(),
+            per_routing_service: 100,
+            per_routing_byte: 200,
+            is_decentralized: true,
+        });
+
+        subject.handle_rate_pack_change(RatePackChangeMessage {
+            rate_pack: rate_pack(1000),
+        });
+
+        assert_eq!(subject.per_routing_service, rate_pack_routing(1000));
+        assert_eq!(subject.per_routing_byte, rate_pack_routing_byte(1000));
+    }
 }
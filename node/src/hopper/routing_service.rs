@@ -10,9 +10,12 @@ use crate::sub_lib::hopper::{ExpiredCoresPackage, HopperSubs, MessageType};
 use crate::sub_lib::logger::Logger;
 use crate::sub_lib::neighborhood::{GossipFailure_0v1, NeighborhoodSubs};
 use crate::sub_lib::proxy_client::{
-    ClientResponsePayload_0v1, DnsResolveFailure_0v1, ProxyClientSubs,
+    ClientResponsePayload_0v1, DatagramResponsePayload_0v1, DnsResolveFailure_0v1,
+    ExitPolicyViolation_0v1, ProxyClientSubs,
+};
+use crate::sub_lib::proxy_server::{
+    ClientRequestPayload_0v1, DatagramRequestPayload_0v1, ProxyServerSubs,
 };
-use crate::sub_lib::proxy_server::{ClientRequestPayload_0v1, ProxyServerSubs};
 use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
 use actix::Recipient;
 use std::borrow::Borrow;
@@ -58,6 +61,11 @@ impl RoutingService {
         }
     }
 
+    pub fn set_rates(&mut self, per_routing_service: u64, per_routing_byte: u64) {
+        self.per_routing_service = per_routing_service;
+        self.per_routing_byte = per_routing_byte;
+    }
+
     pub fn route(&self, ibcd: InboundClientData) {
         let data_size = ibcd.data.len();
         debug!(
@@ -354,6 +362,84 @@ impl RoutingService {
                     ))
                     .expect("ProxyServer is dead")
             }
+            (Component::ProxyServer, MessageType::ExitPolicyViolated(vd)) => {
+                let violation = match ExitPolicyViolation_0v1::try_from(vd) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        error!(
+                            self.logger,
+                            "Received unmigratable ExitPolicyViolated: {:?}", e
+                        );
+                        return;
+                    }
+                };
+                self.routing_service_subs
+                    .proxy_server_subs
+                    .exit_policy_violation_from_hopper
+                    .try_send(ExpiredCoresPackage::new(
+                        expired_package.immediate_neighbor,
+                        expired_package.paying_wallet,
+                        expired_package.remaining_route,
+                        violation,
+                        expired_package.payload_len,
+                    ))
+                    .expect("ProxyServer is dead")
+            }
+            (Component::ProxyClient, MessageType::DatagramRequest(vd)) => {
+                if !self.is_decentralized || payer_owns_secret_key {
+                    let datagram_request = match DatagramRequestPayload_0v1::try_from(vd) {
+                        Ok(drp) => drp,
+                        Err(e) => {
+                            error!(
+                                self.logger,
+                                "Received unmigratable DatagramRequestPayload: {:?}", e
+                            );
+                            return;
+                        }
+                    };
+                    self.routing_service_subs
+                        .proxy_client_subs
+                        .datagram_from_hopper
+                        .try_send(ExpiredCoresPackage::new(
+                            expired_package.immediate_neighbor,
+                            expired_package.paying_wallet,
+                            expired_package.remaining_route,
+                            datagram_request,
+                            expired_package.payload_len,
+                        ))
+                        .expect("ProxyClient is dead")
+                } else {
+                    let payload_len = expired_package.payload_len;
+                    warning!(
+                        self.logger,
+                        "Refusing to route Expired CORES package with {}-byte datagram without proof of paying wallet ownership.",
+                        payload_len
+                    );
+                }
+            }
+            (Component::ProxyServer, MessageType::DatagramResponse(vd)) => {
+                let datagram_response = match DatagramResponsePayload_0v1::try_from(vd) {
+                    Ok(drp) => drp,
+                    Err(e) => {
+                        error!(
+                            self.logger,
+                            "Received unmigratable DatagramResponsePayload: {:?}", e
+                        );
+                        return;
+                    }
+                };
+                self.routing_service_subs
+                    .proxy_server_subs
+                    .datagram_from_hopper
+                    .try_send(ExpiredCoresPackage::new(
+                        expired_package.immediate_neighbor,
+                        expired_package.paying_wallet,
+                        expired_package.remaining_route,
+                        datagram_response,
+                        expired_package.payload_len,
+                    ))
+                    .expect("ProxyServer is dead")
+            }
             (Component::Neighborhood, MessageType::Gossip(vd)) => {
                 let gossip = match Gossip_0v1::try_from(vd) {
                     Ok(g) => g,
@@ -514,7 +600,9 @@ mod tests {
     use crate::sub_lib::cryptde_null::CryptDENull;
     use crate::sub_lib::hopper::{IncipientCoresPackage, MessageType, MessageType::ClientRequest};
     use crate::sub_lib::neighborhood::GossipFailure_0v1;
-    use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
+    use crate::sub_lib::proxy_client::{
+        ClientResponsePayload_0v1, DnsResolveFailure_0v1, ExitPolicyViolation_0v1,
+    };
     use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
     use crate::sub_lib::route::{Route, RouteSegment};
     use crate::sub_lib::versioned_data::VersionedData;
@@ -591,6 +679,183 @@ mod tests {
         assert_eq!(dns_resolve_failure, message.payload);
     }
 
+    #[test]
+    fn exit_policy_violations_are_reported_to_the_proxy_server() {
+        let main_cryptde = main_cryptde();
+        let alias_cryptde = alias_cryptde();
+        let route = route_to_proxy_server(main_cryptde.public_key(), main_cryptde);
+        let stream_key = make_meaningless_stream_key();
+        let exit_policy_violation =
+            ExitPolicyViolation_0v1::new(stream_key, "port 25 is not in --exit-ports".to_string());
+        let lcp = LiveCoresPackage::new(
+            route,
+            encodex(
+                alias_cryptde,
+                alias_cryptde.public_key(),
+                &MessageType::ExitPolicyViolated(VersionedData::new(
+                    &crate::sub_lib::migrations::exit_policy_violation::MIGRATIONS,
+                    &exit_policy_violation.clone(),
+                )),
+            )
+            .unwrap(),
+        );
+        let data_enc = encodex(main_cryptde, main_cryptde.public_key(), &lcp).unwrap();
+        let inbound_client_data = InboundClientData {
+            peer_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            reception_port: None,
+            sequence_number: None,
+            last_data: false,
+            is_clandestine: false,
+            data: data_enc.into(),
+        };
+        let (proxy_server, _, proxy_server_recording) = make_recorder();
+
+        let system = System::new("exit_policy_violations_are_reported_to_the_proxy_server");
+        let peer_actors = peer_actors_builder().proxy_server(proxy_server).build();
+        let subject = RoutingService::new(
+            main_cryptde,
+            alias_cryptde,
+            RoutingServiceSubs {
+                proxy_client_subs: peer_actors.proxy_client,
+                proxy_server_subs: peer_actors.proxy_server,
+                neighborhood_subs: peer_actors.neighborhood,
+                hopper_subs: peer_actors.hopper,
+                to_dispatcher: peer_actors.dispatcher.from_dispatcher_client,
+                to_accountant_routing: peer_actors.accountant.report_routing_service_provided,
+            },
+            100,
+            200,
+            false,
+        );
+
+        subject.route(inbound_client_data);
+
+        System::current().stop();
+        system.run();
+
+        let recordings = proxy_server_recording.lock().unwrap();
+        let message = recordings.get_record::<ExpiredCoresPackage<ExitPolicyViolation_0v1>>(0);
+        assert_eq!(exit_policy_violation, message.payload);
+    }
+
+    #[test]
+    fn datagram_requests_are_routed_to_the_proxy_client() {
+        let main_cryptde = main_cryptde();
+        let alias_cryptde = alias_cryptde();
+        let route = route_to_proxy_client(main_cryptde.public_key(), main_cryptde);
+        let datagram_request = DatagramRequestPayload_0v1 {
+            association_key: make_meaningless_stream_key(),
+            target_hostname: "dns.example.com".to_string(),
+            target_port: 53,
+            data: vec![1, 2, 3, 4],
+            originator_public_key: alias_cryptde.public_key().clone(),
+        };
+        let lcp = LiveCoresPackage::new(
+            route,
+            encodex::<MessageType>(
+                main_cryptde,
+                main_cryptde.public_key(),
+                &datagram_request.clone().into(),
+            )
+            .unwrap(),
+        );
+        let data_enc = encodex(main_cryptde, main_cryptde.public_key(), &lcp).unwrap();
+        let inbound_client_data = InboundClientData {
+            peer_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            reception_port: None,
+            sequence_number: None,
+            last_data: false,
+            is_clandestine: false,
+            data: data_enc.into(),
+        };
+        let (proxy_client, _, proxy_client_recording) = make_recorder();
+
+        let system = System::new("datagram_requests_are_routed_to_the_proxy_client");
+        let peer_actors = peer_actors_builder().proxy_client(proxy_client).build();
+        let subject = RoutingService::new(
+            main_cryptde,
+            alias_cryptde,
+            RoutingServiceSubs {
+                proxy_client_subs: peer_actors.proxy_client,
+                proxy_server_subs: peer_actors.proxy_server,
+                neighborhood_subs: peer_actors.neighborhood,
+                hopper_subs: peer_actors.hopper,
+                to_dispatcher: peer_actors.dispatcher.from_dispatcher_client,
+                to_accountant_routing: peer_actors.accountant.report_routing_service_provided,
+            },
+            100,
+            200,
+            false,
+        );
+
+        subject.route(inbound_client_data);
+
+        System::current().stop();
+        system.run();
+
+        let recordings = proxy_client_recording.lock().unwrap();
+        let message = recordings.get_record::<ExpiredCoresPackage<DatagramRequestPayload_0v1>>(0);
+        assert_eq!(datagram_request, message.payload);
+    }
+
+    #[test]
+    fn datagram_responses_are_routed_to_the_proxy_server() {
+        let main_cryptde = main_cryptde();
+        let alias_cryptde = alias_cryptde();
+        let route = route_to_proxy_server(main_cryptde.public_key(), main_cryptde);
+        let datagram_response = DatagramResponsePayload_0v1 {
+            association_key: make_meaningless_stream_key(),
+            source: SocketAddr::from_str("8.8.8.8:53").unwrap(),
+            data: vec![5, 6, 7, 8],
+        };
+        let lcp = LiveCoresPackage::new(
+            route,
+            encodex::<MessageType>(
+                alias_cryptde,
+                alias_cryptde.public_key(),
+                &datagram_response.clone().into(),
+            )
+            .unwrap(),
+        );
+        let data_enc = encodex(main_cryptde, main_cryptde.public_key(), &lcp).unwrap();
+        let inbound_client_data = InboundClientData {
+            peer_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            reception_port: None,
+            sequence_number: None,
+            last_data: false,
+            is_clandestine: false,
+            data: data_enc.into(),
+        };
+        let (proxy_server, _, proxy_server_recording) = make_recorder();
+
+        let system = System::new("datagram_responses_are_routed_to_the_proxy_server");
+        let peer_actors = peer_actors_builder().proxy_server(proxy_server).build();
+        let subject = RoutingService::new(
+            main_cryptde,
+            alias_cryptde,
+            RoutingServiceSubs {
+                proxy_client_subs: peer_actors.proxy_client,
+                proxy_server_subs: peer_actors.proxy_server,
+                neighborhood_subs: peer_actors.neighborhood,
+                hopper_subs: peer_actors.hopper,
+                to_dispatcher: peer_actors.dispatcher.from_dispatcher_client,
+                to_accountant_routing: peer_actors.accountant.report_routing_service_provided,
+            },
+            100,
+            200,
+            false,
+        );
+
+        subject.route(inbound_client_data);
+
+        System::current().stop();
+        system.run();
+
+        let recordings = proxy_server_recording.lock().unwrap();
+        let message = recordings.get_record::<ExpiredCoresPackage<DatagramResponsePayload_0v1>>(0);
+        assert_eq!(datagram_response, message.payload);
+    }
+
     #[test]
     fn logs_and_ignores_message_that_cannot_be_deserialized() {
         init_test_logging();
@@ -1910,6 +2175,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn route_expired_package_handles_unmigratable_exit_policy_violation() {
+        init_test_logging();
+        let (hopper, _, hopper_recording_arc) = make_recorder();
+        let peer_actors = peer_actors_builder().hopper(hopper).build();
+        let subject = RoutingService::new(
+            main_cryptde(),
+            alias_cryptde(),
+            RoutingServiceSubs {
+                proxy_client_subs: peer_actors.proxy_client,
+                proxy_server_subs: peer_actors.proxy_server,
+                neighborhood_subs: peer_actors.neighborhood,
+                hopper_subs: peer_actors.hopper,
+                to_dispatcher: peer_actors.dispatcher.from_dispatcher_client,
+                to_accountant_routing: peer_actors.accountant.report_routing_service_provided,
+            },
+            100,
+            200,
+            false,
+        );
+        let expired_package = ExpiredCoresPackage::new(
+            SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+            None,
+            Route { hops: vec![] },
+            MessageType::ExitPolicyViolated(VersionedData::test_new(dv!(0, 0), vec![])),
+            0,
+        );
+        let system =
+            System::new("route_expired_package_handles_unmigratable_exit_policy_violation");
+
+        subject.route_expired_package(Component::ProxyServer, expired_package, true);
+
+        System::current().stop_with_code(0);
+        system.run();
+        let hopper_recording = hopper_recording_arc.lock().unwrap();
+        assert_eq!(hopper_recording.len(), 0);
+        TestLogHandler::new().exists_log_containing(
+            "ERROR: RoutingService: Received unmigratable ExitPolicyViolated: MigrationNotFound(DataVersion { major: 0, minor: 0 }, DataVersion { major: 0, minor: 1 })",
+        );
+    }
+
     #[test]
     fn route_expired_package_handles_unmigratable_gossip_failure() {
         init_test_logging();
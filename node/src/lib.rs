@@ -32,6 +32,7 @@ pub mod json_framer;
 pub mod json_masquerader;
 mod listener_handler;
 pub mod masquerader;
+pub mod metrics;
 pub mod neighborhood;
 pub mod node_configurator;
 mod null_masquerader;
@@ -41,6 +42,7 @@ pub mod proxy_client;
 pub mod proxy_server;
 pub mod run_modes;
 pub mod server_initializer;
+pub mod socks_discriminator_factory;
 pub mod stream_handler_pool;
 mod stream_messages;
 mod stream_reader;
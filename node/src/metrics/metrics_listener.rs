@@ -0,0 +1,170 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::metrics::Metrics;
+use crate::sub_lib::logger::Logger;
+use masq_lib::utils::localhost;
+use std::io;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_REQUEST_HEAD_SIZE: usize = 8192;
+
+// Binds the metrics port on localhost only, then answers scrapes on a thread of its own so that
+// a slow scraper can never hold up an actor.
+pub fn start_metrics_listener(port: u16, metrics: &'static Metrics) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(SocketAddr::new(localhost(), port))?;
+    let local_addr = listener.local_addr()?;
+    let logger = Logger::new("MetricsListener");
+    info!(logger, "Serving metrics at http://{}/metrics", local_addr);
+    thread::spawn(move || {
+        listener
+            .incoming()
+            .for_each(|stream_result| match stream_result {
+                Ok(stream) => {
+                    if let Err(e) = serve(stream, metrics) {
+                        debug!(logger, "Metrics request failed: {}", e)
+                    }
+                }
+                Err(e) => warning!(logger, "Could not accept metrics connection: {}", e),
+            })
+    });
+    Ok(local_addr)
+}
+
+fn serve(mut stream: TcpStream, metrics: &Metrics) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let request_head = read_request_head(&mut stream)?;
+    let request_line = request_head.lines().next().unwrap_or("");
+    let (status, body) = match request_line.split_whitespace().collect::<Vec<&str>>()[..] {
+        ["GET", "/metrics", _] => ("200 OK", metrics.render()),
+        ["GET", _, _] => ("404 Not Found", "Metrics are at /metrics\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "Only GET is supported\n".to_string(),
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn read_request_head(stream: &mut TcpStream) -> io::Result<String> {
+    let mut head = vec![];
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request head too large",
+            ));
+        }
+        let len = stream.read(&mut buf)?;
+        if len == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..len]);
+    }
+    Ok(String::from_utf8_lossy(&head).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::ROUTE_QUERY_FAILURES;
+    use masq_lib::utils::find_free_port;
+
+    fn request(addr: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn leaked_metrics() -> &'static Metrics {
+        Box::leak(Box::new(Metrics::default()))
+    }
+
+    #[test]
+    fn metrics_listener_serves_rendered_metrics() {
+        let metrics = leaked_metrics();
+        metrics.increment(&ROUTE_QUERY_FAILURES, &[], 2);
+        let port = find_free_port();
+
+        let addr = start_metrics_listener(port, metrics).unwrap();
+
+        assert_eq!(addr, SocketAddr::new(localhost(), port));
+        let body = metrics.render();
+        let response = request(addr, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert_eq!(
+            response,
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        );
+        assert!(body.contains("masq_route_query_failures_total 2\n"));
+    }
+
+    #[test]
+    fn metrics_listener_serves_one_request_after_another() {
+        let metrics = leaked_metrics();
+        let addr = start_metrics_listener(find_free_port(), metrics).unwrap();
+        request(addr, "GET /metrics HTTP/1.1\r\n\r\n");
+        metrics.increment(&ROUTE_QUERY_FAILURES, &[], 1);
+
+        let response = request(addr, "GET /metrics HTTP/1.1\r\n\r\n");
+
+        assert!(
+            response.ends_with("masq_route_query_failures_total 1\n"),
+            "{}",
+            response
+        );
+    }
+
+    #[test]
+    fn metrics_listener_rejects_other_paths() {
+        let addr = start_metrics_listener(find_free_port(), leaked_metrics()).unwrap();
+
+        let response = request(addr, "GET /booga HTTP/1.1\r\n\r\n");
+
+        assert!(
+            response.starts_with("HTTP/1.1 404 Not Found\r\n"),
+            "{}",
+            response
+        );
+    }
+
+    #[test]
+    fn metrics_listener_rejects_other_methods() {
+        let addr = start_metrics_listener(find_free_port(), leaked_metrics()).unwrap();
+
+        let response = request(addr, "POST /metrics HTTP/1.1\r\n\r\n");
+
+        assert!(
+            response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"),
+            "{}",
+            response
+        );
+    }
+
+    #[test]
+    fn metrics_listener_complains_if_port_is_taken() {
+        let port = find_free_port();
+        let _occupier = TcpListener::bind(SocketAddr::new(localhost(), port)).unwrap();
+
+        let result = start_metrics_listener(port, leaked_metrics());
+
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::AddrInUse);
+    }
+}
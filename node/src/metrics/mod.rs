@@ -0,0 +1,360 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+pub mod metrics_listener;
+
+#[cfg(not(test))]
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Summary,
+}
+
+impl MetricKind {
+    fn type_name(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Summary => "summary",
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct MetricFamily {
+    pub name: &'static str,
+    pub kind: MetricKind,
+    pub help: &'static str,
+}
+
+pub const BYTES_ROUTED: MetricFamily = MetricFamily {
+    name: "masq_bytes_routed_total",
+    kind: MetricKind::Counter,
+    help: "Payload bytes this Node has routed for other Nodes",
+};
+pub const BYTES_EXITED: MetricFamily = MetricFamily {
+    name: "masq_bytes_exited_total",
+    kind: MetricKind::Counter,
+    help: "Payload bytes this Node has carried to and from the Internet for other Nodes",
+};
+pub const BYTES_CONSUMED: MetricFamily = MetricFamily {
+    name: "masq_bytes_consumed_total",
+    kind: MetricKind::Counter,
+    help: "Payload bytes other Nodes have routed or exited for this Node, by service",
+};
+pub const ACTIVE_STREAMS: MetricFamily = MetricFamily {
+    name: "masq_active_streams",
+    kind: MetricKind::Gauge,
+    help: "Streams currently open in each stream handler pool",
+};
+pub const GOSSIP_RECEIVED: MetricFamily = MetricFamily {
+    name: "masq_gossip_received_total",
+    kind: MetricKind::Counter,
+    help: "Gossip messages received, by the handler that claimed them",
+};
+pub const GOSSIP_ACCEPTED: MetricFamily = MetricFamily {
+    name: "masq_gossip_accepted_total",
+    kind: MetricKind::Counter,
+    help: "Gossip messages that changed the neighborhood database or drew a reply, by handler",
+};
+pub const GOSSIP_REJECTED: MetricFamily = MetricFamily {
+    name: "masq_gossip_rejected_total",
+    kind: MetricKind::Counter,
+    help: "Gossip messages that failed or got their sender banned, by handler",
+};
+pub const DNS_FAILURES: MetricFamily = MetricFamily {
+    name: "masq_dns_failures_total",
+    kind: MetricKind::Counter,
+    help: "Exit requests whose target hostname could not be resolved",
+};
+pub const ROUTE_QUERY_FAILURES: MetricFamily = MetricFamily {
+    name: "masq_route_query_failures_total",
+    kind: MetricKind::Counter,
+    help: "Route queries the Neighborhood could not satisfy",
+};
+pub const PAYABLE_TOTAL: MetricFamily = MetricFamily {
+    name: "masq_payable_total_gwei",
+    kind: MetricKind::Gauge,
+    help: "Total this Node owes other Nodes, in Gwei, as of the last payable scan",
+};
+pub const RECEIVABLE_TOTAL: MetricFamily = MetricFamily {
+    name: "masq_receivable_total_gwei",
+    kind: MetricKind::Gauge,
+    help: "Total other Nodes owe this Node, in Gwei, as of the last payable scan",
+};
+pub const BLOCKCHAIN_RPC_DURATION: MetricFamily = MetricFamily {
+    name: "masq_blockchain_rpc_duration_seconds",
+    kind: MetricKind::Summary,
+    help: "Time spent waiting for blockchain service calls, by method",
+};
+pub const BLOCKCHAIN_RPC_ERRORS: MetricFamily = MetricFamily {
+    name: "masq_blockchain_rpc_errors_total",
+    kind: MetricKind::Counter,
+    help: "Blockchain service calls that failed, by method",
+};
+pub const BLOCKCHAIN_ENDPOINT_ERRORS: MetricFamily = MetricFamily {
+    name: "masq_blockchain_endpoint_errors_total",
+    kind: MetricKind::Counter,
+    help: "Calls a blockchain service endpoint failed to answer, by endpoint",
+};
+
+#[cfg(not(test))]
+lazy_static! {
+    static ref METRICS: Metrics = Metrics::default();
+}
+
+#[cfg(not(test))]
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+// Tests run in parallel, so each test thread gets its own registry to make exact assertions on
+#[cfg(test)]
+pub fn metrics() -> &'static Metrics {
+    thread_local! {
+        static METRICS: &'static Metrics = Box::leak(Box::new(Metrics::default()));
+    }
+    METRICS.with(|metrics| *metrics)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Sample {
+    Value(f64),
+    Summary { sum: f64, count: u64 },
+}
+
+struct Series {
+    family: &'static MetricFamily,
+    samples: BTreeMap<String, Sample>,
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    series: Mutex<BTreeMap<&'static str, Series>>,
+}
+
+impl Metrics {
+    pub fn increment(&self, family: &'static MetricFamily, labels: &[(&str, &str)], by: u64) {
+        self.update(family, labels, |sample| match sample {
+            Some(Sample::Value(value)) => Sample::Value(value + by as f64),
+            _ => Sample::Value(by as f64),
+        })
+    }
+
+    pub fn set(&self, family: &'static MetricFamily, labels: &[(&str, &str)], value: f64) {
+        self.update(family, labels, |_| Sample::Value(value))
+    }
+
+    pub fn observe(
+        &self,
+        family: &'static MetricFamily,
+        labels: &[(&str, &str)],
+        duration: Duration,
+    ) {
+        let seconds = duration.as_secs_f64();
+        self.update(family, labels, |sample| match sample {
+            Some(Sample::Summary { sum, count }) => Sample::Summary {
+                sum: sum + seconds,
+                count: count + 1,
+            },
+            _ => Sample::Summary {
+                sum: seconds,
+                count: 1,
+            },
+        })
+    }
+
+    pub fn value(&self, family: &'static MetricFamily, labels: &[(&str, &str)]) -> Option<f64> {
+        match self.sample(family, labels) {
+            Some(Sample::Value(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn observation_count(
+        &self,
+        family: &'static MetricFamily,
+        labels: &[(&str, &str)],
+    ) -> Option<u64> {
+        match self.sample(family, labels) {
+            Some(Sample::Summary { count, .. }) => Some(count),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let series = self.series.lock().expect("Metrics are poisoned");
+        let mut output = String::new();
+        series.values().for_each(|series| {
+            let family = series.family;
+            writeln!(output, "# HELP {} {}", family.name, family.help).expect("write! failed");
+            writeln!(output, "# TYPE {} {}", family.name, family.kind.type_name())
+                .expect("write! failed");
+            series
+                .samples
+                .iter()
+                .for_each(|(labels, sample)| match sample {
+                    Sample::Value(value) => writeln!(output, "{}{} {}", family.name, labels, value)
+                        .expect("write! failed"),
+                    Sample::Summary { sum, count } => {
+                        writeln!(output, "{}_sum{} {}", family.name, labels, sum)
+                            .expect("write! failed");
+                        writeln!(output, "{}_count{} {}", family.name, labels, count)
+                            .expect("write! failed");
+                    }
+                })
+        });
+        output
+    }
+
+    fn update<F>(&self, family: &'static MetricFamily, labels: &[(&str, &str)], f: F)
+    where
+        F: FnOnce(Option<Sample>) -> Sample,
+    {
+        let mut series = self.series.lock().expect("Metrics are poisoned");
+        let samples = &mut series
+            .entry(family.name)
+            .or_insert_with(|| Series {
+                family,
+                samples: BTreeMap::new(),
+            })
+            .samples;
+        let key = Self::label_string(labels);
+        let new_sample = f(samples.get(&key).cloned());
+        samples.insert(key, new_sample);
+    }
+
+    fn sample(&self, family: &'static MetricFamily, labels: &[(&str, &str)]) -> Option<Sample> {
+        let series = self.series.lock().expect("Metrics are poisoned");
+        series
+            .get(family.name)
+            .and_then(|series| series.samples.get(&Self::label_string(labels)).cloned())
+    }
+
+    fn label_string(labels: &[(&str, &str)]) -> String {
+        if labels.is_empty() {
+            return String::new();
+        }
+        let pairs = labels
+            .iter()
+            .map(|(name, value)| {
+                let escaped = value
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n");
+                format!("{}=\"{}\"", name, escaped)
+            })
+            .collect::<Vec<String>>();
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_accumulate_separately_for_each_label_set() {
+        let subject = Metrics::default();
+
+        subject.increment(&BYTES_CONSUMED, &[("service", "routing")], 100);
+        subject.increment(&BYTES_CONSUMED, &[("service", "exit")], 50);
+        subject.increment(&BYTES_CONSUMED, &[("service", "routing")], 25);
+
+        assert_eq!(
+            subject.value(&BYTES_CONSUMED, &[("service", "routing")]),
+            Some(125.0)
+        );
+        assert_eq!(
+            subject.value(&BYTES_CONSUMED, &[("service", "exit")]),
+            Some(50.0)
+        );
+        assert_eq!(subject.value(&BYTES_CONSUMED, &[]), None);
+    }
+
+    #[test]
+    fn gauges_are_replaced_rather_than_accumulated() {
+        let subject = Metrics::default();
+
+        subject.set(&PAYABLE_TOTAL, &[], 1234.0);
+        subject.set(&PAYABLE_TOTAL, &[], 567.0);
+
+        assert_eq!(subject.value(&PAYABLE_TOTAL, &[]), Some(567.0));
+    }
+
+    #[test]
+    fn observations_are_summed_and_counted() {
+        let subject = Metrics::default();
+
+        subject.observe(
+            &BLOCKCHAIN_RPC_DURATION,
+            &[("method", "eth_getBalance")],
+            Duration::from_millis(250),
+        );
+        subject.observe(
+            &BLOCKCHAIN_RPC_DURATION,
+            &[("method", "eth_getBalance")],
+            Duration::from_millis(500),
+        );
+
+        assert_eq!(
+            subject.observation_count(&BLOCKCHAIN_RPC_DURATION, &[("method", "eth_getBalance")]),
+            Some(2)
+        );
+        assert_eq!(
+            subject.render(),
+            "# HELP masq_blockchain_rpc_duration_seconds Time spent waiting for blockchain service calls, by method\n\
+             # TYPE masq_blockchain_rpc_duration_seconds summary\n\
+             masq_blockchain_rpc_duration_seconds_sum{method=\"eth_getBalance\"} 0.75\n\
+             masq_blockchain_rpc_duration_seconds_count{method=\"eth_getBalance\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn render_produces_prometheus_text_format_in_name_order() {
+        let subject = Metrics::default();
+        subject.increment(&ROUTE_QUERY_FAILURES, &[], 3);
+        subject.set(&ACTIVE_STREAMS, &[("pool", "proxy_client")], 2.0);
+        subject.set(&ACTIVE_STREAMS, &[("pool", "node")], 7.0);
+
+        let result = subject.render();
+
+        assert_eq!(
+            result,
+            "# HELP masq_active_streams Streams currently open in each stream handler pool\n\
+             # TYPE masq_active_streams gauge\n\
+             masq_active_streams{pool=\"node\"} 7\n\
+             masq_active_streams{pool=\"proxy_client\"} 2\n\
+             # HELP masq_route_query_failures_total Route queries the Neighborhood could not satisfy\n\
+             # TYPE masq_route_query_failures_total counter\n\
+             masq_route_query_failures_total 3\n"
+        );
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        let subject = Metrics::default();
+        subject.increment(&GOSSIP_RECEIVED, &[("handler", "a\"b\\c\nd")], 1);
+
+        let result = subject.render();
+
+        assert!(
+            result.contains("masq_gossip_received_total{handler=\"a\\\"b\\\\c\\nd\"} 1\n"),
+            "{}",
+            result
+        );
+    }
+
+    #[test]
+    fn empty_registry_renders_nothing() {
+        let subject = Metrics::default();
+
+        assert_eq!(subject.render(), String::new());
+    }
+}
@@ -1,5 +1,6 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 
+use crate::metrics::{metrics, GOSSIP_ACCEPTED, GOSSIP_RECEIVED, GOSSIP_REJECTED};
 use crate::neighborhood::gossip::{GossipBuilder, Gossip_0v1};
 use crate::neighborhood::neighborhood_database::{NeighborhoodDatabase, NeighborhoodDatabaseError};
 use crate::neighborhood::node_record::NodeRecord;
@@ -1048,7 +1049,9 @@ impl<'a> GossipAcceptor for GossipAcceptorReal<'a> {
             .map(|h| (h.qualifies(database, &agrs, gossip_source), h.as_ref()))
             .find(|pair| !matches!(pair, (Qualification::Unmatched, _)))
             .expect("gossip_handlers should intercept everything");
-        match qualification {
+        let labels = [("handler", handler_ref.type_name())];
+        metrics().increment(&GOSSIP_RECEIVED, &labels, 1);
+        let result = match qualification {
             Qualification::Matched => {
                 debug!(
                     self.logger,
@@ -1061,7 +1064,17 @@ impl<'a> GossipAcceptor for GossipAcceptorReal<'a> {
                 panic!("Nothing in gossip_handlers returned Matched or Malformed")
             }
             Qualification::Malformed(reason) => GossipAcceptanceResult::Ban(reason),
+        };
+        match result {
+            GossipAcceptanceResult::Accepted | GossipAcceptanceResult::Reply(..) => {
+                metrics().increment(&GOSSIP_ACCEPTED, &labels, 1)
+            }
+            GossipAcceptanceResult::Failed(..) | GossipAcceptanceResult::Ban(_) => {
+                metrics().increment(&GOSSIP_REJECTED, &labels, 1)
+            }
+            GossipAcceptanceResult::Ignored => (),
         }
+        result
     }
 }
 
@@ -2108,6 +2121,10 @@ mod tests {
         );
 
         assert_eq!(GossipAcceptanceResult::Ignored, result);
+        let labels = [("handler", "StandardGossipHandler")];
+        assert_eq!(metrics().value(&GOSSIP_RECEIVED, &labels), Some(1.0));
+        assert_eq!(metrics().value(&GOSSIP_ACCEPTED, &labels), None);
+        assert_eq!(metrics().value(&GOSSIP_REJECTED, &labels), None);
     }
 
     #[test]
@@ -2131,6 +2148,34 @@ mod tests {
             &debut_node,
             dest_db.node_by_key(debut_node.public_key()).unwrap()
         );
+        let labels = [("handler", "DebutHandler")];
+        assert_eq!(metrics().value(&GOSSIP_RECEIVED, &labels), Some(1.0));
+        assert_eq!(metrics().value(&GOSSIP_ACCEPTED, &labels), Some(1.0));
+        assert_eq!(metrics().value(&GOSSIP_REJECTED, &labels), None);
+    }
+
+    #[test]
+    fn malformed_debut_is_counted_as_rejected() {
+        let root_node = make_node_record(1234, true);
+        let root_node_cryptde = CryptDENull::from(&root_node.public_key(), DEFAULT_CHAIN_ID);
+        let mut dest_db = db_from_node(&root_node);
+        let (mut gossip, _, gossip_source) = make_debut(2345, Mode::OriginateOnly);
+        gossip.node_records[0].node_addr_opt = Some(NodeAddr::new(
+            &IpAddr::from_str("1.2.3.4").unwrap(),
+            &[1234],
+        ));
+        let subject = GossipAcceptorReal::new(&root_node_cryptde);
+
+        let result = subject.handle(&mut dest_db, gossip.try_into().unwrap(), gossip_source);
+
+        match result {
+            GossipAcceptanceResult::Ban(_) => (),
+            x => panic!("Expected Ban, got {:?}", x),
+        }
+        let labels = [("handler", "DebutHandler")];
+        assert_eq!(metrics().value(&GOSSIP_RECEIVED, &labels), Some(1.0));
+        assert_eq!(metrics().value(&GOSSIP_ACCEPTED, &labels), None);
+        assert_eq!(metrics().value(&GOSSIP_REJECTED, &labels), Some(1.0));
     }
 
     #[test]
@@ -13,6 +13,7 @@ pub mod node_record;
 use crate::blockchain::blockchain_interface::{chain_id_from_name, contract_address};
 use crate::bootstrapper::BootstrapperConfig;
 use crate::database::db_initializer::{DbInitializer, DbInitializerReal};
+use crate::metrics::{metrics, ROUTE_QUERY_FAILURES};
 use crate::neighborhood::gossip::{DotGossipEndpoint, GossipNodeRecord, Gossip_0v1};
 use crate::neighborhood::gossip_acceptor::GossipAcceptanceResult;
 use crate::neighborhood::node_record::NodeRecordInner_0v1;
@@ -30,11 +31,14 @@ use crate::sub_lib::neighborhood::NeighborhoodSubs;
 use crate::sub_lib::neighborhood::NodeDescriptor;
 use crate::sub_lib::neighborhood::NodeQueryMessage;
 use crate::sub_lib::neighborhood::NodeQueryResponseMetadata;
+use crate::sub_lib::neighborhood::NodeRatePackChangeMessage;
 use crate::sub_lib::neighborhood::NodeRecordMetadataMessage;
 use crate::sub_lib::neighborhood::RemoveNeighborMessage;
+use crate::sub_lib::neighborhood::RouteCostBudget;
 use crate::sub_lib::neighborhood::RouteQueryMessage;
 use crate::sub_lib::neighborhood::RouteQueryResponse;
 use crate::sub_lib::neighborhood::{DispatcherNodeQueryMessage, GossipFailure_0v1};
+use crate::sub_lib::neighborhood::{RatePack, RatePackChangeMessage};
 use crate::sub_lib::node_addr::NodeAddr;
 use crate::sub_lib::peer_actors::{BindMessage, StartMessage};
 use crate::sub_lib::proxy_server::DEFAULT_MINIMUM_HOP_COUNT;
@@ -59,17 +63,27 @@ use itertools::Itertools;
 use masq_lib::constants::DEFAULT_CHAIN_NAME;
 use masq_lib::messages::FromMessageBody;
 use masq_lib::messages::UiMessageError::UnexpectedMessage;
-use masq_lib::messages::{UiMessageError, UiShutdownRequest};
-use masq_lib::ui_gateway::{NodeFromUiMessage, NodeToUiMessage};
+use masq_lib::messages::{
+    ToMessageBody, UiChangeRatePackRequest, UiChangeRatePackResponse, UiMessageError,
+    UiNeighborhoodNode, UiNeighborhoodRequest, UiNeighborhoodResponse, UiRatePack,
+    UiShutdownRequest, RATE_PACK_ERROR,
+};
+use masq_lib::ui_gateway::MessagePath::Conversation;
+use masq_lib::ui_gateway::MessageTarget::ClientId;
+use masq_lib::ui_gateway::{MessageBody, NodeFromUiMessage, NodeToUiMessage};
 use masq_lib::utils::exit_process;
 use neighborhood_database::NeighborhoodDatabase;
 use node_record::NodeRecord;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 
 pub const CRASH_KEY: &str = "NEIGHBORHOOD";
+// Payload size assumed when weighing byte rates against service rates to rank candidate routes
+pub const ROUTE_COST_ESTIMATE_PAYLOAD_SIZE: u64 = 1000;
 
 pub struct Neighborhood {
     cryptde: &'static dyn CryptDE,
@@ -77,14 +91,17 @@ pub struct Neighborhood {
     hopper_no_lookup: Option<Recipient<NoLookupIncipientCoresPackage>>,
     is_connected: bool,
     connected_signal: Option<Recipient<StartMessage>>,
-    _to_ui_message_sub: Option<Recipient<NodeToUiMessage>>,
+    to_ui_message_sub: Option<Recipient<NodeToUiMessage>>,
+    rate_pack_change_subs: Vec<Recipient<RatePackChangeMessage>>,
+    node_rate_pack_change_sub: Option<Recipient<NodeRatePackChangeMessage>>,
     gossip_acceptor: Box<dyn GossipAcceptor>,
     gossip_producer: Box<dyn GossipProducer>,
     neighborhood_database: NeighborhoodDatabase,
     consuming_wallet_opt: Option<Wallet>,
+    route_cost_budget: RouteCostBudget,
     next_return_route_id: u32,
     initial_neighbors: Vec<NodeDescriptor>,
-    chain_id: u8,
+    chain_id: u64,
     data_directory: PathBuf,
     persistent_config_opt: Option<Box<dyn PersistentConfiguration>>,
     db_password_opt: Option<String>,
@@ -103,6 +120,12 @@ impl Handler<BindMessage> for Neighborhood {
         self.hopper = Some(msg.peer_actors.hopper.from_hopper_client);
         self.hopper_no_lookup = Some(msg.peer_actors.hopper.from_hopper_client_no_lookup);
         self.connected_signal = Some(msg.peer_actors.accountant.start);
+        self.to_ui_message_sub = Some(msg.peer_actors.ui_gateway.node_to_ui_message_sub);
+        self.rate_pack_change_subs = vec![
+            msg.peer_actors.hopper.rate_pack_change,
+            msg.peer_actors.proxy_client.rate_pack_change,
+        ];
+        self.node_rate_pack_change_sub = Some(msg.peer_actors.proxy_server.node_rate_pack_change);
     }
 }
 
@@ -272,6 +295,12 @@ impl Handler<NodeFromUiMessage> for Neighborhood {
     fn handle(&mut self, msg: NodeFromUiMessage, _ctx: &mut Self::Context) -> Self::Result {
         let client_id = msg.client_id;
         let opcode = msg.body.opcode.clone();
+        if let Ok((payload, context_id)) = UiChangeRatePackRequest::fmb(msg.body.clone()) {
+            return self.handle_change_rate_pack(client_id, context_id, payload);
+        }
+        if let Ok((_, context_id)) = UiNeighborhoodRequest::fmb(msg.body.clone()) {
+            return self.handle_neighborhood_request(client_id, context_id);
+        }
         let result: Result<(UiShutdownRequest, u64), UiMessageError> =
             UiShutdownRequest::fmb(msg.body);
         match result {
@@ -367,12 +396,15 @@ impl Neighborhood {
             hopper: None,
             hopper_no_lookup: None,
             connected_signal: None,
-            _to_ui_message_sub: None,
+            to_ui_message_sub: None,
+            rate_pack_change_subs: vec![],
+            node_rate_pack_change_sub: None,
             is_connected: false,
             gossip_acceptor,
             gossip_producer,
             neighborhood_database,
             consuming_wallet_opt: config.consuming_wallet.clone(),
+            route_cost_budget: neighborhood_config.route_cost_budget,
             next_return_route_id: 0,
             initial_neighbors,
             chain_id: config.blockchain_bridge_config.chain_id,
@@ -426,6 +458,7 @@ impl Neighborhood {
             }
             Err(msg) => {
                 error!(self.logger, "Unsatisfied route query: {}", msg);
+                metrics().increment(&ROUTE_QUERY_FAILURES, &[], 1);
                 None
             }
         }
@@ -585,9 +618,44 @@ impl Neighborhood {
 
     fn handle_gossip_agrs(&mut self, agrs: Vec<AccessibleGossipRecord>, gossip_source: SocketAddr) {
         let neighbor_keys_before = self.neighbor_keys();
+        let rate_packs_before = self.rate_packs();
         self.handle_agrs(agrs, gossip_source);
         let neighbor_keys_after = self.neighbor_keys();
         self.handle_database_changes(&neighbor_keys_before, &neighbor_keys_after);
+        self.report_rate_pack_changes(&rate_packs_before);
+    }
+
+    fn rate_packs(&self) -> HashMap<PublicKey, RatePack> {
+        self.neighborhood_database
+            .keys()
+            .into_iter()
+            .flat_map(|key| self.neighborhood_database.node_by_key(key))
+            .map(|node| (node.public_key().clone(), node.rate_pack().clone()))
+            .collect()
+    }
+
+    // The ProxyServer bills routes it already has at the rates they were chosen with
+    fn report_rate_pack_changes(&self, rate_packs_before: &HashMap<PublicKey, RatePack>) {
+        self.rate_packs()
+            .into_iter()
+            .filter(|(key, rate_pack)| match rate_packs_before.get(key) {
+                Some(rate_pack_before) => rate_pack_before != rate_pack,
+                None => false,
+            })
+            .for_each(|(public_key, rate_pack)| {
+                debug!(
+                    self.logger,
+                    "Node {} changed its rate pack to {}", public_key, rate_pack
+                );
+                self.node_rate_pack_change_sub
+                    .as_ref()
+                    .expect("ProxyServer is unbound")
+                    .try_send(NodeRatePackChangeMessage {
+                        public_key,
+                        rate_pack,
+                    })
+                    .expect("ProxyServer is dead")
+            });
     }
 
     fn neighbor_keys(&self) -> Vec<PublicKey> {
@@ -858,20 +926,39 @@ impl Neighborhood {
         target_component: Component,
         direction: RouteDirection,
     ) -> Result<RouteSegment, String> {
-        let mut node_seqs =
-            self.complete_routes(vec![origin], target, minimum_hop_count, direction);
+        let node_seqs = self.complete_routes(vec![origin], target, minimum_hop_count, direction);
+        let target_str = match target {
+            Some(t) => format!(" {}", t),
+            None => String::from("Unknown"),
+        };
 
         if node_seqs.is_empty() {
-            let target_str = match target {
-                Some(t) => format!(" {}", t),
-                None => String::from("Unknown"),
-            };
-            Err(format!(
+            return Err(format!(
                 "Couldn't find any routes: at least {}-hop from {} to {:?} at {}",
                 minimum_hop_count, origin, target_component, target_str
+            ));
+        }
+        let mut node_seqs = node_seqs
+            .into_iter()
+            .filter(|node_seq| {
+                let (byte_rate, service_rate) = self.route_rates(node_seq);
+                self.route_cost_budget.admits(byte_rate, service_rate)
+            })
+            .collect_vec();
+        if node_seqs.is_empty() {
+            Err(format!(
+                "Couldn't find any routes within budget of {}b+{} per segment: at least {}-hop from {} to {:?} at {}",
+                self.route_cost_budget.max_byte_rate,
+                self.route_cost_budget.max_service_rate,
+                minimum_hop_count,
+                origin,
+                target_component,
+                target_str
             ))
         } else {
+            self.sort_routes_by_cost(node_seqs.as_mut());
             // When the target is Some all exit nodes will be the target and it is not optimal to sort.
+            // Sorting is stable, so routes with equally desirable exit Nodes stay cheapest-first.
             if target.is_none() {
                 self.sort_routes_by_desirable_exit_nodes(node_seqs.as_mut());
             }
@@ -880,6 +967,36 @@ impl Neighborhood {
         }
     }
 
+    fn route_rates(&self, node_seq: &[&PublicKey]) -> (u64, u64) {
+        node_seq
+            .iter()
+            .map(|key| {
+                self.calculate_expected_service(
+                    key,
+                    node_seq.first().copied(),
+                    node_seq.last().copied(),
+                )
+                .expect("Route contains unknown Node")
+            })
+            .fold((0u64, 0u64), |(byte_rate, service_rate), service| {
+                (
+                    byte_rate.saturating_add(service.byte_rate()),
+                    service_rate.saturating_add(service.service_rate()),
+                )
+            })
+    }
+
+    fn route_cost(&self, node_seq: &[&PublicKey]) -> u64 {
+        let (byte_rate, service_rate) = self.route_rates(node_seq);
+        byte_rate
+            .saturating_mul(ROUTE_COST_ESTIMATE_PAYLOAD_SIZE)
+            .saturating_add(service_rate)
+    }
+
+    fn sort_routes_by_cost(&self, node_seqs: &mut Vec<Vec<&PublicKey>>) {
+        node_seqs.sort_by_key(|node_seq| self.route_cost(node_seq));
+    }
+
     fn sort_routes_by_desirable_exit_nodes(&self, node_seqs: &mut Vec<Vec<&PublicKey>>) {
         if node_seqs.is_empty() {
             panic!("Unable to sort routes by desirable exit nodes: Missing routes.");
@@ -1186,6 +1303,116 @@ impl Neighborhood {
         };
     }
 
+    fn handle_change_rate_pack(
+        &mut self,
+        client_id: u64,
+        context_id: u64,
+        request: UiChangeRatePackRequest,
+    ) {
+        let body = if self.neighborhood_database.root().routes_data() {
+            self.change_rate_pack(RatePack {
+                routing_byte_rate: request.routing_byte_rate,
+                routing_service_rate: request.routing_service_rate,
+                exit_byte_rate: request.exit_byte_rate,
+                exit_service_rate: request.exit_service_rate,
+            });
+            UiChangeRatePackResponse {}.tmb(context_id)
+        } else {
+            MessageBody {
+                opcode: "changeRatePack".to_string(),
+                path: Conversation(context_id),
+                payload: Err((
+                    RATE_PACK_ERROR,
+                    "This Node provides no services, so it has no rate pack to change".to_string(),
+                )),
+            }
+        };
+        self.to_ui_message_sub
+            .as_ref()
+            .expect("UiGateway is unbound")
+            .try_send(NodeToUiMessage {
+                target: ClientId(client_id),
+                body,
+            })
+            .expect("UiGateway is dead");
+    }
+
+    fn change_rate_pack(&mut self, rate_pack: RatePack) {
+        info!(self.logger, "Changing rate pack to {}", rate_pack);
+        self.rate_pack_change_subs.iter().for_each(|sub| {
+            sub.try_send(RatePackChangeMessage {
+                rate_pack: rate_pack.clone(),
+            })
+            .expect("Rate pack recipient is dead")
+        });
+        let root = self.neighborhood_database.root_mut();
+        if root.set_rate_pack(rate_pack) {
+            root.increment_version();
+            self.gossip_to_neighbors();
+        }
+    }
+
+    fn handle_neighborhood_request(&self, client_id: u64, context_id: u64) {
+        let db = &self.neighborhood_database;
+        let root_key = db.root().public_key();
+        let mut other_keys = db
+            .keys()
+            .into_iter()
+            .filter(|key| *key != root_key)
+            .collect::<Vec<&PublicKey>>();
+        other_keys.sort();
+        let nodes = vec![root_key]
+            .into_iter()
+            .chain(other_keys)
+            .flat_map(|key| db.node_by_key(key))
+            .map(|node| Self::to_ui_neighborhood_node(node, db))
+            .collect();
+        let response = UiNeighborhoodResponse {
+            nodes,
+            dot_graph: db.to_dot_graph(),
+        };
+        self.to_ui_message_sub
+            .as_ref()
+            .expect("UiGateway is unbound")
+            .try_send(NodeToUiMessage {
+                target: ClientId(client_id),
+                body: response.tmb(context_id),
+            })
+            .expect("UiGateway is dead");
+    }
+
+    fn to_ui_neighborhood_node(node: &NodeRecord, db: &NeighborhoodDatabase) -> UiNeighborhoodNode {
+        let full_neighbor_keys = node.full_neighbor_keys(db);
+        let half_neighbor_keys = node
+            .half_neighbor_keys()
+            .into_iter()
+            .filter(|key| !full_neighbor_keys.contains(key))
+            .collect::<HashSet<&PublicKey>>();
+        let sorted_strings = |keys: HashSet<&PublicKey>| {
+            keys.into_iter()
+                .sorted()
+                .map(|key| key.to_string())
+                .collect::<Vec<String>>()
+        };
+        let rate_pack = node.rate_pack();
+        UiNeighborhoodNode {
+            public_key: node.public_key().to_string(),
+            node_addr_opt: node.node_addr_opt().map(|node_addr| node_addr.to_string()),
+            version: node.version(),
+            rate_pack: UiRatePack {
+                routing_byte_rate: rate_pack.routing_byte_rate,
+                routing_service_rate: rate_pack.routing_service_rate,
+                exit_byte_rate: rate_pack.exit_byte_rate,
+                exit_service_rate: rate_pack.exit_service_rate,
+            },
+            desirable: node.is_desirable(),
+            accepts_connections: node.accepts_connections(),
+            routes_data: node.routes_data(),
+            full_neighbors: sorted_strings(full_neighbor_keys),
+            half_neighbors: sorted_strings(half_neighbor_keys),
+        }
+    }
+
     #[allow(unreachable_code)]
     fn handle_shutdown_order(&self, client_id: u64, _msg: UiShutdownRequest) {
         info!(
@@ -1230,7 +1457,9 @@ mod tests {
     use crate::sub_lib::hop::LiveHop;
     use crate::sub_lib::hopper::MessageType;
     use crate::sub_lib::neighborhood::{ExpectedServices, NeighborhoodMode};
-    use crate::sub_lib::neighborhood::{NeighborhoodConfig, DEFAULT_RATE_PACK};
+    use crate::sub_lib::neighborhood::{
+        NeighborhoodConfig, DEFAULT_RATE_PACK, UNLIMITED_ROUTE_COST_BUDGET,
+    };
     use crate::sub_lib::peer_actors::PeerActors;
     use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
     use crate::sub_lib::versioned_data::VersionedData;
@@ -1283,6 +1512,7 @@ mod tests {
                     "AQIDBA:1.2.3.4:1234",
                 )
                 .unwrap()]),
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             },
             earning_wallet.clone(),
             None,
@@ -1305,6 +1535,7 @@ mod tests {
                     "AQIDBA@1.2.3.4:1234",
                 )
                 .unwrap()]),
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             },
             earning_wallet.clone(),
             None,
@@ -1325,6 +1556,7 @@ mod tests {
             &bc_from_nc_plus(
                 NeighborhoodConfig {
                     mode: NeighborhoodMode::ZeroHop,
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 earning_wallet.clone(),
                 None,
@@ -1353,6 +1585,7 @@ mod tests {
                         vec![neighbor.node_descriptor(DEFAULT_CHAIN_ID, cryptde)],
                         DEFAULT_RATE_PACK.clone(),
                     ),
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 earning_wallet.clone(),
                 None,
@@ -1382,6 +1615,7 @@ mod tests {
             &bc_from_nc_plus(
                 NeighborhoodConfig {
                     mode: NeighborhoodMode::ZeroHop,
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 earning_wallet.clone(),
                 consuming_wallet.clone(),
@@ -1427,6 +1661,7 @@ mod tests {
                         ))],
                         rate_pack(100),
                     ),
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 earning_wallet.clone(),
                 consuming_wallet.clone(),
@@ -1473,6 +1708,7 @@ mod tests {
                         ],
                         rate_pack(100),
                     ),
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 earning_wallet.clone(),
                 consuming_wallet.clone(),
@@ -1541,6 +1777,7 @@ mod tests {
                         ],
                         rate_pack(100),
                     ),
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 earning_wallet.clone(),
                 None,
@@ -1612,6 +1849,7 @@ mod tests {
                         ))],
                         rate_pack(100),
                     ),
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 earning_wallet.clone(),
                 consuming_wallet.clone(),
@@ -1647,6 +1885,7 @@ mod tests {
                         vec![node_record_to_neighbor_config(&one_neighbor)],
                         rate_pack(100),
                     ),
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 earning_wallet.clone(),
                 consuming_wallet.clone(),
@@ -1699,6 +1938,7 @@ mod tests {
                         ))],
                         rate_pack(100),
                     ),
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 earning_wallet.clone(),
                 consuming_wallet.clone(),
@@ -1739,6 +1979,7 @@ mod tests {
                         ))],
                         rate_pack(100),
                     ),
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 node_record.earning_wallet(),
                 None,
@@ -1783,6 +2024,7 @@ mod tests {
         system.run();
         let result = future.wait().unwrap();
         assert_eq!(result, None);
+        assert_eq!(metrics().value(&ROUTE_QUERY_FAILURES, &[]), Some(1.0));
     }
 
     #[test]
@@ -1970,6 +2212,7 @@ mod tests {
             ),
         };
         assert_eq!(result, expected_response);
+        assert_eq!(metrics().value(&ROUTE_QUERY_FAILURES, &[]), None);
     }
 
     #[test]
@@ -2183,6 +2426,228 @@ mod tests {
         subject.sort_routes_by_desirable_exit_nodes(&mut node_sequences);
     }
 
+    #[test]
+    fn neighborhood_adopts_route_cost_budget_from_config() {
+        let budget = RouteCostBudget {
+            max_byte_rate: 1234,
+            max_service_rate: 5678,
+        };
+        let config = bc_from_nc_plus(
+            NeighborhoodConfig {
+                mode: NeighborhoodMode::ZeroHop,
+                route_cost_budget: budget,
+            },
+            make_wallet("earning"),
+            None,
+            "neighborhood_adopts_route_cost_budget_from_config",
+        );
+
+        let subject = Neighborhood::new(main_cryptde(), &config);
+
+        assert_eq!(subject.route_cost_budget, budget);
+    }
+
+    fn make_diamond_subject() -> (NodeRecord, NodeRecord, NodeRecord, NodeRecord, Neighborhood) {
+        // p is the root; a is an expensive relay and b a cheap one, both leading to exit x
+        let mut subject = make_standard_subject();
+        let p = &subject.neighborhood_database.root().clone();
+        let a = &make_node_record(5000, false);
+        let b = &make_node_record(3000, false);
+        let x = &make_node_record(4000, false);
+        {
+            let db = &mut subject.neighborhood_database;
+            db.add_node(a.clone()).unwrap();
+            db.add_node(b.clone()).unwrap();
+            db.add_node(x.clone()).unwrap();
+            let mut dual_edge = |a: &NodeRecord, b: &NodeRecord| {
+                db.add_arbitrary_full_neighbor(a.public_key(), b.public_key())
+            };
+            dual_edge(p, a);
+            dual_edge(p, b);
+            dual_edge(a, x);
+            dual_edge(b, x);
+        }
+        (p.clone(), a.clone(), b.clone(), x.clone(), subject)
+    }
+
+    #[test]
+    fn make_round_trip_route_prefers_the_cheapest_relays_in_both_directions() {
+        let (_, _, b, x, mut subject) = make_diamond_subject();
+
+        let result = subject
+            .make_round_trip_route(RouteQueryMessage::data_indefinite_route_request(2))
+            .unwrap();
+
+        assert_eq!(
+            result.expected_services,
+            ExpectedServices::RoundTrip(
+                vec![
+                    ExpectedService::Nothing,
+                    ExpectedService::Routing(
+                        b.public_key().clone(),
+                        b.earning_wallet(),
+                        rate_pack(3000),
+                    ),
+                    ExpectedService::Exit(
+                        x.public_key().clone(),
+                        x.earning_wallet(),
+                        rate_pack(4000),
+                    ),
+                ],
+                vec![
+                    ExpectedService::Exit(
+                        x.public_key().clone(),
+                        x.earning_wallet(),
+                        rate_pack(4000),
+                    ),
+                    ExpectedService::Routing(
+                        b.public_key().clone(),
+                        b.earning_wallet(),
+                        rate_pack(3000),
+                    ),
+                    ExpectedService::Nothing,
+                ],
+                0,
+            )
+        );
+    }
+
+    fn make_choice_of_exits_subject(
+    ) -> (NodeRecord, NodeRecord, NodeRecord, NodeRecord, Neighborhood) {
+        // p is the root and r its only relay; behind r are a cheap exit that has been marked
+        // undesirable and an expensive but desirable one
+        let mut subject = make_standard_subject();
+        let p = &subject.neighborhood_database.root().clone();
+        let r = &make_node_record(2000, false);
+        let mut cheap = make_node_record(1000, false);
+        cheap.set_desirable(false);
+        let expensive = &make_node_record(6000, false);
+        {
+            let db = &mut subject.neighborhood_database;
+            db.add_node(r.clone()).unwrap();
+            db.add_node(cheap.clone()).unwrap();
+            db.add_node(expensive.clone()).unwrap();
+            let mut dual_edge = |a: &NodeRecord, b: &NodeRecord| {
+                db.add_arbitrary_full_neighbor(a.public_key(), b.public_key())
+            };
+            dual_edge(p, r);
+            dual_edge(r, &cheap);
+            dual_edge(r, expensive);
+        }
+        (p.clone(), r.clone(), cheap, expensive.clone(), subject)
+    }
+
+    #[test]
+    fn make_route_segment_prefers_desirable_exit_nodes_over_cheaper_ones() {
+        let (p, r, _, expensive, subject) = make_choice_of_exits_subject();
+
+        let result = subject
+            .make_route_segment(
+                p.public_key(),
+                None,
+                2,
+                Component::ProxyClient,
+                RouteDirection::Over,
+            )
+            .unwrap();
+
+        assert_eq!(
+            result.keys,
+            vec![
+                p.public_key().clone(),
+                r.public_key().clone(),
+                expensive.public_key().clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn make_route_segment_skips_routes_that_exceed_the_budget() {
+        let (p, r, cheap, _, mut subject) = make_choice_of_exits_subject();
+        let (byte_rate, service_rate) =
+            subject.route_rates(&[p.public_key(), r.public_key(), cheap.public_key()]);
+        subject.route_cost_budget = RouteCostBudget {
+            max_byte_rate: byte_rate,
+            max_service_rate: service_rate,
+        };
+
+        let result = subject
+            .make_route_segment(
+                p.public_key(),
+                None,
+                2,
+                Component::ProxyClient,
+                RouteDirection::Over,
+            )
+            .unwrap();
+
+        assert_eq!(
+            result.keys,
+            vec![
+                p.public_key().clone(),
+                r.public_key().clone(),
+                cheap.public_key().clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn make_round_trip_route_returns_error_when_no_route_fits_the_budget() {
+        let (_, _, _, _, mut subject) = make_diamond_subject();
+        subject.route_cost_budget = RouteCostBudget {
+            max_byte_rate: 100,
+            max_service_rate: 10000,
+        };
+
+        let result =
+            subject.make_round_trip_route(RouteQueryMessage::data_indefinite_route_request(2));
+
+        assert_eq!(
+            result,
+            Err(format!(
+                "Couldn't find any routes within budget of 100b+10000 per segment: at least 2-hop from {} to ProxyClient at Unknown",
+                main_cryptde().public_key()
+            ))
+        );
+    }
+
+    #[test]
+    fn route_query_rejects_routes_that_exceed_the_configured_route_cost_budget() {
+        init_test_logging();
+        let system =
+            System::new("route_query_rejects_routes_that_exceed_the_configured_route_cost_budget");
+        let (_, _, _, _, mut subject) = make_diamond_subject();
+        subject.route_cost_budget = RouteCostBudget::from_str("100|10000").unwrap();
+        let addr: Addr<Neighborhood> = subject.start();
+        let sub: Recipient<RouteQueryMessage> = addr.recipient::<RouteQueryMessage>();
+
+        let future = sub.send(RouteQueryMessage::data_indefinite_route_request(2));
+
+        System::current().stop_with_code(0);
+        system.run();
+        let result = future.wait().unwrap();
+        assert_eq!(result, None);
+        TestLogHandler::new().exists_log_containing(
+            "ERROR: Neighborhood: Unsatisfied route query: Couldn't find any routes within budget of 100b+10000 per segment",
+        );
+    }
+
+    #[test]
+    fn route_cost_weighs_byte_rates_by_the_estimated_payload_size() {
+        let (p, _, b, x, subject) = make_diamond_subject();
+        let route = [p.public_key(), b.public_key(), x.public_key()];
+
+        let result = subject.route_cost(&route);
+
+        let byte_rate = rate_pack(3000).routing_byte_rate + rate_pack(4000).exit_byte_rate;
+        let service_rate = rate_pack(3000).routing_service_rate + rate_pack(4000).exit_service_rate;
+        assert_eq!(subject.route_rates(&route), (byte_rate, service_rate));
+        assert_eq!(
+            result,
+            byte_rate * ROUTE_COST_ESTIMATE_PAYLOAD_SIZE + service_rate
+        );
+    }
+
     #[test]
     fn compose_route_query_response_returns_an_error_when_route_segment_is_empty() {
         let mut subject = make_standard_subject();
@@ -2469,6 +2934,7 @@ mod tests {
                             vec![],
                             rate_pack(100),
                         ),
+                        route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                     },
                     earning_wallet.clone(),
                     consuming_wallet.clone(),
@@ -2844,6 +3310,54 @@ mod tests {
         assert_eq!(subject.is_connected, true);
     }
 
+    #[test]
+    fn neighborhood_reports_rate_packs_changed_by_gossip_to_the_proxy_server() {
+        let subject_node = make_global_cryptde_node_record(5555, true); // 9e7p7un06eHs6frl5A
+        let changed = make_node_record(1111, true);
+        let unchanged = make_node_record(2222, false);
+        let newcomer = make_node_record(3333, false);
+        let mut subject: Neighborhood = neighborhood_from_nodes(&subject_node, Some(&changed));
+        subject
+            .neighborhood_database
+            .add_node(changed.clone())
+            .unwrap();
+        subject
+            .neighborhood_database
+            .add_node(unchanged.clone())
+            .unwrap();
+        let mut replacement_database = subject.neighborhood_database.clone();
+        replacement_database
+            .node_by_key_mut(changed.public_key())
+            .unwrap()
+            .set_rate_pack(rate_pack(2000));
+        replacement_database.add_node(newcomer.clone()).unwrap();
+        subject.gossip_acceptor = Box::new(DatabaseReplacementGossipAcceptor {
+            replacement_database,
+        });
+        subject.is_connected = true;
+        let (proxy_server, _, proxy_server_recording_arc) = make_recorder();
+        let system =
+            System::new("neighborhood_reports_rate_packs_changed_by_gossip_to_the_proxy_server");
+        let peer_actors = peer_actors_builder().proxy_server(proxy_server).build();
+        subject.node_rate_pack_change_sub =
+            Some(peer_actors.proxy_server.node_rate_pack_change.clone());
+        bind_subject(&mut subject, peer_actors);
+
+        subject.handle_gossip_agrs(vec![], SocketAddr::from_str("1.2.3.4:1234").unwrap());
+
+        System::current().stop();
+        system.run();
+        let proxy_server_recording = proxy_server_recording_arc.lock().unwrap();
+        assert_eq!(
+            proxy_server_recording.get_record::<NodeRatePackChangeMessage>(0),
+            &NodeRatePackChangeMessage {
+                public_key: changed.public_key().clone(),
+                rate_pack: rate_pack(2000),
+            }
+        );
+        assert_eq!(proxy_server_recording.len(), 1);
+    }
+
     struct NeighborReplacementGossipAcceptor {
         pub new_neighbors: Vec<NodeRecord>,
     }
@@ -3385,6 +3899,7 @@ mod tests {
                             vec![],
                             rate_pack(100),
                         ),
+                        route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                     },
                     this_node_inside.earning_wallet(),
                     None,
@@ -3438,6 +3953,7 @@ mod tests {
                         ))],
                         rate_pack(100),
                     ),
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 NodeRecord::earning_wallet_from_key(&cryptde.public_key()),
                 NodeRecord::consuming_wallet_from_key(&cryptde.public_key()),
@@ -3624,8 +4140,7 @@ mod tests {
                                 cryptde,
                             ))],
                             rate_pack(100),
-                        ),
-                    },
+                        ), route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET, },
                     earning_wallet.clone(),
                     consuming_wallet.clone(),
                     "neighborhood_sends_node_query_response_with_none_when_key_query_matches_no_configured_data"
@@ -3685,8 +4200,7 @@ mod tests {
                             NodeAddr::new(&IpAddr::from_str("5.4.3.2").unwrap(), &[5678]),
                             vec![node_record_to_neighbor_config(&one_neighbor)],
                             rate_pack(100),
-                        ),
-                    },
+                        ), route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET, },
                     earning_wallet.clone(),
                     consuming_wallet.clone(),
                     "neighborhood_sends_node_query_response_with_result_when_key_query_matches_configured_data"
@@ -3751,8 +4265,7 @@ mod tests {
                                 cryptde,
                             ))],
                             rate_pack(100),
-                        ),
-                    },
+                        ), route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET, },
                     earning_wallet.clone(),
                     consuming_wallet.clone(),
                     "neighborhood_sends_node_query_response_with_none_when_ip_address_query_matches_no_configured_data"
@@ -3814,8 +4327,7 @@ mod tests {
                             cryptde,
                         ))],
                         rate_pack(100),
-                    ),
-                },
+                    ), route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET, },
                 node_record.earning_wallet(),
                 None,
                 "neighborhood_sends_node_query_response_with_result_when_ip_address_query_matches_configured_data"
@@ -4146,6 +4658,7 @@ mod tests {
             &bc_from_nc_plus(
                 NeighborhoodConfig {
                     mode: NeighborhoodMode::ZeroHop,
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 make_wallet("earning"),
                 None,
@@ -4176,6 +4689,258 @@ mod tests {
             .exists_log_containing("INFO: Neighborhood: Received shutdown order from client 1234");
     }
 
+    #[test]
+    fn change_rate_pack_request_changes_and_gossips_rate_pack() {
+        let (hopper, _, hopper_recording_arc) = make_recorder();
+        let (proxy_client, _, proxy_client_recording_arc) = make_recorder();
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let system = System::new("change_rate_pack_request_changes_and_gossips_rate_pack");
+        let subject_node = make_global_cryptde_node_record(1345, true);
+        let neighbor_node = make_node_record(2456, true);
+        let mut subject = neighborhood_from_nodes(&subject_node, Some(&neighbor_node));
+        subject
+            .neighborhood_database
+            .add_node(neighbor_node.clone())
+            .unwrap();
+        subject
+            .neighborhood_database
+            .add_arbitrary_full_neighbor(subject_node.public_key(), neighbor_node.public_key());
+        let subject_addr = subject.start();
+        let peer_actors = peer_actors_builder()
+            .hopper(hopper)
+            .proxy_client(proxy_client)
+            .ui_gateway(ui_gateway)
+            .build();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: UiChangeRatePackRequest {
+                    routing_byte_rate: 1001,
+                    routing_service_rate: 1002,
+                    exit_byte_rate: 1003,
+                    exit_service_rate: 1004,
+                }
+                .tmb(4321),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let expected_message = RatePackChangeMessage {
+            rate_pack: rate_pack(1000),
+        };
+        let proxy_client_recording = proxy_client_recording_arc.lock().unwrap();
+        assert_eq!(
+            proxy_client_recording.get_record::<RatePackChangeMessage>(0),
+            &expected_message
+        );
+        let hopper_recording = hopper_recording_arc.lock().unwrap();
+        assert_eq!(
+            hopper_recording.get_record::<RatePackChangeMessage>(0),
+            &expected_message
+        );
+        let package = hopper_recording.get_record::<IncipientCoresPackage>(1);
+        let gossip = match decodex::<MessageType>(
+            &CryptDENull::from(neighbor_node.public_key(), DEFAULT_CHAIN_ID),
+            &package.payload,
+        )
+        .unwrap()
+        {
+            MessageType::Gossip(vd) => Gossip_0v1::try_from(vd).unwrap(),
+            x => panic!("Expected Gossip, but found {:?}", x),
+        };
+        let gossiped_root = gossip
+            .node_records
+            .into_iter()
+            .map(|gnr| AccessibleGossipRecord::try_from(gnr).unwrap())
+            .find(|agr| &agr.inner.public_key == subject_node.public_key())
+            .unwrap();
+        assert_eq!(gossiped_root.inner.rate_pack, rate_pack(1000));
+        assert_eq!(gossiped_root.inner.version, subject_node.version() + 1);
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        assert_eq!(
+            ui_gateway_recording.get_record::<NodeToUiMessage>(0),
+            &NodeToUiMessage {
+                target: ClientId(1234),
+                body: UiChangeRatePackResponse {}.tmb(4321),
+            }
+        );
+    }
+
+    #[test]
+    fn change_rate_pack_request_is_refused_when_node_provides_no_services() {
+        let (hopper, _, hopper_recording_arc) = make_recorder();
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let system =
+            System::new("change_rate_pack_request_is_refused_when_node_provides_no_services");
+        let subject = Neighborhood::new(
+            main_cryptde(),
+            &bc_from_nc_plus(
+                NeighborhoodConfig {
+                    mode: NeighborhoodMode::ZeroHop,
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
+                },
+                make_wallet("earning"),
+                None,
+                "change_rate_pack_request_is_refused_when_node_provides_no_services",
+            ),
+        );
+        let subject_addr = subject.start();
+        let peer_actors = peer_actors_builder()
+            .hopper(hopper)
+            .ui_gateway(ui_gateway)
+            .build();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: UiChangeRatePackRequest {
+                    routing_byte_rate: 1,
+                    routing_service_rate: 2,
+                    exit_byte_rate: 3,
+                    exit_service_rate: 4,
+                }
+                .tmb(4321),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        assert_eq!(hopper_recording_arc.lock().unwrap().len(), 0);
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        assert_eq!(
+            ui_gateway_recording.get_record::<NodeToUiMessage>(0),
+            &NodeToUiMessage {
+                target: ClientId(1234),
+                body: MessageBody {
+                    opcode: "changeRatePack".to_string(),
+                    path: Conversation(4321),
+                    payload: Err((
+                        RATE_PACK_ERROR,
+                        "This Node provides no services, so it has no rate pack to change"
+                            .to_string()
+                    )),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn neighborhood_request_is_answered_with_nodes_and_dot_graph() {
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let system = System::new("neighborhood_request_is_answered_with_nodes_and_dot_graph");
+        let subject_node = make_global_cryptde_node_record(1345, true);
+        let full_neighbor = make_node_record(2456, true);
+        let half_neighbor = make_node_record_f(3567, false, false, false);
+        let mut subject = neighborhood_from_nodes(&subject_node, Some(&full_neighbor));
+        {
+            let db = &mut subject.neighborhood_database;
+            db.add_node(full_neighbor.clone()).unwrap();
+            db.add_node(half_neighbor.clone()).unwrap();
+            db.add_arbitrary_full_neighbor(subject_node.public_key(), full_neighbor.public_key());
+            db.add_arbitrary_half_neighbor(subject_node.public_key(), half_neighbor.public_key());
+            db.node_by_key_mut(half_neighbor.public_key())
+                .unwrap()
+                .set_desirable(false);
+        }
+        let subject_addr = subject.start();
+        let peer_actors = peer_actors_builder().ui_gateway(ui_gateway).build();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body: UiNeighborhoodRequest {}.tmb(4321),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        let message = ui_gateway_recording.get_record::<NodeToUiMessage>(0);
+        assert_eq!(message.target, ClientId(1234));
+        let (response, context_id) = UiNeighborhoodResponse::fmb(message.body.clone()).unwrap();
+        assert_eq!(context_id, 4321);
+        assert!(
+            response.dot_graph.starts_with("digraph db { "),
+            "{}",
+            response.dot_graph
+        );
+        let root_key = subject_node.public_key();
+        vec![
+            "\"AgQFBg\" [label=\"AR v0\\nAgQFBg\\n2.4.5.6:2456\"];".to_string(),
+            "\"AwUGBw\" [label=\"ar v0\\nAwUGBw\"];".to_string(),
+            format!("\"AgQFBg\" -> \"{}\";", root_key),
+            format!("\"{}\" -> \"AgQFBg\";", root_key),
+            format!("\"{}\" -> \"AwUGBw\";", root_key),
+        ]
+        .into_iter()
+        .for_each(|fragment| {
+            assert!(
+                response.dot_graph.contains(&fragment),
+                "{} not in {}",
+                fragment,
+                response.dot_graph
+            )
+        });
+        assert_eq!(
+            response.nodes,
+            vec![
+                UiNeighborhoodNode {
+                    public_key: subject_node.public_key().to_string(),
+                    node_addr_opt: Some(subject_node.node_addr_opt().unwrap().to_string()),
+                    version: subject_node.version(),
+                    rate_pack: UiRatePack {
+                        routing_byte_rate: 1346,
+                        routing_service_rate: 1347,
+                        exit_byte_rate: 1348,
+                        exit_service_rate: 1349,
+                    },
+                    desirable: true,
+                    accepts_connections: true,
+                    routes_data: true,
+                    full_neighbors: vec![full_neighbor.public_key().to_string()],
+                    half_neighbors: vec![half_neighbor.public_key().to_string()],
+                },
+                UiNeighborhoodNode {
+                    public_key: full_neighbor.public_key().to_string(),
+                    node_addr_opt: Some("2.4.5.6:2456".to_string()),
+                    version: full_neighbor.version(),
+                    rate_pack: UiRatePack {
+                        routing_byte_rate: 2457,
+                        routing_service_rate: 2458,
+                        exit_byte_rate: 2459,
+                        exit_service_rate: 2460,
+                    },
+                    desirable: true,
+                    accepts_connections: true,
+                    routes_data: true,
+                    full_neighbors: vec![subject_node.public_key().to_string()],
+                    half_neighbors: vec![],
+                },
+                UiNeighborhoodNode {
+                    public_key: half_neighbor.public_key().to_string(),
+                    node_addr_opt: None,
+                    version: half_neighbor.version(),
+                    rate_pack: UiRatePack {
+                        routing_byte_rate: 3568,
+                        routing_service_rate: 3569,
+                        exit_byte_rate: 3570,
+                        exit_service_rate: 3571,
+                    },
+                    desirable: false,
+                    accepts_connections: false,
+                    routes_data: false,
+                    full_neighbors: vec![],
+                    half_neighbors: vec![],
+                },
+            ]
+        );
+    }
+
     #[test]
     fn unexpected_ui_message_is_logged_and_ignored() {
         init_test_logging();
@@ -4184,6 +4949,7 @@ mod tests {
             &bc_from_nc_plus(
                 NeighborhoodConfig {
                     mode: NeighborhoodMode::ZeroHop,
+                    route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
                 },
                 make_wallet("earning"),
                 None,
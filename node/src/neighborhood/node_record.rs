@@ -97,7 +97,7 @@ impl NodeRecord {
         self.metadata.node_addr_opt.clone()
     }
 
-    pub fn node_descriptor(&self, chain_id: u8, cryptde: &dyn CryptDE) -> NodeDescriptor {
+    pub fn node_descriptor(&self, chain_id: u64, cryptde: &dyn CryptDE) -> NodeDescriptor {
         NodeDescriptor::from((
             self,
             chain_id == chain_id_from_name(DEFAULT_CHAIN_NAME),
@@ -251,6 +251,15 @@ impl NodeRecord {
         &self.inner.rate_pack
     }
 
+    pub fn set_rate_pack(&mut self, rate_pack: RatePack) -> bool {
+        if self.inner.rate_pack == rate_pack {
+            false
+        } else {
+            self.inner.rate_pack = rate_pack;
+            true
+        }
+    }
+
     pub fn is_desirable(&self) -> bool {
         self.metadata.desirable
     }
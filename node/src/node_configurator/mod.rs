@@ -152,7 +152,9 @@ pub fn determine_config_file_path(
     .map(|vcl_arg| vcl_arg.dup())
     .collect();
     let orientation_vcl = CommandLineVcl::from(orientation_args);
-    let multi_config = make_new_multi_config(
+    // Only the chain's name is needed to find the data directory; a chain described in a blockchains
+    // file named in the config file is checked once the config file has been read
+    let multi_config = MultiConfig::try_new(
         &orientation_schema,
         vec![Box::new(orientation_vcl)],
         &mut FakeStreamHolder::new().streams(),
@@ -193,7 +195,7 @@ pub fn create_wallet(
 
 pub fn initialize_database(
     data_directory: &PathBuf,
-    chain_id: u8,
+    chain_id: u64,
 ) -> Box<dyn PersistentConfiguration> {
     let conn = DbInitializerReal::new()
         .initialize(data_directory, chain_id, true)
@@ -239,32 +241,31 @@ pub fn data_directory_from_context(
 ) -> PathBuf {
     match data_directory_opt {
         Some(data_directory) => data_directory.clone(),
-        None => {
-            let right_home_dir = real_user
-                .home_dir
-                .as_ref()
-                .expect("No real-user home directory; specify --real-user")
-                .to_string_lossy()
-                .to_string();
-            let wrong_home_dir = dirs_wrapper
-                .home_dir()
-                .expect("No privileged home directory; specify --data-directory")
-                .to_string_lossy()
-                .to_string();
-            let wrong_local_data_dir = dirs_wrapper
-                .data_dir()
-                .expect("No privileged local data directory; specify --data-directory")
-                .to_string_lossy()
-                .to_string();
-            let right_local_data_dir =
-                wrong_local_data_dir.replace(&wrong_home_dir, &right_home_dir);
-            PathBuf::from(right_local_data_dir)
-                .join("MASQ")
-                .join(chain_name)
-        }
+        None => real_user_masq_directory(dirs_wrapper, real_user).join(chain_name),
     }
 }
 
+pub fn real_user_masq_directory(dirs_wrapper: &dyn DirsWrapper, real_user: &RealUser) -> PathBuf {
+    let right_home_dir = real_user
+        .home_dir
+        .as_ref()
+        .expect("No real-user home directory; specify --real-user")
+        .to_string_lossy()
+        .to_string();
+    let wrong_home_dir = dirs_wrapper
+        .home_dir()
+        .expect("No privileged home directory; specify --data-directory")
+        .to_string_lossy()
+        .to_string();
+    let wrong_local_data_dir = dirs_wrapper
+        .data_dir()
+        .expect("No privileged local data directory; specify --data-directory")
+        .to_string_lossy()
+        .to_string();
+    let right_local_data_dir = wrong_local_data_dir.replace(&wrong_home_dir, &right_home_dir);
+    PathBuf::from(right_local_data_dir).join("MASQ")
+}
+
 pub fn prepare_initialization_mode<'a>(
     dirs_wrapper: &dyn DirsWrapper,
     app: &'a App,
@@ -372,7 +373,10 @@ pub fn cannot_be_blank(password: &str) -> Result<(), String> {
 }
 
 pub fn mnemonic_seed_exists(persistent_config: &dyn PersistentConfiguration) -> bool {
-    matches! (persistent_config.mnemonic_seed("bad password"), Ok(Some(_)) | Err(PersistentConfigError::PasswordError))
+    matches!(
+        persistent_config.mnemonic_seed("bad password"),
+        Ok(Some(_)) | Err(PersistentConfigError::PasswordError)
+    )
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -978,6 +982,18 @@ mod tests {
         assert_eq!(&chain_name, DEFAULT_CHAIN_NAME);
     }
 
+    #[test]
+    fn real_user_masq_directory_translates_privileged_data_directory_to_real_user() {
+        let dirs_wrapper = MockDirsWrapper::new()
+            .home_dir_result(Some(PathBuf::from("/root")))
+            .data_dir_result(Some(PathBuf::from("/root/.local/share")));
+        let real_user = RealUser::new(Some(1000), Some(1000), Some(PathBuf::from("/home/booga")));
+
+        let result = real_user_masq_directory(&dirs_wrapper, &real_user);
+
+        assert_eq!(result, PathBuf::from("/home/booga/.local/share/MASQ"));
+    }
+
     #[test]
     fn determine_config_file_path_finds_path_in_args() {
         let _guard = EnvironmentGuard::new();
@@ -18,7 +18,8 @@ use indoc::indoc;
 use masq_lib::command::StdStreams;
 use masq_lib::multi_config::MultiConfig;
 use masq_lib::shared_schema::{
-    chain_arg, data_directory_arg, db_password_arg, real_user_arg, ConfiguratorError,
+    blockchains_file_arg, chain_arg, data_directory_arg, db_password_arg, real_user_arg,
+    ConfiguratorError,
 };
 use std::str::FromStr;
 use unindent::unindent;
@@ -52,7 +53,7 @@ pub trait MnemonicFactory {
     fn make(&self, mnemonic_type: MnemonicType, language: Language) -> Mnemonic;
 }
 
-struct MnemonicFactoryReal {}
+pub struct MnemonicFactoryReal {}
 
 impl MnemonicFactory for MnemonicFactoryReal {
     fn make(&self, mnemonic_type: MnemonicType, language: Language) -> Mnemonic {
@@ -158,6 +159,7 @@ impl NodeConfiguratorGenerateWallet {
                         .takes_value(false)
                         .hidden(true),
                 )
+                .arg(blockchains_file_arg())
                 .arg(chain_arg())
                 .arg(consuming_wallet_arg())
                 .arg(data_directory_arg())
@@ -16,7 +16,8 @@ use indoc::indoc;
 use masq_lib::command::StdStreams;
 use masq_lib::multi_config::MultiConfig;
 use masq_lib::shared_schema::{
-    chain_arg, data_directory_arg, db_password_arg, real_user_arg, ConfiguratorError,
+    blockchains_file_arg, chain_arg, data_directory_arg, db_password_arg, real_user_arg,
+    ConfiguratorError,
 };
 use masq_lib::utils::exit_process;
 
@@ -129,6 +130,7 @@ impl NodeConfiguratorRecoverWallet {
                         .requires_all(&["language"])
                         .help(RECOVER_WALLET_HELP),
                 )
+                .arg(blockchains_file_arg())
                 .arg(chain_arg())
                 .arg(consuming_wallet_arg())
                 .arg(data_directory_arg())
@@ -149,6 +149,7 @@ pub mod standard {
 
     use crate::blockchain::bip32::Bip32ECKeyPair;
     use crate::blockchain::blockchain_interface::chain_id_from_name;
+    use crate::blockchain::failover_transport::FailoverPolicy;
     use crate::bootstrapper::PortConfiguration;
     use crate::http_request_start_finder::HttpRequestDiscriminatorFactory;
     use crate::node_configurator::{
@@ -156,19 +157,25 @@ pub mod standard {
         real_user_data_directory_opt_and_chain_name, request_existing_db_password, DirsWrapper,
     };
     use crate::persistent_configuration::{PersistentConfigError, PersistentConfiguration};
+    use crate::socks_discriminator_factory::SocksDiscriminatorFactory;
     use crate::sub_lib::accountant::DEFAULT_EARNING_WALLET;
     use crate::sub_lib::cryptde::{CryptDE, PlainData, PublicKey};
     use crate::sub_lib::cryptde_null::CryptDENull;
     use crate::sub_lib::cryptde_real::CryptDEReal;
+    use crate::sub_lib::exit_policy::parse_exit_rules;
+    use crate::sub_lib::logger::LogFilters;
     use crate::sub_lib::neighborhood::{
-        NeighborhoodConfig, NeighborhoodMode, NodeDescriptor, DEFAULT_RATE_PACK,
+        NeighborhoodConfig, NeighborhoodMode, NodeDescriptor, RatePack, RouteCostBudget,
+        DEFAULT_RATE_PACK, UNLIMITED_ROUTE_COST_BUDGET,
     };
     use crate::sub_lib::node_addr::NodeAddr;
     use crate::sub_lib::utils::make_new_multi_config;
     use crate::sub_lib::wallet::Wallet;
     use crate::tls_discriminator_factory::TlsDiscriminatorFactory;
     use itertools::Itertools;
-    use masq_lib::constants::{DEFAULT_CHAIN_NAME, DEFAULT_UI_PORT, HTTP_PORT, TLS_PORT};
+    use masq_lib::constants::{
+        DEFAULT_CHAIN_NAME, DEFAULT_UI_PORT, HTTP_PORT, SOCKS_PORT, TLS_PORT,
+    };
     use masq_lib::multi_config::{CommandLineVcl, ConfigFileVcl, EnvironmentVcl, MultiConfig};
     use masq_lib::shared_schema::{ConfiguratorError, ParamError};
     use masq_lib::test_utils::utils::DEFAULT_CHAIN_ID;
@@ -217,6 +224,10 @@ pub mod standard {
                 false,
             ),
         );
+        config.port_configurations.insert(
+            SOCKS_PORT,
+            PortConfiguration::new(vec![Box::new(SocksDiscriminatorFactory::new())], false),
+        );
     }
 
     pub fn privileged_parse_args(
@@ -228,6 +239,27 @@ pub mod standard {
         privileged_config
             .blockchain_bridge_config
             .blockchain_service_url = value_m!(multi_config, "blockchain-service-url", String);
+        if let Some(policy_name) = value_m!(multi_config, "blockchain-service-failover", String) {
+            privileged_config
+                .blockchain_bridge_config
+                .blockchain_service_failover =
+                FailoverPolicy::from_name(&policy_name).expect("Failover policy was not validated");
+        }
+        if let Some(blocks) = value_m!(multi_config, "scan-block-range", u64) {
+            privileged_config.blockchain_bridge_config.scan_block_range = blocks;
+        }
+        if let Some(blocks) = value_m!(multi_config, "confirmation-depth", u64) {
+            privileged_config
+                .blockchain_bridge_config
+                .confirmation_depth = blocks;
+        }
+        if let Some(gas_price) = value_m!(multi_config, "max-gas-price", u64) {
+            privileged_config.blockchain_bridge_config.max_gas_price = gas_price;
+        }
+        privileged_config.blockchain_bridge_config.batch_payments = matches!(
+            value_m!(multi_config, "batch-payments", String),
+            Some(ref value) if value.eq_ignore_ascii_case("on")
+        );
 
         let (real_user, data_directory_opt, chain_name) =
             real_user_data_directory_opt_and_chain_name(dirs_wrapper, &multi_config);
@@ -251,12 +283,36 @@ pub mod standard {
             None => vec![SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)), 53)],
         };
 
+        privileged_config.exit_policy.exit_ports_opt = value_m!(multi_config, "exit-ports", String)
+            .map(|joined_exit_ports| {
+                joined_exit_ports
+                    .split(',')
+                    .map(|port_str| {
+                        port_str
+                            .parse::<u16>()
+                            .expect("Bad clap validation for exit-ports")
+                    })
+                    .collect()
+            });
+        if let Some(exit_policy) = value_m!(multi_config, "exit-policy", String) {
+            privileged_config.exit_policy.rules = parse_exit_rules(&exit_policy)
+                .map_err(|e| ConfiguratorError::required("exit-policy", &e))?;
+        }
+        privileged_config.exit_policy.block_private_ranges = !matches!(
+            value_m!(multi_config, "exit-private-ranges", String),
+            Some(ref value) if value.eq_ignore_ascii_case("allow")
+        );
+
         privileged_config.log_level =
             value_m!(multi_config, "log-level", LevelFilter).unwrap_or(LevelFilter::Warn);
+        privileged_config.log_filters =
+            value_m!(multi_config, "log-filters", LogFilters).unwrap_or_default();
 
         privileged_config.ui_gateway_config.ui_port =
             value_m!(multi_config, "ui-port", u16).unwrap_or(DEFAULT_UI_PORT);
 
+        privileged_config.metrics_port_opt = value_m!(multi_config, "metrics-port", u16);
+
         privileged_config.crash_point =
             value_m!(multi_config, "crash-point", CrashPoint).unwrap_or(CrashPoint::None);
 
@@ -422,7 +478,11 @@ pub mod standard {
             }
         };
         match make_neighborhood_mode(multi_config, neighbor_configs) {
-            Ok(mode) => Ok(NeighborhoodConfig { mode }),
+            Ok(mode) => Ok(NeighborhoodConfig {
+                mode,
+                route_cost_budget: value_m!(multi_config, "route-cost-budget", RouteCostBudget)
+                    .unwrap_or(UNLIMITED_ROUTE_COST_BUDGET),
+            }),
             Err(e) => Err(e),
         }
     }
@@ -534,7 +594,7 @@ pub mod standard {
                 } else {
                     Ok(NeighborhoodMode::OriginateOnly(
                         neighbor_configs,
-                        DEFAULT_RATE_PACK,
+                        rate_pack(multi_config),
                     ))
                 }
             }
@@ -582,10 +642,14 @@ pub mod standard {
         Ok(NeighborhoodMode::Standard(
             NodeAddr::new(&ip, &[]),
             neighbor_configs,
-            DEFAULT_RATE_PACK,
+            rate_pack(multi_config),
         ))
     }
 
+    fn rate_pack(multi_config: &MultiConfig) -> RatePack {
+        value_m!(multi_config, "rate-pack", RatePack).unwrap_or(DEFAULT_RATE_PACK)
+    }
+
     fn get_earning_wallet_from_address(
         multi_config: &MultiConfig,
         persistent_config: &dyn PersistentConfiguration,
@@ -941,6 +1005,7 @@ mod tests {
     use crate::blockchain::blockchain_interface::{
         chain_id_from_name, chain_name_from_id, contract_address,
     };
+    use crate::blockchain::failover_transport::FailoverPolicy;
     use crate::bootstrapper::RealUser;
     use crate::config_dao::{ConfigDao, ConfigDaoReal};
     use crate::database::db_initializer::{DbInitializer, DbInitializerReal};
@@ -950,8 +1015,11 @@ mod tests {
     use crate::sub_lib::cryptde::{CryptDE, PlainData, PublicKey};
     use crate::sub_lib::cryptde_null::CryptDENull;
     use crate::sub_lib::cryptde_real::CryptDEReal;
+    use crate::sub_lib::exit_policy::{parse_exit_rules, ExitPolicy};
+    use crate::sub_lib::logger::LogFilters;
     use crate::sub_lib::neighborhood::{
-        NeighborhoodConfig, NeighborhoodMode, NodeDescriptor, DEFAULT_RATE_PACK,
+        NeighborhoodConfig, NeighborhoodMode, NodeDescriptor, RatePack, RouteCostBudget,
+        DEFAULT_RATE_PACK, UNLIMITED_ROUTE_COST_BUDGET,
     };
     use crate::sub_lib::node_addr::NodeAddr;
     use crate::sub_lib::utils::make_new_test_multi_config;
@@ -959,7 +1027,11 @@ mod tests {
     use crate::test_utils::make_default_persistent_configuration;
     use crate::test_utils::persistent_configuration_mock::PersistentConfigurationMock;
     use crate::test_utils::{assert_string_contains, main_cryptde, ArgsBuilder};
-    use masq_lib::constants::{DEFAULT_CHAIN_NAME, DEFAULT_GAS_PRICE, DEFAULT_UI_PORT};
+    use log::LevelFilter;
+    use masq_lib::constants::{
+        DEFAULT_CHAIN_NAME, DEFAULT_CONFIRMATION_DEPTH, DEFAULT_GAS_PRICE, DEFAULT_MAX_GAS_PRICE,
+        DEFAULT_SCAN_BLOCK_RANGE, DEFAULT_UI_PORT,
+    };
     use masq_lib::multi_config::{
         CommandLineVcl, ConfigFileVcl, MultiConfig, NameValueVclArg, VclArg, VirtualCommandLine,
     };
@@ -1027,11 +1099,135 @@ mod tests {
                         .unwrap()
                     ],
                     DEFAULT_RATE_PACK
-                )
+                ),
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             })
         );
     }
 
+    #[test]
+    fn make_neighborhood_config_standard_uses_configured_rate_pack() {
+        running_test();
+        let multi_config = make_new_test_multi_config(
+            &app(),
+            vec![Box::new(CommandLineVcl::new(
+                ArgsBuilder::new()
+                    .param("--neighborhood-mode", "standard")
+                    .param("--ip", "1.2.3.4")
+                    .param("--rate-pack", "1|2|3|4")
+                    .into(),
+            ))],
+        )
+        .unwrap();
+
+        let result = standard::make_neighborhood_config(
+            &multi_config,
+            &mut FakeStreamHolder::new().streams(),
+            Some(&make_default_persistent_configuration()),
+            &mut BootstrapperConfig::new(),
+        );
+
+        assert_eq!(
+            result.unwrap().mode.rate_pack(),
+            &RatePack {
+                routing_byte_rate: 1,
+                routing_service_rate: 2,
+                exit_byte_rate: 3,
+                exit_service_rate: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn make_neighborhood_config_originate_only_uses_configured_rate_pack() {
+        running_test();
+        let multi_config = make_new_test_multi_config(
+            &app(),
+            vec![Box::new(CommandLineVcl::new(
+                ArgsBuilder::new()
+                    .param("--neighborhood-mode", "originate-only")
+                    .param("--neighbors", "QmlsbA@1.2.3.4:1234;2345")
+                    .param("--rate-pack", "5|6|7|8")
+                    .param("--fake-public-key", "booga")
+                    .into(),
+            ))],
+        )
+        .unwrap();
+
+        let result = standard::make_neighborhood_config(
+            &multi_config,
+            &mut FakeStreamHolder::new().streams(),
+            Some(&make_default_persistent_configuration()),
+            &mut BootstrapperConfig::new(),
+        );
+
+        assert_eq!(
+            result.unwrap().mode.rate_pack(),
+            &RatePack {
+                routing_byte_rate: 5,
+                routing_service_rate: 6,
+                exit_byte_rate: 7,
+                exit_service_rate: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn make_neighborhood_config_uses_configured_route_cost_budget() {
+        running_test();
+        let multi_config = make_new_test_multi_config(
+            &app(),
+            vec![Box::new(CommandLineVcl::new(
+                ArgsBuilder::new()
+                    .param("--neighborhood-mode", "zero-hop")
+                    .param("--route-cost-budget", "1000|100000")
+                    .into(),
+            ))],
+        )
+        .unwrap();
+
+        let result = standard::make_neighborhood_config(
+            &multi_config,
+            &mut FakeStreamHolder::new().streams(),
+            Some(&make_default_persistent_configuration()),
+            &mut BootstrapperConfig::new(),
+        );
+
+        assert_eq!(
+            result.unwrap().route_cost_budget,
+            RouteCostBudget {
+                max_byte_rate: 1000,
+                max_service_rate: 100000,
+            }
+        );
+    }
+
+    #[test]
+    fn make_neighborhood_config_leaves_route_cost_budget_unlimited_when_not_configured() {
+        running_test();
+        let multi_config = make_new_test_multi_config(
+            &app(),
+            vec![Box::new(CommandLineVcl::new(
+                ArgsBuilder::new()
+                    .param("--neighborhood-mode", "zero-hop")
+                    .into(),
+            ))],
+        )
+        .unwrap();
+
+        let result = standard::make_neighborhood_config(
+            &multi_config,
+            &mut FakeStreamHolder::new().streams(),
+            Some(&make_default_persistent_configuration()),
+            &mut BootstrapperConfig::new(),
+        );
+
+        assert_eq!(
+            result.unwrap().route_cost_budget,
+            UNLIMITED_ROUTE_COST_BUDGET
+        );
+    }
+
     #[test]
     fn make_neighborhood_config_standard_missing_ip() {
         running_test();
@@ -1101,7 +1297,8 @@ mod tests {
                         NodeDescriptor::from_str(main_cryptde(), "VGVk@2.3.4.5:3456;4567").unwrap()
                     ],
                     DEFAULT_RATE_PACK
-                )
+                ),
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             })
         );
     }
@@ -1160,7 +1357,8 @@ mod tests {
                 mode: NeighborhoodMode::ConsumeOnly(vec![
                     NodeDescriptor::from_str(main_cryptde(), "QmlsbA@1.2.3.4:1234;2345").unwrap(),
                     NodeDescriptor::from_str(main_cryptde(), "VGVk@2.3.4.5:3456;4567").unwrap()
-                ],)
+                ],),
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             })
         );
     }
@@ -1217,7 +1415,8 @@ mod tests {
         assert_eq!(
             result,
             Ok(NeighborhoodConfig {
-                mode: NeighborhoodMode::ZeroHop
+                mode: NeighborhoodMode::ZeroHop,
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             })
         );
     }
@@ -1406,6 +1605,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_read_exit_policy_from_config_file() {
+        running_test();
+        let _guard = EnvironmentGuard::new();
+        let home_dir = ensure_node_home_directory_exists(
+            "node_configurator",
+            "can_read_exit_policy_from_config_file",
+        );
+        {
+            let mut config_file = File::create(home_dir.join("config.toml")).unwrap();
+            config_file
+                .write_all(
+                    b"exit-policy = \"deny 203.0.113.0/24, deny *:25\"\nexit-ports = \"25,443\"\n",
+                )
+                .unwrap();
+        }
+        let subject = NodeConfiguratorStandardPrivileged::new();
+
+        let configuration = subject
+            .configure(
+                &[
+                    "".to_string(),
+                    "--data-directory".to_string(),
+                    home_dir.to_str().unwrap().to_string(),
+                ],
+                &mut FakeStreamHolder::new().streams(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            configuration.exit_policy,
+            ExitPolicy {
+                rules: parse_exit_rules("deny 203.0.113.0/24,deny *:25").unwrap(),
+                exit_ports_opt: Some(vec![25, 443]),
+                block_private_ranges: true,
+            }
+        );
+    }
+
+    #[test]
+    fn privileged_parse_args_rejects_malformed_exit_policy() {
+        running_test();
+        let args = ArgsBuilder::new().param("--exit-policy", "deny *:25,permit *");
+        let mut config = BootstrapperConfig::new();
+        let vcls: Vec<Box<dyn VirtualCommandLine>> =
+            vec![Box::new(CommandLineVcl::new(args.into()))];
+        let multi_config = make_new_test_multi_config(&app(), vcls).unwrap();
+
+        let result = standard::privileged_parse_args(
+            &RealDirsWrapper {},
+            &multi_config,
+            &mut config,
+            &mut FakeStreamHolder::new().streams(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ConfiguratorError::required(
+                "exit-policy",
+                "'permit *' must begin with 'allow' or 'deny'"
+            ))
+        );
+    }
+
     #[test]
     fn can_read_dns_servers_and_consuming_private_key_from_config_file() {
         running_test();
@@ -1487,6 +1750,9 @@ mod tests {
         let args = ArgsBuilder::new()
             .param("--config-file", "specified_config.toml")
             .param("--dns-servers", "12.34.56.78,23.45.67.89")
+            .param("--exit-ports", "443,993")
+            .param("--exit-policy", "deny *:25,allow *.example.com")
+            .param("--exit-private-ranges", "allow")
             .param(
                 "--neighbors",
                 "QmlsbA:1.2.3.4:1234;2345,VGVk:2.3.4.5:3456;4567",
@@ -1494,9 +1760,16 @@ mod tests {
             .param("--ip", "34.56.78.90")
             .param("--clandestine-port", "1234")
             .param("--ui-port", "5335")
+            .param("--metrics-port", "5336")
             .param("--data-directory", home_dir.to_str().unwrap())
+            .param("--blockchain-service-failover", "round-robin")
+            .param("--scan-block-range", "2000")
+            .param("--confirmation-depth", "30")
+            .param("--max-gas-price", "150")
+            .param("--batch-payments", "on")
             .param("--blockchain-service-url", "http://127.0.0.1:8545")
             .param("--log-level", "trace")
+            .param("--log-filters", "Neighborhood=debug,Hopper=off")
             .param("--fake-public-key", "AQIDBA")
             .param("--db-password", "secret-db-password")
             .param(
@@ -1532,17 +1805,44 @@ mod tests {
                 SocketAddr::from_str("23.45.67.89:53").unwrap()
             ),
         );
+        assert_eq!(
+            config.exit_policy,
+            ExitPolicy {
+                rules: parse_exit_rules("deny *:25,allow *.example.com").unwrap(),
+                exit_ports_opt: Some(vec![443, 993]),
+                block_private_ranges: false,
+            }
+        );
         assert_eq!(config.ui_gateway_config.ui_port, 5335);
+        assert_eq!(config.metrics_port_opt, Some(5336));
+        assert_eq!(
+            config.log_filters,
+            LogFilters {
+                filters: vec![
+                    ("Neighborhood".to_string(), LevelFilter::Debug),
+                    ("Hopper".to_string(), LevelFilter::Off),
+                ]
+            }
+        );
         assert_eq!(
             config.neighborhood_config,
             NeighborhoodConfig {
-                mode: NeighborhoodMode::ZeroHop // not populated on the privileged side
+                mode: NeighborhoodMode::ZeroHop, // not populated on the privileged side
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             }
         );
         assert_eq!(
             config.blockchain_bridge_config.blockchain_service_url,
             Some("http://127.0.0.1:8545".to_string()),
         );
+        assert_eq!(
+            config.blockchain_bridge_config.blockchain_service_failover,
+            FailoverPolicy::RoundRobin,
+        );
+        assert_eq!(config.blockchain_bridge_config.scan_block_range, 2000);
+        assert_eq!(config.blockchain_bridge_config.confirmation_depth, 30);
+        assert_eq!(config.blockchain_bridge_config.max_gas_price, 150);
+        assert!(config.blockchain_bridge_config.batch_payments);
         assert_eq!(config.data_directory, home_dir);
         assert_eq!(
             config.main_cryptde_null_opt.unwrap().public_key(),
@@ -1636,7 +1936,8 @@ mod tests {
                         NodeDescriptor::from_str(main_cryptde(), "VGVk@2.3.4.5:3456;4567").unwrap(),
                     ],
                     DEFAULT_RATE_PACK.clone()
-                )
+                ),
+                route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
             }
         );
     }
@@ -1749,8 +2050,24 @@ mod tests {
             config.dns_servers,
             vec!(SocketAddr::from_str("1.1.1.1:53").unwrap())
         );
+        assert_eq!(config.exit_policy, ExitPolicy::default());
         assert_eq!(config.crash_point, CrashPoint::None);
         assert_eq!(config.ui_gateway_config.ui_port, DEFAULT_UI_PORT);
+        assert_eq!(config.metrics_port_opt, None);
+        assert_eq!(config.log_filters, LogFilters::default());
+        assert_eq!(
+            config.blockchain_bridge_config.scan_block_range,
+            DEFAULT_SCAN_BLOCK_RANGE
+        );
+        assert_eq!(
+            config.blockchain_bridge_config.confirmation_depth,
+            DEFAULT_CONFIRMATION_DEPTH
+        );
+        assert_eq!(
+            config.blockchain_bridge_config.max_gas_price,
+            DEFAULT_MAX_GAS_PRICE
+        );
+        assert!(!config.blockchain_bridge_config.batch_payments);
         assert!(config.main_cryptde_null_opt.is_none());
         assert_eq!(
             config.real_user,
@@ -2438,7 +2755,7 @@ mod tests {
     }
 
     #[test]
-    fn privileged_configuration_accepts_network_chain_selection_for_ropsten() {
+    fn privileged_configuration_accepts_network_chain_selection_for_the_test_chain() {
         running_test();
         let subject = NodeConfiguratorStandardPrivileged::new();
         let args = ArgsBuilder::new()
@@ -2475,12 +2792,12 @@ mod tests {
     }
 
     #[test]
-    fn privileged_configuration_accepts_ropsten_network_chain_selection() {
+    fn privileged_configuration_accepts_mainnet_network_chain_selection() {
         running_test();
         let subject = NodeConfiguratorStandardPrivileged::new();
         let args = ArgsBuilder::new()
             .param("--ip", "1.2.3.4")
-            .param("--chain", TEST_DEFAULT_CHAIN_NAME);
+            .param("--chain", "mainnet");
         let args_vec: Vec<String> = args.into();
 
         let bootstrapper_config = subject
@@ -2488,7 +2805,7 @@ mod tests {
             .unwrap();
         assert_eq!(
             bootstrapper_config.blockchain_bridge_config.chain_id,
-            chain_id_from_name(TEST_DEFAULT_CHAIN_NAME)
+            chain_id_from_name("mainnet")
         );
     }
 
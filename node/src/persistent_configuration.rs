@@ -4,7 +4,7 @@ use crate::blockchain::bip39::{Bip39, Bip39Error};
 use crate::config_dao::ConfigDaoError;
 use crate::config_dao::{ConfigDao, ConfigDaoReal};
 use crate::database::db_initializer::ConnectionWrapper;
-use crate::sub_lib::cryptde::PlainData;
+use crate::sub_lib::cryptde::{PlainData, PrivateKey};
 use crate::sub_lib::neighborhood::NodeDescriptor;
 use crate::sub_lib::wallet::Wallet;
 use masq_lib::constants::{HIGHEST_USABLE_PORT, LOWEST_USABLE_INSECURE_PORT};
@@ -25,6 +25,11 @@ pub trait PersistentConfiguration: Send {
     fn current_schema_version(&self) -> String;
     fn set_password(&self, db_password: &str);
     fn check_password(&self, db_password: &str) -> Option<bool>;
+    fn change_password(
+        &self,
+        old_password_opt: Option<&str>,
+        new_password: &str,
+    ) -> Result<(), PersistentConfigError>;
     fn clandestine_port(&self) -> u16;
     fn set_clandestine_port(&self, port: u16);
     fn gas_price(&self) -> u64;
@@ -51,6 +56,15 @@ pub trait PersistentConfiguration: Send {
         node_descriptors_opt: Option<Vec<NodeDescriptor>>,
         db_password: &str,
     ) -> Result<(), PersistentConfigError>;
+    fn node_private_key(
+        &self,
+        db_password: &str,
+    ) -> Result<Option<PrivateKey>, PersistentConfigError>;
+    fn set_node_private_key(
+        &self,
+        private_key: &PrivateKey,
+        db_password: &str,
+    ) -> Result<(), PersistentConfigError>;
     fn start_block(&self) -> u64;
     fn set_start_block_transactionally(&self, tx: &Transaction, value: u64) -> Result<(), String>;
 }
@@ -97,6 +111,21 @@ impl PersistentConfiguration for PersistentConfigurationReal {
         }
     }
 
+    fn change_password(
+        &self,
+        old_password_opt: Option<&str>,
+        new_password: &str,
+    ) -> Result<(), PersistentConfigError> {
+        match self.dao.change_password(old_password_opt, new_password) {
+            Ok(_) => Ok(()),
+            Err(ConfigDaoError::PasswordError) => Err(PersistentConfigError::PasswordError),
+            Err(e) => Err(PersistentConfigError::DatabaseError(format!(
+                "Can't change database password: {:?}",
+                e
+            ))),
+        }
+    }
+
     fn clandestine_port(&self) -> u16 {
         let unchecked_port = match self.dao.get_u64("clandestine_port") {
             Ok(n) => n,
@@ -370,6 +399,40 @@ impl PersistentConfiguration for PersistentConfigurationReal {
         }
     }
 
+    fn node_private_key(
+        &self,
+        db_password: &str,
+    ) -> Result<Option<PrivateKey>, PersistentConfigError> {
+        match self.dao.get_bytes_e("node_private_key", db_password) {
+            Ok(plain_data) => Ok(Some(PrivateKey::new(plain_data.as_slice()))),
+            Err(ConfigDaoError::NotPresent) => Ok(None),
+            Err(ConfigDaoError::PasswordError) => Err(PersistentConfigError::PasswordError),
+            Err(e) => Err(PersistentConfigError::DatabaseError(format!(
+                "Can't continue; node private key configuration is inaccessible: {:?}",
+                e
+            ))),
+        }
+    }
+
+    fn set_node_private_key(
+        &self,
+        private_key: &PrivateKey,
+        db_password: &str,
+    ) -> Result<(), PersistentConfigError> {
+        match self.dao.set_bytes_e(
+            "node_private_key",
+            &PlainData::new(private_key.as_slice()),
+            db_password,
+        ) {
+            Ok(_) => Ok(()),
+            Err(ConfigDaoError::PasswordError) => Err(PersistentConfigError::PasswordError),
+            Err(e) => Err(PersistentConfigError::DatabaseError(format!(
+                "Can't continue; node private key configuration is inaccessible: {:?}",
+                e
+            ))),
+        }
+    }
+
     fn start_block(&self) -> u64 {
         self.dao.get_u64("start_block").unwrap_or_else(|e| {
             panic!(
@@ -559,6 +622,51 @@ mod tests {
         assert_eq!(1, get_string_params.len());
     }
 
+    #[test]
+    fn change_password_passes_passwords_to_dao() {
+        let change_password_params_arc = Arc::new(Mutex::new(vec![]));
+        let config_dao = ConfigDaoMock::new()
+            .change_password_params(&change_password_params_arc)
+            .change_password_result(Ok(()));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.change_password(Some("old password"), "new password");
+
+        assert_eq!(result, Ok(()));
+        let change_password_params = change_password_params_arc.lock().unwrap();
+        assert_eq!(
+            *change_password_params,
+            vec![(Some("old password".to_string()), "new password".to_string())]
+        );
+    }
+
+    #[test]
+    fn change_password_reports_bad_old_password() {
+        let config_dao =
+            ConfigDaoMock::new().change_password_result(Err(ConfigDaoError::PasswordError));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.change_password(Some("wrong password"), "new password");
+
+        assert_eq!(result, Err(PersistentConfigError::PasswordError));
+    }
+
+    #[test]
+    fn change_password_reports_database_trouble() {
+        let config_dao = ConfigDaoMock::new()
+            .change_password_result(Err(ConfigDaoError::DatabaseError("booga".to_string())));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.change_password(Some("old password"), "new password");
+
+        assert_eq!(
+            result,
+            Err(PersistentConfigError::DatabaseError(
+                "Can't change database password: DatabaseError(\"booga\")".to_string()
+            ))
+        );
+    }
+
     #[test]
     #[should_panic(expected = "Can't continue; example_encrypted could not be read")]
     fn check_password_panics_if_get_string_fails() {
@@ -986,6 +1094,112 @@ mod tests {
         assert_eq!(set_bytes_e_params.len(), 1);
     }
 
+    #[test]
+    fn node_private_key_reports_absence() {
+        let config_dao = ConfigDaoMock::new().get_bytes_e_result(Err(ConfigDaoError::NotPresent));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.node_private_key("password");
+
+        assert_eq!(result, Ok(None));
+    }
+
+    #[test]
+    fn node_private_key_reports_password_error() {
+        let config_dao =
+            ConfigDaoMock::new().get_bytes_e_result(Err(ConfigDaoError::PasswordError));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.node_private_key("password");
+
+        assert_eq!(result, Err(PersistentConfigError::PasswordError));
+    }
+
+    #[test]
+    fn node_private_key_reports_dao_error() {
+        let config_dao = ConfigDaoMock::new().get_bytes_e_result(Err(ConfigDaoError::TypeError));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.node_private_key("password");
+
+        assert_eq!(
+            result,
+            Err(PersistentConfigError::DatabaseError(
+                "Can't continue; node private key configuration is inaccessible: TypeError"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn node_private_key_success() {
+        let get_bytes_e_params_arc = Arc::new(Mutex::new(vec![]));
+        let config_dao = ConfigDaoMock::new()
+            .get_bytes_e_params(&get_bytes_e_params_arc)
+            .get_bytes_e_result(Ok(PlainData::new(b"private key")));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.node_private_key("password");
+
+        assert_eq!(result, Ok(Some(PrivateKey::new(b"private key"))));
+        let get_bytes_e_params = get_bytes_e_params_arc.lock().unwrap();
+        assert_eq!(
+            *get_bytes_e_params,
+            vec![("node_private_key".to_string(), "password".to_string())]
+        );
+    }
+
+    #[test]
+    fn set_node_private_key_reports_password_error() {
+        let config_dao =
+            ConfigDaoMock::new().set_bytes_e_result(Err(ConfigDaoError::PasswordError));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.set_node_private_key(&PrivateKey::new(b"private key"), "password");
+
+        assert_eq!(result, Err(PersistentConfigError::PasswordError));
+    }
+
+    #[test]
+    fn set_node_private_key_reports_dao_error() {
+        let config_dao = ConfigDaoMock::new().set_bytes_e_result(Err(ConfigDaoError::TypeError));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.set_node_private_key(&PrivateKey::new(b"private key"), "password");
+
+        assert_eq!(
+            result,
+            Err(PersistentConfigError::DatabaseError(
+                "Can't continue; node private key configuration is inaccessible: TypeError"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn node_private_key_survives_a_round_trip_through_the_database() {
+        let home_dir = ensure_node_home_directory_exists(
+            "persistent_configuration",
+            "node_private_key_survives_a_round_trip_through_the_database",
+        );
+        let conn = DbInitializerReal::new()
+            .initialize(&home_dir, DEFAULT_CHAIN_ID, true)
+            .unwrap();
+        let subject = PersistentConfigurationReal::from(conn);
+        let private_key = PrivateKey::new(&[7u8; 96]);
+
+        let before = subject.node_private_key("password");
+        subject
+            .set_node_private_key(&private_key, "password")
+            .unwrap();
+        let after = subject.node_private_key("password");
+        let with_bad_password = subject.node_private_key("bad password");
+
+        assert_eq!(before, Ok(None));
+        assert_eq!(after, Ok(Some(private_key)));
+        assert_eq!(with_bad_password, Err(PersistentConfigError::PasswordError));
+    }
+
     #[test]
     fn set_start_block_transactionally_returns_err_when_transaction_fails() {
         let config_dao = ConfigDaoMock::new()
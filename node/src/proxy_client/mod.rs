@@ -8,6 +8,7 @@ mod stream_handler_pool;
 mod stream_reader;
 mod stream_writer;
 
+use crate::metrics::{metrics, DNS_FAILURES};
 use crate::proxy_client::resolver_wrapper::ResolverWrapperFactory;
 use crate::proxy_client::resolver_wrapper::ResolverWrapperFactoryReal;
 use crate::proxy_client::stream_handler_pool::StreamHandlerPool;
@@ -16,29 +17,38 @@ use crate::proxy_client::stream_handler_pool::StreamHandlerPoolFactoryReal;
 use crate::sub_lib::accountant::ReportExitServiceProvidedMessage;
 use crate::sub_lib::cryptde::CryptDE;
 use crate::sub_lib::cryptde::PublicKey;
+use crate::sub_lib::exit_policy::ExitPolicy;
 use crate::sub_lib::hopper::MessageType;
 use crate::sub_lib::hopper::{ExpiredCoresPackage, IncipientCoresPackage};
 use crate::sub_lib::logger::Logger;
+use crate::sub_lib::neighborhood::RatePackChangeMessage;
 use crate::sub_lib::peer_actors::BindMessage;
 use crate::sub_lib::proxy_client::InboundServerData;
 use crate::sub_lib::proxy_client::ProxyClientConfig;
 use crate::sub_lib::proxy_client::ProxyClientSubs;
-use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
-use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
+use crate::sub_lib::proxy_client::{
+    ClientResponsePayload_0v1, DatagramResponsePayload_0v1, DnsResolveFailure_0v1,
+    ExitPolicyViolation_0v1, UDP_ASSOCIATION_IDLE_TTL,
+};
+use crate::sub_lib::proxy_server::{ClientRequestPayload_0v1, DatagramRequestPayload_0v1};
 use crate::sub_lib::route::Route;
 use crate::sub_lib::sequence_buffer::SequencedPacket;
 use crate::sub_lib::stream_key::StreamKey;
+use crate::sub_lib::ttl_hashmap::TtlHashMap;
+use crate::sub_lib::udp_relay::InboundDatagram;
 use crate::sub_lib::utils::NODE_MAILBOX_CAPACITY;
 use crate::sub_lib::versioned_data::VersionedData;
 use crate::sub_lib::wallet::Wallet;
 use actix::Actor;
 use actix::Addr;
+use actix::AsyncContext;
 use actix::Context;
 use actix::Handler;
 use actix::Recipient;
 use pretty_hex::PrettyHex;
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::time::Duration;
 use trust_dns_resolver::config::NameServerConfig;
 use trust_dns_resolver::config::Protocol;
 use trust_dns_resolver::config::ResolverConfig;
@@ -55,8 +65,11 @@ pub struct ProxyClient {
     to_accountant: Option<Recipient<ReportExitServiceProvidedMessage>>,
     pool: Option<Box<dyn StreamHandlerPool>>,
     stream_contexts: HashMap<StreamKey, StreamContext>,
+    association_contexts: TtlHashMap<StreamKey, StreamContext>,
+    udp_association_sweep_interval: Duration,
     exit_service_rate: u64,
     exit_byte_rate: u64,
+    exit_policy: ExitPolicy,
     logger: Logger,
 }
 
@@ -90,7 +103,27 @@ impl Handler<BindMessage> for ProxyClient {
             msg.peer_actors.proxy_client,
             self.exit_service_rate,
             self.exit_byte_rate,
+            self.exit_policy.clone(),
         ));
+        ctx.run_interval(self.udp_association_sweep_interval, |proxy_client, _ctx| {
+            proxy_client.remove_expired_udp_associations()
+        });
+    }
+}
+
+impl Handler<RatePackChangeMessage> for ProxyClient {
+    type Result = ();
+
+    fn handle(&mut self, msg: RatePackChangeMessage, _ctx: &mut Self::Context) -> Self::Result {
+        self.exit_service_rate = msg.rate_pack.exit_service_rate;
+        self.exit_byte_rate = msg.rate_pack.exit_byte_rate;
+        if let Some(pool) = self.pool.as_ref() {
+            pool.set_exit_rates(self.exit_service_rate, self.exit_byte_rate);
+        }
+        debug!(
+            self.logger,
+            "Now charging {}+{}b for exit services", self.exit_service_rate, self.exit_byte_rate
+        );
     }
 }
 
@@ -102,10 +135,7 @@ impl Handler<ExpiredCoresPackage<ClientRequestPayload_0v1>> for ProxyClient {
         msg: ExpiredCoresPackage<ClientRequestPayload_0v1>,
         _ctx: &mut Self::Context,
     ) -> Self::Result {
-        let is_zero_hop = match msg.remaining_route.next_hop(self.cryptde) {
-            Ok(live_hop) => &live_hop.public_key == self.cryptde.public_key(),
-            Err(_) => false,
-        };
+        let is_zero_hop = self.is_zero_hop(&msg.remaining_route);
         let payload = msg.payload;
         let paying_wallet = msg.paying_wallet;
         if paying_wallet.is_some() || is_zero_hop {
@@ -171,39 +201,126 @@ impl Handler<InboundServerData> for ProxyClient {
     }
 }
 
-impl Handler<DnsResolveFailure_0v1> for ProxyClient {
+impl Handler<ExpiredCoresPackage<DatagramRequestPayload_0v1>> for ProxyClient {
     type Result = ();
 
-    fn handle(&mut self, msg: DnsResolveFailure_0v1, _ctx: &mut Self::Context) -> Self::Result {
-        let stream_key = msg.stream_key;
-        let stream_context_opt = self.stream_contexts.get(&stream_key);
-        match stream_context_opt {
-            Some(stream_context) => {
-                let package = IncipientCoresPackage::new(
-                    self.cryptde,
-                    stream_context.return_route.clone(),
-                    MessageType::DnsResolveFailed(VersionedData::new(
-                        &crate::sub_lib::migrations::dns_resolve_failure::MIGRATIONS,
-                        &msg,
-                    )),
-                    &stream_context.payload_destination_key,
-                )
-                .expect("Failed to create IncipientCoresPackage");
-                self.to_hopper
-                    .as_ref()
-                    .expect("Hopper is unbound")
-                    .try_send(package)
-                    .expect("Hopper is dead");
+    fn handle(
+        &mut self,
+        msg: ExpiredCoresPackage<DatagramRequestPayload_0v1>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let is_zero_hop = self.is_zero_hop(&msg.remaining_route);
+        let payload = msg.payload;
+        let paying_wallet = msg.paying_wallet;
+        if paying_wallet.is_some() || is_zero_hop {
+            debug!(
+                self.logger,
+                "Received DatagramRequestPayload: association {}, length {}",
+                payload.association_key,
+                payload.data.len()
+            );
+            self.association_contexts.insert(
+                payload.association_key,
+                StreamContext {
+                    return_route: msg.remaining_route,
+                    payload_destination_key: payload.originator_public_key.clone(),
+                    paying_wallet: paying_wallet.clone(),
+                },
+            );
+            self.pool
+                .as_mut()
+                .expect("StreamHandlerPool unbound")
+                .process_datagram(payload, paying_wallet);
+        } else {
+            warning!(
+                self.logger,
+                "Refusing to provide exit services for {}-byte datagram without paying wallet",
+                payload.data.len()
+            );
+        }
+    }
+}
+
+impl Handler<InboundDatagram> for ProxyClient {
+    type Result = ();
+
+    fn handle(&mut self, msg: InboundDatagram, _ctx: &mut Self::Context) -> Self::Result {
+        let msg_data_len = msg.data.len();
+        let msg_source = msg.source;
+        let stream_context = match self.association_contexts.get(&msg.association_key) {
+            Some(sc) => sc,
+            None => {
                 debug!(
                     self.logger,
-                    "Removing stream key {} for DnsResolveFailure", stream_key
+                    "Received {}-byte datagram from {} for expired association {} - ignoring",
+                    msg_data_len,
+                    msg_source,
+                    msg.association_key
                 );
-                self.stream_contexts.remove(&stream_key);
+                return;
             }
-            None => error!(
+        };
+        self.pool
+            .as_ref()
+            .expect("StreamHandlerPool unbound")
+            .refresh_udp_association(msg.association_key);
+        let payload = DatagramResponsePayload_0v1 {
+            association_key: msg.association_key,
+            source: msg.source,
+            data: msg.data,
+        };
+        let icp = match IncipientCoresPackage::new(
+            self.cryptde,
+            stream_context.return_route.clone(),
+            payload.into(),
+            &stream_context.payload_destination_key,
+        ) {
+            Ok(icp) => icp,
+            Err(err) => {
+                error!(
+                    self.logger,
+                    "Could not create CORES package for {}-byte datagram from {}: {} - ignoring",
+                    msg_data_len,
+                    msg_source,
+                    err
+                );
+                return;
+            }
+        };
+        self.to_hopper
+            .as_ref()
+            .expect("Hopper unbound")
+            .try_send(icp)
+            .expect("Hopper is dead");
+        self.report_response_exit_to_accountant(&stream_context, msg_data_len);
+    }
+}
+
+impl Handler<DnsResolveFailure_0v1> for ProxyClient {
+    type Result = ();
+
+    fn handle(&mut self, msg: DnsResolveFailure_0v1, _ctx: &mut Self::Context) -> Self::Result {
+        let stream_key = msg.stream_key;
+        metrics().increment(&DNS_FAILURES, &[], 1);
+        if !self.report_failure_to_originator(stream_key, msg.into(), "DnsResolveFailure") {
+            error!(
                 self.logger,
-                "DNS resolution for nonexistent stream ({:?}) failed.", msg.stream_key
-            ),
+                "DNS resolution for nonexistent stream ({:?}) failed.", stream_key
+            )
+        }
+    }
+}
+
+impl Handler<ExitPolicyViolation_0v1> for ProxyClient {
+    type Result = ();
+
+    fn handle(&mut self, msg: ExitPolicyViolation_0v1, _ctx: &mut Self::Context) -> Self::Result {
+        let stream_key = msg.stream_key;
+        if !self.report_failure_to_originator(stream_key, msg.into(), "ExitPolicyViolation") {
+            error!(
+                self.logger,
+                "Exit policy violated by nonexistent stream ({:?}).", stream_key
+            )
         }
     }
 }
@@ -222,8 +339,11 @@ impl ProxyClient {
             to_accountant: None,
             pool: None,
             stream_contexts: HashMap::new(),
+            association_contexts: TtlHashMap::new(UDP_ASSOCIATION_IDLE_TTL),
+            udp_association_sweep_interval: UDP_ASSOCIATION_IDLE_TTL,
             exit_service_rate: config.exit_service_rate,
             exit_byte_rate: config.exit_byte_rate,
+            exit_policy: config.exit_policy,
             logger: Logger::new("ProxyClient"),
         }
     }
@@ -236,9 +356,61 @@ impl ProxyClient {
                 .recipient::<ExpiredCoresPackage<ClientRequestPayload_0v1>>(),
             inbound_server_data: addr.clone().recipient::<InboundServerData>(),
             dns_resolve_failed: addr.clone().recipient::<DnsResolveFailure_0v1>(),
+            exit_policy_violated: addr.clone().recipient::<ExitPolicyViolation_0v1>(),
+            datagram_from_hopper: addr
+                .clone()
+                .recipient::<ExpiredCoresPackage<DatagramRequestPayload_0v1>>(),
+            inbound_datagram: addr.clone().recipient::<InboundDatagram>(),
+            rate_pack_change: addr.clone().recipient::<RatePackChangeMessage>(),
+        }
+    }
+
+    // Quiet associations would otherwise keep their exit sockets open until the next datagram
+    fn remove_expired_udp_associations(&self) {
+        self.association_contexts.remove_expired_entries();
+        self.pool
+            .as_ref()
+            .expect("StreamHandlerPool unbound")
+            .remove_expired_udp_associations();
+    }
+
+    fn is_zero_hop(&self, remaining_route: &Route) -> bool {
+        match remaining_route.next_hop(self.cryptde) {
+            Ok(live_hop) => &live_hop.public_key == self.cryptde.public_key(),
+            Err(_) => false,
         }
     }
 
+    fn report_failure_to_originator(
+        &mut self,
+        stream_key: StreamKey,
+        payload: MessageType,
+        failure_name: &str,
+    ) -> bool {
+        let stream_context = match self.stream_contexts.get(&stream_key) {
+            Some(stream_context) => stream_context,
+            None => return false,
+        };
+        let package = IncipientCoresPackage::new(
+            self.cryptde,
+            stream_context.return_route.clone(),
+            payload,
+            &stream_context.payload_destination_key,
+        )
+        .expect("Failed to create IncipientCoresPackage");
+        self.to_hopper
+            .as_ref()
+            .expect("Hopper is unbound")
+            .try_send(package)
+            .expect("Hopper is dead");
+        debug!(
+            self.logger,
+            "Removing stream key {} for {}", stream_key, failure_name
+        );
+        self.stream_contexts.remove(&stream_key);
+        true
+    }
+
     fn send_response_to_hopper(
         &self,
         msg: InboundServerData,
@@ -312,6 +484,7 @@ impl ProxyClient {
     }
 }
 
+#[derive(PartialEq, Eq)]
 struct StreamContext {
     return_route: Route,
     payload_destination_key: PublicKey,
@@ -321,7 +494,7 @@ struct StreamContext {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::blockchain::blockchain_interface::ROPSTEN_TESTNET_CONTRACT_ADDRESS;
+    use crate::blockchain::blockchain_interface::contract_address;
     use crate::proxy_client::local_test_utils::ResolverWrapperFactoryMock;
     use crate::proxy_client::local_test_utils::ResolverWrapperMock;
     use crate::proxy_client::resolver_wrapper::ResolverWrapper;
@@ -346,6 +519,7 @@ mod tests {
     use crate::test_utils::recorder::Recorder;
     use crate::test_utils::*;
     use actix::System;
+    use masq_lib::test_utils::utils::DEFAULT_CHAIN_ID;
     use std::cell::RefCell;
     use std::net::IpAddr;
     use std::net::SocketAddr;
@@ -353,6 +527,7 @@ mod tests {
     use std::sync::Arc;
     use std::sync::Mutex;
     use std::thread;
+    use std::time::Instant;
 
     fn dnss() -> Vec<SocketAddr> {
         vec![SocketAddr::from_str("8.8.8.8:53").unwrap()]
@@ -360,6 +535,10 @@ mod tests {
 
     pub struct StreamHandlerPoolMock {
         process_package_parameters: Arc<Mutex<Vec<(ClientRequestPayload_0v1, Option<Wallet>)>>>,
+        process_datagram_parameters: Arc<Mutex<Vec<(DatagramRequestPayload_0v1, Option<Wallet>)>>>,
+        set_exit_rates_parameters: Arc<Mutex<Vec<(u64, u64)>>>,
+        refresh_udp_association_parameters: Arc<Mutex<Vec<StreamKey>>>,
+        remove_expired_udp_associations_parameters: Arc<Mutex<Vec<()>>>,
     }
 
     impl StreamHandlerPool for StreamHandlerPoolMock {
@@ -373,15 +552,59 @@ mod tests {
                 .unwrap()
                 .push((payload, paying_wallet));
         }
+
+        fn process_datagram(
+            &mut self,
+            payload: DatagramRequestPayload_0v1,
+            paying_wallet: Option<Wallet>,
+        ) {
+            self.process_datagram_parameters
+                .lock()
+                .unwrap()
+                .push((payload, paying_wallet));
+        }
+
+        fn set_exit_rates(&self, exit_service_rate: u64, exit_byte_rate: u64) {
+            self.set_exit_rates_parameters
+                .lock()
+                .unwrap()
+                .push((exit_service_rate, exit_byte_rate));
+        }
+
+        fn refresh_udp_association(&self, association_key: StreamKey) {
+            self.refresh_udp_association_parameters
+                .lock()
+                .unwrap()
+                .push(association_key);
+        }
+
+        fn remove_expired_udp_associations(&self) {
+            self.remove_expired_udp_associations_parameters
+                .lock()
+                .unwrap()
+                .push(());
+        }
     }
 
     impl StreamHandlerPoolMock {
         pub fn new() -> StreamHandlerPoolMock {
             StreamHandlerPoolMock {
                 process_package_parameters: Arc::new(Mutex::new(vec![])),
+                process_datagram_parameters: Arc::new(Mutex::new(vec![])),
+                set_exit_rates_parameters: Arc::new(Mutex::new(vec![])),
+                refresh_udp_association_parameters: Arc::new(Mutex::new(vec![])),
+                remove_expired_udp_associations_parameters: Arc::new(Mutex::new(vec![])),
             }
         }
 
+        pub fn set_exit_rates_parameters(
+            self,
+            parameters: &mut Arc<Mutex<Vec<(u64, u64)>>>,
+        ) -> StreamHandlerPoolMock {
+            *parameters = self.set_exit_rates_parameters.clone();
+            self
+        }
+
         pub fn process_package_parameters(
             self,
             parameters: &mut Arc<Mutex<Vec<(ClientRequestPayload_0v1, Option<Wallet>)>>>,
@@ -389,6 +612,30 @@ mod tests {
             *parameters = self.process_package_parameters.clone();
             self
         }
+
+        pub fn process_datagram_parameters(
+            self,
+            parameters: &mut Arc<Mutex<Vec<(DatagramRequestPayload_0v1, Option<Wallet>)>>>,
+        ) -> StreamHandlerPoolMock {
+            *parameters = self.process_datagram_parameters.clone();
+            self
+        }
+
+        pub fn refresh_udp_association_parameters(
+            self,
+            parameters: &mut Arc<Mutex<Vec<StreamKey>>>,
+        ) -> StreamHandlerPoolMock {
+            *parameters = self.refresh_udp_association_parameters.clone();
+            self
+        }
+
+        pub fn remove_expired_udp_associations_parameters(
+            self,
+            parameters: &mut Arc<Mutex<Vec<()>>>,
+        ) -> StreamHandlerPoolMock {
+            *parameters = self.remove_expired_udp_associations_parameters.clone();
+            self
+        }
     }
 
     pub struct StreamHandlerPoolFactoryMock {
@@ -401,6 +648,7 @@ mod tests {
                     ProxyClientSubs,
                     u64,
                     u64,
+                    ExitPolicy,
                 )>,
             >,
         >,
@@ -416,6 +664,7 @@ mod tests {
             proxy_client_subs: ProxyClientSubs,
             exit_service_rate: u64,
             exit_byte_rate: u64,
+            exit_policy: ExitPolicy,
         ) -> Box<dyn StreamHandlerPool> {
             self.make_parameters.lock().unwrap().push((
                 resolver,
@@ -424,6 +673,7 @@ mod tests {
                 proxy_client_subs,
                 exit_service_rate,
                 exit_byte_rate,
+                exit_policy,
             ));
             self.make_results.borrow_mut().remove(0)
         }
@@ -448,6 +698,7 @@ mod tests {
                         ProxyClientSubs,
                         u64,
                         u64,
+                        ExitPolicy,
                     )>,
                 >,
             >,
@@ -475,6 +726,7 @@ mod tests {
             dns_servers: vec![],
             exit_service_rate: 100,
             exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
         });
     }
 
@@ -494,6 +746,10 @@ mod tests {
             .make_parameters(&mut pool_factory_make_parameters)
             .make_result(Box::new(pool));
         let peer_actors = peer_actors_builder().build();
+        let exit_policy = ExitPolicy {
+            exit_ports_opt: Some(vec![443, 993]),
+            ..ExitPolicy::default()
+        };
         let mut subject = ProxyClient::new(ProxyClientConfig {
             cryptde: main_cryptde(),
             dns_servers: vec![
@@ -502,6 +758,7 @@ mod tests {
             ],
             exit_service_rate: 100,
             exit_byte_rate: 200,
+            exit_policy: exit_policy.clone(),
         });
         subject.resolver_wrapper_factory = Box::new(resolver_wrapper_factory);
         subject.stream_handler_pool_factory = Box::new(pool_factory);
@@ -534,6 +791,10 @@ mod tests {
         );
         assert_eq!(opts, ResolverOpts::default());
         assert_eq!(resolver_wrapper_new_parameters.is_empty(), true);
+        let pool_factory_make_parameters = pool_factory_make_parameters.lock().unwrap();
+        assert_eq!(pool_factory_make_parameters[0].4, 100);
+        assert_eq!(pool_factory_make_parameters[0].5, 200);
+        assert_eq!(pool_factory_make_parameters[0].6, exit_policy);
     }
 
     #[test]
@@ -565,6 +826,7 @@ mod tests {
             dns_servers: dnss(),
             exit_service_rate: 100,
             exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
         });
         let subject_addr: Addr<ProxyClient> = subject.start();
 
@@ -587,6 +849,7 @@ mod tests {
                 dns_servers: vec![SocketAddr::from_str("1.1.1.1:53").unwrap()],
                 exit_service_rate: 0,
                 exit_byte_rate: 0,
+                exit_policy: ExitPolicy::default(),
             });
             let subject_addr = subject.start();
             let subject_subs = ProxyClient::make_subs_from(&subject_addr);
@@ -607,6 +870,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn counts_dns_resolution_failures_in_metrics() {
+        let system = System::new("counts_dns_resolution_failures_in_metrics");
+        let subject = ProxyClient::new(ProxyClientConfig {
+            cryptde: main_cryptde(),
+            dns_servers: vec![SocketAddr::from_str("1.1.1.1:53").unwrap()],
+            exit_service_rate: 0,
+            exit_byte_rate: 0,
+            exit_policy: ExitPolicy::default(),
+        });
+        let subject_addr = subject.start();
+        let subject_subs = ProxyClient::make_subs_from(&subject_addr);
+
+        subject_subs
+            .dns_resolve_failed
+            .try_send(DnsResolveFailure_0v1::new(make_meaningless_stream_key()))
+            .unwrap();
+        subject_subs
+            .dns_resolve_failed
+            .try_send(DnsResolveFailure_0v1::new(make_meaningless_stream_key()))
+            .unwrap();
+
+        System::current().stop_with_code(0);
+        system.run();
+        assert_eq!(metrics().value(&DNS_FAILURES, &[]), Some(2.0));
+    }
+
     #[test]
     fn forwards_dns_resolve_failed_to_hopper() {
         init_test_logging();
@@ -626,6 +916,7 @@ mod tests {
                 dns_servers: vec![SocketAddr::from_str("1.1.1.1:53").unwrap()],
                 exit_service_rate: 0,
                 exit_byte_rate: 0,
+                exit_policy: ExitPolicy::default(),
             });
             subject.stream_contexts.insert(
                 stream_key_inner,
@@ -673,6 +964,118 @@ mod tests {
         );
     }
 
+    #[test]
+    fn forwards_exit_policy_violation_to_hopper() {
+        init_test_logging();
+        let cryptde = main_cryptde();
+        let (hopper, hopper_awaiter, hopper_recording_arc) = make_recorder();
+        let stream_key = make_meaningless_stream_key();
+        let return_route = make_meaningless_route();
+        let originator_key = make_meaningless_public_key();
+        let stream_key_inner = stream_key;
+        let return_route_inner = return_route.clone();
+        let originator_key_inner = originator_key.clone();
+        thread::spawn(move || {
+            let system = System::new("forwards_exit_policy_violation_to_hopper");
+            let peer_actors = peer_actors_builder().hopper(hopper).build();
+            let mut subject = ProxyClient::new(ProxyClientConfig {
+                cryptde,
+                dns_servers: vec![SocketAddr::from_str("1.1.1.1:53").unwrap()],
+                exit_service_rate: 0,
+                exit_byte_rate: 0,
+                exit_policy: ExitPolicy::default(),
+            });
+            subject.stream_contexts.insert(
+                stream_key_inner,
+                StreamContext {
+                    return_route: return_route_inner,
+                    payload_destination_key: originator_key_inner,
+                    paying_wallet: None,
+                },
+            );
+            let subject_addr = subject.start();
+            let subject_subs = ProxyClient::make_subs_from(&subject_addr);
+
+            send_bind_message!(subject_subs, peer_actors);
+
+            subject_subs
+                .exit_policy_violated
+                .try_send(ExitPolicyViolation_0v1::new(
+                    stream_key_inner,
+                    "port 25 is not in --exit-ports".to_string(),
+                ))
+                .unwrap();
+
+            subject_subs
+                .exit_policy_violated
+                .try_send(ExitPolicyViolation_0v1::new(
+                    stream_key_inner,
+                    "port 25 is not in --exit-ports".to_string(),
+                ))
+                .unwrap();
+
+            system.run();
+        });
+
+        hopper_awaiter.await_message_count(1);
+
+        let message_type: MessageType =
+            ExitPolicyViolation_0v1::new(stream_key, "port 25 is not in --exit-ports".to_string())
+                .into();
+        assert_eq!(
+            &IncipientCoresPackage::new(cryptde, return_route, message_type, &originator_key)
+                .unwrap(),
+            hopper_recording_arc
+                .lock()
+                .unwrap()
+                .get_record::<IncipientCoresPackage>(0)
+        );
+        TestLogHandler::new().await_log_containing(
+            &format!(
+                "ERROR: ProxyClient: Exit policy violated by nonexistent stream ({:?}).",
+                stream_key
+            ),
+            1000,
+        );
+    }
+
+    #[test]
+    fn rate_pack_change_is_relayed_to_stream_handler_pool() {
+        let system = System::new("rate_pack_change_is_relayed_to_stream_handler_pool");
+        let peer_actors = peer_actors_builder().build();
+        let mut set_exit_rates_parameters = Arc::new(Mutex::new(vec![]));
+        let pool = Box::new(
+            StreamHandlerPoolMock::new().set_exit_rates_parameters(&mut set_exit_rates_parameters),
+        );
+        let pool_factory = StreamHandlerPoolFactoryMock::new().make_result(pool);
+        let resolver_factory =
+            ResolverWrapperFactoryMock::new().new_result(Box::new(ResolverWrapperMock::new()));
+        let mut subject = ProxyClient::new(ProxyClientConfig {
+            cryptde: main_cryptde(),
+            dns_servers: dnss(),
+            exit_service_rate: 100,
+            exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
+        });
+        subject.resolver_wrapper_factory = Box::new(resolver_factory);
+        subject.stream_handler_pool_factory = Box::new(pool_factory);
+        let subject_addr: Addr<ProxyClient> = subject.start();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr
+            .try_send(RatePackChangeMessage {
+                rate_pack: rate_pack(1000),
+            })
+            .unwrap();
+
+        System::current().stop_with_code(0);
+        system.run();
+        assert_eq!(
+            *set_exit_rates_parameters.lock().unwrap(),
+            vec![(rate_pack_exit(1000), rate_pack_exit_byte(1000))]
+        );
+    }
+
     #[test]
     fn data_from_hopper_is_relayed_to_stream_handler_pool() {
         let cryptde = main_cryptde();
@@ -692,7 +1095,7 @@ mod tests {
             SocketAddr::from_str("1.2.3.4:1234").unwrap(),
             Some(make_wallet("consuming")),
             make_meaningless_route(),
-            request.clone().into(),
+            request.clone(),
             0,
         );
         let hopper = Recorder::new();
@@ -713,6 +1116,7 @@ mod tests {
             dns_servers: dnss(),
             exit_service_rate: 100,
             exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
         });
         subject.resolver_wrapper_factory = Box::new(resolver_factory);
         subject.stream_handler_pool_factory = Box::new(pool_factory);
@@ -768,6 +1172,7 @@ mod tests {
             dns_servers: dnss(),
             exit_service_rate: rate_pack_exit(100),
             exit_byte_rate: rate_pack_exit_byte(100),
+            exit_policy: ExitPolicy::default(),
         });
         subject.resolver_wrapper_factory = Box::new(resolver_factory);
         subject.stream_handler_pool_factory = Box::new(pool_factory);
@@ -805,14 +1210,14 @@ mod tests {
             ),
             main_cryptde,
             None,
-            Some(ROPSTEN_TESTNET_CONTRACT_ADDRESS),
+            Some(contract_address(DEFAULT_CHAIN_ID)),
         )
         .unwrap();
         let package = ExpiredCoresPackage::new(
             SocketAddr::from_str("1.2.3.4:1234").unwrap(),
             None,
             zero_hop_remaining_route,
-            request.clone().into(),
+            request.clone(),
             0,
         );
         let hopper = Recorder::new();
@@ -833,6 +1238,7 @@ mod tests {
             dns_servers: dnss(),
             exit_service_rate: rate_pack_exit(100),
             exit_byte_rate: rate_pack_exit_byte(100),
+            exit_policy: ExitPolicy::default(),
         });
         subject.resolver_wrapper_factory = Box::new(resolver_factory);
         subject.stream_handler_pool_factory = Box::new(pool_factory);
@@ -847,6 +1253,300 @@ mod tests {
         assert_eq!(parameter, (request, None,));
     }
 
+    #[test]
+    fn datagram_from_hopper_is_relayed_to_stream_handler_pool() {
+        let cryptde = main_cryptde();
+        let request = DatagramRequestPayload_0v1 {
+            association_key: make_meaningless_stream_key(),
+            target_hostname: String::from("that.try"),
+            target_port: 53,
+            data: b"inbound datagram".to_vec(),
+            originator_public_key: PublicKey::new(&b"originator"[..]),
+        };
+        let package = ExpiredCoresPackage::new(
+            SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+            Some(make_wallet("consuming")),
+            make_meaningless_route(),
+            request.clone(),
+            0,
+        );
+        let system = System::new("datagram_from_hopper_is_relayed_to_stream_handler_pool");
+        let peer_actors = peer_actors_builder().build();
+        let mut process_datagram_parameters = Arc::new(Mutex::new(vec![]));
+        let pool = Box::new(
+            StreamHandlerPoolMock::new()
+                .process_datagram_parameters(&mut process_datagram_parameters),
+        );
+        let pool_factory = StreamHandlerPoolFactoryMock::new().make_result(pool);
+        let resolver_factory =
+            ResolverWrapperFactoryMock::new().new_result(Box::new(ResolverWrapperMock::new()));
+        let mut subject = ProxyClient::new(ProxyClientConfig {
+            cryptde,
+            dns_servers: dnss(),
+            exit_service_rate: 100,
+            exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
+        });
+        subject.resolver_wrapper_factory = Box::new(resolver_factory);
+        subject.stream_handler_pool_factory = Box::new(pool_factory);
+        let subject_addr: Addr<ProxyClient> = subject.start();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr.try_send(package).unwrap();
+
+        System::current().stop_with_code(0);
+        system.run();
+        let parameter = process_datagram_parameters.lock().unwrap().remove(0);
+        assert_eq!(parameter, (request, Some(make_wallet("consuming"))));
+    }
+
+    #[test]
+    fn refuse_to_relay_datagram_with_no_paying_wallet() {
+        init_test_logging();
+        let request = DatagramRequestPayload_0v1 {
+            association_key: make_meaningless_stream_key(),
+            target_hostname: String::from("that.try"),
+            target_port: 53,
+            data: b"inbound datagram".to_vec(),
+            originator_public_key: PublicKey::new(&b"originator"[..]),
+        };
+        let package = ExpiredCoresPackage::new(
+            SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+            None,
+            make_meaningless_route(),
+            request,
+            0,
+        );
+        let system = System::new("refuse_to_relay_datagram_with_no_paying_wallet");
+        let peer_actors = peer_actors_builder().build();
+        let mut process_datagram_parameters = Arc::new(Mutex::new(vec![]));
+        let pool = Box::new(
+            StreamHandlerPoolMock::new()
+                .process_datagram_parameters(&mut process_datagram_parameters),
+        );
+        let pool_factory = StreamHandlerPoolFactoryMock::new().make_result(pool);
+        let resolver_factory =
+            ResolverWrapperFactoryMock::new().new_result(Box::new(ResolverWrapperMock::new()));
+        let mut subject = ProxyClient::new(ProxyClientConfig {
+            cryptde: main_cryptde(),
+            dns_servers: dnss(),
+            exit_service_rate: 100,
+            exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
+        });
+        subject.resolver_wrapper_factory = Box::new(resolver_factory);
+        subject.stream_handler_pool_factory = Box::new(pool_factory);
+        let subject_addr: Addr<ProxyClient> = subject.start();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr.try_send(package).unwrap();
+
+        System::current().stop();
+        system.run();
+        assert_eq!(process_datagram_parameters.lock().unwrap().len(), 0);
+        TestLogHandler::new().exists_log_containing(
+            "WARN: ProxyClient: Refusing to provide exit services for 16-byte datagram without paying wallet",
+        );
+    }
+
+    #[test]
+    fn inbound_datagram_is_translated_to_cores_package() {
+        init_test_logging();
+        let (hopper, _, hopper_recording_arc) = make_recorder();
+        let (accountant, _, accountant_recording_arc) = make_recorder();
+        let association_key = make_meaningless_stream_key();
+        let unknown_key = StreamKey::new(
+            PublicKey::new(&b"unknown"[..]),
+            SocketAddr::from_str("2.3.4.5:6789").unwrap(),
+        );
+        let source = SocketAddr::from_str("1.2.3.4:53").unwrap();
+        let data: &[u8] = b"Answers to questions nobody asked.";
+        let system = System::new("inbound_datagram_is_translated_to_cores_package");
+        let mut subject = ProxyClient::new(ProxyClientConfig {
+            cryptde: main_cryptde(),
+            dns_servers: dnss(),
+            exit_service_rate: 100,
+            exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
+        });
+        subject.association_contexts.insert(
+            association_key,
+            StreamContext {
+                return_route: make_meaningless_route(),
+                payload_destination_key: PublicKey::new(&b"abcd"[..]),
+                paying_wallet: Some(make_wallet("paying")),
+            },
+        );
+        let subject_addr: Addr<ProxyClient> = subject.start();
+        let peer_actors = peer_actors_builder()
+            .hopper(hopper)
+            .accountant(accountant)
+            .build();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr
+            .try_send(InboundDatagram {
+                association_key,
+                source,
+                data: Vec::from(data),
+            })
+            .unwrap();
+        subject_addr
+            .try_send(InboundDatagram {
+                association_key: unknown_key,
+                source,
+                data: Vec::from(data),
+            })
+            .unwrap();
+
+        System::current().stop_with_code(0);
+        system.run();
+        let hopper_recording = hopper_recording_arc.lock().unwrap();
+        assert_eq!(
+            hopper_recording.get_record::<IncipientCoresPackage>(0),
+            &IncipientCoresPackage::new(
+                main_cryptde(),
+                make_meaningless_route(),
+                DatagramResponsePayload_0v1 {
+                    association_key,
+                    source,
+                    data: Vec::from(data),
+                }
+                .into(),
+                &PublicKey::new(&b"abcd"[..]),
+            )
+            .unwrap()
+        );
+        assert_eq!(hopper_recording.len(), 1);
+        let accountant_recording = accountant_recording_arc.lock().unwrap();
+        assert_eq!(
+            accountant_recording.get_record::<ReportExitServiceProvidedMessage>(0),
+            &ReportExitServiceProvidedMessage {
+                paying_wallet: make_wallet("paying"),
+                payload_size: data.len(),
+                service_rate: 100,
+                byte_rate: 200,
+            }
+        );
+        assert_eq!(accountant_recording.len(), 1);
+        TestLogHandler::new().exists_log_containing(&format!(
+            "DEBUG: ProxyClient: Received {}-byte datagram from 1.2.3.4:53 for expired association {} - ignoring",
+            data.len(),
+            unknown_key
+        ));
+    }
+
+    #[test]
+    fn inbound_datagram_refreshes_its_udp_association() {
+        let association_key = make_meaningless_stream_key();
+        let unknown_key = StreamKey::new(
+            PublicKey::new(&b"unknown"[..]),
+            SocketAddr::from_str("2.3.4.5:6789").unwrap(),
+        );
+        let source = SocketAddr::from_str("1.2.3.4:53").unwrap();
+        let system = System::new("inbound_datagram_refreshes_its_udp_association");
+        let mut refresh_udp_association_parameters = Arc::new(Mutex::new(vec![]));
+        let pool = StreamHandlerPoolMock::new()
+            .refresh_udp_association_parameters(&mut refresh_udp_association_parameters);
+        let pool_factory = StreamHandlerPoolFactoryMock::new().make_result(Box::new(pool));
+        let mut subject = ProxyClient::new(ProxyClientConfig {
+            cryptde: main_cryptde(),
+            dns_servers: dnss(),
+            exit_service_rate: 100,
+            exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
+        });
+        subject.stream_handler_pool_factory = Box::new(pool_factory);
+        subject.association_contexts.insert(
+            association_key,
+            StreamContext {
+                return_route: make_meaningless_route(),
+                payload_destination_key: PublicKey::new(&b"abcd"[..]),
+                paying_wallet: Some(make_wallet("paying")),
+            },
+        );
+        let subject_addr: Addr<ProxyClient> = subject.start();
+        subject_addr
+            .try_send(BindMessage {
+                peer_actors: peer_actors_builder().build(),
+            })
+            .unwrap();
+
+        subject_addr
+            .try_send(InboundDatagram {
+                association_key,
+                source,
+                data: b"booga".to_vec(),
+            })
+            .unwrap();
+        subject_addr
+            .try_send(InboundDatagram {
+                association_key: unknown_key,
+                source,
+                data: b"booga".to_vec(),
+            })
+            .unwrap();
+
+        System::current().stop_with_code(0);
+        system.run();
+        assert_eq!(
+            *refresh_udp_association_parameters.lock().unwrap(),
+            vec![association_key]
+        );
+    }
+
+    #[test]
+    fn expired_udp_associations_are_removed_periodically() {
+        let (parameters_tx, parameters_rx) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            let system = System::new("expired_udp_associations_are_removed_periodically");
+            let mut remove_expired_udp_associations_parameters = Arc::new(Mutex::new(vec![]));
+            let pool = StreamHandlerPoolMock::new().remove_expired_udp_associations_parameters(
+                &mut remove_expired_udp_associations_parameters,
+            );
+            parameters_tx
+                .send(remove_expired_udp_associations_parameters)
+                .unwrap();
+            let mut subject = ProxyClient::new(ProxyClientConfig {
+                cryptde: main_cryptde(),
+                dns_servers: dnss(),
+                exit_service_rate: 100,
+                exit_byte_rate: 200,
+                exit_policy: ExitPolicy::default(),
+            });
+            subject.stream_handler_pool_factory =
+                Box::new(StreamHandlerPoolFactoryMock::new().make_result(Box::new(pool)));
+            subject.udp_association_sweep_interval = Duration::from_millis(10);
+            let subject_addr: Addr<ProxyClient> = subject.start();
+            subject_addr
+                .try_send(BindMessage {
+                    peer_actors: peer_actors_builder().build(),
+                })
+                .unwrap();
+
+            system.run();
+        });
+
+        let remove_expired_udp_associations_parameters = parameters_rx.recv().unwrap();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while remove_expired_udp_associations_parameters
+            .lock()
+            .unwrap()
+            .len()
+            < 2
+            && Instant::now() < deadline
+        {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(
+            remove_expired_udp_associations_parameters
+                .lock()
+                .unwrap()
+                .len()
+                >= 2
+        );
+    }
+
     #[test]
     fn inbound_server_data_is_translated_to_cores_packages() {
         init_test_logging();
@@ -860,6 +1560,7 @@ mod tests {
             dns_servers: vec![SocketAddr::from_str("8.7.6.5:4321").unwrap()],
             exit_service_rate: 100,
             exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
         });
         subject.stream_contexts.insert(
             stream_key.clone(),
@@ -996,6 +1697,7 @@ mod tests {
             dns_servers: vec![SocketAddr::from_str("8.7.6.5:4321").unwrap()],
             exit_service_rate: 100,
             exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
         });
         subject.stream_contexts.insert(
             stream_key.clone(),
@@ -1046,6 +1748,7 @@ mod tests {
             dns_servers: vec![SocketAddr::from_str("8.7.6.5:4321").unwrap()],
             exit_service_rate: 100,
             exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
         });
         subject.stream_contexts.insert(
             stream_key.clone(),
@@ -1094,6 +1797,7 @@ mod tests {
             dns_servers: vec![SocketAddr::from_str("8.7.6.5:4321").unwrap()],
             exit_service_rate: 100,
             exit_byte_rate: 200,
+            exit_policy: ExitPolicy::default(),
         });
         let mut process_package_params_arc = Arc::new(Mutex::new(vec![]));
         let pool = StreamHandlerPoolMock::new()
@@ -1137,7 +1841,7 @@ mod tests {
                 SocketAddr::from_str("2.3.4.5:1235").unwrap(),
                 Some(make_wallet("gnimusnoc")),
                 new_return_route.clone(),
-                payload.clone().into(),
+                payload.clone(),
                 0,
             ))
             .unwrap();
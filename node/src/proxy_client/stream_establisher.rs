@@ -133,13 +133,14 @@ mod tests {
     use crate::test_utils::recorder::make_recorder;
     use crate::test_utils::recorder::peer_actors_builder;
     use crate::test_utils::stream_connector_mock::StreamConnectorMock;
-    use crate::test_utils::tokio_wrapper_mocks::ReadHalfWrapperMock;
+    use crate::test_utils::tokio_wrapper_mocks::{ReadHalfWrapperMock, WriteHalfWrapperMock};
     use actix::System;
     use futures::future::lazy;
     use std::io::ErrorKind;
     use std::net::SocketAddr;
     use std::str::FromStr;
     use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
     use std::thread;
     use tokio::prelude::Async;
 
@@ -223,4 +224,75 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn establish_stream_connects_to_whatever_port_the_payload_names() {
+        let connect_one_params_arc = Arc::new(Mutex::new(vec![]));
+        let connect_one_params_arc_thread = connect_one_params_arc.clone();
+        let (proxy_client, _, _) = make_recorder();
+        let (done_tx, done_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let system =
+                System::new("establish_stream_connects_to_whatever_port_the_payload_names");
+            let peer_actors = peer_actors_builder().proxy_client(proxy_client).build();
+            let (stream_adder_tx, _stream_adder_rx) = mpsc::channel();
+            let (stream_killer_tx, _stream_killer_rx) = mpsc::channel();
+            let peer_addr = SocketAddr::from_str("4.3.2.1:5222").unwrap();
+            let mut subject = StreamEstablisher {
+                cryptde: main_cryptde(),
+                stream_adder_tx,
+                stream_killer_tx,
+                stream_connector: Box::new(
+                    StreamConnectorMock::new()
+                        .connect_one_params(&connect_one_params_arc_thread)
+                        .with_connection(
+                            peer_addr,
+                            peer_addr,
+                            ReadHalfWrapperMock::new()
+                                .poll_read_result(vec![], Ok(Async::NotReady)),
+                            WriteHalfWrapperMock::new(),
+                        ),
+                ),
+                proxy_client_sub: peer_actors.proxy_client.inbound_server_data,
+                logger: Logger::new("ProxyClient"),
+                channel_factory: Box::new(FuturesChannelFactoryReal {}),
+            };
+            let payload = ClientRequestPayload_0v1 {
+                stream_key: make_meaningless_stream_key(),
+                sequenced_packet: SequencedPacket {
+                    data: b"<stream:stream>".to_vec(),
+                    sequence_number: 0,
+                    last_data: false,
+                },
+                target_hostname: Some("4.3.2.1".to_string()),
+                target_port: 5222,
+                protocol: ProxyProtocol::SOCKS5,
+                originator_public_key: main_cryptde().public_key().clone(),
+            };
+            actix::spawn(lazy(move || {
+                subject
+                    .establish_stream(
+                        &payload,
+                        vec![IpAddr::from_str("4.3.2.1").unwrap()],
+                        "4.3.2.1".to_string(),
+                    )
+                    .unwrap();
+                done_tx.send(()).unwrap();
+                Ok(())
+            }));
+            system.run();
+        });
+
+        done_rx.recv().unwrap();
+
+        let connect_one_params = connect_one_params_arc.lock().unwrap();
+        assert_eq!(
+            *connect_one_params,
+            vec![(
+                vec![IpAddr::from_str("4.3.2.1").unwrap()],
+                "4.3.2.1".to_string(),
+                5222
+            )]
+        );
+    }
 }
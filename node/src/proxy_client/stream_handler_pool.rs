@@ -1,18 +1,25 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 #![allow(proc_macro_derive_resolution_fallback)]
 
+use crate::metrics::{metrics, ACTIVE_STREAMS};
 use crate::proxy_client::resolver_wrapper::ResolverWrapper;
 use crate::proxy_client::stream_establisher::StreamEstablisherFactoryReal;
 use crate::proxy_client::stream_establisher::{StreamEstablisher, StreamEstablisherFactory};
 use crate::sub_lib::accountant::ReportExitServiceProvidedMessage;
 use crate::sub_lib::channel_wrappers::SenderWrapper;
 use crate::sub_lib::cryptde::CryptDE;
+use crate::sub_lib::exit_policy::ExitPolicy;
 use crate::sub_lib::logger::Logger;
-use crate::sub_lib::proxy_client::{error_socket_addr, ProxyClientSubs};
-use crate::sub_lib::proxy_client::{DnsResolveFailure_0v1, InboundServerData};
-use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
+use crate::sub_lib::proxy_client::{error_socket_addr, ProxyClientSubs, UDP_ASSOCIATION_IDLE_TTL};
+use crate::sub_lib::proxy_client::{
+    DnsResolveFailure_0v1, ExitPolicyViolation_0v1, InboundServerData,
+};
+use crate::sub_lib::proxy_server::{ClientRequestPayload_0v1, DatagramRequestPayload_0v1};
 use crate::sub_lib::sequence_buffer::SequencedPacket;
 use crate::sub_lib::stream_key::StreamKey;
+use crate::sub_lib::ttl_hashmap::TtlHashMap;
+use crate::sub_lib::udp_relay::UdpAssociation;
+use crate::sub_lib::udp_socket_wrapper::{UdpSocketWrapperFactory, UdpSocketWrapperFactoryReal};
 use crate::sub_lib::wallet::Wallet;
 use actix::Recipient;
 use futures::future;
@@ -20,6 +27,7 @@ use futures::future::Future;
 use std::collections::HashMap;
 use std::io;
 use std::net::{AddrParseError, IpAddr, SocketAddr};
+use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::mpsc;
 use std::sync::mpsc::Receiver;
@@ -32,12 +40,22 @@ use trust_dns_resolver::lookup_ip::LookupIp;
 
 pub trait StreamHandlerPool {
     fn process_package(&self, payload: ClientRequestPayload_0v1, paying_wallet: Option<Wallet>);
+    fn process_datagram(
+        &mut self,
+        payload: DatagramRequestPayload_0v1,
+        paying_wallet: Option<Wallet>,
+    );
+    fn set_exit_rates(&self, exit_service_rate: u64, exit_byte_rate: u64);
+    fn refresh_udp_association(&self, association_key: StreamKey);
+    fn remove_expired_udp_associations(&self);
 }
 
 pub struct StreamHandlerPoolReal {
     inner: Arc<Mutex<StreamHandlerPoolRealInner>>,
     stream_adder_rx: Receiver<(StreamKey, Box<dyn SenderWrapper<SequencedPacket>>)>,
     stream_killer_rx: Receiver<(StreamKey, u64)>,
+    udp_associations: TtlHashMap<StreamKey, UdpAssociation>,
+    udp_socket_factory: Box<dyn UdpSocketWrapperFactory>,
 }
 
 struct StreamHandlerPoolRealInner {
@@ -49,6 +67,17 @@ struct StreamHandlerPoolRealInner {
     establisher_factory: Box<dyn StreamEstablisherFactory>,
     exit_service_rate: u64,
     exit_byte_rate: u64,
+    exit_policy: ExitPolicy,
+}
+
+impl StreamHandlerPoolRealInner {
+    fn report_active_streams(&self) {
+        metrics().set(
+            &ACTIVE_STREAMS,
+            &[("pool", "proxy_client")],
+            self.stream_writer_channels.len() as f64,
+        );
+    }
 }
 
 impl StreamHandlerPool for StreamHandlerPoolReal {
@@ -56,6 +85,31 @@ impl StreamHandlerPool for StreamHandlerPoolReal {
         self.do_housekeeping();
         Self::process_package(payload, paying_wallet, self.inner.clone())
     }
+
+    fn process_datagram(
+        &mut self,
+        payload: DatagramRequestPayload_0v1,
+        paying_wallet: Option<Wallet>,
+    ) {
+        let association = self.find_or_make_udp_association(payload.association_key);
+        Self::relay_datagram(payload, paying_wallet, association, self.inner.clone())
+    }
+
+    fn set_exit_rates(&self, exit_service_rate: u64, exit_byte_rate: u64) {
+        let mut inner = self.inner.lock().expect("Stream handler pool is poisoned");
+        inner.exit_service_rate = exit_service_rate;
+        inner.exit_byte_rate = exit_byte_rate;
+    }
+
+    // Replies count as activity too: an association that only hears from its target would
+    // otherwise be closed in the middle of the conversation.
+    fn refresh_udp_association(&self, association_key: StreamKey) {
+        let _ = self.udp_associations.get(&association_key);
+    }
+
+    fn remove_expired_udp_associations(&self) {
+        self.udp_associations.remove_expired_entries();
+    }
 }
 
 type StreamEstablisherResult =
@@ -69,6 +123,7 @@ impl StreamHandlerPoolReal {
         proxy_client_subs: ProxyClientSubs,
         exit_service_rate: u64,
         exit_byte_rate: u64,
+        exit_policy: ExitPolicy,
     ) -> StreamHandlerPoolReal {
         let (stream_killer_tx, stream_killer_rx) = mpsc::channel();
         let (stream_adder_tx, stream_adder_rx) = mpsc::channel();
@@ -88,9 +143,12 @@ impl StreamHandlerPoolReal {
                 logger: Logger::new("ProxyClient"),
                 exit_service_rate,
                 exit_byte_rate,
+                exit_policy,
             })),
             stream_adder_rx,
             stream_killer_rx,
+            udp_associations: TtlHashMap::new(UDP_ASSOCIATION_IDLE_TTL),
+            udp_socket_factory: Box::new(UdpSocketWrapperFactoryReal {}),
         }
     }
 
@@ -140,6 +198,141 @@ impl StreamHandlerPoolReal {
         };
     }
 
+    // An association whose relay has shut down is replaced, rather than left to swallow datagrams
+    // until its idle TTL runs out.
+    fn find_or_make_udp_association(&mut self, association_key: StreamKey) -> Rc<UdpAssociation> {
+        if let Some(association) = self.udp_associations.get(&association_key) {
+            if !association.is_closed() {
+                return association;
+            }
+        }
+        let inbound_datagram_sub = {
+            let inner = self.inner.lock().expect("Stream handler pool is poisoned");
+            debug!(inner.logger, "Opening UDP association {}", association_key);
+            inner.proxy_client_subs.inbound_datagram.clone()
+        };
+        let (association, relay) = UdpAssociation::new(
+            association_key,
+            self.udp_socket_factory.make(),
+            false,
+            inbound_datagram_sub,
+        );
+        actix::spawn(relay);
+        self.udp_associations.insert(association_key, association);
+        self.udp_associations
+            .get(&association_key)
+            .expect("UDP association disappeared")
+    }
+
+    fn relay_datagram(
+        payload: DatagramRequestPayload_0v1,
+        paying_wallet: Option<Wallet>,
+        association: Rc<UdpAssociation>,
+        inner_arc: Arc<Mutex<StreamHandlerPoolRealInner>>,
+    ) {
+        let (exit_policy, accountant_sub, exit_service_rate, exit_byte_rate, logger) = {
+            let inner = inner_arc.lock().expect("Stream handler pool is poisoned");
+            (
+                inner.exit_policy.clone(),
+                inner.accountant_sub.clone(),
+                inner.exit_service_rate,
+                inner.exit_byte_rate,
+                inner.logger.clone(),
+            )
+        };
+        let failure_logger = logger.clone();
+        let association_key = payload.association_key;
+        let target_hostname = payload.target_hostname.clone();
+        let target_port = payload.target_port;
+        let payload_size = payload.data.len();
+        let future = Self::resolve_datagram_target(&payload.target_hostname, &inner_arc)
+            .and_then(move |ip_addrs| {
+                let ip_addr = Self::choose_datagram_target(
+                    &exit_policy,
+                    &payload.target_hostname,
+                    target_port,
+                    ip_addrs,
+                )?;
+                association.send(SocketAddr::new(ip_addr, target_port), payload.data)?;
+                match paying_wallet {
+                    Some(wallet) => accountant_sub
+                        .try_send(ReportExitServiceProvidedMessage {
+                            paying_wallet: wallet,
+                            payload_size,
+                            service_rate: exit_service_rate,
+                            byte_rate: exit_byte_rate,
+                        })
+                        .expect("Accountant is dead"),
+                    None => debug!(
+                        logger,
+                        "Sent {}-byte datagram without consuming wallet for free", payload_size
+                    ),
+                }
+                Ok(())
+            })
+            .map_err(move |error| {
+                warning!(
+                    failure_logger,
+                    "Dropped {}-byte datagram to {}:{} for association {}: {}",
+                    payload_size,
+                    target_hostname,
+                    target_port,
+                    association_key,
+                    error
+                )
+            });
+        actix::spawn(future);
+    }
+
+    fn resolve_datagram_target(
+        target_hostname: &str,
+        inner_arc: &Arc<Mutex<StreamHandlerPoolRealInner>>,
+    ) -> Box<dyn Future<Item = Vec<IpAddr>, Error = String>> {
+        if let Ok(ip_addr) = Self::parse_ip(target_hostname) {
+            return Box::new(future::ok(vec![ip_addr]));
+        }
+        let target_hostname = target_hostname.to_string();
+        Box::new(
+            inner_arc
+                .lock()
+                .expect("Stream handler pool is poisoned")
+                .resolver
+                .lookup_ip(&Self::make_fqdn(&target_hostname))
+                .map(|lookup_ip| lookup_ip.iter().collect())
+                .map_err(move |e| {
+                    format!(
+                        "Could not find IP address for host {}: {}",
+                        target_hostname, e
+                    )
+                }),
+        )
+    }
+
+    // There's no way to tell a UDP client why its datagram went nowhere, so a datagram the exit
+    // policy forbids is simply dropped.
+    fn choose_datagram_target(
+        exit_policy: &ExitPolicy,
+        target_hostname: &str,
+        target_port: u16,
+        ip_addrs: Vec<IpAddr>,
+    ) -> Result<IpAddr, String> {
+        let mut reason_opt: Option<String> = None;
+        ip_addrs
+            .into_iter()
+            .find(
+                |ip_addr| match exit_policy.check(target_hostname, *ip_addr, target_port) {
+                    Ok(()) => true,
+                    Err(reason) => {
+                        reason_opt.get_or_insert(reason);
+                        false
+                    }
+                },
+            )
+            .ok_or_else(|| {
+                reason_opt.unwrap_or_else(|| format!("no IP address found for {}", target_hostname))
+            })
+    }
+
     fn clean_up_bad_stream(
         inner_arc: Arc<Mutex<StreamHandlerPoolRealInner>>,
         stream_key: &StreamKey,
@@ -158,6 +351,7 @@ impl StreamHandlerPoolReal {
                 sender_wrapper.peer_addr()
             );
         }
+        inner.report_active_streams();
         Self::send_terminating_package(
             stream_key,
             source,
@@ -190,6 +384,7 @@ impl StreamHandlerPoolReal {
                         "Trying to remove StreamWriter {:?}, but it's already gone", stream_key
                     ),
                 }
+                inner.report_active_streams();
             }
             if payload_size > 0 {
                 match paying_wallet {
@@ -272,10 +467,19 @@ impl StreamHandlerPoolReal {
         inner_arc: Arc<Mutex<StreamHandlerPoolRealInner>>,
         target_hostname: String,
     ) -> StreamEstablisherResult {
+        let (exit_policy, exit_policy_violated_sub, logger) = Self::exit_policy_parts(&inner_arc);
         let mut stream_establisher = StreamHandlerPoolReal::make_establisher(inner_arc);
         Box::new(
             future::lazy(move || {
-                stream_establisher.establish_stream(&payload, vec![ip_addr], target_hostname)
+                let ip_addrs = Self::enforce_exit_policy(
+                    &exit_policy,
+                    &exit_policy_violated_sub,
+                    &logger,
+                    &payload,
+                    &target_hostname,
+                    vec![ip_addr],
+                )?;
+                stream_establisher.establish_stream(&payload, ip_addrs, target_hostname)
             })
             .map_err(|io_error| format!("Could not establish stream: {:?}", io_error)),
         )
@@ -293,9 +497,9 @@ impl StreamHandlerPoolReal {
             .proxy_client_subs
             .dns_resolve_failed
             .clone();
+        let (exit_policy, exit_policy_violated_sub, logger) = Self::exit_policy_parts(&inner_arc);
         let mut establisher = StreamHandlerPoolReal::make_establisher(inner_arc.clone());
         let stream_key = payload.stream_key;
-        let logger = StreamHandlerPoolReal::make_logger_copy(&inner_arc);
         Box::new(
             inner_arc
                 .lock()
@@ -315,6 +519,8 @@ impl StreamHandlerPoolReal {
                         lookup_result,
                         logger,
                         &mut establisher,
+                        &exit_policy,
+                        &exit_policy_violated_sub,
                     )
                 })
                 .map_err(|io_error| format!("Could not establish stream: {:?}", io_error)),
@@ -327,6 +533,8 @@ impl StreamHandlerPoolReal {
         lookup_result: Result<LookupIp, ResolveError>,
         logger: Logger,
         establisher: &mut StreamEstablisher,
+        exit_policy: &ExitPolicy,
+        exit_policy_violated_sub: &Recipient<ExitPolicyViolation_0v1>,
     ) -> io::Result<Box<dyn SenderWrapper<SequencedPacket>>> {
         let ip_addrs: Vec<IpAddr> = match lookup_result {
             Err(e) => {
@@ -342,9 +550,73 @@ impl StreamHandlerPoolReal {
             logger,
             "Found IP addresses for {}: {:?}", target_hostname, &ip_addrs
         );
+        let ip_addrs = Self::enforce_exit_policy(
+            exit_policy,
+            exit_policy_violated_sub,
+            &logger,
+            payload,
+            &target_hostname,
+            ip_addrs,
+        )?;
         establisher.establish_stream(&payload, ip_addrs, target_hostname)
     }
 
+    fn exit_policy_parts(
+        inner_arc: &Arc<Mutex<StreamHandlerPoolRealInner>>,
+    ) -> (ExitPolicy, Recipient<ExitPolicyViolation_0v1>, Logger) {
+        let inner = inner_arc.lock().expect("Stream handler pool is poisoned");
+        (
+            inner.exit_policy.clone(),
+            inner.proxy_client_subs.exit_policy_violated.clone(),
+            inner.logger.clone(),
+        )
+    }
+
+    // Addresses the exit policy forbids are dropped; if that leaves nothing to connect to, the
+    // originator is told why, and the stream is cleaned up like any other that can't be opened.
+    fn enforce_exit_policy(
+        exit_policy: &ExitPolicy,
+        exit_policy_violated_sub: &Recipient<ExitPolicyViolation_0v1>,
+        logger: &Logger,
+        payload: &ClientRequestPayload_0v1,
+        target_hostname: &str,
+        ip_addrs: Vec<IpAddr>,
+    ) -> io::Result<Vec<IpAddr>> {
+        let mut reason_opt: Option<String> = None;
+        let permitted_ip_addrs: Vec<IpAddr> = ip_addrs
+            .into_iter()
+            .filter(|ip_addr| {
+                match exit_policy.check(target_hostname, *ip_addr, payload.target_port) {
+                    Ok(()) => true,
+                    Err(reason) => {
+                        reason_opt.get_or_insert(reason);
+                        false
+                    }
+                }
+            })
+            .collect();
+        match (permitted_ip_addrs.is_empty(), reason_opt) {
+            (true, Some(reason)) => {
+                warning!(
+                    logger,
+                    "Refusing to provide exit services to {}:{} for stream {}: {}",
+                    target_hostname,
+                    payload.target_port,
+                    payload.stream_key,
+                    reason
+                );
+                exit_policy_violated_sub
+                    .try_send(ExitPolicyViolation_0v1::new(
+                        payload.stream_key,
+                        reason.clone(),
+                    ))
+                    .expect("ProxyClient is dead");
+                Err(io::Error::new(io::ErrorKind::PermissionDenied, reason))
+            }
+            _ => Ok(permitted_ip_addrs),
+        }
+    }
+
     fn make_fqdn(target_hostname: &str) -> String {
         format!("{}.", target_hostname)
     }
@@ -427,6 +699,7 @@ impl StreamHandlerPoolReal {
                 ),
             }
         }
+        inner.report_active_streams();
     }
 
     fn add_new_streams(&self) {
@@ -447,6 +720,7 @@ impl StreamHandlerPoolReal {
                 }
             };
         }
+        inner.report_active_streams();
     }
 }
 
@@ -459,6 +733,7 @@ pub trait StreamHandlerPoolFactory {
         proxy_client_subs: ProxyClientSubs,
         exit_service_rate: u64,
         exit_byte_rate: u64,
+        exit_policy: ExitPolicy,
     ) -> Box<dyn StreamHandlerPool>;
 }
 
@@ -473,6 +748,7 @@ impl StreamHandlerPoolFactory for StreamHandlerPoolFactoryReal {
         proxy_client_subs: ProxyClientSubs,
         exit_service_rate: u64,
         exit_byte_rate: u64,
+        exit_policy: ExitPolicy,
     ) -> Box<dyn StreamHandlerPool> {
         Box::new(StreamHandlerPoolReal::new(
             resolver,
@@ -481,6 +757,7 @@ impl StreamHandlerPoolFactory for StreamHandlerPoolFactoryReal {
             proxy_client_subs,
             exit_service_rate,
             exit_byte_rate,
+            exit_policy,
         ))
     }
 }
@@ -493,9 +770,12 @@ mod tests {
     use crate::proxy_client::stream_establisher::StreamEstablisher;
     use crate::sub_lib::channel_wrappers::FuturesChannelFactoryReal;
     use crate::sub_lib::cryptde::PublicKey;
+    use crate::sub_lib::exit_policy::parse_exit_rules;
     use crate::sub_lib::hopper::ExpiredCoresPackage;
     use crate::sub_lib::hopper::MessageType;
+    use crate::sub_lib::peer_actors::PeerActors;
     use crate::sub_lib::proxy_server::ProxyProtocol;
+    use crate::sub_lib::udp_relay::UdpRelay;
     use crate::test_utils::channel_wrapper_mocks::FuturesChannelFactoryMock;
     use crate::test_utils::channel_wrapper_mocks::ReceiverWrapperMock;
     use crate::test_utils::channel_wrapper_mocks::SenderWrapperMock;
@@ -509,7 +789,11 @@ mod tests {
     use crate::test_utils::stream_connector_mock::StreamConnectorMock;
     use crate::test_utils::tokio_wrapper_mocks::ReadHalfWrapperMock;
     use crate::test_utils::tokio_wrapper_mocks::WriteHalfWrapperMock;
+    use crate::test_utils::udp_socket_wrapper_mocks::{
+        UdpSocketWrapperFactoryMock, UdpSocketWrapperMock,
+    };
     use crate::test_utils::{await_messages, make_wallet};
+    use actix::Actor;
     use actix::System;
     use masq_lib::constants::HTTP_PORT;
     use std::cell::RefCell;
@@ -522,6 +806,7 @@ mod tests {
     use std::sync::Arc;
     use std::sync::Mutex;
     use std::thread;
+    use std::time::{Duration, Instant};
     use tokio;
     use tokio::prelude::Async;
     use trust_dns_resolver::error::ResolveErrorKind;
@@ -584,6 +869,7 @@ mod tests {
                 }),
                 exit_service_rate: Default::default(),
                 exit_byte_rate: Default::default(),
+                exit_policy: ExitPolicy::default(),
             };
             let payload = ClientRequestPayload_0v1 {
                 stream_key,
@@ -610,6 +896,389 @@ mod tests {
         );
     }
 
+    fn make_inner_for_exit_policy_test(
+        peer_actors: &PeerActors,
+        resolver_mock: ResolverWrapperMock,
+        exit_policy: ExitPolicy,
+        test_name: &str,
+    ) -> StreamHandlerPoolRealInner {
+        let logger = Logger::new(test_name);
+        let establisher = StreamEstablisher {
+            cryptde: main_cryptde(),
+            stream_adder_tx: mpsc::channel().0,
+            stream_killer_tx: mpsc::channel().0,
+            stream_connector: Box::new(StreamConnectorMock::new()),
+            proxy_client_sub: peer_actors.proxy_client.inbound_server_data.clone(),
+            logger: logger.clone(),
+            channel_factory: Box::new(FuturesChannelFactoryMock::default()),
+        };
+        StreamHandlerPoolRealInner {
+            accountant_sub: peer_actors.accountant.report_exit_service_provided.clone(),
+            proxy_client_subs: peer_actors.proxy_client.clone(),
+            stream_writer_channels: HashMap::new(),
+            resolver: Box::new(resolver_mock),
+            logger,
+            establisher_factory: Box::new(StreamEstablisherFactoryMock {
+                make_results: RefCell::new(vec![establisher]),
+            }),
+            exit_service_rate: Default::default(),
+            exit_byte_rate: Default::default(),
+            exit_policy,
+        }
+    }
+
+    #[test]
+    fn resolving_to_a_private_address_sends_exit_policy_violation_and_terminates_stream() {
+        init_test_logging();
+        let (proxy_client, proxy_client_awaiter, proxy_client_recording) = make_recorder();
+        let stream_key = make_meaningless_stream_key();
+        thread::spawn(move || {
+            let system = System::new(
+                "resolving_to_a_private_address_sends_exit_policy_violation_and_terminates_stream",
+            );
+            let peer_actors = peer_actors_builder().proxy_client(proxy_client).build();
+            let resolver_mock = ResolverWrapperMock::new()
+                .lookup_ip_success(vec![IpAddr::from_str("192.168.0.1").unwrap()]);
+            let inner = make_inner_for_exit_policy_test(
+                &peer_actors,
+                resolver_mock,
+                ExitPolicy::default(),
+                "resolving_to_a_private_address_sends_exit_policy_violation_and_terminates_stream",
+            );
+            let payload = ClientRequestPayload_0v1 {
+                stream_key,
+                sequenced_packet: SequencedPacket::new(b"booga".to_vec(), 0, false),
+                target_hostname: Some("router.example.com".to_string()),
+                target_port: HTTP_PORT,
+                protocol: ProxyProtocol::HTTP,
+                originator_public_key: main_cryptde().public_key().clone(),
+            };
+
+            StreamHandlerPoolReal::process_package(payload, None, Arc::new(Mutex::new(inner)));
+
+            system.run();
+        });
+
+        proxy_client_awaiter.await_message_count(2);
+        let proxy_client_recording = proxy_client_recording.lock().unwrap();
+        assert_eq!(
+            proxy_client_recording.get_record::<ExitPolicyViolation_0v1>(0),
+            &ExitPolicyViolation_0v1::new(
+                stream_key,
+                "192.168.0.1 is a private address".to_string()
+            ),
+        );
+        assert_eq!(
+            proxy_client_recording.get_record::<InboundServerData>(1),
+            &InboundServerData {
+                stream_key,
+                last_data: true,
+                sequence_number: 0,
+                source: error_socket_addr(),
+                data: vec![],
+            }
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "WARN: resolving_to_a_private_address_sends_exit_policy_violation_and_terminates_stream: \
+             Refusing to provide exit services to router.example.com:80 for stream {}: \
+             192.168.0.1 is a private address",
+            stream_key
+        ));
+    }
+
+    #[test]
+    fn connecting_to_a_denied_ip_address_sends_exit_policy_violation() {
+        let (proxy_client, proxy_client_awaiter, proxy_client_recording) = make_recorder();
+        let stream_key = make_meaningless_stream_key();
+        let lookup_ip_parameters = Arc::new(Mutex::new(vec![]));
+        let lookup_ip_parameters_inner = lookup_ip_parameters.clone();
+        thread::spawn(move || {
+            let system =
+                System::new("connecting_to_a_denied_ip_address_sends_exit_policy_violation");
+            let peer_actors = peer_actors_builder().proxy_client(proxy_client).build();
+            let resolver_mock =
+                ResolverWrapperMock::new().lookup_ip_parameters(&lookup_ip_parameters_inner);
+            let inner = make_inner_for_exit_policy_test(
+                &peer_actors,
+                resolver_mock,
+                ExitPolicy {
+                    rules: parse_exit_rules("deny *:25").unwrap(),
+                    ..ExitPolicy::default()
+                },
+                "connecting_to_a_denied_ip_address_sends_exit_policy_violation",
+            );
+            let payload = ClientRequestPayload_0v1 {
+                stream_key,
+                sequenced_packet: SequencedPacket::new(b"HELO booga".to_vec(), 0, false),
+                target_hostname: Some("2.3.4.5".to_string()),
+                target_port: 25,
+                protocol: ProxyProtocol::TLS,
+                originator_public_key: main_cryptde().public_key().clone(),
+            };
+
+            StreamHandlerPoolReal::process_package(payload, None, Arc::new(Mutex::new(inner)));
+
+            system.run();
+        });
+
+        proxy_client_awaiter.await_message_count(2);
+        assert_eq!(lookup_ip_parameters.lock().unwrap().len(), 0);
+        let proxy_client_recording = proxy_client_recording.lock().unwrap();
+        assert_eq!(
+            proxy_client_recording.get_record::<ExitPolicyViolation_0v1>(0),
+            &ExitPolicyViolation_0v1::new(
+                stream_key,
+                "denied by exit policy rule 'deny *:25'".to_string()
+            ),
+        );
+        assert!(
+            proxy_client_recording
+                .get_record::<InboundServerData>(1)
+                .last_data
+        );
+    }
+
+    #[test]
+    fn enforce_exit_policy_drops_forbidden_addresses_and_keeps_the_rest() {
+        init_test_logging();
+        let _system =
+            System::new("enforce_exit_policy_drops_forbidden_addresses_and_keeps_the_rest");
+        let (proxy_client, _, proxy_client_recording) = make_recorder();
+        let proxy_client_addr = proxy_client.start();
+        let exit_policy_violated_sub = recipient!(proxy_client_addr, ExitPolicyViolation_0v1);
+        let payload = ClientRequestPayload_0v1 {
+            stream_key: make_meaningless_stream_key(),
+            sequenced_packet: SequencedPacket::new(vec![], 0, false),
+            target_hostname: Some("mixed.example.com".to_string()),
+            target_port: HTTP_PORT,
+            protocol: ProxyProtocol::HTTP,
+            originator_public_key: main_cryptde().public_key().clone(),
+        };
+
+        let result = StreamHandlerPoolReal::enforce_exit_policy(
+            &ExitPolicy::default(),
+            &exit_policy_violated_sub,
+            &Logger::new("enforce_exit_policy_drops_forbidden_addresses_and_keeps_the_rest"),
+            &payload,
+            "mixed.example.com",
+            vec![
+                IpAddr::from_str("10.1.2.3").unwrap(),
+                IpAddr::from_str("3.4.5.6").unwrap(),
+                IpAddr::from_str("::1").unwrap(),
+            ],
+        );
+
+        assert_eq!(result.unwrap(), vec![IpAddr::from_str("3.4.5.6").unwrap()]);
+        assert_eq!(proxy_client_recording.lock().unwrap().len(), 0);
+        TestLogHandler::new().exists_no_log_containing(
+            "enforce_exit_policy_drops_forbidden_addresses_and_keeps_the_rest: Refusing",
+        );
+    }
+
+    #[test]
+    fn set_exit_rates_changes_the_rates_reported_to_accountant() {
+        let _system = System::new("set_exit_rates_changes_the_rates_reported_to_accountant");
+        let peer_actors = peer_actors_builder().build();
+        let subject = StreamHandlerPoolReal::new(
+            Box::new(ResolverWrapperMock::new()),
+            main_cryptde(),
+            peer_actors.accountant.report_exit_service_provided.clone(),
+            peer_actors.proxy_client.clone(),
+            100,
+            200,
+            ExitPolicy::default(),
+        );
+
+        subject.set_exit_rates(300, 400);
+
+        let inner = subject.inner.lock().unwrap();
+        assert_eq!(inner.exit_service_rate, 300);
+        assert_eq!(inner.exit_byte_rate, 400);
+    }
+
+    #[test]
+    fn datagrams_for_one_association_share_a_socket_and_are_reported_to_accountant() {
+        let (accountant, accountant_awaiter, accountant_recording) = make_recorder();
+        let association_key = make_meaningless_stream_key();
+        let bind_params = Arc::new(Mutex::new(vec![]));
+        let bind_params_inner = bind_params.clone();
+        let send_to_params = Arc::new(Mutex::new(vec![]));
+        let send_to_params_inner = send_to_params.clone();
+        thread::spawn(move || {
+            let system = System::new(
+                "datagrams_for_one_association_share_a_socket_and_are_reported_to_accountant",
+            );
+            let peer_actors = peer_actors_builder().accountant(accountant).build();
+            let mut subject = StreamHandlerPoolReal::new(
+                Box::new(ResolverWrapperMock::new()),
+                main_cryptde(),
+                peer_actors.accountant.report_exit_service_provided.clone(),
+                peer_actors.proxy_client.clone(),
+                100,
+                200,
+                ExitPolicy::default(),
+            );
+            subject.udp_socket_factory = Box::new(
+                UdpSocketWrapperFactoryMock::new().make_result(
+                    UdpSocketWrapperMock::new()
+                        .bind_params(&bind_params_inner)
+                        .send_to_params(&send_to_params_inner),
+                ),
+            );
+            let make_payload = |data: &[u8]| DatagramRequestPayload_0v1 {
+                association_key,
+                target_hostname: "3.4.5.6".to_string(),
+                target_port: 53,
+                data: data.to_vec(),
+                originator_public_key: main_cryptde().public_key().clone(),
+            };
+
+            subject.process_datagram(make_payload(b"booga"), Some(make_wallet("paying")));
+            subject.process_datagram(make_payload(b"agoob"), None);
+
+            system.run();
+        });
+
+        accountant_awaiter.await_message_count(1);
+        assert_eq!(
+            accountant_recording
+                .lock()
+                .unwrap()
+                .get_record::<ReportExitServiceProvidedMessage>(0),
+            &ReportExitServiceProvidedMessage {
+                paying_wallet: make_wallet("paying"),
+                payload_size: 5,
+                service_rate: 100,
+                byte_rate: 200,
+            }
+        );
+        let target = SocketAddr::from_str("3.4.5.6:53").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while send_to_params.lock().unwrap().len() < 2 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            *send_to_params.lock().unwrap(),
+            vec![(b"booga".to_vec(), target), (b"agoob".to_vec(), target)]
+        );
+        assert_eq!(
+            *bind_params.lock().unwrap(),
+            vec![SocketAddr::from_str("0.0.0.0:0").unwrap()]
+        );
+    }
+
+    #[test]
+    fn datagram_forbidden_by_exit_policy_is_dropped() {
+        init_test_logging();
+        let (accountant, _, accountant_recording) = make_recorder();
+        let association_key = make_meaningless_stream_key();
+        let send_to_params = Arc::new(Mutex::new(vec![]));
+        let send_to_params_inner = send_to_params.clone();
+        thread::spawn(move || {
+            let system = System::new("datagram_forbidden_by_exit_policy_is_dropped");
+            let peer_actors = peer_actors_builder().accountant(accountant).build();
+            let mut subject = StreamHandlerPoolReal::new(
+                Box::new(ResolverWrapperMock::new()),
+                main_cryptde(),
+                peer_actors.accountant.report_exit_service_provided.clone(),
+                peer_actors.proxy_client.clone(),
+                100,
+                200,
+                ExitPolicy::default(),
+            );
+            subject.udp_socket_factory = Box::new(
+                UdpSocketWrapperFactoryMock::new()
+                    .make_result(UdpSocketWrapperMock::new().send_to_params(&send_to_params_inner)),
+            );
+
+            subject.process_datagram(
+                DatagramRequestPayload_0v1 {
+                    association_key,
+                    target_hostname: "192.168.0.1".to_string(),
+                    target_port: 53,
+                    data: b"booga".to_vec(),
+                    originator_public_key: main_cryptde().public_key().clone(),
+                },
+                Some(make_wallet("paying")),
+            );
+
+            system.run();
+        });
+
+        TestLogHandler::new().await_log_containing(
+            &format!(
+                "WARN: ProxyClient: Dropped 5-byte datagram to 192.168.0.1:53 for association {}: \
+                 192.168.0.1 is a private address",
+                association_key
+            ),
+            1000,
+        );
+        assert_eq!(send_to_params.lock().unwrap().len(), 0);
+        assert_eq!(accountant_recording.lock().unwrap().len(), 0);
+    }
+
+    fn make_pool_with_udp_association(
+        association_key: StreamKey,
+        ttl: Duration,
+    ) -> (StreamHandlerPoolReal, UdpRelay) {
+        let peer_actors = peer_actors_builder().build();
+        let mut subject = StreamHandlerPoolReal::new(
+            Box::new(ResolverWrapperMock::new()),
+            main_cryptde(),
+            peer_actors.accountant.report_exit_service_provided.clone(),
+            peer_actors.proxy_client.clone(),
+            100,
+            200,
+            ExitPolicy::default(),
+        );
+        let (association, relay) = UdpAssociation::new(
+            association_key,
+            Box::new(UdpSocketWrapperMock::new()),
+            false,
+            peer_actors.proxy_client.inbound_datagram,
+        );
+        subject.udp_associations = TtlHashMap::new(ttl);
+        subject
+            .udp_associations
+            .insert(association_key, association);
+        (subject, relay)
+    }
+
+    // The relay finishes once the pool has dropped the last handle to its association
+    fn relay_is_finished(relay: &mut UdpRelay) -> bool {
+        future::lazy(|| relay.poll()).wait().unwrap() == Async::Ready(())
+    }
+
+    #[test]
+    fn expired_udp_associations_are_closed_without_waiting_for_another_datagram() {
+        let _system =
+            System::new("expired_udp_associations_are_closed_without_waiting_for_another_datagram");
+        let (subject, mut relay) = make_pool_with_udp_association(
+            make_meaningless_stream_key(),
+            Duration::from_millis(50),
+        );
+
+        thread::sleep(Duration::from_millis(100));
+        subject.remove_expired_udp_associations();
+
+        assert!(relay_is_finished(&mut relay));
+    }
+
+    #[test]
+    fn refreshed_udp_association_outlives_its_idle_ttl() {
+        let _system = System::new("refreshed_udp_association_outlives_its_idle_ttl");
+        let association_key = make_meaningless_stream_key();
+        let (subject, mut relay) =
+            make_pool_with_udp_association(association_key, Duration::from_millis(200));
+
+        thread::sleep(Duration::from_millis(120));
+        subject.refresh_udp_association(association_key);
+        thread::sleep(Duration::from_millis(120));
+        subject.remove_expired_udp_associations();
+
+        assert!(!relay_is_finished(&mut relay));
+    }
+
     #[test]
     fn non_terminal_payload_can_be_sent_over_existing_connection() {
         let cryptde = main_cryptde();
@@ -650,6 +1319,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
             subject
                 .inner
@@ -711,6 +1381,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
             subject
                 .inner
@@ -793,6 +1464,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
             let (stream_killer_tx, stream_killer_rx) = mpsc::channel();
             subject.stream_killer_rx = stream_killer_rx;
@@ -902,6 +1574,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
             let (stream_killer_tx, stream_killer_rx) = mpsc::channel();
             subject.stream_killer_rx = stream_killer_rx;
@@ -989,6 +1662,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
 
             run_process_package_in_actix(subject, package);
@@ -1073,6 +1747,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
             let (stream_killer_tx, stream_killer_rx) = mpsc::channel();
             subject.stream_killer_rx = stream_killer_rx;
@@ -1166,6 +1841,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
             let (stream_killer_tx, stream_killer_rx) = mpsc::channel();
             subject.stream_killer_rx = stream_killer_rx;
@@ -1266,6 +1942,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
 
             let peer_addr = SocketAddr::from_str("3.4.5.6:80").unwrap();
@@ -1359,6 +2036,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
             run_process_package_in_actix(subject, package);
         });
@@ -1427,6 +2105,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
             subject
                 .inner
@@ -1484,6 +2163,7 @@ mod tests {
                 peer_actors.proxy_client.clone(),
                 100,
                 200,
+                ExitPolicy::default(),
             );
 
             subject.inner.lock().unwrap().establisher_factory =
@@ -1520,6 +2200,7 @@ mod tests {
             peer_actors.proxy_client,
             0,
             0,
+            ExitPolicy::default(),
         );
         let (stream_killer_tx, stream_killer_rx) = mpsc::channel();
         subject.stream_killer_rx = stream_killer_rx;
@@ -1563,6 +2244,7 @@ mod tests {
             peer_actors.proxy_client,
             0,
             0,
+            ExitPolicy::default(),
         );
         let (stream_killer_tx, stream_killer_rx) = mpsc::channel();
         subject.stream_killer_rx = stream_killer_rx;
@@ -1576,4 +2258,59 @@ mod tests {
         let proxy_client_recording = proxy_client_recording_arc.lock().unwrap();
         assert_eq!(proxy_client_recording.len(), 0);
     }
+
+    #[test]
+    fn do_housekeeping_reports_active_streams_to_metrics() {
+        let system = System::new("test");
+        let peer_actors = peer_actors_builder().build();
+        let mut subject = StreamHandlerPoolReal::new(
+            Box::new(ResolverWrapperMock::new()),
+            main_cryptde(),
+            peer_actors.accountant.report_exit_service_provided,
+            peer_actors.proxy_client,
+            0,
+            0,
+            ExitPolicy::default(),
+        );
+        let (stream_killer_tx, stream_killer_rx) = mpsc::channel();
+        subject.stream_killer_rx = stream_killer_rx;
+        let (stream_adder_tx, stream_adder_rx) = mpsc::channel();
+        subject.stream_adder_rx = stream_adder_rx;
+        let doomed_stream_key = make_meaningless_stream_key();
+        {
+            let mut inner = subject.inner.lock().unwrap();
+            inner.stream_writer_channels.insert(
+                doomed_stream_key,
+                Box::new(SenderWrapperMock::new(
+                    SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+                )),
+            );
+        }
+        stream_killer_tx.send((doomed_stream_key, 47)).unwrap();
+        vec!["2.3.4.5:6789", "3.4.5.6:7890"]
+            .into_iter()
+            .enumerate()
+            .for_each(|(index, addr)| {
+                let sender_wrapper: Box<dyn SenderWrapper<SequencedPacket>> =
+                    Box::new(SenderWrapperMock::new(SocketAddr::from_str(addr).unwrap()));
+                stream_adder_tx
+                    .send((
+                        StreamKey::new(
+                            PublicKey::new(&[index as u8]),
+                            SocketAddr::from_str(addr).unwrap(),
+                        ),
+                        sender_wrapper,
+                    ))
+                    .unwrap();
+            });
+
+        subject.do_housekeeping();
+
+        System::current().stop_with_code(0);
+        system.run();
+        assert_eq!(
+            metrics().value(&ACTIVE_STREAMS, &[("pool", "proxy_client")]),
+            Some(2.0)
+        );
+    }
 }
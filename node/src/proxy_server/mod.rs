@@ -4,12 +4,19 @@ pub mod client_request_payload_factory;
 pub mod http_protocol_pack;
 pub mod protocol_pack;
 pub mod server_impersonator_http;
+pub mod server_impersonator_socks;
 pub mod server_impersonator_tls;
+pub mod socks_handshake;
+pub mod socks_protocol_pack;
 pub mod tls_protocol_pack;
 
 use crate::proxy_server::client_request_payload_factory::ClientRequestPayloadFactory;
 use crate::proxy_server::http_protocol_pack::HttpProtocolPack;
-use crate::proxy_server::protocol_pack::{from_ibcd, from_protocol, ProtocolPack};
+use crate::proxy_server::protocol_pack::{from_ibcd, from_protocol, Host, ProtocolPack};
+use crate::proxy_server::socks_handshake::{
+    make_reply, make_reply_with_address, make_udp_datagram, parse_udp_datagram, SocksHandshake,
+    SocksOutcome, REPLY_GENERAL_FAILURE, REPLY_SUCCEEDED,
+};
 use crate::stream_messages::NonClandestineAttributes;
 use crate::stream_messages::RemovedStreamType;
 use crate::sub_lib::accountant::ReportExitServiceConsumedMessage;
@@ -21,14 +28,20 @@ use crate::sub_lib::dispatcher::InboundClientData;
 use crate::sub_lib::dispatcher::{Endpoint, StreamShutdownMsg};
 use crate::sub_lib::hopper::{ExpiredCoresPackage, IncipientCoresPackage};
 use crate::sub_lib::logger::Logger;
+use crate::sub_lib::neighborhood::ExpectedServices;
+use crate::sub_lib::neighborhood::NodeRatePackChangeMessage;
 use crate::sub_lib::neighborhood::RatePack;
 use crate::sub_lib::neighborhood::RouteQueryMessage;
 use crate::sub_lib::neighborhood::RouteQueryResponse;
 use crate::sub_lib::neighborhood::{ExpectedService, NodeRecordMetadataMessage};
-use crate::sub_lib::neighborhood::{ExpectedServices, DEFAULT_RATE_PACK};
 use crate::sub_lib::peer_actors::BindMessage;
-use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
+use crate::sub_lib::proxy_client::{
+    ClientResponsePayload_0v1, DatagramResponsePayload_0v1, DnsResolveFailure_0v1,
+    ExitPolicyViolation_0v1,
+};
 use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
+use crate::sub_lib::proxy_server::DatagramRequestPayload_0v1;
+use crate::sub_lib::proxy_server::ProxyProtocol;
 use crate::sub_lib::proxy_server::ProxyServerSubs;
 use crate::sub_lib::proxy_server::{
     AddReturnRouteMessage, AddRouteMessage, DEFAULT_MINIMUM_HOP_COUNT,
@@ -38,6 +51,8 @@ use crate::sub_lib::set_consuming_wallet_message::SetConsumingWalletMessage;
 use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
 use crate::sub_lib::stream_key::StreamKey;
 use crate::sub_lib::ttl_hashmap::TtlHashMap;
+use crate::sub_lib::udp_relay::{InboundDatagram, UdpAssociation};
+use crate::sub_lib::udp_socket_wrapper::{UdpSocketWrapperFactory, UdpSocketWrapperFactoryReal};
 use crate::sub_lib::utils::NODE_MAILBOX_CAPACITY;
 use crate::sub_lib::wallet::Wallet;
 use actix::Actor;
@@ -45,6 +60,8 @@ use actix::Addr;
 use actix::Context;
 use actix::Handler;
 use actix::Recipient;
+use masq_lib::constants::SOCKS_PORT;
+use masq_lib::utils::localhost;
 use pretty_hex::PrettyHex;
 use std::collections::HashMap;
 use std::net::SocketAddr;
@@ -65,6 +82,33 @@ struct ProxyServerOutSubs {
     add_return_route: Recipient<AddReturnRouteMessage>,
     add_route: Recipient<AddRouteMessage>,
     stream_shutdown_sub: Recipient<StreamShutdownMsg>,
+    inbound_datagram: Recipient<InboundDatagram>,
+}
+
+struct SocksTunnel {
+    target_hostname: String,
+    target_port: u16,
+    // Client data is numbered from the start of the handshake, but the exit Node expects the
+    // tunneled data to begin at zero; and the client has already received our handshake replies.
+    request_sequence_offset: u64,
+    response_sequence_offset: u64,
+}
+
+enum SocksStream {
+    Negotiating {
+        handshake: SocksHandshake,
+        replies_sent: u64,
+    },
+    Tunneling(SocksTunnel),
+    // The TCP connection only keeps a UDP association alive; the traffic is in datagrams.
+    UdpAssociated,
+}
+
+// The client-facing end of a SOCKS5 UDP association. The client's address isn't known until its
+// first datagram arrives; after that, datagrams from anywhere else are ignored.
+struct SocksUdpAssociation {
+    association: UdpAssociation,
+    client_addr_opt: Option<SocketAddr>,
 }
 
 pub struct ProxyServer {
@@ -72,7 +116,10 @@ pub struct ProxyServer {
     client_request_payload_factory: ClientRequestPayloadFactory,
     stream_key_factory: Box<dyn StreamKeyFactory>,
     keys_and_addrs: BidiHashMap<StreamKey, SocketAddr>,
-    tunneled_hosts: HashMap<StreamKey, String>,
+    tunneled_hosts: HashMap<StreamKey, (String, u16)>,
+    socks_streams: HashMap<StreamKey, SocksStream>,
+    udp_associations: HashMap<StreamKey, SocksUdpAssociation>,
+    udp_socket_factory: Box<dyn UdpSocketWrapperFactory>,
     stream_key_routes: HashMap<StreamKey, RouteQueryResponse>,
     is_decentralized: bool,
     consuming_wallet_balance: Option<i64>,
@@ -102,6 +149,7 @@ impl Handler<BindMessage> for ProxyServer {
             add_return_route: msg.peer_actors.proxy_server.add_return_route,
             add_route: msg.peer_actors.proxy_server.add_route,
             stream_shutdown_sub: msg.peer_actors.proxy_server.stream_shutdown_sub,
+            inbound_datagram: msg.peer_actors.proxy_server.inbound_datagram,
         };
         self.subs = Some(subs);
     }
@@ -123,7 +171,9 @@ impl Handler<InboundClientData> for ProxyServer {
     type Result = ();
 
     fn handle(&mut self, msg: InboundClientData, _ctx: &mut Self::Context) -> Self::Result {
-        if msg.is_connect() {
+        if msg.reception_port == Some(SOCKS_PORT) {
+            self.handle_socks_client_data(msg);
+        } else if msg.is_connect() {
             self.tls_connect(&msg);
             self.browser_proxy_sequence_offset = true;
         } else {
@@ -177,6 +227,18 @@ impl Handler<ExpiredCoresPackage<DnsResolveFailure_0v1>> for ProxyServer {
     }
 }
 
+impl Handler<ExpiredCoresPackage<ExitPolicyViolation_0v1>> for ProxyServer {
+    type Result = ();
+
+    fn handle(
+        &mut self,
+        msg: ExpiredCoresPackage<ExitPolicyViolation_0v1>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.handle_exit_policy_violation(&msg)
+    }
+}
+
 impl Handler<ExpiredCoresPackage<ClientResponsePayload_0v1>> for ProxyServer {
     type Result = ();
 
@@ -189,6 +251,34 @@ impl Handler<ExpiredCoresPackage<ClientResponsePayload_0v1>> for ProxyServer {
     }
 }
 
+impl Handler<ExpiredCoresPackage<DatagramResponsePayload_0v1>> for ProxyServer {
+    type Result = ();
+
+    fn handle(
+        &mut self,
+        msg: ExpiredCoresPackage<DatagramResponsePayload_0v1>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.handle_datagram_response_payload(&msg)
+    }
+}
+
+impl Handler<InboundDatagram> for ProxyServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: InboundDatagram, _ctx: &mut Self::Context) -> Self::Result {
+        self.handle_inbound_datagram(msg)
+    }
+}
+
+impl Handler<NodeRatePackChangeMessage> for ProxyServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: NodeRatePackChangeMessage, _ctx: &mut Self::Context) -> Self::Result {
+        self.handle_node_rate_pack_change(msg)
+    }
+}
+
 impl Handler<StreamShutdownMsg> for ProxyServer {
     type Result = ();
 
@@ -210,6 +300,9 @@ impl ProxyServer {
             stream_key_factory: Box::new(StreamKeyFactoryReal {}),
             keys_and_addrs: BidiHashMap::new(),
             tunneled_hosts: HashMap::new(),
+            socks_streams: HashMap::new(),
+            udp_associations: HashMap::new(),
+            udp_socket_factory: Box::new(UdpSocketWrapperFactoryReal {}),
             stream_key_routes: HashMap::new(),
             is_decentralized,
             consuming_wallet_balance,
@@ -231,10 +324,18 @@ impl ProxyServer {
             dns_failure_from_hopper: addr
                 .clone()
                 .recipient::<ExpiredCoresPackage<DnsResolveFailure_0v1>>(),
+            exit_policy_violation_from_hopper: addr
+                .clone()
+                .recipient::<ExpiredCoresPackage<ExitPolicyViolation_0v1>>(),
+            datagram_from_hopper: addr
+                .clone()
+                .recipient::<ExpiredCoresPackage<DatagramResponsePayload_0v1>>(),
+            inbound_datagram: addr.clone().recipient::<InboundDatagram>(),
             add_return_route: addr.clone().recipient::<AddReturnRouteMessage>(),
             add_route: addr.clone().recipient::<AddRouteMessage>(),
             stream_shutdown_sub: addr.clone().recipient::<StreamShutdownMsg>(),
             set_consuming_wallet_sub: addr.clone().recipient::<SetConsumingWalletMessage>(),
+            node_rate_pack_change: addr.clone().recipient::<NodeRatePackChangeMessage>(),
         }
     }
 
@@ -243,23 +344,7 @@ impl ProxyServer {
             Some(rri) => rri,
             None => return, // TODO: Eventually we'll have to do something better here, but we'll probably need some heuristics.
         };
-        let exit_public_key = {
-            // ugly, ugly
-            let self_public_key = self.main_cryptde.public_key();
-            return_route_info
-                .find_exit_node_key()
-                .unwrap_or_else(|| {
-                    if return_route_info.is_zero_hop() {
-                        &self_public_key
-                    } else {
-                        panic!(
-                            "Internal error: return_route_info for {} has no exit Node",
-                            return_route_info.return_route_id
-                        );
-                    }
-                })
-                .clone()
-        };
+        let exit_public_key = self.find_exit_public_key(&return_route_info);
         let response = &msg.payload;
         match self.keys_and_addrs.a_to_b(&response.stream_key) {
             Some(socket_addr) => {
@@ -282,7 +367,11 @@ impl ProxyServer {
                     .try_send(TransmitDataMsg {
                         endpoint: Endpoint::Socket(socket_addr),
                         last_data: true,
-                        sequence_number: Some(0), // DNS resolution errors always happen on the first request
+                        // DNS resolution errors always happen on the first request
+                        sequence_number: Some(
+                            self.socks_response_sequence_offset(&response.stream_key)
+                                .unwrap_or(0),
+                        ),
                         data: from_protocol(return_route_info.protocol)
                             .server_impersonator()
                             .dns_resolution_failure_response(
@@ -311,6 +400,77 @@ impl ProxyServer {
         }
     }
 
+    // Unlike a DNS failure, an exit policy violation says nothing about the exit Node's health,
+    // so the exit Node is not marked undesirable.
+    fn handle_exit_policy_violation(&mut self, msg: &ExpiredCoresPackage<ExitPolicyViolation_0v1>) {
+        let return_route_info = match self.get_return_route_info(&msg.remaining_route) {
+            Some(rri) => rri,
+            None => return,
+        };
+        let exit_public_key = self.find_exit_public_key(&return_route_info);
+        let violation = &msg.payload;
+        match self.keys_and_addrs.a_to_b(&violation.stream_key) {
+            Some(socket_addr) => {
+                warning!(
+                    self.logger,
+                    "Exit Node {} refused stream {}: {}",
+                    exit_public_key,
+                    &violation.stream_key,
+                    &violation.reason
+                );
+                self.report_response_services_consumed(&return_route_info, 0, msg.payload_len);
+                self.subs
+                    .as_ref()
+                    .expect("Dispatcher unbound in ProxyServer")
+                    .dispatcher
+                    .try_send(TransmitDataMsg {
+                        endpoint: Endpoint::Socket(socket_addr),
+                        last_data: true,
+                        sequence_number: Some(
+                            self.socks_response_sequence_offset(&violation.stream_key)
+                                .unwrap_or(0),
+                        ),
+                        data: from_protocol(return_route_info.protocol)
+                            .server_impersonator()
+                            .exit_policy_violation_response(
+                                &exit_public_key,
+                                return_route_info.server_name.clone(),
+                                &violation.reason,
+                            ),
+                    })
+                    .expect("Dispatcher is dead");
+                debug!(
+                    self.logger,
+                    "Retiring stream key {}: ExitPolicyViolation", &violation.stream_key
+                );
+                self.purge_stream_key(&violation.stream_key);
+            }
+            None => error!(
+                self.logger,
+                "Discarding ExitPolicyViolation message from an unrecognized stream key {:?}",
+                &violation.stream_key
+            ),
+        }
+    }
+
+    fn find_exit_public_key(&self, return_route_info: &AddReturnRouteMessage) -> PublicKey {
+        // ugly, ugly
+        let self_public_key = self.main_cryptde.public_key();
+        return_route_info
+            .find_exit_node_key()
+            .unwrap_or_else(|| {
+                if return_route_info.is_zero_hop() {
+                    self_public_key
+                } else {
+                    panic!(
+                        "Internal error: return_route_info for {} has no exit Node",
+                        return_route_info.return_route_id
+                    );
+                }
+            })
+            .clone()
+    }
+
     fn handle_client_response_payload(
         &mut self,
         msg: &ExpiredCoresPackage<ClientResponsePayload_0v1>,
@@ -343,7 +503,9 @@ impl ProxyServer {
                 let last_data = response.sequenced_packet.last_data;
                 let sequence_number = Some(
                     response.sequenced_packet.sequence_number
-                        + self.browser_proxy_sequence_offset as u64,
+                        + self
+                            .socks_response_sequence_offset(&response.stream_key)
+                            .unwrap_or(self.browser_proxy_sequence_offset as u64),
                 );
                 self
                     .subs
@@ -375,9 +537,12 @@ impl ProxyServer {
     fn tls_connect(&mut self, msg: &InboundClientData) {
         let http_data = HttpProtocolPack {}.find_host(&msg.data.clone().into());
         match http_data {
-            Some(ref host) if host.port == Some(443) => {
+            Some(Host {
+                ref name,
+                port: Some(port),
+            }) => {
                 let stream_key = self.make_stream_key(&msg);
-                self.tunneled_hosts.insert(stream_key, host.name.clone());
+                self.tunneled_hosts.insert(stream_key, (name.clone(), port));
                 self.subs
                     .as_ref()
                     .expect("Dispatcher unbound in ProxyServer")
@@ -406,6 +571,342 @@ impl ProxyServer {
         }
     }
 
+    fn handle_socks_client_data(&mut self, msg: InboundClientData) {
+        let stream_key = self.make_stream_key(&msg);
+        let (mut handshake, mut replies_sent) = match self.socks_streams.remove(&stream_key) {
+            Some(SocksStream::Tunneling(tunnel)) => {
+                self.socks_streams
+                    .insert(stream_key, SocksStream::Tunneling(tunnel));
+                self.handle_normal_client_data(msg, false);
+                return;
+            }
+            Some(SocksStream::UdpAssociated) => {
+                self.socks_streams
+                    .insert(stream_key, SocksStream::UdpAssociated);
+                debug!(
+                    self.logger,
+                    "Ignoring {} bytes on TCP connection of UDP association {}",
+                    msg.data.len(),
+                    stream_key
+                );
+                return;
+            }
+            Some(SocksStream::Negotiating {
+                handshake,
+                replies_sent,
+            }) => (handshake, replies_sent),
+            None => (SocksHandshake::new(), 0),
+        };
+        let mut progress = handshake.add_data(&msg.data);
+        match progress.outcome {
+            SocksOutcome::Pending => {
+                self.send_socks_replies(msg.peer_addr, progress.replies, &mut replies_sent, false);
+                self.socks_streams.insert(
+                    stream_key,
+                    SocksStream::Negotiating {
+                        handshake,
+                        replies_sent,
+                    },
+                );
+            }
+            SocksOutcome::Refused(reason) => {
+                warning!(
+                    self.logger,
+                    "Refusing SOCKS5 client at {}: {}",
+                    msg.peer_addr,
+                    reason
+                );
+                self.send_socks_replies(msg.peer_addr, progress.replies, &mut replies_sent, true);
+                self.purge_stream_key(&stream_key);
+            }
+            SocksOutcome::Connect(..) | SocksOutcome::UdpAssociate
+                if self.consuming_wallet_balance.is_none() && self.is_decentralized =>
+            {
+                progress.replies.push(
+                    from_protocol(ProxyProtocol::SOCKS5)
+                        .server_impersonator()
+                        .consuming_wallet_absent(),
+                );
+                self.send_socks_replies(msg.peer_addr, progress.replies, &mut replies_sent, true);
+                error!(
+                    self.logger,
+                    "SOCKS5 request rejected due to missing consuming wallet"
+                );
+                self.purge_stream_key(&stream_key);
+            }
+            SocksOutcome::UdpAssociate => {
+                let reply = match self.open_udp_association(stream_key) {
+                    Ok(bound_addr) => make_reply_with_address(REPLY_SUCCEEDED, bound_addr),
+                    Err(e) => {
+                        warning!(
+                            self.logger,
+                            "Refusing SOCKS5 client at {}: could not open UDP relay: {}",
+                            msg.peer_addr,
+                            e
+                        );
+                        progress.replies.push(make_reply(REPLY_GENERAL_FAILURE));
+                        self.send_socks_replies(
+                            msg.peer_addr,
+                            progress.replies,
+                            &mut replies_sent,
+                            true,
+                        );
+                        self.purge_stream_key(&stream_key);
+                        return;
+                    }
+                };
+                progress.replies.push(reply);
+                self.send_socks_replies(msg.peer_addr, progress.replies, &mut replies_sent, false);
+                self.socks_streams
+                    .insert(stream_key, SocksStream::UdpAssociated);
+            }
+            SocksOutcome::Connect(target, leftover) => {
+                progress.replies.push(make_reply(REPLY_SUCCEEDED));
+                self.send_socks_replies(msg.peer_addr, progress.replies, &mut replies_sent, false);
+                let target_port = target.port.expect("SOCKS5 CONNECT without port");
+                debug!(
+                    self.logger,
+                    "Tunneling SOCKS5 stream {} to {}:{}", stream_key, target.name, target_port
+                );
+                let sequence_number = msg.sequence_number.unwrap_or(0);
+                self.socks_streams.insert(
+                    stream_key,
+                    SocksStream::Tunneling(SocksTunnel {
+                        target_hostname: target.name,
+                        target_port,
+                        request_sequence_offset: if leftover.is_empty() {
+                            sequence_number + 1
+                        } else {
+                            sequence_number
+                        },
+                        response_sequence_offset: replies_sent,
+                    }),
+                );
+                if !leftover.is_empty() {
+                    self.handle_normal_client_data(
+                        InboundClientData {
+                            data: leftover,
+                            ..msg
+                        },
+                        false,
+                    );
+                }
+            }
+        }
+    }
+
+    fn send_socks_replies(
+        &self,
+        peer_addr: SocketAddr,
+        mut replies: Vec<Vec<u8>>,
+        replies_sent: &mut u64,
+        last_data: bool,
+    ) {
+        if last_data && replies.is_empty() {
+            replies.push(vec![]);
+        }
+        let reply_count = replies.len();
+        let dispatcher = &self.out_subs("Dispatcher").dispatcher;
+        replies.into_iter().enumerate().for_each(|(index, data)| {
+            dispatcher
+                .try_send(TransmitDataMsg {
+                    endpoint: Endpoint::Socket(peer_addr),
+                    last_data: last_data && (index + 1 == reply_count),
+                    sequence_number: Some(*replies_sent),
+                    data,
+                })
+                .expect("Dispatcher is dead");
+            *replies_sent += 1;
+        });
+    }
+
+    fn open_udp_association(&mut self, stream_key: StreamKey) -> std::io::Result<SocketAddr> {
+        let mut socket = self.udp_socket_factory.make();
+        socket.bind(SocketAddr::new(localhost(), 0))?;
+        let bound_addr = socket.local_addr()?;
+        let inbound_datagram_sub = self.out_subs("ProxyServer").inbound_datagram.clone();
+        let (association, relay) =
+            UdpAssociation::new(stream_key, socket, true, inbound_datagram_sub);
+        actix::spawn(relay);
+        debug!(
+            self.logger,
+            "Relaying datagrams for UDP association {} at {}", stream_key, bound_addr
+        );
+        self.udp_associations.insert(
+            stream_key,
+            SocksUdpAssociation {
+                association,
+                client_addr_opt: None,
+            },
+        );
+        Ok(bound_addr)
+    }
+
+    fn handle_inbound_datagram(&mut self, msg: InboundDatagram) {
+        let association_key = msg.association_key;
+        let udp_association = match self.udp_associations.get_mut(&association_key) {
+            Some(udp_association) => udp_association,
+            None => {
+                debug!(
+                    self.logger,
+                    "Discarding {}-byte datagram for vanished UDP association {}",
+                    msg.data.len(),
+                    association_key
+                );
+                return;
+            }
+        };
+        match udp_association.client_addr_opt {
+            None => udp_association.client_addr_opt = Some(msg.source),
+            Some(client_addr) if client_addr != msg.source => {
+                warning!(
+                    self.logger,
+                    "Discarding datagram from {}: UDP association {} belongs to {}",
+                    msg.source,
+                    association_key,
+                    client_addr
+                );
+                return;
+            }
+            Some(_) => (),
+        }
+        let (target, data) = match parse_udp_datagram(&msg.data) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                warning!(
+                    self.logger,
+                    "Discarding datagram from SOCKS5 client at {}: {}",
+                    msg.source,
+                    e
+                );
+                return;
+            }
+        };
+        let payload = DatagramRequestPayload_0v1 {
+            association_key,
+            target_hostname: target.name,
+            target_port: target.port.expect("SOCKS5 datagram without port"),
+            data,
+            originator_public_key: self.alias_cryptde.public_key().clone(),
+        };
+        self.transmit_datagram(payload);
+    }
+
+    fn transmit_datagram(&mut self, payload: DatagramRequestPayload_0v1) {
+        let route_source = self.out_subs("Neighborhood").route_source.clone();
+        let hopper = self.out_subs("Hopper").hopper.clone();
+        let accountant_exit_sub = self.out_subs("Accountant").accountant_exit.clone();
+        let accountant_routing_sub = self.out_subs("Accountant").accountant_routing.clone();
+        let add_return_route_sub = self.out_subs("ProxyServer").add_return_route.clone();
+        let add_route_sub = self.out_subs("ProxyServer").add_route.clone();
+        let logger = self.logger.clone();
+        let cryptde = self.main_cryptde.dup();
+        if let Some(route_query_response) = self.stream_key_routes.get(&payload.association_key) {
+            ProxyServer::transmit_datagram_to_hopper(
+                cryptde,
+                &hopper,
+                route_query_response.clone(),
+                payload,
+                &logger,
+                &accountant_exit_sub,
+                &accountant_routing_sub,
+                &add_return_route_sub,
+            );
+            return;
+        }
+        let minimum_hop_count = if self.is_decentralized {
+            DEFAULT_MINIMUM_HOP_COUNT
+        } else {
+            0
+        };
+        debug!(
+            logger,
+            "Getting route for new UDP association {}", payload.association_key
+        );
+        tokio::spawn(
+            route_source
+                .send(RouteQueryMessage::data_indefinite_route_request(
+                    minimum_hop_count,
+                ))
+                .then(move |route_result| {
+                    match route_result {
+                        Ok(Some(route_query_response)) => {
+                            add_route_sub
+                                .try_send(AddRouteMessage {
+                                    stream_key: payload.association_key,
+                                    route: route_query_response.clone(),
+                                })
+                                .expect("ProxyServer is dead");
+                            ProxyServer::transmit_datagram_to_hopper(
+                                cryptde,
+                                &hopper,
+                                route_query_response,
+                                payload,
+                                &logger,
+                                &accountant_exit_sub,
+                                &accountant_routing_sub,
+                                &add_return_route_sub,
+                            );
+                        }
+                        Ok(None) => error!(
+                            logger,
+                            "Failed to find route for datagram to {}", payload.target_hostname
+                        ),
+                        Err(e) => error!(
+                            logger,
+                            "Neighborhood refused to answer route request: {}", e
+                        ),
+                    };
+                    Ok(())
+                }),
+        );
+    }
+
+    fn handle_datagram_response_payload(
+        &mut self,
+        msg: &ExpiredCoresPackage<DatagramResponsePayload_0v1>,
+    ) {
+        let response = &msg.payload;
+        let return_route_info = match self.get_return_route_info(&msg.remaining_route) {
+            Some(rri) => rri,
+            None => return,
+        };
+        // The exit and relay Nodes did their work whether or not the client is still listening
+        self.report_response_services_consumed(
+            &return_route_info,
+            response.data.len(),
+            msg.payload_len,
+        );
+        let (udp_association, client_addr) = match self
+            .udp_associations
+            .get(&response.association_key)
+            .and_then(|ua| ua.client_addr_opt.map(|client_addr| (ua, client_addr)))
+        {
+            Some(pair) => pair,
+            None => {
+                debug!(
+                    self.logger,
+                    "Discarding {}-byte datagram from {} for vanished UDP association {}",
+                    response.data.len(),
+                    response.source,
+                    response.association_key
+                );
+                return;
+            }
+        };
+        let datagram = make_udp_datagram(response.source, &response.data);
+        if let Err(e) = udp_association.association.send(client_addr, datagram) {
+            debug!(self.logger, "Discarding datagram for client: {}", e);
+        }
+    }
+
+    fn socks_response_sequence_offset(&self, stream_key: &StreamKey) -> Option<u64> {
+        match self.socks_streams.get(stream_key) {
+            Some(SocksStream::Tunneling(tunnel)) => Some(tunnel.response_sequence_offset),
+            _ => None,
+        }
+    }
+
     fn out_subs(&self, actor_name: &str) -> &ProxyServerOutSubs {
         self.subs
             .as_ref()
@@ -545,6 +1046,31 @@ impl ProxyServer {
         }
     }
 
+    // Routes are chosen once per stream and then billed from the rate packs they were chosen with,
+    // so they have to follow the rates the Neighborhood learns about later.
+    fn handle_node_rate_pack_change(&mut self, msg: NodeRatePackChangeMessage) {
+        debug!(
+            self.logger,
+            "Billing services from {} at new rates: {}", msg.public_key, msg.rate_pack
+        );
+        let update = |services: &mut Vec<ExpectedService>| {
+            services
+                .iter_mut()
+                .for_each(|service| service.update_rate_pack(&msg.public_key, &msg.rate_pack))
+        };
+        self.stream_key_routes
+            .values_mut()
+            .for_each(|route| match &mut route.expected_services {
+                ExpectedServices::OneWay(services) => update(services),
+                ExpectedServices::RoundTrip(over, back, _) => {
+                    update(over);
+                    update(back);
+                }
+            });
+        self.route_ids_to_return_routes
+            .update_values(|return_route| update(&mut return_route.expected_services));
+    }
+
     fn handle_stream_shutdown_msg(&mut self, msg: StreamShutdownMsg) {
         let nca = match msg.stream_type {
             RemovedStreamType::Clandestine => {
@@ -564,7 +1090,11 @@ impl ProxyServer {
             }
             Some(sk) => sk,
         };
-        if msg.report_to_counterpart {
+        let has_counterpart = !matches!(
+            self.socks_streams.get(&stream_key),
+            Some(SocksStream::Negotiating { .. }) | Some(SocksStream::UdpAssociated)
+        );
+        if msg.report_to_counterpart && has_counterpart {
             debug!(
                 self.logger,
                 "Reporting shutdown of {} to counterpart", &stream_key
@@ -616,6 +1146,8 @@ impl ProxyServer {
         let _ = self.keys_and_addrs.remove_a(stream_key);
         let _ = self.stream_key_routes.remove(stream_key);
         let _ = self.tunneled_hosts.remove(stream_key);
+        let _ = self.socks_streams.remove(stream_key);
+        let _ = self.udp_associations.remove(stream_key);
     }
 
     fn make_payload(
@@ -624,12 +1156,22 @@ impl ProxyServer {
         stream_key: &StreamKey,
     ) -> Result<ClientRequestPayload_0v1, ()> {
         let tunnelled_host = self.tunneled_hosts.get(stream_key);
-        let new_ibcd = match tunnelled_host {
-            Some(_) => InboundClientData {
+        let socks_tunnel = match self.socks_streams.get(stream_key) {
+            Some(SocksStream::Tunneling(tunnel)) => Some(tunnel),
+            _ => None,
+        };
+        let new_ibcd = match (tunnelled_host, socks_tunnel) {
+            (Some(_), _) => InboundClientData {
                 reception_port: Some(443),
                 ..ibcd
             },
-            None => ibcd,
+            (None, Some(tunnel)) => InboundClientData {
+                sequence_number: ibcd
+                    .sequence_number
+                    .map(|sn| sn.saturating_sub(tunnel.request_sequence_offset)),
+                ..ibcd
+            },
+            (None, None) => ibcd,
         };
         match self.client_request_payload_factory.make(
             &new_ibcd,
@@ -641,12 +1183,18 @@ impl ProxyServer {
                 error!(self.logger, "Couldn't create ClientRequestPayload");
                 Err(())
             }
-            Some(payload) => match tunnelled_host {
-                Some(hostname) => Ok(ClientRequestPayload_0v1 {
+            Some(payload) => match (tunnelled_host, socks_tunnel) {
+                (Some((hostname, port)), _) => Ok(ClientRequestPayload_0v1 {
                     target_hostname: Some(hostname.clone()),
+                    target_port: *port,
+                    ..payload
+                }),
+                (None, Some(tunnel)) => Ok(ClientRequestPayload_0v1 {
+                    target_hostname: Some(tunnel.target_hostname.clone()),
+                    target_port: tunnel.target_port,
                     ..payload
                 }),
-                None => Ok(payload),
+                (None, None) => Ok(payload),
             },
         }
     }
@@ -683,7 +1231,7 @@ impl ProxyServer {
                 ProxyServer::report_exit_service(
                     accountant_exit_sub,
                     over.clone(),
-                    &payload,
+                    payload.sequenced_packet.data.len(),
                     &logger,
                 );
                 ProxyServer::transmit_to_hopper(
@@ -724,12 +1272,12 @@ impl ProxyServer {
         }
         earning_wallets_and_rates
             .into_iter()
-            .for_each(|(earning_wallet, _rate_pack)| {
+            .for_each(|(earning_wallet, rate_pack)| {
                 let report_routing_service_consumed = ReportRoutingServiceConsumedMessage {
                     earning_wallet: earning_wallet.clone(),
                     payload_size,
-                    service_rate: DEFAULT_RATE_PACK.routing_service_rate,
-                    byte_rate: DEFAULT_RATE_PACK.routing_byte_rate,
+                    service_rate: rate_pack.routing_service_rate,
+                    byte_rate: rate_pack.routing_byte_rate,
                 };
                 accountant_routing_sub
                     .try_send(report_routing_service_consumed)
@@ -740,7 +1288,7 @@ impl ProxyServer {
     fn report_exit_service(
         accountant_exit_sub: &Recipient<ReportExitServiceConsumedMessage>,
         expected_services: Vec<ExpectedService>,
-        payload: &ClientRequestPayload_0v1,
+        payload_size: usize,
         logger: &Logger,
     ) {
         match expected_services
@@ -751,13 +1299,12 @@ impl ProxyServer {
                 }
                 _ => None,
             }) {
-            Some((earning_wallet, _rate_pack)) => {
-                let payload_size = payload.sequenced_packet.data.len();
+            Some((earning_wallet, rate_pack)) => {
                 let report_exit_service_consumed_message = ReportExitServiceConsumedMessage {
                     earning_wallet: earning_wallet.clone(),
                     payload_size,
-                    service_rate: DEFAULT_RATE_PACK.exit_service_rate,
-                    byte_rate: DEFAULT_RATE_PACK.exit_byte_rate,
+                    service_rate: rate_pack.exit_service_rate,
+                    byte_rate: rate_pack.exit_byte_rate,
                 };
                 accountant_exit_sub
                     .try_send(report_exit_service_consumed_message)
@@ -780,18 +1327,10 @@ impl ProxyServer {
         accountant_routing_sub: &Recipient<ReportRoutingServiceConsumedMessage>,
         retire_stream_key_via: Option<&Recipient<StreamShutdownMsg>>,
     ) {
-        let destination_key_opt = if !expected_services.is_empty()
-            && expected_services
-                .iter()
-                .all(|expected_service| matches!(expected_service, ExpectedService::Nothing))
-        {
-            Some(payload.originator_public_key.clone())
-        } else {
-            expected_services.iter().find_map(|service| match service {
-                ExpectedService::Exit(public_key, _, _) => Some(public_key.clone()),
-                _ => None,
-            })
-        };
+        let destination_key_opt = ProxyServer::find_payload_destination_key(
+            &expected_services,
+            &payload.originator_public_key,
+        );
 
         match destination_key_opt {
             None => ProxyServer::handle_route_failure(payload, &logger, source_addr, dispatcher),
@@ -838,22 +1377,109 @@ impl ProxyServer {
         }
     }
 
-    fn handle_route_failure(
-        payload: ClientRequestPayload_0v1,
-        logger: &Logger,
-        source_addr: SocketAddr,
-        dispatcher: &Recipient<TransmitDataMsg>,
-    ) {
-        let target_hostname = ProxyServer::hostname(&payload);
-        ProxyServer::send_route_failure(payload, source_addr, dispatcher);
-        error!(logger, "Failed to find route to {}", target_hostname);
+    // A zero-hop route has no exit Node: the payload is for this Node's own ProxyClient.
+    fn find_payload_destination_key(
+        expected_services: &[ExpectedService],
+        originator_public_key: &PublicKey,
+    ) -> Option<PublicKey> {
+        if !expected_services.is_empty()
+            && expected_services
+                .iter()
+                .all(|expected_service| matches!(expected_service, ExpectedService::Nothing))
+        {
+            Some(originator_public_key.clone())
+        } else {
+            expected_services.iter().find_map(|service| match service {
+                ExpectedService::Exit(public_key, _, _) => Some(public_key.clone()),
+                _ => None,
+            })
+        }
     }
 
-    fn send_route_failure(
-        payload: ClientRequestPayload_0v1,
-        source_addr: SocketAddr,
-        dispatcher: &Recipient<TransmitDataMsg>,
-    ) {
+    #[allow(clippy::too_many_arguments)]
+    fn transmit_datagram_to_hopper(
+        cryptde: Box<dyn CryptDE>,
+        hopper: &Recipient<IncipientCoresPackage>,
+        route_query_response: RouteQueryResponse,
+        payload: DatagramRequestPayload_0v1,
+        logger: &Logger,
+        accountant_exit_sub: &Recipient<ReportExitServiceConsumedMessage>,
+        accountant_routing_sub: &Recipient<ReportRoutingServiceConsumedMessage>,
+        add_return_route_sub: &Recipient<AddReturnRouteMessage>,
+    ) {
+        let (over, back, return_route_id) = match route_query_response.expected_services {
+            ExpectedServices::RoundTrip(over, back, return_route_id) => {
+                (over, back, return_route_id)
+            }
+            _ => panic!("Expected RoundTrip ExpectedServices but got OneWay"),
+        };
+        let payload_destination_key = match ProxyServer::find_payload_destination_key(
+            &over,
+            &payload.originator_public_key,
+        ) {
+            Some(key) => key,
+            None => {
+                error!(
+                    logger,
+                    "Failed to find route for datagram to {}", payload.target_hostname
+                );
+                return;
+            }
+        };
+        add_return_route_sub
+            .try_send(AddReturnRouteMessage {
+                return_route_id,
+                expected_services: back,
+                protocol: ProxyProtocol::SOCKS5,
+                server_name: Some(payload.target_hostname.clone()),
+            })
+            .expect("ProxyServer is dead");
+        ProxyServer::report_exit_service(
+            accountant_exit_sub,
+            over.clone(),
+            payload.data.len(),
+            logger,
+        );
+        debug!(
+            logger,
+            "Transmitting {}-byte datagram for UDP association {} to {}:{}",
+            payload.data.len(),
+            payload.association_key,
+            payload.target_hostname,
+            payload.target_port
+        );
+        let pkg = IncipientCoresPackage::new(
+            cryptde.as_ref(),
+            route_query_response.route,
+            payload.into(),
+            &payload_destination_key,
+        )
+        .expect("Key magically disappeared");
+        ProxyServer::report_routing_service(
+            accountant_routing_sub,
+            over,
+            pkg.payload.len(),
+            logger,
+        );
+        hopper.try_send(pkg).expect("Hopper is dead");
+    }
+
+    fn handle_route_failure(
+        payload: ClientRequestPayload_0v1,
+        logger: &Logger,
+        source_addr: SocketAddr,
+        dispatcher: &Recipient<TransmitDataMsg>,
+    ) {
+        let target_hostname = ProxyServer::hostname(&payload);
+        ProxyServer::send_route_failure(payload, source_addr, dispatcher);
+        error!(logger, "Failed to find route to {}", target_hostname);
+    }
+
+    fn send_route_failure(
+        payload: ClientRequestPayload_0v1,
+        source_addr: SocketAddr,
+        dispatcher: &Recipient<TransmitDataMsg>,
+    ) {
         let data = from_protocol(payload.protocol)
             .server_impersonator()
             .route_query_failure_response(&ProxyServer::hostname(&payload));
@@ -905,7 +1531,7 @@ impl ProxyServer {
             .iter()
             .for_each(|service| match service {
                 ExpectedService::Nothing => (),
-                ExpectedService::Exit(_, wallet, _rate_pack) => self
+                ExpectedService::Exit(_, wallet, rate_pack) => self
                     .subs
                     .as_ref()
                     .expect("ProxyServer unbound")
@@ -913,11 +1539,11 @@ impl ProxyServer {
                     .try_send(ReportExitServiceConsumedMessage {
                         earning_wallet: wallet.clone(),
                         payload_size: exit_size,
-                        service_rate: DEFAULT_RATE_PACK.exit_service_rate,
-                        byte_rate: DEFAULT_RATE_PACK.exit_byte_rate,
+                        service_rate: rate_pack.exit_service_rate,
+                        byte_rate: rate_pack.exit_byte_rate,
                     })
                     .expect("Accountant is dead"),
-                ExpectedService::Routing(_, wallet, _rate_pack) => self
+                ExpectedService::Routing(_, wallet, rate_pack) => self
                     .subs
                     .as_ref()
                     .expect("ProxyServer unbound")
@@ -925,8 +1551,8 @@ impl ProxyServer {
                     .try_send(ReportRoutingServiceConsumedMessage {
                         earning_wallet: wallet.clone(),
                         payload_size: routing_size,
-                        service_rate: DEFAULT_RATE_PACK.routing_service_rate,
-                        byte_rate: DEFAULT_RATE_PACK.routing_byte_rate,
+                        service_rate: rate_pack.routing_service_rate,
+                        byte_rate: rate_pack.routing_byte_rate,
                     })
                     .expect("Accountant is dead"),
             });
@@ -952,6 +1578,7 @@ mod tests {
     use crate::blockchain::blockchain_interface::contract_address;
     use crate::proxy_server::protocol_pack::ServerImpersonator;
     use crate::proxy_server::server_impersonator_http::ServerImpersonatorHttp;
+    use crate::proxy_server::server_impersonator_socks::ServerImpersonatorSocks;
     use crate::proxy_server::server_impersonator_tls::ServerImpersonatorTls;
     use crate::stream_messages::{NonClandestineAttributes, RemovedStreamType};
     use crate::sub_lib::accountant::ReportRoutingServiceConsumedMessage;
@@ -963,7 +1590,9 @@ mod tests {
     use crate::sub_lib::hopper::MessageType;
     use crate::sub_lib::neighborhood::ExpectedServices;
     use crate::sub_lib::neighborhood::{ExpectedService, DEFAULT_RATE_PACK};
-    use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
+    use crate::sub_lib::proxy_client::{
+        ClientResponsePayload_0v1, DnsResolveFailure_0v1, ExitPolicyViolation_0v1,
+    };
     use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
     use crate::sub_lib::proxy_server::ProxyProtocol;
     use crate::sub_lib::route::Route;
@@ -979,6 +1608,9 @@ mod tests {
     use crate::test_utils::recorder::peer_actors_builder;
     use crate::test_utils::recorder::Recorder;
     use crate::test_utils::recorder::Recording;
+    use crate::test_utils::udp_socket_wrapper_mocks::{
+        UdpSocketWrapperFactoryMock, UdpSocketWrapperMock,
+    };
     use crate::test_utils::zero_hop_route_response;
     use crate::test_utils::{alias_cryptde, rate_pack};
     use crate::test_utils::{main_cryptde, make_wallet};
@@ -1013,6 +1645,7 @@ mod tests {
                 add_return_route: addr.clone().recipient::<AddReturnRouteMessage>(),
                 add_route: addr.clone().recipient::<AddRouteMessage>(),
                 stream_shutdown_sub: addr.clone().recipient::<StreamShutdownMsg>(),
+                inbound_datagram: addr.clone().recipient::<InboundDatagram>(),
             }
         }
     }
@@ -1085,14 +1718,15 @@ mod tests {
         idx: usize,
         wallet: &Wallet,
         payload_size: usize,
+        rate_pack: &RatePack,
     ) {
         assert_eq!(
             accountant_recording.get_record::<ReportExitServiceConsumedMessage>(idx),
             &ReportExitServiceConsumedMessage {
                 earning_wallet: wallet.clone(),
                 payload_size,
-                service_rate: DEFAULT_RATE_PACK.exit_service_rate,
-                byte_rate: DEFAULT_RATE_PACK.exit_byte_rate,
+                service_rate: rate_pack.exit_service_rate,
+                byte_rate: rate_pack.exit_byte_rate,
             }
         );
     }
@@ -1102,14 +1736,15 @@ mod tests {
         idx: usize,
         wallet: &Wallet,
         payload_size: usize,
+        rate_pack: &RatePack,
     ) {
         assert_eq!(
             accountant_recording.get_record::<ReportRoutingServiceConsumedMessage>(idx),
             &ReportRoutingServiceConsumedMessage {
                 earning_wallet: wallet.clone(),
                 payload_size,
-                service_rate: DEFAULT_RATE_PACK.routing_service_rate,
-                byte_rate: DEFAULT_RATE_PACK.routing_byte_rate,
+                service_rate: rate_pack.routing_service_rate,
+                byte_rate: rate_pack.routing_byte_rate,
             }
         );
     }
@@ -1390,9 +2025,167 @@ mod tests {
     }
 
     #[test]
-    fn proxy_server_sends_route_failure_for_connect_requests_to_ports_other_than_443() {
+    fn handle_client_response_payload_skips_sequence_numbers_used_by_socks5_handshake() {
+        let system = System::new(
+            "handle_client_response_payload_skips_sequence_numbers_used_by_socks5_handshake",
+        );
+        let (dispatcher_mock, _, dispatcher_log_arc) = make_recorder();
+        let cryptde = main_cryptde();
+        let mut subject = ProxyServer::new(
+            cryptde,
+            alias_cryptde(),
+            false,
+            Some(STANDARD_CONSUMING_WALLET_BALANCE),
+        );
+        let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+        let stream_key = make_meaningless_stream_key();
+        subject.keys_and_addrs.insert(stream_key, socket_addr);
+        subject.socks_streams.insert(
+            stream_key,
+            SocksStream::Tunneling(SocksTunnel {
+                target_hostname: "booga.com".to_string(),
+                target_port: 8443,
+                request_sequence_offset: 2,
+                response_sequence_offset: 2,
+            }),
+        );
+        // If this leaked into the SOCKS5 stream, the sequence number would be off by one
+        subject.browser_proxy_sequence_offset = true;
+        subject.route_ids_to_return_routes.insert(
+            1234,
+            AddReturnRouteMessage {
+                return_route_id: 1234,
+                expected_services: vec![ExpectedService::Nothing],
+                protocol: ProxyProtocol::SOCKS5,
+                server_name: Some("booga.com".to_string()),
+            },
+        );
+        let subject_addr: Addr<ProxyServer> = subject.start();
+        let expired_cores_package: ExpiredCoresPackage<ClientResponsePayload_0v1> =
+            ExpiredCoresPackage::new(
+                SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+                Some(make_wallet("irrelevant")),
+                return_route_with_id(cryptde, 1234),
+                ClientResponsePayload_0v1 {
+                    stream_key,
+                    sequenced_packet: SequencedPacket {
+                        data: b"server hello".to_vec(),
+                        sequence_number: 0,
+                        last_data: false,
+                    },
+                },
+                0,
+            );
+        let mut peer_actors = peer_actors_builder().dispatcher(dispatcher_mock).build();
+        peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr.try_send(expired_cores_package).unwrap();
+
+        System::current().stop();
+        system.run();
+        let dispatcher_recording = dispatcher_log_arc.lock().unwrap();
+        let record = dispatcher_recording.get_record::<TransmitDataMsg>(0);
+        assert_eq!(record.sequence_number, Some(2));
+        assert_eq!(record.data, b"server hello".to_vec());
+    }
+
+    #[test]
+    fn proxy_server_receives_connect_to_port_other_than_443_and_tunnels_to_that_port() {
+        let main_cryptde = main_cryptde();
+        let alias_cryptde = alias_cryptde();
+        let key = alias_cryptde.public_key();
+        let http_request =
+            b"CONNECT imap.realdomain.nu:993 HTTP/1.1\r\nHost: imap.realdomain.nu:993\r\n\r\n";
+        let (hopper_mock, hopper_awaiter, hopper_recording_arc) = make_recorder();
+        let (neighborhood_mock, _, _) = make_recorder();
+        let neighborhood_mock = neighborhood_mock.route_query_response(Some(
+            zero_hop_route_response(main_cryptde.public_key(), main_cryptde),
+        ));
+        let route = zero_hop_route_response(main_cryptde.public_key(), main_cryptde).route;
+        let (dispatcher_mock, _, dispatcher_recording_arc) = make_recorder();
+        let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+        let stream_key = make_meaningless_stream_key();
+        let msg_from_dispatcher = InboundClientData {
+            peer_addr: socket_addr,
+            reception_port: Some(8443),
+            sequence_number: Some(0),
+            last_data: false,
+            is_clandestine: false,
+            data: http_request.to_vec(),
+        };
+        let tunnelled_msg = InboundClientData {
+            peer_addr: socket_addr,
+            reception_port: Some(8443),
+            sequence_number: Some(0),
+            last_data: false,
+            is_clandestine: false,
+            data: b"client hello".to_vec(),
+        };
+        let expected_payload = ClientRequestPayload_0v1 {
+            stream_key,
+            sequenced_packet: SequencedPacket {
+                data: b"client hello".to_vec(),
+                sequence_number: 0,
+                last_data: false,
+            },
+            target_hostname: Some(String::from("imap.realdomain.nu")),
+            target_port: 993,
+            protocol: ProxyProtocol::TLS,
+            originator_public_key: key.clone(),
+        };
+        let expected_pkg =
+            IncipientCoresPackage::new(main_cryptde, route, expected_payload.into(), key).unwrap();
+
+        thread::spawn(move || {
+            let stream_key_factory = StreamKeyFactoryMock::new().make_result(stream_key);
+            let system = System::new(
+                "proxy_server_receives_connect_to_port_other_than_443_and_tunnels_to_that_port",
+            );
+            let mut subject = ProxyServer::new(
+                main_cryptde,
+                alias_cryptde,
+                false,
+                Some(STANDARD_CONSUMING_WALLET_BALANCE),
+            );
+            subject.stream_key_factory = Box::new(stream_key_factory);
+            let subject_addr: Addr<ProxyServer> = subject.start();
+            let mut peer_actors = peer_actors_builder()
+                .dispatcher(dispatcher_mock)
+                .hopper(hopper_mock)
+                .neighborhood(neighborhood_mock)
+                .build();
+            peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+            subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+            subject_addr.try_send(msg_from_dispatcher).unwrap();
+            subject_addr.try_send(tunnelled_msg).unwrap();
+            system.run();
+        });
+
+        hopper_awaiter.await_message_count(1);
+        let dispatcher_recording = dispatcher_recording_arc.lock().unwrap();
+        assert_eq!(
+            dispatcher_recording.get_record::<TransmitDataMsg>(0),
+            &TransmitDataMsg {
+                endpoint: Endpoint::Socket(socket_addr),
+                last_data: false,
+                sequence_number: Some(0),
+                data: b"HTTP/1.1 200 OK\r\n\r\n".to_vec(),
+            }
+        );
+        let hopper_recording = hopper_recording_arc.lock().unwrap();
+        assert_eq!(
+            hopper_recording.get_record::<IncipientCoresPackage>(0),
+            &expected_pkg
+        );
+    }
+
+    #[test]
+    fn proxy_server_sends_bad_request_for_connect_requests_that_name_no_port() {
         let cryptde = main_cryptde();
-        let http_request = b"CONNECT https://realdomain.nu:8443 HTTP/1.1\r\nHost: https://bunkjunk.wrong:443\r\n\r\n";
+        let http_request =
+            b"CONNECT https://realdomain.nu HTTP/1.1\r\nHost: https://bunkjunk.wrong\r\n\r\n";
 
         let (hopper_mock, _hopper_awaiter, _hopper_recording_arc) = make_recorder();
         let (neighborhood_mock, _, _neighborhood_recording_arc) = make_recorder();
@@ -1474,59 +2267,527 @@ mod tests {
 
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
         let stream_key = make_meaningless_stream_key();
-        let request_data = http_request.to_vec();
+        let request_data = http_request.to_vec();
+        let msg_from_dispatcher = InboundClientData {
+            peer_addr: socket_addr.clone(),
+            reception_port: Some(8443),
+            sequence_number: Some(0),
+            last_data: false,
+            is_clandestine: false,
+            data: request_data.clone(),
+        };
+
+        let stream_key_parameters_arc = Arc::new(Mutex::new(vec![]));
+        let stream_key_parameters_arc_thread = stream_key_parameters_arc.clone();
+
+        thread::spawn(move || {
+            let stream_key_factory = StreamKeyFactoryMock::new()
+                .make_parameters(&stream_key_parameters_arc_thread)
+                .make_result(stream_key);
+            let system = System::new(
+                "proxy_server_receives_connect_responds_with_ok_and_stores_stream_key_and_hostname",
+            );
+            let mut subject = ProxyServer::new(
+                cryptde,
+                alias_cryptde(),
+                false,
+                Some(STANDARD_CONSUMING_WALLET_BALANCE),
+            );
+            subject.stream_key_factory = Box::new(stream_key_factory);
+            let subject_addr: Addr<ProxyServer> = subject.start();
+            let mut peer_actors = peer_actors_builder()
+                .dispatcher(dispatcher_mock)
+                .hopper(hopper_mock)
+                .neighborhood(neighborhood_mock)
+                .build();
+            peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+            subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+            subject_addr.try_send(msg_from_dispatcher).unwrap();
+            system.run();
+        });
+
+        thread::sleep(Duration::from_millis(500));
+
+        let expected_transmit_data_msg = TransmitDataMsg {
+            endpoint: Endpoint::Socket(socket_addr),
+            last_data: true,
+            sequence_number: Some(0),
+            data: b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n".to_vec(),
+        };
+
+        let dispatcher_recording = dispatcher_recording_arc.lock().unwrap();
+        let record = dispatcher_recording.get_record::<TransmitDataMsg>(0);
+
+        assert_eq!(&expected_transmit_data_msg, record);
+    }
+
+    #[test]
+    fn proxy_server_negotiates_socks5_connect_then_tunnels_data_to_hopper() {
+        let main_cryptde = main_cryptde();
+        let alias_cryptde = alias_cryptde();
+        let key = alias_cryptde.public_key();
+        let (hopper_mock, hopper_awaiter, hopper_recording_arc) = make_recorder();
+        let (neighborhood_mock, _, _) = make_recorder();
+        let neighborhood_mock = neighborhood_mock.route_query_response(Some(
+            zero_hop_route_response(main_cryptde.public_key(), main_cryptde),
+        ));
+        let route = zero_hop_route_response(main_cryptde.public_key(), main_cryptde).route;
+        let (dispatcher_mock, _, dispatcher_recording_arc) = make_recorder();
+        let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+        let stream_key = make_meaningless_stream_key();
+        let make_ibcd = move |sequence_number: u64, data: &[u8]| InboundClientData {
+            peer_addr: socket_addr,
+            reception_port: Some(SOCKS_PORT),
+            sequence_number: Some(sequence_number),
+            last_data: false,
+            is_clandestine: false,
+            data: data.to_vec(),
+        };
+        let greeting = make_ibcd(0, &[0x05, 0x01, 0x00]);
+        let mut request_data = vec![0x05, 0x01, 0x00, 0x03, 9];
+        request_data.extend(b"booga.com");
+        request_data.extend(&[0x20, 0xFB]);
+        let request = make_ibcd(1, &request_data);
+        let tunneled_data = make_ibcd(2, b"client hello");
+        let expected_payload = ClientRequestPayload_0v1 {
+            stream_key,
+            sequenced_packet: SequencedPacket {
+                data: b"client hello".to_vec(),
+                sequence_number: 0,
+                last_data: false,
+            },
+            target_hostname: Some(String::from("booga.com")),
+            target_port: 8443,
+            protocol: ProxyProtocol::SOCKS5,
+            originator_public_key: key.clone(),
+        };
+        let expected_pkg =
+            IncipientCoresPackage::new(main_cryptde, route.clone(), expected_payload.into(), key)
+                .unwrap();
+
+        thread::spawn(move || {
+            let stream_key_factory = StreamKeyFactoryMock::new().make_result(stream_key);
+            let system =
+                System::new("proxy_server_negotiates_socks5_connect_then_tunnels_data_to_hopper");
+            let mut subject = ProxyServer::new(
+                main_cryptde,
+                alias_cryptde,
+                false,
+                Some(STANDARD_CONSUMING_WALLET_BALANCE),
+            );
+            subject.stream_key_factory = Box::new(stream_key_factory);
+            let subject_addr: Addr<ProxyServer> = subject.start();
+            let mut peer_actors = peer_actors_builder()
+                .dispatcher(dispatcher_mock)
+                .hopper(hopper_mock)
+                .neighborhood(neighborhood_mock)
+                .build();
+            peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+            subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+            subject_addr.try_send(greeting).unwrap();
+            subject_addr.try_send(request).unwrap();
+            subject_addr.try_send(tunneled_data).unwrap();
+            system.run();
+        });
+
+        hopper_awaiter.await_message_count(1);
+        let dispatcher_recording = dispatcher_recording_arc.lock().unwrap();
+        assert_eq!(
+            dispatcher_recording.get_record::<TransmitDataMsg>(0),
+            &TransmitDataMsg {
+                endpoint: Endpoint::Socket(socket_addr),
+                last_data: false,
+                sequence_number: Some(0),
+                data: vec![0x05, 0x00],
+            }
+        );
+        assert_eq!(
+            dispatcher_recording.get_record::<TransmitDataMsg>(1),
+            &TransmitDataMsg {
+                endpoint: Endpoint::Socket(socket_addr),
+                last_data: false,
+                sequence_number: Some(1),
+                data: vec![0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0],
+            }
+        );
+        let hopper_recording = hopper_recording_arc.lock().unwrap();
+        let hopper_record = hopper_recording.get_record::<IncipientCoresPackage>(0);
+        assert_eq!(hopper_record, &expected_pkg);
+    }
+
+    #[test]
+    fn proxy_server_tunnels_data_that_arrives_with_socks5_connect_request() {
+        let main_cryptde = main_cryptde();
+        let alias_cryptde = alias_cryptde();
+        let key = alias_cryptde.public_key();
+        let (hopper_mock, hopper_awaiter, hopper_recording_arc) = make_recorder();
+        let (neighborhood_mock, _, _) = make_recorder();
+        let neighborhood_mock = neighborhood_mock.route_query_response(Some(
+            zero_hop_route_response(main_cryptde.public_key(), main_cryptde),
+        ));
+        let route = zero_hop_route_response(main_cryptde.public_key(), main_cryptde).route;
+        let (dispatcher_mock, _, _) = make_recorder();
+        let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+        let stream_key = make_meaningless_stream_key();
+        let mut data = vec![
+            0x05, 0x01, 0x00, 0x05, 0x01, 0x00, 0x01, 4, 3, 2, 1, 0x00, 0x16,
+        ];
+        data.extend(b"SSH-2.0-client");
+        let msg_from_dispatcher = InboundClientData {
+            peer_addr: socket_addr,
+            reception_port: Some(SOCKS_PORT),
+            sequence_number: Some(0),
+            last_data: false,
+            is_clandestine: false,
+            data,
+        };
+        let expected_payload = ClientRequestPayload_0v1 {
+            stream_key,
+            sequenced_packet: SequencedPacket {
+                data: b"SSH-2.0-client".to_vec(),
+                sequence_number: 0,
+                last_data: false,
+            },
+            target_hostname: Some(String::from("4.3.2.1")),
+            target_port: 22,
+            protocol: ProxyProtocol::SOCKS5,
+            originator_public_key: key.clone(),
+        };
+        let expected_pkg =
+            IncipientCoresPackage::new(main_cryptde, route.clone(), expected_payload.into(), key)
+                .unwrap();
+
+        thread::spawn(move || {
+            let stream_key_factory = StreamKeyFactoryMock::new().make_result(stream_key);
+            let system =
+                System::new("proxy_server_tunnels_data_that_arrives_with_socks5_connect_request");
+            let mut subject = ProxyServer::new(
+                main_cryptde,
+                alias_cryptde,
+                false,
+                Some(STANDARD_CONSUMING_WALLET_BALANCE),
+            );
+            subject.stream_key_factory = Box::new(stream_key_factory);
+            let subject_addr: Addr<ProxyServer> = subject.start();
+            let mut peer_actors = peer_actors_builder()
+                .dispatcher(dispatcher_mock)
+                .hopper(hopper_mock)
+                .neighborhood(neighborhood_mock)
+                .build();
+            peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+            subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+            subject_addr.try_send(msg_from_dispatcher).unwrap();
+            system.run();
+        });
+
+        hopper_awaiter.await_message_count(1);
+        let hopper_recording = hopper_recording_arc.lock().unwrap();
+        let hopper_record = hopper_recording.get_record::<IncipientCoresPackage>(0);
+        assert_eq!(hopper_record, &expected_pkg);
+    }
+
+    #[test]
+    fn proxy_server_answers_socks5_udp_associate_then_relays_datagrams_to_hopper() {
+        let main_cryptde = main_cryptde();
+        let alias_cryptde = alias_cryptde();
+        let key = alias_cryptde.public_key();
+        let (hopper_mock, hopper_awaiter, hopper_recording_arc) = make_recorder();
+        let (neighborhood_mock, _, _) = make_recorder();
+        let neighborhood_mock = neighborhood_mock.route_query_response(Some(
+            zero_hop_route_response(main_cryptde.public_key(), main_cryptde),
+        ));
+        let route = zero_hop_route_response(main_cryptde.public_key(), main_cryptde).route;
+        let (dispatcher_mock, _, dispatcher_recording_arc) = make_recorder();
+        let socket_addr = SocketAddr::from_str("127.0.0.1:5678").unwrap();
+        let client_udp_addr = SocketAddr::from_str("127.0.0.1:5555").unwrap();
+        let stream_key = make_meaningless_stream_key();
+        let bind_params_arc = Arc::new(Mutex::new(vec![]));
+        let bind_params_inner = bind_params_arc.clone();
+        let msg_from_dispatcher = InboundClientData {
+            peer_addr: socket_addr,
+            reception_port: Some(SOCKS_PORT),
+            sequence_number: Some(0),
+            last_data: false,
+            is_clandestine: false,
+            data: vec![
+                0x05, 0x01, 0x00, 0x05, 0x03, 0x00, 0x01, 0, 0, 0, 0, 0x00, 0x00,
+            ],
+        };
+        let mut datagram = vec![0x00, 0x00, 0x00, 0x01, 4, 3, 2, 1, 0x00, 0x35];
+        datagram.extend(b"query");
+        let expected_payload = DatagramRequestPayload_0v1 {
+            association_key: stream_key,
+            target_hostname: String::from("4.3.2.1"),
+            target_port: 53,
+            data: b"query".to_vec(),
+            originator_public_key: key.clone(),
+        };
+        let expected_pkg =
+            IncipientCoresPackage::new(main_cryptde, route.clone(), expected_payload.into(), key)
+                .unwrap();
+
+        thread::spawn(move || {
+            let system = System::new(
+                "proxy_server_answers_socks5_udp_associate_then_relays_datagrams_to_hopper",
+            );
+            let mut subject = ProxyServer::new(
+                main_cryptde,
+                alias_cryptde,
+                false,
+                Some(STANDARD_CONSUMING_WALLET_BALANCE),
+            );
+            subject.stream_key_factory =
+                Box::new(StreamKeyFactoryMock::new().make_result(stream_key));
+            subject.udp_socket_factory = Box::new(
+                UdpSocketWrapperFactoryMock::new().make_result(
+                    UdpSocketWrapperMock::new()
+                        .bind_params(&bind_params_inner)
+                        .local_addr_result(Ok(SocketAddr::from_str("127.0.0.1:4444").unwrap())),
+                ),
+            );
+            let subject_addr: Addr<ProxyServer> = subject.start();
+            let mut peer_actors = peer_actors_builder()
+                .dispatcher(dispatcher_mock)
+                .hopper(hopper_mock)
+                .neighborhood(neighborhood_mock)
+                .build();
+            peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+            subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+            subject_addr.try_send(msg_from_dispatcher).unwrap();
+            subject_addr
+                .try_send(InboundDatagram {
+                    association_key: stream_key,
+                    source: client_udp_addr,
+                    data: datagram,
+                })
+                .unwrap();
+            system.run();
+        });
+
+        hopper_awaiter.await_message_count(1);
+        assert_eq!(
+            *bind_params_arc.lock().unwrap(),
+            vec![SocketAddr::from_str("127.0.0.1:0").unwrap()]
+        );
+        let dispatcher_recording = dispatcher_recording_arc.lock().unwrap();
+        assert_eq!(
+            dispatcher_recording.get_record::<TransmitDataMsg>(0),
+            &TransmitDataMsg {
+                endpoint: Endpoint::Socket(socket_addr),
+                last_data: false,
+                sequence_number: Some(0),
+                data: vec![0x05, 0x00],
+            }
+        );
+        assert_eq!(
+            dispatcher_recording.get_record::<TransmitDataMsg>(1),
+            &TransmitDataMsg {
+                endpoint: Endpoint::Socket(socket_addr),
+                last_data: false,
+                sequence_number: Some(1),
+                data: vec![0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0x11, 0x5C],
+            }
+        );
+        let hopper_recording = hopper_recording_arc.lock().unwrap();
+        let hopper_record = hopper_recording.get_record::<IncipientCoresPackage>(0);
+        assert_eq!(hopper_record, &expected_pkg);
+    }
+
+    #[test]
+    fn proxy_server_relays_datagram_responses_to_socks5_udp_client() {
+        init_test_logging();
+        let system = System::new("proxy_server_relays_datagram_responses_to_socks5_udp_client");
+        let (inbound_datagram_mock, _, _) = make_recorder();
+        let cryptde = main_cryptde();
+        let association_key = make_meaningless_stream_key();
+        let vanished_key = StreamKey::new(
+            PublicKey::new(&b"vanished"[..]),
+            SocketAddr::from_str("2.3.4.5:6789").unwrap(),
+        );
+        let client_udp_addr = SocketAddr::from_str("127.0.0.1:5555").unwrap();
+        let server_addr = SocketAddr::from_str("4.3.2.1:53").unwrap();
+        let send_to_params_arc = Arc::new(Mutex::new(vec![]));
+        let (association, relay) = UdpAssociation::new(
+            association_key,
+            Box::new(UdpSocketWrapperMock::new().send_to_params(&send_to_params_arc)),
+            true,
+            inbound_datagram_mock.start().recipient(),
+        );
+        let mut subject = ProxyServer::new(
+            cryptde,
+            alias_cryptde(),
+            false,
+            Some(STANDARD_CONSUMING_WALLET_BALANCE),
+        );
+        subject.udp_associations.insert(
+            association_key,
+            SocksUdpAssociation {
+                association,
+                client_addr_opt: Some(client_udp_addr),
+            },
+        );
+        subject.route_ids_to_return_routes.insert(
+            1234,
+            AddReturnRouteMessage {
+                return_route_id: 1234,
+                expected_services: vec![ExpectedService::Nothing],
+                protocol: ProxyProtocol::SOCKS5,
+                server_name: Some("4.3.2.1".to_string()),
+            },
+        );
+        let subject_addr: Addr<ProxyServer> = subject.start();
+        let make_package = |association_key| -> ExpiredCoresPackage<DatagramResponsePayload_0v1> {
+            ExpiredCoresPackage::new(
+                SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+                Some(make_wallet("irrelevant")),
+                return_route_with_id(cryptde, 1234),
+                DatagramResponsePayload_0v1 {
+                    association_key,
+                    source: server_addr,
+                    data: b"answer".to_vec(),
+                },
+                0,
+            )
+        };
+        let mut peer_actors = peer_actors_builder().build();
+        peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr
+            .try_send(make_package(association_key))
+            .unwrap();
+        subject_addr.try_send(make_package(vanished_key)).unwrap();
+
+        System::current().stop();
+        system.run();
+        assert_eq!(relay.wait(), Ok(()));
+        let mut expected_datagram = vec![0x00, 0x00, 0x00, 0x01, 4, 3, 2, 1, 0x00, 0x35];
+        expected_datagram.extend(b"answer");
+        assert_eq!(
+            *send_to_params_arc.lock().unwrap(),
+            vec![(expected_datagram, client_udp_addr)]
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "DEBUG: ProxyServer: Discarding 6-byte datagram from 4.3.2.1:53 for vanished UDP association {}",
+            vanished_key
+        ));
+    }
+
+    #[test]
+    fn proxy_server_refuses_socks5_client_that_offers_no_acceptable_authentication_method() {
+        init_test_logging();
+        let (dispatcher, _, dispatcher_log_arc) = make_recorder();
+        let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+        let stream_key = make_meaningless_stream_key();
         let msg_from_dispatcher = InboundClientData {
-            peer_addr: socket_addr.clone(),
-            reception_port: Some(8443),
+            peer_addr: socket_addr,
+            reception_port: Some(SOCKS_PORT),
             sequence_number: Some(0),
             last_data: false,
             is_clandestine: false,
-            data: request_data.clone(),
+            data: vec![0x05, 0x01, 0x80],
         };
+        let system = System::new(
+            "proxy_server_refuses_socks5_client_that_offers_no_acceptable_authentication_method",
+        );
+        let mut subject = ProxyServer::new(
+            main_cryptde(),
+            alias_cryptde(),
+            false,
+            Some(STANDARD_CONSUMING_WALLET_BALANCE),
+        );
+        subject.stream_key_factory = Box::new(StreamKeyFactoryMock::new().make_result(stream_key));
+        let subject_addr: Addr<ProxyServer> = subject.start();
+        let mut peer_actors = peer_actors_builder().dispatcher(dispatcher).build();
+        peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
 
-        let stream_key_parameters_arc = Arc::new(Mutex::new(vec![]));
-        let stream_key_parameters_arc_thread = stream_key_parameters_arc.clone();
-
-        thread::spawn(move || {
-            let stream_key_factory = StreamKeyFactoryMock::new()
-                .make_parameters(&stream_key_parameters_arc_thread)
-                .make_result(stream_key);
-            let system = System::new(
-                "proxy_server_receives_connect_responds_with_ok_and_stores_stream_key_and_hostname",
-            );
-            let mut subject = ProxyServer::new(
-                cryptde,
-                alias_cryptde(),
-                false,
-                Some(STANDARD_CONSUMING_WALLET_BALANCE),
-            );
-            subject.stream_key_factory = Box::new(stream_key_factory);
-            let subject_addr: Addr<ProxyServer> = subject.start();
-            let mut peer_actors = peer_actors_builder()
-                .dispatcher(dispatcher_mock)
-                .hopper(hopper_mock)
-                .neighborhood(neighborhood_mock)
-                .build();
-            peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
-            subject_addr.try_send(BindMessage { peer_actors }).unwrap();
-
-            subject_addr.try_send(msg_from_dispatcher).unwrap();
-            system.run();
-        });
+        subject_addr.try_send(msg_from_dispatcher).unwrap();
 
-        thread::sleep(Duration::from_millis(500));
+        System::current().stop();
+        system.run();
+        let dispatcher_recording = dispatcher_log_arc.lock().unwrap();
+        assert_eq!(
+            dispatcher_recording.get_record::<TransmitDataMsg>(0),
+            &TransmitDataMsg {
+                endpoint: Endpoint::Socket(socket_addr),
+                last_data: true,
+                sequence_number: Some(0),
+                data: vec![0x05, 0xFF],
+            }
+        );
+        assert_eq!(dispatcher_recording.len(), 1);
+        TestLogHandler::new().exists_log_containing(
+            "WARN: ProxyServer: Refusing SOCKS5 client at 1.2.3.4:5678: Client offered no acceptable authentication method: [128]",
+        );
+    }
 
-        let expected_transmit_data_msg = TransmitDataMsg {
-            endpoint: Endpoint::Socket(socket_addr),
-            last_data: true,
+    #[test]
+    fn proxy_server_receives_socks5_connect_with_no_consuming_wallet_and_refuses_it() {
+        init_test_logging();
+        let (hopper, _, hopper_log_arc) = make_recorder();
+        let (neighborhood, _, neighborhood_log_arc) = make_recorder();
+        let (dispatcher, _, dispatcher_log_arc) = make_recorder();
+        let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+        let stream_key = make_meaningless_stream_key();
+        let msg_from_dispatcher = InboundClientData {
+            peer_addr: socket_addr,
+            reception_port: Some(SOCKS_PORT),
             sequence_number: Some(0),
-            data: b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n".to_vec(),
+            last_data: false,
+            is_clandestine: false,
+            data: vec![
+                0x05, 0x01, 0x00, 0x05, 0x01, 0x00, 0x01, 1, 2, 3, 4, 0x01, 0xBB,
+            ],
         };
+        let system = System::new(
+            "proxy_server_receives_socks5_connect_with_no_consuming_wallet_and_refuses_it",
+        );
+        let mut subject = ProxyServer::new(main_cryptde(), alias_cryptde(), true, None);
+        subject.stream_key_factory = Box::new(StreamKeyFactoryMock::new().make_result(stream_key));
+        let subject_addr: Addr<ProxyServer> = subject.start();
+        let mut peer_actors = peer_actors_builder()
+            .dispatcher(dispatcher)
+            .hopper(hopper)
+            .neighborhood(neighborhood)
+            .build();
+        peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
 
-        let dispatcher_recording = dispatcher_recording_arc.lock().unwrap();
-        let record = dispatcher_recording.get_record::<TransmitDataMsg>(0);
+        subject_addr.try_send(msg_from_dispatcher).unwrap();
 
-        assert_eq!(&expected_transmit_data_msg, record);
+        System::current().stop();
+        system.run();
+        let neighborhood_recording = neighborhood_log_arc.lock().unwrap();
+        assert!(neighborhood_recording.is_empty());
+        let hopper_recording = hopper_log_arc.lock().unwrap();
+        assert!(hopper_recording.is_empty());
+        let dispatcher_recording = dispatcher_log_arc.lock().unwrap();
+        assert_eq!(
+            dispatcher_recording.get_record::<TransmitDataMsg>(0),
+            &TransmitDataMsg {
+                endpoint: Endpoint::Socket(socket_addr),
+                last_data: false,
+                sequence_number: Some(0),
+                data: vec![0x05, 0x00],
+            }
+        );
+        assert_eq!(
+            dispatcher_recording.get_record::<TransmitDataMsg>(1),
+            &TransmitDataMsg {
+                endpoint: Endpoint::Socket(socket_addr),
+                last_data: true,
+                sequence_number: Some(1),
+                data: ServerImpersonatorSocks {}.consuming_wallet_absent(),
+            }
+        );
+        TestLogHandler::new().exists_log_containing(
+            "ERROR: ProxyServer: SOCKS5 request rejected due to missing consuming wallet",
+        );
     }
 
     #[test]
@@ -2305,8 +3566,8 @@ mod tests {
             &ReportRoutingServiceConsumedMessage {
                 earning_wallet: route_1_earning_wallet,
                 payload_size: payload_enc.len(),
-                service_rate: DEFAULT_RATE_PACK.routing_service_rate,
-                byte_rate: DEFAULT_RATE_PACK.routing_byte_rate,
+                service_rate: rate_pack(101).routing_service_rate,
+                byte_rate: rate_pack(101).routing_byte_rate,
             }
         );
         let record = recording.get_record::<ReportRoutingServiceConsumedMessage>(2);
@@ -2315,8 +3576,8 @@ mod tests {
             &ReportRoutingServiceConsumedMessage {
                 earning_wallet: route_2_earning_wallet,
                 payload_size: payload_enc.len(),
-                service_rate: DEFAULT_RATE_PACK.routing_service_rate,
-                byte_rate: DEFAULT_RATE_PACK.routing_byte_rate,
+                service_rate: rate_pack(102).routing_service_rate,
+                byte_rate: rate_pack(102).routing_byte_rate,
             }
         );
         let recording = proxy_server_recording_arc.lock().unwrap();
@@ -2516,8 +3777,8 @@ mod tests {
             &ReportExitServiceConsumedMessage {
                 earning_wallet,
                 payload_size: expected_data.len(),
-                service_rate: DEFAULT_RATE_PACK.exit_service_rate,
-                byte_rate: DEFAULT_RATE_PACK.exit_byte_rate,
+                service_rate: rate_pack(101).exit_service_rate,
+                byte_rate: rate_pack(101).exit_byte_rate,
             }
         );
     }
@@ -3165,7 +4426,7 @@ mod tests {
         );
         subject
             .tunneled_hosts
-            .insert(stream_key.clone(), "hostname".to_string());
+            .insert(stream_key, ("hostname".to_string(), 443));
         subject.route_ids_to_return_routes.insert(
             1234,
             AddReturnRouteMessage {
@@ -3349,18 +4610,21 @@ mod tests {
             0,
             &incoming_route_d_wallet,
             first_exit_size,
+            &rate_pack(101),
         );
         check_routing_report(
             &accountant_recording,
             1,
             &incoming_route_e_wallet,
             routing_size,
+            &rate_pack(102),
         );
         check_routing_report(
             &accountant_recording,
             2,
             &incoming_route_f_wallet,
             routing_size,
+            &rate_pack(103),
         );
         let routing_size = second_expired_cores_package.payload_len;
         check_exit_report(
@@ -3368,18 +4632,21 @@ mod tests {
             3,
             &incoming_route_g_wallet,
             second_exit_size,
+            &rate_pack(104),
         );
         check_routing_report(
             &accountant_recording,
             4,
             &incoming_route_h_wallet,
             routing_size,
+            &rate_pack(105),
         );
         check_routing_report(
             &accountant_recording,
             5,
             &incoming_route_i_wallet,
             routing_size,
+            &rate_pack(106),
         );
         assert_eq!(accountant_recording.len(), 6);
     }
@@ -3459,6 +4726,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn handle_exit_policy_violation_sends_message_to_dispatcher_without_deprioritizing_exit() {
+        let system = System::new(
+            "handle_exit_policy_violation_sends_message_to_dispatcher_without_deprioritizing_exit",
+        );
+
+        let (dispatcher_mock, _, dispatcher_log_arc) = make_recorder();
+        let (neighborhood_mock, _, neighborhood_log_arc) = make_recorder();
+
+        let cryptde = main_cryptde();
+        let mut subject = ProxyServer::new(
+            cryptde,
+            alias_cryptde(),
+            false,
+            Some(STANDARD_CONSUMING_WALLET_BALANCE),
+        );
+
+        let stream_key = make_meaningless_stream_key();
+        let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+
+        subject.keys_and_addrs.insert(stream_key, socket_addr);
+
+        let exit_public_key = PublicKey::from(&b"exit_key"[..]);
+        let exit_wallet = make_wallet("exit wallet");
+
+        let subject_addr: Addr<ProxyServer> = subject.start();
+
+        let exit_policy_violation =
+            ExitPolicyViolation_0v1::new(stream_key, "port 25 is not in --exit-ports".to_string());
+
+        let expired_cores_package: ExpiredCoresPackage<ExitPolicyViolation_0v1> =
+            ExpiredCoresPackage::new(
+                SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+                Some(make_wallet("irrelevant")),
+                return_route_with_id(cryptde, 1234),
+                exit_policy_violation,
+                0,
+            );
+
+        let mut peer_actors = peer_actors_builder()
+            .dispatcher(dispatcher_mock)
+            .neighborhood(neighborhood_mock)
+            .build();
+        peer_actors.proxy_server = ProxyServer::make_subs_from(&subject_addr);
+
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+        subject_addr
+            .try_send(AddReturnRouteMessage {
+                return_route_id: 1234,
+                expected_services: vec![ExpectedService::Exit(
+                    exit_public_key.clone(),
+                    exit_wallet,
+                    rate_pack(10),
+                )],
+                protocol: ProxyProtocol::HTTP,
+                server_name: Some("server.com".to_string()),
+            })
+            .unwrap();
+
+        subject_addr.try_send(expired_cores_package).unwrap();
+
+        System::current().stop_with_code(0);
+        system.run();
+
+        let dispatcher_recording = dispatcher_log_arc.lock().unwrap();
+        let record = dispatcher_recording.get_record::<TransmitDataMsg>(0);
+        assert_eq!(
+            TransmitDataMsg {
+                endpoint: Endpoint::Socket(socket_addr),
+                last_data: true,
+                sequence_number: Some(0),
+                data: ServerImpersonatorHttp {}.exit_policy_violation_response(
+                    &exit_public_key,
+                    Some("server.com".to_string()),
+                    "port 25 is not in --exit-ports",
+                ),
+            },
+            *record
+        );
+        assert_eq!(neighborhood_log_arc.lock().unwrap().len(), 0);
+    }
+
     #[test]
     fn handle_dns_resolve_failure_reports_services_consumed() {
         let system = System::new("proxy_server_records_accounting");
@@ -3531,18 +4880,26 @@ mod tests {
         system.run();
 
         let accountant_recording = accountant_recording_arc.lock().unwrap();
-        check_exit_report(&accountant_recording, 0, &incoming_route_d_wallet, 0);
+        check_exit_report(
+            &accountant_recording,
+            0,
+            &incoming_route_d_wallet,
+            0,
+            &rate_pack(101),
+        );
         check_routing_report(
             &accountant_recording,
             1,
             &incoming_route_e_wallet,
             routing_size,
+            &rate_pack(102),
         );
         check_routing_report(
             &accountant_recording,
             2,
             &incoming_route_f_wallet,
             routing_size,
+            &rate_pack(103),
         );
         assert_eq!(accountant_recording.len(), 3);
     }
@@ -3794,7 +5151,7 @@ mod tests {
             .insert(stream_key.clone(), socket_addr.clone());
         subject
             .tunneled_hosts
-            .insert(stream_key.clone(), "tunneled host".to_string());
+            .insert(stream_key, ("tunneled host".to_string(), 443));
         subject.stream_key_routes.insert(
             stream_key.clone(),
             RouteQueryResponse {
@@ -4068,6 +5425,77 @@ mod tests {
         TestLogHandler::new().await_log_containing("ERROR: ProxyServer: Can't report services consumed: received response with bogus return-route ID 1234. Ignoring", 1000);
     }
 
+    #[test]
+    fn node_rate_pack_change_updates_the_rates_of_stored_routes_and_return_routes() {
+        let mut subject = ProxyServer::new(
+            main_cryptde(),
+            alias_cryptde(),
+            true,
+            Some(STANDARD_CONSUMING_WALLET_BALANCE),
+        );
+        let changed_key = PublicKey::new(&[1]);
+        let other_key = PublicKey::new(&[2]);
+        let services = |changed_rate_pack: RatePack| {
+            vec![
+                ExpectedService::Nothing,
+                ExpectedService::Routing(
+                    changed_key.clone(),
+                    make_wallet("changed"),
+                    changed_rate_pack.clone(),
+                ),
+                ExpectedService::Routing(other_key.clone(), make_wallet("other"), rate_pack(102)),
+                ExpectedService::Exit(
+                    changed_key.clone(),
+                    make_wallet("changed"),
+                    changed_rate_pack,
+                ),
+            ]
+        };
+        let stream_key = make_meaningless_stream_key();
+        subject.stream_key_routes.insert(
+            stream_key,
+            RouteQueryResponse {
+                route: Route { hops: vec![] },
+                expected_services: ExpectedServices::RoundTrip(
+                    services(rate_pack(101)),
+                    services(rate_pack(101)),
+                    1234,
+                ),
+            },
+        );
+        subject.route_ids_to_return_routes.insert(
+            1234,
+            AddReturnRouteMessage {
+                return_route_id: 1234,
+                expected_services: services(rate_pack(101)),
+                protocol: ProxyProtocol::HTTP,
+                server_name: None,
+            },
+        );
+
+        subject.handle_node_rate_pack_change(NodeRatePackChangeMessage {
+            public_key: changed_key.clone(),
+            rate_pack: rate_pack(201),
+        });
+
+        assert_eq!(
+            subject
+                .stream_key_routes
+                .get(&stream_key)
+                .unwrap()
+                .expected_services,
+            ExpectedServices::RoundTrip(services(rate_pack(201)), services(rate_pack(201)), 1234)
+        );
+        assert_eq!(
+            subject
+                .route_ids_to_return_routes
+                .get(&1234)
+                .unwrap()
+                .expected_services,
+            services(rate_pack(201))
+        );
+    }
+
     #[test]
     fn handle_stream_shutdown_msg_handles_unknown_peer_addr() {
         let mut subject = ProxyServer::new(main_cryptde(), alias_cryptde(), true, None);
@@ -4086,7 +5514,7 @@ mod tests {
         );
         subject
             .tunneled_hosts
-            .insert(unaffected_stream_key, "blah".to_string());
+            .insert(unaffected_stream_key, ("blah".to_string(), 443));
 
         subject.handle_stream_shutdown_msg(StreamShutdownMsg {
             peer_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
@@ -4170,10 +5598,10 @@ mod tests {
         );
         subject
             .tunneled_hosts
-            .insert(unaffected_stream_key, "blah".to_string());
+            .insert(unaffected_stream_key, ("blah".to_string(), 443));
         subject
             .tunneled_hosts
-            .insert(affected_stream_key, "tunneled.com".to_string());
+            .insert(affected_stream_key, ("tunneled.com".to_string(), 443));
         let subject_addr = subject.start();
         let (hopper, _, hopper_recording_arc) = make_recorder();
         let (proxy_server, _, proxy_server_recording_arc) = make_recorder();
@@ -4377,10 +5805,10 @@ mod tests {
         );
         subject
             .tunneled_hosts
-            .insert(unaffected_stream_key, "blah".to_string());
+            .insert(unaffected_stream_key, ("blah".to_string(), 443));
         subject
             .tunneled_hosts
-            .insert(affected_stream_key, "blah".to_string());
+            .insert(affected_stream_key, ("blah".to_string(), 443));
 
         subject.handle_stream_shutdown_msg(StreamShutdownMsg {
             peer_addr: affected_socket_addr,
@@ -4408,6 +5836,35 @@ mod tests {
         assert!(!subject.tunneled_hosts.contains_key(&affected_stream_key));
     }
 
+    #[test]
+    fn handle_stream_shutdown_msg_does_not_report_to_counterpart_for_socks5_stream_still_negotiating(
+    ) {
+        let mut subject = ProxyServer::new(main_cryptde(), alias_cryptde(), true, None);
+        let socket_addr = SocketAddr::from_str("3.4.5.6:7890").unwrap();
+        let stream_key = StreamKey::new(main_cryptde().public_key().clone(), socket_addr);
+        subject.keys_and_addrs.insert(stream_key, socket_addr);
+        subject.socks_streams.insert(
+            stream_key,
+            SocksStream::Negotiating {
+                handshake: SocksHandshake::new(),
+                replies_sent: 1,
+            },
+        );
+
+        subject.handle_stream_shutdown_msg(StreamShutdownMsg {
+            peer_addr: socket_addr,
+            stream_type: RemovedStreamType::NonClandestine(NonClandestineAttributes {
+                reception_port: SOCKS_PORT,
+                sequence_number: 1,
+            }),
+            report_to_counterpart: true,
+        });
+
+        // Subject is unbound but didn't panic; therefore, no attempt to send to Hopper: perfect!
+        assert!(subject.keys_and_addrs.a_to_b(&stream_key).is_none());
+        assert!(!subject.socks_streams.contains_key(&stream_key));
+    }
+
     #[test]
     #[should_panic(
         expected = "ProxyServer should never get ShutdownStreamMsg about clandestine stream"
@@ -1,11 +1,12 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 use crate::proxy_server::http_protocol_pack::HttpProtocolPack;
+use crate::proxy_server::socks_protocol_pack::SocksProtocolPack;
 use crate::proxy_server::tls_protocol_pack::TlsProtocolPack;
 use crate::sub_lib::cryptde::{PlainData, PublicKey};
 use crate::sub_lib::dispatcher::InboundClientData;
 use crate::sub_lib::logger::Logger;
 use crate::sub_lib::proxy_server::ProxyProtocol;
-use masq_lib::constants::{HTTP_PORT, TLS_PORT};
+use masq_lib::constants::{HTTP_PORT, SOCKS_PORT, TLS_PORT};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Host {
@@ -24,6 +25,7 @@ pub fn from_protocol(protocol: ProxyProtocol) -> Box<dyn ProtocolPack> {
     match protocol {
         ProxyProtocol::HTTP => Box::new(HttpProtocolPack {}),
         ProxyProtocol::TLS => Box::new(TlsProtocolPack {}),
+        ProxyProtocol::SOCKS5 => Box::new(SocksProtocolPack {}),
     }
 }
 
@@ -31,6 +33,7 @@ pub fn from_standard_port(_standard_port: u16) -> Option<Box<dyn ProtocolPack>>
     match _standard_port {
         HTTP_PORT => Some(Box::new(HttpProtocolPack {})),
         TLS_PORT => Some(Box::new(TlsProtocolPack {})),
+        SOCKS_PORT => Some(Box::new(SocksProtocolPack {})),
         _ => None,
     }
 }
@@ -70,5 +73,11 @@ pub trait ServerImpersonator {
         exit_key: &PublicKey,
         server_name_opt: Option<String>,
     ) -> Vec<u8>;
+    fn exit_policy_violation_response(
+        &self,
+        exit_key: &PublicKey,
+        server_name_opt: Option<String>,
+        reason: &str,
+    ) -> Vec<u8>;
     fn consuming_wallet_absent(&self) -> Vec<u8>;
 }
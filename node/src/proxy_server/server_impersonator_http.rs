@@ -43,6 +43,28 @@ impl ServerImpersonator for ServerImpersonatorHttp {
         )
     }
 
+    fn exit_policy_violation_response(
+        &self,
+        exit_key: &PublicKey,
+        server_name_opt: Option<String>,
+        reason: &str,
+    ) -> Vec<u8> {
+        let (server_name, quoted_server_name) = match &server_name_opt {
+            Some(name) => (name.clone(), format!("\"{}\"", name)),
+            None => ("<unspecified>".to_string(), "<unspecified>".to_string()),
+        };
+        ServerImpersonatorHttp::make_error_response(
+            403,
+            "Exit Policy Violation",
+            &format!("Exit Node refused to connect to {}", quoted_server_name),
+            &format!(
+                "We chose the exit Node {} for your request to {}; but its operator doesn't allow \
+        connections there ({}). Other exit Nodes may have different policies.",
+                exit_key, server_name, reason
+            ),
+        )
+    }
+
     fn consuming_wallet_absent(&self) -> Vec<u8> {
         ServerImpersonatorHttp::make_error_response(
             402,
@@ -217,6 +239,26 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn exit_policy_violation_response_produces_expected_error_page() {
+        let subject = ServerImpersonatorHttp {};
+
+        let result = subject.exit_policy_violation_response(
+            &PublicKey::new(&b"exit"[..]),
+            Some("server.com".to_string()),
+            "port 25 is not in --exit-ports",
+        );
+
+        let expected = ServerImpersonatorHttp::make_error_response(
+            403,
+            "Exit Policy Violation",
+            "Exit Node refused to connect to \"server.com\"",
+            "We chose the exit Node ZXhpdA for your request to server.com; but its operator doesn't allow \
+            connections there (port 25 is not in --exit-ports). Other exit Nodes may have different policies.",
+        );
+        assert_eq!(expected, result);
+    }
+
     #[test]
     fn consuming_wallet_absent_response_produces_expected_error_page() {
         let subject = ServerImpersonatorHttp {};
@@ -0,0 +1,81 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::proxy_server::protocol_pack::ServerImpersonator;
+use crate::proxy_server::socks_handshake::{make_reply, REPLY_NOT_ALLOWED_BY_RULESET};
+use crate::sub_lib::cryptde::PublicKey;
+
+// Once a SOCKS5 CONNECT has been answered, the stream belongs to the client's application protocol,
+// so there's nothing we can say in-band about failures: we just close the stream.
+pub struct ServerImpersonatorSocks {}
+
+impl ServerImpersonator for ServerImpersonatorSocks {
+    fn route_query_failure_response(&self, _server_name: &str) -> Vec<u8> {
+        vec![]
+    }
+
+    fn dns_resolution_failure_response(
+        &self,
+        _exit_key: &PublicKey,
+        _server_name: Option<String>,
+    ) -> Vec<u8> {
+        vec![]
+    }
+
+    fn exit_policy_violation_response(
+        &self,
+        _exit_key: &PublicKey,
+        _server_name: Option<String>,
+        _reason: &str,
+    ) -> Vec<u8> {
+        vec![]
+    }
+
+    fn consuming_wallet_absent(&self) -> Vec<u8> {
+        make_reply(REPLY_NOT_ALLOWED_BY_RULESET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_query_failure_response_is_empty() {
+        let subject = ServerImpersonatorSocks {};
+
+        let result = subject.route_query_failure_response("ignored");
+
+        assert_eq!(result, Vec::<u8>::new());
+    }
+
+    #[test]
+    fn dns_resolution_failure_response_is_empty() {
+        let subject = ServerImpersonatorSocks {};
+
+        let result =
+            subject.dns_resolution_failure_response(&PublicKey::new(&b"ignored"[..]), None);
+
+        assert_eq!(result, Vec::<u8>::new());
+    }
+
+    #[test]
+    fn exit_policy_violation_response_is_empty() {
+        let subject = ServerImpersonatorSocks {};
+
+        let result = subject.exit_policy_violation_response(
+            &PublicKey::new(&b"ignored"[..]),
+            None,
+            "ignored",
+        );
+
+        assert_eq!(result, Vec::<u8>::new());
+    }
+
+    #[test]
+    fn consuming_wallet_absent_refuses_the_connect_request() {
+        let subject = ServerImpersonatorSocks {};
+
+        let result = subject.consuming_wallet_absent();
+
+        assert_eq!(result, vec![0x05, 0x02, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+    }
+}
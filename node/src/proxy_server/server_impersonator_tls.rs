@@ -17,6 +17,15 @@ impl ServerImpersonator for ServerImpersonatorTls {
         Vec::from(&TLS_UNRECOGNIZED_NAME_ALERT[..])
     }
 
+    fn exit_policy_violation_response(
+        &self,
+        _exit_key: &PublicKey,
+        _server_name: Option<String>,
+        _reason: &str,
+    ) -> Vec<u8> {
+        Vec::from(&TLS_ACCESS_DENIED_ALERT[..])
+    }
+
     fn consuming_wallet_absent(&self) -> Vec<u8> {
         Vec::from(&TLS_INTERNAL_ERROR_ALERT[..])
     }
@@ -38,6 +47,14 @@ const TLS_UNRECOGNIZED_NAME_ALERT: [u8; 7] = [
     0x70, // unrecognized_name alert
 ];
 
+const TLS_ACCESS_DENIED_ALERT: [u8; 7] = [
+    0x15, // alert
+    0x03, 0x03, // TLS 1.2
+    0x00, 0x02, // packet length
+    0x02, // fatal alert
+    0x31, // access_denied alert
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,6 +78,19 @@ mod tests {
         assert_eq!(Vec::from(&TLS_UNRECOGNIZED_NAME_ALERT[..]), result);
     }
 
+    #[test]
+    fn exit_policy_violation_response_produces_access_denied_alert() {
+        let subject = ServerImpersonatorTls {};
+
+        let result = subject.exit_policy_violation_response(
+            &PublicKey::new(&b"ignored"[..]),
+            None,
+            "ignored",
+        );
+
+        assert_eq!(Vec::from(&TLS_ACCESS_DENIED_ALERT[..]), result);
+    }
+
     #[test]
     fn consuming_wallet_absent_produces_internal_error_alert() {
         let subject = ServerImpersonatorTls {};
@@ -0,0 +1,614 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::proxy_server::protocol_pack::Host;
+use std::convert::TryFrom;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+const SOCKS_VERSION: u8 = 0x05;
+const METHOD_NO_AUTHENTICATION: u8 = 0x00;
+const METHOD_USERNAME_PASSWORD: u8 = 0x02;
+const METHOD_NONE_ACCEPTABLE: u8 = 0xFF;
+const USERNAME_PASSWORD_VERSION: u8 = 0x01;
+const USERNAME_PASSWORD_SUCCESS: u8 = 0x00;
+const USERNAME_PASSWORD_FAILURE: u8 = 0x01;
+const COMMAND_CONNECT: u8 = 0x01;
+const COMMAND_UDP_ASSOCIATE: u8 = 0x03;
+const ADDRESS_TYPE_IPV4: u8 = 0x01;
+const ADDRESS_TYPE_DOMAIN_NAME: u8 = 0x03;
+const ADDRESS_TYPE_IPV6: u8 = 0x04;
+
+pub const REPLY_SUCCEEDED: u8 = 0x00;
+pub const REPLY_GENERAL_FAILURE: u8 = 0x01;
+pub const REPLY_NOT_ALLOWED_BY_RULESET: u8 = 0x02;
+pub const REPLY_COMMAND_NOT_SUPPORTED: u8 = 0x07;
+pub const REPLY_ADDRESS_TYPE_NOT_SUPPORTED: u8 = 0x08;
+
+// The bound address in our replies is always 0.0.0.0:0: the real connection is made by the exit
+// Node, and clients doing CONNECT don't care where it's bound.
+pub fn make_reply(reply_code: u8) -> Vec<u8> {
+    make_reply_with_address(
+        reply_code,
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+    )
+}
+
+// UDP ASSOCIATE replies are different: they tell the client where to send its datagrams.
+pub fn make_reply_with_address(reply_code: u8, bound_addr: SocketAddr) -> Vec<u8> {
+    let mut reply = vec![SOCKS_VERSION, reply_code, 0x00];
+    reply.extend(encode_address(bound_addr));
+    reply
+}
+
+// Every datagram relayed for a UDP association carries a header naming its far end:
+// RSV (2 bytes), FRAG (1 byte), then an address and port just like those in a request.
+pub fn parse_udp_datagram(datagram: &[u8]) -> Result<(Host, Vec<u8>), String> {
+    if datagram.len() < 4 {
+        return Err(format!("{}-byte datagram is too short", datagram.len()));
+    }
+    if datagram[2] != 0x00 {
+        return Err(format!("fragmented datagram (FRAG {})", datagram[2]));
+    }
+    match parse_address(&datagram[3..]) {
+        Ok(Some((target, address_len))) => Ok((target, datagram[(3 + address_len)..].to_vec())),
+        Ok(None) => Err(format!(
+            "{}-byte datagram ends inside its header",
+            datagram.len()
+        )),
+        Err((_, reason)) => Err(reason),
+    }
+}
+
+pub fn make_udp_datagram(source: SocketAddr, data: &[u8]) -> Vec<u8> {
+    let mut datagram = vec![0x00, 0x00, 0x00];
+    datagram.extend(encode_address(source));
+    datagram.extend(data);
+    datagram
+}
+
+fn encode_address(addr: SocketAddr) -> Vec<u8> {
+    let mut encoded = match addr.ip() {
+        IpAddr::V4(ip) => {
+            let mut encoded = vec![ADDRESS_TYPE_IPV4];
+            encoded.extend(&ip.octets());
+            encoded
+        }
+        IpAddr::V6(ip) => {
+            let mut encoded = vec![ADDRESS_TYPE_IPV6];
+            encoded.extend(&ip.octets());
+            encoded
+        }
+    };
+    encoded.extend(&addr.port().to_be_bytes());
+    encoded
+}
+
+// Parses an address type, address, and port; returns the target and the number of bytes it
+// occupied, or None if the data ends before the port does. Errors carry the reply code to send.
+fn parse_address(data: &[u8]) -> Result<Option<(Host, usize)>, (u8, String)> {
+    if data.len() < 2 {
+        return Ok(None);
+    }
+    let address_type = data[0];
+    let (address_offset, address_len) = match address_type {
+        ADDRESS_TYPE_IPV4 => (1, 4),
+        ADDRESS_TYPE_DOMAIN_NAME => (2, data[1] as usize),
+        ADDRESS_TYPE_IPV6 => (1, 16),
+        _ => {
+            return Err((
+                REPLY_ADDRESS_TYPE_NOT_SUPPORTED,
+                format!("Client requested unknown address type {}", address_type),
+            ))
+        }
+    };
+    let total_len = address_offset + address_len + 2;
+    if data.len() < total_len {
+        return Ok(None);
+    }
+    let address = &data[address_offset..(address_offset + address_len)];
+    let name = match address_type {
+        ADDRESS_TYPE_IPV4 => {
+            Ipv4Addr::new(address[0], address[1], address[2], address[3]).to_string()
+        }
+        ADDRESS_TYPE_IPV6 => {
+            Ipv6Addr::from(<[u8; 16]>::try_from(address).expect("Address length was checked"))
+                .to_string()
+        }
+        _ => match String::from_utf8(address.to_vec()) {
+            Ok(name) => name,
+            Err(_) => {
+                return Err((
+                    REPLY_GENERAL_FAILURE,
+                    format!("Client requested unreadable domain name {:?}", address),
+                ))
+            }
+        },
+    };
+    let port = ((data[total_len - 2] as u16) << 8) | (data[total_len - 1] as u16);
+    Ok(Some((
+        Host {
+            name,
+            port: Some(port),
+        },
+        total_len,
+    )))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SocksOutcome {
+    Pending,
+    // The client's target, followed by whatever the client sent after its CONNECT request
+    Connect(Host, Vec<u8>),
+    // The client wants to relay datagrams; we have yet to tell it where to send them
+    UdpAssociate,
+    Refused(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SocksProgress {
+    pub replies: Vec<Vec<u8>>,
+    pub outcome: SocksOutcome,
+}
+
+// What the handshake is waiting to receive from the client next
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SocksState {
+    Greeting,
+    Credentials,
+    Request,
+}
+
+enum Step {
+    Incomplete,
+    Reply(Vec<u8>),
+    Connect(Host),
+    UdpAssociate,
+    Refuse(Option<Vec<u8>>, String),
+}
+
+pub struct SocksHandshake {
+    state: SocksState,
+    data_so_far: Vec<u8>,
+}
+
+impl Default for SocksHandshake {
+    fn default() -> Self {
+        SocksHandshake {
+            state: SocksState::Greeting,
+            data_so_far: vec![],
+        }
+    }
+}
+
+impl SocksHandshake {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_data(&mut self, data: &[u8]) -> SocksProgress {
+        self.data_so_far.extend(data);
+        let mut replies = vec![];
+        loop {
+            let step = match self.state {
+                SocksState::Greeting => self.take_greeting(),
+                SocksState::Credentials => self.take_credentials(),
+                SocksState::Request => self.take_request(),
+            };
+            let outcome = match step {
+                Step::Incomplete => SocksOutcome::Pending,
+                Step::Reply(reply) => {
+                    replies.push(reply);
+                    continue;
+                }
+                Step::Connect(host) => {
+                    SocksOutcome::Connect(host, self.data_so_far.drain(..).collect())
+                }
+                Step::UdpAssociate => SocksOutcome::UdpAssociate,
+                Step::Refuse(reply_opt, reason) => {
+                    replies.extend(reply_opt);
+                    SocksOutcome::Refused(reason)
+                }
+            };
+            return SocksProgress { replies, outcome };
+        }
+    }
+
+    fn take_greeting(&mut self) -> Step {
+        if self.data_so_far.len() < 2 {
+            return Step::Incomplete;
+        }
+        if self.data_so_far[0] != SOCKS_VERSION {
+            return Step::Refuse(
+                None,
+                format!(
+                    "Client speaks SOCKS version {}, not {}",
+                    self.data_so_far[0], SOCKS_VERSION
+                ),
+            );
+        }
+        let greeting_len = 2 + self.data_so_far[1] as usize;
+        if self.data_so_far.len() < greeting_len {
+            return Step::Incomplete;
+        }
+        let methods: Vec<u8> = self.data_so_far.drain(..greeting_len).skip(2).collect();
+        // Proxy ports listen only on localhost, and the Node doesn't authenticate clients on any of
+        // them; username/password is accepted (without being checked) for clients that insist on it.
+        if methods.contains(&METHOD_NO_AUTHENTICATION) {
+            self.state = SocksState::Request;
+            Step::Reply(vec![SOCKS_VERSION, METHOD_NO_AUTHENTICATION])
+        } else if methods.contains(&METHOD_USERNAME_PASSWORD) {
+            self.state = SocksState::Credentials;
+            Step::Reply(vec![SOCKS_VERSION, METHOD_USERNAME_PASSWORD])
+        } else {
+            Step::Refuse(
+                Some(vec![SOCKS_VERSION, METHOD_NONE_ACCEPTABLE]),
+                format!(
+                    "Client offered no acceptable authentication method: {:?}",
+                    methods
+                ),
+            )
+        }
+    }
+
+    fn take_credentials(&mut self) -> Step {
+        if self.data_so_far.len() < 2 {
+            return Step::Incomplete;
+        }
+        if self.data_so_far[0] != USERNAME_PASSWORD_VERSION {
+            return Step::Refuse(
+                Some(vec![USERNAME_PASSWORD_VERSION, USERNAME_PASSWORD_FAILURE]),
+                format!(
+                    "Client used username/password subnegotiation version {}, not {}",
+                    self.data_so_far[0], USERNAME_PASSWORD_VERSION
+                ),
+            );
+        }
+        let password_len_offset = 2 + self.data_so_far[1] as usize;
+        if self.data_so_far.len() <= password_len_offset {
+            return Step::Incomplete;
+        }
+        let credentials_len =
+            password_len_offset + 1 + self.data_so_far[password_len_offset] as usize;
+        if self.data_so_far.len() < credentials_len {
+            return Step::Incomplete;
+        }
+        self.data_so_far.drain(..credentials_len);
+        self.state = SocksState::Request;
+        Step::Reply(vec![USERNAME_PASSWORD_VERSION, USERNAME_PASSWORD_SUCCESS])
+    }
+
+    fn take_request(&mut self) -> Step {
+        if self.data_so_far.len() < 5 {
+            return Step::Incomplete;
+        }
+        if self.data_so_far[0] != SOCKS_VERSION {
+            return Step::Refuse(
+                Some(make_reply(REPLY_GENERAL_FAILURE)),
+                format!(
+                    "Client sent request for SOCKS version {}, not {}",
+                    self.data_so_far[0], SOCKS_VERSION
+                ),
+            );
+        }
+        let (target, address_len) = match parse_address(&self.data_so_far[3..]) {
+            Ok(Some(parsed)) => parsed,
+            Ok(None) => return Step::Incomplete,
+            Err((reply_code, reason)) => return Step::Refuse(Some(make_reply(reply_code)), reason),
+        };
+        let request: Vec<u8> = self.data_so_far.drain(..(3 + address_len)).collect();
+        match request[1] {
+            COMMAND_CONNECT => Step::Connect(target),
+            // The address in a UDP ASSOCIATE request is where the client expects to send from;
+            // clients usually don't know that yet, so we learn it from the first datagram.
+            COMMAND_UDP_ASSOCIATE => Step::UdpAssociate,
+            command => Step::Refuse(
+                Some(make_reply(REPLY_COMMAND_NOT_SUPPORTED)),
+                format!("Client requested unsupported command {}", command),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn connect_request(address_type: u8, address: &[u8], port: u16) -> Vec<u8> {
+        let mut request = vec![SOCKS_VERSION, COMMAND_CONNECT, 0x00, address_type];
+        request.extend(address);
+        request.extend(&[(port >> 8) as u8, (port & 0xFF) as u8]);
+        request
+    }
+
+    fn host(name: &str, port: u16) -> Host {
+        Host {
+            name: name.to_string(),
+            port: Some(port),
+        }
+    }
+
+    #[test]
+    fn make_reply_produces_reply_with_unspecified_bound_address() {
+        let result = make_reply(REPLY_SUCCEEDED);
+
+        assert_eq!(result, vec![0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn no_authentication_is_preferred_when_offered() {
+        let mut subject = SocksHandshake::new();
+
+        let result = subject.add_data(&[0x05, 0x02, 0x02, 0x00]);
+
+        assert_eq!(
+            result,
+            SocksProgress {
+                replies: vec![vec![0x05, 0x00]],
+                outcome: SocksOutcome::Pending,
+            }
+        );
+        assert_eq!(subject.state, SocksState::Request);
+    }
+
+    #[test]
+    fn connect_with_domain_name_completes_handshake() {
+        let mut subject = SocksHandshake::new();
+        subject.add_data(&[0x05, 0x01, 0x00]);
+        let mut domain_name = vec![9u8];
+        domain_name.extend(b"booga.com");
+
+        let result = subject.add_data(&connect_request(
+            ADDRESS_TYPE_DOMAIN_NAME,
+            &domain_name,
+            8443,
+        ));
+
+        assert_eq!(
+            result,
+            SocksProgress {
+                replies: vec![],
+                outcome: SocksOutcome::Connect(host("booga.com", 8443), vec![]),
+            }
+        );
+    }
+
+    #[test]
+    fn connect_with_ipv4_address_completes_handshake() {
+        let mut subject = SocksHandshake::new();
+        subject.add_data(&[0x05, 0x01, 0x00]);
+
+        let result = subject.add_data(&connect_request(ADDRESS_TYPE_IPV4, &[1, 2, 3, 4], 22));
+
+        assert_eq!(
+            result.outcome,
+            SocksOutcome::Connect(host("1.2.3.4", 22), vec![])
+        );
+    }
+
+    #[test]
+    fn connect_with_ipv6_address_completes_handshake() {
+        let mut subject = SocksHandshake::new();
+        subject.add_data(&[0x05, 0x01, 0x00]);
+        let address = [
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01,
+        ];
+
+        let result = subject.add_data(&connect_request(ADDRESS_TYPE_IPV6, &address, 5222));
+
+        assert_eq!(
+            result.outcome,
+            SocksOutcome::Connect(host("2001:db8::1", 5222), vec![])
+        );
+    }
+
+    #[test]
+    fn username_password_is_negotiated_when_no_authentication_is_not_offered() {
+        let mut subject = SocksHandshake::new();
+        let greeting_result = subject.add_data(&[0x05, 0x01, 0x02]);
+        let mut credentials = vec![0x01, 0x05];
+        credentials.extend(b"booga");
+        credentials.push(0x06);
+        credentials.extend(b"agoob!");
+
+        let credentials_result = subject.add_data(&credentials);
+        let request_result =
+            subject.add_data(&connect_request(ADDRESS_TYPE_IPV4, &[4, 3, 2, 1], 1234));
+
+        assert_eq!(greeting_result.replies, vec![vec![0x05, 0x02]]);
+        assert_eq!(greeting_result.outcome, SocksOutcome::Pending);
+        assert_eq!(credentials_result.replies, vec![vec![0x01, 0x00]]);
+        assert_eq!(credentials_result.outcome, SocksOutcome::Pending);
+        assert_eq!(
+            request_result.outcome,
+            SocksOutcome::Connect(host("4.3.2.1", 1234), vec![])
+        );
+    }
+
+    #[test]
+    fn handshake_split_across_many_chunks_is_reassembled() {
+        let mut subject = SocksHandshake::new();
+        let mut handshake = vec![0x05, 0x01, 0x02, 0x01, 0x01, b'u', 0x01, b'p'];
+        handshake.extend(connect_request(ADDRESS_TYPE_IPV4, &[1, 2, 3, 4], 80));
+
+        let results: Vec<SocksProgress> = handshake
+            .iter()
+            .map(|byte| subject.add_data(&[*byte]))
+            .collect();
+
+        let replies: Vec<Vec<u8>> = results
+            .iter()
+            .flat_map(|progress| progress.replies.clone())
+            .collect();
+        assert_eq!(replies, vec![vec![0x05, 0x02], vec![0x01, 0x00]]);
+        let (last, all_but_last) = results.split_last().unwrap();
+        assert!(all_but_last
+            .iter()
+            .all(|progress| progress.outcome == SocksOutcome::Pending));
+        assert_eq!(
+            last.outcome,
+            SocksOutcome::Connect(host("1.2.3.4", 80), vec![])
+        );
+    }
+
+    #[test]
+    fn pipelined_handshake_and_data_are_separated() {
+        let mut subject = SocksHandshake::new();
+        let mut data = vec![0x05, 0x01, 0x00];
+        data.extend(connect_request(ADDRESS_TYPE_IPV4, &[1, 2, 3, 4], 80));
+        data.extend(b"GET / HTTP/1.1\r\n\r\n");
+
+        let result = subject.add_data(&data);
+
+        assert_eq!(
+            result,
+            SocksProgress {
+                replies: vec![vec![0x05, 0x00]],
+                outcome: SocksOutcome::Connect(
+                    host("1.2.3.4", 80),
+                    b"GET / HTTP/1.1\r\n\r\n".to_vec()
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn other_socks_versions_are_refused_without_reply() {
+        let mut subject = SocksHandshake::new();
+
+        let result = subject.add_data(&[0x04, 0x01, 0x00, 0x50, 1, 2, 3, 4, 0x00]);
+
+        assert_eq!(
+            result,
+            SocksProgress {
+                replies: vec![],
+                outcome: SocksOutcome::Refused("Client speaks SOCKS version 4, not 5".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn unacceptable_authentication_methods_are_refused() {
+        let mut subject = SocksHandshake::new();
+
+        let result = subject.add_data(&[0x05, 0x02, 0x01, 0x03]);
+
+        assert_eq!(
+            result,
+            SocksProgress {
+                replies: vec![vec![0x05, 0xFF]],
+                outcome: SocksOutcome::Refused(
+                    "Client offered no acceptable authentication method: [1, 3]".to_string()
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn udp_associate_completes_handshake_without_reply() {
+        let mut subject = SocksHandshake::new();
+        subject.add_data(&[0x05, 0x01, 0x00]);
+        let mut request = connect_request(ADDRESS_TYPE_IPV4, &[0, 0, 0, 0], 0);
+        request[1] = COMMAND_UDP_ASSOCIATE;
+
+        let result = subject.add_data(&request);
+
+        assert_eq!(
+            result,
+            SocksProgress {
+                replies: vec![],
+                outcome: SocksOutcome::UdpAssociate,
+            }
+        );
+    }
+
+    #[test]
+    fn commands_other_than_connect_and_udp_associate_are_refused() {
+        let mut subject = SocksHandshake::new();
+        subject.add_data(&[0x05, 0x01, 0x00]);
+        let mut request = connect_request(ADDRESS_TYPE_IPV4, &[1, 2, 3, 4], 53);
+        request[1] = 0x02; // BIND
+
+        let result = subject.add_data(&request);
+
+        assert_eq!(
+            result,
+            SocksProgress {
+                replies: vec![make_reply(REPLY_COMMAND_NOT_SUPPORTED)],
+                outcome: SocksOutcome::Refused(
+                    "Client requested unsupported command 2".to_string()
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_address_types_are_refused() {
+        let mut subject = SocksHandshake::new();
+        subject.add_data(&[0x05, 0x01, 0x00]);
+
+        let result = subject.add_data(&connect_request(0x05, &[1, 2, 3, 4], 80));
+
+        assert_eq!(
+            result,
+            SocksProgress {
+                replies: vec![make_reply(REPLY_ADDRESS_TYPE_NOT_SUPPORTED)],
+                outcome: SocksOutcome::Refused(
+                    "Client requested unknown address type 5".to_string()
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn make_reply_with_address_reports_bound_address() {
+        let result =
+            make_reply_with_address(REPLY_SUCCEEDED, SocketAddr::from(([127, 0, 0, 1], 4660)));
+
+        assert_eq!(
+            result,
+            vec![0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0x12, 0x34]
+        );
+    }
+
+    #[test]
+    fn udp_datagram_with_domain_name_is_parsed() {
+        let mut datagram = vec![0x00, 0x00, 0x00, ADDRESS_TYPE_DOMAIN_NAME, 9];
+        datagram.extend(b"booga.com");
+        datagram.extend(&[0x00, 0x35, 1, 2, 3]);
+
+        let result = parse_udp_datagram(&datagram);
+
+        assert_eq!(result, Ok((host("booga.com", 53), vec![1, 2, 3])));
+    }
+
+    #[test]
+    fn fragmented_and_truncated_udp_datagrams_are_rejected() {
+        assert_eq!(
+            parse_udp_datagram(&[0x00, 0x00, 0x01, ADDRESS_TYPE_IPV4, 1, 2, 3, 4, 0, 53]),
+            Err("fragmented datagram (FRAG 1)".to_string())
+        );
+        assert_eq!(
+            parse_udp_datagram(&[0x00, 0x00, 0x00, ADDRESS_TYPE_IPV4, 1, 2, 3]),
+            Err("7-byte datagram ends inside its header".to_string())
+        );
+        assert_eq!(
+            parse_udp_datagram(&[0x00, 0x00]),
+            Err("2-byte datagram is too short".to_string())
+        );
+    }
+
+    #[test]
+    fn udp_datagram_is_made_with_ipv6_source() {
+        let source = SocketAddr::from_str("[2001:db8::1]:53").unwrap();
+
+        let result = make_udp_datagram(source, &[9, 8, 7]);
+
+        let mut expected = vec![0x00, 0x00, 0x00, ADDRESS_TYPE_IPV6];
+        expected.extend(&Ipv6Addr::from_str("2001:db8::1").unwrap().octets());
+        expected.extend(&[0x00, 0x35, 9, 8, 7]);
+        assert_eq!(result, expected);
+        assert_eq!(
+            parse_udp_datagram(&result),
+            Ok((host("2001:db8::1", 53), vec![9, 8, 7]))
+        );
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::proxy_server::protocol_pack::{Host, ProtocolPack, ServerImpersonator};
+use crate::proxy_server::server_impersonator_socks::ServerImpersonatorSocks;
+use crate::sub_lib::cryptde::PlainData;
+use crate::sub_lib::proxy_server::ProxyProtocol;
+use masq_lib::constants::SOCKS_PORT;
+
+pub struct SocksProtocolPack {}
+
+impl ProtocolPack for SocksProtocolPack {
+    fn proxy_protocol(&self) -> ProxyProtocol {
+        ProxyProtocol::SOCKS5
+    }
+
+    fn standard_port(&self) -> u16 {
+        SOCKS_PORT
+    }
+
+    // The target of a SOCKS5 stream is named in the handshake, not in the data that follows it;
+    // the ProxyServer remembers it from the CONNECT request.
+    fn find_host(&self, _data: &PlainData) -> Option<Host> {
+        None
+    }
+
+    fn server_impersonator(&self) -> Box<dyn ServerImpersonator> {
+        Box::new(ServerImpersonatorSocks {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knows_its_protocol() {
+        let result = SocksProtocolPack {}.proxy_protocol();
+
+        assert_eq!(result, ProxyProtocol::SOCKS5);
+    }
+
+    #[test]
+    fn knows_its_standard_port() {
+        let result = SocksProtocolPack {}.standard_port();
+
+        assert_eq!(result, SOCKS_PORT);
+    }
+
+    #[test]
+    fn never_finds_a_host_in_tunneled_data() {
+        let data = PlainData::new(b"GET http://booga.com/ HTTP/1.1\r\nHost: booga.com\r\n\r\n");
+
+        let result = SocksProtocolPack {}.find_host(&data);
+
+        assert_eq!(result, None);
+    }
+}
@@ -3,6 +3,7 @@
 use crate::daemon::daemon_initializer::{DaemonInitializer, RecipientsFactoryReal, RerunnerReal};
 use crate::daemon::ChannelFactoryReal;
 use crate::database::config_dumper;
+use crate::database::node_key_rotator;
 use crate::node_configurator::node_configurator_generate_wallet::NodeConfiguratorGenerateWallet;
 use crate::node_configurator::node_configurator_initialization::NodeConfiguratorInitialization;
 use crate::node_configurator::node_configurator_recover_wallet::NodeConfiguratorRecoverWallet;
@@ -19,6 +20,7 @@ enum Mode {
     GenerateWallet,
     RecoverWallet,
     DumpConfig,
+    RotateNodeKey,
     Initialization,
     Service,
 }
@@ -61,6 +63,7 @@ impl RunModes {
             Mode::GenerateWallet => self.generate_wallet(args, streams),
             Mode::RecoverWallet => self.recover_wallet(args, streams),
             Mode::DumpConfig => self.runner.dump_config(args, streams),
+            Mode::RotateNodeKey => self.runner.rotate_node_key(args, streams),
             Mode::Initialization => self.runner.initialization(args, streams),
             Mode::Service => self.runner.run_service(args, streams),
         } {
@@ -113,6 +116,8 @@ impl RunModes {
     fn determine_mode_and_priv_req(&self, args: &[String]) -> (Mode, bool) {
         if args.contains(&"--dump-config".to_string()) {
             (Mode::DumpConfig, false)
+        } else if args.contains(&"--rotate-node-key".to_string()) {
+            (Mode::RotateNodeKey, false)
         } else if args.contains(&"--recover-wallet".to_string()) {
             (Mode::RecoverWallet, false)
         } else if args.contains(&"--generate-wallet".to_string()) {
@@ -164,6 +169,11 @@ trait Runner {
         args: &[String],
         streams: &mut StdStreams<'_>,
     ) -> Result<i32, ConfiguratorError>;
+    fn rotate_node_key(
+        &self,
+        args: &[String],
+        streams: &mut StdStreams<'_>,
+    ) -> Result<i32, ConfiguratorError>;
     fn initialization(
         &self,
         args: &[String],
@@ -206,6 +216,14 @@ impl Runner for RunnerReal {
         config_dumper::dump_config(args, streams)
     }
 
+    fn rotate_node_key(
+        &self,
+        args: &[String],
+        streams: &mut StdStreams<'_>,
+    ) -> Result<i32, ConfiguratorError> {
+        node_key_rotator::rotate_node_key(args, streams)
+    }
+
     fn initialization(
         &self,
         args: &[String],
@@ -258,6 +276,8 @@ mod tests {
         run_service_results: RefCell<Vec<Result<i32, ConfiguratorError>>>,
         dump_config_params: Arc<Mutex<Vec<Vec<String>>>>,
         dump_config_results: RefCell<Vec<Result<i32, ConfiguratorError>>>,
+        rotate_node_key_params: Arc<Mutex<Vec<Vec<String>>>>,
+        rotate_node_key_results: RefCell<Vec<Result<i32, ConfiguratorError>>>,
         initialization_params: Arc<Mutex<Vec<Vec<String>>>>,
         initialization_results: RefCell<Vec<Result<i32, ConfiguratorError>>>,
         configuration_run_params: Arc<Mutex<Vec<Vec<String>>>>,
@@ -283,6 +303,18 @@ mod tests {
             self.dump_config_results.borrow_mut().remove(0)
         }
 
+        fn rotate_node_key(
+            &self,
+            args: &[String],
+            _streams: &mut StdStreams<'_>,
+        ) -> Result<i32, ConfiguratorError> {
+            self.rotate_node_key_params
+                .lock()
+                .unwrap()
+                .push(args.to_vec());
+            self.rotate_node_key_results.borrow_mut().remove(0)
+        }
+
         fn initialization(
             &self,
             args: &[String],
@@ -318,6 +350,8 @@ mod tests {
                 run_service_results: RefCell::new(vec![]),
                 dump_config_params: Arc::new(Mutex::new(vec![])),
                 dump_config_results: RefCell::new(vec![]),
+                rotate_node_key_params: Arc::new(Mutex::new(vec![])),
+                rotate_node_key_results: RefCell::new(vec![]),
                 initialization_params: Arc::new(Mutex::new(vec![])),
                 initialization_results: RefCell::new(vec![]),
                 configuration_run_params: Arc::new(Mutex::new(vec![])),
@@ -345,6 +379,16 @@ mod tests {
             self
         }
 
+        pub fn rotate_node_key_params(mut self, params: &Arc<Mutex<Vec<Vec<String>>>>) -> Self {
+            self.rotate_node_key_params = params.clone();
+            self
+        }
+
+        pub fn rotate_node_key_result(self, result: Result<i32, ConfiguratorError>) -> Self {
+            self.rotate_node_key_results.borrow_mut().push(result);
+            self
+        }
+
         pub fn initialization_params(mut self, params: &Arc<Mutex<Vec<Vec<String>>>>) -> Self {
             self.initialization_params = params.clone();
             self
@@ -387,6 +431,13 @@ mod tests {
             .for_each(|args| check_mode(args, Mode::DumpConfig, false));
     }
 
+    #[test]
+    fn rotate_node_key() {
+        [["--rotate-node-key"]]
+            .iter()
+            .for_each(|args| check_mode(args, Mode::RotateNodeKey, false));
+    }
+
     #[test]
     fn initialization() {
         [["--initialization"]]
@@ -436,6 +487,11 @@ mod tests {
             Mode::DumpConfig,
             false,
         );
+        check_mode(
+            &["--initialization", "--rotate-node-key"],
+            Mode::RotateNodeKey,
+            false,
+        );
     }
 
     #[test]
@@ -444,6 +500,7 @@ mod tests {
             ["--booga", "--goober", "--generate-wallet", "--dump-config"],
             ["--booga", "--goober", "--recover-wallet", "--dump-config"],
             ["--booga", "--goober", "--initialization", "--dump-config"],
+            ["--booga", "--goober", "--rotate-node-key", "--dump-config"],
             [
                 "--generate-wallet",
                 "--recover_wallet",
@@ -620,6 +677,8 @@ parm2 - msg2\n"
         let runner = RunnerMock::new()
             .dump_config_params(&runner_params_arc)
             .dump_config_result(Ok(0))
+            .rotate_node_key_params(&runner_params_arc)
+            .rotate_node_key_result(Ok(0))
             .configuration_run_params(&runner_params_arc)
             .configuration_run_result(Ok(0))
             .configuration_run_result(Ok(0));
@@ -629,11 +688,13 @@ parm2 - msg2\n"
             .expect_privilege_params(&dropper_params_arc)
             .expect_privilege_result(false)
             .expect_privilege_result(false)
+            .expect_privilege_result(false)
             .expect_privilege_result(false);
         subject.privilege_dropper = Box::new(privilege_dropper);
         let mut generate_wallet_holder = FakeStreamHolder::new();
         let mut recover_wallet_holder = FakeStreamHolder::new();
         let mut dump_config_holder = FakeStreamHolder::new();
+        let mut rotate_node_key_holder = FakeStreamHolder::new();
 
         let generate_wallet_exit_code = subject.go(
             &["--generate-wallet".to_string()],
@@ -647,6 +708,10 @@ parm2 - msg2\n"
             &["--dump-config".to_string()],
             &mut dump_config_holder.streams(),
         );
+        let rotate_node_key_exit_code = subject.go(
+            &["--rotate-node-key".to_string()],
+            &mut rotate_node_key_holder.streams(),
+        );
 
         assert_eq!(generate_wallet_exit_code, 0);
         assert_eq!(generate_wallet_holder.stdout.get_string(), "");
@@ -666,15 +731,22 @@ parm2 - msg2\n"
             dump_config_holder.stderr.get_string(),
             RunModes::privilege_mismatch_message(&Mode::DumpConfig, false)
         );
+        assert_eq!(rotate_node_key_exit_code, 0);
+        assert_eq!(rotate_node_key_holder.stdout.get_string(), "");
+        assert_eq!(
+            rotate_node_key_holder.stderr.get_string(),
+            RunModes::privilege_mismatch_message(&Mode::RotateNodeKey, false)
+        );
         let params = dropper_params_arc.lock().unwrap();
-        assert_eq!(*params, vec![false, false, false]);
+        assert_eq!(*params, vec![false, false, false, false]);
         let params = runner_params_arc.lock().unwrap();
         assert_eq!(
             *params,
             vec![
                 vec!["--generate-wallet"],
                 vec!["--recover-wallet"],
-                vec!["--dump-config"]
+                vec!["--dump-config"],
+                vec!["--rotate-node-key"]
             ]
         )
     }
@@ -8,13 +8,14 @@ use crate::node_configurator::node_configurator_standard::NodeConfiguratorStanda
 use crate::node_configurator::NodeConfigurator;
 use crate::node_configurator::RealDirsWrapper;
 use crate::sub_lib;
+use crate::sub_lib::logger::LogFilters;
 use crate::sub_lib::socket_server::SocketServer;
 use backtrace::Backtrace;
 use chrono::{DateTime, Local};
-use flexi_logger::LogSpecBuilder;
 use flexi_logger::Logger;
 use flexi_logger::{Cleanup, Criterion, LevelFilter, Naming};
 use flexi_logger::{DeferredNow, Duplicate, Record};
+use flexi_logger::{LogSpecBuilder, LogSpecification, ReconfigurationHandle};
 use futures::try_ready;
 use lazy_static::lazy_static;
 use masq_lib::command::Command;
@@ -146,6 +147,13 @@ impl Default for ServerInitializer {
 
 lazy_static! {
     pub static ref LOGFILE_NAME: Mutex<PathBuf> = Mutex::new(PathBuf::from("uninitialized"));
+    static ref LOG_CONFIGURATION: Mutex<Option<LogConfiguration>> = Mutex::new(None);
+}
+
+struct LogConfiguration {
+    handle: ReconfigurationHandle,
+    log_level: LevelFilter,
+    log_filters: LogFilters,
 }
 
 pub trait LoggerInitializerWrapper: Send {
@@ -154,10 +162,63 @@ pub trait LoggerInitializerWrapper: Send {
         file_path: PathBuf,
         real_user: &RealUser,
         log_level: LevelFilter,
+        log_filters: &LogFilters,
         discriminant_opt: Option<&str>,
     );
 }
 
+// Changes the log level and log filters of the running logging subsystem
+pub trait LogReconfigurer: Send {
+    fn current(&self) -> (LevelFilter, LogFilters);
+    fn reconfigure(&mut self, log_level: LevelFilter, log_filters: LogFilters);
+}
+
+pub struct LogReconfigurerReal {}
+
+impl LogReconfigurer for LogReconfigurerReal {
+    fn current(&self) -> (LevelFilter, LogFilters) {
+        let guard = log_configuration_guard();
+        let configuration = guard
+            .as_ref()
+            .expect("Logging subsystem is not initialized");
+        (configuration.log_level, configuration.log_filters.clone())
+    }
+
+    fn reconfigure(&mut self, log_level: LevelFilter, log_filters: LogFilters) {
+        let mut guard = log_configuration_guard();
+        let configuration = guard
+            .as_mut()
+            .expect("Logging subsystem is not initialized");
+        configuration
+            .handle
+            .set_new_spec(make_log_specification(log_level, &log_filters));
+        configuration.log_level = log_level;
+        configuration.log_filters = log_filters;
+    }
+}
+
+fn log_configuration_guard<'a>() -> MutexGuard<'a, Option<LogConfiguration>> {
+    match LOG_CONFIGURATION.lock() {
+        Ok(guard) => guard,
+        Err(poison_err) => poison_err.into_inner(),
+    }
+}
+
+pub fn make_log_specification(
+    log_level: LevelFilter,
+    log_filters: &LogFilters,
+) -> LogSpecification {
+    let mut builder = LogSpecBuilder::new();
+    builder
+        .default(log_level)
+        .module("tokio", LevelFilter::Off)
+        .module("mio", LevelFilter::Off);
+    log_filters.filters.iter().for_each(|(component, level)| {
+        builder.module(component, *level);
+    });
+    builder.build()
+}
+
 pub struct LoggerInitializerWrapperReal {}
 
 impl LoggerInitializerWrapper for LoggerInitializerWrapperReal {
@@ -166,30 +227,30 @@ impl LoggerInitializerWrapper for LoggerInitializerWrapperReal {
         file_path: PathBuf,
         real_user: &RealUser,
         log_level: LevelFilter,
+        log_filters: &LogFilters,
         discriminant_opt: Option<&str>,
     ) {
-        let mut logger = Logger::with(
-            LogSpecBuilder::new()
-                .default(log_level)
-                .module("tokio", LevelFilter::Off)
-                .module("mio", LevelFilter::Off)
-                .build(),
-        )
-        .log_to_file()
-        .directory(file_path.clone())
-        .print_message()
-        .duplicate_to_stderr(Duplicate::Info)
-        .suppress_timestamp()
-        .format(format_function)
-        .rotate(
-            Criterion::Size(100_000_000),
-            Naming::Numbers,
-            Cleanup::KeepZipFiles(50),
-        );
+        let mut logger = Logger::with(make_log_specification(log_level, log_filters))
+            .log_to_file()
+            .directory(file_path.clone())
+            .print_message()
+            .duplicate_to_stderr(Duplicate::Info)
+            .suppress_timestamp()
+            .format(format_function)
+            .rotate(
+                Criterion::Size(100_000_000),
+                Naming::Numbers,
+                Cleanup::KeepZipFiles(50),
+            );
         if let Some(discriminant) = discriminant_opt {
             logger = logger.discriminant(discriminant);
         }
-        logger.start().expect("Logging subsystem failed to start");
+        let handle = logger.start().expect("Logging subsystem failed to start");
+        *log_configuration_guard() = Some(LogConfiguration {
+            handle,
+            log_level,
+            log_filters: log_filters.clone(),
+        });
         let privilege_dropper = PrivilegeDropperReal::new();
         let logfile_name = file_path.join(format!(
             "MASQNode_{}rCURRENT.log",
@@ -309,7 +370,8 @@ pub fn real_format_function(
 pub mod test_utils {
     use crate::bootstrapper::RealUser;
     use crate::privilege_drop::PrivilegeDropper;
-    use crate::server_initializer::LoggerInitializerWrapper;
+    use crate::server_initializer::{LogReconfigurer, LoggerInitializerWrapper};
+    use crate::sub_lib::logger::LogFilters;
     #[cfg(not(target_os = "windows"))]
     use crate::test_utils::logging::init_test_logging;
     use log::LevelFilter;
@@ -379,8 +441,10 @@ pub mod test_utils {
         }
     }
 
+    pub type LoggerInitParameters = (PathBuf, RealUser, LevelFilter, LogFilters, Option<String>);
+
     pub struct LoggerInitializerWrapperMock {
-        init_parameters: Arc<Mutex<Vec<(PathBuf, RealUser, LevelFilter, Option<String>)>>>,
+        init_parameters: Arc<Mutex<Vec<LoggerInitParameters>>>,
     }
 
     impl LoggerInitializerWrapper for LoggerInitializerWrapperMock {
@@ -389,12 +453,14 @@ pub mod test_utils {
             file_path: PathBuf,
             real_user: &RealUser,
             log_level: LevelFilter,
+            log_filters: &LogFilters,
             name_segment: Option<&str>,
         ) {
             self.init_parameters.lock().unwrap().push((
                 file_path,
                 real_user.clone(),
                 log_level,
+                log_filters.clone(),
                 match name_segment {
                     Some(s) => Some(s.to_string()),
                     None => None,
@@ -414,12 +480,47 @@ pub mod test_utils {
 
         pub fn init_parameters(
             mut self,
-            parameters: &Arc<Mutex<Vec<(PathBuf, RealUser, LevelFilter, Option<String>)>>>,
+            parameters: &Arc<Mutex<Vec<LoggerInitParameters>>>,
         ) -> Self {
             self.init_parameters = parameters.clone();
             self
         }
     }
+
+    pub struct LogReconfigurerMock {
+        current: (LevelFilter, LogFilters),
+        reconfigure_params: Arc<Mutex<Vec<(LevelFilter, LogFilters)>>>,
+    }
+
+    impl LogReconfigurer for LogReconfigurerMock {
+        fn current(&self) -> (LevelFilter, LogFilters) {
+            self.current.clone()
+        }
+
+        fn reconfigure(&mut self, log_level: LevelFilter, log_filters: LogFilters) {
+            self.reconfigure_params
+                .lock()
+                .unwrap()
+                .push((log_level, log_filters));
+        }
+    }
+
+    impl LogReconfigurerMock {
+        pub fn new(log_level: LevelFilter, log_filters: LogFilters) -> Self {
+            Self {
+                current: (log_level, log_filters),
+                reconfigure_params: Arc::new(Mutex::new(vec![])),
+            }
+        }
+
+        pub fn reconfigure_params(
+            mut self,
+            params: &Arc<Mutex<Vec<(LevelFilter, LogFilters)>>>,
+        ) -> Self {
+            self.reconfigure_params = params.clone();
+            self
+        }
+    }
 }
 
 #[cfg(test)]
@@ -429,6 +530,7 @@ pub mod tests {
     use crate::server_initializer::test_utils::PrivilegeDropperMock;
     use crate::test_utils::logfile_name_guard::LogfileNameGuard;
     use crate::test_utils::logging::{init_test_logging, TestLogHandler};
+    use log::Level;
     use masq_lib::crash_point::CrashPoint;
     use masq_lib::shared_schema::{ConfiguratorError, ParamError};
     use masq_lib::test_utils::fake_stream_holder::{
@@ -641,6 +743,28 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn make_log_specification_applies_log_filters_over_log_level() {
+        let log_filters = LogFilters {
+            filters: vec![
+                ("Neighborhood".to_string(), LevelFilter::Debug),
+                ("NeighborhoodDatabase".to_string(), LevelFilter::Error),
+                ("Hopper".to_string(), LevelFilter::Off),
+            ],
+        };
+
+        let result = make_log_specification(LevelFilter::Warn, &log_filters);
+
+        assert!(result.enabled(Level::Debug, "Neighborhood"));
+        assert!(!result.enabled(Level::Trace, "Neighborhood"));
+        assert!(!result.enabled(Level::Warn, "NeighborhoodDatabase"));
+        assert!(result.enabled(Level::Error, "NeighborhoodDatabase"));
+        assert!(!result.enabled(Level::Error, "Hopper"));
+        assert!(result.enabled(Level::Warn, "Accountant"));
+        assert!(!result.enabled(Level::Info, "Accountant"));
+        assert!(!result.enabled(Level::Error, "tokio"));
+    }
+
     #[test]
     fn panic_hook_handles_missing_location_and_unprintable_payload() {
         init_test_logging();
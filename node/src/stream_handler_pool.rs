@@ -3,6 +3,7 @@ use crate::bootstrapper::PortConfiguration;
 use crate::discriminator::DiscriminatorFactory;
 use crate::json_masquerader::JsonMasquerader;
 use crate::masquerader::Masquerader;
+use crate::metrics::{metrics, ACTIVE_STREAMS};
 use crate::stream_messages::*;
 use crate::stream_reader::StreamReaderReal;
 use crate::stream_writer_sorted::StreamWriterSorted;
@@ -245,6 +246,7 @@ impl StreamHandlerPool {
         let (tx, rx) = self.channel_factory.make(peer_addr);
         self.stream_writers
             .insert(StreamWriterKey::from(peer_addr), Some(tx));
+        self.report_active_streams();
 
         if is_clandestine {
             tokio::spawn(StreamWriterUnsorted::new(write_stream, peer_addr, rx));
@@ -253,6 +255,16 @@ impl StreamHandlerPool {
         };
     }
 
+    fn report_active_streams(&self) {
+        // Writers still waiting for their connections to complete aren't active yet
+        let active_streams = self
+            .stream_writers
+            .values()
+            .filter(|writer_opt| writer_opt.is_some())
+            .count();
+        metrics().set(&ACTIVE_STREAMS, &[("pool", "node")], active_streams as f64);
+    }
+
     fn handle_transmit_data_msg(&mut self, msg: TransmitDataMsg) {
         // TODO Can be recombined with DispatcherNodeQueryMessage after SC-358/GH-96
         debug!(
@@ -335,6 +347,7 @@ impl StreamHandlerPool {
             None | Some(None) => false,
             Some(Some(_sender_wrapper)) => true,
         };
+        self.report_active_streams();
         let stream_shutdown_msg = StreamShutdownMsg {
             peer_addr: msg.peer_addr,
             stream_type: msg.stream_type,
@@ -423,6 +436,7 @@ impl StreamHandlerPool {
                         );
                         self.stream_writers
                             .remove(&StreamWriterKey::from(peer_addr));
+                        self.report_active_streams();
                     }
                     Ok(_) => {
                         debug!(self.logger, "Queued {} bytes for transmission", packet_len);
@@ -434,6 +448,7 @@ impl StreamHandlerPool {
                     );
                     self.stream_writers
                         .remove(&StreamWriterKey::from(peer_addr));
+                    self.report_active_streams();
                 }
             }
             Some(None) => {
@@ -1068,6 +1083,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn handle_remove_stream_msg_reports_active_streams_to_metrics() {
+        let (recorder, _, _) = make_recorder();
+        let system = System::new("test");
+        let sub = recorder.start().recipient::<StreamShutdownMsg>();
+        let mut subject = StreamHandlerPool::new(vec![]);
+        let peer_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+        let local_addr = SocketAddr::from_str("127.0.0.1:0").unwrap();
+        vec!["1.2.3.4:5678", "2.3.4.5:6789"]
+            .into_iter()
+            .for_each(|addr| {
+                subject.stream_writers.insert(
+                    StreamWriterKey::from(SocketAddr::from_str(addr).unwrap()),
+                    Some(Box::new(SenderWrapperMock::new(local_addr))),
+                );
+            });
+        subject.stream_writers.insert(
+            StreamWriterKey::from(SocketAddr::from_str("3.4.5.6:7890").unwrap()),
+            None,
+        );
+
+        subject.handle_remove_stream_msg(RemoveStreamMsg {
+            peer_addr,
+            local_addr,
+            stream_type: RemovedStreamType::Clandestine,
+            sub,
+        });
+
+        System::current().stop_with_code(0);
+        system.run();
+        assert_eq!(
+            metrics().value(&ACTIVE_STREAMS, &[("pool", "node")]),
+            Some(1.0)
+        );
+    }
     #[test]
     fn when_stream_handler_pool_fails_to_create_nonexistent_stream_for_write_then_it_logs_and_notifies_neighborhood(
     ) {
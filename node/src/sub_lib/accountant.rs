@@ -1,5 +1,7 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
-use crate::accountant::{ReceivedPayments, SentPayments};
+use crate::accountant::{
+    ReceivedPayments, ReportTransactionReceipts, ResentPayments, SentPayments,
+};
 use crate::sub_lib::peer_actors::{BindMessage, StartMessage};
 use crate::sub_lib::wallet::Wallet;
 use actix::Message;
@@ -22,6 +24,7 @@ lazy_static! {
 pub struct AccountantConfig {
     pub payable_scan_interval: Duration,
     pub payment_received_scan_interval: Duration,
+    pub pending_payment_scan_interval: Duration,
 }
 
 #[derive(Clone)]
@@ -34,6 +37,8 @@ pub struct AccountantSubs {
     pub report_exit_service_consumed: Recipient<ReportExitServiceConsumedMessage>,
     pub report_new_payments: Recipient<ReceivedPayments>,
     pub report_sent_payments: Recipient<SentPayments>,
+    pub report_transaction_receipts: Recipient<ReportTransactionReceipts>,
+    pub report_resent_payments: Recipient<ResentPayments>,
     pub ui_message_sub: Recipient<NodeFromUiMessage>,
 }
 
@@ -112,6 +117,8 @@ mod tests {
             report_exit_service_consumed: recipient!(recorder, ReportExitServiceConsumedMessage),
             report_new_payments: recipient!(recorder, ReceivedPayments),
             report_sent_payments: recipient!(recorder, SentPayments),
+            report_transaction_receipts: recipient!(recorder, ReportTransactionReceipts),
+            report_resent_payments: recipient!(recorder, ResentPayments),
             ui_message_sub: recipient!(recorder, NodeFromUiMessage),
         };
 
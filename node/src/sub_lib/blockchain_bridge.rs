@@ -2,7 +2,8 @@
 
 use crate::accountant::payable_dao::{PayableAccount, Payment};
 use crate::blockchain::blockchain_bridge::RetrieveTransactions;
-use crate::blockchain::blockchain_interface::BlockchainResult;
+use crate::blockchain::blockchain_interface::{BlockchainResult, TransactionStatus};
+use crate::blockchain::failover_transport::FailoverPolicy;
 use crate::sub_lib::peer_actors::BindMessage;
 use actix::Message;
 use actix::Recipient;
@@ -13,8 +14,13 @@ use std::fmt::{Debug, Formatter};
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct BlockchainBridgeConfig {
     pub blockchain_service_url: Option<String>,
-    pub chain_id: u8,
+    pub blockchain_service_failover: FailoverPolicy,
+    pub chain_id: u64,
     pub gas_price: u64,
+    pub max_gas_price: u64,
+    pub batch_payments: bool,
+    pub scan_block_range: u64,
+    pub confirmation_depth: u64,
 }
 
 #[derive(Clone)]
@@ -22,6 +28,8 @@ pub struct BlockchainBridgeSubs {
     pub bind: Recipient<BindMessage>,
     pub report_accounts_payable: Recipient<ReportAccountsPayable>,
     pub retrieve_transactions: Recipient<RetrieveTransactions>,
+    pub request_transaction_receipts: Recipient<RequestTransactionReceipts>,
+    pub resubmit_payments: Recipient<ResubmitPayments>,
     pub ui_sub: Recipient<NodeFromUiMessage>,
 }
 
@@ -36,6 +44,16 @@ pub struct ReportAccountsPayable {
     pub accounts: Vec<PayableAccount>,
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct RequestTransactionReceipts {
+    pub payments: Vec<Payment>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ResubmitPayments {
+    pub payments: Vec<Payment>,
+}
+
 #[derive(Clone, PartialEq, Debug, Message)]
 pub struct SetDbPasswordMsg {
     pub client_id: u64,
@@ -52,6 +70,14 @@ impl Message for ReportAccountsPayable {
     type Result = Result<Vec<BlockchainResult<Payment>>, String>;
 }
 
+impl Message for RequestTransactionReceipts {
+    type Result = Vec<BlockchainResult<TransactionStatus>>;
+}
+
+impl Message for ResubmitPayments {
+    type Result = Result<Vec<BlockchainResult<Payment>>, String>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,6 +93,8 @@ mod tests {
             bind: recipient!(recorder, BindMessage),
             report_accounts_payable: recipient!(recorder, ReportAccountsPayable),
             retrieve_transactions: recipient!(recorder, RetrieveTransactions),
+            request_transaction_receipts: recipient!(recorder, RequestTransactionReceipts),
+            resubmit_payments: recipient!(recorder, ResubmitPayments),
             ui_sub: recipient!(recorder, NodeFromUiMessage),
         };
 
@@ -24,6 +24,7 @@ lazy_static! {
 
 pub struct CryptDEReal {
     public_key: PublicKey,
+    private_key: PrivateKey,
     encryption_secret_key: encryption::SecretKey,
     signing_secret_key: signing::SecretKey,
     digest: [u8; 32],
@@ -96,8 +97,7 @@ impl CryptDE for CryptDEReal {
     }
 
     fn private_key(&self) -> &PrivateKey {
-        // Hypothesis: private_key() is unused and unnecessary in production code. Consider making it #[cfg(test)].
-        unimplemented!()
+        &self.private_key
     }
 
     fn public_key(&self) -> &PublicKey {
@@ -107,6 +107,7 @@ impl CryptDE for CryptDEReal {
     fn dup(&self) -> Box<dyn CryptDE> {
         Box::new(CryptDEReal {
             public_key: self.public_key.clone(),
+            private_key: self.private_key.clone(),
             encryption_secret_key: encryption::SecretKey(self.encryption_secret_key.0),
             signing_secret_key: signing::SecretKey(self.signing_secret_key.0),
             digest: self.digest,
@@ -182,15 +183,45 @@ impl CryptDE for CryptDEReal {
 }
 
 impl CryptDEReal {
-    pub fn new(chain_id: u8) -> Self {
-        let (e_public, e_secret) = encryption::gen_keypair();
-        let (s_public, s_secret) = signing::gen_keypair();
-        let public_key = Self::local_public_key_from(&e_public, &s_public);
+    pub fn new(chain_id: u64) -> Self {
+        let (_, e_secret) = encryption::gen_keypair();
+        let (_, s_secret) = signing::gen_keypair();
+        Self::from_secret_keys(e_secret, s_secret, chain_id)
+    }
+
+    // The private key is the encryption secret key followed by the signing secret key, as
+    // produced by private_key().
+    pub fn from_private_key(private_key: &PrivateKey, chain_id: u64) -> Result<Self, String> {
+        let expected_len = cxsp::SECRETKEYBYTES + signing::SECRETKEYBYTES;
+        if private_key.len() != expected_len {
+            return Err(format!(
+                "Private key must be {} bytes, not {}",
+                expected_len,
+                private_key.len()
+            ));
+        }
+        let (e_part, s_part) = private_key.as_slice().split_at(cxsp::SECRETKEYBYTES);
+        let e_secret = encryption::SecretKey::from_slice(e_part)
+            .expect("Encryption secret key length already checked");
+        let s_secret = signing::SecretKey::from_slice(s_part)
+            .expect("Signing secret key length already checked");
+        Ok(Self::from_secret_keys(e_secret, s_secret, chain_id))
+    }
+
+    fn from_secret_keys(
+        e_secret: encryption::SecretKey,
+        s_secret: signing::SecretKey,
+        chain_id: u64,
+    ) -> Self {
+        let public_key =
+            Self::local_public_key_from(&e_secret.public_key(), &s_secret.public_key());
+        let private_key = PrivateKey::from([&e_secret.0[..], &s_secret.0[..]].concat());
         let digest = cryptde::create_digest(&public_key, &contract_address(chain_id));
         let pre_shared_data = contract_address(chain_id).0;
 
         Self {
             public_key,
+            private_key,
             encryption_secret_key: e_secret,
             signing_secret_key: s_secret,
             digest,
@@ -244,6 +275,37 @@ mod tests {
         assert_ne!(first_subject.public_key(), second_subject.public_key());
     }
 
+    #[test]
+    fn private_key_round_trips_through_from_private_key() {
+        let original = CryptDEReal::default();
+        let data = PlainData::new(b"Fourscore and seven years ago");
+
+        let subject =
+            CryptDEReal::from_private_key(original.private_key(), DEFAULT_CHAIN_ID).unwrap();
+
+        assert_eq!(subject.public_key(), original.public_key());
+        assert_eq!(subject.private_key(), original.private_key());
+        assert_eq!(subject.digest(), original.digest());
+        let encrypted = original.encode(subject.public_key(), &data).unwrap();
+        assert_eq!(subject.decode(&encrypted).unwrap(), data);
+        let signature = subject.sign(&data).unwrap();
+        assert!(original.verify_signature(&data, &signature, original.public_key()));
+    }
+
+    #[test]
+    fn from_private_key_rejects_key_of_wrong_length() {
+        let result =
+            CryptDEReal::from_private_key(&PrivateKey::new(b"too short"), DEFAULT_CHAIN_ID);
+
+        assert_eq!(
+            result.err(),
+            Some(format!(
+                "Private key must be {} bytes, not 9",
+                cxsp::SECRETKEYBYTES + signing::SECRETKEYBYTES
+            ))
+        );
+    }
+
     #[test]
     fn dup_produces_identical_keys() {
         let subject = CryptDEReal::default();
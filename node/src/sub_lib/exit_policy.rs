@@ -0,0 +1,525 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExitRuleAction {
+    Allow,
+    Deny,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExitRuleTarget {
+    Any,
+    Network(IpAddr, u8),
+    HostGlob(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExitRule {
+    pub action: ExitRuleAction,
+    pub target: ExitRuleTarget,
+    pub ports: Option<(u16, u16)>,
+}
+
+impl fmt::Display for ExitRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let action = match self.action {
+            ExitRuleAction::Allow => "allow",
+            ExitRuleAction::Deny => "deny",
+        };
+        let target = match &self.target {
+            ExitRuleTarget::Any => "*".to_string(),
+            ExitRuleTarget::Network(IpAddr::V4(ip), prefix) => format!("{}/{}", ip, prefix),
+            ExitRuleTarget::Network(IpAddr::V6(ip), prefix) => format!("[{}]/{}", ip, prefix),
+            ExitRuleTarget::HostGlob(glob) => glob.clone(),
+        };
+        match self.ports {
+            None => write!(f, "{} {}", action, target),
+            Some((low, high)) if low == high => write!(f, "{} {}:{}", action, target, low),
+            Some((low, high)) => write!(f, "{} {}:{}-{}", action, target, low, high),
+        }
+    }
+}
+
+// Rules look like "deny *:25", "allow *.example.com:443", "deny 10.0.0.0/8", "allow [fd00::]/8:80-89".
+impl FromStr for ExitRule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let mut words = rule.split_whitespace();
+        let action = match words.next() {
+            Some("allow") => ExitRuleAction::Allow,
+            Some("deny") => ExitRuleAction::Deny,
+            _ => return Err(format!("'{}' must begin with 'allow' or 'deny'", rule)),
+        };
+        let target_and_ports = match (words.next(), words.next()) {
+            (Some(target_and_ports), None) => target_and_ports,
+            _ => return Err(format!("'{}' must name exactly one target", rule)),
+        };
+        let (target_str, ports_str_opt) = split_target_and_ports(target_and_ports)
+            .ok_or_else(|| format!("'{}' has an unterminated IPv6 address", rule))?;
+        let target = parse_target(target_str)
+            .ok_or_else(|| format!("'{}' has an unrecognizable target", rule))?;
+        let ports = match ports_str_opt {
+            None => None,
+            Some(ports_str) => {
+                parse_ports(ports_str).ok_or_else(|| format!("'{}' has bad ports", rule))?
+            }
+        };
+        Ok(ExitRule {
+            action,
+            target,
+            ports,
+        })
+    }
+}
+
+impl ExitRule {
+    fn matches(&self, hostname: &str, ip_addr: IpAddr, port: u16) -> bool {
+        let port_matches = match self.ports {
+            None => true,
+            Some((low, high)) => port >= low && port <= high,
+        };
+        port_matches
+            && match &self.target {
+                ExitRuleTarget::Any => true,
+                ExitRuleTarget::Network(network, prefix) => {
+                    is_in_network(ip_addr, *network, *prefix)
+                }
+                ExitRuleTarget::HostGlob(glob) => glob_matches(glob, hostname),
+            }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExitPolicy {
+    pub rules: Vec<ExitRule>,
+    pub exit_ports_opt: Option<Vec<u16>>,
+    pub block_private_ranges: bool,
+}
+
+impl Default for ExitPolicy {
+    fn default() -> Self {
+        Self {
+            rules: vec![],
+            exit_ports_opt: None,
+            block_private_ranges: true,
+        }
+    }
+}
+
+impl ExitPolicy {
+    // Private ranges and --exit-ports are checked before the rules, so that a permissive rule
+    // like "allow *" can't accidentally open up the operator's own network. Then the first
+    // matching rule decides; if none matches, the connection is allowed.
+    pub fn check(&self, hostname: &str, ip_addr: IpAddr, port: u16) -> Result<(), String> {
+        if let Some(exit_ports) = &self.exit_ports_opt {
+            if !exit_ports.contains(&port) {
+                return Err(format!("port {} is not in --exit-ports", port));
+            }
+        }
+        if self.block_private_ranges && is_private(ip_addr) {
+            return Err(format!("{} is a private address", ip_addr));
+        }
+        match self
+            .rules
+            .iter()
+            .find(|rule| rule.matches(hostname, ip_addr, port))
+        {
+            Some(rule) if rule.action == ExitRuleAction::Deny => {
+                Err(format!("denied by exit policy rule '{}'", rule))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn parse_exit_rules(rules: &str) -> Result<Vec<ExitRule>, String> {
+    rules
+        .split(',')
+        .map(|rule| rule.trim())
+        .filter(|rule| !rule.is_empty())
+        .map(ExitRule::from_str)
+        .collect()
+}
+
+fn split_target_and_ports(target_and_ports: &str) -> Option<(&str, Option<&str>)> {
+    let split_at = if target_and_ports.starts_with('[') {
+        target_and_ports.find(']')? + 1
+    } else {
+        target_and_ports.find(':').unwrap_or(target_and_ports.len())
+    };
+    let (target, rest) = target_and_ports.split_at(split_at);
+    let (target, rest) = if target.starts_with('[') && rest.starts_with('/') {
+        match rest.find(':') {
+            Some(colon) => (&target_and_ports[..split_at + colon], &rest[colon..]),
+            None => (target_and_ports, ""),
+        }
+    } else {
+        (target, rest)
+    };
+    match rest {
+        "" => Some((target, None)),
+        rest if rest.starts_with(':') => Some((target, Some(&rest[1..]))),
+        _ => None,
+    }
+}
+
+fn parse_target(target: &str) -> Option<ExitRuleTarget> {
+    if target == "*" {
+        return Some(ExitRuleTarget::Any);
+    }
+    let (address, prefix_opt) = match target.find('/') {
+        Some(slash) => (&target[..slash], Some(&target[slash + 1..])),
+        None => (target, None),
+    };
+    let address = address.trim_start_matches('[').trim_end_matches(']');
+    match IpAddr::from_str(address) {
+        Ok(ip_addr) => {
+            let max_prefix = if ip_addr.is_ipv4() { 32 } else { 128 };
+            let prefix = match prefix_opt {
+                Some(prefix_str) => prefix_str.parse::<u8>().ok()?,
+                None => max_prefix,
+            };
+            if prefix > max_prefix {
+                None
+            } else {
+                Some(ExitRuleTarget::Network(ip_addr, prefix))
+            }
+        }
+        Err(_) if prefix_opt.is_none() && is_host_glob(target) => {
+            Some(ExitRuleTarget::HostGlob(target.to_lowercase()))
+        }
+        Err(_) => None,
+    }
+}
+
+fn parse_ports(ports: &str) -> Option<Option<(u16, u16)>> {
+    if ports == "*" {
+        return Some(None);
+    }
+    let (low, high) = match ports.find('-') {
+        Some(dash) => (
+            ports[..dash].parse::<u16>().ok()?,
+            ports[dash + 1..].parse::<u16>().ok()?,
+        ),
+        None => {
+            let port = ports.parse::<u16>().ok()?;
+            (port, port)
+        }
+    };
+    if low == 0 || low > high {
+        None
+    } else {
+        Some(Some((low, high)))
+    }
+}
+
+fn is_host_glob(target: &str) -> bool {
+    target
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' || c == '*')
+}
+
+fn glob_matches(glob: &str, hostname: &str) -> bool {
+    let hostname = hostname.to_lowercase();
+    let mut pieces = glob.split('*');
+    let first = pieces.next().unwrap_or("");
+    if !hostname.starts_with(first) {
+        return false;
+    }
+    let mut remainder = &hostname[first.len()..];
+    let mut pieces: Vec<&str> = pieces.collect();
+    let last = match pieces.pop() {
+        Some(last) => last,
+        None => return remainder.is_empty(),
+    };
+    for piece in pieces {
+        match remainder.find(piece) {
+            Some(index) => remainder = &remainder[index + piece.len()..],
+            None => return false,
+        }
+    }
+    remainder.ends_with(last)
+}
+
+fn is_in_network(ip_addr: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    match (canonical(ip_addr), network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = if prefix == 0 {
+                0
+            } else {
+                u32::MAX << (32 - prefix as u32)
+            };
+            (u32::from(ip) & mask) == (u32::from(network) & mask)
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = if prefix == 0 {
+                0
+            } else {
+                u128::MAX << (128 - prefix as u32)
+            };
+            (u128::from(ip) & mask) == (u128::from(network) & mask)
+        }
+        _ => false,
+    }
+}
+
+// An IPv4-mapped IPv6 address reaches the same host as the IPv4 address it contains.
+fn canonical(ip_addr: IpAddr) -> IpAddr {
+    match ip_addr {
+        IpAddr::V6(ip) => match ip.segments() {
+            [0, 0, 0, 0, 0, 0xffff, high, low] => {
+                IpAddr::V4(Ipv4Addr::from(((high as u32) << 16) | low as u32))
+            }
+            _ => ip_addr,
+        },
+        IpAddr::V4(_) => ip_addr,
+    }
+}
+
+fn is_private(ip_addr: IpAddr) -> bool {
+    let private_v4: [(Ipv4Addr, u8); 9] = [
+        (Ipv4Addr::new(0, 0, 0, 0), 8),
+        (Ipv4Addr::new(10, 0, 0, 0), 8),
+        (Ipv4Addr::new(100, 64, 0, 0), 10),
+        (Ipv4Addr::new(127, 0, 0, 0), 8),
+        (Ipv4Addr::new(169, 254, 0, 0), 16),
+        (Ipv4Addr::new(172, 16, 0, 0), 12),
+        (Ipv4Addr::new(192, 168, 0, 0), 16),
+        (Ipv4Addr::new(224, 0, 0, 0), 4),
+        (Ipv4Addr::new(240, 0, 0, 0), 4),
+    ];
+    let private_v6: [(Ipv6Addr, u8); 4] = [
+        (Ipv6Addr::UNSPECIFIED, 128),
+        (Ipv6Addr::LOCALHOST, 128),
+        (Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0), 7),
+        (Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0), 10),
+    ];
+    private_v4
+        .iter()
+        .any(|(network, prefix)| is_in_network(ip_addr, IpAddr::V4(*network), *prefix))
+        || private_v6
+            .iter()
+            .any(|(network, prefix)| is_in_network(ip_addr, IpAddr::V6(*network), *prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(ip_str: &str) -> IpAddr {
+        IpAddr::from_str(ip_str).unwrap()
+    }
+
+    #[test]
+    fn parses_rules_of_every_kind() {
+        let result = parse_exit_rules(
+            "deny *:25, allow *.Example.com:443,deny 10.1.0.0/16:*, allow [fd00::]/8:80-89,deny 1.2.3.4",
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                ExitRule {
+                    action: ExitRuleAction::Deny,
+                    target: ExitRuleTarget::Any,
+                    ports: Some((25, 25)),
+                },
+                ExitRule {
+                    action: ExitRuleAction::Allow,
+                    target: ExitRuleTarget::HostGlob("*.example.com".to_string()),
+                    ports: Some((443, 443)),
+                },
+                ExitRule {
+                    action: ExitRuleAction::Deny,
+                    target: ExitRuleTarget::Network(ip("10.1.0.0"), 16),
+                    ports: None,
+                },
+                ExitRule {
+                    action: ExitRuleAction::Allow,
+                    target: ExitRuleTarget::Network(ip("fd00::"), 8),
+                    ports: Some((80, 89)),
+                },
+                ExitRule {
+                    action: ExitRuleAction::Deny,
+                    target: ExitRuleTarget::Network(ip("1.2.3.4"), 32),
+                    ports: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rules_display_the_way_they_parse() {
+        let rules = "deny *:25,allow *.example.com:443,deny 10.1.0.0/16,allow [fd00::]/8:80-89";
+
+        let result = parse_exit_rules(rules)
+            .unwrap()
+            .into_iter()
+            .map(|rule| rule.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+
+        assert_eq!(result, rules);
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        vec![
+            ("permit *", "'permit *' must begin with 'allow' or 'deny'"),
+            ("deny", "'deny' must name exactly one target"),
+            ("deny a b", "'deny a b' must name exactly one target"),
+            (
+                "deny [fd00::",
+                "'deny [fd00::' has an unterminated IPv6 address",
+            ),
+            (
+                "deny 10.0.0.0/33",
+                "'deny 10.0.0.0/33' has an unrecognizable target",
+            ),
+            ("deny foo/8", "'deny foo/8' has an unrecognizable target"),
+            (
+                "deny b@d.com",
+                "'deny b@d.com' has an unrecognizable target",
+            ),
+            ("deny *:0", "'deny *:0' has bad ports"),
+            ("deny *:90-80", "'deny *:90-80' has bad ports"),
+            ("deny *:http", "'deny *:http' has bad ports"),
+        ]
+        .into_iter()
+        .for_each(|(rule, expected)| {
+            assert_eq!(
+                parse_exit_rules(rule),
+                Err(expected.to_string()),
+                "{}",
+                rule
+            )
+        });
+    }
+
+    #[test]
+    fn default_policy_blocks_private_ranges_and_allows_everything_else() {
+        let subject = ExitPolicy::default();
+
+        vec![
+            "0.1.2.3",
+            "10.1.2.3",
+            "100.64.0.1",
+            "127.0.0.1",
+            "169.254.1.1",
+            "172.16.5.4",
+            "172.31.255.255",
+            "192.168.1.1",
+            "224.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "fd12::1",
+            "fe80::1",
+            "::ffff:192.168.1.1",
+        ]
+        .into_iter()
+        .for_each(|ip_str| {
+            assert_eq!(
+                subject.check("booga.com", ip(ip_str), 80),
+                Err(format!("{} is a private address", ip(ip_str))),
+                "{}",
+                ip_str
+            )
+        });
+        vec!["1.2.3.4", "172.32.0.1", "100.128.0.1", "2001:db8::1"]
+            .into_iter()
+            .for_each(|ip_str| {
+                assert_eq!(
+                    subject.check("booga.com", ip(ip_str), 80),
+                    Ok(()),
+                    "{}",
+                    ip_str
+                )
+            });
+    }
+
+    #[test]
+    fn private_ranges_can_be_unblocked() {
+        let subject = ExitPolicy {
+            block_private_ranges: false,
+            ..ExitPolicy::default()
+        };
+
+        let result = subject.check("localhost", ip("127.0.0.1"), 8080);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn exit_ports_are_enforced_before_rules() {
+        let subject = ExitPolicy {
+            rules: parse_exit_rules("allow *").unwrap(),
+            exit_ports_opt: Some(vec![80, 443]),
+            block_private_ranges: true,
+        };
+
+        assert_eq!(subject.check("booga.com", ip("1.2.3.4"), 443), Ok(()));
+        assert_eq!(
+            subject.check("booga.com", ip("1.2.3.4"), 22),
+            Err("port 22 is not in --exit-ports".to_string())
+        );
+    }
+
+    #[test]
+    fn explicit_allow_rule_does_not_override_private_range_blocking() {
+        let subject = ExitPolicy {
+            rules: parse_exit_rules("allow 192.168.0.0/16").unwrap(),
+            ..ExitPolicy::default()
+        };
+
+        let result = subject.check("printer", ip("192.168.1.5"), 631);
+
+        assert_eq!(result, Err("192.168.1.5 is a private address".to_string()));
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let subject = ExitPolicy {
+            rules: parse_exit_rules(
+                "allow mail.example.com:25,deny *:25,deny *.example.com,deny 5.6.0.0/16:1-1024,allow *",
+            )
+            .unwrap(),
+            ..ExitPolicy::default()
+        };
+
+        assert_eq!(subject.check("mail.example.com", ip("1.2.3.4"), 25), Ok(()));
+        assert_eq!(
+            subject.check("smtp.booga.com", ip("1.2.3.4"), 25),
+            Err("denied by exit policy rule 'deny *:25'".to_string())
+        );
+        assert_eq!(
+            subject.check("WWW.Example.COM", ip("1.2.3.4"), 443),
+            Err("denied by exit policy rule 'deny *.example.com'".to_string())
+        );
+        assert_eq!(
+            subject.check("booga.com", ip("5.6.7.8"), 443),
+            Err("denied by exit policy rule 'deny 5.6.0.0/16:1-1024'".to_string())
+        );
+        assert_eq!(subject.check("booga.com", ip("5.6.7.8"), 8443), Ok(()));
+        assert_eq!(subject.check("example.com", ip("1.2.3.4"), 443), Ok(()));
+    }
+
+    #[test]
+    fn glob_matching_handles_wildcards_anywhere() {
+        assert!(glob_matches("*", "anything.at.all"));
+        assert!(glob_matches("*.com", "booga.com"));
+        assert!(!glob_matches("*.com", "booga.co"));
+        assert!(glob_matches("www.*.com", "www.booga.com"));
+        assert!(!glob_matches("www.*.com", "ftp.booga.com"));
+        assert!(glob_matches("a*b*c", "abc"));
+        assert!(glob_matches("a*b*c", "a-x-b-y-c"));
+        assert!(!glob_matches("a*b*c", "a-x-c-y-b"));
+        assert!(glob_matches("booga.com", "BOOGA.com"));
+        assert!(!glob_matches("booga.com", "booga.com.evil"));
+    }
+}
@@ -5,11 +5,14 @@ use crate::sub_lib::cryptde::CryptDE;
 use crate::sub_lib::cryptde::CryptData;
 use crate::sub_lib::cryptde::PublicKey;
 use crate::sub_lib::dispatcher::InboundClientData;
-use crate::sub_lib::neighborhood::GossipFailure_0v1;
+use crate::sub_lib::neighborhood::{GossipFailure_0v1, RatePackChangeMessage};
 use crate::sub_lib::node_addr::NodeAddr;
 use crate::sub_lib::peer_actors::BindMessage;
-use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
-use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
+use crate::sub_lib::proxy_client::{
+    ClientResponsePayload_0v1, DatagramResponsePayload_0v1, DnsResolveFailure_0v1,
+    ExitPolicyViolation_0v1,
+};
+use crate::sub_lib::proxy_server::{ClientRequestPayload_0v1, DatagramRequestPayload_0v1};
 use crate::sub_lib::route::Route;
 use crate::sub_lib::versioned_data::VersionedData;
 use crate::sub_lib::wallet::Wallet;
@@ -70,6 +73,9 @@ pub enum MessageType {
     Gossip(VersionedData<Gossip_0v1>),
     GossipFailure(VersionedData<GossipFailure_0v1>),
     DnsResolveFailed(VersionedData<DnsResolveFailure_0v1>),
+    ExitPolicyViolated(VersionedData<ExitPolicyViolation_0v1>),
+    DatagramRequest(VersionedData<DatagramRequestPayload_0v1>),
+    DatagramResponse(VersionedData<DatagramResponsePayload_0v1>),
 }
 
 impl IncipientCoresPackage {
@@ -134,6 +140,7 @@ pub struct HopperSubs {
     pub from_hopper_client: Recipient<IncipientCoresPackage>,
     pub from_hopper_client_no_lookup: Recipient<NoLookupIncipientCoresPackage>,
     pub from_dispatcher: Recipient<InboundClientData>,
+    pub rate_pack_change: Recipient<RatePackChangeMessage>,
 }
 
 impl Debug for HopperSubs {
@@ -165,6 +172,7 @@ mod tests {
             from_hopper_client: recipient!(recorder, IncipientCoresPackage),
             from_hopper_client_no_lookup: recipient!(recorder, NoLookupIncipientCoresPackage),
             from_dispatcher: recipient!(recorder, InboundClientData),
+            rate_pack_change: recipient!(recorder, RatePackChangeMessage),
         };
 
         assert_eq!(format!("{:?}", subject), "HopperSubs");
@@ -1,9 +1,13 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 use log::logger;
 use log::Level;
+use log::LevelFilter;
 #[cfg(not(test))]
 use log::Metadata;
 use log::Record;
+use masq_lib::shared_schema::common_validators::validate_log_filters;
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Clone)]
 pub struct Logger {
@@ -153,6 +157,52 @@ impl Logger {
     }
 }
 
+// Overrides of the global log level for particular components, keyed on the names their Loggers
+// are created with: for example, Neighborhood=debug,Hopper=warn
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LogFilters {
+    pub filters: Vec<(String, LevelFilter)>,
+}
+
+impl FromStr for LogFilters {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(LogFilters::default());
+        }
+        validate_log_filters(s.to_string()).map_err(|bad| format!("Bad log filters: {}", bad))?;
+        let filters = s
+            .split(',')
+            .map(|filter| {
+                let mut pieces = filter.split('=');
+                let component = pieces.next().expect("Validation failed");
+                let level = pieces.next().expect("Validation failed");
+                (
+                    component.to_string(),
+                    LevelFilter::from_str(level).expect("Validation failed"),
+                )
+            })
+            .collect();
+        Ok(LogFilters { filters })
+    }
+}
+
+impl fmt::Display for LogFilters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let filters = self
+            .filters
+            .iter()
+            .map(|(component, level)| format!("{}={}", component, level_name(*level)))
+            .collect::<Vec<String>>();
+        write!(f, "{}", filters.join(","))
+    }
+}
+
+pub fn level_name(level: LevelFilter) -> String {
+    level.to_string().to_lowercase()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -392,6 +442,37 @@ mod tests {
         tlh.exists_log_containing("error! 42");
     }
 
+    #[test]
+    fn log_filters_can_be_parsed_and_displayed() {
+        let result = LogFilters::from_str("Neighborhood=debug,Hopper=WARN").unwrap();
+
+        assert_eq!(
+            result,
+            LogFilters {
+                filters: vec![
+                    ("Neighborhood".to_string(), LevelFilter::Debug),
+                    ("Hopper".to_string(), LevelFilter::Warn),
+                ]
+            }
+        );
+        assert_eq!(result.to_string(), "Neighborhood=debug,Hopper=warn");
+    }
+
+    #[test]
+    fn empty_log_filters_are_no_log_filters() {
+        let result = LogFilters::from_str("").unwrap();
+
+        assert_eq!(result, LogFilters::default());
+        assert_eq!(result.to_string(), "");
+    }
+
+    #[test]
+    fn bad_log_filters_are_rejected() {
+        let result = LogFilters::from_str("Neighborhood=debug,Hopper=loud");
+
+        assert_eq!(result, Err("Bad log filters: \"Hopper=loud\"".to_string()));
+    }
+
     fn timestamp_as_string(timestamp: &SystemTime) -> String {
         let date_time: DateTime<Local> = DateTime::from(timestamp.clone());
         let fmt = StrftimeItems::new("%Y-%m-%dT%H:%M:%S%.3f");
@@ -0,0 +1,197 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::sub_lib::cryptde::PublicKey;
+use crate::sub_lib::migrations::utils::value_to_type;
+use crate::sub_lib::proxy_server::DatagramRequestPayload_0v1;
+use crate::sub_lib::stream_key::StreamKey;
+use crate::sub_lib::versioned_data::Migrations;
+use crate::sub_lib::versioned_data::FUTURE_VERSION;
+use crate::sub_lib::versioned_data::{MigrationError, StepError, VersionedData};
+use lazy_static::lazy_static;
+use serde_cbor::Value;
+use std::convert::TryFrom;
+
+lazy_static! {
+    pub static ref MIGRATIONS: Migrations = {
+        let current_version = dv!(0, 1);
+        let mut migrations = Migrations::new(current_version);
+
+        migrate_value!(dv!(0, 1), DatagramRequestPayload_0v1, DatagramRequestPayloadMF_0v1, {|value: serde_cbor::Value| {
+            DatagramRequestPayload_0v1::try_from (&value)
+        }});
+        migrations.add_step (FUTURE_VERSION, dv!(0, 1), Box::new (DatagramRequestPayloadMF_0v1{}));
+
+        // add more steps here
+
+        migrations
+    };
+}
+
+impl From<DatagramRequestPayload_0v1> for VersionedData<DatagramRequestPayload_0v1> {
+    fn from(payload: DatagramRequestPayload_0v1) -> Self {
+        VersionedData::new(&MIGRATIONS, &payload)
+    }
+}
+
+impl TryFrom<VersionedData<DatagramRequestPayload_0v1>> for DatagramRequestPayload_0v1 {
+    type Error = MigrationError;
+
+    fn try_from(vd: VersionedData<DatagramRequestPayload_0v1>) -> Result<Self, Self::Error> {
+        vd.extract(&MIGRATIONS)
+    }
+}
+
+impl TryFrom<&Value> for DatagramRequestPayload_0v1 {
+    type Error = StepError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Map(map) => {
+                let mut association_key_opt: Option<StreamKey> = None;
+                let mut target_hostname_opt: Option<String> = None;
+                let mut target_port_opt: Option<u16> = None;
+                let mut data_opt: Option<Vec<u8>> = None;
+                let mut originator_public_key_opt: Option<PublicKey> = None;
+                map.keys().for_each(|k| {
+                    let v = map.get(k).expect("Disappeared");
+                    if let Value::Text(field_name) = k {
+                        match field_name.as_str() {
+                            "association_key" => {
+                                association_key_opt = value_to_type::<StreamKey>(v)
+                            }
+                            "target_hostname" => target_hostname_opt = value_to_type::<String>(v),
+                            "target_port" => target_port_opt = value_to_type::<u16>(v),
+                            "data" => data_opt = value_to_type::<Vec<u8>>(v),
+                            "originator_public_key" => {
+                                originator_public_key_opt = value_to_type::<PublicKey>(v)
+                            }
+                            _ => (),
+                        }
+                    }
+                });
+                let mut missing_fields: Vec<&'static str> = vec![];
+                fn check_field<'a, T>(
+                    missing_fields: &mut Vec<&'a str>,
+                    name: &'a str,
+                    field: &Option<T>,
+                ) {
+                    if field.is_none() {
+                        missing_fields.push(name)
+                    }
+                }
+                check_field(&mut missing_fields, "association_key", &association_key_opt);
+                check_field(&mut missing_fields, "target_hostname", &target_hostname_opt);
+                check_field(&mut missing_fields, "target_port", &target_port_opt);
+                check_field(&mut missing_fields, "data", &data_opt);
+                check_field(
+                    &mut missing_fields,
+                    "originator_public_key",
+                    &originator_public_key_opt,
+                );
+                if let Some(field) = missing_fields.first() {
+                    return Err(StepError::SemanticError(
+                        <serde_cbor::Error as serde::de::Error>::missing_field(field).to_string(),
+                    ));
+                }
+                Ok(DatagramRequestPayload_0v1 {
+                    association_key: association_key_opt.expect("association_key disappeared"),
+                    target_hostname: target_hostname_opt.expect("target_hostname disappeared"),
+                    target_port: target_port_opt.expect("target_port disappeared"),
+                    data: data_opt.expect("data disappeared"),
+                    originator_public_key: originator_public_key_opt
+                        .expect("originator_public_key disappeared"),
+                })
+            }
+            _ => Err(StepError::SemanticError(format!(
+                "Expected Value::Map; found {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sub_lib::versioned_data::DataVersion;
+    use serde_derive::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+
+    #[test]
+    fn can_migrate_from_the_future() {
+        #[derive(Serialize, Deserialize)]
+        struct ExampleFutureDRP {
+            pub association_key: StreamKey,
+            pub target_hostname: String,
+            pub target_port: u16,
+            pub data: Vec<u8>,
+            pub originator_public_key: PublicKey,
+            pub another_field: String,
+            pub yet_another_field: u64,
+        }
+        let expected_crp = DatagramRequestPayload_0v1 {
+            association_key: StreamKey::new(
+                PublicKey::new(&[1, 2, 3, 4]),
+                SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+            ),
+            target_hostname: "dns.example.com".to_string(),
+            target_port: 53,
+            data: vec![5, 6, 7, 8],
+            originator_public_key: PublicKey::new(&[2, 3, 4, 5]),
+        };
+        let future_crp = ExampleFutureDRP {
+            association_key: expected_crp.association_key,
+            target_hostname: expected_crp.target_hostname.clone(),
+            target_port: expected_crp.target_port,
+            data: expected_crp.data.clone(),
+            originator_public_key: expected_crp.originator_public_key.clone(),
+            another_field: "These are the times that try men's souls".to_string(),
+            yet_another_field: 1234567890,
+        };
+        let future_migrations = Migrations::new(DataVersion::new(4095, 4095));
+        let serialized =
+            serde_cbor::ser::to_vec(&VersionedData::new(&future_migrations, &future_crp)).unwrap();
+        let future_vd =
+            serde_cbor::de::from_slice::<VersionedData<DatagramRequestPayload_0v1>>(&serialized)
+                .unwrap();
+
+        let actual_crp = DatagramRequestPayload_0v1::try_from(future_vd).unwrap();
+
+        assert_eq!(actual_crp, expected_crp);
+    }
+
+    #[test]
+    fn cannot_migrate_from_map_with_missing_field() {
+        let mut map = BTreeMap::new();
+        map.insert(
+            Value::Text("target_hostname".to_string()),
+            Value::Text("dns.example.com".to_string()),
+        );
+        map.insert(Value::Text("target_port".to_string()), Value::Integer(53));
+
+        let result = DatagramRequestPayload_0v1::try_from(&Value::Map(map));
+
+        assert_eq!(
+            result,
+            Err(StepError::SemanticError(
+                "missing field `association_key`".to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn cannot_migrate_from_value_other_than_map() {
+        let value = Value::Bool(true);
+
+        let result = DatagramRequestPayload_0v1::try_from(&value);
+
+        assert_eq!(
+            result,
+            Err(StepError::SemanticError(
+                "Expected Value::Map; found Bool(true)".to_string()
+            ))
+        )
+    }
+}
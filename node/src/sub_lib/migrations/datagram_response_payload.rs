@@ -0,0 +1,175 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::sub_lib::migrations::utils::value_to_type;
+use crate::sub_lib::proxy_client::DatagramResponsePayload_0v1;
+use crate::sub_lib::stream_key::StreamKey;
+use crate::sub_lib::versioned_data::Migrations;
+use crate::sub_lib::versioned_data::FUTURE_VERSION;
+use crate::sub_lib::versioned_data::{MigrationError, StepError, VersionedData};
+use lazy_static::lazy_static;
+use serde_cbor::Value;
+use std::convert::TryFrom;
+use std::net::SocketAddr;
+
+lazy_static! {
+    pub static ref MIGRATIONS: Migrations = {
+        let current_version = dv!(0, 1);
+        let mut migrations = Migrations::new(current_version);
+
+        migrate_value!(dv!(0, 1), DatagramResponsePayload_0v1, DatagramResponsePayloadMF_0v1, {|value: serde_cbor::Value| {
+            DatagramResponsePayload_0v1::try_from (&value)
+        }});
+        migrations.add_step (FUTURE_VERSION, dv!(0, 1), Box::new (DatagramResponsePayloadMF_0v1{}));
+
+        // add more steps here
+
+        migrations
+    };
+}
+
+impl From<DatagramResponsePayload_0v1> for VersionedData<DatagramResponsePayload_0v1> {
+    fn from(payload: DatagramResponsePayload_0v1) -> Self {
+        VersionedData::new(&MIGRATIONS, &payload)
+    }
+}
+
+impl TryFrom<VersionedData<DatagramResponsePayload_0v1>> for DatagramResponsePayload_0v1 {
+    type Error = MigrationError;
+
+    fn try_from(vd: VersionedData<DatagramResponsePayload_0v1>) -> Result<Self, Self::Error> {
+        vd.extract(&MIGRATIONS)
+    }
+}
+
+impl TryFrom<&Value> for DatagramResponsePayload_0v1 {
+    type Error = StepError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Map(map) => {
+                let mut association_key_opt: Option<StreamKey> = None;
+                let mut source_opt: Option<SocketAddr> = None;
+                let mut data_opt: Option<Vec<u8>> = None;
+                map.keys().for_each(|k| {
+                    let v = map.get(k).expect("Disappeared");
+                    if let Value::Text(field_name) = k {
+                        match field_name.as_str() {
+                            "association_key" => {
+                                association_key_opt = value_to_type::<StreamKey>(v)
+                            }
+                            "source" => source_opt = value_to_type::<SocketAddr>(v),
+                            "data" => data_opt = value_to_type::<Vec<u8>>(v),
+                            _ => (),
+                        }
+                    }
+                });
+                let mut missing_fields: Vec<&'static str> = vec![];
+                fn check_field<'a, T>(
+                    missing_fields: &mut Vec<&'a str>,
+                    name: &'a str,
+                    field: &Option<T>,
+                ) {
+                    if field.is_none() {
+                        missing_fields.push(name)
+                    }
+                }
+                check_field(&mut missing_fields, "association_key", &association_key_opt);
+                check_field(&mut missing_fields, "source", &source_opt);
+                check_field(&mut missing_fields, "data", &data_opt);
+                if let Some(field) = missing_fields.first() {
+                    return Err(StepError::SemanticError(
+                        <serde_cbor::Error as serde::de::Error>::missing_field(field).to_string(),
+                    ));
+                }
+                Ok(DatagramResponsePayload_0v1 {
+                    association_key: association_key_opt.expect("association_key disappeared"),
+                    source: source_opt.expect("source disappeared"),
+                    data: data_opt.expect("data disappeared"),
+                })
+            }
+            _ => Err(StepError::SemanticError(format!(
+                "Expected Value::Map; found {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sub_lib::cryptde::PublicKey;
+    use crate::sub_lib::versioned_data::DataVersion;
+    use serde_derive::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    #[test]
+    fn can_migrate_from_the_future() {
+        #[derive(Serialize, Deserialize)]
+        struct ExampleFutureDRP {
+            pub association_key: StreamKey,
+            pub source: SocketAddr,
+            pub data: Vec<u8>,
+            pub another_field: String,
+            pub yet_another_field: u64,
+        }
+        let expected_crp = DatagramResponsePayload_0v1 {
+            association_key: StreamKey::new(
+                PublicKey::new(&[1, 2, 3, 4]),
+                SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+            ),
+            source: SocketAddr::from_str("8.8.8.8:53").unwrap(),
+            data: vec![5, 6, 7, 8],
+        };
+        let future_crp = ExampleFutureDRP {
+            association_key: expected_crp.association_key,
+            source: expected_crp.source,
+            data: expected_crp.data.clone(),
+            another_field: "These are the times that try men's souls".to_string(),
+            yet_another_field: 1234567890,
+        };
+        let future_migrations = Migrations::new(DataVersion::new(4095, 4095));
+        let serialized =
+            serde_cbor::ser::to_vec(&VersionedData::new(&future_migrations, &future_crp)).unwrap();
+        let future_vd =
+            serde_cbor::de::from_slice::<VersionedData<DatagramResponsePayload_0v1>>(&serialized)
+                .unwrap();
+
+        let actual_crp = DatagramResponsePayload_0v1::try_from(future_vd).unwrap();
+
+        assert_eq!(actual_crp, expected_crp);
+    }
+
+    #[test]
+    fn cannot_migrate_from_map_with_missing_field() {
+        let mut map = BTreeMap::new();
+        map.insert(
+            Value::Text("source".to_string()),
+            Value::Text("1.2.3.4:53".to_string()),
+        );
+
+        let result = DatagramResponsePayload_0v1::try_from(&Value::Map(map));
+
+        assert_eq!(
+            result,
+            Err(StepError::SemanticError(
+                "missing field `association_key`".to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn cannot_migrate_from_value_other_than_map() {
+        let value = Value::Bool(true);
+
+        let result = DatagramResponsePayload_0v1::try_from(&value);
+
+        assert_eq!(
+            result,
+            Err(StepError::SemanticError(
+                "Expected Value::Map; found Bool(true)".to_string()
+            ))
+        )
+    }
+}
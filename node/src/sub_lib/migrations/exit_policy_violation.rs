@@ -0,0 +1,166 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::sub_lib::migrations::utils::value_to_type;
+use crate::sub_lib::proxy_client::ExitPolicyViolation_0v1;
+use crate::sub_lib::stream_key::StreamKey;
+use crate::sub_lib::versioned_data::Migrations;
+use crate::sub_lib::versioned_data::FUTURE_VERSION;
+use crate::sub_lib::versioned_data::{MigrationError, StepError, VersionedData};
+use lazy_static::lazy_static;
+use serde_cbor::Value;
+use std::convert::TryFrom;
+
+lazy_static! {
+    pub static ref MIGRATIONS: Migrations = {
+        let current_version = dv!(0, 1);
+        let mut migrations = Migrations::new(current_version);
+
+        migrate_value!(dv!(0, 1), ExitPolicyViolation_0v1, ExitPolicyViolationMF_0v1, {|value: serde_cbor::Value| {
+            ExitPolicyViolation_0v1::try_from (&value)
+        }});
+        migrations.add_step (FUTURE_VERSION, dv!(0, 1), Box::new (ExitPolicyViolationMF_0v1{}));
+
+        // add more steps here
+
+        migrations
+    };
+}
+
+impl From<ExitPolicyViolation_0v1> for VersionedData<ExitPolicyViolation_0v1> {
+    fn from(violation: ExitPolicyViolation_0v1) -> Self {
+        VersionedData::new(&MIGRATIONS, &violation)
+    }
+}
+
+impl TryFrom<VersionedData<ExitPolicyViolation_0v1>> for ExitPolicyViolation_0v1 {
+    type Error = MigrationError;
+
+    fn try_from(vd: VersionedData<ExitPolicyViolation_0v1>) -> Result<Self, Self::Error> {
+        vd.extract(&MIGRATIONS)
+    }
+}
+
+impl TryFrom<&Value> for ExitPolicyViolation_0v1 {
+    type Error = StepError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Map(map) => {
+                let mut stream_key_opt: Option<StreamKey> = None;
+                let mut reason_opt: Option<String> = None;
+                map.keys().for_each(|k| {
+                    let v = map.get(k).expect("Disappeared");
+                    if let Value::Text(field_name) = k {
+                        match field_name.as_str() {
+                            "stream_key" => stream_key_opt = value_to_type::<StreamKey>(v),
+                            "reason" => reason_opt = value_to_type::<String>(v),
+                            _ => (),
+                        }
+                    }
+                });
+                let mut missing_fields: Vec<&'static str> = vec![];
+                fn check_field<'a, T>(
+                    missing_fields: &mut Vec<&'a str>,
+                    name: &'a str,
+                    field: &Option<T>,
+                ) {
+                    if field.is_none() {
+                        missing_fields.push(name)
+                    }
+                }
+                check_field(&mut missing_fields, "stream_key", &stream_key_opt);
+                check_field(&mut missing_fields, "reason", &reason_opt);
+                if let Some(field) = missing_fields.first() {
+                    return Err(StepError::SemanticError(
+                        <serde_cbor::Error as serde::de::Error>::missing_field(field).to_string(),
+                    ));
+                }
+                Ok(ExitPolicyViolation_0v1 {
+                    stream_key: stream_key_opt.expect("stream_key disappeared"),
+                    reason: reason_opt.expect("reason disappeared"),
+                })
+            }
+            _ => Err(StepError::SemanticError(format!(
+                "Expected Value::Map; found {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sub_lib::cryptde::PublicKey;
+    use crate::sub_lib::versioned_data::DataVersion;
+    use serde_derive::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+
+    #[test]
+    fn can_migrate_from_the_future() {
+        #[derive(Serialize, Deserialize)]
+        struct ExampleFutureEPV {
+            pub stream_key: StreamKey,
+            pub reason: String,
+            pub another_field: String,
+            pub yet_another_field: u64,
+        }
+        let expected_crp = ExitPolicyViolation_0v1 {
+            stream_key: StreamKey::new(
+                PublicKey::new(&[1, 2, 3, 4]),
+                SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+            ),
+            reason: "10.0.0.1 is a private address".to_string(),
+        };
+        let future_crp = ExampleFutureEPV {
+            stream_key: expected_crp.stream_key,
+            reason: expected_crp.reason.clone(),
+            another_field: "These are the times that try men's souls".to_string(),
+            yet_another_field: 1234567890,
+        };
+        let future_migrations = Migrations::new(DataVersion::new(4095, 4095));
+        let serialized =
+            serde_cbor::ser::to_vec(&VersionedData::new(&future_migrations, &future_crp)).unwrap();
+        let future_vd =
+            serde_cbor::de::from_slice::<VersionedData<ExitPolicyViolation_0v1>>(&serialized)
+                .unwrap();
+
+        let actual_crp = ExitPolicyViolation_0v1::try_from(future_vd).unwrap();
+
+        assert_eq!(actual_crp, expected_crp);
+    }
+
+    #[test]
+    fn cannot_migrate_from_map_with_missing_field() {
+        let mut map = BTreeMap::new();
+        map.insert(
+            Value::Text("reason".to_string()),
+            Value::Text("10.0.0.1 is a private address".to_string()),
+        );
+
+        let result = ExitPolicyViolation_0v1::try_from(&Value::Map(map));
+
+        assert_eq!(
+            result,
+            Err(StepError::SemanticError(
+                "missing field `stream_key`".to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn cannot_migrate_from_value_other_than_map() {
+        let value = Value::Bool(true);
+
+        let result = ExitPolicyViolation_0v1::try_from(&value);
+
+        assert_eq!(
+            result,
+            Err(StepError::SemanticError(
+                "Expected Value::Map; found Bool(true)".to_string()
+            ))
+        )
+    }
+}
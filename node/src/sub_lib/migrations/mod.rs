@@ -2,7 +2,10 @@
 
 pub mod client_request_payload;
 pub mod client_response_payload;
+pub mod datagram_request_payload;
+pub mod datagram_response_payload;
 pub mod dns_resolve_failure;
+pub mod exit_policy_violation;
 pub mod gossip;
 pub mod gossip_failure;
 pub mod node_record_inner;
@@ -19,6 +19,7 @@ pub mod cryptde_null;
 pub mod cryptde_real;
 pub mod data_version;
 pub mod dispatcher;
+pub mod exit_policy;
 pub mod framer;
 pub mod framer_utils;
 pub mod hop;
@@ -38,6 +39,7 @@ pub mod sequence_buffer;
 pub mod sequencer;
 pub mod set_consuming_wallet_message;
 pub mod socket_server;
+pub mod socks_framer;
 pub mod stream_connector;
 pub mod stream_handler_pool;
 pub mod stream_key;
@@ -45,6 +47,7 @@ pub mod tcp_wrappers;
 pub mod tls_framer;
 pub mod tokio_wrappers;
 pub mod ttl_hashmap;
+pub mod udp_relay;
 pub mod udp_socket_wrapper;
 pub mod ui_gateway;
 pub mod wallet;
@@ -1,5 +1,4 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
-use crate::blockchain::blockchain_interface::chain_id_from_name;
 use crate::neighborhood::gossip::Gossip_0v1;
 use crate::neighborhood::node_record::NodeRecord;
 use crate::sub_lib::cryptde::{CryptDE, PublicKey};
@@ -17,7 +16,6 @@ use actix::Message;
 use actix::Recipient;
 use core::fmt;
 use lazy_static::lazy_static;
-use masq_lib::constants::DEFAULT_CHAIN_NAME;
 use masq_lib::ui_gateway::NodeFromUiMessage;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt::{Debug, Display, Formatter};
@@ -38,6 +36,11 @@ pub const ZERO_RATE_PACK: RatePack = RatePack {
     exit_service_rate: 0,
 };
 
+pub const UNLIMITED_ROUTE_COST_BUDGET: RouteCostBudget = RouteCostBudget {
+    max_byte_rate: u64::MAX,
+    max_service_rate: u64::MAX,
+};
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum NeighborhoodMode {
     Standard(NodeAddr, Vec<NodeDescriptor>, RatePack),
@@ -170,14 +173,12 @@ impl From<(&NodeRecord, bool, &dyn CryptDE)> for NodeDescriptor {
 impl NodeDescriptor {
     pub fn from_str(cryptde: &dyn CryptDE, s: &str) -> Result<NodeDescriptor, String> {
         let (mainnet, pieces) = {
-            let chain_id = chain_id_from_name(DEFAULT_CHAIN_NAME);
-            let delimiter = node_descriptor_delimiter(chain_id);
+            let delimiter = node_descriptor_delimiter(true);
             let pieces: Vec<&str> = s.splitn(2, delimiter).collect();
             if pieces.len() == 2 {
                 (true, pieces)
             } else {
-                let chain_id = chain_id_from_name("ropsten");
-                let delimiter = node_descriptor_delimiter(chain_id);
+                let delimiter = node_descriptor_delimiter(false);
                 let pieces: Vec<&str> = s.splitn(2, delimiter).collect();
                 if pieces.len() == 2 {
                     (false, pieces)
@@ -221,7 +222,7 @@ impl NodeDescriptor {
             Some(node_addr) => node_addr.to_string(),
             None => ":".to_string(),
         };
-        let delimiter = if self.mainnet { "@" } else { ":" };
+        let delimiter = node_descriptor_delimiter(self.mainnet);
         format!(
             "{}{}{}",
             contact_public_key_string, delimiter, node_addr_string
@@ -232,6 +233,49 @@ impl NodeDescriptor {
 #[derive(Clone, Debug, PartialEq)]
 pub struct NeighborhoodConfig {
     pub mode: NeighborhoodMode,
+    pub route_cost_budget: RouteCostBudget,
+}
+
+// The most a single route segment (over or back) may charge, summed across every Node in it
+// that expects to be paid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RouteCostBudget {
+    pub max_byte_rate: u64,
+    pub max_service_rate: u64,
+}
+
+impl RouteCostBudget {
+    pub fn admits(&self, byte_rate: u64, service_rate: u64) -> bool {
+        byte_rate <= self.max_byte_rate && service_rate <= self.max_service_rate
+    }
+
+    pub fn to_config_string(&self) -> String {
+        format!("{}|{}", self.max_byte_rate, self.max_service_rate)
+    }
+}
+
+// The format of --route-cost-budget: maximum byte rate and maximum service rate, separated by
+// '|' (e.g. "1000|100000").
+impl FromStr for RouteCostBudget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rates = s
+            .split('|')
+            .map(|rate| rate.trim().parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| format!("'{}' contains a rate that is not a whole number", s))?;
+        match rates.as_slice() {
+            [max_byte_rate, max_service_rate] => Ok(RouteCostBudget {
+                max_byte_rate: *max_byte_rate,
+                max_service_rate: *max_service_rate,
+            }),
+            _ => Err(format!(
+                "'{}' must contain exactly two rates separated by '|'",
+                s
+            )),
+        }
+    }
 }
 
 lazy_static! {
@@ -336,6 +380,36 @@ pub enum ExpectedService {
     Nothing,
 }
 
+impl ExpectedService {
+    pub fn byte_rate(&self) -> u64 {
+        match self {
+            ExpectedService::Routing(_, _, rate_pack) => rate_pack.routing_byte_rate,
+            ExpectedService::Exit(_, _, rate_pack) => rate_pack.exit_byte_rate,
+            ExpectedService::Nothing => 0,
+        }
+    }
+
+    pub fn service_rate(&self) -> u64 {
+        match self {
+            ExpectedService::Routing(_, _, rate_pack) => rate_pack.routing_service_rate,
+            ExpectedService::Exit(_, _, rate_pack) => rate_pack.exit_service_rate,
+            ExpectedService::Nothing => 0,
+        }
+    }
+
+    pub fn update_rate_pack(&mut self, public_key: &PublicKey, new_rate_pack: &RatePack) {
+        match self {
+            ExpectedService::Routing(key, _, rate_pack)
+            | ExpectedService::Exit(key, _, rate_pack)
+                if key == public_key =>
+            {
+                *rate_pack = new_rate_pack.clone()
+            }
+            _ => (),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum ExpectedServices {
     OneWay(Vec<ExpectedService>),
@@ -353,6 +427,21 @@ pub struct RemoveNeighborMessage {
     pub public_key: PublicKey,
 }
 
+// Sent by the Neighborhood when the operator changes this Node's rate pack at runtime, so that the
+// services it provides are charged at the rates it's now advertising.
+#[derive(Clone, Debug, Message, PartialEq)]
+pub struct RatePackChangeMessage {
+    pub rate_pack: RatePack,
+}
+
+// Sent by the Neighborhood when Gossip changes another Node's rate pack, so that routes already
+// chosen through that Node are charged at its new rates.
+#[derive(Clone, Debug, Message, PartialEq)]
+pub struct NodeRatePackChangeMessage {
+    pub public_key: PublicKey,
+    pub rate_pack: RatePack,
+}
+
 #[derive(Clone, Debug, Message, PartialEq)]
 pub enum NodeRecordMetadataMessage {
     Desirable(PublicKey, bool),
@@ -379,6 +468,46 @@ impl fmt::Display for RatePack {
     }
 }
 
+// The format of --rate-pack: routing byte rate, routing service rate, exit byte rate and exit
+// service rate, separated by '|' (e.g. "100|10000|101|10001").
+impl FromStr for RatePack {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rates = s
+            .split('|')
+            .map(|rate| rate.trim().parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| format!("'{}' contains a rate that is not a whole number", s))?;
+        match rates.as_slice() {
+            [routing_byte_rate, routing_service_rate, exit_byte_rate, exit_service_rate] => {
+                Ok(RatePack {
+                    routing_byte_rate: *routing_byte_rate,
+                    routing_service_rate: *routing_service_rate,
+                    exit_byte_rate: *exit_byte_rate,
+                    exit_service_rate: *exit_service_rate,
+                })
+            }
+            _ => Err(format!(
+                "'{}' must contain exactly four rates separated by '|'",
+                s
+            )),
+        }
+    }
+}
+
+impl RatePack {
+    pub fn to_config_string(&self) -> String {
+        format!(
+            "{}|{}|{}|{}",
+            self.routing_byte_rate,
+            self.routing_service_rate,
+            self.exit_byte_rate,
+            self.exit_service_rate
+        )
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[allow(non_camel_case_types)]
 pub enum GossipFailure_0v1 {
@@ -444,6 +573,42 @@ mod tests {
         assert_eq!(format!("{:?}", subject), "NeighborhoodSubs");
     }
 
+    #[test]
+    fn rate_pack_from_str_reads_rates_in_field_order() {
+        let result = RatePack::from_str("101|102|103|104");
+
+        assert_eq!(result, Ok(rate_pack(100)));
+    }
+
+    #[test]
+    fn rate_pack_survives_the_trip_through_its_config_string() {
+        let subject = rate_pack(1000);
+
+        let result = RatePack::from_str(&subject.to_config_string());
+
+        assert_eq!(result, Ok(subject));
+    }
+
+    #[test]
+    fn rate_pack_from_str_complains_about_wrong_number_of_rates() {
+        let result = RatePack::from_str("1|2|3");
+
+        assert_eq!(
+            result,
+            Err("'1|2|3' must contain exactly four rates separated by '|'".to_string())
+        );
+    }
+
+    #[test]
+    fn rate_pack_from_str_complains_about_non_numeric_rates() {
+        let result = RatePack::from_str("1|2|-3|4");
+
+        assert_eq!(
+            result,
+            Err("'1|2|-3|4' contains a rate that is not a whole number".to_string())
+        );
+    }
+
     #[test]
     fn node_descriptor_from_str_requires_two_pieces_to_a_configuration() {
         let result = NodeDescriptor::from_str(main_cryptde(), "only_one_piece");
@@ -712,6 +877,80 @@ mod tests {
         assert!(subject.is_zero_hop());
     }
 
+    #[test]
+    fn expected_service_reports_the_rates_appropriate_to_its_service() {
+        let key = PublicKey::new(&b"key"[..]);
+        let wallet = Wallet::new("0x1234");
+        let routing = ExpectedService::Routing(key.clone(), wallet.clone(), rate_pack(100));
+        let exit = ExpectedService::Exit(key, wallet, rate_pack(100));
+
+        assert_eq!(routing.byte_rate(), 101);
+        assert_eq!(routing.service_rate(), 102);
+        assert_eq!(exit.byte_rate(), 103);
+        assert_eq!(exit.service_rate(), 104);
+        assert_eq!(ExpectedService::Nothing.byte_rate(), 0);
+        assert_eq!(ExpectedService::Nothing.service_rate(), 0);
+    }
+
+    #[test]
+    fn route_cost_budget_admits_only_rates_within_both_limits() {
+        let subject = RouteCostBudget {
+            max_byte_rate: 100,
+            max_service_rate: 1000,
+        };
+
+        assert!(subject.admits(100, 1000));
+        assert!(subject.admits(0, 0));
+        assert!(!subject.admits(101, 1000));
+        assert!(!subject.admits(100, 1001));
+        assert!(UNLIMITED_ROUTE_COST_BUDGET.admits(u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn route_cost_budget_from_str_reads_byte_rate_then_service_rate() {
+        let result = RouteCostBudget::from_str("1000 | 100000");
+
+        assert_eq!(
+            result,
+            Ok(RouteCostBudget {
+                max_byte_rate: 1000,
+                max_service_rate: 100000,
+            })
+        );
+    }
+
+    #[test]
+    fn route_cost_budget_survives_the_trip_through_its_config_string() {
+        let subject = RouteCostBudget {
+            max_byte_rate: 123,
+            max_service_rate: 4567,
+        };
+
+        let result = RouteCostBudget::from_str(&subject.to_config_string());
+
+        assert_eq!(result, Ok(subject));
+    }
+
+    #[test]
+    fn route_cost_budget_from_str_complains_about_wrong_number_of_rates() {
+        let result = RouteCostBudget::from_str("1|2|3");
+
+        assert_eq!(
+            result,
+            Err("'1|2|3' must contain exactly two rates separated by '|'".to_string())
+        );
+    }
+
+    #[test]
+    fn route_cost_budget_from_str_complains_about_non_numeric_rates() {
+        let result = RouteCostBudget::from_str("1|lots");
+
+        assert_eq!(
+            result,
+            Err("'1|lots' contains a rate that is not a whole number".to_string())
+        );
+    }
+
     #[test]
     fn gossip_failure_display() {
         // Structured this way so that modifications to GossipFailure_0v1 will draw attention here
@@ -1,10 +1,13 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 use crate::sub_lib::cryptde::CryptDE;
+use crate::sub_lib::exit_policy::ExitPolicy;
 use crate::sub_lib::hopper::{ExpiredCoresPackage, MessageType};
+use crate::sub_lib::neighborhood::RatePackChangeMessage;
 use crate::sub_lib::peer_actors::BindMessage;
-use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
+use crate::sub_lib::proxy_server::{ClientRequestPayload_0v1, DatagramRequestPayload_0v1};
 use crate::sub_lib::sequence_buffer::SequencedPacket;
 use crate::sub_lib::stream_key::StreamKey;
+use crate::sub_lib::udp_relay::InboundDatagram;
 use crate::sub_lib::versioned_data::VersionedData;
 use actix::Message;
 use actix::Recipient;
@@ -13,6 +16,10 @@ use std::fmt::{Debug, Formatter};
 use std::net::Ipv4Addr;
 use std::net::SocketAddr;
 use std::net::SocketAddrV4;
+use std::time::Duration;
+
+// How long a UDP association may sit idle before its socket on the exit Node is closed
+pub const UDP_ASSOCIATION_IDLE_TTL: Duration = Duration::from_secs(60);
 
 pub fn error_socket_addr() -> SocketAddr {
     SocketAddr::from(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 0))
@@ -24,6 +31,7 @@ pub struct ProxyClientConfig {
     pub dns_servers: Vec<SocketAddr>,
     pub exit_service_rate: u64,
     pub exit_byte_rate: u64,
+    pub exit_policy: ExitPolicy,
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -33,6 +41,16 @@ pub struct ClientResponsePayload_0v1 {
     pub sequenced_packet: SequencedPacket,
 }
 
+// A UDP datagram that arrived at the exit Node's socket for an association, addressed back to the
+// client that opened the association.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[allow(non_camel_case_types)]
+pub struct DatagramResponsePayload_0v1 {
+    pub association_key: StreamKey,
+    pub source: SocketAddr,
+    pub data: Vec<u8>,
+}
+
 #[derive(Message, Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[allow(non_camel_case_types)]
 pub struct DnsResolveFailure_0v1 {
@@ -45,6 +63,19 @@ impl DnsResolveFailure_0v1 {
     }
 }
 
+#[derive(Message, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[allow(non_camel_case_types)]
+pub struct ExitPolicyViolation_0v1 {
+    pub stream_key: StreamKey,
+    pub reason: String,
+}
+
+impl ExitPolicyViolation_0v1 {
+    pub fn new(stream_key: StreamKey, reason: String) -> Self {
+        Self { stream_key, reason }
+    }
+}
+
 impl Into<MessageType> for ClientResponsePayload_0v1 {
     fn into(self) -> MessageType {
         MessageType::ClientResponse(VersionedData::new(
@@ -63,12 +94,34 @@ impl Into<MessageType> for DnsResolveFailure_0v1 {
     }
 }
 
+impl From<DatagramResponsePayload_0v1> for MessageType {
+    fn from(payload: DatagramResponsePayload_0v1) -> Self {
+        MessageType::DatagramResponse(VersionedData::new(
+            &crate::sub_lib::migrations::datagram_response_payload::MIGRATIONS,
+            &payload,
+        ))
+    }
+}
+
+impl From<ExitPolicyViolation_0v1> for MessageType {
+    fn from(violation: ExitPolicyViolation_0v1) -> Self {
+        MessageType::ExitPolicyViolated(VersionedData::new(
+            &crate::sub_lib::migrations::exit_policy_violation::MIGRATIONS,
+            &violation,
+        ))
+    }
+}
+
 #[derive(Clone)]
 pub struct ProxyClientSubs {
     pub bind: Recipient<BindMessage>,
     pub from_hopper: Recipient<ExpiredCoresPackage<ClientRequestPayload_0v1>>,
     pub inbound_server_data: Recipient<InboundServerData>,
     pub dns_resolve_failed: Recipient<DnsResolveFailure_0v1>,
+    pub exit_policy_violated: Recipient<ExitPolicyViolation_0v1>,
+    pub datagram_from_hopper: Recipient<ExpiredCoresPackage<DatagramRequestPayload_0v1>>,
+    pub inbound_datagram: Recipient<InboundDatagram>,
+    pub rate_pack_change: Recipient<RatePackChangeMessage>,
 }
 
 impl Debug for ProxyClientSubs {
@@ -135,6 +188,13 @@ mod tests {
             from_hopper: recipient!(recorder, ExpiredCoresPackage<ClientRequestPayload_0v1>),
             inbound_server_data: recipient!(recorder, InboundServerData),
             dns_resolve_failed: recipient!(recorder, DnsResolveFailure_0v1),
+            exit_policy_violated: recipient!(recorder, ExitPolicyViolation_0v1),
+            datagram_from_hopper: recipient!(
+                recorder,
+                ExpiredCoresPackage<DatagramRequestPayload_0v1>
+            ),
+            inbound_datagram: recipient!(recorder, InboundDatagram),
+            rate_pack_change: recipient!(recorder, RatePackChangeMessage),
         };
 
         assert_eq!(format!("{:?}", subject), "ProxyClientSubs");
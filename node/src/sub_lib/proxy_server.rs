@@ -4,12 +4,18 @@ use crate::sub_lib::data_version::DataVersion;
 use crate::sub_lib::dispatcher::InboundClientData;
 use crate::sub_lib::dispatcher::StreamShutdownMsg;
 use crate::sub_lib::hopper::{ExpiredCoresPackage, MessageType};
-use crate::sub_lib::neighborhood::{ExpectedService, RouteQueryResponse};
+use crate::sub_lib::neighborhood::{
+    ExpectedService, NodeRatePackChangeMessage, RouteQueryResponse,
+};
 use crate::sub_lib::peer_actors::BindMessage;
-use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
+use crate::sub_lib::proxy_client::{
+    ClientResponsePayload_0v1, DatagramResponsePayload_0v1, DnsResolveFailure_0v1,
+    ExitPolicyViolation_0v1,
+};
 use crate::sub_lib::sequence_buffer::SequencedPacket;
 use crate::sub_lib::set_consuming_wallet_message::SetConsumingWalletMessage;
 use crate::sub_lib::stream_key::StreamKey;
+use crate::sub_lib::udp_relay::InboundDatagram;
 use crate::sub_lib::versioned_data::VersionedData;
 use actix::Message;
 use actix::Recipient;
@@ -22,6 +28,7 @@ pub const DEFAULT_MINIMUM_HOP_COUNT: usize = 3;
 pub enum ProxyProtocol {
     HTTP,
     TLS,
+    SOCKS5,
 }
 
 // TODO: Based on the way it's used, this struct should comprise two elements: one, a nested
@@ -47,13 +54,34 @@ impl Into<MessageType> for ClientRequestPayload_0v1 {
     }
 }
 
+// A single UDP datagram from a client to a target behind the exit Node. Datagrams with the same
+// association key share one UDP socket on the exit Node, so replies can find their way back.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[allow(non_camel_case_types)]
+pub struct DatagramRequestPayload_0v1 {
+    pub association_key: StreamKey,
+    pub target_hostname: String,
+    pub target_port: u16,
+    pub data: Vec<u8>,
+    pub originator_public_key: PublicKey,
+}
+
+impl From<DatagramRequestPayload_0v1> for MessageType {
+    fn from(payload: DatagramRequestPayload_0v1) -> Self {
+        MessageType::DatagramRequest(VersionedData::new(
+            &crate::sub_lib::migrations::datagram_request_payload::MIGRATIONS,
+            &payload,
+        ))
+    }
+}
+
 impl ClientRequestPayload_0v1 {
     pub fn version() -> DataVersion {
         DataVersion::new(0, 0).expect("Internal Error")
     }
 }
 
-#[derive(Message, Debug, PartialEq, Eq)]
+#[derive(Clone, Message, Debug, PartialEq, Eq)]
 pub struct AddReturnRouteMessage {
     pub return_route_id: u32,
     pub expected_services: Vec<ExpectedService>,
@@ -74,10 +102,14 @@ pub struct ProxyServerSubs {
     pub from_dispatcher: Recipient<InboundClientData>,
     pub from_hopper: Recipient<ExpiredCoresPackage<ClientResponsePayload_0v1>>,
     pub dns_failure_from_hopper: Recipient<ExpiredCoresPackage<DnsResolveFailure_0v1>>,
+    pub exit_policy_violation_from_hopper: Recipient<ExpiredCoresPackage<ExitPolicyViolation_0v1>>,
+    pub datagram_from_hopper: Recipient<ExpiredCoresPackage<DatagramResponsePayload_0v1>>,
+    pub inbound_datagram: Recipient<InboundDatagram>,
     pub add_return_route: Recipient<AddReturnRouteMessage>,
     pub add_route: Recipient<AddRouteMessage>,
     pub stream_shutdown_sub: Recipient<StreamShutdownMsg>,
     pub set_consuming_wallet_sub: Recipient<SetConsumingWalletMessage>,
+    pub node_rate_pack_change: Recipient<NodeRatePackChangeMessage>,
 }
 
 impl Debug for ProxyServerSubs {
@@ -105,10 +137,20 @@ mod tests {
                 recorder,
                 ExpiredCoresPackage<DnsResolveFailure_0v1>
             ),
+            exit_policy_violation_from_hopper: recipient!(
+                recorder,
+                ExpiredCoresPackage<ExitPolicyViolation_0v1>
+            ),
+            datagram_from_hopper: recipient!(
+                recorder,
+                ExpiredCoresPackage<DatagramResponsePayload_0v1>
+            ),
+            inbound_datagram: recipient!(recorder, InboundDatagram),
             add_return_route: recipient!(recorder, AddReturnRouteMessage),
             add_route: recipient!(recorder, AddRouteMessage),
             stream_shutdown_sub: recipient!(recorder, StreamShutdownMsg),
             set_consuming_wallet_sub: recipient!(recorder, SetConsumingWalletMessage),
+            node_rate_pack_change: recipient!(recorder, NodeRatePackChangeMessage),
         };
 
         assert_eq!(format!("{:?}", subject), "ProxyServerSubs");
@@ -0,0 +1,66 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::sub_lib::framer::FramedChunk;
+use crate::sub_lib::framer::Framer;
+
+// SOCKS5 streams have no framing the Node can rely on: the handshake is reassembled by the
+// ProxyServer, and everything after it is opaque tunneled data. So every chunk read is a frame.
+#[derive(Default)]
+pub struct SocksFramer {
+    data_so_far: Vec<u8>,
+}
+
+impl Framer for SocksFramer {
+    fn add_data(&mut self, data: &[u8]) {
+        self.data_so_far.extend(data);
+    }
+
+    fn take_frame(&mut self) -> Option<FramedChunk> {
+        if self.data_so_far.is_empty() {
+            None
+        } else {
+            Some(FramedChunk {
+                chunk: self.data_so_far.drain(..).collect(),
+                last_chunk: false,
+            })
+        }
+    }
+}
+
+impl SocksFramer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_frame_returns_none_when_there_is_no_data() {
+        let mut subject = SocksFramer::new();
+
+        let result = subject.take_frame();
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn take_frame_returns_everything_added_so_far() {
+        let mut subject = SocksFramer::new();
+        subject.add_data(&[0x05, 0x01]);
+        subject.add_data(&[0x00]);
+
+        let first_result = subject.take_frame();
+        let second_result = subject.take_frame();
+
+        assert_eq!(
+            first_result,
+            Some(FramedChunk {
+                chunk: vec![0x05, 0x01, 0x00],
+                last_chunk: false,
+            })
+        );
+        assert_eq!(second_result, None);
+    }
+}
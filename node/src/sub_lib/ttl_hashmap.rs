@@ -54,7 +54,21 @@ where
         }
     }
 
-    fn remove_expired_entries(&self) {
+    // Changes values in place without counting as an access, so their expiration doesn't move.
+    pub fn update_values<F>(&mut self, mut update: F)
+    where
+        V: Clone,
+        F: FnMut(&mut V),
+    {
+        self.data
+            .borrow_mut()
+            .values_mut()
+            .for_each(|(value, _)| update(Rc::make_mut(value)));
+    }
+
+    // Runs on every get and insert, but a map that may go quiet for a long time should also be
+    // swept on a timer, or what its entries hold onto lives on until the next access.
+    pub fn remove_expired_entries(&self) {
         let now = Instant::now();
 
         if now.duration_since(*self.last_check.borrow()) < self.ttl {
@@ -169,6 +183,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ttl_hashmap_remove_expired_entries_removes_expired_entries_without_an_access() {
+        let mut subject = TtlHashMap::new(Duration::from_millis(10));
+        subject.insert(42u32, "Hello");
+
+        thread::sleep(Duration::from_millis(20));
+
+        subject.remove_expired_entries();
+
+        assert_eq!(subject.data.borrow().get(&42u32), None);
+    }
+
+    #[test]
+    fn ttl_hashmap_remove_expired_entries_keeps_unexpired_entries() {
+        let mut subject = TtlHashMap::new(Duration::from_millis(100));
+        subject.insert(42u32, "Hello");
+        subject.last_check = RefCell::new(Instant::now() - Duration::from_millis(200));
+
+        subject.remove_expired_entries();
+
+        assert_eq!(
+            subject.data.borrow().get(&42u32).unwrap().0.as_ref(),
+            &"Hello"
+        );
+    }
+
+    #[test]
+    fn ttl_hashmap_update_values_changes_values_without_refreshing_them() {
+        let mut subject = TtlHashMap::new(Duration::from_millis(100));
+        subject.insert(42u32, "Hello".to_string());
+        let held = subject.get(&42u32).unwrap();
+        let timestamp = subject.data.borrow().get(&42u32).unwrap().1;
+
+        subject.update_values(|value| value.push_str(", world"));
+
+        let data = subject.data.borrow();
+        let (value, new_timestamp) = data.get(&42u32).unwrap();
+        assert_eq!(value.as_ref(), "Hello, world");
+        assert_eq!(*new_timestamp, timestamp);
+        assert_eq!(held.as_ref(), "Hello");
+    }
+
     fn time_since_msg(timestamp: Instant, nominal: u64) -> String {
         format!(
             "Should still be there after nominal {}ms, actual {}ms",
@@ -0,0 +1,294 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::sub_lib::channel_wrappers::{ReceiverWrapper, ReceiverWrapperReal};
+use crate::sub_lib::logger::Logger;
+use crate::sub_lib::stream_key::StreamKey;
+use crate::sub_lib::udp_socket_wrapper::UdpSocketWrapperTrait;
+use actix::Message;
+use actix::Recipient;
+use futures::sync::mpsc;
+use futures::sync::mpsc::UnboundedSender;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use tokio::prelude::Async;
+use tokio::prelude::Future;
+
+// A datagram that arrived at the relay socket of a UDP association
+#[derive(Message, Clone, Debug, PartialEq)]
+pub struct InboundDatagram {
+    pub association_key: StreamKey,
+    pub source: SocketAddr,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutboundDatagram {
+    pub target: SocketAddr,
+    pub data: Vec<u8>,
+}
+
+// The handle by which a UDP association is kept alive. When the last handle is dropped, the
+// UdpRelay behind it finishes and closes its socket.
+pub struct UdpAssociation {
+    association_key: StreamKey,
+    outbound_tx: UnboundedSender<OutboundDatagram>,
+}
+
+// Associations are identified by their keys; TtlHashMap insists on Eq.
+impl PartialEq for UdpAssociation {
+    fn eq(&self, other: &Self) -> bool {
+        self.association_key == other.association_key
+    }
+}
+
+impl Eq for UdpAssociation {}
+
+impl UdpAssociation {
+    pub fn new(
+        association_key: StreamKey,
+        socket: Box<dyn UdpSocketWrapperTrait>,
+        is_bound: bool,
+        inbound_sub: Recipient<InboundDatagram>,
+    ) -> (UdpAssociation, UdpRelay) {
+        let (outbound_tx, outbound_rx) = mpsc::unbounded();
+        let association = UdpAssociation {
+            association_key,
+            outbound_tx,
+        };
+        let relay = UdpRelay {
+            association_key,
+            socket,
+            is_bound,
+            outbound_rx: Box::new(ReceiverWrapperReal::new(outbound_rx)),
+            inbound_sub,
+            logger: Logger::new(&format!("UdpRelay for {}", association_key)),
+        };
+        (association, relay)
+    }
+
+    pub fn association_key(&self) -> StreamKey {
+        self.association_key
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.outbound_tx.is_closed()
+    }
+
+    // Fails only if the relay has already shut down.
+    pub fn send(&self, target: SocketAddr, data: Vec<u8>) -> Result<(), String> {
+        self.outbound_tx
+            .unbounded_send(OutboundDatagram { target, data })
+            .map_err(|_| format!("association {} is closed", self.association_key))
+    }
+}
+
+pub struct UdpRelay {
+    association_key: StreamKey,
+    socket: Box<dyn UdpSocketWrapperTrait>,
+    is_bound: bool,
+    outbound_rx: Box<dyn ReceiverWrapper<OutboundDatagram>>,
+    inbound_sub: Recipient<InboundDatagram>,
+    logger: Logger,
+}
+
+impl Future for UdpRelay {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Result<Async<<Self as Future>::Item>, <Self as Future>::Error> {
+        loop {
+            match self.outbound_rx.poll() {
+                Ok(Async::Ready(Some(datagram))) => self.send(datagram),
+                Ok(Async::Ready(None)) | Err(()) => {
+                    debug!(
+                        self.logger,
+                        "Association {} is gone; closing its socket", self.association_key
+                    );
+                    return Ok(Async::Ready(()));
+                }
+                Ok(Async::NotReady) => break,
+            }
+        }
+        if !self.is_bound {
+            return Ok(Async::NotReady);
+        }
+        let mut buf: [u8; 65536] = [0; 65536];
+        loop {
+            match self.socket.recv_from(&mut buf) {
+                Ok(Async::Ready((len, source))) => self
+                    .inbound_sub
+                    .try_send(InboundDatagram {
+                        association_key: self.association_key,
+                        source,
+                        data: Vec::from(&buf[0..len]),
+                    })
+                    .expect("Datagram recipient is dead"),
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    warning!(
+                        self.logger,
+                        "Closing association {} after receive error: {}",
+                        self.association_key,
+                        e
+                    );
+                    return Err(());
+                }
+            }
+        }
+    }
+}
+
+impl UdpRelay {
+    fn send(&mut self, datagram: OutboundDatagram) {
+        if !self.is_bound {
+            // Exit-side sockets aren't bound until we know which address family the target uses
+            let local_ip = match datagram.target.ip() {
+                IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            };
+            if let Err(e) = self.socket.bind(SocketAddr::new(local_ip, 0)) {
+                warning!(
+                    self.logger,
+                    "Could not bind socket for association {}: {}",
+                    self.association_key,
+                    e
+                );
+                return;
+            }
+            self.is_bound = true;
+        }
+        match self.socket.send_to(&datagram.data, datagram.target) {
+            Ok(Async::Ready(_)) => (),
+            // UDP makes no delivery promises; a datagram that can't be sent right now is dropped
+            Ok(Async::NotReady) => debug!(
+                self.logger,
+                "Socket busy; dropped {}-byte datagram to {}",
+                datagram.data.len(),
+                datagram.target
+            ),
+            Err(e) => warning!(
+                self.logger,
+                "Could not send {}-byte datagram to {}: {}",
+                datagram.data.len(),
+                datagram.target,
+                e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::logging::init_test_logging;
+    use crate::test_utils::logging::TestLogHandler;
+    use crate::test_utils::make_meaningless_stream_key;
+    use crate::test_utils::recorder::make_recorder;
+    use crate::test_utils::udp_socket_wrapper_mocks::UdpSocketWrapperMock;
+    use actix::{Actor, System};
+    use futures::future;
+    use std::io;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn relay_binds_to_target_address_family_and_sends_outbound_datagrams() {
+        let bind_params_arc = Arc::new(Mutex::new(vec![]));
+        let send_to_params_arc = Arc::new(Mutex::new(vec![]));
+        let socket = UdpSocketWrapperMock::new()
+            .bind_params(&bind_params_arc)
+            .send_to_params(&send_to_params_arc);
+        let (recorder, _, _) = make_recorder();
+        let _system =
+            System::new("relay_binds_to_target_address_family_and_sends_outbound_datagrams");
+        let (association, relay) = UdpAssociation::new(
+            make_meaningless_stream_key(),
+            Box::new(socket),
+            false,
+            recorder.start().recipient(),
+        );
+        let target = SocketAddr::from_str("[2001:db8::1]:53").unwrap();
+        association.send(target, vec![1, 2, 3]).unwrap();
+        association.send(target, vec![4, 5]).unwrap();
+        drop(association);
+
+        let result = relay.wait();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            *bind_params_arc.lock().unwrap(),
+            vec![SocketAddr::from_str("[::]:0").unwrap()]
+        );
+        assert_eq!(
+            *send_to_params_arc.lock().unwrap(),
+            vec![(vec![1, 2, 3], target), (vec![4, 5], target)]
+        );
+    }
+
+    #[test]
+    fn relay_forwards_inbound_datagrams_to_recipient() {
+        let association_key = make_meaningless_stream_key();
+        let source = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+        let socket = UdpSocketWrapperMock::new()
+            .recv_from_result(Ok(Async::Ready((3, source))), vec![6, 7, 8])
+            .recv_from_result(Ok(Async::Ready((2, source))), vec![9, 10]);
+        let (recorder, _, recording_arc) = make_recorder();
+        let system = System::new("relay_forwards_inbound_datagrams_to_recipient");
+        let (_association, mut relay) = UdpAssociation::new(
+            association_key,
+            Box::new(socket),
+            true,
+            recorder.start().recipient(),
+        );
+
+        let result = future::lazy(|| Ok::<_, ()>(relay.poll())).wait();
+
+        assert_eq!(result, Ok(Ok(Async::NotReady)));
+        System::current().stop();
+        system.run();
+        let recording = recording_arc.lock().unwrap();
+        assert_eq!(
+            recording.get_record::<InboundDatagram>(0),
+            &InboundDatagram {
+                association_key,
+                source,
+                data: vec![6, 7, 8]
+            }
+        );
+        assert_eq!(
+            recording.get_record::<InboundDatagram>(1),
+            &InboundDatagram {
+                association_key,
+                source,
+                data: vec![9, 10]
+            }
+        );
+        assert_eq!(recording.len(), 2);
+    }
+
+    #[test]
+    fn relay_shuts_down_after_receive_error() {
+        init_test_logging();
+        let association_key = make_meaningless_stream_key();
+        let socket = UdpSocketWrapperMock::new()
+            .recv_from_result(Err(io::Error::from(io::ErrorKind::ConnectionReset)), vec![]);
+        let (recorder, _, _) = make_recorder();
+        let _system = System::new("relay_shuts_down_after_receive_error");
+        let (association, relay) = UdpAssociation::new(
+            association_key,
+            Box::new(socket),
+            true,
+            recorder.start().recipient(),
+        );
+
+        let result = relay.wait();
+
+        assert_eq!(result, Err(()));
+        assert_eq!(
+            association.send(SocketAddr::from_str("1.2.3.4:53").unwrap(), vec![]),
+            Err(format!("association {} is closed", association_key))
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "WARN: UdpRelay for {}: Closing association {} after receive error",
+            association_key, association_key
+        ));
+    }
+}
@@ -9,6 +9,19 @@ pub trait UdpSocketWrapperTrait: Sync + Send {
     fn bind(&mut self, addr: SocketAddr) -> io::Result<bool>;
     fn recv_from(&mut self, buf: &mut [u8]) -> Result<Async<(usize, SocketAddr)>, io::Error>;
     fn send_to(&mut self, buf: &[u8], addr: SocketAddr) -> Result<Async<usize>, io::Error>;
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+}
+
+pub trait UdpSocketWrapperFactory {
+    fn make(&self) -> Box<dyn UdpSocketWrapperTrait>;
+}
+
+pub struct UdpSocketWrapperFactoryReal {}
+
+impl UdpSocketWrapperFactory for UdpSocketWrapperFactoryReal {
+    fn make(&self) -> Box<dyn UdpSocketWrapperTrait> {
+        Box::new(UdpSocketWrapperReal::new())
+    }
 }
 
 #[derive(Default)]
@@ -42,4 +55,11 @@ impl UdpSocketWrapperTrait for UdpSocketWrapperReal {
             None => panic!("call bind before send_to"),
         }
     }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self.delegate {
+            Some(ref socket) => socket.local_addr(),
+            None => panic!("call bind before local_addr"),
+        }
+    }
 }
@@ -12,6 +12,7 @@ pub struct UiGatewayConfig {
                                  // the GetNodeDescriptor message, which A) is part of MASQNode-UI,
                                  // and B) shouldn't be answered by the UiGateway anyway. Move it
                                  // to the Dispatcher part of the BootstrapperConfig.
+    pub ui_auth_token: String,
 }
 
 #[derive(Clone)]
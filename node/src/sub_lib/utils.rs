@@ -2,6 +2,7 @@
 
 use crate::sub_lib::logger::Logger;
 use clap::App;
+use masq_lib::blockchains::{check_chain_name, use_blockchains_file};
 use masq_lib::command::StdStreams;
 use masq_lib::messages::UiCrashRequest;
 use masq_lib::multi_config::{MultiConfig, VirtualCommandLine};
@@ -9,6 +10,7 @@ use masq_lib::shared_schema::ConfiguratorError;
 #[cfg(test)]
 use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
 use std::io::ErrorKind;
+use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 static DEAD_STREAM_ERRORS: [ErrorKind; 5] = [
@@ -93,10 +95,12 @@ pub fn plus<T>(mut source: Vec<T>, item: T) -> Vec<T> {
     result
 }
 
-pub static NODE_DESCRIPTOR_DELIMITERS: [char; 4] = ['_', '@', ':', ':'];
-
-pub fn node_descriptor_delimiter(chain_id: u8) -> char {
-    NODE_DESCRIPTOR_DELIMITERS[chain_id as usize]
+pub fn node_descriptor_delimiter(mainnet: bool) -> char {
+    if mainnet {
+        '@'
+    } else {
+        ':'
+    }
 }
 
 pub fn make_new_multi_config<'a>(
@@ -104,7 +108,15 @@ pub fn make_new_multi_config<'a>(
     vcls: Vec<Box<dyn VirtualCommandLine>>,
     streams: &mut StdStreams,
 ) -> Result<MultiConfig<'a>, ConfiguratorError> {
-    MultiConfig::try_new(schema, vcls, streams)
+    let multi_config = MultiConfig::try_new(schema, vcls, streams)?;
+    // The chain can only be checked once the blockchains file, wherever it came from, is loaded
+    if let Some(blockchains_file) = multi_config.arg_matches().value_of("blockchains-file") {
+        use_blockchains_file(Path::new(blockchains_file))?;
+    }
+    if let Some(chain_name) = multi_config.arg_matches().value_of("chain") {
+        check_chain_name(chain_name)?;
+    }
+    Ok(multi_config)
 }
 
 pub fn handle_ui_crash_request(
@@ -135,6 +147,8 @@ pub fn make_new_test_multi_config<'a>(
 pub mod tests {
     use super::*;
     use crate::test_utils::logging::{init_test_logging, TestLogHandler};
+    use masq_lib::multi_config::CommandLineVcl;
+    use masq_lib::shared_schema::{blockchains_file_arg, chain_arg};
 
     #[test]
     fn indicates_dead_stream_identifies_dead_stream_errors() {
@@ -186,6 +200,42 @@ pub mod tests {
         });
     }
 
+    #[test]
+    fn make_new_multi_config_reports_a_blockchains_file_that_cannot_be_loaded() {
+        let schema = App::new("test")
+            .arg(blockchains_file_arg())
+            .arg(chain_arg());
+        let vcl = CommandLineVcl::new(vec![
+            "".to_string(),
+            "--blockchains-file".to_string(),
+            "/nonexistent/blockchains.toml".to_string(),
+        ]);
+
+        let result = make_new_test_multi_config(&schema, vec![Box::new(vcl)]);
+
+        let param_errors = result.err().unwrap().param_errors;
+        assert_eq!(param_errors.len(), 1);
+        assert_eq!(param_errors[0].parameter, "blockchains-file");
+    }
+
+    #[test]
+    fn make_new_multi_config_rejects_an_unknown_chain() {
+        let schema = App::new("test")
+            .arg(blockchains_file_arg())
+            .arg(chain_arg());
+        let vcl = CommandLineVcl::new(vec![
+            "".to_string(),
+            "--chain".to_string(),
+            "booga".to_string(),
+        ]);
+
+        let result = make_new_test_multi_config(&schema, vec![Box::new(vcl)]);
+
+        let param_errors = result.err().unwrap().param_errors;
+        assert_eq!(param_errors.len(), 1);
+        assert_eq!(param_errors[0].parameter, "chain");
+    }
+
     #[test]
     fn node_mailbox_capacity_is_unbound() {
         assert_eq!(NODE_MAILBOX_CAPACITY, 0)
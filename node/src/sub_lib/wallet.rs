@@ -17,8 +17,7 @@ use std::result::Result;
 use std::str::FromStr;
 use web3::types::{Address, H256};
 
-pub const DEFAULT_CONSUMING_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
-pub const DEFAULT_EARNING_DERIVATION_PATH: &str = "m/44'/60'/0'/0/1";
+pub use masq_lib::constants::{DEFAULT_CONSUMING_DERIVATION_PATH, DEFAULT_EARNING_DERIVATION_PATH};
 
 #[derive(Debug, PartialEq)]
 pub enum WalletError {
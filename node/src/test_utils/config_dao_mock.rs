@@ -4,8 +4,12 @@ use crate::sub_lib::cryptde::PlainData;
 use std::cell::RefCell;
 use std::sync::{Arc, Mutex};
 
+type ChangePasswordParam = (Option<String>, String);
+
 #[derive(Default)]
 pub struct ConfigDaoMock {
+    change_password_params: Arc<Mutex<Vec<ChangePasswordParam>>>,
+    change_password_results: RefCell<Vec<Result<(), ConfigDaoError>>>,
     get_string_params: Arc<Mutex<Vec<String>>>,
     get_string_results: RefCell<Vec<Result<String, ConfigDaoError>>>,
     set_string_params: Arc<Mutex<Vec<(String, String)>>>,
@@ -38,10 +42,14 @@ impl ConfigDao for ConfigDaoMock {
 
     fn change_password(
         &self,
-        _old_password_opt: Option<&str>,
-        _new_password: &str,
+        old_password_opt: Option<&str>,
+        new_password: &str,
     ) -> Result<(), ConfigDaoError> {
-        unimplemented!()
+        self.change_password_params.lock().unwrap().push((
+            old_password_opt.map(|s| s.to_string()),
+            String::from(new_password),
+        ));
+        self.change_password_results.borrow_mut().remove(0)
     }
 
     fn get_string(&self, name: &str) -> Result<String, ConfigDaoError> {
@@ -119,6 +127,19 @@ impl ConfigDaoMock {
         Self::default()
     }
 
+    pub fn change_password_params(
+        mut self,
+        params_arc: &Arc<Mutex<Vec<ChangePasswordParam>>>,
+    ) -> ConfigDaoMock {
+        self.change_password_params = params_arc.clone();
+        self
+    }
+
+    pub fn change_password_result(self, result: Result<(), ConfigDaoError>) -> ConfigDaoMock {
+        self.change_password_results.borrow_mut().push(result);
+        self
+    }
+
     pub fn get_string_params(mut self, params_arc: &Arc<Mutex<Vec<String>>>) -> ConfigDaoMock {
         self.get_string_params = params_arc.clone();
         self
@@ -120,6 +120,13 @@ impl TestLogHandler {
         }
     }
 
+    pub fn count_logs_containing(&self, fragment: &str) -> usize {
+        self.get_logs()
+            .iter()
+            .filter(|log| log.contains(fragment))
+            .count()
+    }
+
     pub fn await_log_containing(&self, fragment: &str, millis: u64) -> usize {
         let began_at = Instant::now();
         while to_millis(&began_at.elapsed()) < millis {
@@ -8,7 +8,9 @@ use crate::neighborhood::{AccessibleGossipRecord, Neighborhood};
 use crate::sub_lib::cryptde::PublicKey;
 use crate::sub_lib::cryptde::{CryptDE, PlainData};
 use crate::sub_lib::cryptde_null::CryptDENull;
-use crate::sub_lib::neighborhood::{NeighborhoodConfig, NeighborhoodMode, NodeDescriptor};
+use crate::sub_lib::neighborhood::{
+    NeighborhoodConfig, NeighborhoodMode, NodeDescriptor, UNLIMITED_ROUTE_COST_BUDGET,
+};
 use crate::sub_lib::node_addr::NodeAddr;
 use crate::sub_lib::wallet::Wallet;
 use crate::test_utils::*;
@@ -97,9 +99,11 @@ pub fn neighborhood_from_nodes(
                 ))],
                 root.rate_pack().clone(),
             ),
+            route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
         },
         None => NeighborhoodConfig {
             mode: NeighborhoodMode::ZeroHop,
+            route_cost_budget: UNLIMITED_ROUTE_COST_BUDGET,
         },
     };
     config.earning_wallet = root.earning_wallet();
@@ -1,7 +1,7 @@
 // Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use crate::persistent_configuration::{PersistentConfigError, PersistentConfiguration};
-use crate::sub_lib::cryptde::PlainData;
+use crate::sub_lib::cryptde::{PlainData, PrivateKey};
 use crate::sub_lib::neighborhood::NodeDescriptor;
 use crate::sub_lib::wallet::Wallet;
 use rusqlite::Transaction;
@@ -9,6 +9,7 @@ use std::cell::RefCell;
 use std::sync::{Arc, Mutex};
 
 type MnemonicSeedParam = (Vec<u8>, String);
+type ChangePasswordParam = (Option<String>, String);
 
 #[allow(clippy::type_complexity)]
 #[derive(Clone, Default)]
@@ -17,6 +18,8 @@ pub struct PersistentConfigurationMock {
     set_password_params: Arc<Mutex<Vec<String>>>,
     check_password_params: Arc<Mutex<Vec<String>>>,
     check_password_results: RefCell<Vec<Option<bool>>>,
+    change_password_params: Arc<Mutex<Vec<ChangePasswordParam>>>,
+    change_password_results: RefCell<Vec<Result<(), PersistentConfigError>>>,
     clandestine_port_results: RefCell<Vec<u16>>,
     set_clandestine_port_params: Arc<Mutex<Vec<u16>>>,
     mnemonic_seed_params: Arc<Mutex<Vec<String>>>,
@@ -40,6 +43,10 @@ pub struct PersistentConfigurationMock {
         RefCell<Vec<Result<Option<Vec<NodeDescriptor>>, PersistentConfigError>>>,
     set_past_neighbors_params: Arc<Mutex<Vec<(Option<Vec<NodeDescriptor>>, String)>>>,
     set_past_neighbors_results: RefCell<Vec<Result<(), PersistentConfigError>>>,
+    node_private_key_params: Arc<Mutex<Vec<String>>>,
+    node_private_key_results: RefCell<Vec<Result<Option<PrivateKey>, PersistentConfigError>>>,
+    set_node_private_key_params: Arc<Mutex<Vec<(PrivateKey, String)>>>,
+    set_node_private_key_results: RefCell<Vec<Result<(), PersistentConfigError>>>,
 }
 
 impl PersistentConfiguration for PersistentConfigurationMock {
@@ -62,6 +69,18 @@ impl PersistentConfiguration for PersistentConfigurationMock {
         self.check_password_results.borrow_mut().remove(0)
     }
 
+    fn change_password(
+        &self,
+        old_password_opt: Option<&str>,
+        new_password: &str,
+    ) -> Result<(), PersistentConfigError> {
+        self.change_password_params.lock().unwrap().push((
+            old_password_opt.map(|s| s.to_string()),
+            new_password.to_string(),
+        ));
+        self.change_password_results.borrow_mut().remove(0)
+    }
+
     fn clandestine_port(&self) -> u16 {
         Self::result_from(&self.clandestine_port_results)
     }
@@ -158,6 +177,29 @@ impl PersistentConfiguration for PersistentConfigurationMock {
         self.set_past_neighbors_results.borrow_mut().remove(0)
     }
 
+    fn node_private_key(
+        &self,
+        db_password: &str,
+    ) -> Result<Option<PrivateKey>, PersistentConfigError> {
+        self.node_private_key_params
+            .lock()
+            .unwrap()
+            .push(db_password.to_string());
+        self.node_private_key_results.borrow_mut().remove(0)
+    }
+
+    fn set_node_private_key(
+        &self,
+        private_key: &PrivateKey,
+        db_password: &str,
+    ) -> Result<(), PersistentConfigError> {
+        self.set_node_private_key_params
+            .lock()
+            .unwrap()
+            .push((private_key.clone(), db_password.to_string()));
+        self.set_node_private_key_results.borrow_mut().remove(0)
+    }
+
     fn start_block(&self) -> u64 {
         if self.start_block_results.borrow().is_empty() {
             return 0;
@@ -207,6 +249,22 @@ impl PersistentConfigurationMock {
         self
     }
 
+    pub fn change_password_params(
+        mut self,
+        params: &Arc<Mutex<Vec<ChangePasswordParam>>>,
+    ) -> PersistentConfigurationMock {
+        self.change_password_params = params.clone();
+        self
+    }
+
+    pub fn change_password_result(
+        self,
+        result: Result<(), PersistentConfigError>,
+    ) -> PersistentConfigurationMock {
+        self.change_password_results.borrow_mut().push(result);
+        self
+    }
+
     pub fn clandestine_port_result(self, result: u16) -> PersistentConfigurationMock {
         self.clandestine_port_results.borrow_mut().push(result);
         self
@@ -326,6 +384,38 @@ impl PersistentConfigurationMock {
         self
     }
 
+    pub fn node_private_key_params(
+        mut self,
+        params: &Arc<Mutex<Vec<String>>>,
+    ) -> PersistentConfigurationMock {
+        self.node_private_key_params = params.clone();
+        self
+    }
+
+    pub fn node_private_key_result(
+        self,
+        result: Result<Option<PrivateKey>, PersistentConfigError>,
+    ) -> PersistentConfigurationMock {
+        self.node_private_key_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn set_node_private_key_params(
+        mut self,
+        params: &Arc<Mutex<Vec<(PrivateKey, String)>>>,
+    ) -> PersistentConfigurationMock {
+        self.set_node_private_key_params = params.clone();
+        self
+    }
+
+    pub fn set_node_private_key_result(
+        self,
+        result: Result<(), PersistentConfigError>,
+    ) -> PersistentConfigurationMock {
+        self.set_node_private_key_results.borrow_mut().push(result);
+        self
+    }
+
     pub fn set_consuming_wallet_derivation_path_params(
         mut self,
         params: &Arc<Mutex<Vec<(String, String)>>>,
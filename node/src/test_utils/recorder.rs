@@ -1,8 +1,12 @@
 // Copyright (c) 2017-2019, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 use crate::accountant::payable_dao::Payment;
-use crate::accountant::{ReceivedPayments, SentPayments};
+use crate::accountant::{
+    ReceivedPayments, ReportTransactionReceipts, ResentPayments, SentPayments,
+};
 use crate::blockchain::blockchain_bridge::RetrieveTransactions;
-use crate::blockchain::blockchain_interface::{BlockchainError, BlockchainResult, Transaction};
+use crate::blockchain::blockchain_interface::{
+    BlockchainError, BlockchainResult, RetrievedTransactions, TransactionStatus,
+};
 use crate::daemon::crash_notification::CrashNotification;
 use crate::daemon::DaemonBindMessage;
 use crate::neighborhood::gossip::Gossip_0v1;
@@ -14,6 +18,7 @@ use crate::sub_lib::accountant::ReportRoutingServiceProvidedMessage;
 use crate::sub_lib::accountant::{AccountantSubs, GetFinancialStatisticsMessage};
 use crate::sub_lib::blockchain_bridge::{BlockchainBridgeSubs, SetDbPasswordMsg};
 use crate::sub_lib::blockchain_bridge::{ReportAccountsPayable, SetGasPriceMsg};
+use crate::sub_lib::blockchain_bridge::{RequestTransactionReceipts, ResubmitPayments};
 use crate::sub_lib::dispatcher::InboundClientData;
 use crate::sub_lib::dispatcher::{DispatcherSubs, StreamShutdownMsg};
 use crate::sub_lib::hopper::IncipientCoresPackage;
@@ -23,7 +28,9 @@ use crate::sub_lib::neighborhood::NeighborhoodDotGraphRequest;
 use crate::sub_lib::neighborhood::NeighborhoodSubs;
 use crate::sub_lib::neighborhood::NodeQueryMessage;
 use crate::sub_lib::neighborhood::NodeQueryResponseMetadata;
+use crate::sub_lib::neighborhood::NodeRatePackChangeMessage;
 use crate::sub_lib::neighborhood::NodeRecordMetadataMessage;
+use crate::sub_lib::neighborhood::RatePackChangeMessage;
 use crate::sub_lib::neighborhood::RemoveNeighborMessage;
 use crate::sub_lib::neighborhood::RouteQueryMessage;
 use crate::sub_lib::neighborhood::RouteQueryResponse;
@@ -31,14 +38,17 @@ use crate::sub_lib::neighborhood::{DispatcherNodeQueryMessage, GossipFailure_0v1
 use crate::sub_lib::peer_actors::PeerActors;
 use crate::sub_lib::peer_actors::{BindMessage, StartMessage};
 use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, InboundServerData};
-use crate::sub_lib::proxy_client::{DnsResolveFailure_0v1, ProxyClientSubs};
+use crate::sub_lib::proxy_client::{
+    DatagramResponsePayload_0v1, DnsResolveFailure_0v1, ExitPolicyViolation_0v1, ProxyClientSubs,
+};
 use crate::sub_lib::proxy_server::ProxyServerSubs;
 use crate::sub_lib::proxy_server::{
-    AddReturnRouteMessage, AddRouteMessage, ClientRequestPayload_0v1,
+    AddReturnRouteMessage, AddRouteMessage, ClientRequestPayload_0v1, DatagramRequestPayload_0v1,
 };
 use crate::sub_lib::set_consuming_wallet_message::SetConsumingWalletMessage;
 use crate::sub_lib::stream_handler_pool::DispatcherNodeQueryResponse;
 use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
+use crate::sub_lib::udp_relay::InboundDatagram;
 use crate::sub_lib::ui_gateway::UiGatewaySubs;
 use crate::test_utils::to_millis;
 use actix::Actor;
@@ -59,8 +69,10 @@ pub struct Recorder {
     recording: Arc<Mutex<Recording>>,
     node_query_responses: Vec<Option<NodeQueryResponseMetadata>>,
     route_query_responses: Vec<Option<RouteQueryResponse>>,
-    retrieve_transactions_responses: Vec<Result<Vec<Transaction>, BlockchainError>>,
+    retrieve_transactions_responses: Vec<Result<RetrievedTransactions, BlockchainError>>,
     report_accounts_payable_responses: Vec<Result<Vec<BlockchainResult<Payment>>, String>>,
+    request_transaction_receipts_responses: Vec<Vec<BlockchainResult<TransactionStatus>>>,
+    resubmit_payments_responses: Vec<Result<Vec<BlockchainResult<Payment>>, String>>,
 }
 
 #[derive(Default)]
@@ -97,22 +109,29 @@ recorder_message_handler!(DaemonBindMessage);
 recorder_message_handler!(DispatcherNodeQueryMessage);
 recorder_message_handler!(DispatcherNodeQueryResponse);
 recorder_message_handler!(DnsResolveFailure_0v1);
+recorder_message_handler!(ExitPolicyViolation_0v1);
 recorder_message_handler!(ExpiredCoresPackage<ClientRequestPayload_0v1>);
 recorder_message_handler!(ExpiredCoresPackage<ClientResponsePayload_0v1>);
+recorder_message_handler!(ExpiredCoresPackage<DatagramRequestPayload_0v1>);
+recorder_message_handler!(ExpiredCoresPackage<DatagramResponsePayload_0v1>);
 recorder_message_handler!(ExpiredCoresPackage<DnsResolveFailure_0v1>);
+recorder_message_handler!(ExpiredCoresPackage<ExitPolicyViolation_0v1>);
 recorder_message_handler!(ExpiredCoresPackage<Gossip_0v1>);
 recorder_message_handler!(ExpiredCoresPackage<GossipFailure_0v1>);
 recorder_message_handler!(ExpiredCoresPackage<MessageType>);
 recorder_message_handler!(GetFinancialStatisticsMessage);
 recorder_message_handler!(InboundClientData);
+recorder_message_handler!(InboundDatagram);
 recorder_message_handler!(InboundServerData);
 recorder_message_handler!(IncipientCoresPackage);
 recorder_message_handler!(NeighborhoodDotGraphRequest);
 recorder_message_handler!(NodeFromUiMessage);
 recorder_message_handler!(NodeToUiMessage);
 recorder_message_handler!(NodeRecordMetadataMessage);
+recorder_message_handler!(NodeRatePackChangeMessage);
 recorder_message_handler!(NoLookupIncipientCoresPackage);
 recorder_message_handler!(PoolBindMessage);
+recorder_message_handler!(RatePackChangeMessage);
 recorder_message_handler!(ReceivedPayments);
 recorder_message_handler!(RemoveNeighborMessage);
 recorder_message_handler!(RemoveStreamMsg);
@@ -120,6 +139,8 @@ recorder_message_handler!(ReportExitServiceConsumedMessage);
 recorder_message_handler!(ReportExitServiceProvidedMessage);
 recorder_message_handler!(ReportRoutingServiceConsumedMessage);
 recorder_message_handler!(ReportRoutingServiceProvidedMessage);
+recorder_message_handler!(ReportTransactionReceipts);
+recorder_message_handler!(ResentPayments);
 recorder_message_handler!(SentPayments);
 recorder_message_handler!(SetConsumingWalletMessage);
 recorder_message_handler!(SetDbPasswordMsg);
@@ -192,6 +213,38 @@ impl Handler<ReportAccountsPayable> for Recorder {
     }
 }
 
+impl Handler<RequestTransactionReceipts> for Recorder {
+    type Result = MessageResult<RequestTransactionReceipts>;
+
+    fn handle(
+        &mut self,
+        msg: RequestTransactionReceipts,
+        _ctx: &mut Self::Context,
+    ) -> <Self as Handler<RequestTransactionReceipts>>::Result {
+        self.record(msg);
+        MessageResult(extract_response(
+            &mut self.request_transaction_receipts_responses,
+            "No RequestTransactionReceiptsResponses prepared for RequestTransactionReceipts",
+        ))
+    }
+}
+
+impl Handler<ResubmitPayments> for Recorder {
+    type Result = MessageResult<ResubmitPayments>;
+
+    fn handle(
+        &mut self,
+        msg: ResubmitPayments,
+        _ctx: &mut Self::Context,
+    ) -> <Self as Handler<ResubmitPayments>>::Result {
+        self.record(msg);
+        MessageResult(extract_response(
+            &mut self.resubmit_payments_responses,
+            "No ResubmitPaymentsResponses prepared for ResubmitPayments",
+        ))
+    }
+}
+
 fn extract_response<T>(responses: &mut Vec<T>, err_msg: &str) -> T
 where
     T: Clone,
@@ -240,7 +293,7 @@ impl Recorder {
 
     pub fn retrieve_transactions_response(
         mut self,
-        response: Result<Vec<Transaction>, BlockchainError>,
+        response: Result<RetrievedTransactions, BlockchainError>,
     ) -> Recorder {
         self.retrieve_transactions_responses.push(response);
         self
@@ -253,6 +306,22 @@ impl Recorder {
         self.report_accounts_payable_responses.push(response);
         self
     }
+
+    pub fn request_transaction_receipts_response(
+        mut self,
+        response: Vec<BlockchainResult<TransactionStatus>>,
+    ) -> Recorder {
+        self.request_transaction_receipts_responses.push(response);
+        self
+    }
+
+    pub fn resubmit_payments_response(
+        mut self,
+        response: Result<Vec<BlockchainResult<Payment>>, String>,
+    ) -> Recorder {
+        self.resubmit_payments_responses.push(response);
+        self
+    }
 }
 
 impl Recording {
@@ -337,10 +406,18 @@ pub fn make_proxy_server_subs_from(addr: &Addr<Recorder>) -> ProxyServerSubs {
         dns_failure_from_hopper: addr
             .clone()
             .recipient::<ExpiredCoresPackage<DnsResolveFailure_0v1>>(),
+        exit_policy_violation_from_hopper: addr
+            .clone()
+            .recipient::<ExpiredCoresPackage<ExitPolicyViolation_0v1>>(),
+        datagram_from_hopper: addr
+            .clone()
+            .recipient::<ExpiredCoresPackage<DatagramResponsePayload_0v1>>(),
+        inbound_datagram: recipient!(addr, InboundDatagram),
         add_return_route: recipient!(addr, AddReturnRouteMessage),
         add_route: recipient!(addr, AddRouteMessage),
         stream_shutdown_sub: recipient!(addr, StreamShutdownMsg),
         set_consuming_wallet_sub: recipient!(addr, SetConsumingWalletMessage),
+        node_rate_pack_change: recipient!(addr, NodeRatePackChangeMessage),
     }
 }
 
@@ -360,6 +437,7 @@ pub fn make_hopper_subs_from(addr: &Addr<Recorder>) -> HopperSubs {
         from_hopper_client: recipient!(addr, IncipientCoresPackage),
         from_hopper_client_no_lookup: recipient!(addr, NoLookupIncipientCoresPackage),
         from_dispatcher: recipient!(addr, InboundClientData),
+        rate_pack_change: recipient!(addr, RatePackChangeMessage),
     }
 }
 
@@ -371,6 +449,12 @@ pub fn make_proxy_client_subs_from(addr: &Addr<Recorder>) -> ProxyClientSubs {
             .recipient::<ExpiredCoresPackage<ClientRequestPayload_0v1>>(),
         inbound_server_data: recipient!(addr, InboundServerData),
         dns_resolve_failed: recipient!(addr, DnsResolveFailure_0v1),
+        exit_policy_violated: recipient!(addr, ExitPolicyViolation_0v1),
+        datagram_from_hopper: addr
+            .clone()
+            .recipient::<ExpiredCoresPackage<DatagramRequestPayload_0v1>>(),
+        inbound_datagram: recipient!(addr, InboundDatagram),
+        rate_pack_change: recipient!(addr, RatePackChangeMessage),
     }
 }
 
@@ -407,6 +491,8 @@ pub fn make_accountant_subs_from(addr: &Addr<Recorder>) -> AccountantSubs {
         report_exit_service_consumed: recipient!(addr, ReportExitServiceConsumedMessage),
         report_new_payments: recipient!(addr, ReceivedPayments),
         report_sent_payments: recipient!(addr, SentPayments),
+        report_transaction_receipts: recipient!(addr, ReportTransactionReceipts),
+        report_resent_payments: recipient!(addr, ResentPayments),
         ui_message_sub: recipient!(addr, NodeFromUiMessage),
     }
 }
@@ -424,6 +510,8 @@ pub fn make_blockchain_bridge_subs_from(addr: &Addr<Recorder>) -> BlockchainBrid
         bind: recipient!(addr, BindMessage),
         report_accounts_payable: recipient!(addr, ReportAccountsPayable),
         retrieve_transactions: recipient!(addr, RetrieveTransactions),
+        request_transaction_receipts: recipient!(addr, RequestTransactionReceipts),
+        resubmit_payments: recipient!(addr, ResubmitPayments),
         ui_sub: recipient!(addr, NodeFromUiMessage),
     }
 }
@@ -19,6 +19,7 @@ use tokio::prelude::Async;
 pub struct StreamConnectorMock {
     connect_pair_params: Arc<Mutex<Vec<SocketAddr>>>,
     connect_pair_results: RefCell<Vec<Result<ConnectionInfo, io::Error>>>,
+    connect_one_params: Arc<Mutex<Vec<StreamConnectorMockConnectOne>>>,
     split_stream_results: RefCell<Vec<Option<ConnectionInfo>>>,
 }
 
@@ -31,11 +32,16 @@ impl StreamConnector for StreamConnectorMock {
 
     fn connect_one(
         &self,
-        _ip_addrs: Vec<IpAddr>,
-        _target_hostname: &str,
-        _target_port: u16,
+        ip_addrs: Vec<IpAddr>,
+        target_hostname: &str,
+        target_port: u16,
         _logger: &Logger,
     ) -> Result<ConnectionInfo, io::Error> {
+        self.connect_one_params.lock().unwrap().push((
+            ip_addrs,
+            target_hostname.to_string(),
+            target_port,
+        ));
         self.connect_pair_results.borrow_mut().remove(0)
     }
 
@@ -46,12 +52,14 @@ impl StreamConnector for StreamConnectorMock {
 
 type StreamConnectorMockRead = (Vec<u8>, Result<Async<usize>, io::Error>);
 type StreamConnectorMockWrite = Result<Async<usize>, io::Error>;
+type StreamConnectorMockConnectOne = (Vec<IpAddr>, String, u16);
 
 impl StreamConnectorMock {
     pub fn new() -> StreamConnectorMock {
         Self {
             connect_pair_params: Arc::new(Mutex::new(vec![])),
             connect_pair_results: RefCell::new(vec![]),
+            connect_one_params: Arc::new(Mutex::new(vec![])),
             split_stream_results: RefCell::new(vec![]),
         }
     }
@@ -114,6 +122,14 @@ impl StreamConnectorMock {
         self
     }
 
+    pub fn connect_one_params(
+        mut self,
+        params_arc: &Arc<Mutex<Vec<StreamConnectorMockConnectOne>>>,
+    ) -> StreamConnectorMock {
+        self.connect_one_params = params_arc.clone();
+        self
+    }
+
     pub fn split_stream_result(self, result: Option<ConnectionInfo>) -> StreamConnectorMock {
         self.split_stream_results.borrow_mut().push(result);
         self
@@ -0,0 +1,124 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::sub_lib::udp_socket_wrapper::{UdpSocketWrapperFactory, UdpSocketWrapperTrait};
+use std::cell::RefCell;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::prelude::Async;
+
+type RecvFromResult = (Result<Async<(usize, SocketAddr)>, io::Error>, Vec<u8>);
+type SendToParams = Arc<Mutex<Vec<(Vec<u8>, SocketAddr)>>>;
+
+// Once its prepared results run out, this mock binds and sends successfully and has nothing to
+// receive, so it can be polled as long as a test likes.
+#[derive(Default)]
+pub struct UdpSocketWrapperMock {
+    bind_params: Arc<Mutex<Vec<SocketAddr>>>,
+    bind_results: Vec<io::Result<bool>>,
+    recv_from_results: Vec<RecvFromResult>,
+    send_to_params: SendToParams,
+    send_to_results: Vec<Result<Async<usize>, io::Error>>,
+    local_addr_results: Vec<io::Result<SocketAddr>>,
+}
+
+impl UdpSocketWrapperTrait for UdpSocketWrapperMock {
+    fn bind(&mut self, addr: SocketAddr) -> io::Result<bool> {
+        self.bind_params.lock().unwrap().push(addr);
+        if self.bind_results.is_empty() {
+            Ok(true)
+        } else {
+            self.bind_results.remove(0)
+        }
+    }
+
+    fn recv_from(&mut self, buf: &mut [u8]) -> Result<Async<(usize, SocketAddr)>, io::Error> {
+        if self.recv_from_results.is_empty() {
+            return Ok(Async::NotReady);
+        }
+        let (result, data) = self.recv_from_results.remove(0);
+        buf[0..data.len()].copy_from_slice(&data);
+        result
+    }
+
+    fn send_to(&mut self, buf: &[u8], addr: SocketAddr) -> Result<Async<usize>, io::Error> {
+        self.send_to_params
+            .lock()
+            .unwrap()
+            .push((buf.to_vec(), addr));
+        if self.send_to_results.is_empty() {
+            Ok(Async::Ready(buf.len()))
+        } else {
+            self.send_to_results.remove(0)
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self.local_addr_results.first() {
+            Some(Ok(addr)) => Ok(*addr),
+            Some(Err(e)) => Err(io::Error::from(e.kind())),
+            None => panic!("UdpSocketWrapperMock has no local_addr result"),
+        }
+    }
+}
+
+impl UdpSocketWrapperMock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind_params(mut self, params: &Arc<Mutex<Vec<SocketAddr>>>) -> Self {
+        self.bind_params = params.clone();
+        self
+    }
+
+    pub fn bind_result(mut self, result: io::Result<bool>) -> Self {
+        self.bind_results.push(result);
+        self
+    }
+
+    pub fn recv_from_result(
+        mut self,
+        result: Result<Async<(usize, SocketAddr)>, io::Error>,
+        data: Vec<u8>,
+    ) -> Self {
+        self.recv_from_results.push((result, data));
+        self
+    }
+
+    pub fn send_to_params(mut self, params: &SendToParams) -> Self {
+        self.send_to_params = params.clone();
+        self
+    }
+
+    pub fn send_to_result(mut self, result: Result<Async<usize>, io::Error>) -> Self {
+        self.send_to_results.push(result);
+        self
+    }
+
+    pub fn local_addr_result(mut self, result: io::Result<SocketAddr>) -> Self {
+        self.local_addr_results.push(result);
+        self
+    }
+}
+
+#[derive(Default)]
+pub struct UdpSocketWrapperFactoryMock {
+    make_results: RefCell<Vec<UdpSocketWrapperMock>>,
+}
+
+impl UdpSocketWrapperFactory for UdpSocketWrapperFactoryMock {
+    fn make(&self) -> Box<dyn UdpSocketWrapperTrait> {
+        Box::new(self.make_results.borrow_mut().remove(0))
+    }
+}
+
+impl UdpSocketWrapperFactoryMock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn make_result(self, result: UdpSocketWrapperMock) -> Self {
+        self.make_results.borrow_mut().push(result);
+        self
+    }
+}
@@ -1,5 +1,6 @@
 // Copyright (c) 2017-2018, Substratum LLC (https://substratum.net) and/or its affiliates. All rights reserved.
 
+pub mod ui_auth_token;
 mod websocket_supervisor;
 
 #[cfg(test)]
@@ -24,6 +25,7 @@ pub const CRASH_KEY: &str = "UIGATEWAY";
 
 pub struct UiGateway {
     port: u16,
+    ui_auth_token: String,
     websocket_supervisor: Option<Box<dyn WebSocketSupervisor>>,
     incoming_message_recipients: Vec<Recipient<NodeFromUiMessage>>,
     logger: Logger,
@@ -33,6 +35,7 @@ impl UiGateway {
     pub fn new(config: &UiGatewayConfig) -> UiGateway {
         UiGateway {
             port: config.ui_port,
+            ui_auth_token: config.ui_auth_token.clone(),
             websocket_supervisor: None,
             incoming_message_recipients: vec![],
             logger: Logger::new("UiGateway"),
@@ -65,6 +68,7 @@ impl Handler<BindMessage> for UiGateway {
         self.websocket_supervisor = match WebSocketSupervisorReal::new(
             self.port,
             msg.peer_actors.ui_gateway.node_from_ui_message_sub,
+            &self.ui_auth_token,
         ) {
             Ok(wss) => Some(Box::new(wss)),
             Err(e) => panic!("Couldn't start WebSocketSupervisor: {:?}", e),
@@ -79,11 +83,14 @@ impl Handler<DaemonBindMessage> for UiGateway {
     fn handle(&mut self, msg: DaemonBindMessage, ctx: &mut Self::Context) -> Self::Result {
         ctx.set_mailbox_capacity(NODE_MAILBOX_CAPACITY);
         self.incoming_message_recipients = msg.from_ui_message_recipients;
-        self.websocket_supervisor =
-            match WebSocketSupervisorReal::new(self.port, msg.from_ui_message_recipient) {
-                Ok(wss) => Some(Box::new(wss)),
-                Err(e) => panic!("Couldn't start WebSocketSupervisor: {:?}", e),
-            };
+        self.websocket_supervisor = match WebSocketSupervisorReal::new(
+            self.port,
+            msg.from_ui_message_recipient,
+            &self.ui_auth_token,
+        ) {
+            Ok(wss) => Some(Box::new(wss)),
+            Err(e) => panic!("Couldn't start WebSocketSupervisor: {:?}", e),
+        };
         debug!(self.logger, "UIGateway bound");
     }
 }
@@ -133,6 +140,7 @@ mod tests {
         let subject = UiGateway::new(&UiGatewayConfig {
             ui_port: find_free_port(),
             node_descriptor: String::from(""),
+            ui_auth_token: String::from("booga"),
         });
         let system = System::new("test");
         let subject_addr: Addr<UiGateway> = subject.start();
@@ -175,6 +183,7 @@ mod tests {
         let mut subject = UiGateway::new(&UiGatewayConfig {
             ui_port: find_free_port(),
             node_descriptor: String::from(""),
+            ui_auth_token: String::from("booga"),
         });
         let system = System::new("test");
         subject.websocket_supervisor = Some(Box::new(websocket_supervisor));
@@ -0,0 +1,234 @@
+// Copyright (c) 2019-2020, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::bootstrapper::RealUser;
+use crate::node_configurator::{real_user_masq_directory, DirsWrapper};
+use crate::privilege_drop::{PrivilegeDropper, PrivilegeDropperReal};
+use masq_lib::ui_auth::{read_ui_auth_token, ui_auth_token_path};
+use rand::RngCore;
+use rustc_hex::ToHex;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+pub const UI_AUTH_TOKEN_BYTES: usize = 32;
+
+pub trait UiAuthTokenEstablisher: Send {
+    fn establish(&self, real_user: &RealUser) -> String;
+}
+
+pub struct UiAuthTokenEstablisherReal {
+    dirs_wrapper: Box<dyn DirsWrapper>,
+    privilege_dropper: Box<dyn PrivilegeDropper>,
+}
+
+impl UiAuthTokenEstablisher for UiAuthTokenEstablisherReal {
+    fn establish(&self, real_user: &RealUser) -> String {
+        let masq_directory = real_user_masq_directory(self.dirs_wrapper.as_ref(), real_user);
+        let token = establish_ui_auth_token(&masq_directory).unwrap_or_else(|e| {
+            panic!(
+                "Could not establish UI auth token in {:?}: {}",
+                masq_directory, e
+            )
+        });
+        // If we're running as root, neither the directory nor the token may be left owned by root,
+        // or the real user's masq won't be able to read the token.
+        self.privilege_dropper.chown(&masq_directory, real_user);
+        self.privilege_dropper
+            .chown(&ui_auth_token_path(&masq_directory), real_user);
+        token
+    }
+}
+
+impl UiAuthTokenEstablisherReal {
+    pub fn new(dirs_wrapper: Box<dyn DirsWrapper>) -> Self {
+        Self {
+            dirs_wrapper,
+            privilege_dropper: Box::new(PrivilegeDropperReal::new()),
+        }
+    }
+}
+
+pub fn establish_ui_auth_token(masq_directory: &Path) -> io::Result<String> {
+    let path = ui_auth_token_path(masq_directory);
+    if let Some(token) = read_ui_auth_token(&path) {
+        // Whoever can read the token can drive the Node, so a token left looser than 0600 by an
+        // earlier version or by hand is tightened before it's trusted.
+        #[cfg(not(target_os = "windows"))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut permissions = fs::metadata(&path)?.permissions();
+            if permissions.mode() & 0o077 != 0 {
+                permissions.set_mode(0o600);
+                fs::set_permissions(&path, permissions)?;
+            }
+        }
+        return Ok(token);
+    }
+    fs::create_dir_all(masq_directory)?;
+    let mut bytes = [0u8; UI_AUTH_TOKEN_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token: String = bytes.to_hex();
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(&path)?.write_all(token.as_bytes())?;
+    Ok(token)
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use super::UiAuthTokenEstablisher;
+    use crate::bootstrapper::RealUser;
+    use std::cell::RefCell;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    pub struct UiAuthTokenEstablisherMock {
+        establish_params: Arc<Mutex<Vec<RealUser>>>,
+        establish_results: RefCell<Vec<String>>,
+    }
+
+    impl UiAuthTokenEstablisher for UiAuthTokenEstablisherMock {
+        fn establish(&self, real_user: &RealUser) -> String {
+            self.establish_params
+                .lock()
+                .unwrap()
+                .push(real_user.clone());
+            self.establish_results.borrow_mut().remove(0)
+        }
+    }
+
+    impl UiAuthTokenEstablisherMock {
+        pub fn new() -> Self {
+            Self {
+                establish_params: Arc::new(Mutex::new(vec![])),
+                establish_results: RefCell::new(vec![]),
+            }
+        }
+
+        pub fn establish_params(mut self, params: &Arc<Mutex<Vec<RealUser>>>) -> Self {
+            self.establish_params = params.clone();
+            self
+        }
+
+        pub fn establish_result(self, result: &str) -> Self {
+            self.establish_results.borrow_mut().push(result.to_string());
+            self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_test_utils::MockDirsWrapper;
+    use crate::server_initializer::test_utils::PrivilegeDropperMock;
+    use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn establish_ui_auth_token_creates_a_fresh_random_token() {
+        let home_dir = ensure_node_home_directory_exists(
+            "ui_auth_token",
+            "establish_ui_auth_token_creates_a_fresh_random_token",
+        );
+        let masq_directory = home_dir.join("MASQ");
+
+        let result = establish_ui_auth_token(&masq_directory).unwrap();
+
+        assert_eq!(result.len(), UI_AUTH_TOKEN_BYTES * 2);
+        assert_eq!(
+            read_ui_auth_token(&ui_auth_token_path(&masq_directory)),
+            Some(result.clone())
+        );
+        let other_directory = home_dir.join("other");
+        assert_ne!(establish_ui_auth_token(&other_directory).unwrap(), result);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn establish_ui_auth_token_makes_the_token_readable_only_by_its_owner() {
+        use std::os::unix::fs::PermissionsExt;
+        let home_dir = ensure_node_home_directory_exists(
+            "ui_auth_token",
+            "establish_ui_auth_token_makes_the_token_readable_only_by_its_owner",
+        );
+
+        establish_ui_auth_token(&home_dir).unwrap();
+
+        let metadata = fs::metadata(ui_auth_token_path(&home_dir)).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn establish_ui_auth_token_reuses_an_existing_token() {
+        let home_dir = ensure_node_home_directory_exists(
+            "ui_auth_token",
+            "establish_ui_auth_token_reuses_an_existing_token",
+        );
+        fs::write(ui_auth_token_path(&home_dir), "booga\n").unwrap();
+
+        let result = establish_ui_auth_token(&home_dir).unwrap();
+
+        assert_eq!(result, "booga".to_string());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn establish_ui_auth_token_tightens_an_existing_token_readable_by_others() {
+        use std::os::unix::fs::PermissionsExt;
+        let home_dir = ensure_node_home_directory_exists(
+            "ui_auth_token",
+            "establish_ui_auth_token_tightens_an_existing_token_readable_by_others",
+        );
+        let path = ui_auth_token_path(&home_dir);
+        fs::write(&path, "booga\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let result = establish_ui_auth_token(&home_dir).unwrap();
+
+        assert_eq!(result, "booga".to_string());
+        let metadata = fs::metadata(&path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn establisher_puts_token_in_real_user_masq_directory_and_gives_it_to_real_user() {
+        let home_dir = ensure_node_home_directory_exists(
+            "ui_auth_token",
+            "establisher_puts_token_in_real_user_masq_directory_and_gives_it_to_real_user",
+        );
+        let privileged_home_dir = home_dir.join("root");
+        let real_home_dir = home_dir.join("booga");
+        let chown_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut subject = UiAuthTokenEstablisherReal::new(Box::new(
+            MockDirsWrapper::new()
+                .home_dir_result(Some(privileged_home_dir.clone()))
+                .data_dir_result(Some(privileged_home_dir.join("data"))),
+        ));
+        subject.privilege_dropper =
+            Box::new(PrivilegeDropperMock::new().chown_params(&chown_params_arc));
+        let real_user = RealUser::new(Some(123), Some(456), Some(real_home_dir.clone()));
+
+        let result = subject.establish(&real_user);
+
+        let masq_directory = real_home_dir.join("data").join("MASQ");
+        assert_eq!(
+            read_ui_auth_token(&ui_auth_token_path(&masq_directory)),
+            Some(result)
+        );
+        assert_eq!(
+            *chown_params_arc.lock().unwrap(),
+            vec![
+                (masq_directory.clone(), real_user.clone()),
+                (ui_auth_token_path(&masq_directory), real_user),
+            ]
+        );
+    }
+}
@@ -10,15 +10,20 @@ use futures::Future;
 use futures::Sink;
 use futures::Stream;
 use itertools::Itertools;
-use masq_lib::messages::{ToMessageBody, UiUnmarshalError, NODE_UI_PROTOCOL, UNMARSHAL_ERROR};
-use masq_lib::ui_gateway::MessagePath::Conversation;
+use masq_lib::messages::{
+    FromMessageBody, ToMessageBody, UiSubscribeRequest, UiSubscribeResponse, UiUnmarshalError,
+    UiUnsubscribeRequest, UiUnsubscribeResponse, NODE_UI_PROTOCOL, UNAUTHORIZED_ERROR,
+    UNMARSHAL_ERROR,
+};
+use masq_lib::ui_auth::ui_auth_token_from_headers;
+use masq_lib::ui_gateway::MessagePath::{Conversation, FireAndForget};
 use masq_lib::ui_gateway::MessageTarget::ClientId;
 use masq_lib::ui_gateway::{MessageBody, MessageTarget, NodeFromUiMessage, NodeToUiMessage};
 use masq_lib::ui_traffic_converter::UiTrafficConverter;
 use masq_lib::ui_traffic_converter::UnmarshalError::{Critical, NonCritical};
 use masq_lib::utils::localhost;
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 use std::net::SocketAddr;
 use std::sync::Mutex;
@@ -32,6 +37,10 @@ use websocket::server::upgrade::WsUpgrade;
 use websocket::OwnedMessage;
 use websocket::WebSocketError;
 
+// Opcodes a client may use without presenting the UI auth token when it connects. Nothing here
+// may change the state of the Daemon or the Node.
+const UNAUTHENTICATED_OPCODES: &[&str] = &["descriptor"];
+
 trait ClientWrapper: Send + Any {
     fn as_any(&self) -> &dyn Any;
     fn send(&mut self, item: OwnedMessage) -> Result<(), WebSocketError>;
@@ -71,6 +80,53 @@ struct WebSocketSupervisorInner {
     from_ui_message_sub: Recipient<NodeFromUiMessage>,
     client_id_by_socket_addr: HashMap<SocketAddr, u64>,
     client_by_id: HashMap<u64, Box<dyn ClientWrapper>>,
+    auth_token: String,
+    authenticated_client_ids: HashSet<u64>,
+    // Clients missing from here have never subscribed or unsubscribed, and hear every broadcast
+    subscriptions_by_client_id: HashMap<u64, Subscriptions>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Subscriptions {
+    Only(BTreeSet<String>),
+    AllExcept(BTreeSet<String>),
+}
+
+impl Subscriptions {
+    fn hears(&self, opcode: &str) -> bool {
+        match self {
+            Subscriptions::Only(opcodes) => opcodes.contains(opcode),
+            Subscriptions::AllExcept(opcodes) => !opcodes.contains(opcode),
+        }
+    }
+
+    fn subscribe(&mut self, opcodes: Vec<String>) {
+        match self {
+            Subscriptions::Only(subscribed) => subscribed.extend(opcodes),
+            Subscriptions::AllExcept(excepted) => opcodes.iter().for_each(|opcode| {
+                excepted.remove(opcode);
+            }),
+        }
+    }
+
+    fn unsubscribe(&mut self, opcodes: Vec<String>) {
+        match self {
+            Subscriptions::Only(subscribed) => opcodes.iter().for_each(|opcode| {
+                subscribed.remove(opcode);
+            }),
+            Subscriptions::AllExcept(excepted) => excepted.extend(opcodes),
+        }
+    }
+
+    // The opcodes a client is subscribed to, and the ones it isn't if it hears all the others
+    fn describe(&self) -> (Vec<String>, Option<Vec<String>>) {
+        match self {
+            Subscriptions::Only(opcodes) => (opcodes.iter().cloned().collect_vec(), None),
+            Subscriptions::AllExcept(opcodes) => {
+                (vec![], Some(opcodes.iter().cloned().collect_vec()))
+            }
+        }
+    }
 }
 
 impl WebSocketSupervisor for WebSocketSupervisorReal {
@@ -84,6 +140,7 @@ impl WebSocketSupervisorReal {
     pub fn new(
         port: u16,
         from_ui_message_sub: Recipient<NodeFromUiMessage>,
+        auth_token: &str,
     ) -> std::io::Result<WebSocketSupervisorReal> {
         let inner = Arc::new(Mutex::new(WebSocketSupervisorInner {
             port,
@@ -91,6 +148,9 @@ impl WebSocketSupervisorReal {
             from_ui_message_sub,
             client_id_by_socket_addr: HashMap::new(),
             client_by_id: HashMap::new(),
+            auth_token: auth_token.to_string(),
+            authenticated_client_ids: HashSet::new(),
+            subscriptions_by_client_id: HashMap::new(),
         }));
         let logger = Logger::new("WebSocketSupervisor");
         let logger_1 = logger.clone();
@@ -122,18 +182,38 @@ impl WebSocketSupervisorReal {
     fn send_msg(locked_inner: &mut MutexGuard<WebSocketSupervisorInner>, msg: NodeToUiMessage) {
         let client_ids = match msg.target {
             MessageTarget::ClientId(n) => vec![n],
-            MessageTarget::AllExcept(n) => locked_inner
-                .client_by_id
-                .keys()
-                .filter(|k| k != &&n)
-                .copied()
-                .collect_vec(),
-            MessageTarget::AllClients => locked_inner.client_by_id.keys().copied().collect_vec(),
+            MessageTarget::AllExcept(n) => {
+                Self::broadcast_client_ids(locked_inner, &msg.body.opcode, Some(n))
+            }
+            MessageTarget::AllClients => {
+                Self::broadcast_client_ids(locked_inner, &msg.body.opcode, None)
+            }
         };
         let json = UiTrafficConverter::new_marshal(msg.body);
         Self::send_to_clients(locked_inner, client_ids, json);
     }
 
+    // Broadcasts carry information about the Daemon and the Node; only authenticated clients get
+    // to hear them, and only if they haven't narrowed their subscriptions to other opcodes.
+    fn broadcast_client_ids(
+        locked_inner: &WebSocketSupervisorInner,
+        opcode: &str,
+        except_opt: Option<u64>,
+    ) -> Vec<u64> {
+        locked_inner
+            .authenticated_client_ids
+            .iter()
+            .filter(|client_id| Some(**client_id) != except_opt)
+            .filter(
+                |client_id| match locked_inner.subscriptions_by_client_id.get(client_id) {
+                    Some(subscriptions) => subscriptions.hears(opcode),
+                    None => true,
+                },
+            )
+            .copied()
+            .collect_vec()
+    }
+
     fn remove_failures<I, E: Debug>(
         stream: impl Stream<Item = I, Error = E>,
         logger: &Logger,
@@ -179,12 +259,33 @@ impl WebSocketSupervisorReal {
     ) {
         let logger_clone = logger.clone();
         info!(logger_clone, "UI connected at {}", socket_addr);
+        let authenticated = {
+            let locked_inner = inner.lock().expect("WebSocketSupervisor is poisoned");
+            match ui_auth_token_from_headers(&upgrade.request.headers) {
+                Some(token) => Self::tokens_match(&token, &locked_inner.auth_token),
+                None => false,
+            }
+        };
+        if !authenticated {
+            info!(
+                logger_clone,
+                "UI at {} did not present a valid auth token; allowing only {:?}",
+                socket_addr,
+                UNAUTHENTICATED_OPCODES
+            );
+        }
         let upgrade_future =
             upgrade
                 .use_protocol(NODE_UI_PROTOCOL)
                 .accept()
                 .map(move |(client, _)| {
-                    Self::handle_connection(client, &inner, &logger_clone, socket_addr);
+                    Self::handle_connection(
+                        client,
+                        &inner,
+                        &logger_clone,
+                        socket_addr,
+                        authenticated,
+                    );
                 });
         tokio::spawn(upgrade_future.then(|result| {
             match result {
@@ -209,6 +310,7 @@ impl WebSocketSupervisorReal {
         inner: &Arc<Mutex<WebSocketSupervisorInner>>,
         logger: &Logger,
         socket_addr: SocketAddr,
+        authenticated: bool,
     ) {
         let logger_1 = logger.clone();
         let logger_2 = logger.clone();
@@ -226,6 +328,9 @@ impl WebSocketSupervisorReal {
             .client_id_by_socket_addr
             .insert(socket_addr, client_id);
         locked_inner.client_by_id.insert(client_id, client_wrapper);
+        if authenticated {
+            locked_inner.authenticated_client_ids.insert(client_id);
+        }
         let incoming_future = incoming
             .then(move |result| Self::handle_websocket_errors(result, &logger_2, socket_addr))
             .map(move |owned_message| match owned_message {
@@ -264,6 +369,23 @@ impl WebSocketSupervisorReal {
             }
         };
         match UiTrafficConverter::new_unmarshal_from_ui(message, client_id) {
+            Ok(from_ui_message)
+                if !locked_inner.authenticated_client_ids.contains(&client_id)
+                    && !UNAUTHENTICATED_OPCODES.contains(&from_ui_message.body.opcode.as_str()) =>
+            {
+                Self::refuse_unauthenticated_message(
+                    &mut locked_inner,
+                    logger,
+                    socket_addr,
+                    from_ui_message,
+                );
+            }
+            Ok(from_ui_message)
+                if from_ui_message.body.opcode == UiSubscribeRequest::type_opcode()
+                    || from_ui_message.body.opcode == UiUnsubscribeRequest::type_opcode() =>
+            {
+                Self::handle_subscription_message(&mut locked_inner, logger, from_ui_message);
+            }
             Ok(from_ui_message) => {
                 locked_inner
                     .from_ui_message_sub
@@ -331,6 +453,157 @@ impl WebSocketSupervisorReal {
         ok::<(), ()>(())
     }
 
+    fn refuse_unauthenticated_message(
+        locked_inner: &mut MutexGuard<WebSocketSupervisorInner>,
+        logger: &Logger,
+        socket_addr: SocketAddr,
+        msg: NodeFromUiMessage,
+    ) {
+        warning!(
+            logger,
+            "Refused '{}' message from unauthenticated client {} at {}",
+            msg.body.opcode,
+            msg.client_id,
+            socket_addr
+        );
+        if let Conversation(context_id) = msg.body.path {
+            Self::send_msg(
+                locked_inner,
+                NodeToUiMessage {
+                    target: ClientId(msg.client_id),
+                    body: MessageBody {
+                        opcode: msg.body.opcode,
+                        path: Conversation(context_id),
+                        payload: Err((
+                            UNAUTHORIZED_ERROR,
+                            "Connection was not authenticated with the UI auth token".to_string(),
+                        )),
+                    },
+                },
+            );
+        }
+    }
+
+    // Subscriptions belong to the connection rather than to the Daemon or the Node, so they're
+    // handled here instead of being passed on to the UiGateway.
+    fn handle_subscription_message(
+        locked_inner: &mut MutexGuard<WebSocketSupervisorInner>,
+        logger: &Logger,
+        msg: NodeFromUiMessage,
+    ) {
+        let client_id = msg.client_id;
+        let opcode = msg.body.opcode.clone();
+        let path = msg.body.path.clone();
+        let result = if opcode == UiSubscribeRequest::type_opcode() {
+            UiSubscribeRequest::fmb(msg.body).map(|(request, context_id)| {
+                let subscriptions = Self::subscriptions_mut(
+                    locked_inner,
+                    client_id,
+                    Subscriptions::Only(BTreeSet::new()),
+                );
+                subscriptions.subscribe(request.opcodes);
+                let (opcodes, except_opcodes_opt) = subscriptions.describe();
+                Self::log_subscriptions(logger, client_id, &opcodes, &except_opcodes_opt);
+                UiSubscribeResponse {
+                    opcodes,
+                    except_opcodes_opt,
+                }
+                .tmb(context_id)
+            })
+        } else {
+            UiUnsubscribeRequest::fmb(msg.body).map(|(request, context_id)| {
+                debug!(
+                    logger,
+                    "Client {} unsubscribed from {:?}", client_id, request.opcodes
+                );
+                let subscriptions = Self::subscriptions_mut(
+                    locked_inner,
+                    client_id,
+                    Subscriptions::AllExcept(BTreeSet::new()),
+                );
+                subscriptions.unsubscribe(request.opcodes);
+                let (opcodes, except_opcodes_opt) = subscriptions.describe();
+                Self::log_subscriptions(logger, client_id, &opcodes, &except_opcodes_opt);
+                UiUnsubscribeResponse {
+                    opcodes,
+                    except_opcodes_opt,
+                }
+                .tmb(context_id)
+            })
+        };
+        let body = match result {
+            Ok(body) => body,
+            Err(e) => {
+                warning!(
+                    logger,
+                    "Client {} sent unusable '{}' message: {}",
+                    client_id,
+                    opcode,
+                    e
+                );
+                match path {
+                    Conversation(context_id) => MessageBody {
+                        payload: Err((
+                            UNMARSHAL_ERROR,
+                            format!(
+                                "The '{}' payload needs an 'opcodes' array of strings",
+                                opcode
+                            ),
+                        )),
+                        opcode,
+                        path: Conversation(context_id),
+                    },
+                    FireAndForget => return,
+                }
+            }
+        };
+        Self::send_msg(
+            locked_inner,
+            NodeToUiMessage {
+                target: ClientId(client_id),
+                body,
+            },
+        );
+    }
+
+    fn subscriptions_mut<'a>(
+        locked_inner: &'a mut MutexGuard<WebSocketSupervisorInner>,
+        client_id: u64,
+        initial: Subscriptions,
+    ) -> &'a mut Subscriptions {
+        locked_inner
+            .subscriptions_by_client_id
+            .entry(client_id)
+            .or_insert(initial)
+    }
+
+    fn log_subscriptions(
+        logger: &Logger,
+        client_id: u64,
+        opcodes: &[String],
+        except_opcodes_opt: &Option<Vec<String>>,
+    ) {
+        match except_opcodes_opt {
+            Some(except_opcodes) => debug!(
+                logger,
+                "Client {} subscribed to everything except {:?}", client_id, except_opcodes
+            ),
+            None => debug!(logger, "Client {} subscribed to {:?}", client_id, opcodes),
+        }
+    }
+
+    // Compares every byte whatever the outcome, so that the time taken doesn't reveal how much of a
+    // guessed token was right. There's no such thing as a matching empty token.
+    fn tokens_match(presented: &str, expected: &str) -> bool {
+        !expected.is_empty()
+            && presented.len() == expected.len()
+            && presented
+                .bytes()
+                .zip(expected.bytes())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                == 0
+    }
+
     fn handle_close_message(
         inner_arc: &Arc<Mutex<WebSocketSupervisorInner>>,
         logger: &Logger,
@@ -423,6 +696,8 @@ impl WebSocketSupervisorReal {
         socket_addr: SocketAddr,
         logger: &Logger,
     ) {
+        locked_inner.authenticated_client_ids.remove(&client_id);
+        locked_inner.subscriptions_by_client_id.remove(&client_id);
         let mut client = match locked_inner.client_by_id.remove(&client_id) {
             Some(client) => client,
             None => panic!("WebSocketSupervisor got a disconnect from a client that has disappeared from the stable!"),
@@ -455,10 +730,12 @@ mod tests {
     use actix::{Actor, Addr};
     use futures::future::lazy;
     use masq_lib::messages::{
-        FromMessageBody, UiShutdownRequest, UiStartOrder, UiUnmarshalError, NODE_UI_PROTOCOL,
-        UNMARSHAL_ERROR,
+        FromMessageBody, UiDescriptorRequest, UiShutdownRequest, UiShutdownResponse, UiStartOrder,
+        UiSubscribeRequest, UiSubscribeResponse, UiUnmarshalError, UiUnsubscribeRequest,
+        UiUnsubscribeResponse, NODE_UI_PROTOCOL, UNAUTHORIZED_ERROR, UNMARSHAL_ERROR,
     };
     use masq_lib::test_utils::ui_connection::UiConnection;
+    use masq_lib::ui_auth::with_ui_auth_token;
     use masq_lib::ui_gateway::MessagePath::FireAndForget;
     use masq_lib::ui_gateway::NodeFromUiMessage;
     use masq_lib::ui_traffic_converter::UiTrafficConverter;
@@ -481,6 +758,7 @@ mod tests {
             locked_inner
                 .client_by_id
                 .insert(client_id, Box::new(mock_client));
+            locked_inner.authenticated_client_ids.insert(client_id);
             client_id
         }
 
@@ -578,11 +856,16 @@ mod tests {
         }
     }
 
+    const UI_AUTH_TOKEN: &str = "booga";
+
     fn make_client(port: u16, protocol: &str) -> Result<Client<TcpStream>, WebSocketError> {
-        ClientBuilder::new(format!("ws://127.0.0.1:{}", port).as_str())
-            .expect("ClientBuilder could not be built")
-            .add_protocol(protocol)
-            .connect_insecure()
+        with_ui_auth_token(
+            ClientBuilder::new(format!("ws://127.0.0.1:{}", port).as_str())
+                .expect("ClientBuilder could not be built"),
+            UI_AUTH_TOKEN,
+        )
+        .add_protocol(protocol)
+        .connect_insecure()
     }
 
     fn wait_for_client(port: u16, protocol: &str) -> Client<TcpStream> {
@@ -627,7 +910,8 @@ mod tests {
             let system = System::new("logs_pre_upgrade_connection_errors");
             let ui_message_sub = subs(ui_gateway);
             let subject = lazy(move || {
-                let _subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+                let _subject =
+                    WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
                 Ok(())
             });
             actix::spawn(subject);
@@ -649,7 +933,8 @@ mod tests {
             let system = System::new("rejects_connection_attempt_with_improper_protocol_name");
             let ui_message_sub = subs(ui_gateway);
             let subject = lazy(move || {
-                let _subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+                let _subject =
+                    WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
                 Ok(())
             });
             actix::spawn(subject);
@@ -676,13 +961,16 @@ mod tests {
             let system = System::new("logs_unexpected_binary_ping_pong_websocket_messages");
             let ui_message_sub = subs(ui_gateway);
             let subject = lazy(move || {
-                let _subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+                let _subject =
+                    WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
                 Ok(())
             });
             actix::spawn(subject);
             system.run();
         });
-        let mut client = await_value(None, || UiConnection::make(port, NODE_UI_PROTOCOL));
+        let mut client = await_value(None, || {
+            UiConnection::make_authenticated(port, NODE_UI_PROTOCOL, UI_AUTH_TOKEN)
+        });
 
         client.send_message(&OwnedMessage::Binary(vec![1u8, 2u8, 3u8, 4u8]));
         client.send_message(&OwnedMessage::Ping(vec![1u8, 2u8, 3u8, 4u8]));
@@ -713,7 +1001,8 @@ mod tests {
             let system = System::new("can_connect_two_clients_and_receive_messages_from_them");
             let ui_message_sub = subs(ui_gateway);
             let subject = lazy(move || {
-                let _subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+                let _subject =
+                    WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
                 Ok(())
             });
             actix::spawn(subject);
@@ -797,6 +1086,9 @@ mod tests {
             from_ui_message_sub: ui_message_sub.start().recipient::<NodeFromUiMessage>(),
             client_id_by_socket_addr: Default::default(),
             client_by_id: Default::default(),
+            auth_token: UI_AUTH_TOKEN.to_string(),
+            authenticated_client_ids: Default::default(),
+            subscriptions_by_client_id: Default::default(),
         };
         let subject = WebSocketSupervisorReal {
             inner: Arc::new(Mutex::new(subject_inner)),
@@ -864,6 +1156,9 @@ mod tests {
             from_ui_message_sub: ui_message_sub.start().recipient::<NodeFromUiMessage>(),
             client_id_by_socket_addr: Default::default(),
             client_by_id: Default::default(),
+            auth_token: UI_AUTH_TOKEN.to_string(),
+            authenticated_client_ids: Default::default(),
+            subscriptions_by_client_id: Default::default(),
         };
         let subject = WebSocketSupervisorReal {
             inner: Arc::new(Mutex::new(subject_inner)),
@@ -931,6 +1226,9 @@ mod tests {
             from_ui_message_sub: ui_message_sub.start().recipient::<NodeFromUiMessage>(),
             client_id_by_socket_addr: Default::default(),
             client_by_id: Default::default(),
+            auth_token: UI_AUTH_TOKEN.to_string(),
+            authenticated_client_ids: Default::default(),
+            subscriptions_by_client_id: Default::default(),
         };
         let subject = WebSocketSupervisorReal {
             inner: Arc::new(Mutex::new(subject_inner)),
@@ -991,6 +1289,363 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unauthenticated_clients_may_use_only_unauthenticated_opcodes() {
+        init_test_logging();
+        let port = find_free_port();
+        let (ui_gateway, ui_gateway_awaiter, ui_gateway_recording_arc) = make_recorder();
+
+        thread::spawn(move || {
+            let system =
+                System::new("unauthenticated_clients_may_use_only_unauthenticated_opcodes");
+            let ui_message_sub = subs(ui_gateway);
+            let subject = lazy(move || {
+                let _subject =
+                    WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
+                Ok(())
+            });
+            actix::spawn(subject);
+            system.run();
+        });
+        let mut anonymous_client = await_value(None, || UiConnection::make(port, NODE_UI_PROTOCOL));
+        let mut impostor_client = UiConnection::new_authenticated(port, NODE_UI_PROTOCOL, "bogus");
+
+        let anonymous_result: Result<UiShutdownResponse, (u64, String)> =
+            anonymous_client.transact_with_context_id(UiShutdownRequest {}, 1234);
+        let impostor_result: Result<UiShutdownResponse, (u64, String)> =
+            impostor_client.transact_with_context_id(UiShutdownRequest {}, 4321);
+        impostor_client.send_with_context_id(UiDescriptorRequest {}, 5678);
+
+        let expected_error = Err((
+            UNAUTHORIZED_ERROR,
+            "Connection was not authenticated with the UI auth token".to_string(),
+        ));
+        assert_eq!(anonymous_result, expected_error);
+        assert_eq!(impostor_result, expected_error);
+        ui_gateway_awaiter.await_message_count(1);
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        assert_eq!(
+            ui_gateway_recording.get_record::<NodeFromUiMessage>(0),
+            &NodeFromUiMessage {
+                client_id: 1,
+                body: UiDescriptorRequest {}.tmb(5678),
+            }
+        );
+        assert_eq!(ui_gateway_recording.len(), 1);
+        TestLogHandler::new().exists_log_matching(
+            "WARN: WebSocketSupervisor: Refused 'shutdown' message from unauthenticated client 0 at 127\\.0\\.0\\.1:\\d+",
+        );
+    }
+
+    #[test]
+    fn broadcasts_go_only_to_authenticated_clients() {
+        let port = find_free_port();
+        let (ui_gateway, _, _) = make_recorder();
+        let ui_message_sub = subs(ui_gateway);
+        let system = System::new("broadcasts_go_only_to_authenticated_clients");
+        let lazy_future = lazy(move || {
+            let subject =
+                WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
+            let authenticated_client = ClientWrapperMock::new()
+                .send_result(Ok(()))
+                .flush_result(Ok(()));
+            let authenticated_client_id = subject.inject_mock_client(authenticated_client);
+            let anonymous_client_id = subject.inject_mock_client(ClientWrapperMock::new());
+            subject
+                .inner
+                .lock()
+                .unwrap()
+                .authenticated_client_ids
+                .remove(&anonymous_client_id);
+            let msg = NodeToUiMessage {
+                target: MessageTarget::AllClients,
+                body: MessageBody {
+                    opcode: "booga".to_string(),
+                    path: FireAndForget,
+                    payload: Ok("{}".to_string()),
+                },
+            };
+
+            subject.send_msg(msg);
+
+            let authenticated_client_ref = subject.get_mock_client(authenticated_client_id);
+            assert_eq!(
+                authenticated_client_ref.send_params.lock().unwrap().len(),
+                1
+            );
+            let anonymous_client_ref = subject.get_mock_client(anonymous_client_id);
+            assert_eq!(anonymous_client_ref.send_params.lock().unwrap().len(), 0);
+            Ok(())
+        });
+        actix::spawn(lazy_future);
+        System::current().stop();
+        system.run();
+    }
+
+    #[test]
+    fn broadcasts_go_only_to_clients_subscribed_to_their_opcodes() {
+        let port = find_free_port();
+        let (ui_gateway, _, _) = make_recorder();
+        let ui_message_sub = subs(ui_gateway);
+        let system = System::new("broadcasts_go_only_to_clients_subscribed_to_their_opcodes");
+        let lazy_future = lazy(move || {
+            let subject =
+                WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
+            let make_client = || {
+                ClientWrapperMock::new()
+                    .send_result(Ok(()))
+                    .flush_result(Ok(()))
+                    .send_result(Ok(()))
+                    .flush_result(Ok(()))
+            };
+            let unsubscribed_client_id = subject.inject_mock_client(make_client());
+            let booga_client_id = subject.inject_mock_client(make_client());
+            let other_client_id = subject.inject_mock_client(make_client());
+            let deaf_client_id = subject.inject_mock_client(make_client());
+            let all_but_other_client_id = subject.inject_mock_client(make_client());
+            let all_but_booga_client_id = subject.inject_mock_client(make_client());
+            let excepted_client_id = subject.inject_mock_client(make_client());
+            {
+                let mut locked_inner = subject.inner.lock().unwrap();
+                let opcode_set = |opcodes: &[&str]| {
+                    opcodes
+                        .iter()
+                        .map(|opcode| opcode.to_string())
+                        .collect::<BTreeSet<String>>()
+                };
+                let mut subscribe = |client_id: u64, subscriptions: Subscriptions| {
+                    locked_inner
+                        .subscriptions_by_client_id
+                        .insert(client_id, subscriptions);
+                };
+                subscribe(
+                    booga_client_id,
+                    Subscriptions::Only(opcode_set(&["booga", "other"])),
+                );
+                subscribe(other_client_id, Subscriptions::Only(opcode_set(&["other"])));
+                subscribe(deaf_client_id, Subscriptions::Only(opcode_set(&[])));
+                subscribe(
+                    all_but_other_client_id,
+                    Subscriptions::AllExcept(opcode_set(&["other"])),
+                );
+                subscribe(
+                    all_but_booga_client_id,
+                    Subscriptions::AllExcept(opcode_set(&["booga"])),
+                );
+            }
+            let make_msg = |target: MessageTarget| NodeToUiMessage {
+                target,
+                body: MessageBody {
+                    opcode: "booga".to_string(),
+                    path: FireAndForget,
+                    payload: Ok("{}".to_string()),
+                },
+            };
+
+            subject.send_msg(make_msg(MessageTarget::AllClients));
+            subject.send_msg(make_msg(MessageTarget::AllExcept(excepted_client_id)));
+
+            let send_count = |client_id: u64| {
+                subject
+                    .get_mock_client(client_id)
+                    .send_params
+                    .lock()
+                    .unwrap()
+                    .len()
+            };
+            assert_eq!(send_count(unsubscribed_client_id), 2);
+            assert_eq!(send_count(booga_client_id), 2);
+            assert_eq!(send_count(other_client_id), 0);
+            assert_eq!(send_count(deaf_client_id), 0);
+            assert_eq!(send_count(all_but_other_client_id), 2);
+            assert_eq!(send_count(all_but_booga_client_id), 0);
+            assert_eq!(send_count(excepted_client_id), 1);
+            Ok(())
+        });
+        actix::spawn(lazy_future);
+        System::current().stop();
+        system.run();
+    }
+
+    #[test]
+    fn subscriptions_are_handled_here_and_not_passed_on_to_the_ui_gateway() {
+        init_test_logging();
+        let port = find_free_port();
+        let (ui_gateway, ui_gateway_awaiter, ui_gateway_recording_arc) = make_recorder();
+
+        thread::spawn(move || {
+            let system =
+                System::new("subscriptions_are_handled_here_and_not_passed_on_to_the_ui_gateway");
+            let ui_message_sub = subs(ui_gateway);
+            let subject = lazy(move || {
+                let _subject =
+                    WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
+                Ok(())
+            });
+            actix::spawn(subject);
+            system.run();
+        });
+        let mut client = await_value(None, || {
+            UiConnection::make_authenticated(port, NODE_UI_PROTOCOL, UI_AUTH_TOKEN)
+        });
+
+        let subscribe_result: Result<UiSubscribeResponse, (u64, String)> = client
+            .transact_with_context_id(
+                UiSubscribeRequest {
+                    opcodes: vec!["setup".to_string(), "crashed".to_string()],
+                },
+                1234,
+            );
+        let unsubscribe_result: Result<UiUnsubscribeResponse, (u64, String)> = client
+            .transact_with_context_id(
+                UiUnsubscribeRequest {
+                    opcodes: vec!["crashed".to_string(), "booga".to_string()],
+                },
+                2345,
+            );
+        client.send_string(
+            r#"{"opcode": "subscribe", "contextId": 3456, "payload": {"booga": []}}"#.to_string(),
+        );
+        let bad_subscribe_result: Result<UiSubscribeResponse, (u64, String)> = client.receive();
+        client.send_with_context_id(UiDescriptorRequest {}, 4567);
+
+        assert_eq!(
+            subscribe_result,
+            Ok(UiSubscribeResponse {
+                opcodes: vec!["crashed".to_string(), "setup".to_string()],
+                except_opcodes_opt: None,
+            })
+        );
+        assert_eq!(
+            unsubscribe_result,
+            Ok(UiUnsubscribeResponse {
+                opcodes: vec!["setup".to_string()],
+                except_opcodes_opt: None,
+            })
+        );
+        assert_eq!(
+            bad_subscribe_result,
+            Err((
+                UNMARSHAL_ERROR,
+                "The 'subscribe' payload needs an 'opcodes' array of strings".to_string()
+            ))
+        );
+        ui_gateway_awaiter.await_message_count(1);
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        assert_eq!(
+            ui_gateway_recording.get_record::<NodeFromUiMessage>(0),
+            &NodeFromUiMessage {
+                client_id: 0,
+                body: UiDescriptorRequest {}.tmb(4567),
+            }
+        );
+        assert_eq!(ui_gateway_recording.len(), 1);
+        TestLogHandler::new().exists_log_containing(
+            "DEBUG: WebSocketSupervisor: Client 0 subscribed to [\"setup\"]",
+        );
+    }
+
+    #[test]
+    fn unsubscribing_without_having_subscribed_leaves_every_other_broadcast_audible() {
+        init_test_logging();
+        let port = find_free_port();
+        let (ui_gateway, _, _) = make_recorder();
+
+        thread::spawn(move || {
+            let system = System::new(
+                "unsubscribing_without_having_subscribed_leaves_every_other_broadcast_audible",
+            );
+            let ui_message_sub = subs(ui_gateway);
+            let subject = lazy(move || {
+                let _subject =
+                    WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
+                Ok(())
+            });
+            actix::spawn(subject);
+            system.run();
+        });
+        let mut client = await_value(None, || {
+            UiConnection::make_authenticated(port, NODE_UI_PROTOCOL, UI_AUTH_TOKEN)
+        });
+
+        let unsubscribe_result: Result<UiUnsubscribeResponse, (u64, String)> = client
+            .transact_with_context_id(
+                UiUnsubscribeRequest {
+                    opcodes: vec!["crashed".to_string(), "setup".to_string()],
+                },
+                1234,
+            );
+        let subscribe_result: Result<UiSubscribeResponse, (u64, String)> = client
+            .transact_with_context_id(
+                UiSubscribeRequest {
+                    opcodes: vec!["setup".to_string()],
+                },
+                2345,
+            );
+
+        assert_eq!(
+            unsubscribe_result,
+            Ok(UiUnsubscribeResponse {
+                opcodes: vec![],
+                except_opcodes_opt: Some(vec!["crashed".to_string(), "setup".to_string()]),
+            })
+        );
+        assert_eq!(
+            subscribe_result,
+            Ok(UiSubscribeResponse {
+                opcodes: vec![],
+                except_opcodes_opt: Some(vec!["crashed".to_string()]),
+            })
+        );
+        let tlh = TestLogHandler::new();
+        tlh.exists_log_containing(
+            "DEBUG: WebSocketSupervisor: Client 0 unsubscribed from [\"crashed\", \"setup\"]",
+        );
+        tlh.exists_log_containing(
+            "DEBUG: WebSocketSupervisor: Client 0 subscribed to everything except [\"crashed\"]",
+        );
+    }
+
+    #[test]
+    fn closing_a_connection_forgets_its_subscriptions() {
+        let (ui_gateway, _, _) = make_recorder();
+        let from_ui_message_sub = subs(ui_gateway);
+        let client = ClientWrapperMock::new()
+            .send_result(Ok(()))
+            .flush_result(Ok(()));
+        let mut client_by_id: HashMap<u64, Box<dyn ClientWrapper>> = HashMap::new();
+        client_by_id.insert(0, Box::new(client));
+        let mut subscriptions_by_client_id = HashMap::new();
+        subscriptions_by_client_id.insert(0, Subscriptions::Only(BTreeSet::new()));
+        let mut inner = WebSocketSupervisorInner {
+            port: 0,
+            next_client_id: 1,
+            from_ui_message_sub,
+            client_id_by_socket_addr: HashMap::new(),
+            client_by_id,
+            auth_token: UI_AUTH_TOKEN.to_string(),
+            authenticated_client_ids: HashSet::new(),
+            subscriptions_by_client_id,
+        };
+
+        WebSocketSupervisorReal::close_connection(
+            &mut inner,
+            0,
+            SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            &Logger::new("test"),
+        );
+
+        assert!(inner.subscriptions_by_client_id.is_empty());
+    }
+
+    #[test]
+    fn tokens_match_only_when_identical_and_not_empty() {
+        assert!(WebSocketSupervisorReal::tokens_match("booga", "booga"));
+        assert!(!WebSocketSupervisorReal::tokens_match("boogb", "booga"));
+        assert!(!WebSocketSupervisorReal::tokens_match("boog", "booga"));
+        assert!(!WebSocketSupervisorReal::tokens_match("", ""));
+    }
+
     #[test]
     fn can_handle_flush_failure_after_send() {
         init_test_logging();
@@ -1007,6 +1662,9 @@ mod tests {
             from_ui_message_sub,
             client_id_by_socket_addr: Default::default(),
             client_by_id,
+            auth_token: UI_AUTH_TOKEN.to_string(),
+            authenticated_client_ids: Default::default(),
+            subscriptions_by_client_id: Default::default(),
         }));
 
         WebSocketSupervisorReal::send_to_clients(
@@ -1027,14 +1685,17 @@ mod tests {
             let system = System::new("once_a_client_sends_a_close_no_more_data_is_accepted");
             let ui_message_sub = subs(ui_gateway);
             let subject = lazy(move || {
-                let _subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+                let _subject =
+                    WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
                 Ok(())
             });
             actix::spawn(subject);
             system.run();
         });
 
-        let mut client = await_value(None, || UiConnection::make(port, NODE_UI_PROTOCOL));
+        let mut client = await_value(None, || {
+            UiConnection::make_authenticated(port, NODE_UI_PROTOCOL, UI_AUTH_TOKEN)
+        });
 
         client.send(UiShutdownRequest {});
         client.send_message(&OwnedMessage::Close(None));
@@ -1063,13 +1724,16 @@ mod tests {
             let system = System::new("a_client_that_violates_the_protocol_is_terminated");
             let ui_message_sub = subs(ui_gateway);
             let subject = lazy(move || {
-                let _subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+                let _subject =
+                    WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
                 Ok(())
             });
             actix::spawn(subject);
             system.run();
         });
-        let mut client = await_value(None, || UiConnection::make(port, "MASQNode-UIv2"));
+        let mut client = await_value(None, || {
+            UiConnection::make_authenticated(port, "MASQNode-UIv2", UI_AUTH_TOKEN)
+        });
         client.send(UiShutdownRequest {});
         {
             let writer = client.writer();
@@ -1096,7 +1760,8 @@ mod tests {
         let ui_message_sub = subs(ui_gateway);
         let system = System::new("send_msg_sends_a_message_to_the_client");
         let lazy_future = lazy(move || {
-            let subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+            let subject =
+                WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
             let one_mock_client = ClientWrapperMock::new()
                 .send_result(Ok(()))
                 .flush_result(Ok(()));
@@ -1137,7 +1802,8 @@ mod tests {
         let ui_message_sub = subs(ui_gateway);
         let system = System::new("send_msg_sends_a_message_to_the_client");
         let lazy_future = lazy(move || {
-            let subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+            let subject =
+                WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
             let one_mock_client = ClientWrapperMock::new()
                 .send_result(Ok(()))
                 .flush_result(Ok(()));
@@ -1180,7 +1846,8 @@ mod tests {
         let ui_message_sub = subs(ui_gateway);
         let system = System::new("send_msg_sends_a_message_to_the_client");
         let lazy_future = lazy(move || {
-            let subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+            let subject =
+                WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
             let one_mock_client = ClientWrapperMock::new()
                 .send_result(Ok(()))
                 .flush_result(Ok(()));
@@ -1230,7 +1897,8 @@ mod tests {
         let ui_message_sub = subs(ui_gateway);
         let system = System::new("send_msg_tries_to_send_message_and_panics_on_flush");
         let lazy_future = lazy(move || {
-            let subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+            let subject =
+                WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
             let mock_client = ClientWrapperMock::new()
                 .send_result(Ok(()))
                 .flush_result(Err(WebSocketError::NoDataAvailable));
@@ -1262,7 +1930,8 @@ mod tests {
         let ui_message_sub = subs(ui_gateway);
         let system = System::new("send_msg_tries_to_send_message_and_panics");
         let lazy_future = lazy(move || {
-            let subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+            let subject =
+                WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
             let mock_client =
                 ClientWrapperMock::new().send_result(Err(WebSocketError::NoDataAvailable));
             let msg = NodeToUiMessage {
@@ -1292,7 +1961,8 @@ mod tests {
         let ui_message_sub = subs(ui_gateway);
         let system = System::new("send_msg_fails_to_look_up_client_to_send_to");
         let lazy_future = lazy(move || {
-            let subject = WebSocketSupervisorReal::new(port, ui_message_sub).unwrap();
+            let subject =
+                WebSocketSupervisorReal::new(port, ui_message_sub, UI_AUTH_TOKEN).unwrap();
             let msg = NodeToUiMessage {
                 target: MessageTarget::ClientId(7),
                 body: MessageBody {
@@ -27,7 +27,7 @@ const EARNING_PATH: &str = "m/44'/60'/3'/2/1";
 const EARNING_ADDRESS: &str = "0x0123456789ABCDEF0123456789ABCDEF01234567";
 const CONSUMING_PATH: &str = "m/44'/60'/1'/2/3";
 
-fn persistent_config(chain_id: u8) -> PersistentConfigurationReal {
+fn persistent_config(chain_id: u64) -> PersistentConfigurationReal {
     PersistentConfigurationReal::from(
         DbInitializerReal::new()
             .initialize(&MASQNode::data_dir().to_path_buf(), chain_id, true)
@@ -7,6 +7,7 @@ use masq_lib::messages::{
     UiFinancialsRequest, UiRedirect, UiStartOrder, UiStartResponse, NODE_NOT_RUNNING_ERROR,
 };
 use masq_lib::test_utils::ui_connection::UiConnection;
+use masq_lib::ui_auth::default_ui_auth_token;
 use masq_lib::utils::find_free_port;
 use node_lib::daemon::launch_verifier::{VerifierTools, VerifierToolsReal};
 use node_lib::database::db_initializer::DATABASE_FILE;
@@ -49,7 +50,9 @@ fn initialization_sequence_integration() {
     let mut daemon = MASQNode::start_daemon(Some(
         CommandConfig::new().pair("--ui-port", format!("{}", daemon_port).as_str()),
     ));
-    let mut initialization_client = UiConnection::new(daemon_port, NODE_UI_PROTOCOL);
+    let ui_auth_token = default_ui_auth_token().unwrap();
+    let mut initialization_client =
+        UiConnection::new_authenticated(daemon_port, NODE_UI_PROTOCOL, &ui_auth_token);
     let data_directory = std::env::current_dir()
         .unwrap()
         .join("generated")
@@ -99,7 +102,11 @@ fn initialization_sequence_integration() {
     let actual_payload: UiFinancialsRequest =
         serde_json::from_str(&running_financials_response.payload).unwrap();
     assert_eq!(actual_payload, expected_payload);
-    let mut service_client = UiConnection::new(start_response.redirect_ui_port, NODE_UI_PROTOCOL);
+    let mut service_client = UiConnection::new_authenticated(
+        start_response.redirect_ui_port,
+        NODE_UI_PROTOCOL,
+        &ui_auth_token,
+    );
     service_client.send(UiShutdownRequest {});
     wait_for_process_end(start_response.new_process_id);
     let _ = daemon.kill();
@@ -4,6 +4,7 @@ pub mod utils;
 
 use crate::utils::CommandConfig;
 use masq_lib::messages::{ToMessageBody, UiCrashRequest};
+use masq_lib::ui_auth::with_default_ui_auth_token;
 use masq_lib::ui_traffic_converter::UiTrafficConverter;
 use masq_lib::utils::{find_free_port, localhost};
 use websocket::{ClientBuilder, OwnedMessage};
@@ -75,11 +76,14 @@ fn start_node_and_request_crash(crash_key: &str) {
         }
         .tmb(0),
     );
-    let mut client = ClientBuilder::new(format!("ws://{}:{}", localhost(), port).as_str())
-        .expect("Couldn't create ClientBuilder")
-        .add_protocol("MASQNode-UIv2")
-        .connect_insecure()
-        .unwrap();
+    node.wait_for_log("UIGateway bound", Some(5000));
+    let mut client = with_default_ui_auth_token(
+        ClientBuilder::new(format!("ws://{}:{}", localhost(), port).as_str())
+            .expect("Couldn't create ClientBuilder"),
+    )
+    .add_protocol("MASQNode-UIv2")
+    .connect_insecure()
+    .unwrap();
     client.send_message(&OwnedMessage::Text(msg)).unwrap();
 
     let success = node.wait_for_exit().unwrap().status.success();
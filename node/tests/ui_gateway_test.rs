@@ -7,6 +7,7 @@ use masq_lib::messages::{
     UiShutdownRequest, NODE_UI_PROTOCOL,
 };
 use masq_lib::test_utils::ui_connection::UiConnection;
+use masq_lib::ui_auth::default_ui_auth_token;
 use masq_lib::utils::find_free_port;
 use utils::CommandConfig;
 
@@ -18,10 +19,13 @@ fn dispatcher_message_integration() {
         CommandConfig::new().pair("--ui-port", &port.to_string()),
     ));
     node.wait_for_log("UIGateway bound", Some(5000));
+    let ui_auth_token = default_ui_auth_token().unwrap();
     let descriptor_req = UiDescriptorRequest {};
-    let mut descriptor_client = UiConnection::new(port, NODE_UI_PROTOCOL);
+    let mut descriptor_client =
+        UiConnection::new_authenticated(port, NODE_UI_PROTOCOL, &ui_auth_token);
     let shutdown_req = UiShutdownRequest {};
-    let mut shutdown_client = UiConnection::new(port, NODE_UI_PROTOCOL);
+    let mut shutdown_client =
+        UiConnection::new_authenticated(port, NODE_UI_PROTOCOL, &ui_auth_token);
 
     descriptor_client.send(descriptor_req);
     let _: UiDescriptorResponse = descriptor_client.receive().unwrap();
@@ -38,13 +42,14 @@ fn request_financial_information_integration() {
         CommandConfig::new().pair("--ui-port", &port.to_string()),
     ));
     node.wait_for_log("UIGateway bound", Some(5000));
+    let ui_auth_token = default_ui_auth_token().unwrap();
     let financials_request = UiFinancialsRequest {
         payable_minimum_amount: 0,
         payable_maximum_age: 1_000_000_000_000,
         receivable_minimum_amount: 0,
         receivable_maximum_age: 1_000_000_000_000,
     };
-    let mut client = UiConnection::new(port, NODE_UI_PROTOCOL);
+    let mut client = UiConnection::new_authenticated(port, NODE_UI_PROTOCOL, &ui_auth_token);
 
     client.send(financials_request);
     let financials_response: UiFinancialsResponse = client.receive().unwrap();